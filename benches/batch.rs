@@ -0,0 +1,94 @@
+#![allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! バッチ事前チェックのベンチマーク。
+//!
+//! ```text
+//! cargo bench --bench batch
+//! ```
+//!
+//! 同じ注文列を `check_order` の逐次呼び出しと `check_batch` で評価し、
+//! 1 注文あたりの所要時間を比較する。外部ベンチマーククレートは使わない。
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use alice_ledger::{Order, OrderId, OrderType, Position, Side, TimeInForce};
+use alice_risk::{PreTradeChecker, RiskLimits};
+
+const ORDERS: usize = 4096;
+const ROUNDS: u32 = 2_000;
+
+fn orders() -> Vec<Order> {
+    (0..ORDERS as u64)
+        .map(|i| Order {
+            id: OrderId(i),
+            side: if i % 2 == 0 { Side::Bid } else { Side::Ask },
+            order_type: OrderType::Limit,
+            price: 1_000 + (i % 97) as i64,
+            // ほぼすべて受理され、約 1/64 が数量制限に掛かる。
+            quantity: if i % 64 == 0 { 1_000 } else { 1 + i % 50 },
+            filled_quantity: 0,
+            timestamp_ns: i,
+            time_in_force: TimeInForce::GTC,
+        })
+        .collect()
+}
+
+fn positions() -> Vec<Position> {
+    (0..ORDERS as i64)
+        .map(|i| Position {
+            symbol_hash: i as u64,
+            net_quantity: (i % 200) - 100,
+            avg_entry_price: 1_000,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 0,
+        })
+        .collect()
+}
+
+/// `f` を `ROUNDS` 回実行し、1 注文あたりの平均 ns を返す。
+fn measure(mut f: impl FnMut() -> usize) -> f64 {
+    black_box(f());
+    let start = Instant::now();
+    let mut accepted = 0;
+    for _ in 0..ROUNDS {
+        accepted += f();
+    }
+    black_box(accepted);
+    start.elapsed().as_nanos() as f64 / f64::from(ROUNDS) / ORDERS as f64
+}
+
+fn main() {
+    let checker = PreTradeChecker::new(RiskLimits::default());
+    let orders = orders();
+    let held = positions();
+    let positions: Vec<Option<&Position>> = held.iter().map(Some).collect();
+    let mut out = Vec::with_capacity(ORDERS);
+
+    let mut scalar_out = Vec::with_capacity(ORDERS);
+    let scalar = measure(|| {
+        scalar_out.clear();
+        for (o, p) in orders.iter().zip(&positions) {
+            scalar_out.push(checker.check_order(black_box(o), *p));
+        }
+        scalar_out.iter().filter(|r| r.is_ok()).count()
+    });
+    let batch = measure(|| {
+        out.clear();
+        checker.check_batch(black_box(&orders), &positions, &mut out);
+        out.iter().filter(|r| r.is_ok()).count()
+    });
+
+    println!("check_order  {scalar:>8.2} ns/order");
+    println!("check_batch  {batch:>8.2} ns/order");
+    println!("speedup      {:>8.2}x", scalar / batch);
+}
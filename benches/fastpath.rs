@@ -0,0 +1,116 @@
+#![allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 分岐の少ない単一注文チェックのベンチマーク。
+//!
+//! ```text
+//! cargo bench --bench fastpath
+//! ```
+//!
+//! すべて受理される注文列と、受理・拒否が不規則に混在する注文列について、
+//! `check_order`・`check_order_fast`・`check_mask` の 1 注文あたりの所要時間を
+//! 比較する。外部ベンチマーククレートは使わない。
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use alice_ledger::{Order, OrderId, OrderType, Position, Side, TimeInForce};
+use alice_risk::{PreTradeChecker, RiskLimits};
+
+const ORDERS: usize = 4096;
+const ROUNDS: u32 = 2_000;
+
+/// `mixed` のとき、およそ 4 件に 1 件は数量または想定元本の制限に掛かる。
+fn orders(mixed: bool) -> Vec<Order> {
+    let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+    (0..ORDERS as u64)
+        .map(|i| {
+            // xorshift で拒否の位置を予測しにくくする。
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let (price, quantity) = match state % 8 {
+                0 if mixed => (1_000, 1_000),
+                1 if mixed => (9_000_000, 10),
+                _ => (1_000 + (i % 97) as i64, 1 + i % 50),
+            };
+            Order {
+                id: OrderId(i),
+                side: if i % 2 == 0 { Side::Bid } else { Side::Ask },
+                order_type: OrderType::Limit,
+                price,
+                quantity,
+                filled_quantity: 0,
+                timestamp_ns: i,
+                time_in_force: TimeInForce::GTC,
+            }
+        })
+        .collect()
+}
+
+fn positions() -> Vec<Position> {
+    (0..ORDERS as i64)
+        .map(|i| Position {
+            symbol_hash: i as u64,
+            net_quantity: (i % 200) - 100,
+            avg_entry_price: 1_000,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 0,
+        })
+        .collect()
+}
+
+/// `f` を `ROUNDS` 回実行し、1 注文あたりの平均 ns を返す。
+fn measure(mut f: impl FnMut() -> usize) -> f64 {
+    black_box(f());
+    let start = Instant::now();
+    let mut accepted = 0;
+    for _ in 0..ROUNDS {
+        accepted += f();
+    }
+    black_box(accepted);
+    start.elapsed().as_nanos() as f64 / f64::from(ROUNDS) / ORDERS as f64
+}
+
+fn main() {
+    let checker = PreTradeChecker::new(RiskLimits::default());
+    let held = positions();
+    let positions: Vec<Option<&Position>> = held.iter().map(Some).collect();
+
+    for (label, orders) in [("accepted", orders(false)), ("mixed", orders(true))] {
+        let scalar = measure(|| {
+            orders
+                .iter()
+                .zip(&positions)
+                .filter(|(o, p)| checker.check_order(black_box(o), **p).is_ok())
+                .count()
+        });
+        let fast = measure(|| {
+            orders
+                .iter()
+                .zip(&positions)
+                .filter(|(o, p)| checker.check_order_fast(black_box(o), **p).is_ok())
+                .count()
+        });
+        let mask = measure(|| {
+            orders
+                .iter()
+                .zip(&positions)
+                .filter(|(o, p)| checker.check_mask(black_box(o), **p).is_empty())
+                .count()
+        });
+
+        println!("[{label}]");
+        println!("check_order       {scalar:>8.2} ns/order");
+        println!("check_order_fast  {fast:>8.2} ns/order");
+        println!("check_mask        {mask:>8.2} ns/order");
+    }
+}
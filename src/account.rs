@@ -0,0 +1,162 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 口座種別（現金・信用・ポートフォリオ・マージン）。
+//!
+//! [`AccountType`] は口座に適用する追加の判定と既定の証拠金モデルを決める。
+//! 現金口座は設定された制限にかかわらず、売り建て（保有数量を超える売り）と
+//! 手元現金を超える買付（レバレッジ）を [`AccountType::check_order`] で常に
+//! 拒否し、証拠金は全額（100%）とする。信用口座は Reg-T 方式、ポートフォリオ・
+//! マージン口座はエンジン共通のリスクベース（bps）方式を使い、追加の判定は無い。
+//!
+//! [`RiskEngine::set_account_type`](crate::RiskEngine::set_account_type) で
+//! 口座に割り当てる。
+
+use alice_ledger::{Order, Side};
+
+use crate::margin::MarginParams;
+use crate::regt::{MarginModel, RegTParams};
+
+// ---------------------------------------------------------------------------
+// AccountReject
+// ---------------------------------------------------------------------------
+
+/// 口座種別による拒否。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountReject {
+    /// 現金口座での売り建て（保有数量を超える売り）。
+    ShortInCashAccount { quantity: u64, held: i64 },
+    /// 現金口座での手元現金を超える買付。
+    LeverageInCashAccount { required: i64, available: i64 },
+}
+
+// ---------------------------------------------------------------------------
+// AccountType
+// ---------------------------------------------------------------------------
+
+/// 口座種別。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    /// 現金口座: 売り建て・レバレッジ不可、証拠金は全額。
+    Cash,
+    /// 信用口座: Reg-T 方式の証拠金。
+    Margin,
+    /// ポートフォリオ・マージン口座: エンジン共通のリスクベース方式。
+    PortfolioMargin,
+}
+
+impl AccountType {
+    /// 既定の証拠金モデル。`None` はエンジン共通のモデル。
+    #[must_use]
+    pub fn margin_model(self) -> Option<MarginModel> {
+        match self {
+            Self::Cash => Some(MarginModel::Bps(MarginParams {
+                initial_margin_bps: 10_000,
+                maintenance_margin_bps: 10_000,
+                spread_credit_bps: 0,
+                cross_margin_offset_bps: 0,
+                delta_hedge_haircut_bps: 10_000,
+                mpor_base_days: 1,
+                mpor_days: 1,
+            })),
+            Self::Margin => Some(MarginModel::RegT(RegTParams::default())),
+            Self::PortfolioMargin => None,
+        }
+    }
+
+    /// 口座種別による追加の判定。
+    ///
+    /// 現金口座では、約定後のネット建玉が負になる売り（`held` は約定前の
+    /// ネット建玉）と、代金 `|price| * quantity` が `available_cash` を超える
+    /// 買いを拒否する。その他の口座種別は常に通す。
+    ///
+    /// # Errors
+    ///
+    /// [`AccountReject::ShortInCashAccount`] または
+    /// [`AccountReject::LeverageInCashAccount`]。
+    pub fn check_order(
+        self,
+        order: &Order,
+        held: i64,
+        available_cash: i64,
+    ) -> Result<(), AccountReject> {
+        if self != Self::Cash {
+            return Ok(());
+        }
+        match order.side {
+            Side::Ask if i128::from(order.quantity) > i128::from(held) => {
+                Err(AccountReject::ShortInCashAccount {
+                    quantity: order.quantity,
+                    held,
+                })
+            }
+            Side::Ask => Ok(()),
+            Side::Bid => {
+                let required = (u128::from(order.price.unsigned_abs()) * u128::from(order.quantity))
+                    .min(i64::MAX as u128) as i64;
+                if required > available_cash {
+                    return Err(AccountReject::LeverageInCashAccount {
+                        required,
+                        available: available_cash,
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity).build()
+    }
+
+    #[test]
+    fn cash_accounts_never_short_or_lever() {
+        let cash = AccountType::Cash;
+        assert!(cash.check_order(&order(Side::Ask, 10), 10, 0).is_ok());
+        assert_eq!(
+            cash.check_order(&order(Side::Ask, 11), 10, 0),
+            Err(AccountReject::ShortInCashAccount {
+                quantity: 11,
+                held: 10,
+            })
+        );
+        assert!(cash.check_order(&order(Side::Bid, 10), 0, 1_000).is_ok());
+        assert_eq!(
+            cash.check_order(&order(Side::Bid, 11), 0, 1_000),
+            Err(AccountReject::LeverageInCashAccount {
+                required: 1_100,
+                available: 1_000,
+            })
+        );
+        for other in [AccountType::Margin, AccountType::PortfolioMargin] {
+            assert!(other.check_order(&order(Side::Ask, 11), -5, 0).is_ok());
+        }
+    }
+
+    #[test]
+    fn margin_models() {
+        assert_eq!(
+            AccountType::Cash
+                .margin_model()
+                .map(|m| m.maintenance_margin(100, 10)),
+            Some(1_000)
+        );
+        assert_eq!(
+            AccountType::Margin.margin_model(),
+            Some(MarginModel::RegT(RegTParams::default()))
+        );
+        assert_eq!(AccountType::PortfolioMargin.margin_model(), None);
+    }
+}
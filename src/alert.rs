@@ -0,0 +1,126 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 重大イベントの通知。
+//!
+//! [`RiskEngine`](crate::RiskEngine) はブレーカー作動・キルスイッチ・マージン
+//! コール・日次損失上限到達・ドローダウンによる自動縮小のたびに、登録された [`AlertSink`] を呼ぶ。
+//! 組込み側は PagerDuty・Slack・Webhook などへの送信をこのトレイトで実装すれば
+//! よく、エンジンの状態をポーリングする必要はない。
+
+use alloc::vec::Vec;
+
+use crate::derisk::DeriskState;
+use crate::margin::StaleMark;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// Severity / AlertEvent
+// ---------------------------------------------------------------------------
+
+/// 通知の重大度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// 参考情報。
+    Info,
+    /// 注意が必要。
+    Warning,
+    /// 即時対応が必要。
+    Critical,
+}
+
+/// 通知対象のイベント。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertEvent {
+    /// 口座のサーキットブレーカーが作動した。
+    BreakerTripped { account_id: u64 },
+    /// キルスイッチで全口座を停止した。
+    KillSwitch { accounts: usize },
+    /// 資産額が維持証拠金を下回った。`stale_mark` は維持証拠金の計算に
+    /// 使った値洗い価格のうち最も古いもの（鮮度の基準を超えた場合のみ）。
+    MarginCall {
+        account_id: u64,
+        equity: i64,
+        maintenance_margin: i64,
+        stale_mark: Option<StaleMark>,
+    },
+    /// 日次損失が上限に達した。
+    LossLimitHit {
+        account_id: u64,
+        daily_pnl: i64,
+        limit: i64,
+    },
+    /// ドローダウンの閾値に達し、自動でリスクを縮小した。
+    Derisked {
+        account_id: u64,
+        drawdown_bps: u32,
+        state: DeriskState,
+    },
+}
+
+impl AlertEvent {
+    /// イベントの既定の重大度。
+    #[must_use]
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::BreakerTripped { .. } | Self::MarginCall { .. } => Severity::Warning,
+            Self::KillSwitch { .. } | Self::LossLimitHit { .. } | Self::Derisked { .. } => {
+                Severity::Critical
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AlertSink
+// ---------------------------------------------------------------------------
+
+/// 重大イベントの送信先。
+pub trait AlertSink {
+    /// `event` を `severity` で通知する。
+    fn alert(&mut self, severity: Severity, event: &AlertEvent);
+}
+
+/// 受け取った通知を順に保持する（テスト・バッチ処理用）。
+impl AlertSink for Vec<(Severity, AlertEvent)> {
+    fn alert(&mut self, severity: Severity, event: &AlertEvent) {
+        self.push((severity, event.clone()));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severities() {
+        assert_eq!(
+            AlertEvent::BreakerTripped { account_id: 1 }.severity(),
+            Severity::Warning
+        );
+        assert_eq!(
+            AlertEvent::KillSwitch { accounts: 3 }.severity(),
+            Severity::Critical
+        );
+        assert!(Severity::Critical > Severity::Warning);
+    }
+
+    #[test]
+    fn vec_sink_collects() {
+        let mut sink: Vec<(Severity, AlertEvent)> = Vec::new();
+        let event = AlertEvent::LossLimitHit {
+            account_id: 1,
+            daily_pnl: -10,
+            limit: -5,
+        };
+        sink.alert(event.severity(), &event);
+        assert_eq!(sink, [(Severity::Critical, event)]);
+    }
+}
@@ -0,0 +1,246 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 戦略（アルゴ）別の制限。
+//!
+//! 1 口座で多数の独立した戦略を動かす場合に、口座の [`PreTradeChecker`] に
+//! 加えて戦略 ID（[`DecisionTags::algo_id`]）ごとに独自の [`RiskLimits`] を
+//! 持つチェッカーを置く。建玉・想定元本・日次損失・非常駐注文レートなどの
+//! 制限と状態は戦略ごとに独立し、注文はまず戦略の制限、次に口座の制限で
+//! 判定する。戦略 ID の無い注文や制限を登録していない戦略の注文は口座の
+//! 制限のみで判定する。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{DecisionTags, PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// AlgoReject
+// ---------------------------------------------------------------------------
+
+/// 戦略別制限付き口座での拒否理由。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgoReject {
+    /// 戦略の制限に違反。
+    Strategy { algo_id: u64, reject: RiskReject },
+    /// 口座全体の制限に違反。
+    Account(RiskReject),
+}
+
+impl AlgoReject {
+    /// 元の [`RiskReject`]。
+    #[must_use]
+    pub const fn reject(&self) -> &RiskReject {
+        match self {
+            Self::Strategy { reject, .. } | Self::Account(reject) => reject,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AlgoLimits
+// ---------------------------------------------------------------------------
+
+/// 口座のチェッカーと戦略別チェッカーの組。
+pub struct AlgoLimits {
+    account: PreTradeChecker,
+    strategies: BTreeMap<u64, PreTradeChecker>,
+}
+
+impl AlgoLimits {
+    /// 口座全体の制限から作成。
+    #[must_use]
+    pub const fn new(account_limits: RiskLimits) -> Self {
+        Self {
+            account: PreTradeChecker::new(account_limits),
+            strategies: BTreeMap::new(),
+        }
+    }
+
+    /// 戦略の制限を登録する（既存の戦略は状態ごと置き換え）。
+    pub fn add_strategy(&mut self, algo_id: u64, limits: RiskLimits) {
+        self.strategies
+            .insert(algo_id, PreTradeChecker::new(limits));
+    }
+
+    /// 戦略の制限を削除する。
+    pub fn remove_strategy(&mut self, algo_id: u64) -> Option<PreTradeChecker> {
+        self.strategies.remove(&algo_id)
+    }
+
+    /// 口座全体のチェッカー。
+    #[must_use]
+    pub const fn account(&self) -> &PreTradeChecker {
+        &self.account
+    }
+
+    /// 口座全体のチェッカー（可変）。
+    pub const fn account_mut(&mut self) -> &mut PreTradeChecker {
+        &mut self.account
+    }
+
+    /// 戦略のチェッカー。
+    #[must_use]
+    pub fn strategy(&self, algo_id: u64) -> Option<&PreTradeChecker> {
+        self.strategies.get(&algo_id)
+    }
+
+    /// 戦略のチェッカー（可変）。
+    pub fn strategy_mut(&mut self, algo_id: u64) -> Option<&mut PreTradeChecker> {
+        self.strategies.get_mut(&algo_id)
+    }
+
+    /// 制限を登録済みの戦略数。
+    #[must_use]
+    pub fn strategy_count(&self) -> usize {
+        self.strategies.len()
+    }
+
+    fn strategy_of(&mut self, tags: DecisionTags) -> Option<&mut PreTradeChecker> {
+        self.strategies.get_mut(&tags.algo_id?)
+    }
+
+    /// 戦略の制限、次に口座の制限で注文をチェックする。
+    ///
+    /// `strategy_position` はその戦略の建玉、`account_position` は口座全体の
+    /// 建玉。
+    ///
+    /// # Errors
+    ///
+    /// 最初に違反した制限を [`AlgoReject`] で返す。
+    pub fn check_order(
+        &self,
+        order: &Order,
+        tags: DecisionTags,
+        strategy_position: Option<&Position>,
+        account_position: Option<&Position>,
+    ) -> Result<(), AlgoReject> {
+        if let Some(algo_id) = tags.algo_id {
+            if let Some(strategy) = self.strategies.get(&algo_id) {
+                strategy
+                    .check_order(order, strategy_position)
+                    .map_err(|reject| AlgoReject::Strategy { algo_id, reject })?;
+            }
+        }
+        self.account
+            .check_order(order, account_position)
+            .map_err(AlgoReject::Account)
+    }
+
+    /// 受理した注文を戦略と口座の未約定注文として記録する。
+    pub fn on_order_accepted(&mut self, order: &Order, tags: DecisionTags) {
+        if let Some(strategy) = self.strategy_of(tags) {
+            strategy.on_order_accepted(order);
+        }
+        self.account.on_order_accepted(order);
+    }
+
+    /// 約定・取消で終了した注文を戦略と口座の未約定注文から外す。
+    pub fn on_order_closed(&mut self, order_id: u64, tags: DecisionTags) {
+        if let Some(strategy) = self.strategy_of(tags) {
+            strategy.on_order_closed(order_id);
+        }
+        self.account.on_order_closed(order_id);
+    }
+
+    /// P&L の変化を戦略と口座の日次損益に加算する。
+    pub fn record_pnl(&mut self, tags: DecisionTags, delta: i64) {
+        if let Some(strategy) = self.strategy_of(tags) {
+            let pnl = strategy.daily_pnl().saturating_add(delta);
+            strategy.update_daily_pnl(pnl);
+        }
+        let pnl = self.account.daily_pnl().saturating_add(delta);
+        self.account.update_daily_pnl(pnl);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(id: u64, quantity: u64) -> Order {
+        OrderBuilder::bid(100, quantity).id(id).build()
+    }
+
+    const fn algo(id: u64) -> DecisionTags {
+        DecisionTags {
+            algo_id: Some(id),
+            dea: false,
+        }
+    }
+
+    fn book() -> AlgoLimits {
+        let mut book = AlgoLimits::new(RiskLimits::default());
+        let tight = RiskLimits {
+            max_notional: 1_000,
+            max_daily_loss: -1_000,
+            ..RiskLimits::default()
+        };
+        book.add_strategy(1, tight.clone());
+        book.add_strategy(2, tight);
+        book
+    }
+
+    #[test]
+    fn strategy_limit_checked_first() {
+        let book = book();
+        assert!(book.check_order(&order(1, 10), algo(1), None, None).is_ok());
+        assert!(matches!(
+            book.check_order(&order(1, 11), algo(1), None, None),
+            Err(AlgoReject::Strategy {
+                algo_id: 1,
+                reject: RiskReject::NotionalExceeded { .. }
+            })
+        ));
+        // 未登録の戦略・タグ無しは口座の制限のみ。
+        assert!(book.check_order(&order(1, 11), algo(9), None, None).is_ok());
+        assert!(book
+            .check_order(&order(1, 11), DecisionTags::default(), None, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn loss_tracked_per_strategy() {
+        let mut book = book();
+        book.record_pnl(algo(1), -1_000);
+        let err = book
+            .check_order(&order(1, 1), algo(1), None, None)
+            .unwrap_err();
+        assert!(matches!(err.reject(), RiskReject::DailyLossLimitHit { .. }));
+        assert!(book.check_order(&order(2, 1), algo(2), None, None).is_ok());
+        assert_eq!(book.account().daily_pnl(), -1_000);
+        assert_eq!(book.strategy(2).unwrap().daily_pnl(), 0);
+    }
+
+    #[test]
+    fn account_limits_still_apply() {
+        let mut book = AlgoLimits::new(RiskLimits {
+            max_open_orders: 1,
+            ..RiskLimits::default()
+        });
+        book.add_strategy(1, RiskLimits::default());
+        book.on_order_accepted(&order(1, 1), algo(1));
+        assert_eq!(book.strategy(1).unwrap().open_order_count(), 1);
+        assert!(matches!(
+            book.check_order(&order(2, 1), algo(2), None, None),
+            Err(AlgoReject::Account(RiskReject::MaxOpenOrdersReached { .. }))
+        ));
+        book.on_order_closed(1, algo(1));
+        assert_eq!(book.strategy(1).unwrap().open_order_count(), 0);
+        assert_eq!(book.strategy_count(), 1);
+        assert!(book.remove_strategy(1).is_some());
+        assert!(book.strategy_mut(1).is_none());
+    }
+}
@@ -0,0 +1,360 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 拒否された注文の上申と、監督者による 1 回限りの例外承認。
+//!
+//! 拒否された注文は [`ApprovalQueue::escalate`] で承認待ちに上げられ、監督者が
+//! API から [`ApprovalQueue::approve`]（または [`ApprovalQueue::deny`]）する。
+//! 承認された例外は、同じ口座・同じ注文（ID・売買方向・価格・数量）が同じ
+//! 種類の拒否を受けたときに 1 回だけ使える（[`ApprovalQueue::check_order`]）。
+//! 上申・承認・却下・使用はすべて、承認者と元の拒否理由付きで
+//! [`ApprovalQueue::audit_log`] に残る。上申者本人は承認できない。
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// ApprovalError
+// ---------------------------------------------------------------------------
+
+/// 承認操作の失敗。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalError {
+    /// 承認待ちに無い上申。
+    NotPending { request_id: u64 },
+    /// 上申者本人による承認。
+    SelfApproval { request_id: u64 },
+}
+
+// ---------------------------------------------------------------------------
+// PendingApproval / ApprovalRecord
+// ---------------------------------------------------------------------------
+
+/// 承認待ちの上申。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingApproval {
+    /// 上申 ID。
+    pub request_id: u64,
+    /// 口座 ID。
+    pub account_id: u64,
+    /// 拒否された注文。
+    pub order: Order,
+    /// 元の拒否理由。
+    pub reject: RiskReject,
+    /// 上申者。
+    pub requested_by: String,
+    /// 上申時刻（ns）。
+    pub requested_ns: u64,
+}
+
+/// 監査ログの操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalAction {
+    /// 上申。
+    Escalated,
+    /// 承認（1 回限りの例外を付与）。
+    Approved,
+    /// 却下。
+    Denied,
+    /// 例外の使用。
+    Used,
+}
+
+/// 監査ログの 1 件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalRecord {
+    /// 上申 ID。
+    pub request_id: u64,
+    /// 口座 ID。
+    pub account_id: u64,
+    /// 注文 ID。
+    pub order_id: u64,
+    /// 元の拒否理由。
+    pub reject: RiskReject,
+    /// 操作。
+    pub action: ApprovalAction,
+    /// 操作者（上申は上申者、承認・却下・使用は承認者）。
+    pub actor: String,
+    /// 操作時刻（ns）。
+    pub timestamp_ns: u64,
+}
+
+// ---------------------------------------------------------------------------
+// ApprovalQueue
+// ---------------------------------------------------------------------------
+
+/// 承認済みで未使用の例外。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Exception {
+    request: PendingApproval,
+    approver: String,
+}
+
+/// 上申の承認待ち行列と監査ログ。
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalQueue {
+    next_id: u64,
+    pending: BTreeMap<u64, PendingApproval>,
+    /// (口座 ID, 注文 ID) → 例外。
+    exceptions: BTreeMap<(u64, u64), Exception>,
+    audit: Vec<ApprovalRecord>,
+}
+
+impl ApprovalQueue {
+    /// 空の行列を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            next_id: 0,
+            pending: BTreeMap::new(),
+            exceptions: BTreeMap::new(),
+            audit: Vec::new(),
+        }
+    }
+
+    fn log(&mut self, request: &PendingApproval, action: ApprovalAction, actor: &str, now_ns: u64) {
+        self.audit.push(ApprovalRecord {
+            request_id: request.request_id,
+            account_id: request.account_id,
+            order_id: request.order.id.0,
+            reject: request.reject.clone(),
+            action,
+            actor: actor.into(),
+            timestamp_ns: now_ns,
+        });
+    }
+
+    /// 拒否された注文を承認待ちに上げ、上申 ID を返す。
+    pub fn escalate(
+        &mut self,
+        account_id: u64,
+        order: &Order,
+        reject: RiskReject,
+        requested_by: &str,
+        now_ns: u64,
+    ) -> u64 {
+        self.next_id += 1;
+        let request = PendingApproval {
+            request_id: self.next_id,
+            account_id,
+            order: order.clone(),
+            reject,
+            requested_by: requested_by.into(),
+            requested_ns: now_ns,
+        };
+        self.log(&request, ApprovalAction::Escalated, requested_by, now_ns);
+        self.pending.insert(request.request_id, request);
+        self.next_id
+    }
+
+    /// 承認待ちの上申（上申 ID 順）。
+    pub fn pending(&self) -> impl Iterator<Item = &PendingApproval> {
+        self.pending.values()
+    }
+
+    /// 上申を承認し、1 回限りの例外を付与する。
+    ///
+    /// # Errors
+    ///
+    /// 承認待ちに無い場合は [`ApprovalError::NotPending`]、上申者本人の場合は
+    /// [`ApprovalError::SelfApproval`]（上申は承認待ちのまま残る）。
+    pub fn approve(
+        &mut self,
+        request_id: u64,
+        approver: &str,
+        now_ns: u64,
+    ) -> Result<(), ApprovalError> {
+        let request = self
+            .pending
+            .get(&request_id)
+            .ok_or(ApprovalError::NotPending { request_id })?;
+        if request.requested_by == approver {
+            return Err(ApprovalError::SelfApproval { request_id });
+        }
+        let Some(request) = self.pending.remove(&request_id) else {
+            return Err(ApprovalError::NotPending { request_id });
+        };
+        self.log(&request, ApprovalAction::Approved, approver, now_ns);
+        self.exceptions.insert(
+            (request.account_id, request.order.id.0),
+            Exception {
+                request,
+                approver: approver.into(),
+            },
+        );
+        Ok(())
+    }
+
+    /// 上申を却下する。
+    ///
+    /// # Errors
+    ///
+    /// 承認待ちに無い場合は [`ApprovalError::NotPending`]。
+    pub fn deny(
+        &mut self,
+        request_id: u64,
+        approver: &str,
+        now_ns: u64,
+    ) -> Result<(), ApprovalError> {
+        let request = self
+            .pending
+            .remove(&request_id)
+            .ok_or(ApprovalError::NotPending { request_id })?;
+        self.log(&request, ApprovalAction::Denied, approver, now_ns);
+        Ok(())
+    }
+
+    /// 口座の注文に使える未使用の例外があるか。
+    #[must_use]
+    pub fn has_exception(&self, account_id: u64, order: &Order) -> bool {
+        self.exceptions
+            .get(&(account_id, order.id.0))
+            .is_some_and(|e| same_order(&e.request.order, order))
+    }
+
+    /// `checker` で判定し、拒否された場合は未使用の例外で 1 回だけ通す。
+    ///
+    /// 例外は同じ口座・同じ注文（ID・売買方向・価格・数量）が承認時と同じ
+    /// 種類の拒否を受けた場合だけ使い、その時点で消費して監査ログに残す。
+    ///
+    /// # Errors
+    ///
+    /// 例外で通せない場合は `checker` の拒否。
+    pub fn check_order(
+        &mut self,
+        checker: &PreTradeChecker,
+        account_id: u64,
+        order: &Order,
+        position: Option<&Position>,
+        now_ns: u64,
+    ) -> Result<(), RiskReject> {
+        let Err(reject) = checker.check_order(order, position) else {
+            return Ok(());
+        };
+        let key = (account_id, order.id.0);
+        let usable = self.exceptions.get(&key).is_some_and(|e| {
+            same_order(&e.request.order, order)
+                && core::mem::discriminant(&e.request.reject) == core::mem::discriminant(&reject)
+        });
+        match usable.then(|| self.exceptions.remove(&key)).flatten() {
+            Some(exception) => {
+                self.log(
+                    &exception.request,
+                    ApprovalAction::Used,
+                    &exception.approver,
+                    now_ns,
+                );
+                Ok(())
+            }
+            None => Err(reject),
+        }
+    }
+
+    /// 監査ログ（記録順）。
+    #[must_use]
+    pub fn audit_log(&self) -> &[ApprovalRecord] {
+        &self.audit
+    }
+}
+
+/// 例外の対象として同じ注文か（ID・売買方向・価格・数量）。
+fn same_order(approved: &Order, order: &Order) -> bool {
+    approved.id == order.id
+        && approved.side == order.side
+        && approved.price == order.price
+        && approved.quantity == order.quantity
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(id: u64, quantity: u64) -> Order {
+        OrderBuilder::bid(100, quantity).id(id).build()
+    }
+
+    fn checker() -> PreTradeChecker {
+        PreTradeChecker::new(RiskLimits {
+            max_order_size: 10,
+            ..RiskLimits::default()
+        })
+    }
+
+    #[test]
+    fn approved_exception_is_used_once() {
+        let checker = checker();
+        let mut queue = ApprovalQueue::new();
+        let big = order(7, 20);
+        let reject = checker.check_order(&big, None).unwrap_err();
+        let id = queue.escalate(1, &big, reject.clone(), "trader", 10);
+        assert_eq!(queue.pending().count(), 1);
+        assert_eq!(
+            queue.approve(id, "trader", 11),
+            Err(ApprovalError::SelfApproval { request_id: id })
+        );
+        queue.approve(id, "supervisor", 12).unwrap();
+        assert_eq!(queue.pending().count(), 0);
+        // 別口座・内容の違う注文には使えない。
+        assert!(queue.check_order(&checker, 2, &big, None, 13).is_err());
+        assert!(!queue.has_exception(1, &order(7, 30)));
+        assert!(queue.has_exception(1, &big));
+        assert!(queue.check_order(&checker, 1, &big, None, 14).is_ok());
+        assert!(queue.check_order(&checker, 1, &big, None, 15).is_err());
+
+        let actions: Vec<_> = queue
+            .audit_log()
+            .iter()
+            .map(|r| (r.action, r.actor.as_str(), r.timestamp_ns))
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                (ApprovalAction::Escalated, "trader", 10),
+                (ApprovalAction::Approved, "supervisor", 12),
+                (ApprovalAction::Used, "supervisor", 14),
+            ]
+        );
+        assert!(queue.audit_log().iter().all(|r| r.reject == reject));
+    }
+
+    #[test]
+    fn denied_and_mismatched_rejects() {
+        let mut checker = checker();
+        let mut queue = ApprovalQueue::new();
+        let big = order(1, 20);
+        let reject = checker.check_order(&big, None).unwrap_err();
+        let id = queue.escalate(1, &big, reject.clone(), "trader", 0);
+        queue.deny(id, "supervisor", 1).unwrap();
+        assert_eq!(
+            queue.approve(id, "supervisor", 2),
+            Err(ApprovalError::NotPending { request_id: id })
+        );
+        assert!(queue.check_order(&checker, 1, &big, None, 3).is_err());
+
+        // 承認時と別の種類の拒否には使えない。
+        let id = queue.escalate(1, &big, reject, "trader", 4);
+        queue.approve(id, "supervisor", 5).unwrap();
+        checker.trip_circuit_breaker();
+        assert!(queue.check_order(&checker, 1, &big, None, 6).is_err());
+        assert!(queue.has_exception(1, &big));
+        assert_eq!(
+            queue.audit_log().last().map(|r| r.action),
+            Some(ApprovalAction::Approved)
+        );
+    }
+}
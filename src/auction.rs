@@ -0,0 +1,239 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! ボラティリティ中断のオークションモード。
+//!
+//! 価格が急変した銘柄は連続売買を止め、板寄せ（オークション）で再開する
+//! ことがある。オークション中は約定が一度にまとまり、価格も大きく動くので、
+//! 連続売買の閾値のままではブレーカーとプライスカラーが誤って作動する。
+//! [`AuctionMode`] はオークション中だけ [`AuctionParams`] のパラメータに
+//! 切り替え、即時執行（IOC / FOK）の注文を拒否する。
+//!
+//! オークション中かどうかは、カレンダーの時間帯（[`AuctionMode::add_window`]）
+//! または外部シグナル（[`AuctionMode::set_signal`]）で決まる。
+
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{rests_on_book, PreTradeChecker, RiskReject};
+use crate::circuit::CircuitBreaker;
+use crate::config::BreakerConfig;
+use crate::limit::RiskLimits;
+use crate::market::MarketContext;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// AuctionParams
+// ---------------------------------------------------------------------------
+
+/// オークション中に使うパラメータ。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuctionParams {
+    /// BBO からの許容幅（bps）。[`RiskLimits::max_through_bbo_bps`] を
+    /// 置き換える。
+    pub max_through_bbo_bps: u32,
+    /// フィルレートブレーカーのパラメータ。
+    pub breaker: BreakerConfig,
+}
+
+impl Default for AuctionParams {
+    fn default() -> Self {
+        Self {
+            max_through_bbo_bps: 2_000,
+            breaker: BreakerConfig {
+                max_move: 2_000,
+                max_fills_per_window: 1_000,
+                window_ns: 1_000_000_000,
+            },
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AuctionMode
+// ---------------------------------------------------------------------------
+
+/// 連続売買とオークションの切り替え。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuctionMode {
+    /// 連続売買中のフィルレートブレーカーのパラメータ。
+    continuous: BreakerConfig,
+    /// オークション中のパラメータ。
+    params: AuctionParams,
+    /// カレンダーのオークション時間帯（開始, 終了）（ns、終了は含まない）。
+    windows: Vec<(u64, u64)>,
+    /// 外部シグナルによるオークション。
+    signal: bool,
+}
+
+impl AuctionMode {
+    /// 連続売買中のブレーカーのパラメータとオークション中のパラメータから
+    /// 作成。
+    #[must_use]
+    pub const fn new(continuous: BreakerConfig, params: AuctionParams) -> Self {
+        Self {
+            continuous,
+            params,
+            windows: Vec::new(),
+            signal: false,
+        }
+    }
+
+    /// オークション中のパラメータ。
+    #[must_use]
+    pub const fn params(&self) -> &AuctionParams {
+        &self.params
+    }
+
+    /// カレンダーのオークション時間帯 `[start_ns, end_ns)` を追加する。
+    pub fn add_window(&mut self, start_ns: u64, end_ns: u64) {
+        self.windows.push((start_ns, end_ns));
+    }
+
+    /// 外部シグナルでオークションを開始・終了する。カレンダーの時間帯中は
+    /// 終了してもオークションのまま。
+    pub fn set_signal(&mut self, in_auction: bool) {
+        self.signal = in_auction;
+    }
+
+    /// `now_ns` の時点でオークション中か。
+    #[must_use]
+    pub fn in_auction(&self, now_ns: u64) -> bool {
+        self.signal
+            || self
+                .windows
+                .iter()
+                .any(|&(start, end)| start <= now_ns && now_ns < end)
+    }
+
+    /// `now_ns` の時点の制限。オークション中は BBO からの許容幅を置き換える。
+    #[must_use]
+    pub fn limits(&self, limits: &RiskLimits, now_ns: u64) -> RiskLimits {
+        let mut limits = limits.clone();
+        if self.in_auction(now_ns) {
+            limits.max_through_bbo_bps = self.params.max_through_bbo_bps;
+        }
+        limits
+    }
+
+    /// `now_ns` の時点のフィルレートブレーカーのパラメータ。
+    #[must_use]
+    pub fn breaker_config(&self, now_ns: u64) -> &BreakerConfig {
+        if self.in_auction(now_ns) {
+            &self.params.breaker
+        } else {
+            &self.continuous
+        }
+    }
+
+    /// `breaker` の閾値を `now_ns` の時点のパラメータに合わせる。窓の約定数・
+    /// 基準価格・作動状態は残す。
+    pub fn apply_to_breaker(&self, breaker: &mut CircuitBreaker, now_ns: u64) {
+        let config = self.breaker_config(now_ns);
+        breaker.max_move = config.max_move;
+        breaker.max_fills_per_window = config.max_fills_per_window;
+        breaker.window_ns = config.window_ns;
+    }
+
+    /// `order` を注文の時刻のフェーズで判定する。連続売買中は
+    /// [`PreTradeChecker::check_order_in_market`] と同じ。オークション中は
+    /// 即時執行の注文を拒否し、BBO からの許容幅を広げて判定する。
+    ///
+    /// # Errors
+    ///
+    /// オークション中の IOC / FOK は [`RiskReject::NonRestingInAuction`]、
+    /// それ以外は `checker` の拒否。
+    pub fn check_order_in_market<M: MarketContext + ?Sized>(
+        &self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+        market: &M,
+    ) -> Result<(), RiskReject> {
+        let now_ns = order.timestamp_ns;
+        if !self.in_auction(now_ns) {
+            return checker.check_order_in_market(order, position, market);
+        }
+        if !rests_on_book(order) {
+            return Err(RiskReject::NonRestingInAuction);
+        }
+        let limits = self.limits(checker.limits(), now_ns);
+        checker.check_in_market_with(&limits, order, position, market)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{Bbo, MarketSnapshot, SymbolMarket};
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+    use alice_ledger::TimeInForce;
+
+    fn buy(price: i64, time_in_force: TimeInForce, timestamp_ns: u64) -> Order {
+        OrderBuilder::bid(price, 1)
+            .time_in_force(time_in_force)
+            .at(timestamp_ns)
+            .build()
+    }
+
+    #[test]
+    fn calendar_and_signal_switch_rules() {
+        let mut mode = AuctionMode::new(BreakerConfig::default(), AuctionParams::default());
+        mode.add_window(100, 200);
+        assert!(!mode.in_auction(99));
+        assert!(mode.in_auction(100));
+        assert!(!mode.in_auction(200));
+        mode.set_signal(true);
+        assert!(mode.in_auction(300));
+
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            7,
+            SymbolMarket {
+                bbo: Some(Bbo::new(9_990, 10_000)),
+                ..SymbolMarket::default()
+            },
+        );
+        let position = PositionBuilder::new(7, 0).avg_entry_price(0).build();
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let check = |mode: &AuctionMode, order: &Order| {
+            mode.check_order_in_market(&checker, order, Some(&position), &market)
+        };
+        // 連続売買中は 5% を超えて気配を突き抜ける指値を拒否する。
+        mode.set_signal(false);
+        assert!(matches!(
+            check(&mode, &buy(10_600, TimeInForce::GTC, 0)),
+            Err(RiskReject::PriceThroughBbo { .. })
+        ));
+        assert!(check(&mode, &buy(10_000, TimeInForce::IOC, 0)).is_ok());
+        // オークション中は許容幅が広がり、IOC は拒否される。
+        assert!(check(&mode, &buy(10_600, TimeInForce::GTC, 150)).is_ok());
+        assert_eq!(
+            check(&mode, &buy(10_000, TimeInForce::IOC, 150)),
+            Err(RiskReject::NonRestingInAuction)
+        );
+    }
+
+    #[test]
+    fn breaker_thresholds_follow_phase() {
+        let mut mode = AuctionMode::new(BreakerConfig::default(), AuctionParams::default());
+        let mut breaker = BreakerConfig::default().build();
+        breaker.reset(10_000, 0);
+        mode.set_signal(true);
+        mode.apply_to_breaker(&mut breaker, 0);
+        // オークションの約定は連続売買の閾値では作動する値幅でも通る。
+        assert!(!breaker.on_fill(11_000, 1));
+        mode.set_signal(false);
+        mode.apply_to_breaker(&mut breaker, 2);
+        assert_eq!(breaker.max_move, BreakerConfig::default().max_move);
+        assert!(breaker.on_fill(11_000, 3));
+    }
+}
@@ -0,0 +1,493 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! バッチ事前チェック。
+//!
+//! 複数注文を [`LANES`] 件ずつ構造体配列（SoA）へ詰め替え、数量・ポジション・
+//! 想定元本の比較を分岐のないループで一括評価する。ループは自動ベクトル化
+//! されやすい形（固定長配列・ビット演算による判定合成）で書かれており、
+//! `std::simd` のような nightly 機能には依存しない。
+//!
+//! 判定は保守的で、いずれかの比較に掛かったレーンだけを
+//! [`PreTradeChecker::check_order`] で再評価して正確な [`RiskReject`] を得る。
+//! 流動性の低い銘柄（[`RiskLimits::illiquid_symbols`]）のレーンは上限が縮むため
+//! 常に再評価する。そのため結果は常に「同じ状態で 1 件ずつ `check_order` を
+//! 呼んだ場合」と一致する。
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{rests_on_book, PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+
+/// 1 回の比較で評価する注文数。
+pub const LANES: usize = 8;
+
+// ---------------------------------------------------------------------------
+// Lanes (SoA)
+// ---------------------------------------------------------------------------
+
+/// [`LANES`] 件分の注文を `SoA` 形式で保持する。
+#[derive(Debug, Clone, Copy)]
+struct Lanes {
+    quantity: [u64; LANES],
+    price: [i64; LANES],
+    /// サイドを反映した数量（買い: 正、売り: 負）。数量が `i64` を超える場合は 0。
+    delta: [i64; LANES],
+    /// チェック時点のネットポジション。
+    net: [i64; LANES],
+    /// 板に滞留する注文か（IOC / FOK は時間窓の判定があるためスカラー評価）。
+    resting: [bool; LANES],
+}
+
+impl Lanes {
+    /// `orders[i]` / `positions[i]` を詰め込む。
+    fn load(orders: &[Order; LANES], positions: &[Option<&Position>; LANES]) -> Self {
+        Self {
+            quantity: core::array::from_fn(|i| orders[i].quantity),
+            price: core::array::from_fn(|i| orders[i].price),
+            delta: core::array::from_fn(|i| {
+                let q = i64::try_from(orders[i].quantity).unwrap_or(0);
+                match orders[i].side {
+                    Side::Bid => q,
+                    Side::Ask => -q,
+                }
+            }),
+            net: core::array::from_fn(|i| positions[i].map_or(0, |p| p.net_quantity)),
+            resting: core::array::from_fn(|i| rests_on_book(&orders[i])),
+        }
+    }
+
+    /// 数量・ポジション・想定元本・未約定注文の想定元本合計（`open_notional`
+    /// に加算した値）のいずれかに掛かる可能性のあるレーン、および非滞留注文の
+    /// レーンのビットマスク。
+    ///
+    /// `i64` に収まらない数量やオーバーフローの可能性がある計算も立てる
+    /// （正確な判定は呼び出し側のスカラー再評価に任せる）。
+    /// 分岐を作らないよう、短絡評価ではなくビット演算で合成する。
+    #[allow(clippy::needless_bitwise_bool)]
+    fn suspect_mask(&self, limits: &RiskLimits, open_notional: i64) -> u32 {
+        let checks = limits.checks;
+        let mut mask = 0_u32;
+        for i in 0..LANES {
+            let q = self.quantity[i];
+            let size = checks.order_size & (q > limits.max_order_size);
+            let huge = q > i64::MAX as u64;
+            let (after, overflow) = self.net[i].overflowing_add(self.delta[i]);
+            let position =
+                checks.position & (huge | overflow | (after.unsigned_abs() > limits.max_position));
+            let (n, wrapped) = self.price[i].overflowing_mul(q as i64);
+            let (n, negated) = n.overflowing_abs();
+            let wrapped = wrapped | negated;
+            let notional = checks.notional & (huge | wrapped | (n > limits.max_notional));
+            let working = checks.open_order_notional
+                & (huge
+                    | wrapped
+                    | (open_notional.saturating_add(n) > limits.max_open_order_notional));
+            mask |= u32::from(size | position | notional | working | !self.resting[i]) << i;
+        }
+        mask
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PreTradeChecker::check_batch
+// ---------------------------------------------------------------------------
+
+impl PreTradeChecker {
+    /// 複数注文を現在の状態で一括チェックし、`out` に結果を追記する。
+    ///
+    /// `positions[i]` は `orders[i]` の銘柄のポジション。結果は各注文について
+    /// [`check_order`](Self::check_order) を呼んだ場合と同一で、状態は変化しない。
+    ///
+    /// # Panics
+    ///
+    /// `orders` と `positions` の長さが異なる場合。
+    pub fn check_batch(
+        &self,
+        orders: &[Order],
+        positions: &[Option<&Position>],
+        out: &mut Vec<Result<(), RiskReject>>,
+    ) {
+        assert_eq!(
+            orders.len(),
+            positions.len(),
+            "orders and positions must have the same length"
+        );
+        out.reserve(orders.len());
+        if self.is_circuit_breaker_tripped() {
+            out.extend(
+                orders
+                    .iter()
+                    .map(|_| Err(RiskReject::CircuitBreakerTripped)),
+            );
+            return;
+        }
+        // 注文に依存しない判定は 1 回だけ評価する。
+        let common = self.account_state_check();
+        let limits = self.limits();
+        let open_notional = self.open_order_notional();
+        let order_chunks = orders.chunks_exact(LANES);
+        let position_chunks = positions.chunks_exact(LANES);
+        let (order_rest, position_rest) = (order_chunks.remainder(), position_chunks.remainder());
+        for (orders, positions) in order_chunks.zip(position_chunks) {
+            let (Ok(orders), Ok(positions)) = (orders.try_into(), positions.try_into()) else {
+                unreachable!("chunks_exact yields LANES elements");
+            };
+            let mut mask = Lanes::load(orders, positions).suspect_mask(limits, open_notional);
+            if limits.checks.open_orders_per_symbol {
+                mask |= self.symbol_mask(positions);
+            }
+            if !limits.illiquid_symbols.is_empty() {
+                mask |= illiquid_mask(limits, positions);
+            }
+            if mask == 0 {
+                out.extend(core::iter::repeat_n(&common, LANES).cloned());
+                continue;
+            }
+            for (i, (order, position)) in orders.iter().zip(positions).enumerate() {
+                if mask & (1 << i) == 0 {
+                    out.push(common.clone());
+                } else {
+                    out.push(self.check_order(order, *position));
+                }
+            }
+        }
+        // 端数は逐次評価する。
+        for (order, position) in order_rest.iter().zip(position_rest) {
+            out.push(self.check_order(order, *position));
+        }
+    }
+
+    /// 銘柄別の未約定注文数の上限に達しているレーンのビットマスク。
+    ///
+    /// 銘柄ごとに件数が異なるため、共通判定ではなくレーン単位で評価する。
+    fn symbol_mask(&self, positions: &[Option<&Position>; LANES]) -> u32 {
+        let mut mask = 0_u32;
+        for (i, position) in positions.iter().enumerate() {
+            mask |= u32::from(self.symbol_open_orders_check(*position).is_err()) << i;
+        }
+        mask
+    }
+
+    /// 滞留注文に対する未約定注文数と日次損失の判定（`check_order` の 5・6 番目）。
+    ///
+    /// 銘柄別の未約定注文数と未約定注文の想定元本合計は注文ごとに異なるため、
+    /// ここでは評価せずマスクで拾ったレーンのスカラー再評価に任せる。
+    fn account_state_check(&self) -> Result<(), RiskReject> {
+        let limits = self.limits();
+        if limits.checks.open_orders && self.open_order_count() >= limits.max_open_orders {
+            return Err(RiskReject::MaxOpenOrdersReached {
+                count: self.open_order_count(),
+                limit: limits.max_open_orders,
+            });
+        }
+        if limits.checks.daily_loss && self.loss_pnl() <= limits.max_daily_loss {
+            return Err(self.daily_loss_reject(limits.max_daily_loss));
+        }
+        Ok(())
+    }
+}
+
+/// 流動性の低い銘柄のレーンのビットマスク。
+///
+/// 発注数量・建玉の上限が銘柄ごとに縮むため、口座全体の上限で比べる
+/// [`Lanes::suspect_mask`] では拾えない。
+fn illiquid_mask(limits: &RiskLimits, positions: &[Option<&Position>; LANES]) -> u32 {
+    let mut mask = 0_u32;
+    for (i, position) in positions.iter().enumerate() {
+        mask |= u32::from(position.is_some_and(|p| limits.is_illiquid(p.symbol_hash))) << i;
+    }
+    mask
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::ArithmeticMode;
+    use crate::limit::EnabledChecks;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+    use alice_ledger::TimeInForce;
+    use proptest::prelude::*;
+
+    fn order(id: u64, side: Side, price: i64, quantity: u64) -> Order {
+        OrderBuilder::new(side, price, quantity).id(id).build()
+    }
+
+    fn position(net_quantity: i64) -> Position {
+        PositionBuilder::new(1, net_quantity).build()
+    }
+
+    fn scalar(
+        checker: &PreTradeChecker,
+        orders: &[Order],
+        positions: &[Option<&Position>],
+    ) -> Vec<Result<(), RiskReject>> {
+        orders
+            .iter()
+            .zip(positions)
+            .map(|(o, p)| checker.check_order(o, *p))
+            .collect()
+    }
+
+    fn batch(
+        checker: &PreTradeChecker,
+        orders: &[Order],
+        positions: &[Option<&Position>],
+    ) -> Vec<Result<(), RiskReject>> {
+        let mut out = Vec::new();
+        checker.check_batch(orders, positions, &mut out);
+        out
+    }
+
+    #[test]
+    fn mixed_batch_matches_scalar() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let long = position(990);
+        let orders: Vec<Order> = (0..20)
+            .map(|i| {
+                let side = if i % 2 == 0 { Side::Bid } else { Side::Ask };
+                order(i, side, 1000 + i as i64 * 10_000, i * 7)
+            })
+            .collect();
+        let positions: Vec<Option<&Position>> = (0..20)
+            .map(|i| if i % 3 == 0 { Some(&long) } else { None })
+            .collect();
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        assert!(got.iter().any(Result::is_ok));
+        assert!(got.iter().any(Result::is_err));
+    }
+
+    #[test]
+    fn breaker_rejects_all() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        checker.trip_circuit_breaker();
+        let orders = [order(1, Side::Bid, 1, 1), order(2, Side::Ask, 1, 1)];
+        let got = batch(&checker, &orders, &[None, None]);
+        assert_eq!(got, vec![Err(RiskReject::CircuitBreakerTripped); 2]);
+    }
+
+    #[test]
+    fn account_state_reject_shared() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        checker.update_daily_pnl(RiskLimits::default().max_daily_loss);
+        let orders = [order(1, Side::Bid, 1, 1), order(2, Side::Bid, 1, 1_000_000)];
+        let positions = [None, None];
+        let got = batch(&checker, &orders, &positions);
+        assert!(matches!(got[0], Err(RiskReject::DailyLossLimitHit { .. })));
+        // 注文単位の判定が優先される。
+        assert!(matches!(got[1], Err(RiskReject::OrderSizeTooLarge { .. })));
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+    }
+
+    #[test]
+    fn appends_to_out() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let mut out = vec![Err(RiskReject::ArithmeticOverflow)];
+        checker.check_batch(&[order(1, Side::Bid, 1, 1)], &[None], &mut out);
+        assert_eq!(out.len(), 2);
+        assert!(out[1].is_ok());
+        checker.check_batch(&[], &[], &mut out);
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn length_mismatch_panics() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        checker.check_batch(&[order(1, Side::Bid, 1, 1)], &[], &mut Vec::new());
+    }
+
+    #[test]
+    fn checked_mode_overflow_matches_scalar() {
+        let limits = RiskLimits {
+            max_order_size: u64::MAX,
+            max_position: u64::MAX,
+            max_notional: i64::MAX,
+            max_open_orders: u32::MAX,
+            max_daily_loss: i64::MIN + 1,
+            ..RiskLimits::default()
+        };
+        let checker = PreTradeChecker::new(limits).with_arithmetic_mode(ArithmeticMode::Checked);
+        let edge = position(i64::MAX);
+        let orders = [
+            order(1, Side::Bid, i64::MAX, 2),
+            order(2, Side::Bid, 1, u64::MAX),
+            order(3, Side::Bid, 1, 1),
+            order(4, Side::Ask, 1, 1),
+        ];
+        let positions = [None, None, Some(&edge), Some(&edge)];
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        assert_eq!(got[0], Err(RiskReject::ArithmeticOverflow));
+        assert_eq!(got[3], Ok(()));
+    }
+
+    #[test]
+    fn disabled_checks_match_scalar() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_order_size: 0,
+            max_open_orders: 0,
+            checks: EnabledChecks {
+                order_size: false,
+                open_orders: false,
+                ..EnabledChecks::ALL
+            },
+            ..RiskLimits::default()
+        })
+        .with_arithmetic_mode(ArithmeticMode::Checked);
+        let orders: Vec<Order> = (0..10)
+            .map(|i| order(i, Side::Bid, 1000, 10 + i * 20_000))
+            .collect();
+        let positions = [None; 10];
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        assert!(got[0].is_ok());
+    }
+
+    #[test]
+    fn non_resting_orders_match_scalar() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 0,
+            max_non_resting_orders: 1,
+            ..RiskLimits::default()
+        });
+        let mut ioc = order(1, Side::Bid, 1000, 1);
+        ioc.time_in_force = TimeInForce::IOC;
+        checker.on_order_accepted(&ioc);
+        let mut later = ioc.clone();
+        later.timestamp_ns = 2_000_000_000;
+        let orders: Vec<Order> = (0..9)
+            .map(|i| match i % 3 {
+                0 => order(i, Side::Bid, 1000, 1),
+                1 => ioc.clone(),
+                _ => later.clone(),
+            })
+            .collect();
+        let positions = [None; 9];
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        assert!(matches!(
+            got[0],
+            Err(RiskReject::MaxOpenOrdersReached { .. })
+        ));
+        assert!(matches!(
+            got[1],
+            Err(RiskReject::NonRestingRateExceeded { .. })
+        ));
+        assert!(got[2].is_ok());
+    }
+
+    #[test]
+    fn open_order_notional_matches_scalar() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_order_notional: 100_000,
+            ..RiskLimits::default()
+        });
+        checker.on_order_accepted(&order(100, Side::Bid, 1000, 90));
+        let orders: Vec<Order> = (0..16)
+            .map(|i| order(i, Side::Ask, 1000, 1 + i % 4 * 5))
+            .collect();
+        let positions = [None; 16];
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        assert!(got[0].is_ok());
+        assert!(matches!(
+            got[3],
+            Err(RiskReject::OpenOrderNotionalExceeded {
+                max_allowed: 10,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn per_symbol_open_orders_match_scalar() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders_per_symbol: 1,
+            ..RiskLimits::default()
+        });
+        checker.on_order_accepted_in(&order(100, Side::Bid, 1000, 1), 1);
+        let full = position(0);
+        let other = PositionBuilder::new(2, 0).build();
+        let orders: Vec<Order> = (0..8).map(|i| order(i, Side::Bid, 1000, 1)).collect();
+        let positions: Vec<Option<&Position>> = (0..8)
+            .map(|i| match i % 3 {
+                0 => Some(&full),
+                1 => Some(&other),
+                _ => None,
+            })
+            .collect();
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        assert!(matches!(
+            got[0],
+            Err(RiskReject::SymbolOpenOrdersReached { symbol_hash: 1, .. })
+        ));
+        assert!(got[1].is_ok() && got[2].is_ok());
+    }
+
+    #[test]
+    fn illiquid_symbols_match_scalar() {
+        let mut limits = RiskLimits::default();
+        limits.illiquid_symbols.insert(7);
+        let checker = PreTradeChecker::new(limits);
+        let illiquid = PositionBuilder::new(7, 0).build();
+        let liquid = position(0);
+        let orders: Vec<Order> = (0..8).map(|i| order(i, Side::Bid, 1000, 100)).collect();
+        let positions: Vec<Option<&Position>> = (0..8)
+            .map(|i| {
+                if i % 2 == 0 {
+                    Some(&illiquid)
+                } else {
+                    Some(&liquid)
+                }
+            })
+            .collect();
+        let got = batch(&checker, &orders, &positions);
+        assert_eq!(got, scalar(&checker, &orders, &positions));
+        // 既定の縮小率 50% で 100 の上限が 50 になる。
+        assert!(matches!(
+            got[0],
+            Err(RiskReject::OrderSizeTooLarge { limit: 50, .. })
+        ));
+        assert!(got[1].is_ok());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_batch_equals_scalar(
+            specs in proptest::collection::vec(
+                (any::<bool>(), -1_000_000_i64..1_000_000, 0_u64..2_000, -2_000_i64..2_000),
+                0..40,
+            ),
+            checked in any::<bool>(),
+            open in 0_u32..150,
+        ) {
+            let mode = if checked { ArithmeticMode::Checked } else { ArithmeticMode::Saturating };
+            let mut checker = PreTradeChecker::new(RiskLimits::default()).with_arithmetic_mode(mode);
+            for id in 0..open {
+                checker.on_order_accepted(&OrderBuilder::bid(1_000, 1).id(u64::from(id)).build());
+            }
+            let orders: Vec<Order> = specs
+                .iter()
+                .enumerate()
+                .map(|(i, &(bid, price, qty, _))| {
+                    order(i as u64, if bid { Side::Bid } else { Side::Ask }, price, qty)
+                })
+                .collect();
+            let held: Vec<Position> = specs.iter().map(|s| position(s.3)).collect();
+            let positions: Vec<Option<&Position>> = held.iter().map(Some).collect();
+            prop_assert_eq!(
+                batch(&checker, &orders, &positions),
+                scalar(&checker, &orders, &positions)
+            );
+        }
+    }
+}
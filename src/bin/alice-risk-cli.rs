@@ -0,0 +1,491 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! `alice-risk-cli` — オンコール運用者向け管理コマンド。
+//!
+//! ```text
+//! alice-risk-cli <config-file> [--snapshot <snapshot-file>]
+//! ```
+//!
+//! 設定ファイル（TOML、[`RiskConfig`](alice_risk::RiskConfig) の形式）に
+//! `ALICE_RISK_` で始まる環境変数の上書きを [`LayeredConfig`] で重ねて
+//! [`PreTradeChecker`] を構築し、標準入力から 1 行 1 コマンドで操作する。
+//! `--snapshot` を指定すると、稼働中のエンジンが [`SnapshotCodec`] で封印した
+//! [`CheckerState`] を読み込み、その時点の日次損益・未約定注文数・ブレーカーの
+//! 状態を表示する。暗号化されたスナップショットの鍵は環境変数
+//! `ALICE_SNAPSHOT_KEY`（16 進 64 桁）で渡す。
+//!
+//! | コマンド                    | 動作                               |
+//! |-----------------------------|------------------------------------|
+//! | `show utilization`          | 使用率を表示                         |
+//! | `show limits`               | 現在の制限値を `[limits]` 表で表示     |
+//! | `show source <field>`       | 起動時の値の供給元を表示               |
+//! | `set limit <field> <value>` | 制限値を変更（新バージョンを記録）     |
+//! | `show history`              | 制限バージョン履歴を表示               |
+//! | `rollback <version>`        | 指定バージョンの制限へ戻す             |
+//! | `trip`                      | サーキットブレーカーを発動            |
+//! | `reset`                     | サーキットブレーカーを解除            |
+//! | `export audit`              | 本セッションの操作ログを出力          |
+//! | `quit`                      | 終了                               |
+//!
+//! 状態を変えるコマンド（`set limit`・`rollback`・`trip`・`reset`）は
+//! 予行（dry run）で、本プロセス内のチェッカーだけを変更し稼働中のエンジンには
+//! 届かない。イベントログ（[`EventLog`](alice_risk::EventLog)）はメモリ上にしか
+//! 無く、外部から追記する経路が無いため。出力にも `[dry run]` を付ける。
+//! 本番への反映は `export audit` の記録をもとに、エンジン側の制限変更の手順で
+//! 行う。
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+use alice_risk::{
+    CheckerState, LayeredConfig, PreTradeChecker, RiskLimits, SnapshotCodec, Utilization,
+};
+
+/// 制限変更の記録に使う作成者名。
+const AUTHOR: &str = "alice-risk-cli";
+
+/// 状態を変えるコマンドの出力の接頭辞。
+const DRY_RUN: &str = "[dry run]";
+
+/// スナップショットの鍵を渡す環境変数。
+///
+/// 設定の環境変数レイヤー（[`ENV_PREFIX`](alice_risk::config::ENV_PREFIX)）は接頭辞付きの未知の変数を
+/// エラーにするため、接頭辞の外に置く。
+const SNAPSHOT_KEY_VAR: &str = "ALICE_SNAPSHOT_KEY";
+
+/// 現在時刻（UNIX エポックからの ns）。
+fn now_ns() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+}
+
+// ---------------------------------------------------------------------------
+// Session
+// ---------------------------------------------------------------------------
+
+/// 1 回の CLI セッション状態。
+struct Session {
+    /// 起動時に読み込んだ設定（供給元の表示に使う）。
+    config: LayeredConfig,
+    checker: PreTradeChecker,
+    /// 状態を変更した操作の記録（コマンド番号, コマンド文字列）。
+    audit: Vec<(u64, String)>,
+    seq: u64,
+}
+
+/// コマンド実行結果。
+enum Outcome {
+    Output(String),
+    Quit,
+}
+
+impl Session {
+    fn new(config: LayeredConfig) -> Self {
+        Self {
+            checker: config.config().checker(),
+            config,
+            audit: Vec::new(),
+            seq: 0,
+        }
+    }
+
+    /// 1 行分のコマンドを実行する。
+    fn execute(&mut self, line: &str) -> Result<Outcome, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        self.seq += 1;
+        let output = match words.as_slice() {
+            [] => String::new(),
+            ["show", "utilization"] => format_utilization(&self.checker.utilization()),
+            ["show", "limits"] => format_limits(self.checker.limits()),
+            ["show", "source", field] => {
+                let path = format!("limits.{field}");
+                if self.config.config().limits.get_field(field).is_none() {
+                    return Err(format!("unknown field: {field}"));
+                }
+                format!("{path} {:?}", self.config.provenance(&path)).to_lowercase()
+            }
+            ["set", "limit", field, value] => {
+                let mut limits = self.checker.limits().clone();
+                limits
+                    .set_field(field, value)
+                    .map_err(|e| format!("set limit {field}: {e:?}"))?;
+                let version = self.checker.apply_limits(limits, AUTHOR, now_ns());
+                self.record(line);
+                format!("{DRY_RUN} {field} = {value} (version {version})")
+            }
+            ["show", "history"] => self
+                .checker
+                .history()
+                .iter()
+                .map(|v| {
+                    let origin = v
+                        .rollback_of
+                        .map_or(String::new(), |from| format!(" rollback_of={from}"));
+                    format!("{}\t{}\t{}{origin}", v.version, v.timestamp_ns, v.author)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ["rollback", version] => {
+                let target: u64 = version
+                    .parse()
+                    .map_err(|_| format!("invalid version: {version}"))?;
+                let new = self
+                    .checker
+                    .rollback_to(target, AUTHOR, now_ns())
+                    .map_err(|e| format!("rollback: {e:?}"))?;
+                self.record(line);
+                format!("{DRY_RUN} restored version {target} as version {new}")
+            }
+            ["trip"] => {
+                self.checker.trip_circuit_breaker();
+                self.record(line);
+                format!("{DRY_RUN} circuit breaker TRIPPED")
+            }
+            ["reset"] => {
+                self.checker.reset_circuit_breaker();
+                self.record(line);
+                format!("{DRY_RUN} circuit breaker reset")
+            }
+            ["export", "audit"] => self
+                .audit
+                .iter()
+                .map(|(seq, cmd)| format!("{seq}\t{cmd}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ["quit" | "exit"] => return Ok(Outcome::Quit),
+            _ => return Err(format!("unknown command: {}", line.trim())),
+        };
+        Ok(Outcome::Output(output))
+    }
+
+    fn record(&mut self, line: &str) {
+        self.audit.push((self.seq, line.trim().to_string()));
+    }
+}
+
+/// 使用率を 1 行 1 項目で整形する。
+fn format_utilization(u: &Utilization) -> String {
+    let open = if u.checks.open_orders {
+        format!(
+            "{}/{} ({} bps)",
+            u.open_orders,
+            u.max_open_orders,
+            u.open_orders_bps()
+        )
+    } else {
+        format!("{} (check disabled)", u.open_orders)
+    };
+    let working = if u.checks.open_order_notional {
+        format!(
+            "{} / {} ({} bps)",
+            u.open_order_notional,
+            u.max_open_order_notional,
+            u.open_order_notional_bps()
+        )
+    } else {
+        format!("{} (check disabled)", u.open_order_notional)
+    };
+    let loss = if u.checks.daily_loss {
+        format!(
+            "{} / {} ({} bps)",
+            u.daily_pnl,
+            u.max_daily_loss,
+            u.daily_loss_bps()
+        )
+    } else {
+        format!("{} (check disabled)", u.daily_pnl)
+    };
+    format!(
+        "open_orders {open}\nopen_order_notional {working}\ndaily_pnl {loss}\ncircuit_breaker {}",
+        if u.circuit_breaker_tripped {
+            "TRIPPED"
+        } else {
+            "ok"
+        },
+    )
+}
+
+/// 制限値を設定ファイルの `[limits]` 表として整形する。
+fn format_limits(limits: &RiskLimits) -> String {
+    let lines = RiskLimits::FIELDS
+        .iter()
+        .chain(&RiskLimits::CHECK_FIELDS)
+        .map(|name| format!("{name} = {}", limits.get_field(name).unwrap_or_default()))
+        .chain(RiskLimits::LIST_FIELDS.iter().map(|name| {
+            format!(
+                "{name} = \"{}\"",
+                limits.get_field(name).unwrap_or_default()
+            )
+        }));
+    core::iter::once("[limits]".to_string())
+        .chain(lines)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 16 進 64 桁の鍵を解析する。
+fn parse_key(hex: &str) -> Result<[u8; 32], String> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(format!("{SNAPSHOT_KEY_VAR}: expected 64 hex digits"));
+    }
+    let mut key = [0_u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("{SNAPSHOT_KEY_VAR}: expected 64 hex digits"))?;
+    }
+    Ok(key)
+}
+
+/// 封印されたスナップショットから [`CheckerState`] を取り出す。
+fn open_snapshot(sealed: &[u8], key: Option<&[u8; 32]>) -> Result<CheckerState, String> {
+    let codec = key.map_or_else(SnapshotCodec::new, |k| SnapshotCodec::new().with_key(k));
+    let payload = codec.open(sealed).map_err(|e| e.to_string())?;
+    CheckerState::from_bytes(&payload).ok_or_else(|| "not a checker state snapshot".to_string())
+}
+
+/// 設定ファイルと環境変数、指定があればスナップショットを読み込む。
+fn load(args: &[String]) -> Result<Session, String> {
+    let (path, snapshot) = match args {
+        [path] => (path, None),
+        [path, flag, snapshot] if flag == "--snapshot" => (path, Some(snapshot)),
+        _ => return Err("usage: alice-risk-cli <config-file> [--snapshot <snapshot-file>]".into()),
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let mut config = LayeredConfig::new();
+    config
+        .apply_file(&text)
+        .map_err(|e| format!("{path}: {e}"))?;
+    config
+        .apply_process_env()
+        .map_err(|e| format!("environment: {e}"))?;
+    let mut session = Session::new(config);
+    if let Some(snapshot) = snapshot {
+        let key = std::env::var(SNAPSHOT_KEY_VAR)
+            .ok()
+            .map(|hex| parse_key(&hex))
+            .transpose()?;
+        let sealed = std::fs::read(snapshot).map_err(|e| format!("{snapshot}: {e}"))?;
+        let state = open_snapshot(&sealed, key.as_ref()).map_err(|e| format!("{snapshot}: {e}"))?;
+        session.checker.restore_state(&state);
+    }
+    Ok(session)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut session = match load(&args) {
+        Ok(session) => session,
+        Err(e) if e.starts_with("usage:") => {
+            eprintln!("{e}");
+            return ExitCode::from(2);
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        match session.execute(&line) {
+            Ok(Outcome::Output(out)) if out.is_empty() => {}
+            Ok(Outcome::Output(out)) => {
+                let _ = writeln!(stdout, "{out}");
+            }
+            Ok(Outcome::Quit) => break,
+            Err(e) => eprintln!("error: {e}"),
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alice_risk::config::ENV_PREFIX;
+    use alice_risk::RiskConfig;
+
+    fn output(session: &mut Session, line: &str) -> String {
+        match session.execute(line) {
+            Ok(Outcome::Output(out)) => out,
+            Ok(Outcome::Quit) => panic!("unexpected quit"),
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[test]
+    fn loads_layered_config_with_sources() {
+        let mut config = LayeredConfig::new();
+        config
+            .apply_file(
+                "# desk A\n[limits]\nmax_order_size = 50\nmax_notional = 1_000 # cap\n\n\
+                 [symbols.42]\nmax_open_orders_per_symbol = 3\n",
+            )
+            .unwrap();
+        config
+            .apply_env([("ALICE_RISK_LIMITS__MAX_NOTIONAL", "2_000")])
+            .unwrap();
+        let mut s = Session::new(config);
+        let limits = s.checker.limits();
+        assert_eq!(limits.max_order_size, 50);
+        assert_eq!(limits.max_notional, 2_000);
+        assert_eq!(limits.max_position, RiskLimits::default().max_position);
+        assert_eq!(
+            output(&mut s, "show source max_order_size"),
+            "limits.max_order_size file"
+        );
+        assert_eq!(
+            output(&mut s, "show source max_notional"),
+            "limits.max_notional env"
+        );
+        assert_eq!(
+            output(&mut s, "show source max_position"),
+            "limits.max_position default"
+        );
+        assert!(s.execute("show source max_foo").is_err());
+    }
+
+    #[test]
+    fn config_errors_name_file_and_line() {
+        let path = std::env::temp_dir().join(format!("alice-risk-cli-{}.toml", std::process::id()));
+        std::fs::write(&path, "[limits]\nmax_order_size = 5\nmax_foo = 5\n").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let err = load(core::slice::from_ref(&path)).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.starts_with(&path), "{err}");
+        assert!(err.contains("line 3"), "{err}");
+        assert!(err.contains("unknown field"), "{err}");
+    }
+
+    #[test]
+    fn format_limits_roundtrip() {
+        let mut limits = RiskLimits {
+            max_order_size: 3,
+            ..RiskLimits::default()
+        };
+        limits.illiquid_symbols.extend([17, 42]);
+        let text = format_limits(&limits);
+        assert_eq!(RiskConfig::from_toml_str(&text).unwrap().limits, limits);
+    }
+
+    #[test]
+    fn format_limits_roundtrip_toggles() {
+        let mut limits = RiskLimits::default();
+        limits.checks.open_orders = false;
+        let text = format_limits(&limits);
+        assert_eq!(RiskConfig::from_toml_str(&text).unwrap().limits, limits);
+    }
+
+    #[test]
+    fn disabled_check_in_utilization() {
+        let mut s = Session::new(LayeredConfig::new());
+        output(&mut s, "set limit check_open_orders false");
+        assert!(output(&mut s, "show utilization").contains("open_orders 0 (check disabled)"));
+    }
+
+    #[test]
+    fn set_limit_and_show() {
+        let mut s = Session::new(LayeredConfig::new());
+        output(&mut s, "set limit max_open_orders 10");
+        assert_eq!(s.checker.limits().max_open_orders, 10);
+        assert!(output(&mut s, "show utilization").contains("open_orders 0/10"));
+        assert!(output(&mut s, "show utilization")
+            .contains("open_order_notional 0 / 1000000000 (0 bps)"));
+        assert!(s.execute("set limit max_open_orders x").is_err());
+    }
+
+    #[test]
+    fn history_and_rollback() {
+        let mut s = Session::new(LayeredConfig::new());
+        assert!(output(&mut s, "set limit max_order_size 5").ends_with("(version 1)"));
+        output(&mut s, "set limit max_order_size 1");
+        assert_eq!(
+            output(&mut s, "rollback 1"),
+            "[dry run] restored version 1 as version 3"
+        );
+        assert_eq!(s.checker.limits().max_order_size, 5);
+        let history = output(&mut s, "show history");
+        assert_eq!(history.lines().count(), 4);
+        assert!(history.lines().last().unwrap().ends_with("rollback_of=1"));
+        assert!(s.execute("rollback 99").is_err());
+        assert!(s.execute("rollback x").is_err());
+    }
+
+    #[test]
+    fn trip_reset_and_audit() {
+        let mut s = Session::new(LayeredConfig::new());
+        output(&mut s, "show limits");
+        output(&mut s, "trip");
+        assert!(s.checker.is_circuit_breaker_tripped());
+        assert!(output(&mut s, "show utilization").contains("TRIPPED"));
+        output(&mut s, "reset");
+        assert!(!s.checker.is_circuit_breaker_tripped());
+        // 読み取り専用コマンドは監査ログに残らない。
+        assert_eq!(output(&mut s, "export audit"), "2\ttrip\n4\treset");
+    }
+
+    #[test]
+    fn state_changes_are_labelled_dry_run() {
+        let mut s = Session::new(LayeredConfig::new());
+        for line in ["set limit max_order_size 5", "trip", "reset"] {
+            assert!(output(&mut s, line).starts_with(DRY_RUN), "{line}");
+        }
+        assert!(!output(&mut s, "show limits").starts_with(DRY_RUN));
+    }
+
+    #[test]
+    fn snapshot_state_is_shown() {
+        let state = CheckerState {
+            daily_pnl: -1_234,
+            open_order_count: 7,
+            circuit_breaker_tripped: true,
+            ..CheckerState::default()
+        };
+        let key = [9_u8; 32];
+        let sealed = SnapshotCodec::new()
+            .with_key(&key)
+            .seal(&state.to_bytes(), [1; 12]);
+        assert_eq!(open_snapshot(&sealed, Some(&key)), Ok(state.clone()));
+        assert!(open_snapshot(&sealed, None).is_err());
+        let plain = SnapshotCodec::new().seal(&[0; 3], [0; 12]);
+        assert!(open_snapshot(&plain, None).is_err());
+
+        let mut s = Session::new(LayeredConfig::new());
+        s.checker.restore_state(&state);
+        let shown = output(&mut s, "show utilization");
+        assert!(shown.contains("open_orders 7/500"), "{shown}");
+        assert!(shown.contains("daily_pnl -1234"), "{shown}");
+        assert!(shown.contains("TRIPPED"), "{shown}");
+    }
+
+    #[test]
+    fn snapshot_key_parsing() {
+        assert_eq!(parse_key(&"0a".repeat(32)), Ok([10; 32]));
+        assert!(parse_key("0a").is_err());
+        assert!(parse_key(&"zz".repeat(32)).is_err());
+        // 鍵の変数を設定しても設定の読み込みは失敗しない。
+        assert!(!SNAPSHOT_KEY_VAR.starts_with(ENV_PREFIX));
+        let mut config = LayeredConfig::new();
+        assert!(config
+            .apply_env([(SNAPSHOT_KEY_VAR, "0a".repeat(32))])
+            .is_ok());
+    }
+
+    #[test]
+    fn unknown_command_and_quit() {
+        let mut s = Session::new(LayeredConfig::new());
+        assert!(s.execute("launch rockets").is_err());
+        assert!(matches!(s.execute("quit"), Ok(Outcome::Quit)));
+    }
+}
@@ -0,0 +1,499 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 空売りの貸株料と貸株調達難銘柄（hard-to-borrow）の規制。
+//!
+//! [`BorrowFees`] は銘柄ごとの年率貸株料を保持し、売り建玉の調達コストを
+//! 日割りで日次損益に計上する（[`BorrowFees::accrue`]）。調達難の閾値を
+//! 設定すると、料率がそれを超える銘柄での新規の売り建て・売り増しを
+//! [`BorrowFees::check_short`] で拒否する。料率の登録が無い銘柄は一般担保
+//! （GC）扱いで、コストも規制も掛からない。
+//!
+//! 貸株料は口座に不利な側（切り上げ）へ丸める。
+//!
+//! [`LocateInventory`] は銘柄ごとの借株手当て（locate）の在庫を管理する。
+//! 売り建てになる注文の受理で売り建て分の在庫を消費し、取消で未約定分を
+//! 戻す。在庫は [`LocateInventory::replenish`] で補充し、銘柄ごとの使用状況は
+//! [`LocateInventory::usage`] で問い合わせる。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::PreTradeChecker;
+use crate::market::MarketContext;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// BorrowReject
+// ---------------------------------------------------------------------------
+
+/// 貸株規制の違反。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BorrowReject {
+    /// 貸株料が調達難の閾値を超える銘柄での新規の売り建て・売り増し。
+    HardToBorrow {
+        symbol_hash: u64,
+        rate_bps: u32,
+        threshold_bps: u32,
+    },
+    /// 売り建て分に足りる借株手当ての在庫が無い。
+    NoLocate {
+        symbol_hash: u64,
+        required: u64,
+        available: u64,
+    },
+}
+
+/// 売り注文 `order` のうち、約定すると売り建てになる数量。
+///
+/// 買い建玉 `net` の範囲内の返済分を除く。買い注文は 0。
+#[must_use]
+pub const fn short_quantity(order: &Order, net: i64) -> u64 {
+    match order.side {
+        Side::Bid => 0,
+        Side::Ask if net > 0 => order.quantity.saturating_sub(net.unsigned_abs()),
+        Side::Ask => order.quantity,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// BorrowFees
+// ---------------------------------------------------------------------------
+
+/// 銘柄ごとの貸株料。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowFees {
+    /// 銘柄 → 年率貸株料（bps）。
+    rates: BTreeMap<u64, u32>,
+    /// 調達難とみなす料率（bps、これを超えると調達難）。`None` は規制しない。
+    htb_threshold_bps: Option<u32>,
+    /// 日割りの分母（360 または 365）。
+    day_count: u32,
+}
+
+impl BorrowFees {
+    /// 日割りの分母 `day_count` で作成。
+    #[must_use]
+    pub const fn new(day_count: u32) -> Self {
+        Self {
+            rates: BTreeMap::new(),
+            htb_threshold_bps: None,
+            day_count,
+        }
+    }
+
+    /// 調達難の閾値を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_htb_threshold(mut self, threshold_bps: u32) -> Self {
+        self.htb_threshold_bps = Some(threshold_bps);
+        self
+    }
+
+    /// 銘柄の年率貸株料を設定する。
+    pub fn set_rate(&mut self, symbol_hash: u64, rate_bps: u32) {
+        self.rates.insert(symbol_hash, rate_bps);
+    }
+
+    /// 銘柄の料率を外す（一般担保扱いに戻す）。
+    pub fn remove(&mut self, symbol_hash: u64) -> Option<u32> {
+        self.rates.remove(&symbol_hash)
+    }
+
+    /// 銘柄の年率貸株料（bps）。
+    #[must_use]
+    pub fn rate(&self, symbol_hash: u64) -> Option<u32> {
+        self.rates.get(&symbol_hash).copied()
+    }
+
+    /// 調達難の閾値。
+    #[must_use]
+    pub const fn htb_threshold_bps(&self) -> Option<u32> {
+        self.htb_threshold_bps
+    }
+
+    /// 調達難銘柄か（閾値が設定され、料率がそれを超える）。
+    #[must_use]
+    pub fn is_hard_to_borrow(&self, symbol_hash: u64) -> bool {
+        matches!(
+            (self.rate(symbol_hash), self.htb_threshold_bps),
+            (Some(rate), Some(threshold)) if rate > threshold
+        )
+    }
+
+    /// ネット建玉 `net_quantity` を `price` で評価した `days` 日分の貸株料
+    /// （0 以上）。
+    ///
+    /// `|net| * |price| * rate_bps * days / (10000 * day_count)` を切り上げる。
+    /// 買い建玉、料率の無い銘柄、`day_count` が 0 の場合は 0。
+    #[must_use]
+    pub fn cost(&self, symbol_hash: u64, net_quantity: i64, price: i64, days: u32) -> i64 {
+        let Some(rate) = self.rate(symbol_hash) else {
+            return 0;
+        };
+        let denominator = 10_000_u128 * u128::from(self.day_count);
+        if net_quantity >= 0 || denominator == 0 {
+            return 0;
+        }
+        let numerator = u128::from(net_quantity.unsigned_abs())
+            .saturating_mul(u128::from(price.unsigned_abs()))
+            .saturating_mul(u128::from(rate) * u128::from(days));
+        numerator.div_ceil(denominator).min(i64::MAX as u128) as i64
+    }
+
+    /// `positions` の売り建玉の `days` 日分の貸株料を `checker` の日次損益から
+    /// 差し引き、その合計を返す。
+    ///
+    /// 各建玉は `market` の参照価格で評価する。参照価格の無い銘柄は計上しない。
+    pub fn accrue<M: MarketContext + ?Sized>(
+        &self,
+        checker: &mut PreTradeChecker,
+        positions: &[Position],
+        market: &M,
+        days: u32,
+    ) -> i64 {
+        let total = positions.iter().fold(0_i64, |acc, p| {
+            let cost = market.reference_price(p.symbol_hash).map_or(0, |price| {
+                self.cost(p.symbol_hash, p.net_quantity, price, days)
+            });
+            acc.saturating_add(cost)
+        });
+        checker.update_daily_pnl(total.saturating_neg());
+        total
+    }
+
+    /// 新規の売り建て・売り増しを調達難銘柄で拒否する。
+    ///
+    /// 売り注文のうち、約定後のネット建玉が負になるもの（買い建玉の範囲内の
+    /// 返済売りを除く）を対象とする。ポジションが無い場合はフラットとみなす。
+    ///
+    /// # Errors
+    ///
+    /// 調達難銘柄での売り建て・売り増しは [`BorrowReject::HardToBorrow`]。
+    pub fn check_short(
+        &self,
+        order: &Order,
+        symbol_hash: u64,
+        position: Option<&Position>,
+    ) -> Result<(), BorrowReject> {
+        if order.side != Side::Ask {
+            return Ok(());
+        }
+        let opens_short = short_quantity(order, position.map_or(0, |p| p.net_quantity)) > 0;
+        match (self.rate(symbol_hash), self.htb_threshold_bps) {
+            (Some(rate_bps), Some(threshold_bps)) if opens_short && rate_bps > threshold_bps => {
+                Err(BorrowReject::HardToBorrow {
+                    symbol_hash,
+                    rate_bps,
+                    threshold_bps,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for BorrowFees {
+    fn default() -> Self {
+        Self::new(360)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LocateInventory
+// ---------------------------------------------------------------------------
+
+/// 1 銘柄の借株手当ての使用状況。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LocateUsage {
+    /// 補充された在庫の累計。
+    pub granted: u64,
+    /// 受理済みの売り建てで消費した数量（取消で戻した分を除く）。
+    pub consumed: u64,
+}
+
+impl LocateUsage {
+    /// 残りの在庫。
+    #[must_use]
+    pub const fn available(&self) -> u64 {
+        self.granted.saturating_sub(self.consumed)
+    }
+
+    /// 使用率（bps、10000 = 在庫をすべて消費）。在庫が 0 の場合は `None`。
+    #[must_use]
+    pub const fn utilization_bps(&self) -> Option<u32> {
+        if self.granted == 0 {
+            return None;
+        }
+        let bps = (self.consumed as u128 * 10_000) / self.granted as u128;
+        Some(if bps > u32::MAX as u128 {
+            u32::MAX
+        } else {
+            bps as u32
+        })
+    }
+}
+
+/// 借株手当ての在庫。受理で消費し、取消で戻す。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocateInventory {
+    /// 銘柄 → 使用状況。
+    symbols: BTreeMap<u64, LocateUsage>,
+    /// 注文 ID → （銘柄, 未約定の消費数量）。
+    reservations: BTreeMap<u64, (u64, u64)>,
+}
+
+impl LocateInventory {
+    /// 空の在庫を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            symbols: BTreeMap::new(),
+            reservations: BTreeMap::new(),
+        }
+    }
+
+    /// 銘柄の在庫を `quantity` 補充する。
+    pub fn replenish(&mut self, symbol_hash: u64, quantity: u64) {
+        let usage = self.symbols.entry(symbol_hash).or_default();
+        usage.granted = usage.granted.saturating_add(quantity);
+    }
+
+    /// 銘柄の使用状況。一度も補充されていない銘柄は `None`。
+    #[must_use]
+    pub fn usage(&self, symbol_hash: u64) -> Option<LocateUsage> {
+        self.symbols.get(&symbol_hash).copied()
+    }
+
+    /// 銘柄の残りの在庫（未補充の銘柄は 0）。
+    #[must_use]
+    pub fn available(&self, symbol_hash: u64) -> u64 {
+        self.usage(symbol_hash).map_or(0, |u| u.available())
+    }
+
+    /// 注文 ID の未約定の消費数量。
+    #[must_use]
+    pub fn reserved(&self, order_id: u64) -> Option<u64> {
+        self.reservations
+            .get(&order_id)
+            .map(|&(_, quantity)| quantity)
+    }
+
+    /// `order` の売り建て分に足りる在庫があるかを消費せずに確かめる。
+    ///
+    /// # Errors
+    ///
+    /// 在庫が足りない場合は [`BorrowReject::NoLocate`]。
+    pub fn check(
+        &self,
+        order: &Order,
+        symbol_hash: u64,
+        position: Option<&Position>,
+    ) -> Result<(), BorrowReject> {
+        let required = short_quantity(order, position.map_or(0, |p| p.net_quantity));
+        let available = self.available(symbol_hash);
+        if required > available {
+            return Err(BorrowReject::NoLocate {
+                symbol_hash,
+                required,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// 受理した `order` の売り建て分の在庫を消費する。
+    ///
+    /// 売り建てにならない注文は何もしない。同じ注文 ID の既存の消費は
+    /// 先に戻してから消費し直す（訂正）。
+    ///
+    /// # Errors
+    ///
+    /// 在庫が足りない場合は [`BorrowReject::NoLocate`]（消費しない）。
+    pub fn on_order_accepted(
+        &mut self,
+        order: &Order,
+        symbol_hash: u64,
+        position: Option<&Position>,
+    ) -> Result<(), BorrowReject> {
+        let previous = self.on_order_cancelled(order.id.0);
+        if let Err(reject) = self.check(order, symbol_hash, position) {
+            if let Some((symbol, quantity)) = previous {
+                self.consume(order.id.0, symbol, quantity);
+            }
+            return Err(reject);
+        }
+        let required = short_quantity(order, position.map_or(0, |p| p.net_quantity));
+        if required > 0 {
+            self.consume(order.id.0, symbol_hash, required);
+        }
+        Ok(())
+    }
+
+    /// 注文 `order_id` の `quantity` 約定を記録する。約定分の消費は確定し、
+    /// 取消で戻らない。
+    pub fn on_order_filled(&mut self, order_id: u64, quantity: u64) {
+        if let Some(entry) = self.reservations.get_mut(&order_id) {
+            entry.1 = entry.1.saturating_sub(quantity);
+            if entry.1 == 0 {
+                self.reservations.remove(&order_id);
+            }
+        }
+    }
+
+    /// 注文 `order_id` の取消: 未約定の消費を在庫に戻し、（銘柄, 戻した数量）を
+    /// 返す。
+    pub fn on_order_cancelled(&mut self, order_id: u64) -> Option<(u64, u64)> {
+        let (symbol_hash, quantity) = self.reservations.remove(&order_id)?;
+        if let Some(usage) = self.symbols.get_mut(&symbol_hash) {
+            usage.consumed = usage.consumed.saturating_sub(quantity);
+        }
+        Some((symbol_hash, quantity))
+    }
+
+    fn consume(&mut self, order_id: u64, symbol_hash: u64, quantity: u64) {
+        let usage = self.symbols.entry(symbol_hash).or_default();
+        usage.consumed = usage.consumed.saturating_add(quantity);
+        self.reservations.insert(order_id, (symbol_hash, quantity));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::market::{MarketSnapshot, SymbolMarket};
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+    use alice_ledger::OrderId;
+
+    fn position(symbol_hash: u64, net_quantity: i64) -> Position {
+        PositionBuilder::new(symbol_hash, net_quantity)
+            .avg_entry_price(0)
+            .build()
+    }
+
+    fn sell(quantity: u64) -> Order {
+        OrderBuilder::ask(100, quantity).build()
+    }
+
+    fn fees() -> BorrowFees {
+        let mut fees = BorrowFees::new(360).with_htb_threshold(1_000);
+        fees.set_rate(1, 36);
+        fees.set_rate(2, 3_600);
+        fees
+    }
+
+    #[test]
+    fn short_cost_rounds_up() {
+        let fees = fees();
+        // 1_000 株 × 100 × 36% × 1 / 360 = 100。
+        assert_eq!(fees.cost(2, -1_000, 100, 1), 100);
+        assert_eq!(fees.cost(2, -1_000, 100, 3), 300);
+        // 1_000 株 × 90 × 0.36% / 360 = 0.9 → 1（切り上げ）。
+        assert_eq!(fees.cost(1, -1_000, 90, 1), 1);
+        assert_eq!(fees.cost(2, 1_000, 100, 1), 0);
+        assert_eq!(fees.cost(3, -1_000, 100, 1), 0);
+    }
+
+    #[test]
+    fn accrue_into_daily_pnl() {
+        let fees = fees();
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let mut market = MarketSnapshot::new();
+        for symbol in [1, 2] {
+            market.insert(
+                symbol,
+                SymbolMarket {
+                    reference_price: Some(100),
+                    ..SymbolMarket::default()
+                },
+            );
+        }
+        checker.update_daily_pnl(500);
+        let held = [position(1, -1_000), position(2, -1_000), position(3, -5)];
+        assert_eq!(fees.accrue(&mut checker, &held, &market, 1), 101);
+        assert_eq!(checker.daily_pnl(), 399);
+    }
+
+    #[test]
+    fn locates_consumed_and_released() {
+        let mut inventory = LocateInventory::new();
+        assert_eq!(
+            inventory.check(&sell(10), 5, None),
+            Err(BorrowReject::NoLocate {
+                symbol_hash: 5,
+                required: 10,
+                available: 0,
+            })
+        );
+        inventory.replenish(5, 100);
+        assert!(inventory.on_order_accepted(&sell(60), 5, None).is_ok());
+        assert_eq!(inventory.available(5), 40);
+        // 買い建玉 30 の返済分は消費しない。
+        let mut second = sell(50);
+        second.id = OrderId(2);
+        assert!(inventory
+            .on_order_accepted(&second, 5, Some(&position(5, 30)))
+            .is_ok());
+        assert_eq!(inventory.reserved(2), Some(20));
+        assert_eq!(inventory.available(5), 20);
+        assert_eq!(
+            inventory.usage(5).and_then(|u| u.utilization_bps()),
+            Some(8_000)
+        );
+
+        // 約定分は確定し、取消では未約定分だけ戻る。
+        inventory.on_order_filled(1, 45);
+        assert_eq!(inventory.on_order_cancelled(1), Some((5, 15)));
+        assert_eq!(inventory.on_order_cancelled(1), None);
+        assert_eq!(inventory.available(5), 35);
+        assert_eq!(inventory.usage(6), None);
+    }
+
+    #[test]
+    fn rejected_amend_keeps_reservation() {
+        let mut inventory = LocateInventory::new();
+        inventory.replenish(5, 50);
+        assert!(inventory.on_order_accepted(&sell(40), 5, None).is_ok());
+        assert!(matches!(
+            inventory.on_order_accepted(&sell(60), 5, None),
+            Err(BorrowReject::NoLocate { available: 50, .. })
+        ));
+        assert_eq!(inventory.reserved(1), Some(40));
+        assert!(inventory.on_order_accepted(&sell(50), 5, None).is_ok());
+        assert_eq!(inventory.available(5), 0);
+    }
+
+    #[test]
+    fn hard_to_borrow_blocks_new_shorts() {
+        let fees = fees();
+        assert!(fees.is_hard_to_borrow(2));
+        assert!(!fees.is_hard_to_borrow(1));
+        assert_eq!(
+            fees.check_short(&sell(10), 2, None),
+            Err(BorrowReject::HardToBorrow {
+                symbol_hash: 2,
+                rate_bps: 3_600,
+                threshold_bps: 1_000,
+            })
+        );
+        // 買い建玉の範囲内の返済売りは可、超える分は売り建てになる。
+        assert!(fees
+            .check_short(&sell(10), 2, Some(&position(2, 10)))
+            .is_ok());
+        assert!(fees
+            .check_short(&sell(11), 2, Some(&position(2, 10)))
+            .is_err());
+        assert!(fees.check_short(&sell(10), 1, None).is_ok());
+        assert!(BorrowFees::default()
+            .check_short(&sell(10), 2, None)
+            .is_ok());
+    }
+}
@@ -10,9 +10,21 @@
 //! violation; if all checks pass, `Ok(())` is returned and the order may proceed
 //! to the matching engine.
 
-use alice_ledger::{Order, Position, Side};
+use alloc::collections::BTreeMap;
 
-use crate::limit::RiskLimits;
+use alice_ledger::{Order, OrderType, Position, Side, TimeInForce};
+
+use crate::fastpath::ThresholdTable;
+use crate::greeks::{GreeksExposure, GreeksReject};
+use crate::instrument::{bucket_exposure, ExpiryRestriction, Instrument, MaturityBucket, DAY_NS};
+use crate::limit::{EnabledChecks, LimitHistoryError, LimitVersion, RiskLimits, SymbolCaps};
+use crate::market::{Bbo, MarketContext};
+use crate::orders::{OpenOrders, StaleAction, StaleOrder, WorkingOrder};
+use crate::quarantine::QuarantineMode;
+use crate::snapshot::CheckerState;
+use crate::throttle::{RateWindow, WindowCounter};
+
+extern crate alloc;
 
 // ---------------------------------------------------------------------------
 // RiskReject
@@ -29,6 +41,9 @@ pub enum RiskReject {
         after: i64,
         /// Configured maximum absolute position in lots.
         limit: u64,
+        /// Largest quantity of this order that would have kept the position
+        /// within the limit (0 if none).
+        max_allowed: u64,
     },
     /// Order quantity exceeds the per-order size limit.
     OrderSizeTooLarge {
@@ -36,6 +51,8 @@ pub enum RiskReject {
         size: u64,
         /// Configured maximum order size in lots.
         limit: u64,
+        /// Largest quantity that would have passed the size check.
+        max_allowed: u64,
     },
     /// Notional value of the order exceeds the configured ceiling.
     NotionalExceeded {
@@ -43,6 +60,9 @@ pub enum RiskReject {
         notional: i64,
         /// Configured maximum notional in ticks.
         limit: i64,
+        /// Largest quantity at this order's price that would have stayed
+        /// within the notional limit (0 if none).
+        max_allowed: u64,
     },
     /// Number of open orders has reached the configured maximum.
     MaxOpenOrdersReached {
@@ -53,13 +73,639 @@ pub enum RiskReject {
     },
     /// Daily loss has reached or exceeded the configured kill-switch threshold.
     DailyLossLimitHit {
-        /// Current daily P&L (negative indicates a loss).
+        /// Daily P&L measured on the checker's [`LossBasis`] (negative
+        /// indicates a loss).
         loss: i64,
         /// Configured maximum daily loss (negative value).
         limit: i64,
+        /// Realized component of the daily P&L.
+        realized: i64,
+        /// Unrealized component of the daily P&L.
+        unrealized: i64,
+    },
+    /// The order's symbol has lost at least its per-symbol loss limit
+    /// today (realized + unrealized).
+    SymbolLossLimitHit {
+        /// Symbol of the order.
+        symbol_hash: u64,
+        /// Current daily P&L of the symbol (negative indicates a loss).
+        loss: i64,
+        /// Configured maximum loss for the symbol (negative value).
+        limit: i64,
+    },
+    /// Account equity has fallen from its high-water mark by at least the
+    /// trailing drawdown limit.
+    TrailingDrawdownHit {
+        /// Current drawdown from the high-water mark in ticks.
+        drawdown: i64,
+        /// Configured maximum drawdown in ticks.
+        limit: i64,
+    },
+    /// Order quantity exceeds the allowed share of the symbol's average
+    /// daily volume.
+    ParticipationExceeded {
+        /// Order quantity in lots.
+        quantity: u64,
+        /// Average daily volume of the symbol in lots.
+        adv: u64,
+        /// Configured maximum participation in basis points of ADV.
+        limit_bps: u32,
+        /// Largest quantity within the participation limit.
+        max_allowed: u64,
+    },
+    /// Position after the order would exceed the limit derived from the
+    /// symbol's average daily volume or displayed book depth.
+    LiquidityLimitBreached {
+        /// Symbol of the order.
+        symbol_hash: u64,
+        /// Net position after the order.
+        after: i64,
+        /// Liquidity-derived position limit in lots.
+        limit: u64,
+        /// Largest quantity that would have stayed within the limit.
+        max_allowed: u64,
+    },
+    /// The symbol's contribution to portfolio volatility would exceed its
+    /// share of the volatility budget.
+    RiskContributionExceeded {
+        /// Symbol of the order.
+        symbol_hash: u64,
+        /// Contribution to portfolio volatility after the order, in ticks.
+        contribution: i64,
+        /// Per-symbol share of the volatility budget, in ticks.
+        limit: i64,
+    },
+    /// The underlying's net Greeks after the order would exceed a limit.
+    GreeksLimitExceeded {
+        /// Underlying the Greeks are aggregated on.
+        underlying_hash: u64,
+        /// Projected delta, gamma and vega after the order fills.
+        projected: GreeksExposure,
+        /// First Greek over its limit.
+        breach: GreeksReject,
+    },
+    /// Worst-case gross exposure, assuming every working order fills at its
+    /// limit price, would exceed the configured limit.
+    ProjectedExposureExceeded {
+        /// Projected gross exposure in ticks.
+        gross: i64,
+        /// Configured maximum projected gross exposure in ticks.
+        limit: i64,
+    },
+    /// Worst-case maintenance margin, assuming every working order fills at
+    /// its limit price, would exceed the account equity.
+    ProjectedMarginShortfall {
+        /// Account equity in ticks.
+        equity: i64,
+        /// Projected maintenance margin in ticks.
+        maintenance_margin: i64,
+    },
+    /// A symbol the check needs to value has no reference price.
+    MissingReferencePrice {
+        /// Symbol without a reference price.
+        symbol_hash: u64,
+    },
+    /// The account has been switched to reduce-only by automatic de-risking
+    /// and the order would not reduce the position.
+    DrawdownReduceOnly {
+        /// Drawdown from the high-water mark in basis points.
+        drawdown_bps: u32,
+    },
+    /// Account equity has fallen from the day's peak by at least the
+    /// give-back limit.
+    GiveBackLimitHit {
+        /// Equity given back since the day's peak in ticks.
+        give_back: i64,
+        /// Configured maximum give-back in ticks.
+        limit: i64,
+    },
+    /// Open orders resting in the order's symbol have reached the per-symbol
+    /// maximum.
+    SymbolOpenOrdersReached {
+        /// Symbol of the order (`Position::symbol_hash`).
+        symbol_hash: u64,
+        /// Current open order count in the symbol.
+        count: u32,
+        /// Maximum open orders for the symbol.
+        limit: u32,
+    },
+    /// Combined notional of working orders plus this order would exceed the
+    /// open-order notional ceiling.
+    OpenOrderNotionalExceeded {
+        /// Notional of the orders already working, in ticks.
+        open: i64,
+        /// Notional of this order, in ticks.
+        notional: i64,
+        /// Configured maximum open-order notional in ticks.
+        limit: i64,
+        /// Largest quantity at this order's price that would have stayed
+        /// within the remaining open-order notional (0 if none).
+        max_allowed: u64,
+    },
+    /// Limit price is too far through the opposite side of the best
+    /// bid/offer to be a plausible order.
+    PriceThroughBbo {
+        /// Limit price of the order.
+        price: i64,
+        /// Opposite-side quote the order crosses (ask for a buy, bid for a
+        /// sell).
+        touch: i64,
+        /// Distance through `touch`, in basis points of `touch`.
+        through_bps: u32,
+        /// Configured maximum distance in basis points.
+        limit_bps: u32,
+    },
+    /// The contract is close to expiry and the order would open a new
+    /// position (or, in the final session, not reduce one).
+    ExpiryRestricted {
+        /// Symbol of the contract.
+        symbol_hash: u64,
+        /// Restriction in force at the order's timestamp.
+        restriction: ExpiryRestriction,
+        /// Contract expiry (ns).
+        expiry_ns: u64,
+    },
+    /// Position in a physically delivered contract would exceed the tighter
+    /// spot-month limit that applies ahead of first notice day.
+    SpotMonthLimitBreached {
+        /// Symbol of the contract.
+        symbol_hash: u64,
+        /// Current net position.
+        current: i64,
+        /// Net position if the order were filled.
+        after: i64,
+        /// Configured spot-month limit in lots.
+        limit: u64,
+        /// Largest quantity of this order that would have stayed within the
+        /// spot-month limit (0 if none).
+        max_allowed: u64,
+    },
+    /// Net position of the product in one maturity bucket would exceed that
+    /// bucket's limit.
+    MaturityBucketLimitBreached {
+        /// Product of the contract.
+        product_hash: u64,
+        /// Bucket the contract falls in at the order's timestamp.
+        bucket: MaturityBucket,
+        /// Net bucket position before the order.
+        current: i64,
+        /// Net bucket position if the order were filled.
+        after: i64,
+        /// Configured limit for the bucket in lots.
+        limit: u64,
+    },
+    /// A post-only order would cross the best bid/offer on arrival and take
+    /// liquidity.
+    PostOnlyWouldCross {
+        /// Limit price of the order.
+        price: i64,
+        /// Opposite-side quote it would trade against.
+        touch: i64,
+    },
+    /// Same symbol, side, price and quantity as an order accepted moments
+    /// earlier — most likely a resubmission.
+    DuplicateOrder {
+        /// Id of the earlier accepted order.
+        previous_id: u64,
+        /// Time since the earlier order was accepted (ns).
+        age_ns: u64,
+    },
+    /// A client order id still held for idempotent retries was reused for
+    /// an order with a different side, price, quantity or symbol.
+    ConflictingRetry {
+        /// The reused client order id.
+        client_order_id: u64,
+    },
+    /// Iceberg display quantity is zero, larger than the order, or below the
+    /// venue minimum.
+    InvalidIceberg {
+        /// Requested display quantity.
+        display: u64,
+        /// Total order quantity (displayed plus hidden).
+        quantity: u64,
+        /// Configured minimum display quantity.
+        min_display: u64,
+    },
+    /// Too many immediate-or-cancel / fill-or-kill orders in the current
+    /// throughput window.
+    NonRestingRateExceeded {
+        /// Non-resting orders already accepted in the window.
+        count: u32,
+        /// Configured maximum per window.
+        limit: u32,
+    },
+    /// Too many order messages in the current message-rate window.
+    MessageRateExceeded {
+        /// Messages already sent in the window.
+        count: u32,
+        /// Configured maximum per window.
+        limit: u32,
+    },
+    /// The weighted message budget (new + amend + cancel) of the current
+    /// window would be exceeded by this message.
+    MessageBudgetExceeded {
+        /// Weight already used in the window.
+        used: u32,
+        /// Weight of the rejected message.
+        weight: u32,
+        /// Configured budget per window.
+        limit: u32,
+    },
+    /// The gateway session the message arrived on has been choked off;
+    /// the account's other sessions are unaffected.
+    SessionChoked {
+        /// Gateway session id.
+        session_id: u64,
+    },
+    /// The account is in the penalty box after too many rejects in a short
+    /// window; all orders are blocked until the cooldown ends.
+    PenaltyBox {
+        /// Rejects counted in the window that triggered the cooldown.
+        rejects: u32,
+        /// End of the cooldown (ns).
+        until_ns: u64,
+    },
+    /// Too many cancel requests in the current cancel-rate window.
+    CancelRateExceeded {
+        /// Cancels already sent in the window.
+        count: u32,
+        /// Configured maximum per window.
+        limit: u32,
+    },
+    /// The account exceeded its cancel-rate budget and new orders are
+    /// blocked until the penalty ends.
+    CancelRatePenalty {
+        /// End of the penalty (ns).
+        until_ns: u64,
+    },
+    /// Reference-price / mark updates for the symbol are older than the
+    /// staleness threshold, so price-based checks cannot be trusted.
+    StaleMarketData {
+        /// Symbol of the order.
+        symbol_hash: u64,
+        /// Age of the last update (ns), or `None` if none was received.
+        age_ns: Option<u64>,
+        /// Configured maximum age (ns).
+        max_age_ns: u64,
+    },
+    /// The symbol is halted by its circuit breaker.
+    SymbolHalted {
+        /// Symbol of the order.
+        symbol_hash: u64,
+        /// End of the halt (ns), or `None` until it is resumed manually.
+        until_ns: Option<u64>,
+    },
+    /// Trading is halted market-wide by a level of the market-wide
+    /// circuit breaker.
+    MarketWideHalt {
+        /// Triggered level (1-based).
+        level: u8,
+        /// End of the halt (ns).
+        until_ns: u64,
+    },
+    /// An IOC / FOK order was submitted while the symbol is in a
+    /// volatility-interruption auction, which accepts only resting orders.
+    NonRestingInAuction,
+    /// A limit price is further from the symbol's last price than the
+    /// collar tightened by a propagated halt allows.
+    PriceOutsideCollar {
+        /// Limit price of the order.
+        price: i64,
+        /// Last price the collar is anchored at.
+        reference: i64,
+        /// Distance from `reference`, in basis points of `reference`.
+        deviation_bps: u32,
+        /// Tightened collar (bps).
+        limit_bps: u32,
+    },
+    /// The order's strategy is quarantined and the order is not permitted
+    /// under the quarantine mode.
+    StrategyQuarantined {
+        /// Strategy tag of the order.
+        algo_id: u64,
+        /// Restriction in force for the strategy.
+        mode: QuarantineMode,
     },
     /// A circuit breaker has been manually tripped; all orders are blocked.
     CircuitBreakerTripped,
+    /// An intermediate notional, position or margin computation overflowed
+    /// `i64`.
+    ///
+    /// Only produced in [`ArithmeticMode::Checked`].
+    ArithmeticOverflow,
+}
+
+impl RiskReject {
+    /// Name of the [`RiskLimits`] field whose threshold produced this reject.
+    ///
+    /// Returns `None` for rejects not driven by a configured limit.
+    #[must_use]
+    pub const fn limit_field(&self) -> Option<&'static str> {
+        match self {
+            Self::PositionLimitBreached { .. } => Some("max_position"),
+            Self::OrderSizeTooLarge { .. } => Some("max_order_size"),
+            Self::NotionalExceeded { .. } => Some("max_notional"),
+            Self::MaxOpenOrdersReached { .. } => Some("max_open_orders"),
+            Self::DailyLossLimitHit { .. } => Some("max_daily_loss"),
+            Self::NonRestingRateExceeded { .. } => Some("max_non_resting_orders"),
+            Self::OpenOrderNotionalExceeded { .. } => Some("max_open_order_notional"),
+            Self::SymbolOpenOrdersReached { .. } => Some("max_open_orders_per_symbol"),
+            Self::PriceThroughBbo { .. } => Some("max_through_bbo_bps"),
+            Self::InvalidIceberg { .. } => Some("min_display_quantity"),
+            Self::ExpiryRestricted { .. } => Some("expiry_no_open_days"),
+            Self::SpotMonthLimitBreached { .. } => Some("spot_month_position_limit"),
+            Self::MaturityBucketLimitBreached { bucket, .. } => Some(match bucket {
+                MaturityBucket::Front => "max_front_month_position",
+                MaturityBucket::Back => "max_back_month_position",
+                MaturityBucket::Long => "max_long_dated_position",
+            }),
+            Self::CircuitBreakerTripped
+            | Self::ArithmeticOverflow
+            | Self::PostOnlyWouldCross { .. }
+            | Self::DuplicateOrder { .. }
+            | Self::ConflictingRetry { .. }
+            | Self::MessageRateExceeded { .. }
+            | Self::MessageBudgetExceeded { .. }
+            | Self::SessionChoked { .. }
+            | Self::StaleMarketData { .. }
+            | Self::SymbolHalted { .. }
+            | Self::MarketWideHalt { .. }
+            | Self::NonRestingInAuction
+            | Self::SymbolLossLimitHit { .. }
+            | Self::TrailingDrawdownHit { .. }
+            | Self::GiveBackLimitHit { .. }
+            | Self::DrawdownReduceOnly { .. }
+            | Self::ParticipationExceeded { .. }
+            | Self::LiquidityLimitBreached { .. }
+            | Self::RiskContributionExceeded { .. }
+            | Self::GreeksLimitExceeded { .. }
+            | Self::ProjectedExposureExceeded { .. }
+            | Self::ProjectedMarginShortfall { .. }
+            | Self::MissingReferencePrice { .. }
+            | Self::PriceOutsideCollar { .. }
+            | Self::PenaltyBox { .. }
+            | Self::CancelRateExceeded { .. }
+            | Self::CancelRatePenalty { .. }
+            | Self::StrategyQuarantined { .. } => None,
+        }
+    }
+}
+
+/// Return `true` if `order` can rest on the book.
+///
+/// Immediate-or-cancel and fill-or-kill orders either execute or are
+/// cancelled on arrival, so they never occupy an open-order slot.
+#[inline(always)]
+#[must_use]
+pub const fn rests_on_book(order: &Order) -> bool {
+    !matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK)
+}
+
+/// Largest quantity on `side` that keeps `|current ± quantity|` within `limit`.
+pub(crate) fn position_headroom(current: i64, side: Side, limit: u64) -> u64 {
+    let room = match side {
+        Side::Bid => i128::from(limit) - i128::from(current),
+        Side::Ask => i128::from(limit) + i128::from(current),
+    };
+    room.clamp(0, u64::MAX as i128) as u64
+}
+
+/// Largest quantity whose absolute notional at `price` does not exceed
+/// `limit`.
+pub(crate) fn notional_headroom(price: i64, limit: i64) -> u64 {
+    if price == 0 || limit < 0 {
+        return 0;
+    }
+    limit as u64 / price.unsigned_abs()
+}
+
+// ---------------------------------------------------------------------------
+// ArithmeticMode
+// ---------------------------------------------------------------------------
+
+/// The caps of `limits` for `position`'s symbol
+/// ([`RiskLimits::symbol_caps`]), or those of `limits` itself when the
+/// symbol is unknown.
+#[inline]
+pub(crate) fn symbol_caps(limits: &RiskLimits, position: Option<&Position>) -> SymbolCaps {
+    position.map_or_else(
+        || SymbolCaps::from(limits),
+        |p| limits.symbol_caps(p.symbol_hash),
+    )
+}
+
+/// How overflow in notional and position arithmetic is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArithmeticMode {
+    /// Clamp intermediate results to the `i64` range.
+    ///
+    /// Extreme values are silently capped, so a mis-scaled price still gets
+    /// compared against the limit.
+    #[default]
+    Saturating,
+    /// Reject the order with [`RiskReject::ArithmeticOverflow`] whenever an
+    /// intermediate result does not fit in `i64`.
+    Checked,
+}
+
+impl ArithmeticMode {
+    /// Narrow a wide intermediate to `i64`: clamped when saturating,
+    /// rejected when checked.
+    ///
+    /// # Errors
+    ///
+    /// [`RiskReject::ArithmeticOverflow`] in checked mode when `value` does
+    /// not fit in `i64`.
+    #[inline]
+    pub fn narrow(self, value: i128) -> Result<i64, RiskReject> {
+        match self {
+            Self::Saturating => Ok(value.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64),
+            Self::Checked => i64::try_from(value).map_err(|_| RiskReject::ArithmeticOverflow),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LossBasis
+// ---------------------------------------------------------------------------
+
+/// Which components of the daily P&L the daily loss limit is enforced on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LossBasis {
+    /// Realized plus unrealized P&L.
+    #[default]
+    Combined,
+    /// Realized P&L only; open positions marked at a loss do not count.
+    Realized,
+    /// Unrealized P&L only.
+    Unrealized,
+}
+
+impl LossBasis {
+    /// The P&L this basis measures.
+    #[inline(always)]
+    #[must_use]
+    pub const fn pnl(self, realized: i64, unrealized: i64) -> i64 {
+        match self {
+            Self::Combined => realized.saturating_add(unrealized),
+            Self::Realized => realized,
+            Self::Unrealized => unrealized,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SizingMode
+// ---------------------------------------------------------------------------
+
+/// What [`PreTradeChecker::check_or_clamp`] does with an oversized order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizingMode {
+    /// Reject orders that breach a size, position or notional limit.
+    #[default]
+    Reject,
+    /// Reduce the order quantity to the largest amount that fits the size,
+    /// position and notional limits, rejecting only if nothing fits.
+    Clamp,
+}
+
+/// Successful outcome of [`PreTradeChecker::check_or_clamp`].
+#[derive(Debug, Clone)]
+pub enum CheckOutcome {
+    /// The order passed unchanged.
+    Accepted,
+    /// The order passes only at a reduced quantity.
+    Clamped {
+        /// Copy of the order with `quantity` reduced to fit.
+        order: Order,
+        /// Quantity originally requested.
+        requested: u64,
+    },
+}
+
+impl CheckOutcome {
+    /// Return `true` if the order was modified to fit.
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_adjusted(&self) -> bool {
+        matches!(self, Self::Clamped { .. })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Utilization
+// ---------------------------------------------------------------------------
+
+/// Point-in-time usage of the stateful limits, for operator reporting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utilization {
+    /// Current open order count.
+    pub open_orders: u32,
+    /// Configured maximum open orders.
+    pub max_open_orders: u32,
+    /// Combined notional of tracked working orders, in ticks.
+    pub open_order_notional: i64,
+    /// Configured maximum open-order notional.
+    pub max_open_order_notional: i64,
+    /// Daily P&L measured on the checker's [`LossBasis`] (negative
+    /// indicates a loss).
+    pub daily_pnl: i64,
+    /// Realized component of the daily P&L.
+    pub realized_pnl: i64,
+    /// Unrealized component of the daily P&L.
+    pub unrealized_pnl: i64,
+    /// Configured maximum daily loss (negative value).
+    pub max_daily_loss: i64,
+    /// Whether the circuit breaker is tripped.
+    pub circuit_breaker_tripped: bool,
+    /// Which checks are enforced; disabled limits report zero usage.
+    pub checks: EnabledChecks,
+}
+
+impl Utilization {
+    /// Open order usage in basis points of the limit (10000 = at limit).
+    ///
+    /// Reports 0 when the open order check is disabled.
+    #[must_use]
+    pub fn open_orders_bps(&self) -> u32 {
+        if !self.checks.open_orders {
+            return 0;
+        }
+        ratio_bps(self.open_orders as i128, self.max_open_orders as i128)
+    }
+
+    /// Open-order notional usage in basis points of the limit.
+    ///
+    /// Reports 0 when the open-order notional check is disabled or the
+    /// working notional is not positive.
+    #[must_use]
+    pub fn open_order_notional_bps(&self) -> u32 {
+        if !self.checks.open_order_notional || self.open_order_notional <= 0 {
+            return 0;
+        }
+        ratio_bps(
+            self.open_order_notional as i128,
+            self.max_open_order_notional as i128,
+        )
+    }
+
+    /// Daily loss usage in basis points of the kill-switch threshold.
+    ///
+    /// A flat or profitable day, or a disabled daily loss check, reports 0.
+    #[must_use]
+    pub fn daily_loss_bps(&self) -> u32 {
+        if !self.checks.daily_loss || self.daily_pnl >= 0 {
+            return 0;
+        }
+        ratio_bps(-(self.daily_pnl as i128), -(self.max_daily_loss as i128))
+    }
+}
+
+/// `used / limit` in basis points, saturating at `u32::MAX`.
+///
+/// Any usage against a zero (or negative) limit counts as fully used.
+fn ratio_bps(used: i128, limit: i128) -> u32 {
+    if limit <= 0 {
+        return if used > 0 { 10_000 } else { 0 };
+    }
+    (used.saturating_mul(10_000) / limit).min(u32::MAX as i128) as u32
+}
+
+// ---------------------------------------------------------------------------
+// DecisionRecord
+// ---------------------------------------------------------------------------
+
+/// Regulatory fields captured with a decision (`MiFID II` RTS 6 audit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecisionTags {
+    /// Identifier of the algorithm that generated the order, if any.
+    pub algo_id: Option<u64>,
+    /// `true` when the order arrived through direct electronic access.
+    pub dea: bool,
+}
+
+/// Auditable outcome of a single pre-trade check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionRecord {
+    /// Identifier of the checked order.
+    pub order_id: u64,
+    /// Order timestamp in nanoseconds.
+    pub timestamp_ns: u64,
+    /// Version of the limits the order was evaluated against.
+    pub limit_version: u64,
+    /// Accept (`Ok`) or the first reject that fired.
+    pub result: Result<(), RiskReject>,
+    /// Regulatory fields supplied by the caller.
+    pub tags: DecisionTags,
+}
+
+impl DecisionRecord {
+    /// Return `true` if the order was accepted.
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_accepted(&self) -> bool {
+        self.result.is_ok()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -72,12 +718,41 @@ pub enum RiskReject {
 /// and evaluates each incoming order against the configured [`RiskLimits`].
 pub struct PreTradeChecker {
     limits: RiskLimits,
-    /// Accumulated P&L for the current trading day (may be negative).
+    /// Accumulated realized P&L for the current trading day (may be
+    /// negative).
     daily_pnl: i64,
-    /// Number of orders currently resting on the book.
+    /// Unrealized P&L of open positions at the latest mark.
+    unrealized_pnl: i64,
+    /// P&L components the daily loss limit is enforced on.
+    loss_basis: LossBasis,
+    /// Resting orders counted via [`Self::increment_open_orders`], without
+    /// an order id.
     open_order_count: u32,
+    /// Resting orders reserved via [`Self::on_order_accepted`], by order id.
+    working: OpenOrders,
+    /// Per-symbol overrides of [`RiskLimits::max_open_orders_per_symbol`].
+    symbol_open_order_limits: BTreeMap<u64, u32>,
+    /// Non-resting orders accepted in the current throughput window.
+    non_resting_in_window: u32,
+    /// Start of the current non-resting throughput window (ns).
+    non_resting_window_start_ns: u64,
+    /// Non-resting orders accepted in the current sustained-rate window.
+    non_resting_sustained: WindowCounter,
     /// When `true`, all new orders are rejected until explicitly reset.
     circuit_breaker_tripped: bool,
+    /// Overflow handling for notional and position arithmetic.
+    arithmetic_mode: ArithmeticMode,
+    /// Whether oversized orders are rejected or clamped.
+    sizing_mode: SizingMode,
+    /// Recommendation attached to stale working orders.
+    stale_action: StaleAction,
+    /// Version identifier of the active limits.
+    limit_version: u64,
+    /// Every applied limit version, oldest first.  Empty until the first
+    /// change, at which point version 0 (the constructor limits) is seeded.
+    history: Vec<LimitVersion>,
+    /// Comparison table derived from `limits`, rebuilt whenever they change.
+    thresholds: ThresholdTable,
 }
 
 impl PreTradeChecker {
@@ -85,14 +760,73 @@ impl PreTradeChecker {
     #[inline(always)]
     #[must_use]
     pub const fn new(limits: RiskLimits) -> Self {
+        let thresholds = ThresholdTable::from_limits(&limits);
         Self {
             limits,
             daily_pnl: 0,
+            unrealized_pnl: 0,
+            loss_basis: LossBasis::Combined,
             open_order_count: 0,
+            working: OpenOrders::new(),
+            symbol_open_order_limits: BTreeMap::new(),
+            non_resting_in_window: 0,
+            non_resting_window_start_ns: 0,
+            non_resting_sustained: WindowCounter::new(),
             circuit_breaker_tripped: false,
+            arithmetic_mode: ArithmeticMode::Saturating,
+            sizing_mode: SizingMode::Reject,
+            stale_action: StaleAction::Warn,
+            limit_version: 0,
+            history: Vec::new(),
+            thresholds,
         }
     }
 
+    /// Select how arithmetic overflow is handled (builder style).
+    ///
+    /// Defaults to [`ArithmeticMode::Saturating`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn with_arithmetic_mode(mut self, mode: ArithmeticMode) -> Self {
+        self.arithmetic_mode = mode;
+        self
+    }
+
+    /// Select whether [`Self::check_or_clamp`] rejects or clamps oversized
+    /// orders (builder style).
+    ///
+    /// Defaults to [`SizingMode::Reject`]. [`Self::check_order`] always
+    /// rejects regardless of this setting.
+    #[inline(always)]
+    #[must_use]
+    pub const fn with_sizing_mode(mut self, mode: SizingMode) -> Self {
+        self.sizing_mode = mode;
+        self
+    }
+
+    /// Select the recommendation attached to [`Self::stale_orders`] reports
+    /// (builder style).
+    ///
+    /// Defaults to [`StaleAction::Warn`].  The checker never cancels orders
+    /// itself.
+    #[inline(always)]
+    #[must_use]
+    pub const fn with_stale_action(mut self, action: StaleAction) -> Self {
+        self.stale_action = action;
+        self
+    }
+
+    /// Select which P&L components the daily loss limit is enforced on
+    /// (builder style).
+    ///
+    /// Defaults to [`LossBasis::Combined`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn with_loss_basis(mut self, basis: LossBasis) -> Self {
+        self.loss_basis = basis;
+        self
+    }
+
     /// Run all pre-trade risk checks for `order` against the optional current
     /// `position`.
     ///
@@ -101,7 +835,8 @@ impl PreTradeChecker {
     /// 2. Order size
     /// 3. Resulting position size
     /// 4. Notional value
-    /// 5. Open order count
+    /// 5. Open order count, per-symbol open order count and open-order
+    ///    notional (resting orders), or non-resting throughput (IOC / FOK)
     /// 6. Daily loss limit
     ///
     /// Returns `Ok(())` if every check passes, or the first [`RiskReject`]
@@ -114,563 +849,2883 @@ impl PreTradeChecker {
         &self,
         order: &Order,
         position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.check_order_with_limits(&self.limits, order, position)
+    }
+
+    /// Run [`Self::check_order`] against `limits` instead of the active
+    /// limits, using this checker's running counters and working orders.
+    ///
+    /// Used to evaluate a candidate limit set in shadow (see
+    /// [`ShadowLimits`](crate::ShadowLimits)).  Per-symbol open order
+    /// overrides set with [`Self::set_symbol_open_order_limit`] still apply,
+    /// and an order in an illiquid symbol of `limits` is checked against
+    /// [`RiskLimits::symbol_caps`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject`] if any of `limits` is breached.
+    pub fn check_order_with_limits(
+        &self,
+        limits: &RiskLimits,
+        order: &Order,
+        position: Option<&Position>,
     ) -> Result<(), RiskReject> {
         // 1. Circuit breaker takes priority over all other checks.
         if self.circuit_breaker_tripped {
             return Err(RiskReject::CircuitBreakerTripped);
         }
 
+        let checks = limits.checks;
+        let caps = symbol_caps(limits, position);
+
         // 2. Order size check.
-        if order.quantity > self.limits.max_order_size {
+        if checks.order_size && order.quantity > caps.max_order_size {
             return Err(RiskReject::OrderSizeTooLarge {
                 size: order.quantity,
-                limit: self.limits.max_order_size,
+                limit: caps.max_order_size,
+                max_allowed: caps.max_order_size,
             });
         }
 
         // 3. Position limit check — compute net position after this order.
-        let current_net: i64 = position.map_or(0, |p| p.net_quantity);
-        let signed_delta: i64 = match order.side {
-            Side::Bid => order.quantity as i64,
-            Side::Ask => -(order.quantity as i64),
-        };
-        let after_net: i64 = current_net.saturating_add(signed_delta);
-        if after_net.unsigned_abs() > self.limits.max_position {
-            return Err(RiskReject::PositionLimitBreached {
-                current: current_net,
-                after: after_net,
-                limit: self.limits.max_position,
-            });
+        if checks.position {
+            let current_net: i64 = position.map_or(0, |p| p.net_quantity);
+            let after_net: i64 = self.net_after(current_net, order)?;
+            if after_net.unsigned_abs() > caps.max_position {
+                return Err(RiskReject::PositionLimitBreached {
+                    current: current_net,
+                    after: after_net,
+                    limit: caps.max_position,
+                    max_allowed: position_headroom(current_net, order.side, caps.max_position),
+                });
+            }
         }
 
-        // 4. Notional value check.  Use i128 to avoid overflow during
-        //    multiplication, then saturate back to i64 for comparison.
-        let notional: i64 = {
-            let n = (order.price as i128).saturating_mul(order.quantity as i128);
-            n.min(i64::MAX as i128) as i64
-        };
-        if notional > self.limits.max_notional {
-            return Err(RiskReject::NotionalExceeded {
-                notional,
-                limit: self.limits.max_notional,
-            });
+        // 4. Notional value check.
+        if checks.notional {
+            let notional: i64 = self.notional(order)?;
+            if notional > limits.max_notional {
+                return Err(RiskReject::NotionalExceeded {
+                    notional,
+                    limit: limits.max_notional,
+                    max_allowed: notional_headroom(order.price, limits.max_notional),
+                });
+            }
         }
 
-        // 5. Open order count check.
-        if self.open_order_count >= self.limits.max_open_orders {
-            return Err(RiskReject::MaxOpenOrdersReached {
-                count: self.open_order_count,
-                limit: self.limits.max_open_orders,
-            });
+        // 5. Open order count check for resting orders; throughput check for
+        //    orders that never rest.
+        if rests_on_book(order) {
+            let open = self.open_order_count();
+            if checks.open_orders && open >= limits.max_open_orders {
+                return Err(RiskReject::MaxOpenOrdersReached {
+                    count: open,
+                    limit: limits.max_open_orders,
+                });
+            }
+            self.symbol_open_orders_check_with(limits, position)?;
+            if checks.open_order_notional {
+                let working = self.working.total_notional();
+                let notional = self.notional(order)?;
+                if working.saturating_add(notional) > limits.max_open_order_notional {
+                    return Err(RiskReject::OpenOrderNotionalExceeded {
+                        open: working,
+                        notional,
+                        limit: limits.max_open_order_notional,
+                        max_allowed: notional_headroom(
+                            order.price,
+                            limits.max_open_order_notional.saturating_sub(working),
+                        ),
+                    });
+                }
+            }
+        } else if checks.non_resting_rate {
+            if let Some((count, limit)) = self.non_resting_breach(limits, order.timestamp_ns, 1) {
+                return Err(RiskReject::NonRestingRateExceeded { count, limit });
+            }
         }
 
         // 6. Daily loss limit check.
-        if self.daily_pnl <= self.limits.max_daily_loss {
-            return Err(RiskReject::DailyLossLimitHit {
-                loss: self.daily_pnl,
-                limit: self.limits.max_daily_loss,
-            });
+        if checks.daily_loss && self.loss_pnl() <= limits.max_daily_loss {
+            return Err(self.daily_loss_reject(limits.max_daily_loss));
         }
 
         Ok(())
     }
 
-    /// Update the running daily P&L tracker.
+    /// Validate an iceberg order showing `display` of its `order.quantity`,
+    /// then run [`Self::check_order`].
     ///
-    /// `pnl` is added to the accumulated total; a negative value represents
-    /// a loss. When the total reaches `max_daily_loss`, subsequent orders
-    /// will be rejected by [`Self::check_order`].
-    #[inline(always)]
-    pub const fn update_daily_pnl(&mut self, pnl: i64) {
-        self.daily_pnl = self.daily_pnl.saturating_add(pnl);
-    }
-
-    /// Record that a new order has been placed on the book.
-    #[inline(always)]
-    pub const fn increment_open_orders(&mut self) {
-        self.open_order_count = self.open_order_count.saturating_add(1);
+    /// The ledger order carries no display quantity, so it is passed
+    /// separately.  The display must be non-zero, no larger than the order
+    /// and at least [`RiskLimits::min_display_quantity`].  The hidden
+    /// remainder is not discounted: size, position, notional and open-order
+    /// notional are all checked on the full quantity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject::InvalidIceberg`] for malformed display
+    /// parameters, or any reject from [`Self::check_order`].
+    pub fn check_iceberg_order(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        display: u64,
+    ) -> Result<(), RiskReject> {
+        if self.circuit_breaker_tripped {
+            return Err(RiskReject::CircuitBreakerTripped);
+        }
+        let min_display = self.limits.min_display_quantity;
+        if self.limits.checks.iceberg
+            && (display == 0 || display > order.quantity || display < min_display)
+        {
+            return Err(RiskReject::InvalidIceberg {
+                display,
+                quantity: order.quantity,
+                min_display,
+            });
+        }
+        self.check_order(order, position)
     }
 
-    /// Record that an open order has been cancelled or fully filled.
-    #[inline(always)]
-    pub const fn decrement_open_orders(&mut self) {
-        self.open_order_count = self.open_order_count.saturating_sub(1);
+    /// Run [`Self::check_order`], then reject a limit order priced more than
+    /// [`RiskLimits::max_through_bbo_bps`] through the opposite side of `bbo`.
+    ///
+    /// A buy is compared against the ask and a sell against the bid; market
+    /// orders, a missing quote or `None` skip the reasonability check.  An
+    /// illiquid symbol is held to the tighter
+    /// [`RiskLimits::illiquid_max_through_bbo_bps`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject`] if any risk limit is breached.
+    pub fn check_order_with_bbo(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        bbo: Option<&Bbo>,
+    ) -> Result<(), RiskReject> {
+        self.check_order(order, position)?;
+        self.bbo_check(order, position, bbo)
     }
 
-    /// Trip the circuit breaker, blocking all further order submissions until
-    /// [`Self::reset_circuit_breaker`] is called.
-    #[inline(always)]
-    pub const fn trip_circuit_breaker(&mut self) {
-        self.circuit_breaker_tripped = true;
+    /// The price-reasonability step of [`Self::check_order_with_bbo`].
+    pub(crate) fn bbo_check(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        bbo: Option<&Bbo>,
+    ) -> Result<(), RiskReject> {
+        Self::bbo_check_with(&self.limits, order, position, bbo)
     }
 
-    /// Clear the circuit breaker, allowing order submissions to resume.
-    #[inline(always)]
-    pub const fn reset_circuit_breaker(&mut self) {
-        self.circuit_breaker_tripped = false;
+    /// [`Self::bbo_check`] against `limits` instead of the active limits.
+    pub(crate) fn bbo_check_with(
+        limits: &RiskLimits,
+        order: &Order,
+        position: Option<&Position>,
+        bbo: Option<&Bbo>,
+    ) -> Result<(), RiskReject> {
+        if !limits.checks.bbo_reasonability || matches!(order.order_type, OrderType::Market) {
+            return Ok(());
+        }
+        let Some(bbo) = bbo else {
+            return Ok(());
+        };
+        let limit_bps = symbol_caps(limits, position).max_through_bbo_bps;
+        match bbo.through_bps(order.side, order.price) {
+            Some(through_bps) if through_bps > limit_bps => Err(RiskReject::PriceThroughBbo {
+                price: order.price,
+                touch: bbo.touch(order.side).unwrap_or(0),
+                through_bps,
+                limit_bps,
+            }),
+            _ => Ok(()),
+        }
     }
 
-    /// Perform end-of-day reset: clears daily P&L and open order count.
+    /// Run [`Self::check_order_with_bbo`] with market data for `position`'s
+    /// symbol taken from `market`.
     ///
-    /// The circuit breaker state is intentionally preserved across daily
-    /// resets; it must be explicitly cleared with [`Self::reset_circuit_breaker`].
-    #[inline(always)]
-    pub const fn reset_daily(&mut self) {
-        self.daily_pnl = 0;
-        self.open_order_count = 0;
+    /// A market order is evaluated at the opposite-side quote (falling back
+    /// to the reference price) instead of its own price, so its notional and
+    /// open-order notional reflect what it would actually trade at.  Without
+    /// a position the symbol is unknown and the order is checked as by
+    /// [`Self::check_order`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject`] if any risk limit is breached.
+    pub fn check_order_in_market<M: MarketContext + ?Sized>(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        market: &M,
+    ) -> Result<(), RiskReject> {
+        self.check_in_market_with(&self.limits, order, position, market)
     }
 
-    /// Return the current daily P&L value.
+    /// [`Self::check_order_in_market`] against `limits` instead of the
+    /// active limits.
+    pub(crate) fn check_in_market_with<M: MarketContext + ?Sized>(
+        &self,
+        limits: &RiskLimits,
+        order: &Order,
+        position: Option<&Position>,
+        market: &M,
+    ) -> Result<(), RiskReject> {
+        let Some(symbol_hash) = position.map(|p| p.symbol_hash) else {
+            return self.check_order_with_limits(limits, order, position);
+        };
+        let bbo = market.bbo(symbol_hash);
+        let check = |order: &Order| {
+            self.check_order_with_limits(limits, order, position)?;
+            Self::bbo_check_with(limits, order, position, bbo.as_ref())
+        };
+        if matches!(order.order_type, OrderType::Market) {
+            let price = bbo
+                .and_then(|b| b.touch(order.side))
+                .or_else(|| market.reference_price(symbol_hash));
+            if let Some(price) = price {
+                return check(&Order {
+                    price,
+                    ..order.clone()
+                });
+            }
+        }
+        check(order)
+    }
+
+    /// Run [`Self::check_order`], then apply the expiry restrictions and
+    /// spot-month limit of `instrument` at `order.timestamp_ns`.
+    ///
+    /// Within [`RiskLimits::expiry_no_open_days`] of expiry, orders may add
+    /// to or reduce an existing position but not open one from flat or flip
+    /// it.  From the start of the final session, and after expiry, only
+    /// orders that reduce the position are accepted.
+    ///
+    /// For a physically delivered contract, from
+    /// [`RiskLimits::spot_month_days_before_notice`] days before first notice
+    /// day the position may not grow beyond
+    /// [`RiskLimits::spot_month_position_limit`]; orders that shrink an
+    /// over-limit position are still accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject::ExpiryRestricted`],
+    /// [`RiskReject::SpotMonthLimitBreached`], or any reject from
+    /// [`Self::check_order`].
+    pub fn check_instrument_order(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        instrument: &Instrument,
+    ) -> Result<(), RiskReject> {
+        self.check_order(order, position)?;
+        let checks = self.limits.checks;
+        let net = position.map_or(0, |p| p.net_quantity);
+        let now_ns = order.timestamp_ns;
+        if checks.expiry {
+            let no_open_ns = u64::from(self.limits.expiry_no_open_days).saturating_mul(DAY_NS);
+            if let (Some(restriction), Some(expiry_ns)) = (
+                instrument.restriction_at(now_ns, no_open_ns),
+                instrument.expiry_ns,
+            ) {
+                if !restriction.permits(order, net) {
+                    return Err(RiskReject::ExpiryRestricted {
+                        symbol_hash: instrument.symbol_hash,
+                        restriction,
+                        expiry_ns,
+                    });
+                }
+            }
+        }
+        let lead_ns = u64::from(self.limits.spot_month_days_before_notice).saturating_mul(DAY_NS);
+        if checks.spot_month && instrument.in_spot_month(now_ns, lead_ns) {
+            let limit = self.limits.spot_month_position_limit;
+            let after = self.net_after(net, order)?;
+            if after.unsigned_abs() > limit && after.unsigned_abs() > net.unsigned_abs() {
+                return Err(RiskReject::SpotMonthLimitBreached {
+                    symbol_hash: instrument.symbol_hash,
+                    current: net,
+                    after,
+                    limit,
+                    max_allowed: position_headroom(net, order.side, limit),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check `order` in `instrument` against the maturity-bucket limits of its
+    /// product.
+    ///
+    /// `holdings` lists the current net position of every held contract
+    /// (other products are ignored).  Positions in the same bucket net, so a
+    /// calendar spread within the bucket only uses its outright residual.
+    /// The order is only rejected if it grows the bucket's absolute net
+    /// position beyond the limit.  Run alongside [`Self::check_order`] or
+    /// [`Self::check_instrument_order`]; the breaker and per-order limits
+    /// are not repeated here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject::MaturityBucketLimitBreached`] if the bucket
+    /// limit would be exceeded.
+    pub fn check_maturity_buckets(
+        &self,
+        order: &Order,
+        instrument: &Instrument,
+        holdings: &[(Instrument, i64)],
+    ) -> Result<(), RiskReject> {
+        if !self.limits.checks.maturity_buckets {
+            return Ok(());
+        }
+        let now_ns = order.timestamp_ns;
+        let days = self.limits.front_month_days;
+        let bucket = instrument.bucket_at(now_ns, days);
+        let current =
+            bucket_exposure(instrument.product_hash, holdings, now_ns, days)[bucket.index()];
+        let after = self.net_after(current, order)?;
+        let limit = self.limits.bucket_limit(bucket);
+        if after.unsigned_abs() > limit && after.unsigned_abs() > current.unsigned_abs() {
+            return Err(RiskReject::MaturityBucketLimitBreached {
+                product_hash: instrument.product_hash,
+                bucket,
+                current,
+                after,
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// Run [`Self::check_order_with_bbo`] for a post-only order, rejecting it
+    /// if it would immediately cross `bbo`.
+    ///
+    /// A buy crosses at or above the ask and a sell at or below the bid.
+    /// Post-only orders exist to earn maker fees, so a cross is always an
+    /// error and cannot be disabled.  A market order always crosses; a
+    /// missing opposite quote cannot be crossed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject::PostOnlyWouldCross`], or any reject from
+    /// [`Self::check_order_with_bbo`].
+    pub fn check_post_only_order(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        bbo: &Bbo,
+    ) -> Result<(), RiskReject> {
+        self.check_order_with_bbo(order, position, Some(bbo))?;
+        let Some(touch) = bbo.touch(order.side) else {
+            return Ok(());
+        };
+        let crosses = matches!(order.order_type, OrderType::Market)
+            || match order.side {
+                Side::Bid => order.price >= touch,
+                Side::Ask => order.price <= touch,
+            };
+        if crosses {
+            return Err(RiskReject::PostOnlyWouldCross {
+                price: order.price,
+                touch,
+            });
+        }
+        Ok(())
+    }
+
+    /// Run all pre-trade checks, clamping the quantity in [`SizingMode::Clamp`].
+    ///
+    /// In [`SizingMode::Reject`] this is [`Self::check_order`]. In
+    /// [`SizingMode::Clamp`], a size, position, notional or open-order
+    /// notional reject with a non-zero `max_allowed` is retried at that
+    /// quantity until the order passes; the reduced order is returned as
+    /// [`CheckOutcome::Clamped`].
+    /// Any other reject, or a limit with no headroom, is returned as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RiskReject`] if the order cannot be made to pass.
+    pub fn check_or_clamp(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<CheckOutcome, RiskReject> {
+        let first = match self.check_order(order, position) {
+            Ok(()) => return Ok(CheckOutcome::Accepted),
+            Err(reject) => reject,
+        };
+        if self.sizing_mode == SizingMode::Reject {
+            return Err(first);
+        }
+        let mut clamped = order.clone();
+        let mut reject = first.clone();
+        // Each retry can only lower the quantity and each of the four
+        // quantity checks fires at most once, so this terminates quickly.
+        loop {
+            let (RiskReject::OrderSizeTooLarge { max_allowed, .. }
+            | RiskReject::PositionLimitBreached { max_allowed, .. }
+            | RiskReject::NotionalExceeded { max_allowed, .. }
+            | RiskReject::OpenOrderNotionalExceeded { max_allowed, .. }) = reject
+            else {
+                return Err(reject);
+            };
+            if max_allowed == 0 || max_allowed >= clamped.quantity {
+                // Nothing fits, or the limit is not quantity-driven here.
+                return Err(if clamped.quantity == order.quantity {
+                    first
+                } else {
+                    reject
+                });
+            }
+            clamped.quantity = max_allowed;
+            match self.check_order(&clamped, position) {
+                Ok(()) => {
+                    return Ok(CheckOutcome::Clamped {
+                        order: clamped,
+                        requested: order.quantity,
+                    })
+                }
+                Err(next) => reject = next,
+            }
+        }
+    }
+
+    /// Per-symbol open order check for a resting order in `position`'s symbol.
+    ///
+    /// The symbol is only known through the position, so an order checked
+    /// without one is not counted against any symbol; pass a flat position
+    /// to enforce the limit for a symbol not yet held.
+    pub(crate) fn symbol_open_orders_check(
+        &self,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.symbol_open_orders_check_with(&self.limits, position)
+    }
+
+    fn symbol_open_orders_check_with(
+        &self,
+        limits: &RiskLimits,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let Some(symbol_hash) = position.map(|p| p.symbol_hash) else {
+            return Ok(());
+        };
+        if !limits.checks.open_orders_per_symbol {
+            return Ok(());
+        }
+        let count = self.working.count_for(symbol_hash);
+        let limit = self
+            .symbol_open_order_limits
+            .get(&symbol_hash)
+            .copied()
+            .unwrap_or(limits.max_open_orders_per_symbol);
+        if count >= limit {
+            return Err(RiskReject::SymbolOpenOrdersReached {
+                symbol_hash,
+                count,
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// Resting orders reserved for `symbol_hash`.
+    pub(crate) fn symbol_open_order_count(&self, symbol_hash: u64) -> u32 {
+        self.working.count_for(symbol_hash)
+    }
+
+    /// Non-resting orders counted in the window that contains `timestamp_ns`.
+    pub(crate) const fn non_resting_count_at(&self, timestamp_ns: u64) -> u32 {
+        self.non_resting_count_within(self.limits.non_resting_window_ns, timestamp_ns)
+    }
+
+    /// Non-resting orders counted at `timestamp_ns` for a burst window of
+    /// `window_ns`.
+    const fn non_resting_count_within(&self, window_ns: u64, timestamp_ns: u64) -> u32 {
+        let elapsed = timestamp_ns.saturating_sub(self.non_resting_window_start_ns);
+        if elapsed >= window_ns {
+            0
+        } else {
+            self.non_resting_in_window
+        }
+    }
+
+    /// Whether one more non-resting order at `timestamp_ns` would exceed the
+    /// sustained threshold of the active limits.
+    pub(crate) const fn non_resting_sustained_exceeded(&self, timestamp_ns: u64) -> bool {
+        RateWindow::new(
+            self.limits.max_non_resting_orders_sustained,
+            self.limits.non_resting_sustained_window_ns,
+        )
+        .exceeded(&self.non_resting_sustained, timestamp_ns, 1)
+        .is_some()
+    }
+
+    /// Window count and limit of the first non-resting threshold (burst,
+    /// then sustained) that `n` more orders at `timestamp_ns` would exceed
+    /// under `limits`. Both thresholds and window lengths come from `limits`,
+    /// so candidate limits can be evaluated against the live counters.
+    pub(crate) const fn non_resting_breach(
+        &self,
+        limits: &RiskLimits,
+        timestamp_ns: u64,
+        n: u32,
+    ) -> Option<(u32, u32)> {
+        let count = self.non_resting_count_within(limits.non_resting_window_ns, timestamp_ns);
+        if count.saturating_add(n) > limits.max_non_resting_orders {
+            return Some((count, limits.max_non_resting_orders));
+        }
+        let sustained = RateWindow::new(
+            limits.max_non_resting_orders_sustained,
+            limits.non_resting_sustained_window_ns,
+        );
+        match sustained.exceeded(&self.non_resting_sustained, timestamp_ns, n) {
+            Some(count) => Some((count, sustained.limit)),
+            None => None,
+        }
+    }
+
+    /// Net position after `order` would be fully filled.
+    pub(crate) fn net_after(&self, current_net: i64, order: &Order) -> Result<i64, RiskReject> {
+        match self.arithmetic_mode {
+            ArithmeticMode::Saturating => {
+                let signed_delta: i64 = match order.side {
+                    Side::Bid => order.quantity as i64,
+                    Side::Ask => -(order.quantity as i64),
+                };
+                Ok(current_net.saturating_add(signed_delta))
+            }
+            ArithmeticMode::Checked => {
+                let qty =
+                    i64::try_from(order.quantity).map_err(|_| RiskReject::ArithmeticOverflow)?;
+                let after = match order.side {
+                    Side::Bid => current_net.checked_add(qty),
+                    Side::Ask => current_net.checked_sub(qty),
+                };
+                after.ok_or(RiskReject::ArithmeticOverflow)
+            }
+        }
+    }
+
+    /// Absolute notional value (`|price| * quantity`) of `order`.
+    ///
+    /// Prices may be negative (spreads, some commodities), so the magnitude
+    /// is what gets compared against notional limits.  Uses an i128
+    /// intermediate, which cannot overflow for any `i64` price and `u64`
+    /// quantity; only the conversion back to `i64` is mode-dependent.
+    pub(crate) fn notional(&self, order: &Order) -> Result<i64, RiskReject> {
+        let n = ((order.price as i128) * (order.quantity as i128)).abs();
+        self.arithmetic_mode.narrow(n)
+    }
+
+    /// Return the active risk limits.
     #[inline(always)]
     #[must_use]
-    pub const fn daily_pnl(&self) -> i64 {
-        self.daily_pnl
+    pub const fn limits(&self) -> &RiskLimits {
+        &self.limits
     }
 
-    /// Return the current open order count.
+    /// Return the comparison table derived from the active limits.
     #[inline(always)]
     #[must_use]
-    pub const fn open_order_count(&self) -> u32 {
-        self.open_order_count
+    pub const fn thresholds(&self) -> &ThresholdTable {
+        &self.thresholds
     }
 
-    /// Return whether the circuit breaker is currently tripped.
+    /// Replace the active risk limits without attribution.
+    ///
+    /// Equivalent to [`Self::apply_limits`] with an empty author and a zero
+    /// timestamp.  Running counters (daily P&L, open orders, circuit breaker)
+    /// are kept.
+    #[inline(always)]
+    pub fn set_limits(&mut self, limits: RiskLimits) {
+        self.apply_limits(limits, "", 0);
+    }
+
+    /// Apply a new limit version and return its version identifier.
+    ///
+    /// The previous versions remain available through [`Self::history`] and
+    /// can be restored with [`Self::rollback_to`].  Running counters are kept.
+    pub fn apply_limits(&mut self, limits: RiskLimits, author: &str, timestamp_ns: u64) -> u64 {
+        self.push_version(limits, author, timestamp_ns, None)
+    }
+
+    /// Restore the limits of a previously applied `version`.
+    ///
+    /// The rollback is itself recorded as a new version (with
+    /// [`LimitVersion::rollback_of`] set), so the history stays append-only.
+    /// Returns the identifier of the new version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LimitHistoryError::UnknownVersion`] if `version` was never
+    /// applied.
+    pub fn rollback_to(
+        &mut self,
+        version: u64,
+        author: &str,
+        timestamp_ns: u64,
+    ) -> Result<u64, LimitHistoryError> {
+        let limits = if version == 0 && self.history.is_empty() {
+            self.limits.clone()
+        } else {
+            self.history
+                .iter()
+                .find(|v| v.version == version)
+                .ok_or(LimitHistoryError::UnknownVersion { version })?
+                .limits
+                .clone()
+        };
+        Ok(self.push_version(limits, author, timestamp_ns, Some(version)))
+    }
+
+    fn push_version(
+        &mut self,
+        limits: RiskLimits,
+        author: &str,
+        timestamp_ns: u64,
+        rollback_of: Option<u64>,
+    ) -> u64 {
+        if self.history.is_empty() {
+            self.history.push(LimitVersion {
+                version: 0,
+                limits: self.limits.clone(),
+                author: String::new(),
+                timestamp_ns: 0,
+                rollback_of: None,
+            });
+        }
+        let version = self.limit_version + 1;
+        self.history.push(LimitVersion {
+            version,
+            limits: limits.clone(),
+            author: author.to_string(),
+            timestamp_ns,
+            rollback_of,
+        });
+        self.thresholds = ThresholdTable::from_limits(&limits);
+        self.limits = limits;
+        self.limit_version = version;
+        version
+    }
+
+    /// Add `symbol_hash` to, or remove it from, the illiquid symbols
+    /// ([`RiskLimits::illiquid_symbols`]) as a new limit version, and return
+    /// its identifier.
+    ///
+    /// Like any other change through [`Self::apply_limits`], the previous
+    /// classification can be restored with [`Self::rollback_to`].
+    pub fn set_illiquid(
+        &mut self,
+        symbol_hash: u64,
+        illiquid: bool,
+        author: &str,
+        timestamp_ns: u64,
+    ) -> u64 {
+        let mut limits = self.limits.clone();
+        if illiquid {
+            limits.illiquid_symbols.insert(symbol_hash);
+        } else {
+            limits.illiquid_symbols.remove(&symbol_hash);
+        }
+        self.apply_limits(limits, author, timestamp_ns)
+    }
+
+    /// Return the identifier of the active limit version.
     #[inline(always)]
     #[must_use]
-    pub const fn is_circuit_breaker_tripped(&self) -> bool {
-        self.circuit_breaker_tripped
+    pub const fn limit_version(&self) -> u64 {
+        self.limit_version
     }
-}
 
-// ---------------------------------------------------------------------------
-// Tests
-// ---------------------------------------------------------------------------
+    /// Return every applied limit version, oldest first.
+    ///
+    /// Empty until the limits are first changed.
+    #[inline(always)]
+    #[must_use]
+    pub fn history(&self) -> &[LimitVersion] {
+        &self.history
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use alice_ledger::{OrderId, OrderType, TimeInForce};
+    /// Run [`Self::check_order`] and record the outcome together with the
+    /// active limit version.
+    #[must_use]
+    pub fn decide(&self, order: &Order, position: Option<&Position>) -> DecisionRecord {
+        self.decide_tagged(order, position, DecisionTags::default())
+    }
 
-    fn make_order(side: Side, price: i64, quantity: u64) -> Order {
-        Order {
-            id: OrderId(1),
-            side,
-            order_type: OrderType::Limit,
-            price,
-            quantity,
-            filled_quantity: 0,
-            timestamp_ns: 0,
-            time_in_force: TimeInForce::GTC,
+    /// [`Self::decide`] with the algorithm id and DEA flag recorded in the
+    /// audit record.
+    #[must_use]
+    pub fn decide_tagged(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        tags: DecisionTags,
+    ) -> DecisionRecord {
+        DecisionRecord {
+            order_id: order.id.0,
+            timestamp_ns: order.timestamp_ns,
+            limit_version: self.limit_version,
+            result: self.check_order(order, position),
+            tags,
         }
     }
 
-    fn make_position(net_quantity: i64) -> Position {
-        Position {
-            symbol_hash: 0xDEAD_BEEF,
-            net_quantity,
-            avg_entry_price: 1000,
-            realized_pnl: 0,
-            unrealized_pnl: 0,
-            trade_count: 0,
+    /// Capture the running counters for persistence.
+    ///
+    /// Orders tracked by id are included so that a restart keeps them
+    /// counted against the open order limits.  Limits and their version
+    /// history are not; see [`CheckerState`].
+    #[must_use]
+    pub fn state(&self) -> CheckerState {
+        CheckerState {
+            daily_pnl: self.daily_pnl,
+            unrealized_pnl: self.unrealized_pnl,
+            loss_basis: self.loss_basis,
+            open_order_count: self.open_order_count,
+            non_resting_in_window: self.non_resting_in_window,
+            non_resting_window_start_ns: self.non_resting_window_start_ns,
+            non_resting_sustained_in_window: self.non_resting_sustained.count,
+            non_resting_sustained_start_ns: self.non_resting_sustained.start_ns,
+            circuit_breaker_tripped: self.circuit_breaker_tripped,
+            working: self.working.iter().cloned().collect(),
+        }
+    }
+
+    /// Restore running counters captured with [`Self::state`].
+    ///
+    /// The limits, their version and history are left as they are: a
+    /// snapshot does not carry them, so restoring only the version would
+    /// stamp decisions with a version the active limits do not belong to.
+    pub fn restore_state(&mut self, state: &CheckerState) {
+        self.daily_pnl = state.daily_pnl;
+        self.unrealized_pnl = state.unrealized_pnl;
+        self.loss_basis = state.loss_basis;
+        self.open_order_count = state.open_order_count;
+        self.non_resting_in_window = state.non_resting_in_window;
+        self.non_resting_window_start_ns = state.non_resting_window_start_ns;
+        self.non_resting_sustained = WindowCounter {
+            start_ns: state.non_resting_sustained_start_ns,
+            count: state.non_resting_sustained_in_window,
+        };
+        self.circuit_breaker_tripped = state.circuit_breaker_tripped;
+        self.working = OpenOrders::new();
+        for order in &state.working {
+            self.working.insert(order.clone());
+        }
+    }
+
+    /// Snapshot current usage of the stateful limits.
+    #[must_use]
+    pub fn utilization(&self) -> Utilization {
+        Utilization {
+            open_orders: self.open_order_count(),
+            max_open_orders: self.limits.max_open_orders,
+            open_order_notional: self.working.total_notional(),
+            max_open_order_notional: self.limits.max_open_order_notional,
+            daily_pnl: self.loss_pnl(),
+            realized_pnl: self.daily_pnl,
+            unrealized_pnl: self.unrealized_pnl,
+            max_daily_loss: self.limits.max_daily_loss,
+            circuit_breaker_tripped: self.circuit_breaker_tripped,
+            checks: self.limits.checks,
+        }
+    }
+
+    /// Return the configured sizing mode.
+    #[inline(always)]
+    #[must_use]
+    pub const fn sizing_mode(&self) -> SizingMode {
+        self.sizing_mode
+    }
+
+    /// Return the configured arithmetic mode.
+    #[inline(always)]
+    #[must_use]
+    pub const fn arithmetic_mode(&self) -> ArithmeticMode {
+        self.arithmetic_mode
+    }
+
+    /// Update the running daily P&L tracker.
+    ///
+    /// `pnl` is added to the accumulated realized total; a negative value
+    /// represents a loss. When the P&L on the checker's [`LossBasis`]
+    /// reaches `max_daily_loss`, subsequent orders will be rejected by
+    /// [`Self::check_order`].
+    #[inline(always)]
+    pub const fn update_daily_pnl(&mut self, pnl: i64) {
+        self.daily_pnl = self.daily_pnl.saturating_add(pnl);
+    }
+
+    /// Replace the unrealized P&L with the value at the latest mark.
+    ///
+    /// Not included in [`Self::state`]; re-mark open positions after a
+    /// restore.
+    #[inline(always)]
+    pub const fn set_unrealized_pnl(&mut self, pnl: i64) {
+        self.unrealized_pnl = pnl;
+    }
+
+    /// Reserve capacity for an order that passed [`Self::check_order`].
+    ///
+    /// A resting order is tracked by id and takes an open-order slot until
+    /// [`Self::on_order_closed`]; GTC orders survive [`Self::reset_daily`].
+    /// An immediate-or-cancel or fill-or-kill order instead counts against
+    /// the non-resting throughput window at `order.timestamp_ns`.
+    ///
+    /// Calling this again for an already tracked order replaces it, which
+    /// refreshes its working notional after a partial fill.
+    ///
+    /// The order is not attributed to a symbol; use
+    /// [`Self::on_order_accepted_in`] to count it against a per-symbol limit.
+    pub fn on_order_accepted(&mut self, order: &Order) {
+        self.reserve(WorkingOrder::from_order(order), order);
+    }
+
+    /// [`Self::on_order_accepted`] for an order in `symbol_hash`, counting it
+    /// against [`RiskLimits::max_open_orders_per_symbol`] while it rests.
+    pub fn on_order_accepted_in(&mut self, order: &Order, symbol_hash: u64) {
+        self.reserve(
+            WorkingOrder::from_order(order).with_symbol(symbol_hash),
+            order,
+        );
+    }
+
+    fn reserve(&mut self, working: WorkingOrder, order: &Order) {
+        if rests_on_book(order) {
+            self.working.insert(working);
+            return;
         }
+        if self.non_resting_count_at(order.timestamp_ns) == 0 {
+            self.non_resting_window_start_ns = order.timestamp_ns;
+            self.non_resting_in_window = 0;
+        }
+        self.non_resting_in_window = self.non_resting_in_window.saturating_add(1);
+        self.non_resting_sustained.add(
+            self.limits.non_resting_sustained_window_ns,
+            order.timestamp_ns,
+            1,
+        );
+    }
+
+    /// Release the slot of a tracked order that was cancelled or fully
+    /// filled.  Returns `false` if `order_id` was not tracked.
+    pub fn on_order_closed(&mut self, order_id: u64) -> bool {
+        self.working.remove(order_id).is_some()
+    }
+
+    /// Resting orders tracked by id.
+    #[inline(always)]
+    #[must_use]
+    pub const fn open_orders(&self) -> &OpenOrders {
+        &self.working
+    }
+
+    /// Record that a new order has been placed on the book.
+    ///
+    /// Anonymous counterpart of [`Self::on_order_accepted`] for callers that
+    /// do not track order ids.  Without an id or time in force the order is
+    /// treated as a day order: the count is cleared by [`Self::reset_daily`],
+    /// so a GTC order counted here stops taking a slot the next day.
+    #[deprecated(
+        note = "day orders only; use `on_order_accepted` so GTC orders survive `reset_daily`"
+    )]
+    #[inline(always)]
+    pub const fn increment_open_orders(&mut self) {
+        self.open_order_count = self.open_order_count.saturating_add(1);
+    }
+
+    /// Record that an open order counted by [`Self::increment_open_orders`]
+    /// has been cancelled or fully filled.
+    #[deprecated(note = "day orders only; use `on_order_closed` with `on_order_accepted`")]
+    #[inline(always)]
+    pub const fn decrement_open_orders(&mut self) {
+        self.open_order_count = self.open_order_count.saturating_sub(1);
+    }
+
+    /// Trip the circuit breaker, blocking all further order submissions until
+    /// [`Self::reset_circuit_breaker`] is called.
+    #[inline(always)]
+    pub const fn trip_circuit_breaker(&mut self) {
+        self.circuit_breaker_tripped = true;
+    }
+
+    /// Clear the circuit breaker, allowing order submissions to resume.
+    #[inline(always)]
+    pub const fn reset_circuit_breaker(&mut self) {
+        self.circuit_breaker_tripped = false;
+    }
+
+    /// Perform end-of-day reset: clears daily P&L, the anonymous open order
+    /// count, tracked day orders and the non-resting throughput window.
+    ///
+    /// Tracked GTC orders are still resting and are carried over.  The
+    /// anonymous count ([`Self::increment_open_orders`]) cannot tell GTC from
+    /// day orders and is always cleared.
+    ///
+    /// The circuit breaker state is intentionally preserved across daily
+    /// resets; it must be explicitly cleared with [`Self::reset_circuit_breaker`].
+    pub fn reset_daily(&mut self) {
+        self.daily_pnl = 0;
+        self.unrealized_pnl = 0;
+        self.open_order_count = 0;
+        self.working.roll_day();
+        self.non_resting_in_window = 0;
+        self.non_resting_window_start_ns = 0;
+        self.non_resting_sustained = WindowCounter::new();
+    }
+
+    /// Return the current daily P&L value.
+    ///
+    /// This is the realized P&L set with [`Self::update_daily_pnl`]; see
+    /// [`Self::combined_pnl`] for realized plus unrealized.
+    #[inline(always)]
+    #[must_use]
+    pub const fn daily_pnl(&self) -> i64 {
+        self.daily_pnl
+    }
+
+    /// Return the daily P&L including the unrealized component.
+    #[inline(always)]
+    #[must_use]
+    pub const fn combined_pnl(&self) -> i64 {
+        self.daily_pnl.saturating_add(self.unrealized_pnl)
+    }
+
+    /// Return the realized component of the daily P&L.
+    #[inline(always)]
+    #[must_use]
+    pub const fn realized_pnl(&self) -> i64 {
+        self.daily_pnl
+    }
+
+    /// Return the unrealized component of the daily P&L.
+    #[inline(always)]
+    #[must_use]
+    pub const fn unrealized_pnl(&self) -> i64 {
+        self.unrealized_pnl
+    }
+
+    /// Return the configured loss basis.
+    #[inline(always)]
+    #[must_use]
+    pub const fn loss_basis(&self) -> LossBasis {
+        self.loss_basis
+    }
+
+    /// Return the daily P&L the daily loss limit is compared against.
+    #[inline(always)]
+    #[must_use]
+    pub const fn loss_pnl(&self) -> i64 {
+        self.loss_basis.pnl(self.daily_pnl, self.unrealized_pnl)
+    }
+
+    /// The reject for a daily loss at or beyond `limit`.
+    pub(crate) const fn daily_loss_reject(&self, limit: i64) -> RiskReject {
+        RiskReject::DailyLossLimitHit {
+            loss: self.loss_pnl(),
+            limit,
+            realized: self.daily_pnl,
+            unrealized: self.unrealized_pnl,
+        }
+    }
+
+    /// Return the current open order count (anonymous plus tracked).
+    #[inline(always)]
+    #[must_use]
+    pub fn open_order_count(&self) -> u32 {
+        let tracked = u32::try_from(self.working.len()).unwrap_or(u32::MAX);
+        self.open_order_count.saturating_add(tracked)
+    }
+
+    /// Override [`RiskLimits::max_open_orders_per_symbol`] for one symbol.
+    #[inline(always)]
+    pub fn set_symbol_open_order_limit(&mut self, symbol_hash: u64, limit: u32) {
+        self.symbol_open_order_limits.insert(symbol_hash, limit);
+    }
+
+    /// Return the open order limit that applies to `symbol_hash`.
+    #[inline(always)]
+    #[must_use]
+    pub fn symbol_open_order_limit(&self, symbol_hash: u64) -> u32 {
+        self.symbol_open_order_limits
+            .get(&symbol_hash)
+            .copied()
+            .unwrap_or(self.limits.max_open_orders_per_symbol)
+    }
+
+    /// Tracked working orders older than
+    /// [`RiskLimits::max_working_order_age_ns`] at `now_ns`, by order id.
+    ///
+    /// Empty when the stale order check is disabled.  Anonymous orders have
+    /// no placement time and are never reported.
+    #[must_use]
+    pub fn stale_orders(&self, now_ns: u64) -> Vec<StaleOrder> {
+        if !self.limits.checks.stale_orders {
+            return Vec::new();
+        }
+        self.working
+            .older_than(now_ns, self.limits.max_working_order_age_ns)
+            .map(|o| StaleOrder {
+                order_id: o.order_id,
+                symbol_hash: o.symbol_hash,
+                age_ns: o.age_ns(now_ns),
+                action: self.stale_action,
+            })
+            .collect()
+    }
+
+    /// Return the combined notional of tracked working orders.
+    ///
+    /// Anonymous orders counted via [`Self::increment_open_orders`] carry
+    /// no price and do not contribute.
+    #[inline(always)]
+    #[must_use]
+    pub const fn open_order_notional(&self) -> i64 {
+        self.working.total_notional()
+    }
+
+    /// Return whether the circuit breaker is currently tripped.
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_circuit_breaker_tripped(&self) -> bool {
+        self.circuit_breaker_tripped
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{MarketSnapshot, SymbolMarket};
+    use alice_ledger::{OrderId, TimeInForce};
+
+    fn make_order(side: Side, price: i64, quantity: u64) -> Order {
+        Order {
+            id: OrderId(1),
+            side,
+            order_type: OrderType::Limit,
+            price,
+            quantity,
+            filled_quantity: 0,
+            timestamp_ns: 0,
+            time_in_force: TimeInForce::GTC,
+        }
+    }
+
+    fn make_position(net_quantity: i64) -> Position {
+        Position {
+            symbol_hash: 0xDEAD_BEEF,
+            net_quantity,
+            avg_entry_price: 1000,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 0,
+        }
+    }
+
+    fn default_checker() -> PreTradeChecker {
+        PreTradeChecker::new(RiskLimits::default())
+    }
+
+    /// Accept `n` resting GTC orders with ids `1..=n`.
+    fn rest_orders(checker: &mut PreTradeChecker, n: u64) {
+        for id in 1..=n {
+            checker.on_order_accepted(&Order {
+                id: OrderId(id),
+                ..make_order(Side::Bid, 1000, 1)
+            });
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Happy path
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_order_passes_all_checks() {
+        let checker = default_checker();
+        let order = make_order(Side::Bid, 1000, 10);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    // -----------------------------------------------------------------------
+    // Order size
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_reject_order_size() {
+        let checker = default_checker();
+        // max_order_size = 100; quantity = 101 should fail.
+        let order = make_order(Side::Bid, 1000, 101);
+        let result = checker.check_order(&order, None);
+        assert!(
+            matches!(result, Err(RiskReject::OrderSizeTooLarge { .. })),
+            "expected OrderSizeTooLarge, got {:?}",
+            result
+        );
+        if let Err(RiskReject::OrderSizeTooLarge {
+            size,
+            limit,
+            max_allowed,
+        }) = result
+        {
+            assert_eq!(size, 101);
+            assert_eq!(limit, 100);
+            assert_eq!(max_allowed, 100);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Position limit
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_reject_position_limit_long() {
+        let checker = default_checker();
+        // Current long = 990, order adds 100 → net 1090 > max 1000.
+        let position = make_position(990);
+        let order = make_order(Side::Bid, 1000, 100);
+        let result = checker.check_order(&order, Some(&position));
+        assert!(
+            matches!(result, Err(RiskReject::PositionLimitBreached { .. })),
+            "expected PositionLimitBreached, got {:?}",
+            result
+        );
+        if let Err(RiskReject::PositionLimitBreached {
+            current,
+            after,
+            limit,
+            max_allowed,
+        }) = result
+        {
+            assert_eq!(current, 990);
+            assert_eq!(after, 1090);
+            assert_eq!(limit, 1000);
+            assert_eq!(max_allowed, 10);
+        }
+    }
+
+    #[test]
+    fn test_reject_position_limit_short() {
+        let checker = default_checker();
+        // Current short = -990, order sells 100 → net -1090; abs > 1000.
+        let position = make_position(-990);
+        let order = make_order(Side::Ask, 1000, 100);
+        let result = checker.check_order(&order, Some(&position));
+        assert!(
+            matches!(result, Err(RiskReject::PositionLimitBreached { .. })),
+            "expected PositionLimitBreached, got {:?}",
+            result
+        );
+        if let Err(RiskReject::PositionLimitBreached {
+            current,
+            after,
+            limit,
+            max_allowed,
+        }) = result
+        {
+            assert_eq!(current, -990);
+            assert_eq!(after, -1090);
+            assert_eq!(limit, 1000);
+            assert_eq!(max_allowed, 10);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Notional
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_reject_notional() {
+        let checker = default_checker();
+        // price 10_000_000 * quantity 100 = 1_000_000_000 > max_notional 100_000_000.
+        let order = make_order(Side::Bid, 10_000_000, 100);
+        let result = checker.check_order(&order, None);
+        assert!(
+            matches!(result, Err(RiskReject::NotionalExceeded { .. })),
+            "expected NotionalExceeded, got {:?}",
+            result
+        );
+        if let Err(RiskReject::NotionalExceeded {
+            notional,
+            limit,
+            max_allowed,
+        }) = result
+        {
+            assert_eq!(notional, 1_000_000_000);
+            assert_eq!(limit, 100_000_000);
+            assert_eq!(max_allowed, 10);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Max open orders
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_reject_max_orders() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 2,
+            ..RiskLimits::default()
+        });
+        rest_orders(&mut checker, 2);
+
+        let order = make_order(Side::Bid, 1000, 1);
+        let result = checker.check_order(&order, None);
+        assert!(
+            matches!(result, Err(RiskReject::MaxOpenOrdersReached { .. })),
+            "expected MaxOpenOrdersReached, got {:?}",
+            result
+        );
+        if let Err(RiskReject::MaxOpenOrdersReached { count, limit }) = result {
+            assert_eq!(count, 2);
+            assert_eq!(limit, 2);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Daily loss
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_reject_daily_loss() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_daily_loss: -1000,
+            ..RiskLimits::default()
+        });
+        checker.update_daily_pnl(-1000);
+
+        let order = make_order(Side::Bid, 1000, 1);
+        let result = checker.check_order(&order, None);
+        assert!(
+            matches!(result, Err(RiskReject::DailyLossLimitHit { .. })),
+            "expected DailyLossLimitHit, got {:?}",
+            result
+        );
+        if let Err(RiskReject::DailyLossLimitHit {
+            loss,
+            limit,
+            realized,
+            unrealized,
+        }) = result
+        {
+            assert_eq!(loss, -1000);
+            assert_eq!(limit, -1000);
+            assert_eq!((realized, unrealized), (-1000, 0));
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Circuit breaker
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_reject_circuit_breaker() {
+        let mut checker = default_checker();
+        checker.trip_circuit_breaker();
+
+        let order = make_order(Side::Bid, 1000, 1);
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+
+        // Reset should allow orders again.
+        checker.reset_circuit_breaker();
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    // -----------------------------------------------------------------------
+    // Daily reset
+    // -----------------------------------------------------------------------
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_reset_daily() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_daily_loss: -500,
+            max_open_orders: 2,
+            ..RiskLimits::default()
+        });
+        checker.update_daily_pnl(-500);
+        checker.increment_open_orders();
+        checker.increment_open_orders();
+
+        // Both counters should be at their limits.
+        let order = make_order(Side::Bid, 1000, 1);
+        assert!(checker.check_order(&order, None).is_err());
+
+        // After daily reset, both counters are cleared.
+        checker.reset_daily();
+        assert!(checker.check_order(&order, None).is_ok());
+
+        // Circuit breaker is NOT cleared by reset_daily.
+        checker.trip_circuit_breaker();
+        checker.reset_daily();
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+    }
+
+    // -------------------------------------------------------------------
+    // Order size boundary
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_order_size_at_exact_limit_passes() {
+        let checker = default_checker();
+        // max_order_size = 100; exactly 100 should pass (<= not <).
+        let order = make_order(Side::Bid, 1000, 100);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // Position limit boundary
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_position_at_exact_limit_passes() {
+        let checker = default_checker();
+        // Current = 900, bid +100 → net 1000 = max_position: should pass.
+        let position = make_position(900);
+        let order = make_order(Side::Bid, 1000, 100);
+        assert!(checker.check_order(&order, Some(&position)).is_ok());
+    }
+
+    #[test]
+    fn test_position_one_over_limit_rejects() {
+        let checker = default_checker();
+        // Current = 901, bid +100 → net 1001 > 1000: reject.
+        let position = make_position(901);
+        let order = make_order(Side::Bid, 1000, 100);
+        let result = checker.check_order(&order, Some(&position));
+        assert!(matches!(
+            result,
+            Err(RiskReject::PositionLimitBreached { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bid_reduces_short_position_within_limit() {
+        let checker = default_checker();
+        // Current short = -950, bid +100 → net = -850; abs = 850 < 1000: pass.
+        let position = make_position(-950);
+        let order = make_order(Side::Bid, 1000, 100);
+        assert!(checker.check_order(&order, Some(&position)).is_ok());
+    }
+
+    #[test]
+    fn test_ask_reduces_long_position_within_limit() {
+        let checker = default_checker();
+        // Current long = 950, ask -50 → net = 900; abs = 900 < 1000: pass.
+        let position = make_position(950);
+        let order = make_order(Side::Ask, 1000, 50);
+        assert!(checker.check_order(&order, Some(&position)).is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // Notional boundary
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_notional_at_exact_limit_passes() {
+        let checker = default_checker();
+        // max_notional = 100_000_000; price=1_000_000, qty=100 → notional = 100_000_000: pass.
+        let order = make_order(Side::Bid, 1_000_000, 100);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    #[test]
+    fn test_notional_one_over_limit_rejects() {
+        let checker = default_checker();
+        // notional = 100_000_001 > 100_000_000: reject.
+        let order = make_order(Side::Bid, 100_000_001, 1);
+        let result = checker.check_order(&order, None);
+        assert!(matches!(result, Err(RiskReject::NotionalExceeded { .. })));
+    }
+
+    #[test]
+    fn test_negative_price_uses_absolute_notional() {
+        let mut checker = default_checker();
+        // |-1_000_001| * 100 = 100_000_100 > 100_000_000: reject, with the
+        // headroom computed from the price magnitude.
+        let order = make_order(Side::Ask, -1_000_001, 100);
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::NotionalExceeded {
+                notional: 100_000_100,
+                limit: 100_000_000,
+                max_allowed: 99,
+            })
+        );
+        let small = make_order(Side::Ask, -1_000, 10);
+        assert!(checker.check_order(&small, None).is_ok());
+        checker.on_order_accepted(&small);
+        assert_eq!(checker.open_order_notional(), 10_000);
+    }
+
+    // -------------------------------------------------------------------
+    // Open orders boundary
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_open_orders_one_below_limit_passes() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 3,
+            ..RiskLimits::default()
+        });
+        rest_orders(&mut checker, 2);
+        // count=2 < limit=3: pass.
+        let order = make_order(Side::Bid, 1000, 1);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // Daily loss boundary
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_daily_loss_just_above_limit_passes() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_daily_loss: -1000,
+            ..RiskLimits::default()
+        });
+        checker.update_daily_pnl(-999);
+        // pnl=-999 > max_daily_loss=-1000: pass.
+        let order = make_order(Side::Bid, 1000, 1);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    #[test]
+    fn test_daily_loss_below_limit_rejects() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_daily_loss: -1000,
+            ..RiskLimits::default()
+        });
+        checker.update_daily_pnl(-1001);
+        // pnl=-1001 <= -1000: reject.
+        let order = make_order(Side::Bid, 1000, 1);
+        assert!(matches!(
+            checker.check_order(&order, None),
+            Err(RiskReject::DailyLossLimitHit { .. })
+        ));
+    }
+
+    // -------------------------------------------------------------------
+    // State management
+    // -------------------------------------------------------------------
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_increment_decrement_open_orders() {
+        let mut checker = default_checker();
+        assert_eq!(checker.open_order_count(), 0);
+        checker.increment_open_orders();
+        checker.increment_open_orders();
+        assert_eq!(checker.open_order_count(), 2);
+        checker.decrement_open_orders();
+        assert_eq!(checker.open_order_count(), 1);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_decrement_open_orders_saturates_at_zero() {
+        let mut checker = default_checker();
+        checker.decrement_open_orders();
+        // Should saturate to 0, not underflow.
+        assert_eq!(checker.open_order_count(), 0);
+    }
+
+    #[test]
+    fn test_update_daily_pnl_accumulates() {
+        let mut checker = default_checker();
+        checker.update_daily_pnl(100);
+        checker.update_daily_pnl(-50);
+        checker.update_daily_pnl(25);
+        assert_eq!(checker.daily_pnl(), 75);
+    }
+
+    #[test]
+    fn test_loss_basis_selects_components() {
+        let limits = RiskLimits {
+            max_daily_loss: -1000,
+            ..RiskLimits::default()
+        };
+        let order = make_order(Side::Bid, 1000, 1);
+        let mut combined = PreTradeChecker::new(limits.clone());
+        let mut realized =
+            PreTradeChecker::new(limits.clone()).with_loss_basis(LossBasis::Realized);
+        let mut unrealized = PreTradeChecker::new(limits).with_loss_basis(LossBasis::Unrealized);
+        for checker in [&mut combined, &mut realized, &mut unrealized] {
+            checker.update_daily_pnl(-600);
+            checker.set_unrealized_pnl(-500);
+        }
+        assert_eq!(combined.daily_pnl(), -600);
+        assert_eq!(combined.combined_pnl(), -1100);
+        assert_eq!(
+            combined.check_order(&order, None),
+            Err(RiskReject::DailyLossLimitHit {
+                loss: -1100,
+                limit: -1000,
+                realized: -600,
+                unrealized: -500,
+            })
+        );
+        assert!(realized.check_order(&order, None).is_ok());
+        assert!(unrealized.check_order(&order, None).is_ok());
+
+        unrealized.set_unrealized_pnl(-1000);
+        assert!(unrealized.check_order(&order, None).is_err());
+        let u = unrealized.utilization();
+        assert_eq!(
+            (u.daily_pnl, u.realized_pnl, u.unrealized_pnl),
+            (-1000, -600, -1000)
+        );
+        assert_eq!(u.daily_loss_bps(), 10_000);
+
+        unrealized.reset_daily();
+        assert_eq!(unrealized.daily_pnl(), 0);
+    }
+
+    #[test]
+    fn test_is_circuit_breaker_tripped_accessor() {
+        let mut checker = default_checker();
+        assert!(!checker.is_circuit_breaker_tripped());
+        checker.trip_circuit_breaker();
+        assert!(checker.is_circuit_breaker_tripped());
+        checker.reset_circuit_breaker();
+        assert!(!checker.is_circuit_breaker_tripped());
+    }
+
+    // -------------------------------------------------------------------
+    // Check ordering: circuit breaker has highest priority
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_circuit_breaker_priority_over_other_rejects() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_order_size: 1,
+            max_daily_loss: 0,
+            ..RiskLimits::default()
+        });
+        // Multiple violations: order too large, daily loss hit, AND circuit breaker.
+        checker.update_daily_pnl(-1);
+        checker.trip_circuit_breaker();
+
+        let order = make_order(Side::Bid, 1000, 100);
+        // Circuit breaker should be returned, not OrderSizeTooLarge.
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+    }
+
+    // -------------------------------------------------------------------
+    // No position: defaults to zero net
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_no_position_large_bid_within_limit() {
+        let checker = default_checker();
+        // No position (None) → net=0, bid+100 → net=100, abs < 1000: pass.
+        let order = make_order(Side::Bid, 500, 100);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    #[test]
+    fn test_no_position_large_ask_within_limit() {
+        let checker = default_checker();
+        // No position, ask-100 → net=-100, abs < 1000: pass.
+        let order = make_order(Side::Ask, 500, 100);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // Zero-size order passes size and notional checks
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_zero_quantity_order_passes() {
+        let checker = default_checker();
+        // quantity=0 is <= max_order_size; notional=0 is <= max_notional; passes all.
+        let order = make_order(Side::Bid, 1_000_000, 0);
+        assert!(checker.check_order(&order, None).is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // update_daily_pnl saturates at i64::MIN without overflow
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_update_daily_pnl_saturates_at_min() {
+        let mut checker = default_checker();
+        checker.update_daily_pnl(i64::MIN);
+        // Adding any negative value to i64::MIN must not wrap — stays at i64::MIN.
+        checker.update_daily_pnl(-1);
+        assert_eq!(checker.daily_pnl(), i64::MIN);
+    }
+
+    // -------------------------------------------------------------------
+    // Order-size check fires before position-limit check (priority ordering)
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_order_size_rejects_before_position_limit() {
+        // Limits: tiny max_order_size=5, huge position limit.
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_order_size: 5,
+            max_position: u64::MAX,
+            max_notional: i64::MAX,
+            max_open_orders: u32::MAX,
+            max_daily_loss: i64::MIN + 1,
+            ..RiskLimits::default()
+        });
+        // quantity=10 violates order size; if position check ran first it would pass.
+        let order = make_order(Side::Bid, 1, 10);
+        let result = checker.check_order(&order, None);
+        assert!(
+            matches!(
+                result,
+                Err(RiskReject::OrderSizeTooLarge {
+                    size: 10,
+                    limit: 5,
+                    max_allowed: 5
+                })
+            ),
+            "expected OrderSizeTooLarge, got {:?}",
+            result
+        );
+    }
+
+    // -------------------------------------------------------------------
+    // Non-resting orders
+    // -------------------------------------------------------------------
+
+    fn make_tif_order(tif: TimeInForce, timestamp_ns: u64) -> Order {
+        Order {
+            time_in_force: tif,
+            timestamp_ns,
+            ..make_order(Side::Bid, 1000, 1)
+        }
+    }
+
+    #[test]
+    fn test_rests_on_book() {
+        assert!(rests_on_book(&make_tif_order(TimeInForce::GTC, 0)));
+        assert!(!rests_on_book(&make_tif_order(TimeInForce::IOC, 0)));
+        assert!(!rests_on_book(&make_tif_order(TimeInForce::FOK, 0)));
+    }
+
+    #[test]
+    fn test_ioc_ignores_open_order_limit() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 1,
+            ..RiskLimits::default()
+        });
+        checker.on_order_accepted(&make_tif_order(TimeInForce::GTC, 0));
+        assert_eq!(checker.open_order_count(), 1);
+        let gtc = make_tif_order(TimeInForce::GTC, 1);
+        assert!(matches!(
+            checker.check_order(&gtc, None),
+            Err(RiskReject::MaxOpenOrdersReached { .. })
+        ));
+        for tif in [TimeInForce::IOC, TimeInForce::FOK] {
+            let order = make_tif_order(tif, 1);
+            assert!(checker.check_order(&order, None).is_ok());
+            checker.on_order_accepted(&order);
+        }
+        assert_eq!(checker.open_order_count(), 1);
+    }
+
+    #[test]
+    fn test_non_resting_rate_limit_and_window_roll() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_non_resting_orders: 2,
+            non_resting_window_ns: 1_000,
+            ..RiskLimits::default()
+        });
+        for ts in [0, 10] {
+            checker.on_order_accepted(&make_tif_order(TimeInForce::IOC, ts));
+        }
+        let third = make_tif_order(TimeInForce::FOK, 999);
+        assert_eq!(
+            checker.check_order(&third, None),
+            Err(RiskReject::NonRestingRateExceeded { count: 2, limit: 2 })
+        );
+        // Resting orders are unaffected by the throughput window.
+        assert!(checker
+            .check_order(&make_tif_order(TimeInForce::GTC, 999), None)
+            .is_ok());
+        // The window has rolled by t = 1000.
+        let next = make_tif_order(TimeInForce::IOC, 1_000);
+        assert!(checker.check_order(&next, None).is_ok());
+        checker.on_order_accepted(&next);
+        checker.on_order_accepted(&make_tif_order(TimeInForce::IOC, 1_001));
+        assert!(checker
+            .check_order(&make_tif_order(TimeInForce::IOC, 1_002), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_non_resting_sustained_rate() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_non_resting_orders: 2,
+            non_resting_window_ns: 100,
+            max_non_resting_orders_sustained: 3,
+            non_resting_sustained_window_ns: 1_000,
+            ..RiskLimits::default()
+        });
+        // A burst of two passes; the sustained window caps the third one.
+        for ts in [0, 10, 100] {
+            let order = make_tif_order(TimeInForce::IOC, ts);
+            assert!(checker.check_order(&order, None).is_ok());
+            checker.on_order_accepted(&order);
+        }
+        let order = make_tif_order(TimeInForce::IOC, 200);
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::NonRestingRateExceeded { count: 3, limit: 3 })
+        );
+        assert_eq!(
+            checker.check_order_fast(&order, None),
+            checker.check_order(&order, None)
+        );
+        let state = checker.state();
+        let mut restored = PreTradeChecker::new(checker.limits().clone());
+        restored.restore_state(&state);
+        assert!(restored.check_order(&order, None).is_err());
+        assert!(checker
+            .check_order(&make_tif_order(TimeInForce::IOC, 1_000), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_non_resting_windows_follow_candidate_limits() {
+        let active = RiskLimits {
+            max_non_resting_orders: 2,
+            non_resting_window_ns: 100,
+            max_non_resting_orders_sustained: 3,
+            non_resting_sustained_window_ns: 1_000,
+            ..RiskLimits::default()
+        };
+        let mut checker = PreTradeChecker::new(active.clone());
+        for ts in [0, 10] {
+            let order = make_tif_order(TimeInForce::IOC, ts);
+            assert!(checker.check_order(&order, None).is_ok());
+            checker.on_order_accepted(&order);
+        }
+
+        // A longer candidate burst window still counts the first two orders.
+        let order = make_tif_order(TimeInForce::IOC, 150);
+        let longer = RiskLimits {
+            non_resting_window_ns: 1_000,
+            ..active.clone()
+        };
+        assert!(checker.check_order(&order, None).is_ok());
+        assert_eq!(
+            checker.check_order_with_limits(&longer, &order, None),
+            Err(RiskReject::NonRestingRateExceeded { count: 2, limit: 2 })
+        );
+
+        // A shorter candidate sustained window has already rolled over.
+        checker.on_order_accepted(&order);
+        let order = make_tif_order(TimeInForce::IOC, 300);
+        let shorter = RiskLimits {
+            non_resting_sustained_window_ns: 200,
+            ..active
+        };
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::NonRestingRateExceeded { count: 3, limit: 3 })
+        );
+        assert!(checker
+            .check_order_with_limits(&shorter, &order, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_non_resting_rate_disabled_and_daily_reset() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_non_resting_orders: 0,
+            ..RiskLimits::default()
+        });
+        let ioc = make_tif_order(TimeInForce::IOC, 5);
+        assert_eq!(
+            checker.check_order(&ioc, None).unwrap_err().limit_field(),
+            Some("max_non_resting_orders")
+        );
+        let mut limits = checker.limits().clone();
+        limits.checks.non_resting_rate = false;
+        checker.set_limits(limits);
+        assert!(checker.check_order(&ioc, None).is_ok());
+
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_non_resting_orders: 1,
+            ..RiskLimits::default()
+        });
+        checker.on_order_accepted(&ioc);
+        assert!(checker.check_order(&ioc, None).is_err());
+        checker.reset_daily();
+        assert!(checker.check_order(&ioc, None).is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // Tracked open orders
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_tracked_orders_count_and_close() {
+        let mut checker = default_checker();
+        let a = Order {
+            id: OrderId(7),
+            ..make_tif_order(TimeInForce::GTC, 0)
+        };
+        checker.on_order_accepted(&a);
+        checker.on_order_accepted(&a);
+        assert_eq!(checker.open_order_count(), 1);
+        assert!(checker.open_orders().contains(7));
+        assert!(checker.on_order_closed(7));
+        assert!(!checker.on_order_closed(7));
+        assert_eq!(checker.open_order_count(), 0);
+    }
+
+    #[test]
+    fn test_reset_daily_carries_gtc_orders() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 2,
+            ..RiskLimits::default()
+        });
+        for id in [1, 2] {
+            checker.on_order_accepted(&Order {
+                id: OrderId(id),
+                ..make_tif_order(TimeInForce::GTC, 0)
+            });
+        }
+        checker.reset_daily();
+        // Both GTC orders are still resting.
+        assert_eq!(checker.open_order_count(), 2);
+        assert_eq!(checker.utilization().open_orders, 2);
+        let next = make_tif_order(TimeInForce::GTC, 1);
+        assert!(matches!(
+            checker.check_order(&next, None),
+            Err(RiskReject::MaxOpenOrdersReached { count: 2, .. })
+        ));
+        checker.on_order_closed(1);
+        assert!(checker.check_order(&next, None).is_ok());
+    }
+
+    #[test]
+    fn test_open_order_notional_limit() {
+        // Each order: price 1000 x 10 = 10_000 notional.
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_order_notional: 25_000,
+            ..RiskLimits::default()
+        });
+        for id in [1, 2] {
+            checker.on_order_accepted(&Order {
+                id: OrderId(id),
+                quantity: 10,
+                ..make_tif_order(TimeInForce::GTC, 0)
+            });
+        }
+        assert_eq!(checker.open_order_notional(), 20_000);
+        let next = Order {
+            quantity: 10,
+            ..make_tif_order(TimeInForce::GTC, 1)
+        };
+        let result = checker.check_order(&next, None);
+        assert_eq!(
+            result,
+            Err(RiskReject::OpenOrderNotionalExceeded {
+                open: 20_000,
+                notional: 10_000,
+                limit: 25_000,
+                max_allowed: 5,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().limit_field(),
+            Some("max_open_order_notional")
+        );
+        // Non-resting orders never add working notional.
+        assert!(checker
+            .check_order(&make_tif_order(TimeInForce::IOC, 1), None)
+            .is_ok());
+        checker.on_order_closed(1);
+        assert!(checker.check_order(&next, None).is_ok());
+        assert_eq!(checker.utilization().open_order_notional_bps(), 4_000);
+    }
+
+    #[test]
+    fn test_open_order_notional_disabled_and_clamped() {
+        let mut limits = RiskLimits {
+            max_open_order_notional: 15_000,
+            ..RiskLimits::default()
+        };
+        let mut checker = PreTradeChecker::new(limits.clone()).with_sizing_mode(SizingMode::Clamp);
+        checker.on_order_accepted(&Order {
+            quantity: 10,
+            ..make_tif_order(TimeInForce::GTC, 0)
+        });
+        let next = Order {
+            id: OrderId(2),
+            quantity: 10,
+            ..make_tif_order(TimeInForce::GTC, 1)
+        };
+        match checker.check_or_clamp(&next, None) {
+            Ok(CheckOutcome::Clamped { order, requested }) => {
+                assert_eq!(order.quantity, 5);
+                assert_eq!(requested, 10);
+            }
+            other => panic!("expected clamp, got {other:?}"),
+        }
+        limits.checks.open_order_notional = false;
+        checker.set_limits(limits);
+        assert!(checker.check_order(&next, None).is_ok());
+        assert_eq!(checker.utilization().open_order_notional_bps(), 0);
+    }
+
+    fn symbol_position(symbol_hash: u64) -> Position {
+        Position {
+            symbol_hash,
+            net_quantity: 0,
+            avg_entry_price: 0,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_per_symbol_open_order_limit() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders_per_symbol: 2,
+            ..RiskLimits::default()
+        });
+        checker.set_symbol_open_order_limit(9, 3);
+        for id in 0..3 {
+            checker.on_order_accepted_in(
+                &Order {
+                    id: OrderId(id),
+                    ..make_tif_order(TimeInForce::GTC, 0)
+                },
+                if id < 2 { 7 } else { 9 },
+            );
+        }
+        let next = make_tif_order(TimeInForce::GTC, 1);
+        let result = checker.check_order(&next, Some(&symbol_position(7)));
+        assert_eq!(
+            result,
+            Err(RiskReject::SymbolOpenOrdersReached {
+                symbol_hash: 7,
+                count: 2,
+                limit: 2,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().limit_field(),
+            Some("max_open_orders_per_symbol")
+        );
+        // Other symbols, overrides, unknown symbols and IOC orders are unaffected.
+        assert!(checker
+            .check_order(&next, Some(&symbol_position(9)))
+            .is_ok());
+        assert!(checker
+            .check_order(&next, Some(&symbol_position(8)))
+            .is_ok());
+        assert!(checker.check_order(&next, None).is_ok());
+        assert!(checker
+            .check_order(
+                &make_tif_order(TimeInForce::IOC, 1),
+                Some(&symbol_position(7))
+            )
+            .is_ok());
+        assert_eq!(checker.symbol_open_order_limit(9), 3);
+        checker.on_order_closed(0);
+        assert!(checker
+            .check_order(&next, Some(&symbol_position(7)))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_account_open_order_limit_takes_priority() {
+        let mut limits = RiskLimits {
+            max_open_orders: 1,
+            max_open_orders_per_symbol: 1,
+            ..RiskLimits::default()
+        };
+        let mut checker = PreTradeChecker::new(limits.clone());
+        checker.on_order_accepted_in(&make_tif_order(TimeInForce::GTC, 0), 7);
+        let next = make_tif_order(TimeInForce::GTC, 1);
+        let held = symbol_position(7);
+        assert!(matches!(
+            checker.check_order(&next, Some(&held)),
+            Err(RiskReject::MaxOpenOrdersReached { .. })
+        ));
+        limits.max_open_orders = 10;
+        limits.checks.open_orders_per_symbol = false;
+        checker.set_limits(limits);
+        assert!(checker.check_order(&next, Some(&held)).is_ok());
+    }
+
+    #[test]
+    fn test_stale_orders_reported() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_working_order_age_ns: 1_000,
+            ..RiskLimits::default()
+        });
+        checker.on_order_accepted_in(&make_tif_order(TimeInForce::GTC, 0), 7);
+        checker.on_order_accepted(&Order {
+            id: OrderId(2),
+            ..make_tif_order(TimeInForce::GTC, 500)
+        });
+        assert!(checker.stale_orders(1_000).is_empty());
+        assert_eq!(
+            checker.stale_orders(1_200),
+            vec![StaleOrder {
+                order_id: 1,
+                symbol_hash: Some(7),
+                age_ns: 1_200,
+                action: StaleAction::Warn,
+            }]
+        );
+        assert_eq!(checker.stale_orders(2_000).len(), 2);
+        checker.on_order_closed(1);
+        assert_eq!(checker.stale_orders(2_000)[0].order_id, 2);
+    }
+
+    #[test]
+    fn test_stale_action_and_disable() {
+        let mut limits = RiskLimits {
+            max_working_order_age_ns: 10,
+            ..RiskLimits::default()
+        };
+        let mut checker =
+            PreTradeChecker::new(limits.clone()).with_stale_action(StaleAction::RecommendCancel);
+        checker.on_order_accepted(&make_tif_order(TimeInForce::GTC, 0));
+        assert_eq!(
+            checker.stale_orders(100)[0].action,
+            StaleAction::RecommendCancel
+        );
+        // Stale orders are reported, never rejected.
+        assert!(checker
+            .check_order(&make_tif_order(TimeInForce::GTC, 100), None)
+            .is_ok());
+        limits.checks.stale_orders = false;
+        checker.set_limits(limits);
+        assert!(checker.stale_orders(100).is_empty());
+    }
+
+    #[test]
+    fn test_non_resting_orders_not_tracked() {
+        let mut checker = default_checker();
+        checker.on_order_accepted(&make_tif_order(TimeInForce::IOC, 0));
+        assert!(checker.open_orders().is_empty());
+        assert_eq!(checker.open_order_count(), 0);
+    }
+
+    // -------------------------------------------------------------------
+    // BBO reasonability
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_bbo_rejects_buy_far_through_offer() {
+        let checker = default_checker();
+        let bbo = Bbo::new(990, 1000);
+        let buy = make_order(Side::Bid, 1051, 1);
+        let result = checker.check_order_with_bbo(&buy, None, Some(&bbo));
+        assert_eq!(
+            result,
+            Err(RiskReject::PriceThroughBbo {
+                price: 1051,
+                touch: 1000,
+                through_bps: 510,
+                limit_bps: 500,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().limit_field(),
+            Some("max_through_bbo_bps")
+        );
+        let near = make_order(Side::Bid, 1050, 1);
+        assert!(checker
+            .check_order_with_bbo(&near, None, Some(&bbo))
+            .is_ok());
+        assert!(checker.check_order_with_bbo(&buy, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_bbo_rejects_sell_far_through_bid() {
+        let checker = default_checker();
+        let bbo = Bbo::new(1000, 1010);
+        let sell = make_order(Side::Ask, 900, 1);
+        assert!(matches!(
+            checker.check_order_with_bbo(&sell, None, Some(&bbo)),
+            Err(RiskReject::PriceThroughBbo {
+                touch: 1000,
+                through_bps: 1_000,
+                ..
+            })
+        ));
+        // A passive sell above the offer is fine.
+        let passive = make_order(Side::Ask, 2000, 1);
+        assert!(checker
+            .check_order_with_bbo(&passive, None, Some(&bbo))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_bbo_skips_market_orders_and_disabled_check() {
+        let bbo = Bbo::new(990, 1000);
+        let mut market = make_order(Side::Bid, 5000, 1);
+        market.order_type = OrderType::Market;
+        assert!(default_checker()
+            .check_order_with_bbo(&market, None, Some(&bbo))
+            .is_ok());
+        let mut limits = RiskLimits::default();
+        limits.checks.bbo_reasonability = false;
+        let checker = PreTradeChecker::new(limits);
+        let buy = make_order(Side::Bid, 5000, 1);
+        assert!(checker.check_order_with_bbo(&buy, None, Some(&bbo)).is_ok());
+    }
+
+    #[test]
+    fn test_bbo_check_runs_after_limits() {
+        let mut checker = default_checker();
+        checker.trip_circuit_breaker();
+        let bbo = Bbo::new(990, 1000);
+        assert_eq!(
+            checker.check_order_with_bbo(&make_order(Side::Bid, 5000, 1), None, Some(&bbo)),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+    }
+
+    // -------------------------------------------------------------------
+    // Post-only orders
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_post_only_rejects_crossing_orders() {
+        let checker = default_checker();
+        let bbo = Bbo::new(990, 1000);
+        assert!(checker
+            .check_post_only_order(&make_order(Side::Bid, 999, 1), None, &bbo)
+            .is_ok());
+        assert!(checker
+            .check_post_only_order(&make_order(Side::Ask, 991, 1), None, &bbo)
+            .is_ok());
+        let result = checker.check_post_only_order(&make_order(Side::Bid, 1000, 1), None, &bbo);
+        assert_eq!(
+            result,
+            Err(RiskReject::PostOnlyWouldCross {
+                price: 1000,
+                touch: 1000,
+            })
+        );
+        assert_eq!(result.unwrap_err().limit_field(), None);
+        assert!(matches!(
+            checker.check_post_only_order(&make_order(Side::Ask, 990, 1), None, &bbo),
+            Err(RiskReject::PostOnlyWouldCross { touch: 990, .. })
+        ));
+    }
+
+    #[test]
+    fn test_post_only_market_and_missing_quote() {
+        let checker = default_checker();
+        let mut market = make_order(Side::Bid, 0, 1);
+        market.order_type = OrderType::Market;
+        assert!(matches!(
+            checker.check_post_only_order(&market, None, &Bbo::new(990, 1000)),
+            Err(RiskReject::PostOnlyWouldCross { .. })
+        ));
+        let no_offer = Bbo {
+            bid: Some(990),
+            ask: None,
+        };
+        assert!(checker
+            .check_post_only_order(&make_order(Side::Bid, 5000, 1), None, &no_offer)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_post_only_far_through_reports_bbo_reject_first() {
+        let checker = default_checker();
+        assert!(matches!(
+            checker.check_post_only_order(
+                &make_order(Side::Bid, 2000, 1),
+                None,
+                &Bbo::new(990, 1000)
+            ),
+            Err(RiskReject::PriceThroughBbo { .. })
+        ));
+    }
+
+    // -------------------------------------------------------------------
+    // Expiry restrictions
+    // -------------------------------------------------------------------
+
+    fn held(symbol_hash: u64, net_quantity: i64) -> Position {
+        Position {
+            net_quantity,
+            ..symbol_position(symbol_hash)
+        }
+    }
+
+    #[test]
+    fn test_expiry_blocks_new_positions_near_expiry() {
+        let checker = default_checker();
+        let fut = Instrument::expiring(7, 10 * DAY_NS, 9 * DAY_NS + DAY_NS / 2);
+        let mut open = make_order(Side::Bid, 1000, 1);
+        open.timestamp_ns = 8 * DAY_NS;
+        let result = checker.check_instrument_order(&open, Some(&held(7, 0)), &fut);
+        assert_eq!(
+            result,
+            Err(RiskReject::ExpiryRestricted {
+                symbol_hash: 7,
+                restriction: ExpiryRestriction::NoNewPositions,
+                expiry_ns: 10 * DAY_NS,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().limit_field(),
+            Some("expiry_no_open_days")
+        );
+        assert!(checker
+            .check_instrument_order(&open, Some(&held(7, 5)), &fut)
+            .is_ok());
+        open.timestamp_ns = 7 * DAY_NS;
+        assert!(checker
+            .check_instrument_order(&open, Some(&held(7, 0)), &fut)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_expiry_final_session_is_reduce_only() {
+        let checker = default_checker();
+        let fut = Instrument::expiring(7, 10 * DAY_NS, 9 * DAY_NS + DAY_NS / 2);
+        let mut add = make_order(Side::Bid, 1000, 1);
+        add.timestamp_ns = 9 * DAY_NS + DAY_NS / 2;
+        assert!(matches!(
+            checker.check_instrument_order(&add, Some(&held(7, 5)), &fut),
+            Err(RiskReject::ExpiryRestricted {
+                restriction: ExpiryRestriction::ReduceOnly,
+                ..
+            })
+        ));
+        let close = Order {
+            side: Side::Ask,
+            quantity: 5,
+            ..add.clone()
+        };
+        assert!(checker
+            .check_instrument_order(&close, Some(&held(7, 5)), &fut)
+            .is_ok());
     }
 
-    fn default_checker() -> PreTradeChecker {
-        PreTradeChecker::new(RiskLimits::default())
+    #[test]
+    fn test_expiry_disabled_and_perpetual() {
+        let mut limits = RiskLimits::default();
+        limits.checks.expiry = false;
+        let fut = Instrument::expiring(7, DAY_NS, DAY_NS);
+        let mut order = make_order(Side::Bid, 1000, 1);
+        order.timestamp_ns = 2 * DAY_NS;
+        assert!(PreTradeChecker::new(limits)
+            .check_instrument_order(&order, None, &fut)
+            .is_ok());
+        assert!(default_checker()
+            .check_instrument_order(&order, None, &Instrument::perpetual(7))
+            .is_ok());
+        // Ordinary limits still apply first.
+        let big = make_order(Side::Bid, 1000, 1_000);
+        assert!(matches!(
+            default_checker().check_instrument_order(&big, None, &Instrument::perpetual(7)),
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
     }
 
-    // -----------------------------------------------------------------------
-    // Happy path
-    // -----------------------------------------------------------------------
+    #[test]
+    fn test_spot_month_limit_near_first_notice() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            spot_month_position_limit: 10,
+            spot_month_days_before_notice: 5,
+            ..RiskLimits::default()
+        });
+        let fut = Instrument::expiring(7, 60 * DAY_NS, 59 * DAY_NS).deliverable(30 * DAY_NS);
+        let mut buy = make_order(Side::Bid, 1000, 5);
+        buy.timestamp_ns = 24 * DAY_NS;
+        assert!(checker
+            .check_instrument_order(&buy, Some(&held(7, 8)), &fut)
+            .is_ok());
+        buy.timestamp_ns = 25 * DAY_NS;
+        let result = checker.check_instrument_order(&buy, Some(&held(7, 8)), &fut);
+        assert_eq!(
+            result,
+            Err(RiskReject::SpotMonthLimitBreached {
+                symbol_hash: 7,
+                current: 8,
+                after: 13,
+                limit: 10,
+                max_allowed: 2,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().limit_field(),
+            Some("spot_month_position_limit")
+        );
+        // Reducing an over-limit position is still allowed.
+        let sell = Order {
+            side: Side::Ask,
+            ..buy.clone()
+        };
+        assert!(checker
+            .check_instrument_order(&sell, Some(&held(7, 40)), &fut)
+            .is_ok());
+    }
 
     #[test]
-    fn test_order_passes_all_checks() {
-        let checker = default_checker();
-        let order = make_order(Side::Bid, 1000, 10);
-        assert!(checker.check_order(&order, None).is_ok());
+    fn test_spot_month_ignores_cash_settled_and_disabled() {
+        let mut limits = RiskLimits {
+            spot_month_position_limit: 0,
+            ..RiskLimits::default()
+        };
+        let cash = Instrument::expiring(7, 60 * DAY_NS, 59 * DAY_NS);
+        let mut buy = make_order(Side::Bid, 1000, 5);
+        buy.timestamp_ns = 50 * DAY_NS;
+        assert!(PreTradeChecker::new(limits.clone())
+            .check_instrument_order(&buy, Some(&held(7, 1)), &cash)
+            .is_ok());
+        limits.checks.spot_month = false;
+        let deliverable = cash.deliverable(51 * DAY_NS);
+        assert!(PreTradeChecker::new(limits)
+            .check_instrument_order(&buy, Some(&held(7, 1)), &deliverable)
+            .is_ok());
     }
 
-    // -----------------------------------------------------------------------
-    // Order size
-    // -----------------------------------------------------------------------
+    // -------------------------------------------------------------------
+    // Maturity buckets
+    // -------------------------------------------------------------------
 
     #[test]
-    fn test_reject_order_size() {
-        let checker = default_checker();
-        // max_order_size = 100; quantity = 101 should fail.
-        let order = make_order(Side::Bid, 1000, 101);
-        let result = checker.check_order(&order, None);
-        assert!(
-            matches!(result, Err(RiskReject::OrderSizeTooLarge { .. })),
-            "expected OrderSizeTooLarge, got {:?}",
-            result
+    fn test_maturity_bucket_caps_outright_not_spread() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            front_month_days: 30,
+            max_front_month_position: 100,
+            max_back_month_position: 50,
+            ..RiskLimits::default()
+        });
+        let m1 = Instrument::expiring(1, 10 * DAY_NS, 0).of_product(9);
+        let m2 = Instrument::expiring(2, 25 * DAY_NS, 0).of_product(9);
+        let m6 = Instrument::expiring(3, 180 * DAY_NS, 0).of_product(9);
+        // Large calendar spread inside the front bucket nets to +10.
+        let holdings = [(m1, 900), (m2, -890)];
+        let buy = make_order(Side::Bid, 1000, 80);
+        assert!(checker.check_maturity_buckets(&buy, &m2, &holdings).is_ok());
+        let too_big = make_order(Side::Bid, 1000, 91);
+        let result = checker.check_maturity_buckets(&too_big, &m1, &holdings);
+        assert_eq!(
+            result,
+            Err(RiskReject::MaturityBucketLimitBreached {
+                product_hash: 9,
+                bucket: MaturityBucket::Front,
+                current: 10,
+                after: 101,
+                limit: 100,
+            })
         );
-        if let Err(RiskReject::OrderSizeTooLarge { size, limit }) = result {
-            assert_eq!(size, 101);
-            assert_eq!(limit, 100);
-        }
+        assert_eq!(
+            result.unwrap_err().limit_field(),
+            Some("max_front_month_position")
+        );
+        // The back bucket has its own, tighter limit.
+        let back = make_order(Side::Ask, 1000, 51);
+        assert!(matches!(
+            checker.check_maturity_buckets(&back, &m6, &holdings),
+            Err(RiskReject::MaturityBucketLimitBreached {
+                bucket: MaturityBucket::Back,
+                ..
+            })
+        ));
     }
 
-    // -----------------------------------------------------------------------
-    // Position limit
-    // -----------------------------------------------------------------------
+    #[test]
+    fn test_maturity_bucket_allows_reducing_and_disabled() {
+        let mut limits = RiskLimits {
+            max_long_dated_position: 10,
+            ..RiskLimits::default()
+        };
+        let far = Instrument::expiring(1, 400 * DAY_NS, 0);
+        let holdings = [(far, 50)];
+        let sell = make_order(Side::Ask, 1000, 5);
+        assert!(PreTradeChecker::new(limits.clone())
+            .check_maturity_buckets(&sell, &far, &holdings)
+            .is_ok());
+        let buy = make_order(Side::Bid, 1000, 5);
+        assert!(PreTradeChecker::new(limits.clone())
+            .check_maturity_buckets(&buy, &far, &holdings)
+            .is_err());
+        limits.checks.maturity_buckets = false;
+        assert!(PreTradeChecker::new(limits)
+            .check_maturity_buckets(&buy, &far, &holdings)
+            .is_ok());
+    }
+
+    // -------------------------------------------------------------------
+    // Iceberg orders
+    // -------------------------------------------------------------------
 
     #[test]
-    fn test_reject_position_limit_long() {
-        let checker = default_checker();
-        // Current long = 990, order adds 100 → net 1090 > max 1000.
-        let position = make_position(990);
-        let order = make_order(Side::Bid, 1000, 100);
-        let result = checker.check_order(&order, Some(&position));
-        assert!(
-            matches!(result, Err(RiskReject::PositionLimitBreached { .. })),
-            "expected PositionLimitBreached, got {:?}",
-            result
-        );
-        if let Err(RiskReject::PositionLimitBreached {
-            current,
-            after,
-            limit,
-        }) = result
-        {
-            assert_eq!(current, 990);
-            assert_eq!(after, 1090);
-            assert_eq!(limit, 1000);
+    fn test_iceberg_display_validation() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            min_display_quantity: 5,
+            ..RiskLimits::default()
+        });
+        let order = make_order(Side::Bid, 1000, 50);
+        assert!(checker.check_iceberg_order(&order, None, 10).is_ok());
+        assert!(checker.check_iceberg_order(&order, None, 50).is_ok());
+        for display in [0, 4, 51] {
+            let result = checker.check_iceberg_order(&order, None, display);
+            assert_eq!(
+                result,
+                Err(RiskReject::InvalidIceberg {
+                    display,
+                    quantity: 50,
+                    min_display: 5,
+                })
+            );
+            assert_eq!(
+                result.unwrap_err().limit_field(),
+                Some("min_display_quantity")
+            );
         }
     }
 
     #[test]
-    fn test_reject_position_limit_short() {
-        let checker = default_checker();
-        // Current short = -990, order sells 100 → net -1090; abs > 1000.
-        let position = make_position(-990);
-        let order = make_order(Side::Ask, 1000, 100);
-        let result = checker.check_order(&order, Some(&position));
-        assert!(
-            matches!(result, Err(RiskReject::PositionLimitBreached { .. })),
-            "expected PositionLimitBreached, got {:?}",
-            result
+    fn test_iceberg_hidden_size_counts_fully() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_notional: 20_000,
+            ..RiskLimits::default()
+        });
+        // Only 5 lots shown, but all 50 count toward the notional.
+        let order = make_order(Side::Bid, 1000, 50);
+        assert!(matches!(
+            checker.check_iceberg_order(&order, None, 5),
+            Err(RiskReject::NotionalExceeded {
+                notional: 50_000,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_iceberg_check_disabled_and_breaker() {
+        let mut limits = RiskLimits::default();
+        limits.checks.iceberg = false;
+        let mut checker = PreTradeChecker::new(limits);
+        let order = make_order(Side::Bid, 1000, 10);
+        assert!(checker.check_iceberg_order(&order, None, 0).is_ok());
+        checker.trip_circuit_breaker();
+        assert_eq!(
+            checker.check_iceberg_order(&order, None, 0),
+            Err(RiskReject::CircuitBreakerTripped)
         );
-        if let Err(RiskReject::PositionLimitBreached {
-            current,
-            after,
-            limit,
-        }) = result
-        {
-            assert_eq!(current, -990);
-            assert_eq!(after, -1090);
-            assert_eq!(limit, 1000);
-        }
     }
 
-    // -----------------------------------------------------------------------
-    // Notional
-    // -----------------------------------------------------------------------
+    // -------------------------------------------------------------------
+    // Market context
+    // -------------------------------------------------------------------
+
+    fn market_with(symbol_hash: u64, market: SymbolMarket) -> MarketSnapshot {
+        let mut snapshot = MarketSnapshot::new();
+        snapshot.insert(symbol_hash, market);
+        snapshot
+    }
 
     #[test]
-    fn test_reject_notional() {
+    fn test_market_context_supplies_bbo() {
         let checker = default_checker();
-        // price 10_000_000 * quantity 100 = 1_000_000_000 > max_notional 100_000_000.
-        let order = make_order(Side::Bid, 10_000_000, 100);
-        let result = checker.check_order(&order, None);
-        assert!(
-            matches!(result, Err(RiskReject::NotionalExceeded { .. })),
-            "expected NotionalExceeded, got {:?}",
-            result
+        let market = market_with(
+            7,
+            SymbolMarket {
+                bbo: Some(Bbo::new(990, 1000)),
+                ..Default::default()
+            },
         );
-        if let Err(RiskReject::NotionalExceeded { notional, limit }) = result {
-            assert_eq!(notional, 1_000_000_000);
-            assert_eq!(limit, 100_000_000);
-        }
+        let buy = make_order(Side::Bid, 2000, 1);
+        let held = symbol_position(7);
+        assert!(matches!(
+            checker.check_order_in_market(&buy, Some(&held), &market),
+            Err(RiskReject::PriceThroughBbo { touch: 1000, .. })
+        ));
+        // No position: symbol unknown, plain check.
+        assert!(checker.check_order_in_market(&buy, None, &market).is_ok());
+        // No data for the symbol.
+        let other = symbol_position(8);
+        assert!(checker
+            .check_order_in_market(&buy, Some(&other), &market)
+            .is_ok());
     }
 
-    // -----------------------------------------------------------------------
-    // Max open orders
-    // -----------------------------------------------------------------------
-
     #[test]
-    fn test_reject_max_orders() {
-        let mut checker = PreTradeChecker::new(RiskLimits {
-            max_open_orders: 2,
+    fn test_market_order_priced_from_context() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_notional: 50_000,
             ..RiskLimits::default()
         });
-        checker.increment_open_orders();
-        checker.increment_open_orders();
-
-        let order = make_order(Side::Bid, 1000, 1);
-        let result = checker.check_order(&order, None);
-        assert!(
-            matches!(result, Err(RiskReject::MaxOpenOrdersReached { .. })),
-            "expected MaxOpenOrdersReached, got {:?}",
-            result
+        let mut order = make_order(Side::Bid, 0, 10);
+        order.order_type = OrderType::Market;
+        let held = symbol_position(7);
+        let quoted = market_with(
+            7,
+            SymbolMarket {
+                reference_price: Some(1_000),
+                bbo: Some(Bbo::new(5_990, 6_000)),
+                ..Default::default()
+            },
         );
-        if let Err(RiskReject::MaxOpenOrdersReached { count, limit }) = result {
-            assert_eq!(count, 2);
-            assert_eq!(limit, 2);
-        }
+        assert!(matches!(
+            checker.check_order_in_market(&order, Some(&held), &quoted),
+            Err(RiskReject::NotionalExceeded {
+                notional: 60_000,
+                ..
+            })
+        ));
+        let reference_only = market_with(
+            7,
+            SymbolMarket {
+                reference_price: Some(1_000),
+                ..Default::default()
+            },
+        );
+        assert!(checker
+            .check_order_in_market(&order, Some(&held), &reference_only)
+            .is_ok());
+        // Works through a trait object too.
+        let dynamic: &dyn MarketContext = &quoted;
+        assert!(checker
+            .check_order_in_market(&order, Some(&held), dynamic)
+            .is_err());
     }
 
-    // -----------------------------------------------------------------------
-    // Daily loss
-    // -----------------------------------------------------------------------
+    // -------------------------------------------------------------------
+    // Sizing mode
+    // -------------------------------------------------------------------
+
+    fn clamping_checker() -> PreTradeChecker {
+        default_checker().with_sizing_mode(SizingMode::Clamp)
+    }
 
     #[test]
-    fn test_reject_daily_loss() {
-        let mut checker = PreTradeChecker::new(RiskLimits {
-            max_daily_loss: -1000,
-            ..RiskLimits::default()
-        });
-        checker.update_daily_pnl(-1000);
+    fn test_default_sizing_mode_rejects() {
+        let checker = default_checker();
+        assert_eq!(checker.sizing_mode(), SizingMode::Reject);
+        let order = make_order(Side::Bid, 1000, 101);
+        assert!(matches!(
+            checker.check_or_clamp(&order, None),
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
+        let ok = make_order(Side::Bid, 1000, 1);
+        assert!(!checker.check_or_clamp(&ok, None).unwrap().is_adjusted());
+    }
 
-        let order = make_order(Side::Bid, 1000, 1);
-        let result = checker.check_order(&order, None);
-        assert!(
-            matches!(result, Err(RiskReject::DailyLossLimitHit { .. })),
-            "expected DailyLossLimitHit, got {:?}",
-            result
-        );
-        if let Err(RiskReject::DailyLossLimitHit { loss, limit }) = result {
-            assert_eq!(loss, -1000);
-            assert_eq!(limit, -1000);
+    #[test]
+    fn test_clamp_order_size() {
+        let order = make_order(Side::Bid, 1000, 150);
+        match clamping_checker().check_or_clamp(&order, None) {
+            Ok(CheckOutcome::Clamped { order, requested }) => {
+                assert_eq!(order.quantity, 100);
+                assert_eq!(requested, 150);
+            }
+            other => panic!("expected Clamped, got {other:?}"),
         }
     }
 
-    // -----------------------------------------------------------------------
-    // Circuit breaker
-    // -----------------------------------------------------------------------
+    #[test]
+    fn test_clamp_applies_tightest_limit() {
+        // Size allows 100, position headroom 30, notional allows 50.
+        let position = make_position(970);
+        let order = make_order(Side::Bid, 2_000_000, 150);
+        let outcome = clamping_checker()
+            .check_or_clamp(&order, Some(&position))
+            .unwrap();
+        let CheckOutcome::Clamped { order, .. } = outcome else {
+            panic!("expected Clamped");
+        };
+        assert_eq!(order.quantity, 30);
+        assert!(clamping_checker()
+            .check_order(&order, Some(&position))
+            .is_ok());
+    }
 
     #[test]
-    fn test_reject_circuit_breaker() {
-        let mut checker = default_checker();
-        checker.trip_circuit_breaker();
+    fn test_clamp_without_headroom_rejects() {
+        let position = make_position(1000);
+        let order = make_order(Side::Bid, 1000, 10);
+        assert!(matches!(
+            clamping_checker().check_or_clamp(&order, Some(&position)),
+            Err(RiskReject::PositionLimitBreached { max_allowed: 0, .. })
+        ));
+    }
 
-        let order = make_order(Side::Bid, 1000, 1);
+    #[test]
+    fn test_clamp_does_not_bypass_stateful_checks() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 1,
+            ..RiskLimits::default()
+        })
+        .with_sizing_mode(SizingMode::Clamp);
+        rest_orders(&mut checker, 1);
+        let order = make_order(Side::Bid, 1000, 500);
+        assert!(matches!(
+            checker.check_or_clamp(&order, None),
+            Err(RiskReject::MaxOpenOrdersReached { .. })
+        ));
+        checker.trip_circuit_breaker();
         assert_eq!(
-            checker.check_order(&order, None),
-            Err(RiskReject::CircuitBreakerTripped)
+            checker.check_or_clamp(&order, None).unwrap_err(),
+            RiskReject::CircuitBreakerTripped
         );
-
-        // Reset should allow orders again.
-        checker.reset_circuit_breaker();
-        assert!(checker.check_order(&order, None).is_ok());
     }
 
-    // -----------------------------------------------------------------------
-    // Daily reset
-    // -----------------------------------------------------------------------
-
     #[test]
-    fn test_reset_daily() {
-        let mut checker = PreTradeChecker::new(RiskLimits {
-            max_daily_loss: -500,
-            max_open_orders: 2,
-            ..RiskLimits::default()
-        });
-        checker.update_daily_pnl(-500);
-        checker.increment_open_orders();
-        checker.increment_open_orders();
+    fn test_clamp_cannot_fix_position_beyond_limit() {
+        // Short beyond the limit: a small buy does not bring it back inside.
+        let position = make_position(-1500);
+        let order = make_order(Side::Bid, 1000, 50);
+        assert!(matches!(
+            clamping_checker().check_or_clamp(&order, Some(&position)),
+            Err(RiskReject::PositionLimitBreached { .. })
+        ));
+    }
 
-        // Both counters should be at their limits.
-        let order = make_order(Side::Bid, 1000, 1);
-        assert!(checker.check_order(&order, None).is_err());
+    // -------------------------------------------------------------------
+    // Arithmetic mode
+    // -------------------------------------------------------------------
 
-        // After daily reset, both counters are cleared.
-        checker.reset_daily();
-        assert!(checker.check_order(&order, None).is_ok());
+    fn unlimited() -> RiskLimits {
+        RiskLimits {
+            max_order_size: u64::MAX,
+            max_position: u64::MAX,
+            max_notional: i64::MAX,
+            max_open_orders: u32::MAX,
+            max_daily_loss: i64::MIN + 1,
+            max_open_order_notional: i64::MAX,
+            ..RiskLimits::default()
+        }
+    }
 
-        // Circuit breaker is NOT cleared by reset_daily.
-        checker.trip_circuit_breaker();
-        checker.reset_daily();
+    #[test]
+    fn test_default_mode_is_saturating() {
         assert_eq!(
-            checker.check_order(&order, None),
-            Err(RiskReject::CircuitBreakerTripped)
+            default_checker().arithmetic_mode(),
+            ArithmeticMode::Saturating
         );
     }
 
-    // -------------------------------------------------------------------
-    // Order size boundary
-    // -------------------------------------------------------------------
+    #[test]
+    fn test_narrow_by_mode() {
+        let wide = i128::from(i64::MAX) + 1;
+        assert_eq!(ArithmeticMode::Saturating.narrow(wide), Ok(i64::MAX));
+        assert_eq!(ArithmeticMode::Saturating.narrow(-wide - 1), Ok(i64::MIN));
+        assert_eq!(
+            ArithmeticMode::Checked.narrow(wide),
+            Err(RiskReject::ArithmeticOverflow)
+        );
+        assert_eq!(ArithmeticMode::Checked.narrow(-5), Ok(-5));
+    }
 
     #[test]
-    fn test_order_size_at_exact_limit_passes() {
-        let checker = default_checker();
-        // max_order_size = 100; exactly 100 should pass (<= not <).
-        let order = make_order(Side::Bid, 1000, 100);
+    fn test_saturating_mode_caps_notional() {
+        let checker = PreTradeChecker::new(unlimited());
+        // i64::MAX * 2 saturates to i64::MAX, which equals max_notional: pass.
+        let order = make_order(Side::Bid, i64::MAX, 2);
         assert!(checker.check_order(&order, None).is_ok());
     }
 
-    // -------------------------------------------------------------------
-    // Position limit boundary
-    // -------------------------------------------------------------------
-
     #[test]
-    fn test_position_at_exact_limit_passes() {
-        let checker = default_checker();
-        // Current = 900, bid +100 → net 1000 = max_position: should pass.
-        let position = make_position(900);
-        let order = make_order(Side::Bid, 1000, 100);
-        assert!(checker.check_order(&order, Some(&position)).is_ok());
+    fn test_checked_mode_rejects_notional_overflow() {
+        let checker =
+            PreTradeChecker::new(unlimited()).with_arithmetic_mode(ArithmeticMode::Checked);
+        let order = make_order(Side::Bid, i64::MAX, 2);
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::ArithmeticOverflow)
+        );
     }
 
     #[test]
-    fn test_position_one_over_limit_rejects() {
-        let checker = default_checker();
-        // Current = 901, bid +100 → net 1001 > 1000: reject.
-        let position = make_position(901);
-        let order = make_order(Side::Bid, 1000, 100);
-        let result = checker.check_order(&order, Some(&position));
-        assert!(matches!(
-            result,
-            Err(RiskReject::PositionLimitBreached { .. })
-        ));
+    fn test_checked_mode_rejects_position_overflow() {
+        let checker =
+            PreTradeChecker::new(unlimited()).with_arithmetic_mode(ArithmeticMode::Checked);
+        let position = make_position(i64::MAX - 5);
+        let order = make_order(Side::Bid, 1, 10);
+        assert_eq!(
+            checker.check_order(&order, Some(&position)),
+            Err(RiskReject::ArithmeticOverflow)
+        );
     }
 
     #[test]
-    fn test_bid_reduces_short_position_within_limit() {
-        let checker = default_checker();
-        // Current short = -950, bid +100 → net = -850; abs = 850 < 1000: pass.
-        let position = make_position(-950);
-        let order = make_order(Side::Bid, 1000, 100);
-        assert!(checker.check_order(&order, Some(&position)).is_ok());
+    fn test_checked_mode_rejects_quantity_beyond_i64() {
+        let checker =
+            PreTradeChecker::new(unlimited()).with_arithmetic_mode(ArithmeticMode::Checked);
+        let order = make_order(Side::Ask, 1, u64::MAX);
+        assert_eq!(
+            checker.check_order(&order, None),
+            Err(RiskReject::ArithmeticOverflow)
+        );
     }
 
     #[test]
-    fn test_ask_reduces_long_position_within_limit() {
-        let checker = default_checker();
-        // Current long = 950, ask -50 → net = 900; abs = 900 < 1000: pass.
-        let position = make_position(950);
-        let order = make_order(Side::Ask, 1000, 50);
+    fn test_checked_mode_normal_order_passes() {
+        let checker = PreTradeChecker::new(RiskLimits::default())
+            .with_arithmetic_mode(ArithmeticMode::Checked);
+        let position = make_position(-500);
+        let order = make_order(Side::Ask, 1000, 100);
         assert!(checker.check_order(&order, Some(&position)).is_ok());
     }
 
     // -------------------------------------------------------------------
-    // Notional boundary
+    // Limits access and utilization
     // -------------------------------------------------------------------
 
     #[test]
-    fn test_notional_at_exact_limit_passes() {
-        let checker = default_checker();
-        // max_notional = 100_000_000; price=1_000_000, qty=100 → notional = 100_000_000: pass.
-        let order = make_order(Side::Bid, 1_000_000, 100);
-        assert!(checker.check_order(&order, None).is_ok());
+    fn test_set_limits_keeps_counters() {
+        let mut checker = default_checker();
+        rest_orders(&mut checker, 1);
+        checker.update_daily_pnl(-10);
+        checker.set_limits(RiskLimits {
+            max_order_size: 5,
+            ..RiskLimits::default()
+        });
+        assert_eq!(checker.limits().max_order_size, 5);
+        assert_eq!(checker.open_order_count(), 1);
+        assert_eq!(checker.daily_pnl(), -10);
     }
 
     #[test]
-    fn test_notional_one_over_limit_rejects() {
-        let checker = default_checker();
-        // notional = 100_000_001 > 100_000_000: reject.
-        let order = make_order(Side::Bid, 100_000_001, 1);
-        let result = checker.check_order(&order, None);
-        assert!(matches!(result, Err(RiskReject::NotionalExceeded { .. })));
+    fn test_utilization_snapshot() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 4,
+            max_daily_loss: -1000,
+            ..RiskLimits::default()
+        });
+        rest_orders(&mut checker, 1);
+        checker.update_daily_pnl(-250);
+        let u = checker.utilization();
+        assert_eq!(u.open_orders, 1);
+        assert_eq!(u.open_orders_bps(), 2_500);
+        assert_eq!(u.daily_loss_bps(), 2_500);
+        assert!(!u.circuit_breaker_tripped);
     }
 
-    // -------------------------------------------------------------------
-    // Open orders boundary
-    // -------------------------------------------------------------------
+    #[test]
+    fn test_utilization_profit_and_zero_limits() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 0,
+            max_daily_loss: 0,
+            ..RiskLimits::default()
+        });
+        checker.update_daily_pnl(100);
+        let u = checker.utilization();
+        assert_eq!(u.daily_loss_bps(), 0);
+        assert_eq!(u.open_orders_bps(), 0);
+        rest_orders(&mut checker, 1);
+        checker.update_daily_pnl(-200);
+        let u = checker.utilization();
+        assert_eq!(u.open_orders_bps(), 10_000);
+        assert_eq!(u.daily_loss_bps(), 10_000);
+    }
 
     #[test]
-    fn test_open_orders_one_below_limit_passes() {
+    fn test_utilization_disabled_checks_report_zero() {
         let mut checker = PreTradeChecker::new(RiskLimits {
-            max_open_orders: 3,
+            max_open_orders: 1,
+            checks: EnabledChecks {
+                open_orders: false,
+                daily_loss: false,
+                ..EnabledChecks::ALL
+            },
             ..RiskLimits::default()
         });
-        checker.increment_open_orders();
-        checker.increment_open_orders();
-        // count=2 < limit=3: pass.
-        let order = make_order(Side::Bid, 1000, 1);
-        assert!(checker.check_order(&order, None).is_ok());
+        rest_orders(&mut checker, 2);
+        checker.update_daily_pnl(-1_000_000);
+        let u = checker.utilization();
+        assert!(!u.checks.open_orders);
+        assert_eq!(u.open_orders_bps(), 0);
+        assert_eq!(u.daily_loss_bps(), 0);
     }
 
     // -------------------------------------------------------------------
-    // Daily loss boundary
+    // Check enable flags
     // -------------------------------------------------------------------
 
     #[test]
-    fn test_daily_loss_just_above_limit_passes() {
+    fn test_disabled_open_order_check_allows_past_limit() {
         let mut checker = PreTradeChecker::new(RiskLimits {
-            max_daily_loss: -1000,
+            max_open_orders: 1,
+            checks: EnabledChecks {
+                open_orders: false,
+                ..EnabledChecks::ALL
+            },
             ..RiskLimits::default()
         });
-        checker.update_daily_pnl(-999);
-        // pnl=-999 > max_daily_loss=-1000: pass.
+        rest_orders(&mut checker, 2);
         let order = make_order(Side::Bid, 1000, 1);
         assert!(checker.check_order(&order, None).is_ok());
     }
 
     #[test]
-    fn test_daily_loss_below_limit_rejects() {
-        let mut checker = PreTradeChecker::new(RiskLimits {
-            max_daily_loss: -1000,
+    fn test_each_disabled_check_is_skipped() {
+        let breaching = RiskLimits {
+            max_position: 0,
+            max_order_size: 0,
+            max_notional: 0,
+            max_open_orders: 0,
+            max_daily_loss: 0,
             ..RiskLimits::default()
-        });
-        checker.update_daily_pnl(-1001);
-        // pnl=-1001 <= -1000: reject.
-        let order = make_order(Side::Bid, 1000, 1);
+        };
+        let order = make_order(Side::Bid, 1000, 10);
+        let checker = PreTradeChecker::new(breaching.clone());
         assert!(matches!(
             checker.check_order(&order, None),
-            Err(RiskReject::DailyLossLimitHit { .. })
+            Err(RiskReject::OrderSizeTooLarge { .. })
         ));
-    }
-
-    // -------------------------------------------------------------------
-    // State management
-    // -------------------------------------------------------------------
-
-    #[test]
-    fn test_increment_decrement_open_orders() {
-        let mut checker = default_checker();
-        assert_eq!(checker.open_order_count(), 0);
-        checker.increment_open_orders();
-        checker.increment_open_orders();
-        assert_eq!(checker.open_order_count(), 2);
-        checker.decrement_open_orders();
-        assert_eq!(checker.open_order_count(), 1);
-    }
-
-    #[test]
-    fn test_decrement_open_orders_saturates_at_zero() {
-        let mut checker = default_checker();
-        checker.decrement_open_orders();
-        // Should saturate to 0, not underflow.
-        assert_eq!(checker.open_order_count(), 0);
-    }
-
-    #[test]
-    fn test_update_daily_pnl_accumulates() {
-        let mut checker = default_checker();
-        checker.update_daily_pnl(100);
-        checker.update_daily_pnl(-50);
-        checker.update_daily_pnl(25);
-        assert_eq!(checker.daily_pnl(), 75);
+        let checker = PreTradeChecker::new(RiskLimits {
+            checks: EnabledChecks::NONE,
+            ..breaching
+        });
+        assert!(checker.check_order(&order, None).is_ok());
     }
 
     #[test]
-    fn test_is_circuit_breaker_tripped_accessor() {
-        let mut checker = default_checker();
-        assert!(!checker.is_circuit_breaker_tripped());
-        checker.trip_circuit_breaker();
-        assert!(checker.is_circuit_breaker_tripped());
-        checker.reset_circuit_breaker();
-        assert!(!checker.is_circuit_breaker_tripped());
+    fn test_disabled_checks_skip_overflow_detection() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            checks: EnabledChecks::NONE,
+            ..RiskLimits::default()
+        })
+        .with_arithmetic_mode(ArithmeticMode::Checked);
+        let order = make_order(Side::Bid, i64::MAX, u64::MAX);
+        assert!(checker.check_order(&order, None).is_ok());
     }
 
-    // -------------------------------------------------------------------
-    // Check ordering: circuit breaker has highest priority
-    // -------------------------------------------------------------------
-
     #[test]
-    fn test_circuit_breaker_priority_over_other_rejects() {
+    fn test_circuit_breaker_not_disableable() {
         let mut checker = PreTradeChecker::new(RiskLimits {
-            max_order_size: 1,
-            max_daily_loss: 0,
+            checks: EnabledChecks::NONE,
             ..RiskLimits::default()
         });
-        // Multiple violations: order too large, daily loss hit, AND circuit breaker.
-        checker.update_daily_pnl(-1);
         checker.trip_circuit_breaker();
-
-        let order = make_order(Side::Bid, 1000, 100);
-        // Circuit breaker should be returned, not OrderSizeTooLarge.
+        let order = make_order(Side::Bid, 1000, 1);
         assert_eq!(
             checker.check_order(&order, None),
             Err(RiskReject::CircuitBreakerTripped)
@@ -678,75 +3733,115 @@ mod tests {
     }
 
     // -------------------------------------------------------------------
-    // No position: defaults to zero net
+    // Limit history
     // -------------------------------------------------------------------
 
     #[test]
-    fn test_no_position_large_bid_within_limit() {
+    fn test_initial_version_is_zero() {
         let checker = default_checker();
-        // No position (None) → net=0, bid+100 → net=100, abs < 1000: pass.
-        let order = make_order(Side::Bid, 500, 100);
-        assert!(checker.check_order(&order, None).is_ok());
+        assert_eq!(checker.limit_version(), 0);
+        assert!(checker.history().is_empty());
     }
 
     #[test]
-    fn test_no_position_large_ask_within_limit() {
-        let checker = default_checker();
-        // No position, ask-100 → net=-100, abs < 1000: pass.
-        let order = make_order(Side::Ask, 500, 100);
-        assert!(checker.check_order(&order, None).is_ok());
+    fn test_apply_limits_records_history() {
+        let mut checker = default_checker();
+        let tight = RiskLimits {
+            max_order_size: 5,
+            ..RiskLimits::default()
+        };
+        let v = checker.apply_limits(tight.clone(), "alice", 1_000);
+        assert_eq!(v, 1);
+        assert_eq!(checker.limit_version(), 1);
+        assert_eq!(checker.limits(), &tight);
+        let history = checker.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].version, 0);
+        assert_eq!(history[0].limits, RiskLimits::default());
+        assert_eq!(history[1].author, "alice");
+        assert_eq!(history[1].timestamp_ns, 1_000);
+        assert_eq!(history[1].rollback_of, None);
     }
 
-    // -------------------------------------------------------------------
-    // Zero-size order passes size and notional checks
-    // -------------------------------------------------------------------
-
     #[test]
-    fn test_zero_quantity_order_passes() {
-        let checker = default_checker();
-        // quantity=0 is <= max_order_size; notional=0 is <= max_notional; passes all.
-        let order = make_order(Side::Bid, 1_000_000, 0);
-        assert!(checker.check_order(&order, None).is_ok());
+    fn test_rollback_restores_and_appends() {
+        let mut checker = default_checker();
+        checker.apply_limits(
+            RiskLimits {
+                max_order_size: 5,
+                ..RiskLimits::default()
+            },
+            "alice",
+            1,
+        );
+        checker.apply_limits(
+            RiskLimits {
+                max_order_size: 1,
+                ..RiskLimits::default()
+            },
+            "bob",
+            2,
+        );
+        let v = checker.rollback_to(1, "carol", 3).unwrap();
+        assert_eq!(v, 3);
+        assert_eq!(checker.limits().max_order_size, 5);
+        let last = checker.history().last().unwrap();
+        assert_eq!(last.rollback_of, Some(1));
+        assert_eq!(last.author, "carol");
+
+        checker.rollback_to(0, "carol", 4).unwrap();
+        assert_eq!(checker.limits(), &RiskLimits::default());
+        assert_eq!(checker.limit_version(), 4);
     }
 
-    // -------------------------------------------------------------------
-    // update_daily_pnl saturates at i64::MIN without overflow
-    // -------------------------------------------------------------------
+    #[test]
+    fn test_rollback_to_zero_before_any_change() {
+        let mut checker = default_checker();
+        assert_eq!(checker.rollback_to(0, "ops", 1), Ok(1));
+        assert_eq!(checker.limits(), &RiskLimits::default());
+    }
 
     #[test]
-    fn test_update_daily_pnl_saturates_at_min() {
+    fn test_rollback_unknown_version() {
         let mut checker = default_checker();
-        checker.update_daily_pnl(i64::MIN);
-        // Adding any negative value to i64::MIN must not wrap — stays at i64::MIN.
-        checker.update_daily_pnl(-1);
-        assert_eq!(checker.daily_pnl(), i64::MIN);
+        assert_eq!(
+            checker.rollback_to(9, "ops", 1),
+            Err(LimitHistoryError::UnknownVersion { version: 9 })
+        );
+        assert_eq!(checker.limit_version(), 0);
     }
 
-    // -------------------------------------------------------------------
-    // Order-size check fires before position-limit check (priority ordering)
-    // -------------------------------------------------------------------
+    #[test]
+    fn test_set_limits_bumps_version() {
+        let mut checker = default_checker();
+        checker.set_limits(RiskLimits::default());
+        assert_eq!(checker.limit_version(), 1);
+        assert_eq!(checker.history()[1].author, "");
+    }
 
     #[test]
-    fn test_order_size_rejects_before_position_limit() {
-        // Limits: tiny max_order_size=5, huge position limit.
-        let checker = PreTradeChecker::new(RiskLimits {
-            max_order_size: 5,
-            max_position: u64::MAX,
-            max_notional: i64::MAX,
-            max_open_orders: u32::MAX,
-            max_daily_loss: i64::MIN + 1,
-        });
-        // quantity=10 violates order size; if position check ran first it would pass.
-        let order = make_order(Side::Bid, 1, 10);
-        let result = checker.check_order(&order, None);
-        assert!(
-            matches!(
-                result,
-                Err(RiskReject::OrderSizeTooLarge { size: 10, limit: 5 })
-            ),
-            "expected OrderSizeTooLarge, got {:?}",
-            result
+    fn test_decision_record_carries_version() {
+        let mut checker = default_checker();
+        let order = make_order(Side::Bid, 1000, 10);
+        let before = checker.decide(&order, None);
+        assert!(before.is_accepted());
+        assert_eq!(before.limit_version, 0);
+        assert_eq!(before.order_id, 1);
+
+        checker.apply_limits(
+            RiskLimits {
+                max_order_size: 5,
+                ..RiskLimits::default()
+            },
+            "alice",
+            1,
         );
+        let after = checker.decide(&order, None);
+        assert_eq!(after.limit_version, 1);
+        assert!(matches!(
+            after.result,
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
     }
 
     // -------------------------------------------------------------------
@@ -759,8 +3854,16 @@ mod tests {
         let b = RiskReject::CircuitBreakerTripped;
         assert_eq!(a, b);
 
-        let c = RiskReject::OrderSizeTooLarge { size: 10, limit: 5 };
-        let d = RiskReject::OrderSizeTooLarge { size: 10, limit: 5 };
+        let c = RiskReject::OrderSizeTooLarge {
+            size: 10,
+            limit: 5,
+            max_allowed: 5,
+        };
+        let d = RiskReject::OrderSizeTooLarge {
+            size: 10,
+            limit: 5,
+            max_allowed: 5,
+        };
         assert_eq!(c, d);
     }
 
@@ -770,6 +3873,7 @@ mod tests {
             current: 900,
             after: 1100,
             limit: 1000,
+            max_allowed: 100,
         };
         let debug = format!("{:?}", reject);
         assert!(debug.contains("PositionLimitBreached"));
@@ -777,16 +3881,71 @@ mod tests {
         assert!(debug.contains("1100"));
     }
 
+    #[test]
+    fn test_risk_reject_limit_field() {
+        let reject = RiskReject::OrderSizeTooLarge {
+            size: 10,
+            limit: 5,
+            max_allowed: 5,
+        };
+        assert_eq!(reject.limit_field(), Some("max_order_size"));
+        assert_eq!(RiskReject::CircuitBreakerTripped.limit_field(), None);
+        for field in [
+            RiskReject::PositionLimitBreached {
+                current: 0,
+                after: 0,
+                limit: 0,
+                max_allowed: 0,
+            },
+            RiskReject::NotionalExceeded {
+                notional: 0,
+                limit: 0,
+                max_allowed: 0,
+            },
+            RiskReject::MaxOpenOrdersReached { count: 0, limit: 0 },
+            RiskReject::DailyLossLimitHit {
+                loss: 0,
+                limit: 0,
+                realized: 0,
+                unrealized: 0,
+            },
+        ]
+        .iter()
+        .filter_map(RiskReject::limit_field)
+        {
+            assert!(RiskLimits::FIELDS.contains(&field));
+        }
+    }
+
     #[test]
     fn test_risk_reject_clone() {
         let original = RiskReject::NotionalExceeded {
             notional: 500,
             limit: 100,
+            max_allowed: 0,
         };
         let cloned = original.clone();
         assert_eq!(original, cloned);
     }
 
+    #[test]
+    fn test_check_order_with_limits_uses_live_counters() {
+        let mut checker = default_checker();
+        let mut resting = make_order(Side::Bid, 100, 1);
+        resting.id = OrderId(9);
+        checker.on_order_accepted(&resting);
+        let candidate = RiskLimits {
+            max_open_orders: 1,
+            ..RiskLimits::default()
+        };
+        let order = make_order(Side::Bid, 100, 1);
+        assert!(checker.check_order(&order, None).is_ok());
+        assert_eq!(
+            checker.check_order_with_limits(&candidate, &order, None),
+            Err(RiskReject::MaxOpenOrdersReached { count: 1, limit: 1 })
+        );
+    }
+
     // -------------------------------------------------------------------
     // Property-based tests
     // -------------------------------------------------------------------
@@ -809,6 +3968,7 @@ mod tests {
                 max_notional: i64::MAX,
                 max_open_orders: u32::MAX,
                 max_daily_loss: i64::MIN + 1,
+                ..RiskLimits::default()
             };
             let checker = PreTradeChecker::new(limits);
             let order = make_order(Side::Bid, 0, quantity);
@@ -820,6 +3980,39 @@ mod tests {
             );
         }
 
+        /// Resubmitting at a reject's `max_allowed` quantity passes the check
+        /// that produced it, and one lot more is still rejected.
+        #[test]
+        fn prop_max_allowed_is_tight(
+            price in 1i64..100_000i64,
+            quantity in 0u64..10_000u64,
+            current in -2_000i64..2_000i64,
+            side_is_bid in any::<bool>(),
+        ) {
+            let side = if side_is_bid { Side::Bid } else { Side::Ask };
+            let checker = default_checker();
+            let position = make_position(current);
+            let order = make_order(side, price, quantity);
+            let first = checker.check_order(&order, Some(&position));
+            let (kind, max_allowed) = match &first {
+                Err(
+                    e @ (RiskReject::OrderSizeTooLarge { max_allowed, .. }
+                    | RiskReject::PositionLimitBreached { max_allowed, .. }
+                    | RiskReject::NotionalExceeded { max_allowed, .. }),
+                ) => (core::mem::discriminant(e), *max_allowed),
+                _ => return Ok(()),
+            };
+            let check = |q| checker.check_order(&make_order(side, price, q), Some(&position));
+            // 0 means no quantity passes (e.g. already over the position limit).
+            if max_allowed > 0 {
+                if let Err(e) = check(max_allowed) {
+                    prop_assert_ne!(core::mem::discriminant(&e), kind);
+                }
+            }
+            // An earlier check may fire first, but the order never passes.
+            prop_assert!(check(max_allowed + 1).is_err());
+        }
+
         /// If circuit_breaker_tripped is set, check_order always returns
         /// CircuitBreakerTripped regardless of the order parameters.
         #[test]
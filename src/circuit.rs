@@ -79,8 +79,9 @@ impl CircuitBreaker {
         }
 
         // Check price deviation.
-        let deviation = (price - self.reference_price).abs();
-        if deviation > self.max_move {
+        // Prices may be negative; `abs_diff` cannot overflow for any pair.
+        let deviation = price.abs_diff(self.reference_price);
+        if self.max_move < 0 || deviation > self.max_move as u64 {
             self.tripped = true;
             return true;
         }
@@ -179,6 +180,20 @@ mod tests {
         assert!(cb.is_tripped());
     }
 
+    #[test]
+    fn test_negative_prices_measure_distance() {
+        let mut cb = make_cb();
+        cb.reset(-200, 0);
+        // -200 → 300 is a 500-tick move: at the limit, not through it.
+        assert!(!cb.on_fill(300, 1));
+        assert!(cb.on_fill(-701, 2));
+
+        // Extreme spreads do not overflow the deviation.
+        let mut wide = make_cb();
+        wide.reset(i64::MIN, 0);
+        assert!(wide.on_fill(i64::MAX, 1));
+    }
+
     #[test]
     fn test_no_trip_at_exact_max_move() {
         let mut cb = make_cb();
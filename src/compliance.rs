@@ -0,0 +1,783 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 規制対応のコントロール一式と証跡（アテステーション）。
+//!
+//! [`MarketAccessControls::sec_15c3_5`] は SEC Rule 15c3-5（Market Access
+//! Rule）が求める事前コントロールをまとめたプリセット:
+//!
+//! - 与信・資本: ポジション上限、未約定注文の想定元本合計、日次損失上限
+//! - 誤発注: 注文数量上限、1 注文の想定元本上限、BBO 妥当性
+//! - 重複注文: 同一銘柄・売買・価格・数量の短時間での再発注を拒否
+//! - キル機能: [`MarketAccessControls::kill`] で全注文を即時停止
+//!
+//! [`MarketAccessControls::mifid_rts6`] は `MiFID II` RTS 6 に沿った事前コントロール
+//! （価格カラー、最大注文金額、最大注文数量、最大メッセージレート、キル機能）の
+//! プロファイル。判定結果は [`DecisionTags`]（アルゴ ID・DEA フラグ）付きの
+//! [`DecisionRecord`] として記録でき、[`self_assessment`] で年次自己評価用に
+//! アルゴ別に集計できる。
+//!
+//! [`MarketAccessControls::attestation`] は、どのコントロールが有効で閾値が
+//! いくつかを [`Attestation`] として出力する。CEO 認証などの年次レビューに
+//! 添付できるよう、[`Attestation::to_csv`] でそのまま書き出せる。
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{DecisionRecord, DecisionTags, PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+use crate::market::Bbo;
+use crate::throttle::{RateWindow, WindowCounter};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// DuplicateGuard
+// ---------------------------------------------------------------------------
+
+/// 直近に受理した注文と同一内容の注文を検出する。
+#[derive(Debug, Clone)]
+pub struct DuplicateGuard {
+    window_ns: u64,
+    /// (銘柄, 売買, 価格, 数量, 注文 ID, 受理時刻)。古い順。
+    recent: VecDeque<(u64, Side, i64, u64, u64, u64)>,
+}
+
+impl DuplicateGuard {
+    /// `window_ns` 以内の同一注文を重複とみなす。0 で無効。
+    #[must_use]
+    pub const fn new(window_ns: u64) -> Self {
+        Self {
+            window_ns,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// 重複とみなす時間幅（ns）。
+    #[must_use]
+    pub const fn window_ns(&self) -> u64 {
+        self.window_ns
+    }
+
+    /// `symbol_hash` の `order` が直近の注文と重複していないか。
+    ///
+    /// # Errors
+    ///
+    /// 重複していれば [`RiskReject::DuplicateOrder`]。
+    pub fn check(&self, order: &Order, symbol_hash: u64) -> Result<(), RiskReject> {
+        if self.window_ns == 0 {
+            return Ok(());
+        }
+        let now = order.timestamp_ns;
+        let hit = self.recent.iter().rev().find(|&&(s, side, p, q, _, at)| {
+            s == symbol_hash
+                && side == order.side
+                && p == order.price
+                && q == order.quantity
+                && now.saturating_sub(at) <= self.window_ns
+        });
+        match hit {
+            Some(&(.., previous_id, at)) => Err(RiskReject::DuplicateOrder {
+                previous_id,
+                age_ns: now.saturating_sub(at),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 受理した注文を記録し、時間幅を過ぎた記録を捨てる。
+    pub fn record(&mut self, order: &Order, symbol_hash: u64) {
+        if self.window_ns == 0 {
+            return;
+        }
+        let now = order.timestamp_ns;
+        while let Some(&(.., at)) = self.recent.front() {
+            if now.saturating_sub(at) <= self.window_ns {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        self.recent.push_back((
+            symbol_hash,
+            order.side,
+            order.price,
+            order.quantity,
+            order.id.0,
+            now,
+        ));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MessageRate
+// ---------------------------------------------------------------------------
+
+/// 固定時間幅あたりの注文メッセージ数の上限。
+///
+/// [`Self::with_sustained`] で長い時間幅の持続上限も掛けられる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRate {
+    max_messages: u32,
+    window_ns: u64,
+    sustained: RateWindow,
+    burst_sent: WindowCounter,
+    sustained_sent: WindowCounter,
+}
+
+impl MessageRate {
+    /// `window_ns` あたり `max_messages` 件まで。
+    #[must_use]
+    pub const fn new(max_messages: u32, window_ns: u64) -> Self {
+        Self {
+            max_messages,
+            window_ns,
+            sustained: RateWindow::DISABLED,
+            burst_sent: WindowCounter::new(),
+            sustained_sent: WindowCounter::new(),
+        }
+    }
+
+    /// 上限なし。
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self::new(u32::MAX, 0)
+    }
+
+    /// 持続上限を設定する。
+    #[must_use]
+    pub const fn with_sustained(mut self, sustained: RateWindow) -> Self {
+        self.sustained = sustained;
+        self
+    }
+
+    /// 1 時間幅あたりの上限件数（バースト）。
+    #[must_use]
+    pub const fn max_messages(&self) -> u32 {
+        self.max_messages
+    }
+
+    /// 時間幅（ns）。
+    #[must_use]
+    pub const fn window_ns(&self) -> u64 {
+        self.window_ns
+    }
+
+    /// 持続上限。
+    #[must_use]
+    pub const fn sustained(&self) -> &RateWindow {
+        &self.sustained
+    }
+
+    /// `now_ns` のバーストの時間幅で送信済みの件数。
+    #[must_use]
+    pub const fn count_at(&self, now_ns: u64) -> u32 {
+        self.burst_sent.count_at(self.window_ns, now_ns)
+    }
+
+    /// `now_ns` にもう 1 件送れるか。
+    ///
+    /// # Errors
+    ///
+    /// バーストまたは持続の上限に達していれば
+    /// [`RiskReject::MessageRateExceeded`]。
+    pub const fn check(&self, now_ns: u64) -> Result<(), RiskReject> {
+        let count = self.count_at(now_ns);
+        if count >= self.max_messages {
+            return Err(RiskReject::MessageRateExceeded {
+                count,
+                limit: self.max_messages,
+            });
+        }
+        if let Some(count) = self.sustained.exceeded(&self.sustained_sent, now_ns, 1) {
+            return Err(RiskReject::MessageRateExceeded {
+                count,
+                limit: self.sustained.limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// `now_ns` に送ったメッセージ（新規・訂正・取消）を数える。
+    pub const fn record(&mut self, now_ns: u64) {
+        self.burst_sent.add(self.window_ns, now_ns, 1);
+        self.sustained_sent.add(self.sustained.window_ns, now_ns, 1);
+    }
+}
+
+/// 証跡に記載するメッセージレートの閾値（持続上限があれば併記）。
+fn message_rate_threshold(rate: &MessageRate) -> String {
+    let burst = format!("{}/{}ns", rate.max_messages(), rate.window_ns());
+    let sustained = rate.sustained();
+    if sustained.window_ns == 0 {
+        burst
+    } else {
+        format!("{burst};{}/{}ns", sustained.limit, sustained.window_ns)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Attestation
+// ---------------------------------------------------------------------------
+
+/// コントロールの分類。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCategory {
+    /// 与信・資本の閾値。
+    CreditCapital,
+    /// 誤発注の防止。
+    ErroneousOrder,
+    /// 重複注文の防止。
+    Duplicate,
+    /// メッセージレートの制限。
+    MessageRate,
+    /// キル（即時停止）機能。
+    Kill,
+}
+
+impl ControlCategory {
+    /// 報告書での表記。
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::CreditCapital => "credit_capital",
+            Self::ErroneousOrder => "erroneous_order",
+            Self::Duplicate => "duplicate",
+            Self::MessageRate => "message_rate",
+            Self::Kill => "kill",
+        }
+    }
+}
+
+/// 1 コントロールの状態。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlStatus {
+    /// 分類。
+    pub category: ControlCategory,
+    /// コントロール名（[`RiskLimits`] のフィールド名など）。
+    pub control: &'static str,
+    /// 有効か。
+    pub active: bool,
+    /// 閾値（閾値の無いコントロールは `None`）。
+    pub threshold: Option<String>,
+}
+
+/// 有効なコントロールと閾値の証跡。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attestation {
+    /// プロファイル名。
+    pub profile: &'static str,
+    /// 証跡作成時の制限バージョン。
+    pub limit_version: u64,
+    /// 各コントロールの状態。
+    pub controls: Vec<ControlStatus>,
+}
+
+impl Attestation {
+    /// すべてのコントロールが有効か。
+    #[must_use]
+    pub fn all_active(&self) -> bool {
+        self.controls.iter().all(|c| c.active)
+    }
+
+    /// 無効になっているコントロール名。
+    #[must_use]
+    pub fn inactive(&self) -> Vec<&'static str> {
+        self.controls
+            .iter()
+            .filter(|c| !c.active)
+            .map(|c| c.control)
+            .collect()
+    }
+
+    /// CSV（`category,control,active,threshold`）で書き出す。
+    ///
+    /// 先頭行はプロファイル名と制限バージョンのコメント行。
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut out = format!(
+            "# profile={} limit_version={}\ncategory,control,active,threshold\n",
+            self.profile, self.limit_version
+        );
+        for c in &self.controls {
+            let _ = writeln!(
+                out,
+                "{},{},{},{}",
+                c.category.as_str(),
+                c.control,
+                c.active,
+                c.threshold.as_deref().unwrap_or("")
+            );
+        }
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MarketAccessControls
+// ---------------------------------------------------------------------------
+
+/// 市場アクセス規制のコントロール一式。
+pub struct MarketAccessControls {
+    profile: &'static str,
+    checker: PreTradeChecker,
+    duplicates: DuplicateGuard,
+    messages: MessageRate,
+    killed_at_ns: Option<u64>,
+}
+
+impl MarketAccessControls {
+    /// SEC Rule 15c3-5 プリセット。
+    ///
+    /// `limits` の閾値を使い、規則が求めるチェック（ポジション・未約定注文の
+    /// 想定元本・日次損失・注文数量・想定元本・BBO 妥当性）を必ず有効にする。
+    /// `duplicate_window_ns` 以内の同一注文は重複として拒否する。
+    #[must_use]
+    pub fn sec_15c3_5(mut limits: RiskLimits, duplicate_window_ns: u64) -> Self {
+        let checks = &mut limits.checks;
+        checks.position = true;
+        checks.open_order_notional = true;
+        checks.daily_loss = true;
+        checks.order_size = true;
+        checks.notional = true;
+        checks.bbo_reasonability = true;
+        Self {
+            profile: "sec_15c3_5",
+            checker: PreTradeChecker::new(limits),
+            duplicates: DuplicateGuard::new(duplicate_window_ns),
+            messages: MessageRate::unlimited(),
+            killed_at_ns: None,
+        }
+    }
+
+    /// `MiFID II` RTS 6 プロファイル。
+    ///
+    /// 価格カラー（`max_through_bbo_bps`）、最大注文金額（`max_notional`）、
+    /// 最大注文数量（`max_order_size`）のチェックを必ず有効にし、`messages` で
+    /// 最大メッセージレートを課す。
+    #[must_use]
+    pub fn mifid_rts6(mut limits: RiskLimits, messages: MessageRate) -> Self {
+        let checks = &mut limits.checks;
+        checks.bbo_reasonability = true;
+        checks.notional = true;
+        checks.order_size = true;
+        Self {
+            profile: "mifid_rts6",
+            checker: PreTradeChecker::new(limits),
+            duplicates: DuplicateGuard::new(0),
+            messages,
+            killed_at_ns: None,
+        }
+    }
+
+    /// プロファイル名。
+    #[must_use]
+    pub const fn profile(&self) -> &'static str {
+        self.profile
+    }
+
+    /// 内部のチェッカー。
+    #[must_use]
+    pub const fn checker(&self) -> &PreTradeChecker {
+        &self.checker
+    }
+
+    /// 内部のチェッカー（可変）。
+    pub const fn checker_mut(&mut self) -> &mut PreTradeChecker {
+        &mut self.checker
+    }
+
+    /// `symbol_hash` の注文を全コントロールでチェックする。
+    ///
+    /// # Errors
+    ///
+    /// [`PreTradeChecker::check_order_with_bbo`] の拒否、
+    /// [`RiskReject::MessageRateExceeded`]、または [`RiskReject::DuplicateOrder`]。
+    pub fn check_order(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        symbol_hash: u64,
+        bbo: Option<&Bbo>,
+    ) -> Result<(), RiskReject> {
+        self.checker.check_order_with_bbo(order, position, bbo)?;
+        self.messages.check(order.timestamp_ns)?;
+        self.duplicates.check(order, symbol_hash)
+    }
+
+    /// [`Self::check_order`] の結果を `tags` 付きの監査記録にする。
+    #[must_use]
+    pub fn decide(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+        symbol_hash: u64,
+        bbo: Option<&Bbo>,
+        tags: DecisionTags,
+    ) -> DecisionRecord {
+        DecisionRecord {
+            order_id: order.id.0,
+            timestamp_ns: order.timestamp_ns,
+            limit_version: self.checker.limit_version(),
+            result: self.check_order(order, position, symbol_hash, bbo),
+            tags,
+        }
+    }
+
+    /// 受理した注文を記録する（未約定注文・重複検出・メッセージレート）。
+    pub fn on_order_accepted(&mut self, order: &Order, symbol_hash: u64) {
+        self.checker.on_order_accepted_in(order, symbol_hash);
+        self.duplicates.record(order, symbol_hash);
+        self.messages.record(order.timestamp_ns);
+    }
+
+    /// 新規以外のメッセージ（訂正・取消）をメッセージレートに数える。
+    pub const fn on_message(&mut self, now_ns: u64) {
+        self.messages.record(now_ns);
+    }
+
+    /// キル: サーキットブレーカーを作動させ、全注文を即時停止する。
+    ///
+    /// 解除は [`PreTradeChecker::reset_circuit_breaker`] で明示的に行う。
+    pub const fn kill(&mut self, now_ns: u64) {
+        self.checker.trip_circuit_breaker();
+        self.killed_at_ns = Some(now_ns);
+    }
+
+    /// 最後にキルした時刻（ns）。
+    #[must_use]
+    pub const fn killed_at_ns(&self) -> Option<u64> {
+        self.killed_at_ns
+    }
+
+    /// プロファイルのコントロールの現在の状態と閾値の証跡。
+    #[must_use]
+    pub fn attestation(&self) -> Attestation {
+        let l = self.checker.limits();
+        let c = l.checks;
+        let status = |category, control, active, threshold: String| ControlStatus {
+            category,
+            control,
+            active,
+            threshold: Some(threshold),
+        };
+        let mut controls = if self.profile == "mifid_rts6" {
+            Vec::from([
+                status(
+                    ControlCategory::ErroneousOrder,
+                    "price_collar_bps",
+                    c.bbo_reasonability,
+                    format!("{}", l.max_through_bbo_bps),
+                ),
+                status(
+                    ControlCategory::ErroneousOrder,
+                    "max_order_value",
+                    c.notional,
+                    format!("{}", l.max_notional),
+                ),
+                status(
+                    ControlCategory::ErroneousOrder,
+                    "max_order_volume",
+                    c.order_size,
+                    format!("{}", l.max_order_size),
+                ),
+                status(
+                    ControlCategory::MessageRate,
+                    "max_message_rate",
+                    self.messages.max_messages() < u32::MAX,
+                    message_rate_threshold(&self.messages),
+                ),
+            ])
+        } else {
+            Vec::from([
+                status(
+                    ControlCategory::CreditCapital,
+                    "max_position",
+                    c.position,
+                    format!("{}", l.max_position),
+                ),
+                status(
+                    ControlCategory::CreditCapital,
+                    "max_open_order_notional",
+                    c.open_order_notional,
+                    format!("{}", l.max_open_order_notional),
+                ),
+                status(
+                    ControlCategory::CreditCapital,
+                    "max_daily_loss",
+                    c.daily_loss,
+                    format!("{}", l.max_daily_loss),
+                ),
+                status(
+                    ControlCategory::ErroneousOrder,
+                    "max_order_size",
+                    c.order_size,
+                    format!("{}", l.max_order_size),
+                ),
+                status(
+                    ControlCategory::ErroneousOrder,
+                    "max_notional",
+                    c.notional,
+                    format!("{}", l.max_notional),
+                ),
+                status(
+                    ControlCategory::ErroneousOrder,
+                    "max_through_bbo_bps",
+                    c.bbo_reasonability,
+                    format!("{}", l.max_through_bbo_bps),
+                ),
+                status(
+                    ControlCategory::Duplicate,
+                    "duplicate_window_ns",
+                    self.duplicates.window_ns() > 0,
+                    format!("{}", self.duplicates.window_ns()),
+                ),
+            ])
+        };
+        controls.push(ControlStatus {
+            category: ControlCategory::Kill,
+            control: "kill_switch",
+            active: true,
+            threshold: None,
+        });
+        Attestation {
+            profile: self.profile,
+            limit_version: self.checker.limit_version(),
+            controls,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Self-assessment
+// ---------------------------------------------------------------------------
+
+/// アルゴ ID・DEA フラグ別の判定件数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlgoActivity {
+    /// 判定記録の [`DecisionTags`]。
+    pub tags: DecisionTags,
+    /// 受理件数。
+    pub accepted: u64,
+    /// 拒否件数。
+    pub rejected: u64,
+}
+
+/// 判定記録をアルゴ ID・DEA フラグ別に集計する（年次自己評価用）。
+///
+/// アルゴ ID 無し（手動発注）が先頭、以降はアルゴ ID 順。
+#[must_use]
+pub fn self_assessment(records: &[DecisionRecord]) -> Vec<AlgoActivity> {
+    let mut counts: BTreeMap<(Option<u64>, bool), (u64, u64)> = BTreeMap::new();
+    for r in records {
+        let entry = counts.entry((r.tags.algo_id, r.tags.dea)).or_default();
+        if r.is_accepted() {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|((algo_id, dea), (accepted, rejected))| AlgoActivity {
+            tags: DecisionTags { algo_id, dea },
+            accepted,
+            rejected,
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(id: u64, price: i64, quantity: u64, timestamp_ns: u64) -> Order {
+        OrderBuilder::bid(price, quantity)
+            .id(id)
+            .at(timestamp_ns)
+            .build()
+    }
+
+    #[test]
+    fn duplicate_within_window_rejected() {
+        let mut guard = DuplicateGuard::new(1_000);
+        guard.record(&order(1, 100, 5, 0), 7);
+        assert_eq!(
+            guard.check(&order(2, 100, 5, 400), 7),
+            Err(RiskReject::DuplicateOrder {
+                previous_id: 1,
+                age_ns: 400
+            })
+        );
+        // 銘柄・価格・数量のいずれかが違えば重複ではない。
+        assert!(guard.check(&order(2, 100, 5, 400), 8).is_ok());
+        assert!(guard.check(&order(2, 101, 5, 400), 7).is_ok());
+        assert!(guard.check(&order(2, 100, 6, 400), 7).is_ok());
+        // 時間幅を過ぎれば受理。
+        assert!(guard.check(&order(2, 100, 5, 1_001), 7).is_ok());
+        guard.record(&order(3, 1, 1, 5_000), 7);
+        assert_eq!(guard.recent.len(), 1);
+    }
+
+    #[test]
+    fn disabled_guard_accepts_everything() {
+        let mut guard = DuplicateGuard::new(0);
+        guard.record(&order(1, 100, 5, 0), 7);
+        assert!(guard.check(&order(2, 100, 5, 0), 7).is_ok());
+    }
+
+    #[test]
+    fn preset_enables_required_checks() {
+        let limits = RiskLimits {
+            checks: crate::limit::EnabledChecks::NONE,
+            ..RiskLimits::default()
+        };
+        let controls = MarketAccessControls::sec_15c3_5(limits, 1_000_000);
+        let report = controls.attestation();
+        assert!(report.all_active());
+        assert_eq!(report.controls.len(), 8);
+
+        let mut controls = MarketAccessControls::sec_15c3_5(RiskLimits::default(), 0);
+        let mut relaxed = controls.checker().limits().clone();
+        relaxed.checks.daily_loss = false;
+        controls.checker_mut().set_limits(relaxed);
+        assert_eq!(
+            controls.attestation().inactive(),
+            ["max_daily_loss", "duplicate_window_ns"]
+        );
+    }
+
+    #[test]
+    fn check_and_kill() {
+        let mut controls = MarketAccessControls::sec_15c3_5(RiskLimits::default(), 1_000);
+        let first = order(1, 100, 5, 0);
+        assert!(controls.check_order(&first, None, 7, None).is_ok());
+        controls.on_order_accepted(&first, 7);
+        assert!(matches!(
+            controls.check_order(&order(2, 100, 5, 10), None, 7, None),
+            Err(RiskReject::DuplicateOrder { previous_id: 1, .. })
+        ));
+        controls.kill(42);
+        assert_eq!(controls.killed_at_ns(), Some(42));
+        assert_eq!(
+            controls.check_order(&order(3, 200, 1, 20), None, 7, None),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+    }
+
+    #[test]
+    fn message_rate_window() {
+        let mut rate = MessageRate::new(2, 1_000);
+        rate.record(0);
+        rate.record(10);
+        assert_eq!(
+            rate.check(999),
+            Err(RiskReject::MessageRateExceeded { count: 2, limit: 2 })
+        );
+        assert!(rate.check(1_000).is_ok());
+        rate.record(1_000);
+        assert_eq!(rate.count_at(1_001), 1);
+        assert!(MessageRate::unlimited().check(0).is_ok());
+
+        // バーストは通し、持続上限で止める。
+        let mut rate = MessageRate::new(2, 100).with_sustained(RateWindow::new(3, 1_000));
+        for t in [0, 10, 100] {
+            rate.check(t).unwrap();
+            rate.record(t);
+        }
+        assert_eq!(
+            rate.check(200),
+            Err(RiskReject::MessageRateExceeded { count: 3, limit: 3 })
+        );
+        assert!(rate.check(1_000).is_ok());
+    }
+
+    #[test]
+    fn rts6_profile_and_tagged_decisions() {
+        let limits = RiskLimits {
+            checks: crate::limit::EnabledChecks::NONE,
+            ..RiskLimits::default()
+        };
+        let mut controls = MarketAccessControls::mifid_rts6(limits, MessageRate::new(2, 1_000));
+        let report = controls.attestation();
+        assert_eq!(controls.profile(), "mifid_rts6");
+        assert!(report.all_active());
+        assert_eq!(
+            report
+                .controls
+                .iter()
+                .map(|c| c.control)
+                .collect::<Vec<_>>(),
+            [
+                "price_collar_bps",
+                "max_order_value",
+                "max_order_volume",
+                "max_message_rate",
+                "kill_switch"
+            ]
+        );
+        assert!(report
+            .to_csv()
+            .contains("message_rate,max_message_rate,true,2/1000ns\n"));
+
+        let algo = DecisionTags {
+            algo_id: Some(7),
+            dea: false,
+        };
+        let dea = DecisionTags {
+            algo_id: None,
+            dea: true,
+        };
+        let mut records = Vec::new();
+        for (id, tags) in [(1, algo), (2, dea), (3, algo)] {
+            let o = order(id, 100 + id as i64, 1, 0);
+            let record = controls.decide(&o, None, 7, None, tags);
+            if record.is_accepted() {
+                controls.on_order_accepted(&o, 7);
+            }
+            records.push(record);
+        }
+        assert_eq!(records[1].tags, dea);
+        assert!(matches!(
+            records[2].result,
+            Err(RiskReject::MessageRateExceeded { .. })
+        ));
+        assert_eq!(
+            self_assessment(&records),
+            [
+                AlgoActivity {
+                    tags: dea,
+                    accepted: 1,
+                    rejected: 0
+                },
+                AlgoActivity {
+                    tags: algo,
+                    accepted: 1,
+                    rejected: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_export() {
+        let controls = MarketAccessControls::sec_15c3_5(RiskLimits::default(), 500);
+        let csv = controls.attestation().to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("# profile=sec_15c3_5 limit_version=0"));
+        assert_eq!(lines.next(), Some("category,control,active,threshold"));
+        assert!(csv.contains("duplicate,duplicate_window_ns,true,500\n"));
+        assert!(csv.ends_with("kill,kill_switch,true,\n"));
+    }
+}
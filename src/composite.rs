@@ -0,0 +1,301 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 複数の市場の価格から作る合成参照価格。
+//!
+//! 1 つの市場だけで起きた瞬間的な暴落（フラッシュクラッシュ）をそのまま
+//! 参照価格にすると、ブレーカーが誤って停止し、プライスカラーも誤った価格を
+//! 基準にしてしまう。[`CompositeReference`] は銘柄ごとに市場別の最新価格を
+//! 持ち、全市場の中央値から [`CompositeConfig::max_deviation_bps`] を超えて
+//! 離れた市場を外れ値として除いた残りの中央値を合成参照価格とする。
+//!
+//! 合成参照価格は [`CompositeMarket`] で既存の
+//! [`MarketContext`] に重ねてプライスカラーと証拠金に使い、
+//! [`HaltRegistry::on_composite`](crate::HaltRegistry::on_composite) で
+//! ブレーカーに渡す。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use alice_ledger::Side;
+
+use crate::halt::deviation_bps;
+use crate::market::{Bbo, MarketContext};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// CompositeConfig
+// ---------------------------------------------------------------------------
+
+/// 合成参照価格の設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompositeConfig {
+    /// 全市場の中央値からの乖離の上限（bps）。超えた市場は外れ値として除く。
+    pub max_deviation_bps: u32,
+    /// 外れ値を除いた後に必要な市場数。足りなければ合成しない。
+    pub min_venues: usize,
+}
+
+impl Default for CompositeConfig {
+    fn default() -> Self {
+        Self {
+            max_deviation_bps: 200,
+            min_venues: 2,
+        }
+    }
+}
+
+/// 中央値。偶数個なら中央の 2 つの中点。`sorted` は昇順で空でないこと。
+pub(crate) fn median(sorted: &[i64]) -> i64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        i64::midpoint(sorted[mid - 1], sorted[mid])
+    } else {
+        sorted[mid]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CompositeReference
+// ---------------------------------------------------------------------------
+
+/// 銘柄ごとの市場別の価格と、外れ値を除いた合成参照価格。
+#[derive(Debug, Clone, Default)]
+pub struct CompositeReference {
+    config: CompositeConfig,
+    /// 銘柄 → 市場 → 最新価格。
+    prices: BTreeMap<u64, BTreeMap<u64, i64>>,
+}
+
+impl CompositeReference {
+    /// 空の合成参照価格を作成。
+    #[must_use]
+    pub const fn new(config: CompositeConfig) -> Self {
+        Self {
+            config,
+            prices: BTreeMap::new(),
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &CompositeConfig {
+        &self.config
+    }
+
+    /// 市場の最新価格を記録する（置き換え）。
+    pub fn on_price(&mut self, symbol_hash: u64, venue_id: u64, price: i64) {
+        self.prices
+            .entry(symbol_hash)
+            .or_default()
+            .insert(venue_id, price);
+    }
+
+    /// 市場の価格を外す（接続断など）。
+    pub fn remove_venue(&mut self, symbol_hash: u64, venue_id: u64) {
+        if let Some(venues) = self.prices.get_mut(&symbol_hash) {
+            venues.remove(&venue_id);
+        }
+    }
+
+    /// 全市場の中央値と、その中央値から離れすぎていない市場の価格（昇順）。
+    fn accepted(&self, symbol_hash: u64) -> Option<(i64, Vec<i64>)> {
+        let venues = self.prices.get(&symbol_hash)?;
+        let mut all: Vec<i64> = venues.values().copied().collect();
+        if all.is_empty() {
+            return None;
+        }
+        all.sort_unstable();
+        let center = median(&all);
+        all.retain(|&p| deviation_bps(p, center) <= self.config.max_deviation_bps);
+        Some((center, all))
+    }
+
+    /// 合成参照価格。外れ値を除いた市場数が
+    /// [`CompositeConfig::min_venues`] に満たなければ `None`。
+    #[must_use]
+    pub fn price(&self, symbol_hash: u64) -> Option<i64> {
+        let (_, accepted) = self.accepted(symbol_hash)?;
+        if accepted.is_empty() || accepted.len() < self.config.min_venues {
+            return None;
+        }
+        Some(median(&accepted))
+    }
+
+    /// 外れ値として除かれた市場（市場 ID 順）。
+    #[must_use]
+    pub fn outliers(&self, symbol_hash: u64) -> Vec<u64> {
+        let Some((center, _)) = self.accepted(symbol_hash) else {
+            return Vec::new();
+        };
+        self.prices[&symbol_hash]
+            .iter()
+            .filter(|&(_, &p)| deviation_bps(p, center) > self.config.max_deviation_bps)
+            .map(|(&venue, _)| venue)
+            .collect()
+    }
+
+    /// 合成参照価格から離れすぎた気配の側を合成参照価格に置き換える。
+    ///
+    /// 合成できない銘柄では `bbo` をそのまま返す。
+    #[must_use]
+    pub fn anchor_bbo(&self, symbol_hash: u64, bbo: Option<Bbo>) -> Option<Bbo> {
+        let Some(composite) = self.price(symbol_hash) else {
+            return bbo;
+        };
+        let limit = self.config.max_deviation_bps;
+        let anchor = |quote: Option<i64>| {
+            quote.map(|q| {
+                if deviation_bps(q, composite) > limit {
+                    composite
+                } else {
+                    q
+                }
+            })
+        };
+        bbo.map(|b| Bbo {
+            bid: anchor(b.bid),
+            ask: anchor(b.ask),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CompositeMarket
+// ---------------------------------------------------------------------------
+
+/// [`MarketContext`] に合成参照価格を重ねたもの。
+///
+/// 参照価格は合成できた銘柄では合成参照価格になり、最良気配は
+/// [`CompositeReference::anchor_bbo`] で補正される。それ以外の項目は
+/// `inner` のまま。
+#[derive(Debug, Clone, Copy)]
+pub struct CompositeMarket<'a, M: ?Sized> {
+    inner: &'a M,
+    composite: &'a CompositeReference,
+}
+
+impl<'a, M: MarketContext + ?Sized> CompositeMarket<'a, M> {
+    /// `inner` に `composite` を重ねる。
+    #[must_use]
+    pub const fn new(inner: &'a M, composite: &'a CompositeReference) -> Self {
+        Self { inner, composite }
+    }
+}
+
+impl<M: MarketContext + ?Sized> MarketContext for CompositeMarket<'_, M> {
+    fn reference_price(&self, symbol_hash: u64) -> Option<i64> {
+        self.composite
+            .price(symbol_hash)
+            .or_else(|| self.inner.reference_price(symbol_hash))
+    }
+
+    fn bbo(&self, symbol_hash: u64) -> Option<Bbo> {
+        self.composite
+            .anchor_bbo(symbol_hash, self.inner.bbo(symbol_hash))
+    }
+
+    fn adv(&self, symbol_hash: u64) -> Option<u64> {
+        self.inner.adv(symbol_hash)
+    }
+
+    fn volatility_bps(&self, symbol_hash: u64) -> Option<u32> {
+        self.inner.volatility_bps(symbol_hash)
+    }
+
+    fn mark_timestamp_ns(&self, symbol_hash: u64) -> Option<u64> {
+        self.inner.mark_timestamp_ns(symbol_hash)
+    }
+
+    fn displayed_depth(&self, symbol_hash: u64, side: Side) -> Option<u64> {
+        self.inner.displayed_depth(symbol_hash, side)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::{PreTradeChecker, RiskReject};
+    use crate::halt::{BreakerConfig, BreakerMode, HaltRegistry, Reopening};
+    use crate::limit::RiskLimits;
+    use crate::market::{MarketSnapshot, SymbolMarket};
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+
+    fn composite() -> CompositeReference {
+        let mut c = CompositeReference::new(CompositeConfig::default());
+        c.on_price(7, 1, 10_000);
+        c.on_price(7, 2, 10_010);
+        c.on_price(7, 3, 9_990);
+        c
+    }
+
+    #[test]
+    fn rejects_single_venue_crash() {
+        let mut c = composite();
+        assert_eq!(c.price(7), Some(10_000));
+        // 市場 4 だけが暴落しても合成参照価格は動かない。
+        c.on_price(7, 4, 7_000);
+        assert_eq!(c.outliers(7), [4]);
+        assert_eq!(c.price(7), Some(10_000));
+        // 外れ値を除いて市場数が足りなければ合成しない。
+        c.remove_venue(7, 2);
+        c.remove_venue(7, 3);
+        assert_eq!(c.price(7), None);
+        assert_eq!(c.price(8), None);
+        assert!(c.outliers(8).is_empty());
+    }
+
+    #[test]
+    fn anchors_collar_and_breaker() {
+        let mut c = composite();
+        c.on_price(7, 4, 7_000);
+        let mut snapshot = MarketSnapshot::new();
+        snapshot.insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(7_000),
+                // 自社の気配は暴落した市場のもの。
+                bbo: Some(Bbo::new(6_990, 7_010)),
+                ..SymbolMarket::default()
+            },
+        );
+        let market = CompositeMarket::new(&snapshot, &c);
+        assert_eq!(market.reference_price(7), Some(10_000));
+        assert_eq!(market.bbo(7), Some(Bbo::new(10_000, 10_000)));
+
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_through_bbo_bps: 100,
+            ..RiskLimits::default()
+        });
+        let position = PositionBuilder::new(7, 0).avg_entry_price(0).build();
+        // 合成参照価格の近くの売りは通り、暴落価格への売りは拒否される。
+        let sell = |price| OrderBuilder::ask(price, 1).build();
+        assert!(checker
+            .check_order_in_market(&sell(9_950), Some(&position), &market)
+            .is_ok());
+        assert!(matches!(
+            checker.check_order_in_market(&sell(7_000), Some(&position), &market),
+            Err(RiskReject::PriceThroughBbo { touch: 10_000, .. })
+        ));
+
+        let mut halts = HaltRegistry::new();
+        halts.configure(
+            7,
+            BreakerConfig {
+                mode: BreakerMode::Band { band_bps: 500 },
+                halt_ns: 0,
+                reopening: Reopening::Keep,
+            },
+        );
+        halts.set_reference(7, 10_000);
+        assert_eq!(halts.on_composite(7, &c, 1), None);
+        assert!(!halts.is_halted(7, 1));
+    }
+}
@@ -0,0 +1,1288 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! TOML 形式のリスク設定ファイル。
+//!
+//! 外部クレートに依存しないため、本モジュールは設定に必要な TOML の
+//! サブセット（テーブル見出し、`key = value`、整数・真偽値・文字列、
+//! `#` コメント）のみを解析する。
+//!
+//! ```toml
+//! [limits]
+//! max_order_size = 100
+//! max_daily_loss = -500_000
+//! illiquid_symbols = "17, 42"  # 証拠金の上乗せと上限の縮小を掛ける銘柄
+//!
+//! [symbols.42]          # symbol_hash = 42 の上書き
+//! max_position = 50
+//!
+//! [breaker]
+//! max_move = 500
+//! max_fills_per_window = 5
+//! window_ns = 1_000_000_000
+//!
+//! [margin]
+//! initial_margin_bps = 1000
+//! maintenance_margin_bps = 500
+//! spread_credit_bps = 7500  # カレンダースプレッドの証拠金控除
+//! cross_margin_offset_bps = 5000  # 現物とデリバティブの相殺分の証拠金控除
+//! delta_hedge_haircut_bps = 2000  # デルタヘッジの相殺分のうち控除しない割合
+//! mpor_base_days = 2    # 証拠金率が前提とする保有期間（日）
+//! mpor_days = 2
+//!
+//! [mpor.7]              # product_hash = 7 の保有期間（相対取引の 10 日）
+//! days = 10
+//! ```
+//!
+//! 誤設定は読込時に [`ConfigError`]（フィールドパスと行番号付き）として
+//! 報告され、チェックが黙って無効化されることはない。
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::circuit::CircuitBreaker;
+use crate::limit::RiskLimits;
+use crate::margin::{MarginCalculator, MarginParams};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// ConfigValue
+// ---------------------------------------------------------------------------
+
+/// 設定値（TOML スカラー）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigValue {
+    /// 整数（`_` 区切り・`0x` 16 進表記可）。
+    Int(i128),
+    /// 真偽値。
+    Bool(bool),
+    /// 文字列。
+    Str(String),
+}
+
+impl ConfigValue {
+    /// TOML スカラー表記を解析する。
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text {
+            "true" => return Some(Self::Bool(true)),
+            "false" => return Some(Self::Bool(false)),
+            _ => {}
+        }
+        if let Some(inner) = text.strip_prefix('"') {
+            let inner = inner.strip_suffix('"')?;
+            if inner.contains('"') {
+                return None;
+            }
+            return Some(Self::Str(inner.to_string()));
+        }
+        parse_int(text).map(Self::Int)
+    }
+
+    /// 型名（エラー表示用）。
+    const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "integer",
+            Self::Bool(_) => "boolean",
+            Self::Str(_) => "string",
+        }
+    }
+}
+
+/// 整数リテラルを解析する。
+fn parse_int(text: &str) -> Option<i128> {
+    let (negative, body) = match text.as_bytes().first()? {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    if body.starts_with('_') || body.ends_with('_') || body.contains("__") {
+        return None;
+    }
+    let digits: String = body.chars().filter(|&c| c != '_').collect();
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        if hex.is_empty() {
+            return None;
+        }
+        i128::from_str_radix(hex, 16).ok()?
+    } else {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse::<i128>().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+// ---------------------------------------------------------------------------
+// ConfigError
+// ---------------------------------------------------------------------------
+
+/// 設定エラーの種類。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigErrorKind {
+    /// 構文エラー。
+    Syntax(&'static str),
+    /// 未知のテーブル見出し。
+    UnknownTable,
+    /// 未知のフィールド。
+    UnknownField,
+    /// 同じキーが 2 回以上設定された。
+    DuplicateKey,
+    /// 同じテーブルが 2 回以上宣言された。
+    DuplicateTable,
+    /// 値の型が不一致。
+    TypeMismatch {
+        /// 期待された型。
+        expected: &'static str,
+        /// 実際の型。
+        found: &'static str,
+    },
+    /// 値がフィールドの範囲外。
+    OutOfRange,
+    /// 値の組み合わせが不正（意味検査）。
+    Invalid(&'static str),
+}
+
+/// フィールドパスと行番号付きの設定エラー。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 1 始まりの行番号。既定値に起因する場合は 0。
+    pub line: usize,
+    /// フィールドパス（例: `limits.max_order_size`）。
+    pub path: String,
+    /// エラー種別。
+    pub kind: ConfigErrorKind,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: ", self.line, self.path)?;
+        match &self.kind {
+            ConfigErrorKind::Syntax(msg) => write!(f, "syntax error: {msg}"),
+            ConfigErrorKind::UnknownTable => f.write_str("unknown table"),
+            ConfigErrorKind::UnknownField => f.write_str("unknown field"),
+            ConfigErrorKind::DuplicateKey => f.write_str("duplicate key"),
+            ConfigErrorKind::DuplicateTable => f.write_str("duplicate table"),
+            ConfigErrorKind::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            ConfigErrorKind::OutOfRange => f.write_str("value out of range"),
+            ConfigErrorKind::Invalid(msg) => f.write_str(msg),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// BreakerConfig
+// ---------------------------------------------------------------------------
+
+/// [`CircuitBreaker`] の構築パラメータ。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakerConfig {
+    /// 基準価格からの最大乖離（ticks）。
+    pub max_move: i64,
+    /// ウィンドウ内の最大約定数。
+    pub max_fills_per_window: u32,
+    /// ウィンドウ長（ns）。
+    pub window_ns: u64,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_move: 500,
+            max_fills_per_window: 5,
+            window_ns: 1_000_000_000,
+        }
+    }
+}
+
+impl BreakerConfig {
+    /// 設定からサーキットブレーカーを構築する。
+    #[must_use]
+    pub const fn build(&self) -> CircuitBreaker {
+        CircuitBreaker::new(self.max_move, self.max_fills_per_window, self.window_ns)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RiskConfig
+// ---------------------------------------------------------------------------
+
+/// 設定ファイル上のテーブル。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigTable {
+    /// `[limits]`
+    Limits,
+    /// `[symbols.<symbol_hash>]`
+    Symbol(u64),
+    /// `[breaker]`
+    Breaker,
+    /// `[margin]`
+    Margin,
+    /// `[mpor.<product_hash>]`
+    Mpor(u64),
+}
+
+impl ConfigTable {
+    /// テーブル見出し（`[` `]` の内側）を解析する。
+    fn parse(header: &str) -> Option<Self> {
+        match header {
+            "limits" => Some(Self::Limits),
+            "breaker" => Some(Self::Breaker),
+            "margin" => Some(Self::Margin),
+            _ => {
+                if let Some(id) = header.strip_prefix("mpor.") {
+                    return u64::try_from(parse_int(id)?).ok().map(Self::Mpor);
+                }
+                let id = parse_int(header.strip_prefix("symbols.")?)?;
+                u64::try_from(id).ok().map(Self::Symbol)
+            }
+        }
+    }
+
+    /// フィールドパスの接頭辞。
+    #[must_use]
+    pub fn prefix(&self) -> String {
+        match self {
+            Self::Limits => "limits".to_string(),
+            Self::Symbol(id) => alloc::format!("symbols.{id}"),
+            Self::Breaker => "breaker".to_string(),
+            Self::Margin => "margin".to_string(),
+            Self::Mpor(id) => alloc::format!("mpor.{id}"),
+        }
+    }
+}
+
+/// 読込・検証済みのリスク設定一式。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RiskConfig {
+    /// アカウント全体の制限。
+    pub limits: RiskLimits,
+    /// 銘柄別の上書き（`symbol_hash` → フィールド名 → 値）。
+    ///
+    /// 未指定フィールドは [`Self::limits`] を継承する。
+    pub symbol_overrides: BTreeMap<u64, BTreeMap<String, ConfigValue>>,
+    /// サーキットブレーカー設定。
+    pub breaker: BreakerConfig,
+    /// 証拠金率。
+    pub margin: MarginParams,
+    /// 商品別の証拠金の保有期間（`product_hash` → 日数）。
+    pub product_mpor_days: BTreeMap<u64, u32>,
+}
+
+impl RiskConfig {
+    /// TOML 文字列から設定を読み込み、検証する。
+    ///
+    /// # Errors
+    ///
+    /// 構文・型・範囲・意味検査のいずれかに失敗した場合、最初のエラーを
+    /// [`ConfigError`] として返す。
+    pub fn from_toml_str(text: &str) -> Result<Self, ConfigError> {
+        let mut layered = LayeredConfig::new();
+        layered.apply_file(text)?;
+        Ok(layered.into_config())
+    }
+
+    /// 1 フィールドを設定する。
+    ///
+    /// # Errors
+    ///
+    /// 未知のフィールド、型不一致、範囲外の場合に [`ConfigErrorKind`] を返す。
+    pub fn set(
+        &mut self,
+        table: ConfigTable,
+        key: &str,
+        value: ConfigValue,
+    ) -> Result<(), ConfigErrorKind> {
+        match table {
+            ConfigTable::Limits => set_limit(&mut self.limits, key, &value),
+            ConfigTable::Symbol(id) => {
+                // 型・範囲を検査してから保持する。
+                set_limit(&mut self.limits.clone(), key, &value)?;
+                self.symbol_overrides
+                    .entry(id)
+                    .or_default()
+                    .insert(key.to_string(), value);
+                Ok(())
+            }
+            ConfigTable::Breaker => {
+                let b = &mut self.breaker;
+                match key {
+                    "max_move" => b.max_move = int_field(&value)?,
+                    "max_fills_per_window" => b.max_fills_per_window = int_field(&value)?,
+                    "window_ns" => b.window_ns = int_field(&value)?,
+                    _ => return Err(ConfigErrorKind::UnknownField),
+                }
+                Ok(())
+            }
+            ConfigTable::Margin => {
+                let m = &mut self.margin;
+                match key {
+                    "initial_margin_bps" => m.initial_margin_bps = int_field(&value)?,
+                    "maintenance_margin_bps" => m.maintenance_margin_bps = int_field(&value)?,
+                    "spread_credit_bps" => m.spread_credit_bps = int_field(&value)?,
+                    "cross_margin_offset_bps" => m.cross_margin_offset_bps = int_field(&value)?,
+                    "delta_hedge_haircut_bps" => m.delta_hedge_haircut_bps = int_field(&value)?,
+                    "mpor_base_days" => m.mpor_base_days = int_field(&value)?,
+                    "mpor_days" => m.mpor_days = int_field(&value)?,
+                    _ => return Err(ConfigErrorKind::UnknownField),
+                }
+                Ok(())
+            }
+            ConfigTable::Mpor(id) => {
+                match key {
+                    "days" => {
+                        self.product_mpor_days.insert(id, int_field(&value)?);
+                    }
+                    _ => return Err(ConfigErrorKind::UnknownField),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 指定銘柄に適用される制限（上書き適用後）。
+    #[must_use]
+    pub fn limits_for(&self, symbol_hash: u64) -> RiskLimits {
+        let mut limits = self.limits.clone();
+        if let Some(fields) = self.symbol_overrides.get(&symbol_hash) {
+            for (key, value) in fields {
+                // 値は `set` 時に検査済み。
+                let _ = set_limit(&mut limits, key, value);
+            }
+        }
+        limits
+    }
+
+    /// 意味検査。失敗時は（フィールドパス, 種別）を返す。
+    fn validate(&self) -> Result<(), (String, ConfigErrorKind)> {
+        validate_limits(&self.limits, "limits")?;
+        for &id in self.symbol_overrides.keys() {
+            validate_limits(&self.limits_for(id), &ConfigTable::Symbol(id).prefix())?;
+        }
+        if self.breaker.max_move < 0 {
+            return Err((
+                "breaker.max_move".to_string(),
+                ConfigErrorKind::Invalid("must not be negative"),
+            ));
+        }
+        if self.breaker.window_ns == 0 {
+            return Err((
+                "breaker.window_ns".to_string(),
+                ConfigErrorKind::Invalid("must be positive"),
+            ));
+        }
+        if self.margin.maintenance_margin_bps > self.margin.initial_margin_bps {
+            return Err((
+                "margin.maintenance_margin_bps".to_string(),
+                ConfigErrorKind::Invalid("must not exceed initial_margin_bps"),
+            ));
+        }
+        if self.margin.spread_credit_bps > 10_000 {
+            return Err((
+                "margin.spread_credit_bps".to_string(),
+                ConfigErrorKind::Invalid("must not exceed 10000"),
+            ));
+        }
+        if self.margin.cross_margin_offset_bps > 10_000 {
+            return Err((
+                "margin.cross_margin_offset_bps".to_string(),
+                ConfigErrorKind::Invalid("must not exceed 10000"),
+            ));
+        }
+        if self.margin.delta_hedge_haircut_bps > 10_000 {
+            return Err((
+                "margin.delta_hedge_haircut_bps".to_string(),
+                ConfigErrorKind::Invalid("must not exceed 10000"),
+            ));
+        }
+        if self.margin.mpor_base_days == 0 {
+            return Err((
+                "margin.mpor_base_days".to_string(),
+                ConfigErrorKind::Invalid("must be positive"),
+            ));
+        }
+        if self.margin.mpor_days == 0 {
+            return Err((
+                "margin.mpor_days".to_string(),
+                ConfigErrorKind::Invalid("must be positive"),
+            ));
+        }
+        if let Some((&id, _)) = self.product_mpor_days.iter().find(|(_, &d)| d == 0) {
+            return Err((
+                alloc::format!("{}.days", ConfigTable::Mpor(id).prefix()),
+                ConfigErrorKind::Invalid("must be positive"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// アカウント全体の制限で [`PreTradeChecker`] を構築する。
+    ///
+    /// 銘柄別に上書きされた `max_open_orders_per_symbol` は、その銘柄の
+    /// 未約定注文数の上限として登録する。
+    #[must_use]
+    pub fn checker(&self) -> PreTradeChecker {
+        let mut checker = PreTradeChecker::new(self.limits.clone());
+        for (&id, fields) in &self.symbol_overrides {
+            if fields.contains_key("max_open_orders_per_symbol") {
+                checker.set_symbol_open_order_limit(
+                    id,
+                    self.limits_for(id).max_open_orders_per_symbol,
+                );
+            }
+        }
+        checker
+    }
+
+    /// 指定銘柄の制限で [`PreTradeChecker`] を構築する。
+    #[must_use]
+    pub fn symbol_checker(&self, symbol_hash: u64) -> PreTradeChecker {
+        PreTradeChecker::new(self.limits_for(symbol_hash))
+    }
+
+    /// 商品別の保有期間を登録した [`MarginCalculator`] を構築する。
+    #[must_use]
+    pub fn margin_calculator(&self) -> MarginCalculator {
+        self.product_mpor_days.iter().fold(
+            MarginCalculator::new(self.margin.clone()),
+            |calc, (&id, &days)| calc.with_product_mpor(id, days),
+        )
+    }
+
+    /// [`CircuitBreaker`] を構築する。
+    #[must_use]
+    pub const fn circuit_breaker(&self) -> CircuitBreaker {
+        self.breaker.build()
+    }
+}
+
+/// [`RiskLimits`] の 1 フィールドを型付きの値で設定する。
+fn set_limit(
+    limits: &mut RiskLimits,
+    key: &str,
+    value: &ConfigValue,
+) -> Result<(), ConfigErrorKind> {
+    if RiskLimits::CHECK_FIELDS.contains(&key) {
+        let ConfigValue::Bool(enabled) = value else {
+            return Err(ConfigErrorKind::TypeMismatch {
+                expected: "boolean",
+                found: value.type_name(),
+            });
+        };
+        return limits
+            .set_field(key, if *enabled { "true" } else { "false" })
+            .map_err(|_| ConfigErrorKind::OutOfRange);
+    }
+    if RiskLimits::LIST_FIELDS.contains(&key) {
+        let ConfigValue::Str(list) = value else {
+            return Err(ConfigErrorKind::TypeMismatch {
+                expected: "string",
+                found: value.type_name(),
+            });
+        };
+        return limits
+            .set_field(key, list)
+            .map_err(|_| ConfigErrorKind::OutOfRange);
+    }
+    if !RiskLimits::FIELDS.contains(&key) {
+        return Err(ConfigErrorKind::UnknownField);
+    }
+    let ConfigValue::Int(v) = value else {
+        return Err(ConfigErrorKind::TypeMismatch {
+            expected: "integer",
+            found: value.type_name(),
+        });
+    };
+    limits
+        .set_field(key, &v.to_string())
+        .map_err(|_| ConfigErrorKind::OutOfRange)
+}
+
+/// 整数フィールドの値を取り出す。
+fn int_field<T: TryFrom<i128>>(value: &ConfigValue) -> Result<T, ConfigErrorKind> {
+    match value {
+        ConfigValue::Int(v) => T::try_from(*v).map_err(|_| ConfigErrorKind::OutOfRange),
+        other => Err(ConfigErrorKind::TypeMismatch {
+            expected: "integer",
+            found: other.type_name(),
+        }),
+    }
+}
+
+/// 制限値の意味検査。
+fn validate_limits(limits: &RiskLimits, prefix: &str) -> Result<(), (String, ConfigErrorKind)> {
+    if limits.max_daily_loss > 0 {
+        return Err((
+            alloc::format!("{prefix}.max_daily_loss"),
+            ConfigErrorKind::Invalid("must be zero or negative (a loss threshold)"),
+        ));
+    }
+    if limits.max_notional < 0 {
+        return Err((
+            alloc::format!("{prefix}.max_notional"),
+            ConfigErrorKind::Invalid("must not be negative"),
+        ));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// LayeredConfig
+// ---------------------------------------------------------------------------
+
+/// 設定値の供給元レイヤー。優先度の低い順に並ぶ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigLayer {
+    /// 組み込み既定値。
+    Default,
+    /// 設定ファイル。
+    File,
+    /// 環境変数。
+    Env,
+    /// 実行時上書き。
+    Override,
+}
+
+/// 環境変数名の接頭辞。
+///
+/// `ALICE_RISK_LIMITS__MAX_ORDER_SIZE=50` は `limits.max_order_size`、
+/// `ALICE_RISK_SYMBOLS__42__MAX_POSITION=10` は `symbols.42.max_position`
+/// に対応する（`__` がパス区切り）。
+pub const ENV_PREFIX: &str = "ALICE_RISK_";
+
+/// 既定値 → ファイル → 環境変数 → 実行時上書き の順で重ねる設定ローダー。
+///
+/// 各フィールドについてどのレイヤーが値を供給したかを記録するため、
+/// 発動した制限の出所を監査時に説明できる。レイヤーの適用順序に関わらず
+/// 優先度の高いレイヤーの値が優先される。
+#[derive(Debug, Clone, Default)]
+pub struct LayeredConfig {
+    config: RiskConfig,
+    /// フィールドパス → （供給レイヤー, 行番号）。未記録は既定値。
+    provenance: BTreeMap<String, (ConfigLayer, usize)>,
+}
+
+impl LayeredConfig {
+    /// 既定値のみの設定を作成する。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定ファイル（TOML）レイヤーを適用する。
+    ///
+    /// # Errors
+    ///
+    /// 解析・検証に失敗した場合に [`ConfigError`] を返す。その場合ファイルの
+    /// 値は 1 つも適用しない。
+    pub fn apply_file(&mut self, text: &str) -> Result<(), ConfigError> {
+        let entries = parse_entries(text)?;
+        self.apply_validated(|next| {
+            for entry in entries {
+                next.set_at(
+                    ConfigLayer::File,
+                    entry.table,
+                    &entry.key,
+                    entry.value,
+                    entry.line,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// 環境変数レイヤーを適用する。
+    ///
+    /// [`ENV_PREFIX`] で始まらない変数は無視する。接頭辞付きで未知の
+    /// フィールドを指す変数はエラーとする（打ち間違いを黙って無視しない）。
+    ///
+    /// # Errors
+    ///
+    /// 変数名・値が不正な場合、または検証に失敗した場合に [`ConfigError`] を返す。
+    /// その場合どの変数も適用しない。
+    pub fn apply_env<I, K, V>(&mut self, vars: I) -> Result<(), ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.apply_validated(|next| {
+            for (name, value) in vars {
+                let Some(rest) = name.as_ref().strip_prefix(ENV_PREFIX) else {
+                    continue;
+                };
+                let path = rest.to_ascii_lowercase().replace("__", ".");
+                let value = ConfigValue::parse(value.as_ref()).ok_or_else(|| ConfigError {
+                    line: 0,
+                    path: path.clone(),
+                    kind: ConfigErrorKind::Syntax("invalid value"),
+                })?;
+                next.set_path(ConfigLayer::Env, &path, value)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// プロセスの環境変数からレイヤーを適用する。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::apply_env`] と同じ。
+    #[cfg(feature = "std")]
+    pub fn apply_process_env(&mut self) -> Result<(), ConfigError> {
+        self.apply_env(std::env::vars())
+    }
+
+    /// 実行時上書きを 1 フィールド適用する（例: `limits.max_order_size`）。
+    ///
+    /// # Errors
+    ///
+    /// パス・値が不正な場合、または検証に失敗した場合に [`ConfigError`] を返す。
+    /// その場合設定と供給レイヤーは変えない。
+    pub fn set_override(&mut self, path: &str, value: ConfigValue) -> Result<(), ConfigError> {
+        self.apply_validated(|next| next.set_path(ConfigLayer::Override, path, value))
+    }
+
+    /// 合成後の設定。
+    #[must_use]
+    pub const fn config(&self) -> &RiskConfig {
+        &self.config
+    }
+
+    /// 合成後の設定を取り出す。
+    #[must_use]
+    pub fn into_config(self) -> RiskConfig {
+        self.config
+    }
+
+    /// 指定フィールドの値を供給したレイヤー。
+    #[must_use]
+    pub fn provenance(&self, path: &str) -> ConfigLayer {
+        self.provenance
+            .get(path)
+            .map_or(ConfigLayer::Default, |&(layer, _)| layer)
+    }
+
+    /// 拒否理由に対応する制限値のパスと供給レイヤー。
+    ///
+    /// `symbol_hash` を指定した場合、その銘柄の上書きを優先して参照する。
+    /// 制限値に対応しない拒否理由（サーキットブレーカー等）は `None`。
+    #[must_use]
+    pub fn provenance_for_reject(
+        &self,
+        reject: &RiskReject,
+        symbol_hash: Option<u64>,
+    ) -> Option<(String, ConfigLayer)> {
+        let field = reject.limit_field()?;
+        if let Some(id) = symbol_hash {
+            let path = alloc::format!("{}.{field}", ConfigTable::Symbol(id).prefix());
+            if self.provenance.contains_key(&path) {
+                let layer = self.provenance(&path);
+                return Some((path, layer));
+            }
+        }
+        let path = alloc::format!("limits.{field}");
+        let layer = self.provenance(&path);
+        Some((path, layer))
+    }
+
+    /// `apply` を複製に適用し、検証を通った場合だけ置き換える。失敗した場合は
+    /// 設定も供給レイヤーも変えない。
+    fn apply_validated<F>(&mut self, apply: F) -> Result<(), ConfigError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), ConfigError>,
+    {
+        let mut next = self.clone();
+        apply(&mut next)?;
+        next.validate()?;
+        *self = next;
+        Ok(())
+    }
+
+    /// ドット区切りパスで 1 フィールドを設定する。
+    fn set_path(
+        &mut self,
+        layer: ConfigLayer,
+        path: &str,
+        value: ConfigValue,
+    ) -> Result<(), ConfigError> {
+        let unknown = || ConfigError {
+            line: 0,
+            path: path.to_string(),
+            kind: ConfigErrorKind::UnknownField,
+        };
+        let (prefix, key) = path.rsplit_once('.').ok_or_else(unknown)?;
+        let table = ConfigTable::parse(prefix).ok_or_else(unknown)?;
+        self.set_at(layer, table, key, value, 0)
+    }
+
+    /// 優先度を考慮して 1 フィールドを設定する。
+    fn set_at(
+        &mut self,
+        layer: ConfigLayer,
+        table: ConfigTable,
+        key: &str,
+        value: ConfigValue,
+        line: usize,
+    ) -> Result<(), ConfigError> {
+        let path = alloc::format!("{}.{key}", table.prefix());
+        let shadowed = self.provenance(&path) > layer;
+        // 上位レイヤーに隠される値も型・範囲は検査する。
+        let target = if shadowed {
+            &mut self.config.clone()
+        } else {
+            &mut self.config
+        };
+        target.set(table, key, value).map_err(|kind| ConfigError {
+            line,
+            path: path.clone(),
+            kind,
+        })?;
+        if !shadowed {
+            self.provenance.insert(path, (layer, line));
+        }
+        Ok(())
+    }
+
+    /// 合成後の設定を検証する。
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.config.validate().map_err(|(path, kind)| ConfigError {
+            line: self.provenance.get(&path).map_or(0, |&(_, line)| line),
+            path,
+            kind,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+/// 解析済みの 1 代入。
+struct Entry {
+    table: ConfigTable,
+    key: String,
+    value: ConfigValue,
+    line: usize,
+}
+
+/// 文字列外の `#` 以降を取り除く。
+fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            '#' if !in_str => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// TOML サブセットを代入列へ分解する。
+fn parse_entries(text: &str) -> Result<Vec<Entry>, ConfigError> {
+    let mut entries = Vec::new();
+    let mut table: Option<ConfigTable> = None;
+    let mut seen_tables = Vec::new();
+    let mut seen_keys: BTreeMap<(ConfigTable, String), usize> = BTreeMap::new();
+
+    for (idx, raw) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |path: &str, kind| ConfigError {
+            line: line_no,
+            path: path.to_string(),
+            kind,
+        };
+
+        if let Some(rest) = line.strip_prefix('[') {
+            let header = rest
+                .strip_suffix(']')
+                .ok_or_else(|| err(rest, ConfigErrorKind::Syntax("unterminated table header")))?
+                .trim();
+            let parsed = ConfigTable::parse(header)
+                .ok_or_else(|| err(header, ConfigErrorKind::UnknownTable))?;
+            if seen_tables.contains(&parsed) {
+                return Err(err(header, ConfigErrorKind::DuplicateTable));
+            }
+            seen_tables.push(parsed);
+            table = Some(parsed);
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| err(line, ConfigErrorKind::Syntax("expected `key = value`")))?;
+        let key = key.trim();
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+            return Err(err(key, ConfigErrorKind::Syntax("invalid key")));
+        }
+        let Some(current) = table else {
+            return Err(err(key, ConfigErrorKind::UnknownField));
+        };
+        let path = alloc::format!("{}.{key}", current.prefix());
+        let value = ConfigValue::parse(value)
+            .ok_or_else(|| err(&path, ConfigErrorKind::Syntax("invalid value")))?;
+        if seen_keys
+            .insert((current, key.to_string()), line_no)
+            .is_some()
+        {
+            return Err(err(&path, ConfigErrorKind::DuplicateKey));
+        }
+        entries.push(Entry {
+            table: current,
+            key: key.to_string(),
+            value,
+            line: line_no,
+        });
+    }
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: &str = r"
+# desk config
+[limits]
+max_order_size = 50
+max_notional = 10_000_000
+max_daily_loss = -250_000
+
+[symbols.42]
+max_position = 20   # tighter for illiquid name
+
+[symbols.0xFF]
+max_order_size = 5
+
+[breaker]
+max_move = 300
+max_fills_per_window = 10
+window_ns = 500_000_000
+
+[margin]
+initial_margin_bps = 2000
+maintenance_margin_bps = 1000
+spread_credit_bps = 8000
+cross_margin_offset_bps = 6000
+delta_hedge_haircut_bps = 2500
+";
+
+    fn err_of(text: &str) -> ConfigError {
+        RiskConfig::from_toml_str(text).unwrap_err()
+    }
+
+    #[test]
+    fn parse_full_config() {
+        let cfg = RiskConfig::from_toml_str(FULL).unwrap();
+        assert_eq!(cfg.limits.max_order_size, 50);
+        assert_eq!(cfg.limits.max_notional, 10_000_000);
+        assert_eq!(cfg.limits.max_daily_loss, -250_000);
+        assert_eq!(cfg.limits.max_position, RiskLimits::default().max_position);
+        assert_eq!(cfg.breaker.max_move, 300);
+        assert_eq!(cfg.breaker.window_ns, 500_000_000);
+        assert_eq!(cfg.margin.initial_margin_bps, 2000);
+        assert_eq!(cfg.margin.spread_credit_bps, 8000);
+        assert_eq!(cfg.margin.cross_margin_offset_bps, 6000);
+        assert_eq!(cfg.margin.delta_hedge_haircut_bps, 2500);
+    }
+
+    #[test]
+    fn symbol_override_inherits_base() {
+        let cfg = RiskConfig::from_toml_str(FULL).unwrap();
+        let sym = cfg.limits_for(42);
+        assert_eq!(sym.max_position, 20);
+        assert_eq!(sym.max_order_size, 50);
+        assert_eq!(cfg.limits_for(0xFF).max_order_size, 5);
+        assert_eq!(cfg.limits_for(7), cfg.limits);
+    }
+
+    #[test]
+    fn override_applies_regardless_of_table_order() {
+        let cfg = RiskConfig::from_toml_str(
+            "[symbols.1]\nmax_position = 9\n[limits]\nmax_order_size = 3\n",
+        )
+        .unwrap();
+        let sym = cfg.limits_for(1);
+        assert_eq!((sym.max_position, sym.max_order_size), (9, 3));
+    }
+
+    #[test]
+    fn check_toggles() {
+        let cfg = RiskConfig::from_toml_str(
+            "[limits]\ncheck_open_orders = false\n[symbols.5]\ncheck_notional = false\n",
+        )
+        .unwrap();
+        assert!(!cfg.limits.checks.open_orders);
+        assert!(cfg.limits.checks.notional);
+        assert!(!cfg.limits_for(5).checks.notional);
+        assert!(!cfg.limits_for(5).checks.open_orders);
+        let e = err_of("[limits]\ncheck_position = 0\n");
+        assert_eq!(
+            e.kind,
+            ConfigErrorKind::TypeMismatch {
+                expected: "boolean",
+                found: "integer"
+            }
+        );
+    }
+
+    #[test]
+    fn builders_use_config() {
+        let cfg = RiskConfig::from_toml_str(FULL).unwrap();
+        assert_eq!(cfg.checker().limits().max_order_size, 50);
+        assert_eq!(cfg.symbol_checker(42).limits().max_position, 20);
+        assert_eq!(cfg.margin_calculator().initial_margin(10_000, 1), 2_000);
+        assert_eq!(cfg.circuit_breaker().max_move, 300);
+    }
+
+    #[test]
+    fn symbol_open_order_limits_installed() {
+        let cfg = RiskConfig::from_toml_str(
+            "[limits]\nmax_open_orders_per_symbol = 5\n[symbols.7]\nmax_open_orders_per_symbol = 40\n[symbols.8]\nmax_position = 1\n",
+        )
+        .unwrap();
+        let checker = cfg.checker();
+        assert_eq!(checker.symbol_open_order_limit(7), 40);
+        assert_eq!(checker.symbol_open_order_limit(8), 5);
+    }
+
+    #[test]
+    fn empty_is_defaults() {
+        assert_eq!(
+            RiskConfig::from_toml_str("").unwrap(),
+            RiskConfig::default()
+        );
+    }
+
+    #[test]
+    fn unknown_field_has_path_and_line() {
+        let e = err_of("[limits]\nmax_order_size = 5\nmax_leverage = 3\n");
+        assert_eq!(e.line, 3);
+        assert_eq!(e.path, "limits.max_leverage");
+        assert_eq!(e.kind, ConfigErrorKind::UnknownField);
+        assert_eq!(e.to_string(), "line 3: limits.max_leverage: unknown field");
+    }
+
+    #[test]
+    fn unknown_table() {
+        let e = err_of("[limitz]\n");
+        assert_eq!((e.line, e.kind), (1, ConfigErrorKind::UnknownTable));
+        let e = err_of("[symbols.abc]\n");
+        assert_eq!(e.kind, ConfigErrorKind::UnknownTable);
+    }
+
+    #[test]
+    fn key_outside_table() {
+        let e = err_of("max_order_size = 5\n");
+        assert_eq!(e.kind, ConfigErrorKind::UnknownField);
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let e = err_of("[breaker]\nwindow_ns = \"1s\"\n");
+        assert_eq!(e.path, "breaker.window_ns");
+        assert_eq!(
+            e.kind,
+            ConfigErrorKind::TypeMismatch {
+                expected: "integer",
+                found: "string"
+            }
+        );
+        let e = err_of("[symbols.3]\nmax_position = true\n");
+        assert_eq!(e.path, "symbols.3.max_position");
+        assert!(matches!(e.kind, ConfigErrorKind::TypeMismatch { .. }));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let e = err_of("[limits]\nmax_position = -1\n");
+        assert_eq!(e.kind, ConfigErrorKind::OutOfRange);
+        let e = err_of("[margin]\ninitial_margin_bps = 5_000_000_000\n");
+        assert_eq!(e.kind, ConfigErrorKind::OutOfRange);
+    }
+
+    #[test]
+    fn duplicates() {
+        let e = err_of("[limits]\nmax_position = 1\nmax_position = 2\n");
+        assert_eq!((e.line, e.kind), (3, ConfigErrorKind::DuplicateKey));
+        let e = err_of("[limits]\n[margin]\n[limits]\n");
+        assert_eq!((e.line, e.kind), (3, ConfigErrorKind::DuplicateTable));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(matches!(
+            err_of("[limits\n").kind,
+            ConfigErrorKind::Syntax(_)
+        ));
+        assert!(matches!(
+            err_of("[limits]\nmax_position\n").kind,
+            ConfigErrorKind::Syntax(_)
+        ));
+        assert!(matches!(
+            err_of("[limits]\nmax_position = 1__0\n").kind,
+            ConfigErrorKind::Syntax(_)
+        ));
+        assert!(matches!(
+            err_of("[limits]\nmax position = 1\n").kind,
+            ConfigErrorKind::Syntax(_)
+        ));
+    }
+
+    #[test]
+    fn semantic_validation_reports_offending_line() {
+        let e = err_of("[limits]\nmax_order_size = 1\nmax_daily_loss = 100\n");
+        assert_eq!(e.line, 3);
+        assert_eq!(e.path, "limits.max_daily_loss");
+        assert!(matches!(e.kind, ConfigErrorKind::Invalid(_)));
+
+        let e = err_of("[margin]\ninitial_margin_bps = 100\nmaintenance_margin_bps = 200\n");
+        assert_eq!(
+            (e.line, e.path.as_str()),
+            (3, "margin.maintenance_margin_bps")
+        );
+
+        let e = err_of("[breaker]\nwindow_ns = 0\n");
+        assert_eq!(e.path, "breaker.window_ns");
+
+        let e = err_of("[margin]\nspread_credit_bps = 10_001\n");
+        assert_eq!((e.line, e.path.as_str()), (2, "margin.spread_credit_bps"));
+
+        let e = err_of("[margin]\ncross_margin_offset_bps = 10_001\n");
+        assert_eq!(
+            (e.line, e.path.as_str()),
+            (2, "margin.cross_margin_offset_bps")
+        );
+    }
+
+    #[test]
+    fn product_mpor_table() {
+        let cfg = RiskConfig::from_toml_str(
+            "[margin]\nmpor_base_days = 2\nmpor_days = 2\n\n[mpor.7]\ndays = 10\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.product_mpor_days.get(&7), Some(&10));
+        let calc = cfg.margin_calculator();
+        assert_eq!((calc.mpor_days(7), calc.mpor_days(8)), (10, 2));
+
+        let e = err_of("[mpor.7]\ndays = 0\n");
+        assert_eq!((e.line, e.path.as_str()), (2, "mpor.7.days"));
+        let e = err_of("[mpor.7]\nhorizon = 1\n");
+        assert_eq!(e.path, "mpor.7.horizon");
+        let e = err_of("[margin]\nmpor_base_days = 0\n");
+        assert_eq!(e.path, "margin.mpor_base_days");
+    }
+
+    #[test]
+    fn illiquid_symbol_list() {
+        let cfg = RiskConfig::from_toml_str(
+            "[limits]\nilliquid_symbols = \"17, 42\"\nilliquid_limit_factor_bps = 2500\n",
+        )
+        .unwrap();
+        assert!(cfg.limits.is_illiquid(17) && cfg.limits.is_illiquid(42));
+        assert!(!cfg.limits.is_illiquid(7));
+        assert_eq!(cfg.limits.illiquid_limit_factor_bps, 2_500);
+
+        let e = err_of("[limits]\nilliquid_symbols = 17\n");
+        assert_eq!(e.path, "limits.illiquid_symbols");
+        let e = err_of("[limits]\nilliquid_symbols = \"17,x\"\n");
+        assert_eq!(e.path, "limits.illiquid_symbols");
+    }
+
+    #[test]
+    fn semantic_validation_default_origin_is_line_zero() {
+        // 既定の maintenance(500) が initial(100) を上回る。
+        let e = err_of("[margin]\ninitial_margin_bps = 100\n");
+        assert_eq!(e.line, 0);
+        assert_eq!(e.path, "margin.maintenance_margin_bps");
+    }
+
+    #[test]
+    fn comment_inside_string_is_kept() {
+        assert_eq!(
+            ConfigValue::parse("\"a#b\""),
+            Some(ConfigValue::Str("a#b".to_string()))
+        );
+        assert_eq!(strip_comment("k = \"a#b\" # c").trim(), "k = \"a#b\"");
+    }
+
+    // -----------------------------------------------------------------------
+    // LayeredConfig
+    // -----------------------------------------------------------------------
+
+    fn layered() -> LayeredConfig {
+        let mut l = LayeredConfig::new();
+        l.apply_file("[limits]\nmax_order_size = 50\nmax_position = 500\n")
+            .unwrap();
+        l
+    }
+
+    #[test]
+    fn provenance_tracks_each_layer() {
+        let mut l = layered();
+        l.apply_env([
+            ("ALICE_RISK_LIMITS__MAX_POSITION", "400"),
+            ("PATH", "/usr/bin"),
+        ])
+        .unwrap();
+        l.set_override("limits.max_notional", ConfigValue::Int(1_000))
+            .unwrap();
+        assert_eq!(l.provenance("limits.max_order_size"), ConfigLayer::File);
+        assert_eq!(l.provenance("limits.max_position"), ConfigLayer::Env);
+        assert_eq!(l.provenance("limits.max_notional"), ConfigLayer::Override);
+        assert_eq!(l.provenance("limits.max_open_orders"), ConfigLayer::Default);
+        assert_eq!(l.config().limits.max_position, 400);
+        assert_eq!(l.config().limits.max_notional, 1_000);
+    }
+
+    #[test]
+    fn higher_layer_wins_regardless_of_order() {
+        let mut l = LayeredConfig::new();
+        l.set_override("limits.max_order_size", ConfigValue::Int(7))
+            .unwrap();
+        l.apply_env([("ALICE_RISK_LIMITS__MAX_ORDER_SIZE", "8")])
+            .unwrap();
+        l.apply_file("[limits]\nmax_order_size = 9\n").unwrap();
+        assert_eq!(l.config().limits.max_order_size, 7);
+        assert_eq!(l.provenance("limits.max_order_size"), ConfigLayer::Override);
+    }
+
+    #[test]
+    fn shadowed_values_are_still_type_checked() {
+        let mut l = LayeredConfig::new();
+        l.set_override("limits.max_order_size", ConfigValue::Int(7))
+            .unwrap();
+        let e = l
+            .apply_file("[limits]\nmax_order_size = true\n")
+            .unwrap_err();
+        assert!(matches!(e.kind, ConfigErrorKind::TypeMismatch { .. }));
+    }
+
+    #[test]
+    fn env_symbol_override_path() {
+        let mut l = layered();
+        l.apply_env([("ALICE_RISK_SYMBOLS__42__MAX_POSITION", "10")])
+            .unwrap();
+        assert_eq!(l.config().limits_for(42).max_position, 10);
+        assert_eq!(l.provenance("symbols.42.max_position"), ConfigLayer::Env);
+    }
+
+    #[test]
+    fn env_unknown_field_is_error() {
+        let mut l = layered();
+        let e = l
+            .apply_env([("ALICE_RISK_LIMITS__MAX_LEVERAGE", "3")])
+            .unwrap_err();
+        assert_eq!(e.path, "limits.max_leverage");
+        assert_eq!(e.kind, ConfigErrorKind::UnknownField);
+        let e = l.apply_env([("ALICE_RISK_NOPE", "3")]).unwrap_err();
+        assert_eq!(e.kind, ConfigErrorKind::UnknownField);
+    }
+
+    #[test]
+    fn env_invalid_value_is_error() {
+        let mut l = layered();
+        let e = l
+            .apply_env([("ALICE_RISK_LIMITS__MAX_POSITION", "lots")])
+            .unwrap_err();
+        assert!(matches!(e.kind, ConfigErrorKind::Syntax(_)));
+    }
+
+    #[test]
+    fn override_validation_keeps_file_line() {
+        let mut l = LayeredConfig::new();
+        l.apply_file("[margin]\n\ninitial_margin_bps = 600\n")
+            .unwrap();
+        let e = l
+            .set_override("margin.maintenance_margin_bps", ConfigValue::Int(700))
+            .unwrap_err();
+        assert_eq!(e.path, "margin.maintenance_margin_bps");
+        assert_eq!(e.line, 0);
+    }
+
+    #[test]
+    fn rejected_changes_leave_config_unchanged() {
+        let mut l = layered();
+        let before = l.config().clone();
+        // 初期証拠金率（既定 1000）を下回る維持証拠金率は検証で拒否される。
+        assert!(l
+            .set_override("margin.maintenance_margin_bps", ConfigValue::Int(2_000))
+            .is_err());
+        assert_eq!(l.config(), &before);
+        assert_eq!(
+            l.provenance("margin.maintenance_margin_bps"),
+            ConfigLayer::Default
+        );
+        // 途中の値が正しくても、ファイル全体を適用しない。
+        assert!(l
+            .apply_file("[limits]\nmax_order_size = 5\n\n[margin]\nmaintenance_margin_bps = 2000\n")
+            .is_err());
+        assert!(l
+            .apply_env([
+                ("ALICE_RISK_LIMITS__MAX_ORDER_SIZE", "5"),
+                ("ALICE_RISK_LIMITS__MAX_POSITION", "lots"),
+            ])
+            .is_err());
+        assert_eq!(l.config(), &before);
+        assert_eq!(l.provenance("limits.max_order_size"), ConfigLayer::File);
+        assert_eq!(l.config().limits.max_order_size, 50);
+    }
+
+    #[test]
+    fn provenance_for_reject_prefers_symbol_override() {
+        let mut l = layered();
+        l.apply_file("[symbols.42]\nmax_position = 5\n").unwrap();
+        let reject = RiskReject::PositionLimitBreached {
+            current: 0,
+            after: 6,
+            limit: 5,
+            max_allowed: 5,
+        };
+        assert_eq!(
+            l.provenance_for_reject(&reject, Some(42)),
+            Some(("symbols.42.max_position".to_string(), ConfigLayer::File))
+        );
+        assert_eq!(
+            l.provenance_for_reject(&reject, Some(7)),
+            Some(("limits.max_position".to_string(), ConfigLayer::File))
+        );
+        let size = RiskReject::MaxOpenOrdersReached { count: 1, limit: 1 };
+        assert_eq!(
+            l.provenance_for_reject(&size, None),
+            Some(("limits.max_open_orders".to_string(), ConfigLayer::Default))
+        );
+        assert_eq!(
+            l.provenance_for_reject(&RiskReject::CircuitBreakerTripped, None),
+            None
+        );
+    }
+
+    #[test]
+    fn value_parsing() {
+        assert_eq!(ConfigValue::parse("-1_000"), Some(ConfigValue::Int(-1000)));
+        assert_eq!(ConfigValue::parse("0x10"), Some(ConfigValue::Int(16)));
+        assert_eq!(ConfigValue::parse("false"), Some(ConfigValue::Bool(false)));
+        assert_eq!(ConfigValue::parse("0x"), None);
+        assert_eq!(ConfigValue::parse("_1"), None);
+        assert_eq!(ConfigValue::parse("1.5"), None);
+    }
+}
@@ -0,0 +1,322 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 1 注文の判定に使う再利用可能な作業領域。
+//!
+//! [`DecisionContext`] は 1 回の判定の途中結果（銘柄の市場データ、評価価格、
+//! 約定前ポジション、想定元本、掛かった判定、結果）をまとめて保持する。
+//! 呼び出し側が 1 つを持ち回す（スレッドごとに `thread_local!` に置いてもよい）
+//! ことで、市場データの引き当ては 1 判定につき 1 回になり、監査記録
+//! （[`DecisionRecord`]）や受理後の予約は保持済みの値から組み立てられる。
+//! 領域はすべて固定長で、判定のたびに上書きされヒープは使わない。
+
+use alice_ledger::{Order, OrderType, Position};
+
+use crate::check::{rests_on_book, DecisionRecord, DecisionTags, PreTradeChecker, RiskReject};
+use crate::fastpath::CheckMask;
+use crate::market::{MarketContext, SymbolMarket};
+
+// ---------------------------------------------------------------------------
+// DecisionContext
+// ---------------------------------------------------------------------------
+
+/// 1 注文の判定の途中結果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionContext {
+    order_id: u64,
+    timestamp_ns: u64,
+    limit_version: u64,
+    tags: DecisionTags,
+    symbol_hash: Option<u64>,
+    market: SymbolMarket,
+    price: i64,
+    net_before: i64,
+    notional: i64,
+    resting: bool,
+    fired: CheckMask,
+    result: Result<(), RiskReject>,
+}
+
+impl DecisionContext {
+    /// 空の作業領域を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            order_id: 0,
+            timestamp_ns: 0,
+            limit_version: 0,
+            tags: DecisionTags {
+                algo_id: None,
+                dea: false,
+            },
+            symbol_hash: None,
+            market: SymbolMarket {
+                reference_price: None,
+                bbo: None,
+                adv: None,
+                volatility_bps: None,
+            },
+            price: 0,
+            net_before: 0,
+            notional: 0,
+            resting: false,
+            fired: CheckMask::EMPTY,
+            result: Ok(()),
+        }
+    }
+
+    /// `order` を `market` の市場データで判定し、途中結果を保持する。
+    ///
+    /// 判定は [`PreTradeChecker::check_order_in_market`] と同じで、直前の
+    /// 判定の内容はすべて上書きされる。
+    ///
+    /// # Errors
+    ///
+    /// いずれかの制限に掛かる場合 [`RiskReject`]（[`result`](Self::result)
+    /// と同じ値）。
+    pub fn evaluate<M: MarketContext + ?Sized>(
+        &mut self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+        market: &M,
+        tags: DecisionTags,
+    ) -> Result<(), RiskReject> {
+        self.order_id = order.id.0;
+        self.timestamp_ns = order.timestamp_ns;
+        self.limit_version = checker.limit_version();
+        self.tags = tags;
+        self.symbol_hash = position.map(|p| p.symbol_hash);
+        self.market = self
+            .symbol_hash
+            .map_or_else(SymbolMarket::default, |h| SymbolMarket {
+                reference_price: market.reference_price(h),
+                bbo: market.bbo(h),
+                adv: market.adv(h),
+                volatility_bps: market.volatility_bps(h),
+            });
+        self.net_before = position.map_or(0, |p| p.net_quantity);
+        self.resting = rests_on_book(order);
+
+        // 成行注文は反対側気配（無ければ参照価格）で評価する。
+        let market_price = match (self.symbol_hash, order.order_type) {
+            (Some(_), OrderType::Market) => self
+                .market
+                .bbo
+                .and_then(|b| b.touch(order.side))
+                .or(self.market.reference_price),
+            _ => None,
+        };
+        let priced;
+        let evaluated = if let Some(price) = market_price {
+            priced = Order {
+                price,
+                ..order.clone()
+            };
+            &priced
+        } else {
+            order
+        };
+        self.price = evaluated.price;
+        self.notional = (i128::from(evaluated.price) * i128::from(evaluated.quantity))
+            .unsigned_abs()
+            .min(i64::MAX as u128) as i64;
+        self.fired = checker.check_mask(evaluated, position);
+        self.result = checker
+            .reject_for(self.fired, evaluated, position)
+            .and_then(|()| match self.symbol_hash {
+                Some(_) => checker.bbo_check(evaluated, position, self.market.bbo.as_ref()),
+                None => Ok(()),
+            });
+        self.result.clone()
+    }
+
+    /// 受理した判定の注文を `checker` に予約する。
+    ///
+    /// 直前の判定が受理で、`order` がその注文のときだけ予約し `true` を返す。
+    /// 銘柄が分かっていれば銘柄別の未約定注文数にも数える。
+    pub fn commit(&self, checker: &mut PreTradeChecker, order: &Order) -> bool {
+        if self.result.is_err() || order.id.0 != self.order_id {
+            return false;
+        }
+        match self.symbol_hash {
+            Some(symbol_hash) => checker.on_order_accepted_in(order, symbol_hash),
+            None => checker.on_order_accepted(order),
+        }
+        true
+    }
+
+    /// 監査記録。
+    #[must_use]
+    pub fn record(&self) -> DecisionRecord {
+        DecisionRecord {
+            order_id: self.order_id,
+            timestamp_ns: self.timestamp_ns,
+            limit_version: self.limit_version,
+            result: self.result.clone(),
+            tags: self.tags,
+        }
+    }
+
+    /// 判定結果。
+    pub const fn result(&self) -> &Result<(), RiskReject> {
+        &self.result
+    }
+
+    /// 掛かった判定（プライスカラーは含まない）。
+    #[must_use]
+    pub const fn fired(&self) -> CheckMask {
+        self.fired
+    }
+
+    /// 銘柄（ポジションが渡されなかった場合は `None`）。
+    #[must_use]
+    pub const fn symbol_hash(&self) -> Option<u64> {
+        self.symbol_hash
+    }
+
+    /// 判定時に引き当てた銘柄の市場データ。
+    #[must_use]
+    pub const fn market(&self) -> &SymbolMarket {
+        &self.market
+    }
+
+    /// 評価に使った価格（成行注文は気配または参照価格）。
+    #[must_use]
+    pub const fn price(&self) -> i64 {
+        self.price
+    }
+
+    /// 約定前のネットポジション。
+    #[must_use]
+    pub const fn net_before(&self) -> i64 {
+        self.net_before
+    }
+
+    /// 評価価格での想定元本の絶対値（`i64` の範囲に丸める）。
+    #[must_use]
+    pub const fn notional(&self) -> i64 {
+        self.notional
+    }
+
+    /// 板に滞留する注文か。
+    #[must_use]
+    pub const fn resting(&self) -> bool {
+        self.resting
+    }
+}
+
+impl Default for DecisionContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::market::{Bbo, MarketSnapshot};
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+
+    fn order(id: u64, order_type: OrderType, price: i64, quantity: u64) -> Order {
+        let builder = OrderBuilder::bid(price, quantity).id(id).at(5);
+        if matches!(order_type, OrderType::Market) {
+            builder.market().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn position() -> Position {
+        PositionBuilder::new(7, 3).avg_entry_price(0).build()
+    }
+
+    fn market() -> MarketSnapshot {
+        let mut snapshot = MarketSnapshot::new();
+        snapshot.insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(1_005),
+                bbo: Some(Bbo::new(1_000, 1_010)),
+                ..SymbolMarket::default()
+            },
+        );
+        snapshot
+    }
+
+    #[test]
+    fn matches_check_order_in_market() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let snapshot = market();
+        let held = position();
+        let mut ctx = DecisionContext::new();
+        for o in [
+            order(1, OrderType::Limit, 1_010, 5),
+            order(2, OrderType::Limit, 2_000, 5),
+            order(3, OrderType::Market, 0, 50),
+            order(4, OrderType::Limit, 1_000, 500),
+        ] {
+            let got = ctx.evaluate(
+                &checker,
+                &o,
+                Some(&held),
+                &snapshot,
+                DecisionTags::default(),
+            );
+            assert_eq!(
+                got,
+                checker.check_order_in_market(&o, Some(&held), &snapshot)
+            );
+            assert_eq!(ctx.result(), &got);
+        }
+        assert_eq!(ctx.fired(), CheckMask::ORDER_SIZE);
+        let market_order = order(3, OrderType::Market, 0, 50);
+        assert!(ctx
+            .evaluate(
+                &checker,
+                &market_order,
+                Some(&held),
+                &snapshot,
+                DecisionTags::default()
+            )
+            .is_ok());
+        assert_eq!(ctx.price(), 1_010);
+        assert_eq!(ctx.notional(), 50_500);
+        assert_eq!(ctx.net_before(), 3);
+        assert_eq!(ctx.market().reference_price, Some(1_005));
+    }
+
+    #[test]
+    fn record_and_commit() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let snapshot = market();
+        let held = position();
+        let tags = DecisionTags {
+            algo_id: Some(9),
+            dea: true,
+        };
+        let mut ctx = DecisionContext::default();
+        let o = order(1, OrderType::Limit, 1_000, 5);
+        ctx.evaluate(&checker, &o, Some(&held), &snapshot, tags);
+        assert_eq!(ctx.record(), checker.decide_tagged(&o, Some(&held), tags));
+        assert!(!ctx.commit(&mut checker, &order(2, OrderType::Limit, 1_000, 5)));
+        assert!(ctx.commit(&mut checker, &o));
+        assert_eq!(checker.open_order_count(), 1);
+        assert_eq!(ctx.symbol_hash(), Some(7));
+        assert!(ctx.resting());
+
+        // 拒否は予約しない。ポジションが無ければ市場データは引き当てない。
+        let big = order(3, OrderType::Limit, 1_000, 1_000);
+        assert!(ctx.evaluate(&checker, &big, None, &snapshot, tags).is_err());
+        assert_eq!(ctx.market(), &SymbolMarket::default());
+        assert!(!ctx.commit(&mut checker, &big));
+        assert_eq!(checker.open_order_count(), 1);
+    }
+}
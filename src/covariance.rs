@@ -0,0 +1,271 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 銘柄間の共分散とボラティリティ予算。
+//!
+//! [`CovarianceMatrix`] は銘柄のリターンの共分散を持ち、銘柄ごとの想定元本
+//! から組み合わせ全体のボラティリティと、各銘柄の寄与（リスク寄与度）を
+//! 返す。寄与の合計は全体のボラティリティに一致し、負の寄与はヘッジを表す。
+//!
+//! [`VolatilityBudget`] はボラティリティの予算を銘柄に割り当てる（リスク
+//! パリティ）。1 銘柄の寄与が予算の一定割合を超え、かつ注文で寄与が増える
+//! 場合に拒否する。共分散の無い銘柄は判定しない。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::market::MarketContext;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// CovarianceMatrix
+// ---------------------------------------------------------------------------
+
+/// 銘柄のリターン（比率、0.02 = 2%）の共分散。対称で、設定の無い組は 0。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CovarianceMatrix {
+    /// (小さい銘柄, 大きい銘柄) → 共分散。
+    entries: BTreeMap<(u64, u64), f64>,
+}
+
+impl CovarianceMatrix {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    const fn key(a: u64, b: u64) -> (u64, u64) {
+        if a <= b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// `a` と `b` の共分散を設定する（`a == b` なら分散）。
+    pub fn set(&mut self, a: u64, b: u64, covariance: f64) {
+        self.entries.insert(Self::key(a, b), covariance);
+    }
+
+    /// `a` と `b` の共分散。
+    #[must_use]
+    pub fn get(&self, a: u64, b: u64) -> Option<f64> {
+        self.entries.get(&Self::key(a, b)).copied()
+    }
+
+    /// 銘柄の分散。
+    #[must_use]
+    pub fn variance(&self, symbol_hash: u64) -> Option<f64> {
+        self.get(symbol_hash, symbol_hash)
+    }
+
+    /// 想定元本 `exposures`（銘柄, ticks、売りは負）の組み合わせのボラティリティ
+    /// （ticks）。
+    #[must_use]
+    pub fn portfolio_volatility(&self, exposures: &[(u64, i64)]) -> f64 {
+        self.risk_contributions(exposures)
+            .iter()
+            .map(|&(_, c)| c)
+            .sum()
+    }
+
+    /// 銘柄ごとのボラティリティへの寄与（ticks、`exposures` の順）。合計は
+    /// [`Self::portfolio_volatility`]。全体のボラティリティが 0 なら全て 0。
+    #[must_use]
+    pub fn risk_contributions(&self, exposures: &[(u64, i64)]) -> Vec<(u64, f64)> {
+        // (Σx)_i × x_i の合計が全体の分散。
+        let weighted: Vec<(u64, f64)> = exposures
+            .iter()
+            .map(|&(s, x)| {
+                let m: f64 = exposures
+                    .iter()
+                    .map(|&(t, y)| self.get(s, t).unwrap_or(0.0) * y as f64)
+                    .sum();
+                (s, m * x as f64)
+            })
+            .collect();
+        let variance: f64 = weighted.iter().map(|&(_, w)| w).sum();
+        if variance <= 0.0 {
+            return weighted.into_iter().map(|(s, _)| (s, 0.0)).collect();
+        }
+        let volatility = variance.sqrt();
+        weighted
+            .into_iter()
+            .map(|(s, w)| (s, w / volatility))
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// VolatilityBudget
+// ---------------------------------------------------------------------------
+
+/// 組み合わせ全体のボラティリティ予算と、1 銘柄に割り当てる上限の割合。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolatilityBudget {
+    /// 組み合わせ全体のボラティリティの予算（ticks）。
+    pub budget: i64,
+    /// 1 銘柄の寄与の上限（予算に対する bps、2500 = 25%）。
+    pub max_share_bps: u32,
+}
+
+impl VolatilityBudget {
+    /// 1 銘柄の寄与の上限（ticks）。
+    #[must_use]
+    pub fn symbol_limit(&self) -> i64 {
+        (i128::from(self.budget) * i128::from(self.max_share_bps) / 10_000) as i64
+    }
+
+    /// 想定元本 `exposures` の `symbol_hash` に `delta`（ticks、売りは負）を
+    /// 加えたときの寄与を判定する。
+    ///
+    /// # Errors
+    ///
+    /// 加えた後の `symbol_hash` の寄与が上限を超え、かつ寄与が増える場合は
+    /// [`RiskReject::RiskContributionExceeded`]。
+    pub fn check(
+        &self,
+        covariance: &CovarianceMatrix,
+        exposures: &[(u64, i64)],
+        symbol_hash: u64,
+        delta: i64,
+    ) -> Result<(), RiskReject> {
+        if covariance.variance(symbol_hash).is_none() {
+            return Ok(());
+        }
+        let contribution_of = |exposures: &[(u64, i64)]| {
+            covariance
+                .risk_contributions(exposures)
+                .iter()
+                .filter(|&&(s, _)| s == symbol_hash)
+                .map(|&(_, c)| c)
+                .sum::<f64>()
+        };
+        let mut after = exposures.to_vec();
+        match after.iter_mut().find(|(s, _)| *s == symbol_hash) {
+            Some((_, x)) => *x = x.saturating_add(delta),
+            None => after.push((symbol_hash, delta)),
+        }
+        let contribution = contribution_of(&after);
+        let limit = self.symbol_limit();
+        if contribution > limit as f64 && contribution > contribution_of(exposures) {
+            return Err(RiskReject::RiskContributionExceeded {
+                symbol_hash,
+                contribution: contribution as i64,
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// `positions` を参照価格で評価した想定元本に `order` を加えて、`position`
+    /// の銘柄で [`Self::check`] の後に `checker` で判定する。`position` または
+    /// 参照価格が無ければ `checker` だけで判定する。
+    ///
+    /// # Errors
+    ///
+    /// リスク寄与度の上限の拒否、それ以外は `checker` の拒否。
+    pub fn check_order_in_market<M: MarketContext + ?Sized>(
+        &self,
+        covariance: &CovarianceMatrix,
+        checker: &PreTradeChecker,
+        positions: &[Position],
+        order: &Order,
+        position: Option<&Position>,
+        market: &M,
+    ) -> Result<(), RiskReject> {
+        if let Some(p) = position {
+            if let Some(price) = market.reference_price(p.symbol_hash) {
+                let exposures: Vec<(u64, i64)> = positions
+                    .iter()
+                    .filter_map(|q| {
+                        let price = market.reference_price(q.symbol_hash)?;
+                        Some((q.symbol_hash, notional(i128::from(q.net_quantity), price)))
+                    })
+                    .collect();
+                let quantity = i128::from(order.quantity);
+                let quantity = match order.side {
+                    Side::Bid => quantity,
+                    Side::Ask => -quantity,
+                };
+                let delta = notional(quantity, price);
+                self.check(covariance, &exposures, p.symbol_hash, delta)?;
+            }
+        }
+        checker.check_order_in_market(order, position, market)
+    }
+}
+
+/// 数量 × 価格の想定元本（ticks、i64 に飽和）。
+fn notional(quantity: i128, price: i64) -> i64 {
+    (quantity * i128::from(price)).clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> CovarianceMatrix {
+        let mut cov = CovarianceMatrix::new();
+        // ボラティリティ 2% と 1%、相関 0。
+        cov.set(1, 1, 0.0004);
+        cov.set(2, 2, 0.0001);
+        cov
+    }
+
+    #[test]
+    fn contributions_sum_to_portfolio_volatility() {
+        let mut cov = matrix();
+        let exposures = [(1, 30_000), (2, 40_000)];
+        // 分散 = 0.0004 × 30_000² + 0.0001 × 40_000² = 360_000 + 160_000。
+        let vol = cov.portfolio_volatility(&exposures);
+        assert!((vol - 520_000_f64.sqrt()).abs() < 1e-6);
+        let rc = cov.risk_contributions(&exposures);
+        assert!((rc[0].1 - 360_000.0 / vol).abs() < 1e-6);
+        assert!((rc[0].1 + rc[1].1 - vol).abs() < 1e-6);
+        // 完全な逆相関のヘッジは全体のボラティリティを消す。
+        cov.set(1, 2, -0.0002);
+        assert!(cov.portfolio_volatility(&[(1, 10_000), (2, 20_000)]).abs() < 1e-6);
+        assert_eq!(cov.get(2, 1), Some(-0.0002));
+        assert_eq!(cov.risk_contributions(&[]), Vec::new());
+    }
+
+    #[test]
+    fn rejects_dominant_symbol_growth() {
+        let cov = matrix();
+        let budget = VolatilityBudget {
+            budget: 1_000,
+            max_share_bps: 5_000,
+        };
+        assert_eq!(budget.symbol_limit(), 500);
+        let exposures = [(1, 20_000), (2, 40_000)];
+        // 銘柄 1 の寄与は 400 × 400 / √(160_000 + 160_000) ≈ 283。
+        assert!(budget.check(&cov, &exposures, 1, 5_000).is_ok());
+        assert!(matches!(
+            budget.check(&cov, &exposures, 1, 15_000),
+            Err(RiskReject::RiskContributionExceeded {
+                symbol_hash: 1,
+                limit: 500,
+                ..
+            })
+        ));
+        // 寄与を減らす注文は通す。
+        let heavy = [(1, 50_000), (2, 10_000)];
+        assert!(budget.check(&cov, &heavy, 1, -10_000).is_ok());
+        // 共分散の無い銘柄は判定しない。
+        assert!(budget.check(&cov, &exposures, 3, 1_000_000).is_ok());
+    }
+}
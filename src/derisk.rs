@@ -0,0 +1,190 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! ドローダウンに応じた自動のリスク縮小。
+//!
+//! 損失が膨らんでいる最中に人手で制限値を打ち直すのは遅く、誤りやすい。
+//! [`DeriskPolicy`] は過去最高値からの下落率（[`HighWaterMark::drawdown_bps`]）
+//! の閾値ごとに段階的な対応（[`DeriskAction`]）を決める。例えば 5% で全制限を
+//! 50% に縮め、8% で縮小のみ、10% で停止する。
+//!
+//! 対応は [`RiskEngine::with_derisk_policy`] で登録したエンジンが資産額の
+//! 更新のたびに適用する。いったん適用した対応は資産額が戻っても緩めず、
+//! [`RiskEngine::reset_derisk`] で人が解除する。
+//!
+//! [`HighWaterMark::drawdown_bps`]: crate::drawdown::HighWaterMark::drawdown_bps
+//! [`RiskEngine::with_derisk_policy`]: crate::engine::RiskEngine::with_derisk_policy
+//! [`RiskEngine::reset_derisk`]: crate::engine::RiskEngine::reset_derisk
+
+use alloc::vec::Vec;
+
+extern crate alloc;
+
+/// エンジンが縮小・解除した制限バージョンの作成者
+/// （[`LimitVersion::author`](crate::limit::LimitVersion::author)）。
+pub const DERISK_AUTHOR: &str = "derisk";
+
+// ---------------------------------------------------------------------------
+// DeriskAction
+// ---------------------------------------------------------------------------
+
+/// 閾値に達したときの対応。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeriskAction {
+    /// 全制限を `factor_bps`（10000 = そのまま）に縮める
+    /// （[`RiskLimits::scaled`](crate::limit::RiskLimits::scaled)）。
+    Scale { factor_bps: u32 },
+    /// 建玉を減らす注文のみ許可する。
+    ReduceOnly,
+    /// サーキットブレーカーを作動させて全注文を止める。
+    Halt,
+}
+
+/// 下落率の閾値と対応。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeriskStep {
+    /// 過去最高値からの下落率（bps）。この値以上で対応する。
+    pub drawdown_bps: u32,
+    /// 対応。
+    pub action: DeriskAction,
+}
+
+// ---------------------------------------------------------------------------
+// DeriskState
+// ---------------------------------------------------------------------------
+
+/// 達した段階の対応をまとめた状態。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeriskState {
+    /// 制限の縮小率（bps、10000 = 縮小なし）。達した段階のうち最も小さい値。
+    pub factor_bps: u32,
+    /// 縮小のみか（[`DeriskAction::ReduceOnly`] または [`DeriskAction::Halt`]
+    /// に達した）。
+    pub reduce_only: bool,
+    /// 停止したか。
+    pub halted: bool,
+}
+
+impl DeriskState {
+    /// 対応なし。
+    pub const NONE: Self = Self {
+        factor_bps: 10_000,
+        reduce_only: false,
+        halted: false,
+    };
+
+    /// 何らかの対応をしているか。
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.factor_bps < 10_000 || self.reduce_only || self.halted
+    }
+
+    /// 2 つの状態のうち厳しい方を段ごとに取る。
+    #[must_use]
+    pub fn merge(&self, other: &Self) -> Self {
+        Self {
+            factor_bps: self.factor_bps.min(other.factor_bps),
+            reduce_only: self.reduce_only || other.reduce_only,
+            halted: self.halted || other.halted,
+        }
+    }
+}
+
+impl Default for DeriskState {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DeriskPolicy
+// ---------------------------------------------------------------------------
+
+/// 下落率の閾値ごとの対応。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeriskPolicy {
+    /// 閾値の昇順。
+    steps: Vec<DeriskStep>,
+}
+
+impl DeriskPolicy {
+    /// 段階から作成（閾値の昇順に並べ替える）。
+    #[must_use]
+    pub fn new(mut steps: Vec<DeriskStep>) -> Self {
+        steps.sort_by_key(|s| s.drawdown_bps);
+        Self { steps }
+    }
+
+    /// 段階（閾値の昇順）。
+    #[must_use]
+    pub fn steps(&self) -> &[DeriskStep] {
+        &self.steps
+    }
+
+    /// 下落率 `drawdown_bps` で達している全段階の対応。
+    #[must_use]
+    pub fn state(&self, drawdown_bps: u32) -> DeriskState {
+        let mut state = DeriskState::NONE;
+        for step in self
+            .steps
+            .iter()
+            .take_while(|s| s.drawdown_bps <= drawdown_bps)
+        {
+            match step.action {
+                DeriskAction::Scale { factor_bps } => {
+                    state.factor_bps = state.factor_bps.min(factor_bps);
+                }
+                DeriskAction::ReduceOnly => state.reduce_only = true,
+                DeriskAction::Halt => {
+                    state.reduce_only = true;
+                    state.halted = true;
+                }
+            }
+        }
+        state
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_accumulate_by_drawdown() {
+        let policy = DeriskPolicy::new(alloc::vec![
+            DeriskStep {
+                drawdown_bps: 1_000,
+                action: DeriskAction::Halt,
+            },
+            DeriskStep {
+                drawdown_bps: 500,
+                action: DeriskAction::Scale { factor_bps: 5_000 },
+            },
+            DeriskStep {
+                drawdown_bps: 800,
+                action: DeriskAction::ReduceOnly,
+            },
+        ]);
+        assert_eq!(policy.steps()[0].drawdown_bps, 500);
+        assert_eq!(policy.state(499), DeriskState::NONE);
+        assert!(!policy.state(499).is_active());
+        assert_eq!(
+            policy.state(500),
+            DeriskState {
+                factor_bps: 5_000,
+                reduce_only: false,
+                halted: false,
+            }
+        );
+        assert!(policy.state(800).reduce_only);
+        let halted = policy.state(1_200);
+        assert_eq!((halted.factor_bps, halted.halted), (5_000, true));
+        assert_eq!(policy.state(1_200).merge(&policy.state(0)), halted);
+    }
+}
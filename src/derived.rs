@@ -0,0 +1,372 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 銘柄ごとの派生閾値のキャッシュ。
+//!
+//! 気配に対する価格の妥当性（プライスカラー）や値洗い価格での最大数量は、
+//! 制限と市場データから毎回の除算・乗算で求まる。[`ThresholdCache`] はこれを
+//! 市場データの更新時と制限の変更時（[`PreTradeChecker::limit_version`] の
+//! 変化）にだけ計算し直し、注文ごとの判定を比較だけにする。
+//!
+//! [`ThresholdCache::check_order`] の結果は、キャッシュに登録した市場データを
+//! [`MarketContext`](crate::MarketContext) として
+//! [`PreTradeChecker::check_order_in_market`] を呼んだ場合と一致する。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, OrderType, Position, Side};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+use crate::market::{Bbo, SymbolMarket};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// DerivedThresholds
+// ---------------------------------------------------------------------------
+
+/// 1 銘柄分の派生閾値。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DerivedThresholds {
+    /// 成行の買いの評価価格（売り気配、無ければ参照価格）。
+    pub market_buy_price: Option<i64>,
+    /// 成行の売りの評価価格（買い気配、無ければ参照価格）。
+    pub market_sell_price: Option<i64>,
+    /// 指値の買いの上限価格（プライスカラー）。`None` は制限なし。
+    pub max_buy_price: Option<i64>,
+    /// 指値の売りの下限価格（プライスカラー）。`None` は制限なし。
+    pub min_sell_price: Option<i64>,
+    /// 参照価格で評価した 1 注文の最大数量（注文数量と想定元本の制限の小さい方）。
+    pub max_quantity: u64,
+}
+
+impl DerivedThresholds {
+    /// `limits` と `market` から派生閾値を計算する。
+    #[must_use]
+    pub fn compute(limits: &RiskLimits, market: &SymbolMarket) -> Self {
+        let checks = limits.checks;
+        let touch = |side| market.bbo.and_then(|b: Bbo| b.touch(side));
+        let collar = |side| {
+            if checks.bbo_reasonability {
+                touch(side).and_then(|t| collar_bound(side, t, limits.max_through_bbo_bps))
+            } else {
+                None
+            }
+        };
+        let mut max_quantity = if checks.order_size {
+            limits.max_order_size
+        } else {
+            u64::MAX
+        };
+        if let (true, Some(mark)) = (checks.notional, market.reference_price) {
+            if mark != 0 {
+                let room = limits.max_notional.max(0).unsigned_abs() / mark.unsigned_abs();
+                max_quantity = max_quantity.min(room);
+            }
+        }
+        Self {
+            market_buy_price: touch(Side::Bid).or(market.reference_price),
+            market_sell_price: touch(Side::Ask).or(market.reference_price),
+            max_buy_price: collar(Side::Bid),
+            min_sell_price: collar(Side::Ask),
+            max_quantity,
+        }
+    }
+
+    /// `side` の成行注文の評価価格。
+    #[must_use]
+    pub const fn market_price(&self, side: Side) -> Option<i64> {
+        match side {
+            Side::Bid => self.market_buy_price,
+            Side::Ask => self.market_sell_price,
+        }
+    }
+
+    /// 指値 `price` がプライスカラーの外側にあるか。
+    #[must_use]
+    pub const fn outside_collar(&self, side: Side, price: i64) -> bool {
+        match side {
+            Side::Bid => matches!(self.max_buy_price, Some(max) if price > max),
+            Side::Ask => matches!(self.min_sell_price, Some(min) if price < min),
+        }
+    }
+}
+
+/// [`Bbo::through_bps`] が `limit_bps` を超えない最も外側の価格。
+///
+/// 突き抜け幅 `t`、気配の絶対値 `b` について `⌊t·10000 / b⌋ > limit` が拒否
+/// 条件なので、許される最大幅は `⌈(limit + 1)·b / 10000⌉ − 1`。`limit` が
+/// `u32::MAX` なら拒否は起きないため `None`。
+fn collar_bound(side: Side, touch: i64, limit_bps: u32) -> Option<i64> {
+    if limit_bps == u32::MAX {
+        return None;
+    }
+    let base = i128::from(touch).abs();
+    let room = if base == 0 {
+        0
+    } else {
+        ((i128::from(limit_bps) + 1) * base + 9_999) / 10_000 - 1
+    };
+    let bound = match side {
+        Side::Bid => i128::from(touch) + room,
+        Side::Ask => i128::from(touch) - room,
+    };
+    Some(bound.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64)
+}
+
+// ---------------------------------------------------------------------------
+// ThresholdCache
+// ---------------------------------------------------------------------------
+
+/// 銘柄ごとの市場データと派生閾値。
+#[derive(Debug, Clone, Default)]
+pub struct ThresholdCache {
+    /// 派生閾値を計算したときの制限の版。
+    limit_version: Option<u64>,
+    /// 銘柄 → (市場データ, 派生閾値)。
+    symbols: BTreeMap<u64, (SymbolMarket, DerivedThresholds)>,
+}
+
+impl ThresholdCache {
+    /// 空のキャッシュを作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            limit_version: None,
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    /// 銘柄の市場データを登録し、その銘柄の派生閾値を計算し直す。
+    ///
+    /// 制限が前回の計算から変わっていれば、全銘柄を計算し直す。
+    pub fn set_market(
+        &mut self,
+        checker: &PreTradeChecker,
+        symbol_hash: u64,
+        market: SymbolMarket,
+    ) {
+        self.sync(checker);
+        let derived = DerivedThresholds::compute(checker.limits(), &market);
+        self.symbols.insert(symbol_hash, (market, derived));
+    }
+
+    /// 銘柄の登録を外す。
+    pub fn remove(&mut self, symbol_hash: u64) {
+        self.symbols.remove(&symbol_hash);
+    }
+
+    /// 制限が変わっていれば全銘柄の派生閾値を計算し直す。
+    pub fn sync(&mut self, checker: &PreTradeChecker) {
+        let version = checker.limit_version();
+        if self.limit_version == Some(version) {
+            return;
+        }
+        let limits = checker.limits();
+        for (market, derived) in self.symbols.values_mut() {
+            *derived = DerivedThresholds::compute(limits, market);
+        }
+        self.limit_version = Some(version);
+    }
+
+    /// 銘柄の派生閾値（最後に計算した値）。
+    #[must_use]
+    pub fn get(&self, symbol_hash: u64) -> Option<&DerivedThresholds> {
+        self.symbols.get(&symbol_hash).map(|(_, d)| d)
+    }
+
+    /// 登録済みの銘柄数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// 銘柄が登録されていないか。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// キャッシュした派生閾値で `order` を判定する。
+    ///
+    /// 成行注文は反対側気配（無ければ参照価格）で評価し、指値注文はプライス
+    /// カラーと比較する。ポジションが無い、または銘柄が未登録なら
+    /// [`PreTradeChecker::check_order`] と同じ判定になる。
+    ///
+    /// # Errors
+    ///
+    /// いずれかの制限に掛かる場合 [`RiskReject`]。
+    pub fn check_order(
+        &mut self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.sync(checker);
+        let Some((market, derived)) = position.and_then(|p| self.symbols.get(&p.symbol_hash))
+        else {
+            return checker.check_order_fast(order, position);
+        };
+        if matches!(order.order_type, OrderType::Market) {
+            if let Some(price) = derived.market_price(order.side) {
+                let priced = Order {
+                    price,
+                    ..order.clone()
+                };
+                return checker.check_order_fast(&priced, position);
+            }
+            return checker.check_order_fast(order, position);
+        }
+        checker.check_order_fast(order, position)?;
+        if derived.outside_collar(order.side, order.price) {
+            return Err(collar_reject(checker, order, market));
+        }
+        Ok(())
+    }
+}
+
+/// プライスカラーの拒否を組み立てる。
+#[cold]
+#[inline(never)]
+fn collar_reject(checker: &PreTradeChecker, order: &Order, market: &SymbolMarket) -> RiskReject {
+    let bbo = market.bbo.unwrap_or_default();
+    RiskReject::PriceThroughBbo {
+        price: order.price,
+        touch: bbo.touch(order.side).unwrap_or(0),
+        through_bps: bbo.through_bps(order.side, order.price).unwrap_or(0),
+        limit_bps: checker.limits().max_through_bbo_bps,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::EnabledChecks;
+    use crate::market::MarketSnapshot;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+
+    use proptest::prelude::*;
+
+    fn order(side: Side, order_type: OrderType, price: i64, quantity: u64) -> Order {
+        let builder = OrderBuilder::new(side, price, quantity);
+        if matches!(order_type, OrderType::Market) {
+            builder.market().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn position(symbol_hash: u64) -> Position {
+        PositionBuilder::new(symbol_hash, 0)
+            .avg_entry_price(0)
+            .build()
+    }
+
+    fn quoted(bid: i64, ask: i64) -> SymbolMarket {
+        SymbolMarket {
+            reference_price: Some(i64::midpoint(bid, ask)),
+            bbo: Some(Bbo::new(bid, ask)),
+            ..SymbolMarket::default()
+        }
+    }
+
+    #[test]
+    fn derives_collar_and_quantity() {
+        let limits = RiskLimits {
+            max_through_bbo_bps: 100,
+            max_notional: 50_000,
+            ..RiskLimits::default()
+        };
+        let d = DerivedThresholds::compute(&limits, &quoted(990, 1_000));
+        assert_eq!(d.max_buy_price, Some(1_010));
+        assert_eq!(d.min_sell_price, Some(981));
+        assert_eq!(d.market_price(Side::Bid), Some(1_000));
+        assert_eq!(d.max_quantity, 50);
+        assert!(d.outside_collar(Side::Bid, 1_011));
+        assert!(!d.outside_collar(Side::Ask, 981));
+    }
+
+    #[test]
+    fn recomputes_on_limit_and_market_changes() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_through_bbo_bps: 100,
+            ..RiskLimits::default()
+        });
+        let mut cache = ThresholdCache::new();
+        cache.set_market(&checker, 7, quoted(990, 1_000));
+        let held = position(7);
+        let buy = order(Side::Bid, OrderType::Limit, 1_015, 1);
+        assert!(matches!(
+            cache.check_order(&checker, &buy, Some(&held)),
+            Err(RiskReject::PriceThroughBbo {
+                touch: 1_000,
+                through_bps: 150,
+                limit_bps: 100,
+                ..
+            })
+        ));
+        checker.set_limits(RiskLimits {
+            max_through_bbo_bps: 200,
+            ..RiskLimits::default()
+        });
+        assert!(cache.check_order(&checker, &buy, Some(&held)).is_ok());
+        assert_eq!(cache.get(7).and_then(|d| d.max_buy_price), Some(1_020));
+        cache.set_market(&checker, 7, quoted(1_090, 1_100));
+        assert_eq!(cache.get(7).and_then(|d| d.max_buy_price), Some(1_122));
+        cache.remove(7);
+        assert!(cache.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn matches_check_order_in_market(
+            limit_bps in prop_oneof![0_u32..500, Just(u32::MAX), Just(u32::MAX - 1)],
+            collar in any::<bool>(),
+            bid in prop_oneof![-2_000_i64..2_000, Just(i64::MIN), Just(i64::MAX)],
+            spread in 0_i64..50,
+            reference in proptest::option::of(-100_i64..3_000),
+            quoted in any::<bool>(),
+            market in any::<bool>(),
+            side in any::<bool>(),
+            price in prop_oneof![-3_000_i64..3_000, Just(i64::MIN), Just(i64::MAX)],
+            quantity in 1_u64..2_000,
+            registered in any::<bool>(),
+        ) {
+            let limits = RiskLimits {
+                max_through_bbo_bps: limit_bps,
+                checks: EnabledChecks {
+                    bbo_reasonability: collar,
+                    ..EnabledChecks::ALL
+                },
+                ..RiskLimits::default()
+            };
+            let checker = PreTradeChecker::new(limits);
+            let data = SymbolMarket {
+                reference_price: reference,
+                bbo: quoted.then(|| Bbo::new(bid, bid.saturating_add(spread))),
+                ..SymbolMarket::default()
+            };
+            let mut snapshot = MarketSnapshot::new();
+            let mut cache = ThresholdCache::new();
+            if registered {
+                snapshot.insert(3, data);
+                cache.set_market(&checker, 3, data);
+            }
+            let side = if side { Side::Bid } else { Side::Ask };
+            let kind = if market { OrderType::Market } else { OrderType::Limit };
+            let o = order(side, kind, price, quantity);
+            let held = position(3);
+            prop_assert_eq!(
+                cache.check_order(&checker, &o, Some(&held)),
+                checker.check_order_in_market(&o, Some(&held), &snapshot)
+            );
+        }
+    }
+}
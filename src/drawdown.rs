@@ -0,0 +1,268 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 資産額の高値（ハイウォーターマーク）とドローダウン。
+//!
+//! [`HighWaterMark`] は口座の資産額の過去最高値を日をまたいで保持し、
+//! 高値からの下落（ドローダウン）と、その日の最高値からの下落（ギブバック）を
+//! 返す。日次損失上限は日初からの損益しか見ないので、数日かけて少しずつ
+//! 失う口座や、日中の大きな利益を吐き出す口座を止められない。
+//! [`DrawdownLimits`] はこの 2 つに上限を設け、[`HighWaterMark::check`] が
+//! 判定する。
+//!
+//! 高値は日中のカウンタと違い設定から復元できないので、
+//! [`HighWaterMark::to_bytes`] でスナップショットに含めて永続化する。
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+
+// ---------------------------------------------------------------------------
+// HighWaterMark
+// ---------------------------------------------------------------------------
+
+/// 資産額の過去最高値と、その日の始値・最高値。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighWaterMark {
+    /// 過去最高の資産額。
+    high_water: i64,
+    /// 日初の資産額。
+    day_start: i64,
+    /// その日の最高の資産額。
+    day_peak: i64,
+    /// 最新の資産額。
+    equity: i64,
+}
+
+impl HighWaterMark {
+    /// 直列化した長さ（バイト）。
+    pub const LEN: usize = 32;
+
+    /// 現在の資産額を高値・日初として作成。
+    #[must_use]
+    pub const fn new(equity: i64) -> Self {
+        Self {
+            high_water: equity,
+            day_start: equity,
+            day_peak: equity,
+            equity,
+        }
+    }
+
+    /// 資産額を更新する。
+    pub fn on_equity(&mut self, equity: i64) {
+        self.equity = equity;
+        self.high_water = self.high_water.max(equity);
+        self.day_peak = self.day_peak.max(equity);
+    }
+
+    /// 日を切り替える。現在の資産額を日初・その日の最高値とし、過去最高値は
+    /// 残す。
+    pub fn roll_day(&mut self) {
+        self.day_start = self.equity;
+        self.day_peak = self.equity;
+    }
+
+    /// 過去最高の資産額。
+    #[must_use]
+    pub const fn high_water(&self) -> i64 {
+        self.high_water
+    }
+
+    /// 日初の資産額。
+    #[must_use]
+    pub const fn day_start(&self) -> i64 {
+        self.day_start
+    }
+
+    /// その日の最高の資産額。
+    #[must_use]
+    pub const fn day_peak(&self) -> i64 {
+        self.day_peak
+    }
+
+    /// 最新の資産額。
+    #[must_use]
+    pub const fn equity(&self) -> i64 {
+        self.equity
+    }
+
+    /// 過去最高値からの下落（ticks、0 以上）。
+    #[must_use]
+    pub const fn drawdown(&self) -> i64 {
+        self.high_water.saturating_sub(self.equity)
+    }
+
+    /// 過去最高値に対する下落率（bps）。最高値が 0 以下なら 0。
+    #[must_use]
+    pub fn drawdown_bps(&self) -> u32 {
+        if self.high_water <= 0 {
+            return 0;
+        }
+        (i128::from(self.drawdown()) * 10_000 / i128::from(self.high_water))
+            .min(i128::from(u32::MAX)) as u32
+    }
+
+    /// その日の最高値からの下落（ticks、0 以上）。
+    #[must_use]
+    pub const fn give_back(&self) -> i64 {
+        self.day_peak.saturating_sub(self.equity)
+    }
+
+    /// その日の最高時点の利益（日初からの増加）のうち吐き出した割合（bps）。
+    /// 日中に利益が無ければ 0。
+    #[must_use]
+    pub fn give_back_bps(&self) -> u32 {
+        let peak_profit = i128::from(self.day_peak) - i128::from(self.day_start);
+        if peak_profit <= 0 {
+            return 0;
+        }
+        (i128::from(self.give_back()) * 10_000 / peak_profit).min(i128::from(u32::MAX)) as u32
+    }
+
+    /// ドローダウンとギブバックの上限を判定する。
+    ///
+    /// # Errors
+    ///
+    /// 過去最高値からの下落が上限以上なら [`RiskReject::TrailingDrawdownHit`]、
+    /// その日の最高値からの下落が上限以上なら [`RiskReject::GiveBackLimitHit`]。
+    pub fn check(&self, limits: &DrawdownLimits) -> Result<(), RiskReject> {
+        if let Some(limit) = limits.max_drawdown {
+            let drawdown = self.drawdown();
+            if drawdown >= limit {
+                return Err(RiskReject::TrailingDrawdownHit { drawdown, limit });
+            }
+        }
+        if let Some(limit) = limits.max_give_back {
+            let give_back = self.give_back();
+            if give_back >= limit {
+                return Err(RiskReject::GiveBackLimitHit { give_back, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// [`Self::check`] の後に `checker` で判定する。
+    ///
+    /// # Errors
+    ///
+    /// ドローダウン・ギブバックの上限の拒否、それ以外は `checker` の拒否。
+    pub fn check_order(
+        &self,
+        limits: &DrawdownLimits,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.check(limits)?;
+        checker.check_order(order, position)
+    }
+
+    /// リトルエンディアンの固定長バイト列。
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0_u8; Self::LEN];
+        out[0..8].copy_from_slice(&self.high_water.to_le_bytes());
+        out[8..16].copy_from_slice(&self.day_start.to_le_bytes());
+        out[16..24].copy_from_slice(&self.day_peak.to_le_bytes());
+        out[24..32].copy_from_slice(&self.equity.to_le_bytes());
+        out
+    }
+
+    /// [`Self::to_bytes`] の逆変換。長さが違う場合は `None`。
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN {
+            return None;
+        }
+        let i64_at = |i: usize| {
+            let mut b = [0_u8; 8];
+            b.copy_from_slice(&bytes[i..i + 8]);
+            i64::from_le_bytes(b)
+        };
+        Some(Self {
+            high_water: i64_at(0),
+            day_start: i64_at(8),
+            day_peak: i64_at(16),
+            equity: i64_at(24),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DrawdownLimits
+// ---------------------------------------------------------------------------
+
+/// ドローダウンとギブバックの上限（ticks）。`None` は判定しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DrawdownLimits {
+    /// 過去最高値からの下落の上限（トレーリング・ドローダウン）。
+    pub max_drawdown: Option<i64>,
+    /// その日の最高値からの下落の上限（ギブバック）。
+    pub max_give_back: Option<i64>,
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_water_survives_day_roll() {
+        let mut hwm = HighWaterMark::new(10_000);
+        hwm.on_equity(12_000);
+        hwm.on_equity(11_400);
+        assert_eq!((hwm.drawdown(), hwm.drawdown_bps()), (600, 500));
+        // 2_000 の利益のうち 600 を吐き出した。
+        assert_eq!((hwm.give_back(), hwm.give_back_bps()), (600, 3_000));
+
+        hwm.roll_day();
+        assert_eq!(hwm.high_water(), 12_000);
+        assert_eq!((hwm.day_start(), hwm.day_peak()), (11_400, 11_400));
+        hwm.on_equity(11_000);
+        assert_eq!(hwm.drawdown(), 1_000);
+        assert_eq!((hwm.give_back(), hwm.give_back_bps()), (400, 0));
+
+        let restored = HighWaterMark::from_bytes(&hwm.to_bytes()).unwrap();
+        assert_eq!(restored, hwm);
+        assert_eq!(HighWaterMark::from_bytes(&[0; 31]), None);
+    }
+
+    #[test]
+    fn trailing_drawdown_and_give_back_limits() {
+        let limits = DrawdownLimits {
+            max_drawdown: Some(1_000),
+            max_give_back: Some(500),
+        };
+        let mut hwm = HighWaterMark::new(10_000);
+        hwm.on_equity(10_400);
+        hwm.roll_day();
+        hwm.on_equity(10_000);
+        assert!(hwm.check(&limits).is_ok());
+        hwm.on_equity(9_900);
+        assert_eq!(
+            hwm.check(&limits),
+            Err(RiskReject::GiveBackLimitHit {
+                give_back: 500,
+                limit: 500,
+            })
+        );
+        // 数日かけた下落は日次の判定では見えなくても、高値からは数える。
+        hwm.roll_day();
+        assert!(hwm.check(&limits).is_ok());
+        hwm.on_equity(9_400);
+        assert_eq!(
+            hwm.check(&limits),
+            Err(RiskReject::TrailingDrawdownHit {
+                drawdown: 1_000,
+                limit: 1_000,
+            })
+        );
+        assert!(hwm.check(&DrawdownLimits::default()).is_ok());
+    }
+}
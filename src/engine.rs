@@ -0,0 +1,1753 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 複数口座をまとめて管理するリスクエンジンと、ダッシュボード用スナップショット。
+//!
+//! [`RiskEngine`] は口座ごとの [`PreTradeChecker`]・資産額・建玉と、共通の
+//! 証拠金計算器・市場データを保持する。[`RiskEngine::dashboard_snapshot`] は
+//! 全口座の制限使用率・損益・ブレーカー状態・証拠金カバレッジと、想定元本の
+//! 大きい建玉を 1 つの [`DashboardSnapshot`] にまとめる。ダッシュボードは
+//! 口座ごとに何十回も問い合わせる代わりに、これを 1 回取得して
+//! [`DashboardSnapshot::to_json`] で配信すればよい。
+//!
+//! [`RiskEngine::with_alert_sink`] で [`AlertSink`] を登録すると、ブレーカー
+//! 作動・キルスイッチ・マージンコール・日次損失上限到達を通知する。
+//!
+//! [`RiskEngine::roll_session`] はセッション切り替え時に、口座の通貨の
+//! [`InterestSchedule`] に従って余剰担保・不足証拠金の金利を資産額へ計上し、
+//! 日次の状態をリセットする。
+//!
+//! 維持証拠金は既定でエンジン共通の bps 方式で計算し、
+//! [`RiskEngine::set_margin_model`] で口座ごとに Reg-T 方式などの
+//! [`MarginModel`] に切り替えられる。[`RiskEngine::set_account_type`] は
+//! 口座種別（[`AccountType`]）に応じた証拠金モデルを割り当て、現金口座の
+//! 売り建て・レバレッジを [`RiskEngine::check_account_order`] で拒否する。
+//!
+//! [`RiskEngine::with_auction_mode`] で [`AuctionMode`] を登録すると、
+//! [`RiskEngine::check_order_in_market`] はオークション中の規則で判定する。
+//!
+//! 口座ごとに資産額の過去最高値（[`HighWaterMark`]）を日をまたいで保持し、
+//! ダッシュボードに高値からの下落率を載せる。永続化には
+//! [`RiskEngine::high_water`] のバイト列を保存し、起動時に
+//! [`RiskEngine::restore_high_water`] で戻す。
+//!
+//! [`RiskEngine::with_derisk_policy`] で [`DeriskPolicy`] を登録すると、
+//! 資産額の更新のたびに下落率の閾値に応じて制限の縮小・縮小のみ・停止を
+//! 自動で適用する。
+//!
+//! [`RiskEngine::suggest_max_quantity`] は現在の参照価格で全ての判定を通る
+//! 最大の発注数量を返す。[`RiskEngine::projected_exposure`] は未約定注文が
+//! 全て約定した場合の最悪のエクスポージャーと証拠金を返し、
+//! [`RiskEngine::check_projected`] はそれを上限として判定する。
+//!
+//! 口座は証拠金通貨の資産額のほかに、他通貨の残高
+//! （[`RiskEngine::set_balance`]）を持てる。他通貨の残高には通貨ごとの料率で
+//! 金利を計上し、[`RiskEngine::consolidated_equity`] は
+//! [`RiskEngine::with_fx_rates`] のレートで証拠金通貨に換算した合計を返す。
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::account::{AccountReject, AccountType};
+use crate::alert::{AlertEvent, AlertSink};
+use crate::auction::AuctionMode;
+use crate::check::{
+    notional_headroom, position_headroom, PreTradeChecker, RiskReject, Utilization,
+};
+use crate::derisk::{DeriskPolicy, DeriskState, DERISK_AUTHOR};
+use crate::drawdown::HighWaterMark;
+use crate::fx::{self, FxError, FxRates};
+use crate::instrument::ExpiryRestriction;
+use crate::interest::InterestSchedule;
+use crate::limit::RiskLimits;
+use crate::margin::{MarginCalculator, MarginParams, MarkStaleness, StaleMark};
+use crate::market::{MarketContext, MarketSnapshot};
+use crate::orders::WorkingOrder;
+use crate::regt::MarginModel;
+
+extern crate alloc;
+
+/// スナップショットに含める建玉の既定件数。
+pub const DEFAULT_TOP_EXPOSURES: usize = 10;
+
+// ---------------------------------------------------------------------------
+// DashboardSnapshot
+// ---------------------------------------------------------------------------
+
+/// 1 口座の状態。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    /// 口座 ID。
+    pub account_id: u64,
+    /// 制限の使用状況（日次損益・ブレーカー状態を含む）。
+    pub utilization: Utilization,
+    /// 資産額（ticks）。
+    pub equity: i64,
+    /// 資産額の過去最高値（ticks）。
+    pub high_water: i64,
+    /// 過去最高値からの下落率（bps）。
+    pub drawdown_bps: u32,
+    /// 維持証拠金（ticks）。参照価格の無い建玉がある場合は `None`。
+    pub maintenance_margin: Option<i64>,
+    /// 資産額 / 維持証拠金（bps、10000 = ちょうど充足）。維持証拠金が 0 または
+    /// 不明の場合は `None`。
+    pub margin_coverage_bps: Option<u32>,
+    /// 未約定注文が全て約定した場合の最悪のエクスポージャー。参照価格の無い
+    /// 銘柄がある場合は `None`。
+    pub projected: Option<ExposureProjection>,
+}
+
+/// 未約定注文が全て指値で約定したと仮定したエクスポージャーと証拠金。
+///
+/// 銘柄ごとに、買い注文が全て約定した場合と売り注文が全て約定した場合の
+/// うち想定元本の絶対値が大きい方を最悪とする。建玉は参照価格、注文は指値で
+/// 評価し、維持証拠金は参照価格で両方の場合を計算して大きい方を取る。銘柄の
+/// 分からない注文は想定元本の総額にだけ加える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExposureProjection {
+    /// 最悪の場合の想定元本の合計（符号付き、ticks）。
+    pub net_exposure: i64,
+    /// 最悪の場合の想定元本の絶対値の合計（ticks）。
+    pub gross_exposure: i64,
+    /// 最悪の場合の維持証拠金（ticks）。
+    pub maintenance_margin: i64,
+    /// 資産額 / 最悪の場合の維持証拠金（bps）。維持証拠金が 0 の場合は
+    /// `None`。
+    pub margin_coverage_bps: Option<u32>,
+}
+
+/// 1 銘柄の建玉と未約定注文。
+#[derive(Debug, Clone, Copy, Default)]
+struct ProjectedLeg {
+    net: i64,
+    bid_quantity: u64,
+    ask_quantity: u64,
+    bid_notional: i64,
+    ask_notional: i64,
+}
+
+/// 1 建玉のエクスポージャー。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exposure {
+    /// 口座 ID。
+    pub account_id: u64,
+    /// 銘柄。
+    pub symbol_hash: u64,
+    /// ネット建玉。
+    pub net_quantity: i64,
+    /// 参照価格での想定元本（符号付き）。参照価格が無い場合は `None`。
+    pub notional: Option<i64>,
+}
+
+/// 全口座の状態をまとめたスナップショット。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DashboardSnapshot {
+    /// 口座 ID 順の口座状態。
+    pub accounts: Vec<AccountSnapshot>,
+    /// 想定元本の絶対値が大きい順の建玉（参照価格の無い建玉は末尾）。
+    pub top_exposures: Vec<Exposure>,
+    /// 全口座の日次損益の合計。
+    pub total_daily_pnl: i64,
+    /// ブレーカー作動中の口座数。
+    pub tripped_accounts: usize,
+}
+
+impl DashboardSnapshot {
+    /// JSON 文字列に書き出す。
+    ///
+    /// `account_id` と `symbol_hash` は 2^53 を超えるとブラウザの
+    /// `JSON.parse` で精度が落ちるため、文字列として書く。
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"accounts\":[");
+        for (i, a) in self.accounts.iter().enumerate() {
+            let u = &a.utilization;
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"account_id\":\"{}\",\"open_orders\":{},\"max_open_orders\":{},\
+                 \"open_order_notional\":{},\"max_open_order_notional\":{},\
+                 \"daily_pnl\":{},\"max_daily_loss\":{},\"circuit_breaker_tripped\":{},\
+                 \"equity\":{},\"high_water\":{},\"drawdown_bps\":{},\
+                 \"projected_gross_exposure\":{},\"projected_maintenance_margin\":{},\
+                 \"maintenance_margin\":{},\"margin_coverage_bps\":{}}}",
+                a.account_id,
+                u.open_orders,
+                u.max_open_orders,
+                u.open_order_notional,
+                u.max_open_order_notional,
+                u.daily_pnl,
+                u.max_daily_loss,
+                u.circuit_breaker_tripped,
+                a.equity,
+                a.high_water,
+                a.drawdown_bps,
+                json_opt(a.projected.map(|p| p.gross_exposure)),
+                json_opt(a.projected.map(|p| p.maintenance_margin)),
+                json_opt(a.maintenance_margin),
+                json_opt(a.margin_coverage_bps),
+            );
+        }
+        out.push_str("],\"top_exposures\":[");
+        for (i, e) in self.top_exposures.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"account_id\":\"{}\",\"symbol_hash\":\"{}\",\"net_quantity\":{},\"notional\":{}}}",
+                e.account_id,
+                e.symbol_hash,
+                e.net_quantity,
+                json_opt(e.notional),
+            );
+        }
+        let _ = write!(
+            out,
+            "],\"total_daily_pnl\":{},\"tripped_accounts\":{}}}",
+            self.total_daily_pnl, self.tripped_accounts
+        );
+        out
+    }
+}
+
+/// 資産額 / 維持証拠金（bps）。維持証拠金が 0 以下なら `None`。
+fn coverage_bps(equity: i64, maintenance_margin: i64) -> Option<u32> {
+    (maintenance_margin > 0).then(|| {
+        (i128::from(equity.max(0)) * 10_000 / i128::from(maintenance_margin))
+            .min(i128::from(u32::MAX)) as u32
+    })
+}
+
+/// `Some(v)` を数値、`None` を `null` として書く。
+fn json_opt<T: core::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| String::from("null"), |v| alloc::format!("{v}"))
+}
+
+// ---------------------------------------------------------------------------
+// RiskEngine
+// ---------------------------------------------------------------------------
+
+/// 1 口座の状態。
+struct Account {
+    checker: PreTradeChecker,
+    equity: i64,
+    /// 資産額の過去最高値。
+    high_water: HighWaterMark,
+    /// 自動縮小の状態。
+    derisk: DeriskState,
+    /// 自動縮小の前の制限。縮小していなければ `None`。
+    base_limits: Option<RiskLimits>,
+    /// 最悪の場合の想定元本の総額の上限。
+    max_projected_gross: Option<i64>,
+    /// 証拠金通貨。
+    currency_hash: u64,
+    /// 証拠金通貨以外の通貨 → 残高。
+    balances: BTreeMap<u64, i64>,
+    /// 口座固有の証拠金モデル。`None` はエンジン共通の bps 方式。
+    margin_model: Option<MarginModel>,
+    /// 口座種別。`None` は未設定（追加の判定なし）。
+    kind: Option<AccountType>,
+    /// 銘柄 → ネット建玉。
+    positions: BTreeMap<u64, i64>,
+}
+
+impl Account {
+    /// `symbol_hash` の価格 `price` のネット建玉 `net` の維持証拠金（口座の
+    /// 証拠金モデル）。流動性の低い銘柄は
+    /// [`RiskLimits::illiquid_margin_multiplier_bps`] 倍にする。
+    fn required_margin(
+        &self,
+        margin: &MarginCalculator,
+        symbol_hash: u64,
+        price: i64,
+        net: i64,
+    ) -> i64 {
+        let required = match &self.margin_model {
+            Some(model) => model.maintenance_margin(price, net),
+            None => margin.maintenance_margin(price, net.unsigned_abs()),
+        };
+        self.checker.limits().illiquid_margin(symbol_hash, required)
+    }
+
+    /// 未約定注文（と候補の注文 `extra`）が全て約定した場合の最悪の
+    /// エクスポージャー。参照価格の無い銘柄があれば、その銘柄を `Err` で返す。
+    fn projection(
+        &self,
+        margin: &MarginCalculator,
+        market: &MarketSnapshot,
+        extra: Option<(u64, &Order)>,
+    ) -> Result<ExposureProjection, u64> {
+        let mut legs: BTreeMap<u64, ProjectedLeg> = BTreeMap::new();
+        for (&symbol_hash, &net) in &self.positions {
+            legs.entry(symbol_hash).or_default().net = net;
+        }
+        let mut unattributed = 0_i64;
+        let extra = extra
+            .map(|(symbol_hash, order)| WorkingOrder::from_order(order).with_symbol(symbol_hash));
+        for o in self.checker.open_orders().iter().chain(extra.as_ref()) {
+            let Some(symbol_hash) = o.symbol_hash else {
+                unattributed = unattributed.saturating_add(o.notional);
+                continue;
+            };
+            let leg = legs.entry(symbol_hash).or_default();
+            match o.side {
+                Side::Bid => {
+                    leg.bid_quantity = leg.bid_quantity.saturating_add(o.remaining);
+                    leg.bid_notional = leg.bid_notional.saturating_add(o.notional);
+                }
+                Side::Ask => {
+                    leg.ask_quantity = leg.ask_quantity.saturating_add(o.remaining);
+                    leg.ask_notional = leg.ask_notional.saturating_add(o.notional);
+                }
+            }
+        }
+
+        let mut projection = ExposureProjection {
+            gross_exposure: unattributed,
+            ..ExposureProjection::default()
+        };
+        for (symbol_hash, leg) in legs {
+            let price = market.reference_price(symbol_hash).ok_or(symbol_hash)?;
+            let held = (i128::from(price) * i128::from(leg.net))
+                .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64;
+            let long = held.saturating_add(leg.bid_notional);
+            let short = held.saturating_sub(leg.ask_notional);
+            let worst = if long.unsigned_abs() >= short.unsigned_abs() {
+                long
+            } else {
+                short
+            };
+            let long_margin = self.required_margin(
+                margin,
+                symbol_hash,
+                price,
+                leg.net.saturating_add_unsigned(leg.bid_quantity),
+            );
+            let short_margin = self.required_margin(
+                margin,
+                symbol_hash,
+                price,
+                leg.net.saturating_sub_unsigned(leg.ask_quantity),
+            );
+            projection.net_exposure = projection.net_exposure.saturating_add(worst);
+            projection.gross_exposure = projection
+                .gross_exposure
+                .saturating_add(worst.saturating_abs());
+            projection.maintenance_margin = projection
+                .maintenance_margin
+                .saturating_add(long_margin.max(short_margin));
+        }
+        projection.margin_coverage_bps = coverage_bps(self.equity, projection.maintenance_margin);
+        Ok(projection)
+    }
+
+    /// 参照価格で評価した維持証拠金。参照価格の無い建玉がある場合は `None`。
+    fn maintenance_margin(
+        &self,
+        margin: &MarginCalculator,
+        market: &MarketSnapshot,
+    ) -> Option<i64> {
+        self.positions
+            .iter()
+            .try_fold(0_i64, |acc, (&symbol_hash, &net)| {
+                let price = market.reference_price(symbol_hash)?;
+                Some(acc.saturating_add(self.required_margin(margin, symbol_hash, price, net)))
+            })
+    }
+
+    /// `now_ns` 時点の値洗いの鮮度を考慮した維持証拠金と、鮮度の基準を超えた
+    /// 値洗いのうち最も古いもの。参照価格の無い建玉、または拒否される古い
+    /// 値洗いがある場合は `None`。
+    fn maintenance_margin_at(
+        &self,
+        margin: &MarginCalculator,
+        market: &MarketSnapshot,
+        now_ns: u64,
+    ) -> Option<(i64, Option<StaleMark>)> {
+        let mut oldest: Option<StaleMark> = None;
+        let mut total = 0_i64;
+        for (&symbol_hash, &net) in &self.positions {
+            let price = market.reference_price(symbol_hash)?;
+            let required = self.required_margin(margin, symbol_hash, price, net);
+            let mark_ns = market.mark_timestamp_ns(symbol_hash);
+            let required = margin
+                .apply_mark_staleness(required, mark_ns, now_ns)
+                .ok()?;
+            if let Some(stale) = margin.stale_mark(mark_ns, now_ns) {
+                let age = |m: &StaleMark| m.age_ns.unwrap_or(u64::MAX);
+                if oldest.is_none_or(|o| age(&stale) > age(&o)) {
+                    oldest = Some(stale);
+                }
+            }
+            total = total.saturating_add(required);
+        }
+        Some((total, oldest))
+    }
+}
+
+/// 複数口座のリスクエンジン。
+pub struct RiskEngine {
+    accounts: BTreeMap<u64, Account>,
+    margin: MarginCalculator,
+    market: MarketSnapshot,
+    top_exposures: usize,
+    sink: Option<Box<dyn AlertSink>>,
+    interest: InterestSchedule,
+    auction: Option<AuctionMode>,
+    derisk: Option<DeriskPolicy>,
+    fx: FxRates,
+    /// 1 注文の数量の平均日次出来高に対する上限（bps）。
+    max_participation_bps: Option<u32>,
+}
+
+impl RiskEngine {
+    /// 証拠金パラメータから作成。
+    #[must_use]
+    pub const fn new(margin: MarginParams) -> Self {
+        Self {
+            accounts: BTreeMap::new(),
+            margin: MarginCalculator::new(margin),
+            market: MarketSnapshot::new(),
+            top_exposures: DEFAULT_TOP_EXPOSURES,
+            sink: None,
+            interest: InterestSchedule::new(),
+            auction: None,
+            derisk: None,
+            fx: FxRates::new(),
+            max_participation_bps: None,
+        }
+    }
+
+    /// 重大イベントの通知先を登録する（ビルダー形式）。
+    #[must_use]
+    pub fn with_alert_sink(mut self, sink: Box<dyn AlertSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    fn notify(&mut self, event: &AlertEvent) {
+        if let Some(sink) = self.sink.as_mut() {
+            sink.alert(event.severity(), event);
+        }
+    }
+
+    /// スナップショットに含める建玉の件数を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_top_exposures(mut self, n: usize) -> Self {
+        self.top_exposures = n;
+        self
+    }
+
+    /// 値洗い価格の鮮度の基準を設定する（ビルダー形式）。
+    /// [`Self::check_margin_calls_at`] が使う。
+    #[must_use]
+    pub fn with_mark_staleness(mut self, staleness: MarkStaleness) -> Self {
+        self.margin = self.margin.with_mark_staleness(staleness);
+        self
+    }
+
+    /// 金利設定を登録する（ビルダー形式）。
+    #[must_use]
+    pub fn with_interest(mut self, interest: InterestSchedule) -> Self {
+        self.interest = interest;
+        self
+    }
+
+    /// オークションモードを登録する（ビルダー形式）。
+    #[must_use]
+    pub fn with_auction_mode(mut self, auction: AuctionMode) -> Self {
+        self.auction = Some(auction);
+        self
+    }
+
+    /// 他通貨の残高の換算に使う為替レートを登録する（ビルダー形式）。
+    #[must_use]
+    pub fn with_fx_rates(mut self, fx: FxRates) -> Self {
+        self.fx = fx;
+        self
+    }
+
+    /// 為替レート（可変）。
+    pub const fn fx_rates_mut(&mut self) -> &mut FxRates {
+        &mut self.fx
+    }
+
+    /// ドローダウンに応じた自動縮小の方針を登録する（ビルダー形式）。
+    #[must_use]
+    pub fn with_derisk_policy(mut self, policy: DeriskPolicy) -> Self {
+        self.derisk = Some(policy);
+        self
+    }
+
+    /// 1 注文の数量を銘柄の平均日次出来高の `bps` 以下に制限する
+    /// （ビルダー形式）。[`Self::check_order_in_market`] と
+    /// [`Self::suggest_max_quantity`] が使う。
+    #[must_use]
+    pub const fn with_max_participation_bps(mut self, bps: u32) -> Self {
+        self.max_participation_bps = Some(bps);
+        self
+    }
+
+    /// 銘柄の平均日次出来高から決まる 1 注文の数量の上限。上限の設定または
+    /// 出来高が無ければ `None`。
+    fn participation_cap(&self, symbol_hash: u64) -> Option<(u64, u32, u64)> {
+        let limit_bps = self.max_participation_bps?;
+        let adv = self.market.adv(symbol_hash)?;
+        let cap = (u128::from(adv) * u128::from(limit_bps) / 10_000).min(u128::from(u64::MAX));
+        Some((adv, limit_bps, cap as u64))
+    }
+
+    /// オークションモード（可変）。外部シグナルの切り替えに使う。
+    pub fn auction_mode_mut(&mut self) -> Option<&mut AuctionMode> {
+        self.auction.as_mut()
+    }
+
+    /// `now_ns` の時点でオークション中か。
+    #[must_use]
+    pub fn in_auction(&self, now_ns: u64) -> bool {
+        self.auction.as_ref().is_some_and(|a| a.in_auction(now_ns))
+    }
+
+    /// 金利設定（可変）。
+    pub const fn interest_mut(&mut self) -> &mut InterestSchedule {
+        &mut self.interest
+    }
+
+    /// 口座を追加する（既存の口座は状態ごと置き換え）。
+    ///
+    /// 証拠金通貨は 0。変更は [`Self::set_currency`]。
+    pub fn add_account(&mut self, account_id: u64, limits: RiskLimits, equity: i64) {
+        self.accounts.insert(
+            account_id,
+            Account {
+                checker: PreTradeChecker::new(limits),
+                equity,
+                high_water: HighWaterMark::new(equity),
+                derisk: DeriskState::NONE,
+                base_limits: None,
+                max_projected_gross: None,
+                currency_hash: 0,
+                balances: BTreeMap::new(),
+                margin_model: None,
+                kind: None,
+                positions: BTreeMap::new(),
+            },
+        );
+    }
+
+    /// 口座数。
+    #[must_use]
+    pub fn account_count(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// 口座 ID（昇順）。
+    pub fn account_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.accounts.keys().copied()
+    }
+
+    /// 口座のチェッカー。
+    #[must_use]
+    pub fn checker(&self, account_id: u64) -> Option<&PreTradeChecker> {
+        self.accounts.get(&account_id).map(|a| &a.checker)
+    }
+
+    /// 口座のチェッカー（可変）。
+    pub fn checker_mut(&mut self, account_id: u64) -> Option<&mut PreTradeChecker> {
+        self.accounts.get_mut(&account_id).map(|a| &mut a.checker)
+    }
+
+    /// 口座の資産額を更新する（過去最高値も更新する）。未登録の口座は無視する。
+    ///
+    /// 自動縮小の方針があれば、下落率で新たに達した段階の対応を適用し、
+    /// [`AlertEvent::Derisked`] を通知する。
+    pub fn set_equity(&mut self, account_id: u64, equity: i64) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            a.equity = equity;
+            a.high_water.on_equity(equity);
+        }
+        self.apply_derisk(account_id);
+    }
+
+    fn apply_derisk(&mut self, account_id: u64) {
+        let Some(policy) = &self.derisk else {
+            return;
+        };
+        let Some(a) = self.accounts.get_mut(&account_id) else {
+            return;
+        };
+        let drawdown_bps = a.high_water.drawdown_bps();
+        let state = a.derisk.merge(&policy.state(drawdown_bps));
+        if state == a.derisk {
+            return;
+        }
+        if state.factor_bps != a.derisk.factor_bps {
+            let base = a
+                .base_limits
+                .get_or_insert_with(|| a.checker.limits().clone());
+            let scaled = base.scaled(state.factor_bps);
+            a.checker.apply_limits(scaled, DERISK_AUTHOR, 0);
+        }
+        if state.halted {
+            a.checker.trip_circuit_breaker();
+        }
+        a.derisk = state;
+        self.notify(&AlertEvent::Derisked {
+            account_id,
+            drawdown_bps,
+            state,
+        });
+    }
+
+    /// 口座の自動縮小の状態。
+    #[must_use]
+    pub fn derisk_state(&self, account_id: u64) -> Option<DeriskState> {
+        self.accounts.get(&account_id).map(|a| a.derisk)
+    }
+
+    /// 口座の自動縮小を解除し、縮小前の制限に戻す。停止で作動した
+    /// サーキットブレーカーは別にリセットする。未登録の口座は無視する。
+    pub fn reset_derisk(&mut self, account_id: u64) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            if let Some(base) = a.base_limits.take() {
+                a.checker.apply_limits(base, DERISK_AUTHOR, 0);
+            }
+            a.derisk = DeriskState::NONE;
+        }
+    }
+
+    /// 口座の資産額。
+    #[must_use]
+    pub fn equity(&self, account_id: u64) -> Option<i64> {
+        self.accounts.get(&account_id).map(|a| a.equity)
+    }
+
+    /// 口座の資産額の過去最高値とドローダウン。
+    #[must_use]
+    pub fn high_water(&self, account_id: u64) -> Option<&HighWaterMark> {
+        self.accounts.get(&account_id).map(|a| &a.high_water)
+    }
+
+    /// スナップショットから口座の過去最高値を復元する。資産額は
+    /// `high_water` の最新の資産額に合わせる。未登録の口座は無視する。
+    pub fn restore_high_water(&mut self, account_id: u64, high_water: HighWaterMark) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            a.equity = high_water.equity();
+            a.high_water = high_water;
+        }
+    }
+
+    /// 口座の証拠金通貨を設定する。未登録の口座は無視する。
+    pub fn set_currency(&mut self, account_id: u64, currency_hash: u64) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            a.currency_hash = currency_hash;
+        }
+    }
+
+    /// 口座の `currency_hash` 建ての残高を設定する。証拠金通貨なら資産額
+    /// （[`Self::set_equity`]）、それ以外は他通貨の残高で、0 は削除する。
+    /// 未登録の口座は無視する。
+    pub fn set_balance(&mut self, account_id: u64, currency_hash: u64, amount: i64) {
+        let Some(a) = self.accounts.get_mut(&account_id) else {
+            return;
+        };
+        if currency_hash == a.currency_hash {
+            self.set_equity(account_id, amount);
+        } else if amount == 0 {
+            a.balances.remove(&currency_hash);
+        } else {
+            a.balances.insert(currency_hash, amount);
+        }
+    }
+
+    /// 口座の `currency_hash` 建ての残高。証拠金通貨なら資産額。
+    #[must_use]
+    pub fn balance(&self, account_id: u64, currency_hash: u64) -> Option<i64> {
+        let a = self.accounts.get(&account_id)?;
+        if currency_hash == a.currency_hash {
+            return Some(a.equity);
+        }
+        Some(a.balances.get(&currency_hash).copied().unwrap_or(0))
+    }
+
+    /// 口座の全通貨の残高（通貨の昇順、証拠金通貨の資産額を含む）。
+    #[must_use]
+    pub fn balances(&self, account_id: u64) -> Option<Vec<(u64, i64)>> {
+        let a = self.accounts.get(&account_id)?;
+        let mut balances: Vec<(u64, i64)> = a.balances.iter().map(|(&c, &b)| (c, b)).collect();
+        let at = balances.partition_point(|&(c, _)| c < a.currency_hash);
+        balances.insert(at, (a.currency_hash, a.equity));
+        Some(balances)
+    }
+
+    /// 資産額に他通貨の残高を `now_ns` 時点のレートで証拠金通貨へ換算して
+    /// 加えた、口座全体の資産額。未登録の口座は `None`。
+    ///
+    /// 換算は切り捨て（[`fx::pnl_in`]）。使えるレートの無い通貨の残高があれば
+    /// その [`FxError`] を返す。
+    #[must_use]
+    pub fn consolidated_equity(
+        &self,
+        account_id: u64,
+        now_ns: u64,
+    ) -> Option<Result<i64, FxError>> {
+        let a = self.accounts.get(&account_id)?;
+        Some(
+            a.balances
+                .iter()
+                .try_fold(a.equity, |acc, (&currency_hash, &amount)| {
+                    let converted =
+                        fx::pnl_in(&self.fx, amount, currency_hash, a.currency_hash, now_ns)?;
+                    Ok(acc.saturating_add(converted))
+                }),
+        )
+    }
+
+    /// 口座の証拠金モデルを設定する。`None` でエンジン共通の bps 方式に戻す。
+    /// 未登録の口座は無視する。
+    pub fn set_margin_model(&mut self, account_id: u64, model: Option<MarginModel>) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            a.margin_model = model;
+        }
+    }
+
+    /// 口座固有の証拠金モデル。
+    #[must_use]
+    pub fn margin_model(&self, account_id: u64) -> Option<&MarginModel> {
+        self.accounts.get(&account_id)?.margin_model.as_ref()
+    }
+
+    /// 口座種別を設定し、その既定の証拠金モデル（[`AccountType::margin_model`]）
+    /// を割り当てる。未登録の口座は無視する。
+    pub fn set_account_type(&mut self, account_id: u64, account_type: AccountType) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            a.kind = Some(account_type);
+            a.margin_model = account_type.margin_model();
+        }
+    }
+
+    /// 口座種別。
+    #[must_use]
+    pub fn account_type(&self, account_id: u64) -> Option<AccountType> {
+        self.accounts.get(&account_id)?.kind
+    }
+
+    /// `symbol_hash` の `order` を口座種別で判定する
+    /// （[`AccountType::check_order`]）。
+    ///
+    /// 現金口座の手元現金は、資産額から参照価格で評価した買い建玉の時価を
+    /// 引いた額。参照価格の無い買い建玉がある場合は 0 とみなす。未登録の口座と
+    /// 種別が未設定の口座は常に通す。
+    ///
+    /// # Errors
+    ///
+    /// 現金口座の売り建て・レバレッジは [`AccountReject`]。
+    pub fn check_account_order(
+        &self,
+        account_id: u64,
+        symbol_hash: u64,
+        order: &Order,
+    ) -> Result<(), AccountReject> {
+        let Some(a) = self.accounts.get(&account_id) else {
+            return Ok(());
+        };
+        let Some(account_type) = a.kind else {
+            return Ok(());
+        };
+        let held = a.positions.get(&symbol_hash).copied().unwrap_or(0);
+        let long_value = a.positions.iter().filter(|(_, &net)| net > 0).try_fold(
+            0_i64,
+            |acc, (&symbol, &net)| {
+                let price = self.market.reference_price(symbol)?;
+                Some(acc.saturating_add(
+                    (i128::from(price).abs() * i128::from(net)).min(i128::from(i64::MAX)) as i64,
+                ))
+            },
+        );
+        let available_cash = long_value.map_or(0, |v| a.equity.saturating_sub(v).max(0));
+        account_type.check_order(order, held, available_cash)
+    }
+
+    /// `account_id` の `order` をエンジンの市場データで判定する
+    /// （[`PreTradeChecker::check_order_in_market`]）。オークションモードが
+    /// あれば [`AuctionMode::check_order_in_market`] で判定する。未登録の
+    /// 口座は常に通す。
+    ///
+    /// # Errors
+    ///
+    /// 自動縮小で縮小のみの口座の建玉を減らさない注文は
+    /// [`RiskReject::DrawdownReduceOnly`]、`position` の銘柄の出来高に対して
+    /// 大きすぎる注文は [`RiskReject::ParticipationExceeded`]、それ以外は
+    /// 口座のチェッカー、またはオークションの規則の拒否。
+    pub fn check_order_in_market(
+        &self,
+        account_id: u64,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let Some(a) = self.accounts.get(&account_id) else {
+            return Ok(());
+        };
+        if a.derisk.reduce_only {
+            let net = position.map_or(0, |p| p.net_quantity);
+            if !ExpiryRestriction::ReduceOnly.permits(order, net) {
+                return Err(RiskReject::DrawdownReduceOnly {
+                    drawdown_bps: a.high_water.drawdown_bps(),
+                });
+            }
+        }
+        if let Some((adv, limit_bps, cap)) =
+            position.and_then(|p| self.participation_cap(p.symbol_hash))
+        {
+            if order.quantity > cap {
+                return Err(RiskReject::ParticipationExceeded {
+                    quantity: order.quantity,
+                    adv,
+                    limit_bps,
+                    max_allowed: cap,
+                });
+            }
+        }
+        match &self.auction {
+            Some(auction) => {
+                auction.check_order_in_market(&a.checker, order, position, &self.market)
+            }
+            None => a
+                .checker
+                .check_order_in_market(order, position, &self.market),
+        }
+    }
+
+    /// `account_id` が `symbol_hash` を `side` に指値で発注するとき、現在の
+    /// 参照価格で全ての判定を通る最大の数量。執行アルゴリズムが拒否で
+    /// 探らずに発注数量を決めるのに使う。
+    ///
+    /// 発注数量の上限・建玉上限・想定元本の上限・未約定注文の想定元本の
+    /// 上限・出来高に対する上限（[`Self::with_max_participation_bps`]）と、
+    /// 約定後の維持証拠金が資産額を超えないことを満たす。建玉を減らす分は
+    /// 証拠金を増やさないので、維持証拠金が既に不足していても手仕舞いの数量は
+    /// 返す。他の銘柄に参照価格の無い建玉があると維持証拠金が分からないため、
+    /// 手仕舞いの数量までに抑える。
+    ///
+    /// ブレーカーの作動中・日次損失上限の到達時・未約定注文数の上限到達時は
+    /// 0。未登録の口座、または銘柄に参照価格が無ければ `None`。
+    #[must_use]
+    pub fn suggest_max_quantity(
+        &self,
+        account_id: u64,
+        symbol_hash: u64,
+        side: Side,
+    ) -> Option<u64> {
+        let a = self.accounts.get(&account_id)?;
+        let price = self.market.reference_price(symbol_hash)?;
+        let checker = &a.checker;
+        let limits = checker.limits();
+        let caps = limits.symbol_caps(symbol_hash);
+        let checks = limits.checks;
+        if checker.is_circuit_breaker_tripped()
+            || (checks.daily_loss && checker.loss_pnl() <= limits.max_daily_loss)
+            || (checks.open_orders && checker.open_order_count() >= limits.max_open_orders)
+        {
+            return Some(0);
+        }
+        let net = a.positions.get(&symbol_hash).copied().unwrap_or(0);
+        let closing = match side {
+            Side::Bid if net < 0 => net.unsigned_abs(),
+            Side::Ask if net > 0 => net.unsigned_abs(),
+            _ => 0,
+        };
+        let mut max = u64::MAX;
+        if checks.order_size {
+            max = max.min(caps.max_order_size);
+        }
+        if checks.position {
+            max = max.min(position_headroom(net, side, caps.max_position));
+        }
+        if checks.notional {
+            max = max.min(notional_headroom(price, limits.max_notional));
+        }
+        if checks.open_order_notional {
+            let working = checker.open_orders().total_notional();
+            max = max.min(notional_headroom(
+                price,
+                limits.max_open_order_notional.saturating_sub(working),
+            ));
+        }
+        if let Some((_, _, cap)) = self.participation_cap(symbol_hash) {
+            max = max.min(cap);
+        }
+        if a.derisk.reduce_only {
+            max = max.min(closing);
+        }
+        if max <= closing {
+            return Some(max);
+        }
+
+        // 手仕舞いを超える分は建玉が増えるほど維持証拠金が増えるので、
+        // 資産額に収まる最大の数量を二分探索する。
+        let other = a
+            .positions
+            .iter()
+            .filter(|(&symbol, _)| symbol != symbol_hash)
+            .try_fold(0_i64, |acc, (&symbol, &held)| {
+                let price = self.market.reference_price(symbol)?;
+                Some(acc.saturating_add(a.required_margin(&self.margin, symbol, price, held)))
+            });
+        let Some(other) = other else {
+            return Some(closing);
+        };
+        let fits = |quantity: u64| {
+            let after = match side {
+                Side::Bid => net.saturating_add_unsigned(quantity),
+                Side::Ask => net.saturating_sub_unsigned(quantity),
+            };
+            other.saturating_add(a.required_margin(&self.margin, symbol_hash, price, after))
+                <= a.equity
+        };
+        let (mut lo, mut hi) = (closing, max);
+        if fits(hi) {
+            return Some(hi);
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if fits(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(lo)
+    }
+
+    /// 口座の未約定注文が全て約定した場合の最悪のエクスポージャー
+    /// （[`ExposureProjection`]）。注文はチェッカーが
+    /// [`PreTradeChecker::on_order_accepted_in`] で銘柄付きで記録したもの。
+    /// 未登録の口座、または参照価格の無い銘柄がある場合は `None`。
+    #[must_use]
+    pub fn projected_exposure(&self, account_id: u64) -> Option<ExposureProjection> {
+        let a = self.accounts.get(&account_id)?;
+        a.projection(&self.margin, &self.market, None).ok()
+    }
+
+    /// 最悪の場合の想定元本の総額の上限を設定する。`None` で解除。未登録の
+    /// 口座は無視する。
+    pub fn set_max_projected_gross(&mut self, account_id: u64, limit: Option<i64>) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            a.max_projected_gross = limit;
+        }
+    }
+
+    /// `symbol_hash` の `order` も約定すると仮定した最悪のエクスポージャーで
+    /// 判定する。未登録の口座は常に通す。
+    ///
+    /// # Errors
+    ///
+    /// 参照価格の無い銘柄があれば [`RiskReject::MissingReferencePrice`]、
+    /// 想定元本の総額が上限を超えれば [`RiskReject::ProjectedExposureExceeded`]、
+    /// 維持証拠金が資産額を超えれば [`RiskReject::ProjectedMarginShortfall`]。
+    pub fn check_projected(
+        &self,
+        account_id: u64,
+        symbol_hash: u64,
+        order: &Order,
+    ) -> Result<(), RiskReject> {
+        let Some(a) = self.accounts.get(&account_id) else {
+            return Ok(());
+        };
+        let projection = a
+            .projection(&self.margin, &self.market, Some((symbol_hash, order)))
+            .map_err(|symbol_hash| RiskReject::MissingReferencePrice { symbol_hash })?;
+        if let Some(limit) = a.max_projected_gross {
+            if projection.gross_exposure > limit {
+                return Err(RiskReject::ProjectedExposureExceeded {
+                    gross: projection.gross_exposure,
+                    limit,
+                });
+            }
+        }
+        if projection.maintenance_margin > a.equity {
+            return Err(RiskReject::ProjectedMarginShortfall {
+                equity: a.equity,
+                maintenance_margin: projection.maintenance_margin,
+            });
+        }
+        Ok(())
+    }
+
+    /// セッション切り替え: `days` 日分の金利を各口座の資産額に計上し、
+    /// チェッカーの日次の状態をリセットする（[`PreTradeChecker::reset_daily`]）。
+    /// 過去最高値は残し、その日の始値・最高値を計上後の資産額にする
+    /// （[`HighWaterMark::roll_day`]）。
+    ///
+    /// 所要証拠金は参照価格で評価した維持証拠金。参照価格の無い建玉を持つ
+    /// 口座は金利を計上しない。計上額が 0 でない口座の（口座 ID, 計上額）を
+    /// 返す。
+    ///
+    /// 他通貨の残高には、その通貨の料率で残高の正負に応じた金利を残高に
+    /// 計上する（戻り値には含めない）。
+    pub fn roll_session(&mut self, days: u32) -> Vec<(u64, i64)> {
+        let mut postings = Vec::new();
+        for (&account_id, a) in &mut self.accounts {
+            if let Some(requirement) = a.maintenance_margin(&self.margin, &self.market) {
+                let interest = self
+                    .interest
+                    .accrual(a.currency_hash, a.equity, requirement, days);
+                if interest != 0 {
+                    a.equity = a.equity.saturating_add(interest);
+                    postings.push((account_id, interest));
+                }
+            }
+            for (&currency_hash, balance) in &mut a.balances {
+                let interest = self.interest.accrual(currency_hash, *balance, 0, days);
+                *balance = balance.saturating_add(interest);
+            }
+            a.high_water.on_equity(a.equity);
+            a.high_water.roll_day();
+            a.checker.reset_daily();
+        }
+        postings
+    }
+
+    /// 口座の建玉を更新する。ネット 0 の建玉は削除する。
+    pub fn set_position(&mut self, account_id: u64, position: &Position) {
+        if let Some(a) = self.accounts.get_mut(&account_id) {
+            if position.net_quantity == 0 {
+                a.positions.remove(&position.symbol_hash);
+            } else {
+                a.positions
+                    .insert(position.symbol_hash, position.net_quantity);
+            }
+        }
+    }
+
+    /// 口座のサーキットブレーカーを作動させる。作動していなかった場合は
+    /// [`AlertEvent::BreakerTripped`] を通知する。
+    pub fn trip_breaker(&mut self, account_id: u64) {
+        let Some(a) = self.accounts.get_mut(&account_id) else {
+            return;
+        };
+        if a.checker.is_circuit_breaker_tripped() {
+            return;
+        }
+        a.checker.trip_circuit_breaker();
+        self.notify(&AlertEvent::BreakerTripped { account_id });
+    }
+
+    /// キルスイッチ: 全口座のブレーカーを作動させ、
+    /// [`AlertEvent::KillSwitch`] を通知する。
+    pub fn kill_switch(&mut self) {
+        for a in self.accounts.values_mut() {
+            a.checker.trip_circuit_breaker();
+        }
+        let accounts = self.accounts.len();
+        self.notify(&AlertEvent::KillSwitch { accounts });
+    }
+
+    /// 口座の日次損益（実現損益）を更新する。チェッカーの
+    /// [`LossBasis`](crate::check::LossBasis) で測った日次損益が日次損失上限に
+    /// 新たに達した場合は [`AlertEvent::LossLimitHit`] を通知する。
+    pub fn update_daily_pnl(&mut self, account_id: u64, pnl: i64) {
+        let Some(a) = self.accounts.get_mut(&account_id) else {
+            return;
+        };
+        let limits = a.checker.limits();
+        let limit = limits.max_daily_loss;
+        let enforced = limits.checks.daily_loss;
+        let was_hit = a.checker.loss_pnl() <= limit;
+        a.checker.update_daily_pnl(pnl);
+        let daily_pnl = a.checker.loss_pnl();
+        if enforced && !was_hit && daily_pnl <= limit {
+            self.notify(&AlertEvent::LossLimitHit {
+                account_id,
+                daily_pnl,
+                limit,
+            });
+        }
+    }
+
+    /// 資産額が維持証拠金を下回る口座を返し、それぞれ
+    /// [`AlertEvent::MarginCall`] を通知する。
+    ///
+    /// 参照価格の無い建玉を持つ口座は判定しない。
+    pub fn check_margin_calls(&mut self) -> Vec<u64> {
+        let calls: Vec<AlertEvent> = self
+            .accounts
+            .iter()
+            .filter_map(|(&account_id, a)| {
+                let maintenance_margin = a.maintenance_margin(&self.margin, &self.market)?;
+                (a.equity < maintenance_margin).then_some(AlertEvent::MarginCall {
+                    account_id,
+                    equity: a.equity,
+                    maintenance_margin,
+                    stale_mark: None,
+                })
+            })
+            .collect();
+        self.notify_margin_calls(&calls)
+    }
+
+    /// [`Self::check_margin_calls`] と同じ判定を、`now_ns` 時点の値洗いの
+    /// 鮮度を考慮して行う（[`Self::with_mark_staleness`]）。
+    ///
+    /// 古い値洗いに上乗せする設定では上乗せ後の維持証拠金で判定し、最も古い
+    /// 値洗いを通知の `stale_mark` に載せる。古い値洗いを拒否する設定では、
+    /// 古い建玉を持つ口座は参照価格が無い場合と同じく判定しない。
+    pub fn check_margin_calls_at(&mut self, now_ns: u64) -> Vec<u64> {
+        let calls: Vec<AlertEvent> = self
+            .accounts
+            .iter()
+            .filter_map(|(&account_id, a)| {
+                let (maintenance_margin, stale_mark) =
+                    a.maintenance_margin_at(&self.margin, &self.market, now_ns)?;
+                (a.equity < maintenance_margin).then_some(AlertEvent::MarginCall {
+                    account_id,
+                    equity: a.equity,
+                    maintenance_margin,
+                    stale_mark,
+                })
+            })
+            .collect();
+        self.notify_margin_calls(&calls)
+    }
+
+    fn notify_margin_calls(&mut self, calls: &[AlertEvent]) -> Vec<u64> {
+        let mut ids = Vec::with_capacity(calls.len());
+        for event in calls {
+            if let AlertEvent::MarginCall { account_id, .. } = event {
+                ids.push(*account_id);
+            }
+            self.notify(event);
+        }
+        ids
+    }
+
+    /// 市場データ。
+    #[must_use]
+    pub const fn market(&self) -> &MarketSnapshot {
+        &self.market
+    }
+
+    /// 市場データ（可変）。
+    pub const fn market_mut(&mut self) -> &mut MarketSnapshot {
+        &mut self.market
+    }
+
+    /// 全口座の状態をまとめたスナップショット。
+    #[must_use]
+    pub fn dashboard_snapshot(&self) -> DashboardSnapshot {
+        let mut accounts = Vec::with_capacity(self.accounts.len());
+        let mut exposures = Vec::new();
+        let mut total_daily_pnl = 0_i64;
+        let mut tripped_accounts = 0;
+        for (&account_id, a) in &self.accounts {
+            let utilization = a.checker.utilization();
+            total_daily_pnl = total_daily_pnl.saturating_add(utilization.daily_pnl);
+            tripped_accounts += usize::from(utilization.circuit_breaker_tripped);
+
+            let maintenance_margin = a.maintenance_margin(&self.margin, &self.market);
+            for (&symbol_hash, &net_quantity) in &a.positions {
+                let price = self.market.reference_price(symbol_hash);
+                exposures.push(Exposure {
+                    account_id,
+                    symbol_hash,
+                    net_quantity,
+                    notional: price.map(|p| {
+                        (i128::from(p) * i128::from(net_quantity))
+                            .clamp(i128::from(i64::MIN), i128::from(i64::MAX))
+                            as i64
+                    }),
+                });
+            }
+            let margin_coverage_bps = maintenance_margin.and_then(|m| coverage_bps(a.equity, m));
+            accounts.push(AccountSnapshot {
+                account_id,
+                utilization,
+                equity: a.equity,
+                high_water: a.high_water.high_water(),
+                drawdown_bps: a.high_water.drawdown_bps(),
+                maintenance_margin,
+                margin_coverage_bps,
+                projected: a.projection(&self.margin, &self.market, None).ok(),
+            });
+        }
+        exposures.sort_by_key(|e| {
+            core::cmp::Reverse(
+                e.notional
+                    .map(i64::unsigned_abs)
+                    .map_or(0, |n| u128::from(n) + 1),
+            )
+        });
+        exposures.truncate(self.top_exposures);
+        DashboardSnapshot {
+            accounts,
+            top_exposures: exposures,
+            total_daily_pnl,
+            tripped_accounts,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::Severity;
+    use crate::market::SymbolMarket;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+
+    fn position(symbol_hash: u64, net_quantity: i64) -> Position {
+        PositionBuilder::new(symbol_hash, net_quantity)
+            .avg_entry_price(0)
+            .build()
+    }
+
+    fn priced(engine: &mut RiskEngine, symbol: u64, price: i64) {
+        engine.market_mut().insert(
+            symbol,
+            SymbolMarket {
+                reference_price: Some(price),
+                ..SymbolMarket::default()
+            },
+        );
+    }
+
+    fn engine() -> RiskEngine {
+        let mut engine = RiskEngine::new(MarginParams::default()).with_top_exposures(2);
+        engine.add_account(1, RiskLimits::default(), 10_000);
+        engine.add_account(2, RiskLimits::default(), 1_000);
+        priced(&mut engine, 7, 1_000);
+        priced(&mut engine, 8, 50);
+        engine.set_position(1, &position(7, 100));
+        engine.set_position(1, &position(8, -40));
+        engine.set_position(2, &position(7, -30));
+        engine
+    }
+
+    #[test]
+    fn snapshot_covers_all_accounts() {
+        let mut engine = engine();
+        engine.checker_mut(1).unwrap().update_daily_pnl(-500);
+        engine.checker_mut(2).unwrap().update_daily_pnl(200);
+        engine.checker_mut(2).unwrap().trip_circuit_breaker();
+        let snap = engine.dashboard_snapshot();
+        assert_eq!(snap.accounts.len(), 2);
+        assert_eq!(snap.total_daily_pnl, -300);
+        assert_eq!(snap.tripped_accounts, 1);
+        // 口座 1: 5% × (100_000 + 2_000) = 5_100。
+        let a = snap.accounts[0];
+        assert_eq!(a.maintenance_margin, Some(5_100));
+        assert_eq!(a.margin_coverage_bps, Some(19_607));
+        assert_eq!(snap.accounts[1].margin_coverage_bps, Some(6_666));
+    }
+
+    #[test]
+    fn top_exposures_by_notional() {
+        let mut engine = engine();
+        engine.set_position(2, &position(9, 1_000_000));
+        let snap = engine.dashboard_snapshot();
+        let top: Vec<_> = snap
+            .top_exposures
+            .iter()
+            .map(|e| (e.account_id, e.symbol_hash, e.notional))
+            .collect();
+        assert_eq!(top, [(1, 7, Some(100_000)), (2, 7, Some(-30_000))]);
+        // 参照価格の無い建玉は証拠金が不明。
+        assert_eq!(snap.accounts[1].maintenance_margin, None);
+        assert_eq!(snap.accounts[1].margin_coverage_bps, None);
+    }
+
+    /// 通知を共有の Vec に記録する。
+    struct Shared(alloc::rc::Rc<core::cell::RefCell<Vec<(Severity, AlertEvent)>>>);
+
+    impl AlertSink for Shared {
+        fn alert(&mut self, severity: Severity, event: &AlertEvent) {
+            self.0.borrow_mut().alert(severity, event);
+        }
+    }
+
+    #[test]
+    fn alerts_on_critical_events() {
+        let log = alloc::rc::Rc::new(core::cell::RefCell::new(Vec::new()));
+        let mut engine = engine().with_alert_sink(Box::new(Shared(log.clone())));
+        engine.trip_breaker(1);
+        engine.trip_breaker(1);
+        engine.update_daily_pnl(2, -2_000_000);
+        engine.update_daily_pnl(2, -3_000_000);
+        // 口座 2: 維持証拠金 1_500 に対し資産額 1_000。
+        assert_eq!(engine.check_margin_calls(), [2]);
+        engine.kill_switch();
+        assert!(engine.checker(2).unwrap().is_circuit_breaker_tripped());
+        assert_eq!(
+            *log.borrow(),
+            [
+                (
+                    Severity::Warning,
+                    AlertEvent::BreakerTripped { account_id: 1 }
+                ),
+                (
+                    Severity::Critical,
+                    AlertEvent::LossLimitHit {
+                        account_id: 2,
+                        daily_pnl: -2_000_000,
+                        limit: RiskLimits::default().max_daily_loss,
+                    }
+                ),
+                (
+                    Severity::Warning,
+                    AlertEvent::MarginCall {
+                        account_id: 2,
+                        equity: 1_000,
+                        maintenance_margin: 1_500,
+                        stale_mark: None,
+                    }
+                ),
+                (Severity::Critical, AlertEvent::KillSwitch { accounts: 2 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn derisk_on_drawdown() {
+        use crate::derisk::{DeriskAction, DeriskStep};
+        use alice_ledger::Side;
+
+        let step = |drawdown_bps, action| DeriskStep {
+            drawdown_bps,
+            action,
+        };
+        let log = alloc::rc::Rc::new(core::cell::RefCell::new(Vec::new()));
+        let mut engine = engine()
+            .with_derisk_policy(DeriskPolicy::new(alloc::vec![
+                step(500, DeriskAction::Scale { factor_bps: 5_000 }),
+                step(800, DeriskAction::ReduceOnly),
+                step(1_000, DeriskAction::Halt),
+            ]))
+            .with_alert_sink(Box::new(Shared(log.clone())));
+        engine.set_equity(1, 9_500);
+        assert_eq!(engine.checker(1).unwrap().limits().max_position, 500);
+        assert_eq!(
+            engine.checker(1).unwrap().history().last().unwrap().author,
+            DERISK_AUTHOR
+        );
+        // 資産額が戻っても緩めない。
+        engine.set_equity(1, 9_800);
+        assert_eq!(engine.derisk_state(1).unwrap().factor_bps, 5_000);
+
+        engine.set_equity(1, 9_200);
+        let order = |side| OrderBuilder::new(side, 1_000, 50).build();
+        let long = position(7, 100);
+        assert_eq!(
+            engine.check_order_in_market(1, &order(Side::Bid), Some(&long)),
+            Err(RiskReject::DrawdownReduceOnly { drawdown_bps: 800 })
+        );
+        assert!(engine
+            .check_order_in_market(1, &order(Side::Ask), Some(&long))
+            .is_ok());
+
+        engine.set_equity(1, 9_000);
+        assert!(engine.checker(1).unwrap().is_circuit_breaker_tripped());
+        assert_eq!(log.borrow().len(), 3);
+        assert_eq!(
+            log.borrow()[2],
+            (
+                Severity::Critical,
+                AlertEvent::Derisked {
+                    account_id: 1,
+                    drawdown_bps: 1_000,
+                    state: DeriskState {
+                        factor_bps: 5_000,
+                        reduce_only: true,
+                        halted: true,
+                    },
+                }
+            )
+        );
+
+        engine.reset_derisk(1);
+        assert_eq!(engine.derisk_state(1), Some(DeriskState::NONE));
+        assert_eq!(engine.checker(1).unwrap().limits(), &RiskLimits::default());
+        // 口座 2 は下落していない。
+        assert_eq!(engine.derisk_state(2), Some(DeriskState::NONE));
+    }
+
+    #[test]
+    fn illiquid_symbols_tighten_margin_and_quantity() {
+        let mut engine = engine();
+        let checker = engine.checker_mut(1).unwrap();
+        assert_eq!(checker.set_illiquid(7, true, "ops", 5), 1);
+        // 銘柄 7 の維持証拠金は 1.5 倍: 5_000 × 1.5 + 100。
+        let snap = engine.dashboard_snapshot();
+        assert_eq!(snap.accounts[0].maintenance_margin, Some(7_600));
+        // 100 + 1.5 × 5% × 1_000 × (100 + q) ≤ 10_000 → 32。
+        assert_eq!(engine.suggest_max_quantity(1, 7, Side::Bid), Some(32));
+        // 手仕舞いも縮めた発注数量の上限まで。
+        assert_eq!(engine.suggest_max_quantity(1, 7, Side::Ask), Some(50));
+        // 分類の変更は制限の版として戻せる。
+        engine
+            .checker_mut(1)
+            .unwrap()
+            .rollback_to(0, "ops", 6)
+            .unwrap();
+        let snap = engine.dashboard_snapshot();
+        assert_eq!(snap.accounts[0].maintenance_margin, Some(5_100));
+    }
+
+    #[test]
+    fn suggests_largest_passing_quantity() {
+        let engine = engine();
+        // 口座 1: 他の銘柄の証拠金 100 + 5% × 1_000 × (100 + q) ≤ 10_000 → 98。
+        assert_eq!(engine.suggest_max_quantity(1, 7, Side::Bid), Some(98));
+        // 手仕舞いは発注数量の上限まで。
+        assert_eq!(engine.suggest_max_quantity(1, 7, Side::Ask), Some(100));
+        // 口座 2: 証拠金不足でも買い戻し 30 と、その先の 20 は通る。
+        assert_eq!(engine.suggest_max_quantity(2, 7, Side::Bid), Some(50));
+        assert_eq!(engine.suggest_max_quantity(2, 7, Side::Ask), Some(0));
+        assert_eq!(engine.suggest_max_quantity(1, 99, Side::Bid), None);
+        assert_eq!(engine.suggest_max_quantity(9, 7, Side::Bid), None);
+
+        let mut engine = engine.with_max_participation_bps(500);
+        engine.market_mut().insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(1_000),
+                adv: Some(1_000),
+                ..SymbolMarket::default()
+            },
+        );
+        assert_eq!(engine.suggest_max_quantity(1, 7, Side::Bid), Some(50));
+        let order = OrderBuilder::ask(1_000, 60).build();
+        assert_eq!(
+            engine.check_order_in_market(1, &order, Some(&position(7, 100))),
+            Err(RiskReject::ParticipationExceeded {
+                quantity: 60,
+                adv: 1_000,
+                limit_bps: 500,
+                max_allowed: 50,
+            })
+        );
+        engine.checker_mut(1).unwrap().trip_circuit_breaker();
+        assert_eq!(engine.suggest_max_quantity(1, 7, Side::Ask), Some(0));
+    }
+
+    #[test]
+    fn projects_worst_case_with_working_orders() {
+        let order =
+            |id, side, price, quantity| OrderBuilder::new(side, price, quantity).id(id).build();
+        let mut engine = engine();
+        let checker = engine.checker_mut(1).unwrap();
+        checker.on_order_accepted_in(&order(1, Side::Bid, 1_010, 20), 7);
+        checker.on_order_accepted_in(&order(2, Side::Ask, 55, 10), 8);
+        checker.on_order_accepted(&order(3, Side::Bid, 100, 3));
+        // 銘柄 7: 買いが全て約定すると 100_000 + 20_200、証拠金 5% × 120_000。
+        // 銘柄 8: 売りが全て約定すると -2_000 - 550、証拠金 5% × 2_500。
+        let projected = ExposureProjection {
+            net_exposure: 117_650,
+            gross_exposure: 123_050,
+            maintenance_margin: 6_125,
+            margin_coverage_bps: Some(16_326),
+        };
+        assert_eq!(engine.projected_exposure(1), Some(projected));
+        assert_eq!(
+            engine.dashboard_snapshot().accounts[0].projected,
+            Some(projected)
+        );
+
+        engine.set_max_projected_gross(1, Some(130_000));
+        assert!(engine
+            .check_projected(1, 7, &order(4, Side::Bid, 1_000, 5))
+            .is_ok());
+        assert_eq!(
+            engine.check_projected(1, 7, &order(4, Side::Bid, 1_000, 10)),
+            Err(RiskReject::ProjectedExposureExceeded {
+                gross: 133_050,
+                limit: 130_000,
+            })
+        );
+        engine.set_max_projected_gross(1, None);
+        assert_eq!(
+            engine.check_projected(1, 7, &order(4, Side::Bid, 1_000, 80)),
+            Err(RiskReject::ProjectedMarginShortfall {
+                equity: 10_000,
+                maintenance_margin: 10_125,
+            })
+        );
+        assert_eq!(
+            engine.check_projected(1, 99, &order(4, Side::Bid, 1_000, 1)),
+            Err(RiskReject::MissingReferencePrice { symbol_hash: 99 })
+        );
+    }
+
+    #[test]
+    fn margin_calls_report_stale_marks() {
+        use crate::margin::StaleMarkPolicy;
+        let staleness = MarkStaleness {
+            horizon_ns: 100,
+            policy: StaleMarkPolicy::AddOn { bps: 10_000 },
+        };
+        let mut refusing = engine().with_mark_staleness(MarkStaleness {
+            policy: StaleMarkPolicy::Refuse,
+            ..staleness
+        });
+        // 古い（時刻不明の）値洗いは拒否され、その口座は判定しない。
+        assert!(refusing.check_margin_calls_at(0).is_empty());
+
+        let log = alloc::rc::Rc::new(core::cell::RefCell::new(Vec::new()));
+        let mut engine = engine()
+            .with_mark_staleness(staleness)
+            .with_alert_sink(Box::new(Shared(log.clone())));
+        engine.market_mut().set_mark_timestamp(7, 1_000);
+        engine.market_mut().set_mark_timestamp(8, 1_000);
+        // 値洗いが新しければ口座 1 の維持証拠金は 5_100 で充足。
+        assert_eq!(engine.check_margin_calls_at(1_100), [2]);
+        // 古い値洗いの建玉は 2 倍。銘柄 8 だけなら 5_000 + 200 でまだ充足。
+        engine.market_mut().set_mark_timestamp(8, 900);
+        assert_eq!(engine.check_margin_calls_at(1_100), [2]);
+        // 両方古いと 10_200 となり口座 1 も不足する。
+        engine.market_mut().set_mark_timestamp(7, 0);
+        engine.market_mut().set_mark_timestamp(8, 500);
+        assert_eq!(engine.check_margin_calls_at(1_100), [1, 2]);
+        let last = log.borrow().last().cloned();
+        assert!(matches!(
+            last,
+            Some((
+                Severity::Warning,
+                AlertEvent::MarginCall {
+                    account_id: 2,
+                    maintenance_margin: 3_000,
+                    stale_mark: Some(StaleMark {
+                        age_ns: Some(1_100),
+                        horizon_ns: 100,
+                    }),
+                    ..
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn session_rollover_posts_interest() {
+        use crate::interest::InterestRates;
+        let mut engine = engine();
+        engine.interest_mut().set_rates(
+            0,
+            InterestRates {
+                credit_bps: 3_600,
+                debit_bps: 7_200,
+                day_count: 360,
+            },
+        );
+        engine.add_account(3, RiskLimits::default(), 1_000_000);
+        engine.set_currency(3, 9);
+        engine.checker_mut(1).unwrap().update_daily_pnl(-500);
+        // 口座 1: 余剰 10_000 - 5_100 = 4_900 × 36% × 2 / 360 = 9。
+        // 口座 2: 不足 1_500 - 1_000 = 500 × 72% × 2 / 360 → -2（切り上げ）。
+        // 口座 3: 通貨 9 に料率が無いので計上しない。
+        assert_eq!(engine.roll_session(2), [(1, 9), (2, -2)]);
+        assert_eq!(engine.equity(1), Some(10_009));
+        assert_eq!(engine.equity(2), Some(998));
+        assert_eq!(engine.equity(3), Some(1_000_000));
+        assert_eq!(engine.checker(1).unwrap().daily_pnl(), 0);
+
+        // 参照価格の無い建玉がある口座は計上しない。
+        engine.set_position(1, &position(99, 1));
+        // 口座 2: 不足 502 × 72% / 360 = 1.004 → -2。
+        assert_eq!(engine.roll_session(1), [(2, -2)]);
+        assert_eq!(engine.equity(9), None);
+    }
+
+    #[test]
+    fn multi_currency_balances() {
+        use crate::fx::FX_RATE_SCALE;
+        use crate::interest::InterestRates;
+        let mut fx = FxRates::new();
+        // 1 通貨 5 = 0.5 通貨 0、通貨 0 1 単位 = 2.5 通貨 6。
+        fx.set_rate(5, 0, FX_RATE_SCALE / 2, 0);
+        fx.peg(0, 6, 5 * FX_RATE_SCALE / 2);
+        let mut engine = engine().with_fx_rates(fx);
+        engine.interest_mut().set_rates(
+            5,
+            InterestRates {
+                credit_bps: 3_600,
+                debit_bps: 7_200,
+                day_count: 360,
+            },
+        );
+        engine.set_balance(1, 5, 1_001);
+        engine.set_balance(1, 6, -250);
+        engine.set_balance(1, 0, 12_000);
+        assert_eq!(
+            engine.balances(1),
+            Some(alloc::vec![(0, 12_000), (5, 1_001), (6, -250)])
+        );
+        // 12_000 + 500（500.5 を切り捨て）- 100。
+        assert_eq!(engine.consolidated_equity(1, 0), Some(Ok(12_400)));
+        engine.set_balance(1, 7, 1);
+        assert_eq!(
+            engine.consolidated_equity(1, 0),
+            Some(Err(FxError::Missing { from: 7, to: 0 }))
+        );
+        engine.set_balance(1, 7, 0);
+        // 古いレートしか無ければ合計を出さない。
+        engine.fx_rates_mut().set_max_age_ns(Some(10));
+        assert!(matches!(
+            engine.consolidated_equity(1, 11),
+            Some(Err(FxError::Stale { .. }))
+        ));
+        assert_eq!(engine.consolidated_equity(9, 0), None);
+        assert_eq!(engine.balance(1, 7), Some(0));
+
+        // 通貨 5 の残高に 1_001 × 36% × 10 / 360 = 10 の金利。料率の無い
+        // 通貨 6 には計上しない。
+        engine.roll_session(10);
+        assert_eq!(engine.balance(1, 5), Some(1_011));
+        assert_eq!(engine.balance(1, 6), Some(-250));
+        assert_eq!(engine.balance(9, 5), None);
+    }
+
+    #[test]
+    fn high_water_spans_sessions() {
+        let mut engine = engine();
+        engine.set_equity(1, 12_000);
+        engine.set_equity(1, 11_000);
+        engine.roll_session(0);
+        engine.set_equity(1, 10_500);
+        let hwm = *engine.high_water(1).unwrap();
+        assert_eq!((hwm.high_water(), hwm.day_start()), (12_000, 11_000));
+        assert_eq!(engine.dashboard_snapshot().accounts[0].drawdown_bps, 1_250);
+
+        // 再起動後に復元する。
+        let bytes = hwm.to_bytes();
+        let mut engine = RiskEngine::new(MarginParams::default());
+        engine.add_account(1, RiskLimits::default(), 10_500);
+        engine.restore_high_water(1, HighWaterMark::from_bytes(&bytes).unwrap());
+        assert_eq!(engine.high_water(1), Some(&hwm));
+        assert!(engine.high_water(2).is_none());
+    }
+
+    #[test]
+    fn per_account_margin_model() {
+        use crate::regt::RegTParams;
+        let mut engine = engine();
+        // 口座 2: 売り 30 × 1_000。bps 方式 5% = 1_500、Reg-T 30% = 9_000。
+        assert_eq!(
+            engine.dashboard_snapshot().accounts[1].maintenance_margin,
+            Some(1_500)
+        );
+        engine.set_margin_model(2, Some(MarginModel::RegT(RegTParams::default())));
+        assert!(engine.margin_model(1).is_none());
+        let snap = engine.dashboard_snapshot();
+        assert_eq!(snap.accounts[1].maintenance_margin, Some(9_000));
+        assert_eq!(snap.accounts[0].maintenance_margin, Some(5_100));
+        engine.set_margin_model(2, None);
+        assert_eq!(
+            engine.dashboard_snapshot().accounts[1].maintenance_margin,
+            Some(1_500)
+        );
+    }
+
+    #[test]
+    fn cash_account_restrictions() {
+        use alice_ledger::Side;
+        let order = |side, price, quantity| OrderBuilder::new(side, price, quantity).build();
+        let mut engine = engine();
+        engine.add_account(3, RiskLimits::default(), 150_000);
+        engine.set_position(3, &position(7, 100));
+        // 種別が未設定なら制限しない。
+        assert!(engine
+            .check_account_order(3, 7, &order(Side::Ask, 1_000, 150))
+            .is_ok());
+
+        engine.set_account_type(3, AccountType::Cash);
+        assert_eq!(engine.account_type(3), Some(AccountType::Cash));
+        assert!(engine
+            .check_account_order(3, 7, &order(Side::Ask, 1_000, 100))
+            .is_ok());
+        assert!(matches!(
+            engine.check_account_order(3, 7, &order(Side::Ask, 1_000, 101)),
+            Err(AccountReject::ShortInCashAccount { held: 100, .. })
+        ));
+        // 手元現金 = 150_000 − 100 × 1_000。
+        assert!(engine
+            .check_account_order(3, 8, &order(Side::Bid, 50, 1_000))
+            .is_ok());
+        assert_eq!(
+            engine.check_account_order(3, 8, &order(Side::Bid, 50, 1_001)),
+            Err(AccountReject::LeverageInCashAccount {
+                required: 50_050,
+                available: 50_000,
+            })
+        );
+        // 現金口座は全額の証拠金。
+        assert_eq!(
+            engine.dashboard_snapshot().accounts[2].maintenance_margin,
+            Some(100_000)
+        );
+
+        engine.set_account_type(3, AccountType::PortfolioMargin);
+        assert!(engine.margin_model(3).is_none());
+        assert!(engine
+            .check_account_order(3, 7, &order(Side::Ask, 1_000, 500))
+            .is_ok());
+    }
+
+    #[test]
+    fn auction_mode_flag() {
+        use crate::auction::AuctionParams;
+        use crate::config::BreakerConfig;
+        use alice_ledger::TimeInForce;
+
+        let mut engine = engine().with_auction_mode(AuctionMode::new(
+            BreakerConfig::default(),
+            AuctionParams::default(),
+        ));
+        let ioc = OrderBuilder::bid(1_000, 1)
+            .time_in_force(TimeInForce::IOC)
+            .build();
+        assert!(!engine.in_auction(0));
+        assert!(engine.check_order_in_market(1, &ioc, None).is_ok());
+        engine.auction_mode_mut().unwrap().set_signal(true);
+        assert!(engine.in_auction(0));
+        assert_eq!(
+            engine.check_order_in_market(1, &ioc, None),
+            Err(RiskReject::NonRestingInAuction)
+        );
+    }
+
+    #[test]
+    fn flat_positions_removed_and_json() {
+        let mut engine = RiskEngine::new(MarginParams::default());
+        engine.add_account(3, RiskLimits::default(), 0);
+        engine.set_position(3, &position(7, 5));
+        engine.set_position(3, &position(7, 0));
+        let snap = engine.dashboard_snapshot();
+        assert!(snap.top_exposures.is_empty());
+        assert_eq!(snap.accounts[0].margin_coverage_bps, None);
+        let json = snap.to_json();
+        assert!(json.starts_with("{\"accounts\":[{\"account_id\":\"3\",\"open_orders\":0,"));
+        assert!(json.contains("\"maintenance_margin\":0,\"margin_coverage_bps\":null}"));
+        assert!(
+            json.ends_with("\"top_exposures\":[],\"total_daily_pnl\":0,\"tripped_accounts\":0}")
+        );
+        // 2^53 を超える ID も桁を落とさないよう文字列で書く。
+        engine.set_position(3, &position(u64::MAX, 2));
+        let json = engine.dashboard_snapshot().to_json();
+        assert!(json.contains(
+            "{\"account_id\":\"3\",\"symbol_hash\":\"18446744073709551615\",\"net_quantity\":2,\"notional\":null}"
+        ));
+        assert_eq!(engine.account_count(), 1);
+        assert!(engine.checker(4).is_none());
+    }
+}
@@ -0,0 +1,661 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 分岐の少ない単一注文の事前チェック。
+//!
+//! [`PreTradeChecker::check_order`] は判定を上から順に評価し、最初に掛かった
+//! 判定で早期リターンする。注文ごとに分岐の結果が変わるため、受理と拒否が
+//! 混在する注文列では分岐予測の失敗がそのまま遅延になる。
+//!
+//! このモジュールは同じ判定を次の 2 段に分ける:
+//!
+//! 1. [`PreTradeChecker::check_mask`] — すべての判定を毎回評価し、掛かった
+//!    判定を [`CheckMask`] のビットとして返す。比較は「注文・状態から求めた
+//!    `u64` の値 > 事前計算した閾値」の形にそろえ、[`ThresholdTable`] と
+//!    1 対 1 で突き合わせる。閾値表は制限の変更時にだけ作り直すため、判定
+//!    有効フラグや符号付き・`>=` / `<=` の変換は毎回の評価から消える
+//! 2. [`PreTradeChecker::reject_for`] — マスクが空でなければ、最下位ビット
+//!    （`check_order` の評価順で最初の判定）の [`RiskReject`] だけを組み立てる。
+//!    拒否はヒープを使わない値型で、拒否パスは `#[cold]` に追い出してある
+//!
+//! 結果は常に同じ状態で `check_order` を呼んだ場合と一致する。銘柄別の
+//! 未約定注文数だけは上限表の引き当てが必要なため、判定が有効かつ滞留注文
+//! かつポジションが渡された場合にだけ評価する。流動性の低い銘柄
+//! （[`RiskLimits::illiquid_symbols`]）の発注数量・建玉の上限も、ポジションが
+//! 渡された場合に表の値を縮めた上限と比べる。
+//!
+//! # 遅延目標
+//!
+//! x86-64・`--release`・1 コアでの 1 注文あたりの平均（既定の制限、ポジション
+//! あり）:
+//!
+//! | 経路 | 目標 |
+//! |---|---|
+//! | `check_mask` | 15 ns 以下 |
+//! | `check_order_fast`（受理） | 15 ns 以下 |
+//! | `check_order_fast`（拒否を含む混在列） | 20 ns 以下 |
+//!
+//! 評価する判定の数は注文によらず一定なので、所要時間はどの判定に掛かったか
+//! にほとんど依存しない。平均は早期リターンする `check_order` と同程度で、
+//! 利点は遅延のばらつきが小さいことと、掛かった判定をすべて観測できる
+//! ことにある。
+//!
+//! 計測は `cargo bench --bench fastpath` で行う。
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{
+    notional_headroom, position_headroom, rests_on_book, symbol_caps, ArithmeticMode,
+    PreTradeChecker, RiskReject,
+};
+use crate::inline::InlineVec;
+use crate::limit::{EnabledChecks, RiskLimits};
+
+/// [`PreTradeChecker::check_order_all`] の結果（2 件までヒープを使わない）。
+pub type Violations = InlineVec<RiskReject, 2>;
+
+/// [`CheckMask`] が表す判定の数。
+pub const CHECKS: usize = 9;
+
+const BREAKER_BIT: usize = 0;
+const ORDER_SIZE_BIT: usize = 1;
+const POSITION_BIT: usize = 2;
+const NOTIONAL_BIT: usize = 3;
+const OPEN_ORDERS_BIT: usize = 4;
+const SYMBOL_OPEN_ORDERS_BIT: usize = 5;
+const OPEN_ORDER_NOTIONAL_BIT: usize = 6;
+const NON_RESTING_RATE_BIT: usize = 7;
+const DAILY_LOSS_BIT: usize = 8;
+
+// ---------------------------------------------------------------------------
+// CheckMask
+// ---------------------------------------------------------------------------
+
+/// 掛かった判定のビット集合。
+///
+/// ビットの並びは [`PreTradeChecker::check_order`] の評価順と同じで、
+/// [`first`](Self::first) が `check_order` の返す拒否に対応する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CheckMask(u16);
+
+impl CheckMask {
+    /// 空集合。
+    pub const EMPTY: Self = Self(0);
+    /// サーキットブレーカー。
+    pub const CIRCUIT_BREAKER: Self = Self(1 << BREAKER_BIT);
+    /// 注文数量。
+    pub const ORDER_SIZE: Self = Self(1 << ORDER_SIZE_BIT);
+    /// 約定後のポジション。
+    pub const POSITION: Self = Self(1 << POSITION_BIT);
+    /// 想定元本。
+    pub const NOTIONAL: Self = Self(1 << NOTIONAL_BIT);
+    /// 未約定注文数。
+    pub const OPEN_ORDERS: Self = Self(1 << OPEN_ORDERS_BIT);
+    /// 銘柄別の未約定注文数。
+    pub const SYMBOL_OPEN_ORDERS: Self = Self(1 << SYMBOL_OPEN_ORDERS_BIT);
+    /// 未約定注文の想定元本合計。
+    pub const OPEN_ORDER_NOTIONAL: Self = Self(1 << OPEN_ORDER_NOTIONAL_BIT);
+    /// 非滞留注文（IOC / FOK）のスループット。
+    pub const NON_RESTING_RATE: Self = Self(1 << NON_RESTING_RATE_BIT);
+    /// 日次損失。
+    pub const DAILY_LOSS: Self = Self(1 << DAILY_LOSS_BIT);
+    /// すべての判定。
+    pub const ALL: Self = Self((1 << CHECKS) - 1);
+
+    /// 滞留注文にだけ適用する判定。
+    const RESTING_ONLY: Self =
+        Self(Self::OPEN_ORDERS.0 | Self::SYMBOL_OPEN_ORDERS.0 | Self::OPEN_ORDER_NOTIONAL.0);
+
+    /// ビット列から作成する（判定に対応しないビットは捨てる）。
+    #[must_use]
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    /// `checks` で有効な判定の集合（サーキットブレーカーは常に含む）。
+    #[must_use]
+    pub const fn from_checks(checks: EnabledChecks) -> Self {
+        Self(
+            Self::CIRCUIT_BREAKER.0
+                | (checks.order_size as u16) << ORDER_SIZE_BIT
+                | (checks.position as u16) << POSITION_BIT
+                | (checks.notional as u16) << NOTIONAL_BIT
+                | (checks.open_orders as u16) << OPEN_ORDERS_BIT
+                | (checks.open_orders_per_symbol as u16) << SYMBOL_OPEN_ORDERS_BIT
+                | (checks.open_order_notional as u16) << OPEN_ORDER_NOTIONAL_BIT
+                | (checks.non_resting_rate as u16) << NON_RESTING_RATE_BIT
+                | (checks.daily_loss as u16) << DAILY_LOSS_BIT,
+        )
+    }
+
+    /// ビット列。
+    #[must_use]
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// 掛かった判定が無いか。
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// `other` のビットをすべて含むか。
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 和集合。
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// 積集合。
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// 評価順で最初の判定だけを残す（空なら空）。
+    #[must_use]
+    pub const fn first(self) -> Self {
+        Self(self.0 & self.0.wrapping_neg())
+    }
+
+    /// 掛かった判定の数。
+    #[must_use]
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ThresholdTable
+// ---------------------------------------------------------------------------
+
+/// 符号付き整数を大小関係を保ったまま `u64` に写す。
+const fn ordered(value: i64) -> u64 {
+    (value as u64) ^ (1 << 63)
+}
+
+/// [`RiskLimits`] から事前計算した比較表。
+///
+/// 判定 `i`（[`CheckMask`] のビット位置）は「値 > `thresholds()[i]`」のとき
+/// 掛かり、[`inverted`](Self::inverted) のビットだけは比較結果を反転する。
+/// 値と閾値はすべて `u64` で、符号付きの量は大小関係を保つよう符号ビットを
+/// 反転して格納する。`>=` で判定する件数は値に 1 を足して `>` にそろえる。
+/// 銘柄別の未約定注文数は上限が銘柄ごとに異なるため表を使わず評価時に
+/// 比較する（表の値は `u64::MAX`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThresholdTable {
+    enabled: CheckMask,
+    inverted: CheckMask,
+    thresholds: [u64; CHECKS],
+}
+
+impl ThresholdTable {
+    /// `limits` の比較表を作成する。
+    #[must_use]
+    pub const fn from_limits(limits: &RiskLimits) -> Self {
+        Self {
+            enabled: CheckMask::from_checks(limits.checks),
+            // 日次損失は `損益 <= 上限` で掛かる。
+            inverted: CheckMask::DAILY_LOSS,
+            thresholds: [
+                0,
+                limits.max_order_size,
+                limits.max_position,
+                ordered(limits.max_notional),
+                limits.max_open_orders as u64,
+                u64::MAX,
+                ordered(limits.max_open_order_notional),
+                limits.max_non_resting_orders as u64,
+                ordered(limits.max_daily_loss),
+            ],
+        }
+    }
+
+    /// 有効な判定の集合。
+    #[must_use]
+    pub const fn enabled(&self) -> CheckMask {
+        self.enabled
+    }
+
+    /// 比較結果を反転する判定の集合。
+    #[must_use]
+    pub const fn inverted(&self) -> CheckMask {
+        self.inverted
+    }
+
+    /// 判定ごとの閾値（[`CheckMask`] のビット位置順）。
+    #[must_use]
+    pub const fn thresholds(&self) -> &[u64; CHECKS] {
+        &self.thresholds
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PreTradeChecker::check_mask
+// ---------------------------------------------------------------------------
+
+impl PreTradeChecker {
+    /// `order` に掛かるすべての判定を現在の状態で評価する。
+    ///
+    /// 判定の内容と有効フラグは [`check_order`](Self::check_order) と同じで、
+    /// [`ArithmeticMode::Checked`] のオーバーフローはその計算を使う判定の
+    /// ビットとして立つ。状態は変化しない。
+    #[allow(clippy::needless_bitwise_bool)]
+    #[inline]
+    #[must_use]
+    pub fn check_mask(&self, order: &Order, position: Option<&Position>) -> CheckMask {
+        let table = self.thresholds();
+        let checked = matches!(self.arithmetic_mode(), ArithmeticMode::Checked);
+        let resting = rests_on_book(order);
+        let quantity = order.quantity;
+        let current = position.map_or(0, |p| p.net_quantity);
+
+        // 約定後のポジション。両モードの結果を計算してから選ぶ。
+        let bid = matches!(order.side, Side::Bid);
+        let wide = quantity as i64;
+        let saturated = current.saturating_add(if bid { wide } else { wide.wrapping_neg() });
+        let (exact, wrapped) = if bid {
+            current.overflowing_add(wide)
+        } else {
+            current.overflowing_sub(wide)
+        };
+        let after = if checked { exact } else { saturated };
+        let position_overflow = checked & ((quantity > i64::MAX as u64) | wrapped);
+
+        // 想定元本（絶対値）。i128 の積は溢れないため、i64 への変換だけを判定する。
+        let product = (i128::from(order.price) * i128::from(quantity)).unsigned_abs();
+        let notional = product.min(i64::MAX as u128) as i64;
+        let notional_overflow = checked & (product > i64::MAX as u128);
+
+        let symbol = match position {
+            Some(p) if resting && table.enabled().contains(CheckMask::SYMBOL_OPEN_ORDERS) => {
+                self.symbol_open_order_count(p.symbol_hash)
+                    >= self.symbol_open_order_limit(p.symbol_hash)
+            }
+            _ => false,
+        };
+
+        let t = table.thresholds();
+        // 流動性の低い銘柄は発注数量・建玉の上限を縮めて比べる。
+        let (size_limit, position_limit) = match position {
+            Some(p) if self.limits().is_illiquid(p.symbol_hash) => {
+                let caps = self.limits().symbol_caps(p.symbol_hash);
+                (caps.max_order_size, caps.max_position)
+            }
+            _ => (t[ORDER_SIZE_BIT], t[POSITION_BIT]),
+        };
+        let mut bits = u16::from(self.is_circuit_breaker_tripped()) << BREAKER_BIT
+            | u16::from(quantity > size_limit) << ORDER_SIZE_BIT
+            | u16::from(after.unsigned_abs() > position_limit) << POSITION_BIT
+            | u16::from(ordered(notional) > t[NOTIONAL_BIT]) << NOTIONAL_BIT
+            | u16::from(u64::from(self.open_order_count()) + 1 > t[OPEN_ORDERS_BIT])
+                << OPEN_ORDERS_BIT
+            | u16::from(symbol) << SYMBOL_OPEN_ORDERS_BIT
+            | u16::from(
+                ordered(self.open_order_notional().saturating_add(notional))
+                    > t[OPEN_ORDER_NOTIONAL_BIT],
+            ) << OPEN_ORDER_NOTIONAL_BIT
+            | u16::from(
+                (u64::from(self.non_resting_count_at(order.timestamp_ns)) + 1
+                    > t[NON_RESTING_RATE_BIT])
+                    | self.non_resting_sustained_exceeded(order.timestamp_ns),
+            ) << NON_RESTING_RATE_BIT
+            | u16::from(ordered(self.loss_pnl()) > t[DAILY_LOSS_BIT]) << DAILY_LOSS_BIT;
+        bits ^= table.inverted().0;
+        bits |= u16::from(position_overflow) << POSITION_BIT
+            | u16::from(notional_overflow) << NOTIONAL_BIT
+            | u16::from(notional_overflow) << OPEN_ORDER_NOTIONAL_BIT;
+
+        let scope = if resting {
+            !CheckMask::NON_RESTING_RATE.0
+        } else {
+            !CheckMask::RESTING_ONLY.0
+        };
+        CheckMask(bits & table.enabled().0 & scope)
+    }
+
+    /// [`check_mask`](Self::check_mask) の結果を [`RiskReject`] に変換する。
+    ///
+    /// `mask` は同じ状態・同じ注文で得たものを渡す。拒否は評価順で最初の
+    /// 判定についてだけ組み立てる。
+    ///
+    /// # Errors
+    ///
+    /// `mask` が空でない場合、その最初の判定の [`RiskReject`]。
+    #[inline]
+    pub fn reject_for(
+        &self,
+        mask: CheckMask,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        if mask.is_empty() {
+            Ok(())
+        } else {
+            Err(self.build_reject(mask.first(), order, position))
+        }
+    }
+
+    /// [`check_mask`](Self::check_mask) と [`reject_for`](Self::reject_for)
+    /// による [`check_order`](Self::check_order) と同じ判定。
+    ///
+    /// # Errors
+    ///
+    /// いずれかの制限に掛かる場合 [`RiskReject`]。
+    #[inline]
+    pub fn check_order_fast(
+        &self,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.reject_for(self.check_mask(order, position), order, position)
+    }
+
+    /// 掛かったすべての判定の [`RiskReject`] を評価順に返す。
+    ///
+    /// 先頭は [`check_order`](Self::check_order) の拒否と一致する。違反が
+    /// 2 件まではヒープを使わない。状態は変化しない。
+    #[must_use]
+    pub fn check_order_all(&self, order: &Order, position: Option<&Position>) -> Violations {
+        let mut rest = self.check_mask(order, position);
+        let mut violations = Violations::new();
+        while !rest.is_empty() {
+            let first = rest.first();
+            violations.push(self.build_reject(first, order, position));
+            rest = CheckMask(rest.0 & !first.0);
+        }
+        violations
+    }
+
+    /// 1 つの判定の拒否を組み立てる。
+    #[cold]
+    #[inline(never)]
+    fn build_reject(
+        &self,
+        first: CheckMask,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> RiskReject {
+        let limits = self.limits();
+        let caps = symbol_caps(limits, position);
+        match first {
+            CheckMask::ORDER_SIZE => RiskReject::OrderSizeTooLarge {
+                size: order.quantity,
+                limit: caps.max_order_size,
+                max_allowed: caps.max_order_size,
+            },
+            CheckMask::POSITION => {
+                let current = position.map_or(0, |p| p.net_quantity);
+                match self.net_after(current, order) {
+                    Ok(after) => RiskReject::PositionLimitBreached {
+                        current,
+                        after,
+                        limit: caps.max_position,
+                        max_allowed: position_headroom(current, order.side, caps.max_position),
+                    },
+                    Err(reject) => reject,
+                }
+            }
+            CheckMask::NOTIONAL => match self.notional(order) {
+                Ok(notional) => RiskReject::NotionalExceeded {
+                    notional,
+                    limit: limits.max_notional,
+                    max_allowed: notional_headroom(order.price, limits.max_notional),
+                },
+                Err(reject) => reject,
+            },
+            CheckMask::OPEN_ORDERS => RiskReject::MaxOpenOrdersReached {
+                count: self.open_order_count(),
+                limit: limits.max_open_orders,
+            },
+            CheckMask::SYMBOL_OPEN_ORDERS => {
+                let symbol_hash = position.map_or(0, |p| p.symbol_hash);
+                RiskReject::SymbolOpenOrdersReached {
+                    symbol_hash,
+                    count: self.symbol_open_order_count(symbol_hash),
+                    limit: self.symbol_open_order_limit(symbol_hash),
+                }
+            }
+            CheckMask::OPEN_ORDER_NOTIONAL => match self.notional(order) {
+                Ok(notional) => {
+                    let open = self.open_order_notional();
+                    RiskReject::OpenOrderNotionalExceeded {
+                        open,
+                        notional,
+                        limit: limits.max_open_order_notional,
+                        max_allowed: notional_headroom(
+                            order.price,
+                            limits.max_open_order_notional.saturating_sub(open),
+                        ),
+                    }
+                }
+                Err(reject) => reject,
+            },
+            CheckMask::NON_RESTING_RATE => {
+                let (count, limit) = self
+                    .non_resting_breach(limits, order.timestamp_ns, 1)
+                    .unwrap_or((
+                        self.non_resting_count_at(order.timestamp_ns),
+                        limits.max_non_resting_orders,
+                    ));
+                RiskReject::NonRestingRateExceeded { count, limit }
+            }
+            CheckMask::DAILY_LOSS => self.daily_loss_reject(limits.max_daily_loss),
+            // CIRCUIT_BREAKER
+            _ => RiskReject::CircuitBreakerTripped,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+    use alice_ledger::{OrderId, TimeInForce};
+    use proptest::prelude::*;
+
+    fn order(side: Side, price: i64, quantity: u64, tif: TimeInForce) -> Order {
+        OrderBuilder::new(side, price, quantity)
+            .time_in_force(tif)
+            .build()
+    }
+
+    fn position(symbol_hash: u64, net_quantity: i64) -> Position {
+        PositionBuilder::new(symbol_hash, net_quantity).build()
+    }
+
+    #[test]
+    fn mask_ops() {
+        let mask = CheckMask::POSITION.union(CheckMask::DAILY_LOSS);
+        assert_eq!(mask.first(), CheckMask::POSITION);
+        assert_eq!(mask.count(), 2);
+        assert!(mask.contains(CheckMask::DAILY_LOSS));
+        assert!(!mask.contains(CheckMask::NOTIONAL));
+        assert_eq!(
+            mask.intersection(CheckMask::DAILY_LOSS),
+            CheckMask::DAILY_LOSS
+        );
+        assert!(CheckMask::EMPTY.first().is_empty());
+        assert_eq!(CheckMask::from_bits(u16::MAX), CheckMask::ALL);
+        assert_eq!(CheckMask::from_checks(EnabledChecks::ALL), CheckMask::ALL);
+    }
+
+    #[test]
+    fn collects_every_breach() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        checker.update_daily_pnl(RiskLimits::default().max_daily_loss);
+        let huge = order(Side::Bid, 1_000_000, 1_000_000, TimeInForce::GTC);
+        let mask = checker.check_mask(&huge, Some(&position(1, 0)));
+        assert!(mask.contains(
+            CheckMask::ORDER_SIZE
+                .union(CheckMask::POSITION)
+                .union(CheckMask::NOTIONAL)
+                .union(CheckMask::DAILY_LOSS)
+        ));
+        assert!(!mask.contains(CheckMask::NON_RESTING_RATE));
+        assert_eq!(
+            checker.reject_for(mask, &huge, None),
+            checker.check_order(&huge, None)
+        );
+        let all = checker.check_order_all(&huge, None);
+        assert_eq!(all.len(), mask.count() as usize);
+        assert!(all.spilled());
+        assert_eq!(
+            all.first().cloned().map_or(Ok(()), Err),
+            checker.check_order(&huge, None)
+        );
+        let small = order(Side::Bid, 100, 5, TimeInForce::GTC);
+        let one = checker.check_order_all(&small, None);
+        assert!(matches!(
+            one.first(),
+            Some(RiskReject::DailyLossLimitHit { .. })
+        ));
+        assert!(!one.spilled());
+        checker.trip_circuit_breaker();
+        assert_eq!(
+            checker.check_order_fast(&huge, None),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+    }
+
+    #[test]
+    fn table_follows_limit_changes() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let small = order(Side::Bid, 100, 5, TimeInForce::GTC);
+        assert!(checker.check_order_fast(&small, None).is_ok());
+        checker.set_limits(RiskLimits {
+            max_order_size: 4,
+            ..RiskLimits::default()
+        });
+        assert_eq!(checker.thresholds().thresholds()[ORDER_SIZE_BIT], 4);
+        assert_eq!(checker.thresholds().inverted(), CheckMask::DAILY_LOSS);
+        assert_eq!(checker.check_mask(&small, None), CheckMask::ORDER_SIZE);
+        checker.set_limits(RiskLimits {
+            max_order_size: 4,
+            checks: EnabledChecks {
+                order_size: false,
+                ..EnabledChecks::ALL
+            },
+            ..RiskLimits::default()
+        });
+        assert!(checker.check_mask(&small, None).is_empty());
+    }
+
+    #[test]
+    fn checked_overflow_sets_the_failing_check() {
+        let limits = RiskLimits {
+            max_order_size: u64::MAX,
+            max_position: u64::MAX,
+            max_notional: i64::MAX,
+            ..RiskLimits::default()
+        };
+        let checker = PreTradeChecker::new(limits).with_arithmetic_mode(ArithmeticMode::Checked);
+        let wide = order(Side::Bid, i64::MAX, 2, TimeInForce::IOC);
+        assert_eq!(checker.check_mask(&wide, None), CheckMask::NOTIONAL);
+        assert_eq!(
+            checker.check_order_fast(&wide, None),
+            Err(RiskReject::ArithmeticOverflow)
+        );
+        let edge = position(1, i64::MAX);
+        let one = order(Side::Bid, 1, 1, TimeInForce::IOC);
+        assert_eq!(
+            checker.check_order_fast(&one, Some(&edge)),
+            Err(RiskReject::ArithmeticOverflow)
+        );
+    }
+
+    fn tif() -> impl Strategy<Value = TimeInForce> {
+        prop_oneof![
+            Just(TimeInForce::GTC),
+            Just(TimeInForce::IOC),
+            Just(TimeInForce::FOK),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn matches_check_order(
+            checked in any::<bool>(),
+            checks in any::<[bool; 8]>(),
+            max_order_size in 0_u64..2_000,
+            max_position in 0_u64..5_000,
+            max_notional in -1_000_i64..2_000_000,
+            max_open_orders in 0_u32..4,
+            max_open_order_notional in -1_000_i64..3_000_000,
+            per_symbol in 0_u32..3,
+            max_non_resting in 0_u32..3,
+            max_sustained in prop_oneof![Just(u32::MAX), 0_u32..3],
+            max_daily_loss in -10_000_i64..10,
+            daily_pnl in -20_000_i64..100,
+            working in proptest::collection::vec((0_u64..3, 1_i64..2_000, 1_u64..500), 0..4),
+            non_resting in 0_usize..3,
+            breaker in proptest::bool::weighted(0.05),
+            side in any::<bool>(),
+            price in prop_oneof![-2_000_i64..5_000, Just(i64::MAX), Just(i64::MIN)],
+            quantity in prop_oneof![0_u64..3_000, Just(u64::MAX), Just(i64::MAX as u64 + 1)],
+            tif in tif(),
+            net in prop_oneof![-6_000_i64..6_000, Just(i64::MAX), Just(i64::MIN + 1)],
+            symbol in proptest::option::of(0_u64..3),
+        ) {
+            let limits = RiskLimits {
+                max_order_size,
+                max_position,
+                max_notional,
+                max_open_orders,
+                max_open_order_notional,
+                max_open_orders_per_symbol: per_symbol,
+                max_non_resting_orders: max_non_resting,
+                max_non_resting_orders_sustained: max_sustained,
+                non_resting_sustained_window_ns: 60_000_000_000,
+                max_daily_loss,
+                checks: EnabledChecks {
+                    order_size: checks[0],
+                    position: checks[1],
+                    notional: checks[2],
+                    open_orders: checks[3],
+                    open_orders_per_symbol: checks[4],
+                    open_order_notional: checks[5],
+                    non_resting_rate: checks[6],
+                    daily_loss: checks[7],
+                    ..EnabledChecks::ALL
+                },
+                ..RiskLimits::default()
+            };
+            let mode = if checked { ArithmeticMode::Checked } else { ArithmeticMode::Saturating };
+            let mut checker = PreTradeChecker::new(limits).with_arithmetic_mode(mode);
+            for (i, &(sym, p, q)) in working.iter().enumerate() {
+                let mut resting = order(Side::Bid, p, q, TimeInForce::GTC);
+                resting.id = OrderId(100 + i as u64);
+                checker.on_order_accepted_in(&resting, sym);
+            }
+            for _ in 0..non_resting {
+                checker.on_order_accepted(&order(Side::Bid, 1, 1, TimeInForce::IOC));
+            }
+            checker.update_daily_pnl(daily_pnl);
+            if breaker {
+                checker.trip_circuit_breaker();
+            }
+            let side = if side { Side::Bid } else { Side::Ask };
+            // 飽和モードの売りで i64::MIN を符号反転すると check_order が溢れる。
+            prop_assume!(checked || side == Side::Bid || quantity != i64::MAX as u64 + 1);
+            let o = order(side, price, quantity, tif);
+            let held = symbol.map(|s| position(s, net));
+            prop_assert_eq!(
+                checker.check_order_fast(&o, held.as_ref()),
+                checker.check_order(&o, held.as_ref())
+            );
+        }
+    }
+}
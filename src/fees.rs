@@ -0,0 +1,208 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 約定の手数料。
+//!
+//! 高頻度の戦略は手数料を除けば利益でも、手数料込みでは損失になりうる。
+//! [`FeeSchedule`] はメイカー・テイカー別の手数料（約定代金の bps、または
+//! 1 ロットあたりの額）を銘柄ごとに持ち、約定のたびに手数料を日次損益から
+//! 差し引く（[`FeeSchedule::on_fill`]）。日次損益と日次損失上限は手数料込みの
+//! 純損益で判定される。
+//!
+//! 負の料率はリベート（受け取り）。手数料は口座に不利な側へ丸める（支払いは
+//! 切り上げ、リベートは切り捨て）。
+
+use alloc::collections::BTreeMap;
+
+use crate::check::PreTradeChecker;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// FeeRate
+// ---------------------------------------------------------------------------
+
+/// 約定が板に流動性を供給したか（メイカー）、取ったか（テイカー）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    /// 板に置かれていた注文の約定。
+    Maker,
+    /// 板の注文に当たった約定。
+    Taker,
+}
+
+/// 手数料の料率。負の値はリベート。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeRate {
+    /// 約定代金に対する bps。
+    Bps { maker_bps: i32, taker_bps: i32 },
+    /// 1 ロットあたりの額（ticks）。
+    PerLot { maker: i64, taker: i64 },
+}
+
+impl FeeRate {
+    /// 手数料無し。
+    pub const ZERO: Self = Self::PerLot { maker: 0, taker: 0 };
+
+    /// `price` で `quantity` ロット約定したときの手数料（支払いは正、
+    /// リベートは負）。
+    #[must_use]
+    pub fn fee(&self, liquidity: Liquidity, price: i64, quantity: u64) -> i64 {
+        let quantity = i128::from(quantity);
+        let fee = match *self {
+            Self::Bps {
+                maker_bps,
+                taker_bps,
+            } => {
+                let bps = match liquidity {
+                    Liquidity::Maker => maker_bps,
+                    Liquidity::Taker => taker_bps,
+                };
+                let raw = i128::from(price.unsigned_abs())
+                    .saturating_mul(quantity)
+                    .saturating_mul(i128::from(bps));
+                if raw > 0 {
+                    // 支払いは切り上げ、リベートは 0 方向へ切り捨て。
+                    raw.saturating_add(9_999) / 10_000
+                } else {
+                    raw / 10_000
+                }
+            }
+            Self::PerLot { maker, taker } => {
+                let per_lot = match liquidity {
+                    Liquidity::Maker => maker,
+                    Liquidity::Taker => taker,
+                };
+                i128::from(per_lot).saturating_mul(quantity)
+            }
+        };
+        fee.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FeeSchedule
+// ---------------------------------------------------------------------------
+
+/// 既定の料率と銘柄ごとの料率。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeSchedule {
+    default: FeeRate,
+    /// 銘柄 → 料率。
+    rates: BTreeMap<u64, FeeRate>,
+}
+
+impl FeeSchedule {
+    /// 既定の料率で作成。
+    #[must_use]
+    pub const fn new(default: FeeRate) -> Self {
+        Self {
+            default,
+            rates: BTreeMap::new(),
+        }
+    }
+
+    /// 銘柄の料率を設定する。
+    pub fn set_rate(&mut self, symbol_hash: u64, rate: FeeRate) {
+        self.rates.insert(symbol_hash, rate);
+    }
+
+    /// 銘柄の料率を外す（既定の料率に戻す）。
+    pub fn remove(&mut self, symbol_hash: u64) -> Option<FeeRate> {
+        self.rates.remove(&symbol_hash)
+    }
+
+    /// 銘柄に適用する料率。
+    #[must_use]
+    pub fn rate(&self, symbol_hash: u64) -> FeeRate {
+        self.rates
+            .get(&symbol_hash)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// 銘柄の約定の手数料（[`FeeRate::fee`]）。
+    #[must_use]
+    pub fn fee(&self, symbol_hash: u64, liquidity: Liquidity, price: i64, quantity: u64) -> i64 {
+        self.rate(symbol_hash).fee(liquidity, price, quantity)
+    }
+
+    /// 約定の手数料を `checker` の日次損益から差し引き、手数料を返す。
+    pub fn on_fill(
+        &self,
+        checker: &mut PreTradeChecker,
+        symbol_hash: u64,
+        liquidity: Liquidity,
+        price: i64,
+        quantity: u64,
+    ) -> i64 {
+        let fee = self.fee(symbol_hash, liquidity, price, quantity);
+        checker.update_daily_pnl(fee.saturating_neg());
+        fee
+    }
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self::new(FeeRate::ZERO)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+
+    #[test]
+    fn bps_and_per_lot_rounding() {
+        let bps = FeeRate::Bps {
+            maker_bps: -2,
+            taker_bps: 3,
+        };
+        // 10_001 × 1 × 3 / 10_000 = 3.0003 → 4。
+        assert_eq!(bps.fee(Liquidity::Taker, 10_001, 1), 4);
+        // リベート 2.0002 → 2。
+        assert_eq!(bps.fee(Liquidity::Maker, 10_001, 1), -2);
+        assert_eq!(bps.fee(Liquidity::Taker, -10_000, 2), 6);
+        let per_lot = FeeRate::PerLot { maker: 1, taker: 5 };
+        assert_eq!(per_lot.fee(Liquidity::Maker, 10_000, 7), 7);
+        assert_eq!(per_lot.fee(Liquidity::Taker, 10_000, 7), 35);
+        assert_eq!(FeeRate::ZERO.fee(Liquidity::Taker, 10_000, 7), 0);
+    }
+
+    #[test]
+    fn fills_reduce_daily_pnl() {
+        let mut fees = FeeSchedule::new(FeeRate::Bps {
+            maker_bps: 0,
+            taker_bps: 10,
+        });
+        fees.set_rate(2, FeeRate::PerLot { maker: 0, taker: 3 });
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_daily_loss: -100,
+            ..RiskLimits::default()
+        });
+        checker.update_daily_pnl(50);
+        assert_eq!(
+            fees.on_fill(&mut checker, 1, Liquidity::Taker, 10_000, 100),
+            1_000
+        );
+        assert_eq!(
+            fees.on_fill(&mut checker, 2, Liquidity::Taker, 10_000, 10),
+            30
+        );
+        assert_eq!(
+            fees.on_fill(&mut checker, 2, Liquidity::Maker, 10_000, 10),
+            0
+        );
+        // 手数料を除けば 50 の利益でも、手数料込みの純損益は損失上限を超える。
+        assert_eq!(checker.daily_pnl(), -980);
+        assert_eq!(fees.remove(2), Some(FeeRate::PerLot { maker: 0, taker: 3 }));
+        assert_eq!(fees.fee(2, Liquidity::Taker, 10_000, 1), 10);
+    }
+}
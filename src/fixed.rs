@@ -0,0 +1,354 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 固定小数点の価格・数量型。
+//!
+//! エンジン本体は整数 ticks / lots で動作するが、取引所によっては
+//! 端数数量や tick 未満の価格が存在する。[`Fixed`] は `10^SCALE` を分母とする
+//! 10 進固定小数点値で、ticks / lots との相互変換を**無損失**でのみ許可する。
+//! 端数が落ちる変換はすべて `None` を返す。
+
+use core::fmt;
+
+/// `10^exp` を返す。`i64` に収まらない場合は `None`。
+#[must_use]
+pub const fn pow10(exp: u32) -> Option<i64> {
+    10_i64.checked_pow(exp)
+}
+
+/// `10^exp` を `i128` で返す（`exp <= 38`）。
+const fn pow10_i128(exp: u32) -> Option<i128> {
+    10_i128.checked_pow(exp)
+}
+
+// ---------------------------------------------------------------------------
+// Fixed
+// ---------------------------------------------------------------------------
+
+/// `raw / 10^SCALE` を表す固定小数点値。
+///
+/// 例: `Fixed::<8>::from_raw(150_000_000)` は `1.5`。
+///
+/// `SCALE` は [`MAX_SCALE`] 以下（`10^SCALE` が `i64` に収まる範囲）で、
+/// 超える場合は値を作る箇所でコンパイルエラーになる。
+///
+/// ```compile_fail
+/// let _ = alice_risk::Fixed::<19>::from_raw(1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed<const SCALE: u32>(i64);
+
+/// [`Fixed`] の小数桁数の上限。
+pub const MAX_SCALE: u32 = 18;
+
+impl<const SCALE: u32> Fixed<SCALE> {
+    /// `SCALE <= MAX_SCALE` のコンパイル時検査。値を作る経路はすべて
+    /// [`Self::from_raw`] を通る。
+    const VALID_SCALE: () = assert!(SCALE <= MAX_SCALE, "Fixed: SCALE must be at most 18");
+
+    /// ゼロ。
+    pub const ZERO: Self = Self::from_raw(0);
+
+    /// 生の整数表現から作成。
+    #[inline(always)]
+    #[must_use]
+    pub const fn from_raw(raw: i64) -> Self {
+        let () = Self::VALID_SCALE;
+        Self(raw)
+    }
+
+    /// 生の整数表現（`value * 10^SCALE`）。
+    #[inline(always)]
+    #[must_use]
+    pub const fn raw(self) -> i64 {
+        self.0
+    }
+
+    /// 整数値から作成。オーバーフロー時は `None`。
+    #[must_use]
+    pub const fn from_int(value: i64) -> Option<Self> {
+        let Some(unit) = pow10(SCALE) else {
+            return None;
+        };
+        match value.checked_mul(unit) {
+            Some(raw) => Some(Self::from_raw(raw)),
+            None => None,
+        }
+    }
+
+    /// 10 進文字列（例: `"-12.345"`）を解析する。
+    ///
+    /// 小数部が `SCALE` 桁を超える場合、丸めずに `None` を返す。
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, body) = match s.as_bytes().first()? {
+            b'-' => (true, &s[1..]),
+            b'+' => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (int_part, frac_part) = body.split_once('.').unwrap_or((body, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        if frac_part.len() > SCALE as usize {
+            return None;
+        }
+        let mut raw: i128 = 0;
+        for b in int_part.bytes().chain(frac_part.bytes()) {
+            if !b.is_ascii_digit() {
+                return None;
+            }
+            raw = raw.checked_mul(10)?.checked_add(i128::from(b - b'0'))?;
+        }
+        let pad = SCALE - frac_part.len() as u32;
+        raw = raw.checked_mul(pow10_i128(pad)?)?;
+        if negative {
+            raw = -raw;
+        }
+        i64::try_from(raw).ok().map(Self::from_raw)
+    }
+
+    /// 別スケールへ変換する。桁落ちまたはオーバーフローする場合は `None`。
+    #[must_use]
+    pub fn rescale<const TARGET: u32>(self) -> Option<Fixed<TARGET>> {
+        rescale_raw(i128::from(self.0), SCALE, TARGET)
+            .and_then(|raw| i64::try_from(raw).ok())
+            .map(Fixed::<TARGET>::from_raw)
+    }
+
+    /// `unit`（tick サイズ・ロットサイズ）の整数倍として表す。
+    ///
+    /// 価格を ticks に、数量を lots に変換する際に使用する。
+    /// `unit` の整数倍でない、または `unit` が 0 以下の場合は `None`。
+    #[must_use]
+    pub const fn to_units(self, unit: Self) -> Option<i64> {
+        if unit.0 <= 0 || self.0 % unit.0 != 0 {
+            return None;
+        }
+        Some(self.0 / unit.0)
+    }
+
+    /// `units * unit` を固定小数点値として復元する。
+    #[must_use]
+    pub const fn from_units(units: i64, unit: Self) -> Option<Self> {
+        match units.checked_mul(unit.0) {
+            Some(raw) => Some(Self::from_raw(raw)),
+            None => None,
+        }
+    }
+
+    /// 加算。オーバーフロー時は `None`。
+    #[must_use]
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(raw) => Some(Self::from_raw(raw)),
+            None => None,
+        }
+    }
+
+    /// 減算。オーバーフロー時は `None`。
+    #[must_use]
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(raw) => Some(Self::from_raw(raw)),
+            None => None,
+        }
+    }
+
+    /// 乗算（例: 価格 × 数量 = 想定元本）。
+    ///
+    /// 積は `SCALE + RHS` 桁で厳密に計算され、`OUT` 桁へ無損失で
+    /// 変換できる場合のみ `Some` を返す。
+    #[must_use]
+    pub fn checked_mul<const RHS: u32, const OUT: u32>(
+        self,
+        rhs: Fixed<RHS>,
+    ) -> Option<Fixed<OUT>> {
+        let product = i128::from(self.0).checked_mul(i128::from(rhs.raw()))?;
+        rescale_raw(product, SCALE + RHS, OUT)
+            .and_then(|raw| i64::try_from(raw).ok())
+            .map(Fixed::<OUT>::from_raw)
+    }
+
+    /// 絶対値。`i64::MIN` の場合は `None`。
+    #[must_use]
+    pub const fn checked_abs(self) -> Option<Self> {
+        match self.0.checked_abs() {
+            Some(raw) => Some(Self::from_raw(raw)),
+            None => None,
+        }
+    }
+
+    /// 負値なら `true`。
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+}
+
+/// `raw / 10^from` を `raw' / 10^to` へ無損失変換する。
+pub(crate) fn rescale_raw(raw: i128, from: u32, to: u32) -> Option<i128> {
+    if to >= from {
+        raw.checked_mul(pow10_i128(to - from)?)
+    } else {
+        let div = pow10_i128(from - to)?;
+        if raw % div != 0 {
+            return None;
+        }
+        Some(raw / div)
+    }
+}
+
+impl<const SCALE: u32> Default for Fixed<SCALE> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const SCALE: u32> fmt::Display for Fixed<SCALE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        if SCALE == 0 {
+            return write!(f, "{sign}{abs}");
+        }
+        let unit = 10_u64.pow(SCALE);
+        write!(
+            f,
+            "{sign}{}.{:0width$}",
+            abs / unit,
+            abs % unit,
+            width = SCALE as usize
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Px = Fixed<8>;
+    type Qty = Fixed<4>;
+
+    #[test]
+    fn parse_basic() {
+        assert_eq!(Px::parse("1.5"), Some(Px::from_raw(150_000_000)));
+        assert_eq!(Px::parse("-0.00000001"), Some(Px::from_raw(-1)));
+        assert_eq!(Px::parse("42"), Some(Px::from_raw(4_200_000_000)));
+        assert_eq!(Px::parse(".25"), Some(Px::from_raw(25_000_000)));
+    }
+
+    #[test]
+    fn parse_rejects_excess_precision() {
+        assert_eq!(Qty::parse("0.00001"), None);
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        assert_eq!(Px::parse(""), None);
+        assert_eq!(Px::parse("-"), None);
+        assert_eq!(Px::parse("."), None);
+        assert_eq!(Px::parse("1.2.3"), None);
+        assert_eq!(Px::parse("abc"), None);
+    }
+
+    #[test]
+    fn parse_overflow() {
+        assert_eq!(Px::parse("999999999999999"), None);
+    }
+
+    #[test]
+    fn from_int_scales() {
+        assert_eq!(Qty::from_int(3), Some(Qty::from_raw(30_000)));
+        assert_eq!(Px::from_int(i64::MAX), None);
+    }
+
+    #[test]
+    fn rescale_up_and_down() {
+        let q = Qty::parse("1.25").unwrap();
+        let up: Fixed<8> = q.rescale().unwrap();
+        assert_eq!(up.raw(), 125_000_000);
+        let down: Fixed<2> = q.rescale().unwrap();
+        assert_eq!(down.raw(), 125);
+    }
+
+    #[test]
+    fn rescale_lossy_is_none() {
+        let q = Qty::parse("1.2345").unwrap();
+        assert_eq!(q.rescale::<2>(), None);
+    }
+
+    #[test]
+    fn to_units_exact() {
+        let tick = Px::parse("0.25").unwrap();
+        let price = Px::parse("101.75").unwrap();
+        assert_eq!(price.to_units(tick), Some(407));
+        assert_eq!(Px::from_units(407, tick), Some(price));
+    }
+
+    #[test]
+    fn to_units_off_tick_is_none() {
+        let tick = Px::parse("0.25").unwrap();
+        assert_eq!(Px::parse("101.1").unwrap().to_units(tick), None);
+        assert_eq!(Px::parse("1").unwrap().to_units(Px::ZERO), None);
+    }
+
+    #[test]
+    fn checked_mul_notional() {
+        let price = Px::parse("100.5").unwrap();
+        let qty = Qty::parse("0.002").unwrap();
+        let notional: Fixed<4> = price.checked_mul(qty).unwrap();
+        assert_eq!(notional, Fixed::<4>::parse("0.201").unwrap());
+    }
+
+    #[test]
+    fn checked_mul_lossy_is_none() {
+        let price = Px::parse("0.00000001").unwrap();
+        let qty = Qty::parse("0.0001").unwrap();
+        assert_eq!(price.checked_mul::<4, 8>(qty), None);
+    }
+
+    #[test]
+    fn checked_add_sub() {
+        let a = Qty::parse("1.5").unwrap();
+        let b = Qty::parse("0.25").unwrap();
+        assert_eq!(a.checked_add(b), Qty::parse("1.75"));
+        assert_eq!(b.checked_sub(a), Qty::parse("-1.25"));
+        assert_eq!(Qty::from_raw(i64::MAX).checked_add(b), None);
+    }
+
+    #[test]
+    fn checked_abs() {
+        assert_eq!(Qty::from_raw(-5).checked_abs(), Some(Qty::from_raw(5)));
+        assert_eq!(Qty::from_raw(i64::MIN).checked_abs(), None);
+        assert!(Qty::from_raw(-1).is_negative());
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let p = Px::parse("-37.63").unwrap();
+        assert_eq!(format!("{p}"), "-37.63000000");
+        assert_eq!(Px::parse(&format!("{p}")), Some(p));
+        assert_eq!(format!("{}", Fixed::<0>::from_raw(12)), "12");
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Px::parse("1.1").unwrap() > Px::parse("1.09").unwrap());
+        assert!(Px::parse("-1").unwrap() < Px::ZERO);
+    }
+
+    #[test]
+    fn pow10_bounds() {
+        assert_eq!(pow10(0), Some(1));
+        assert_eq!(pow10(18), Some(1_000_000_000_000_000_000));
+        assert_eq!(pow10(19), None);
+    }
+}
@@ -0,0 +1,284 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! FX フォワード・スワップの期間別証拠金。
+//!
+//! 一律の bps 証拠金では、受渡日が遠いフォワードほど大きくなる金利差・
+//! 信用リスクを取りこぼす。[`ForwardMarginCalculator`] は通貨ペアの各レッグを
+//! 受渡日までの残存日数でテナーバケットに分け、バケット内のネット額に
+//! 基本料率とバケットごとの上乗せ（add-on）を掛ける。
+//!
+//! - 同じバケット内の買いと売り（同一期間のスワップの近端・遠端を含む）は
+//!   完全に相殺する。
+//! - 異なるバケット間の逆向きのネット額（期間の違うスワップの近端・遠端）は
+//!   カレンダースプレッドと同様に、一致額の証拠金の
+//!   [`ForwardMarginParams::offset_credit_bps`] を免除する。
+
+use alloc::vec::Vec;
+
+use crate::instrument::DAY_NS;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// FxLeg / FxSwap
+// ---------------------------------------------------------------------------
+
+/// フォワードの 1 レッグ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FxLeg {
+    /// 通貨ペア。
+    pub pair_hash: u64,
+    /// 受渡日（ns）。
+    pub value_date_ns: u64,
+    /// 証拠金通貨建ての想定元本。基準通貨の買いが正、売りが負。
+    pub notional: i64,
+}
+
+/// 近端と遠端の 2 レッグからなる FX スワップ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FxSwap {
+    /// 近端レッグ。
+    pub near: FxLeg,
+    /// 遠端レッグ。
+    pub far: FxLeg,
+}
+
+impl FxSwap {
+    /// `pair_hash` で `near_ns` に `notional` を買い（負なら売り）、
+    /// `far_ns` に同額を反対売買するスワップ。
+    #[must_use]
+    pub const fn new(pair_hash: u64, near_ns: u64, far_ns: u64, notional: i64) -> Self {
+        Self {
+            near: FxLeg {
+                pair_hash,
+                value_date_ns: near_ns,
+                notional,
+            },
+            far: FxLeg {
+                pair_hash,
+                value_date_ns: far_ns,
+                notional: notional.saturating_neg(),
+            },
+        }
+    }
+
+    /// 近端・遠端の順のレッグ。
+    #[must_use]
+    pub const fn legs(&self) -> [FxLeg; 2] {
+        [self.near, self.far]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ForwardMarginParams
+// ---------------------------------------------------------------------------
+
+/// テナーバケットと上乗せ料率。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenorBucket {
+    /// バケットに含める残存日数の上限（この日数以下）。
+    pub max_days: u32,
+    /// 基本料率に上乗せする料率（bps）。
+    pub add_on_bps: u32,
+}
+
+/// FX フォワード証拠金の設定。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardMarginParams {
+    /// 全期間に共通の基本料率（bps）。
+    pub base_bps: u32,
+    /// `max_days` 昇順のテナーバケット。最後のバケットを超える残存日数は
+    /// 最後のバケットに含める。
+    pub buckets: Vec<TenorBucket>,
+    /// バケット間で逆向きに一致する額の証拠金のうち免除する割合（bps）。
+    pub offset_credit_bps: u32,
+}
+
+impl Default for ForwardMarginParams {
+    fn default() -> Self {
+        Self {
+            base_bps: 200,
+            buckets: alloc::vec![
+                TenorBucket {
+                    max_days: 7,
+                    add_on_bps: 0,
+                },
+                TenorBucket {
+                    max_days: 31,
+                    add_on_bps: 50,
+                },
+                TenorBucket {
+                    max_days: 92,
+                    add_on_bps: 100,
+                },
+                TenorBucket {
+                    max_days: 365,
+                    add_on_bps: 200,
+                },
+                TenorBucket {
+                    max_days: u32::MAX,
+                    add_on_bps: 400,
+                },
+            ],
+            offset_credit_bps: 5_000,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ForwardMarginCalculator
+// ---------------------------------------------------------------------------
+
+/// FX フォワード・スワップの期間別証拠金を計算する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardMarginCalculator {
+    params: ForwardMarginParams,
+}
+
+impl ForwardMarginCalculator {
+    /// 設定から作成。
+    #[must_use]
+    pub const fn new(params: ForwardMarginParams) -> Self {
+        Self { params }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn params(&self) -> &ForwardMarginParams {
+        &self.params
+    }
+
+    /// 受渡日 `value_date_ns` のレッグが `now_ns` 時点で属するバケットの位置。
+    ///
+    /// 受渡日を過ぎたレッグは残存 0 日として扱う。バケットが空なら `None`。
+    #[must_use]
+    pub fn bucket_index(&self, value_date_ns: u64, now_ns: u64) -> Option<usize> {
+        let days = value_date_ns.saturating_sub(now_ns).div_ceil(DAY_NS);
+        let last = self.params.buckets.len().checked_sub(1)?;
+        Some(
+            self.params
+                .buckets
+                .iter()
+                .position(|b| days <= u64::from(b.max_days))
+                .unwrap_or(last),
+        )
+    }
+
+    /// `pair_hash` の全レッグに必要な証拠金。
+    ///
+    /// 他の通貨ペアのレッグは無視する。バケットが設定されていない場合は
+    /// 基本料率だけをネット額に掛ける。
+    #[must_use]
+    pub fn margin(&self, pair_hash: u64, legs: &[FxLeg], now_ns: u64) -> i64 {
+        let slots = self.params.buckets.len().max(1);
+        let mut nets = alloc::vec![0_i128; slots];
+        for leg in legs.iter().filter(|l| l.pair_hash == pair_hash) {
+            let slot = &mut nets[self.bucket_index(leg.value_date_ns, now_ns).unwrap_or(0)];
+            *slot = slot.saturating_add(i128::from(leg.notional));
+        }
+
+        // (ネット額, 証拠金) を向きごとに: [買い, 売り]。
+        let mut sides = [(0_i128, 0_i128); 2];
+        for (index, net) in nets.iter().enumerate() {
+            if *net == 0 {
+                continue;
+            }
+            let add_on = self.params.buckets.get(index).map_or(0, |b| b.add_on_bps);
+            let bps = i128::from(self.params.base_bps) + i128::from(add_on);
+            let amount = net.abs();
+            let side = &mut sides[usize::from(*net < 0)];
+            side.0 = side.0.saturating_add(amount);
+            side.1 = side.1.saturating_add(amount.saturating_mul(bps) / 10_000);
+        }
+        let [(long, long_margin), (short, short_margin)] = sides;
+        let gross = long_margin.saturating_add(short_margin);
+        let matched = long.min(short);
+        if matched == 0 {
+            return gross.min(i128::from(i64::MAX)) as i64;
+        }
+        let matched_margin = (long_margin.saturating_mul(matched) / long)
+            .saturating_add(short_margin.saturating_mul(matched) / short);
+        let credit =
+            matched_margin.saturating_mul(i128::from(self.params.offset_credit_bps)) / 10_000;
+        gross.saturating_sub(credit).min(i128::from(i64::MAX)) as i64
+    }
+
+    /// `swaps` の全レッグ（`pair_hash` のもの）に必要な証拠金。
+    #[must_use]
+    pub fn swap_margin(&self, pair_hash: u64, swaps: &[FxSwap], now_ns: u64) -> i64 {
+        let legs: Vec<FxLeg> = swaps.iter().flat_map(FxSwap::legs).collect();
+        self.margin(pair_hash, &legs, now_ns)
+    }
+}
+
+impl Default for ForwardMarginCalculator {
+    fn default() -> Self {
+        Self::new(ForwardMarginParams::default())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(days: u64, notional: i64) -> FxLeg {
+        FxLeg {
+            pair_hash: 1,
+            value_date_ns: days * DAY_NS,
+            notional,
+        }
+    }
+
+    #[test]
+    fn long_dated_forwards_charge_more() {
+        let calc = ForwardMarginCalculator::default();
+        // 2 日物: 基本 200 bps のみ。2 年物: 200 + 400 bps。
+        assert_eq!(calc.margin(1, &[leg(2, 1_000_000)], 0), 20_000);
+        assert_eq!(calc.margin(1, &[leg(730, 1_000_000)], 0), 60_000);
+        assert_eq!(calc.margin(1, &[leg(60, -1_000_000)], 0), 30_000);
+        // 受渡日を過ぎたレッグは最短バケット、他ペアは無視。
+        assert_eq!(calc.margin(1, &[leg(2, 1_000_000)], 5 * DAY_NS), 20_000);
+        assert_eq!(calc.margin(2, &[leg(2, 1_000_000)], 0), 0);
+        assert_eq!(calc.bucket_index(31 * DAY_NS, 0), Some(1));
+        assert_eq!(calc.bucket_index(31 * DAY_NS + 1, 0), Some(2));
+    }
+
+    #[test]
+    fn swap_legs_net_within_and_across_buckets() {
+        let calc = ForwardMarginCalculator::default();
+        // 近端・遠端が同じバケットなら完全に相殺。
+        let short_swap = FxSwap::new(1, 2 * DAY_NS, 5 * DAY_NS, 1_000_000);
+        assert_eq!(calc.swap_margin(1, &[short_swap], 0), 0);
+
+        // 1 週間と 1 年: 20_000 + 40_000 から一致額の 50% を免除。
+        let long_swap = FxSwap::new(1, 2 * DAY_NS, 300 * DAY_NS, 1_000_000);
+        assert_eq!(calc.swap_margin(1, &[long_swap], 0), 30_000);
+        let outright =
+            calc.margin(1, &[leg(2, 1_000_000)], 0) + calc.margin(1, &[leg(300, -1_000_000)], 0);
+        assert_eq!(outright, 60_000);
+
+        // 免除しない設定ではアウトライトの合計。
+        let no_credit = ForwardMarginCalculator::new(ForwardMarginParams {
+            offset_credit_bps: 0,
+            ..ForwardMarginParams::default()
+        });
+        assert_eq!(no_credit.swap_margin(1, &[long_swap], 0), outright);
+    }
+
+    #[test]
+    fn no_buckets_uses_base_rate() {
+        let calc = ForwardMarginCalculator::new(ForwardMarginParams {
+            buckets: Vec::new(),
+            ..ForwardMarginParams::default()
+        });
+        assert_eq!(calc.bucket_index(0, 0), None);
+        assert_eq!(calc.margin(1, &[leg(900, 1_000_000)], 0), 20_000);
+    }
+}
@@ -0,0 +1,505 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 通貨の換算。
+//!
+//! 為替レートは [`FxRateProvider`] が「`from` 1 単位あたりの `to` の額」を
+//! [`FX_RATE_SCALE`] 倍の整数で返す。片方向のレートしか無い通貨の組は
+//! [`convert`] が逆方向のレートで割って換算する。
+//!
+//! レートには 3 種類ある。観測レート（[`FxQuote`]）は時刻を持ち、
+//! [`FxRateProvider::max_age_ns`] より古いものは使わない。固定レート（ペッグ）は
+//! 鮮度を問わず観測レートより優先する。代替レートは観測レートが古い・無い・
+//! 0 以下のときだけ使う。どれも使えなければ換算は [`FxError`] を返し、呼び出し
+//! 側は判定を通さない（フェイルクローズ）。
+//!
+//! 換算の丸めは口座に不利な側に揃える。証拠金などの要求額は切り上げ
+//! （[`margin_in`]）、損益・残高は切り捨て（[`pnl_in`]）、想定元本は絶対値を
+//! 切り上げる（[`notional_in`]）。
+//! [`MarginCalculator::instrument_initial_margin_held`] などは決済通貨で計算した
+//! 証拠金を、銘柄の [`MarginCurrency`](crate::instrument::MarginCurrency) が
+//! 指す通貨に切り上げで換算する。
+
+use alloc::collections::BTreeMap;
+
+use crate::instrument::Instrument;
+use crate::margin::MarginCalculator;
+
+extern crate alloc;
+
+/// 為替レートの倍率（1.0 = `100_000_000`）。
+pub const FX_RATE_SCALE: i64 = 100_000_000;
+
+// ---------------------------------------------------------------------------
+// Rounding
+// ---------------------------------------------------------------------------
+
+/// 換算の端数の丸め。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// 負の無限大方向（損益・残高・資産額）。
+    Floor,
+    /// 正の無限大方向（証拠金などの要求額）。
+    Ceil,
+    /// 0 から離れる方向（想定元本）。
+    Outward,
+}
+
+impl Rounding {
+    /// `numerator / denominator`（`denominator > 0`）をこの向きに丸める。
+    const fn div(self, numerator: i128, denominator: i128) -> i128 {
+        let q = numerator.div_euclid(denominator);
+        let exact = numerator.rem_euclid(denominator) == 0;
+        match self {
+            Self::Floor => q,
+            Self::Ceil | Self::Outward if exact => q,
+            Self::Outward if numerator < 0 => q,
+            Self::Ceil | Self::Outward => q + 1,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FxRateProvider
+// ---------------------------------------------------------------------------
+
+/// 時刻付きの観測レート。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FxQuote {
+    /// `from` 1 単位あたりの `to` の額（[`FX_RATE_SCALE`] 倍）。
+    pub rate: i64,
+    /// 観測時刻（ns）。
+    pub timestamp_ns: u64,
+}
+
+/// 換算に使えるレートが無い。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FxError {
+    /// どちら向きのレートも無い。
+    Missing {
+        /// 換算元の通貨。
+        from: u64,
+        /// 換算先の通貨。
+        to: u64,
+    },
+    /// 観測レートが許容より古く、代替レートも無い。
+    Stale {
+        /// 換算元の通貨。
+        from: u64,
+        /// 換算先の通貨。
+        to: u64,
+        /// 観測レートの古さ（ns）。
+        age_ns: u64,
+        /// 許容する古さ（ns）。
+        max_age_ns: u64,
+    },
+    /// レートが 0 以下で、代替レートも無い。
+    Invalid {
+        /// 換算元の通貨。
+        from: u64,
+        /// 換算先の通貨。
+        to: u64,
+    },
+    /// 換算結果が `i64` に収まらない。
+    Overflow,
+}
+
+/// 為替レートの供給元。
+///
+/// 実装するのは [`Self::quote`] だけでよい。固定・代替レートと鮮度の判定は
+/// 既定では無く、[`Self::rate_at`] がそれらを組み合わせて使えるレートを選ぶ。
+pub trait FxRateProvider {
+    /// `from → to` の最新の観測レート。無ければ `None`。
+    fn quote(&self, from: u64, to: u64) -> Option<FxQuote>;
+
+    /// `from → to` の固定レート。鮮度を問わず観測レートより優先する。
+    fn pegged(&self, _from: u64, _to: u64) -> Option<i64> {
+        None
+    }
+
+    /// 観測レートが使えないときの `from → to` の代替レート。
+    fn fallback(&self, _from: u64, _to: u64) -> Option<i64> {
+        None
+    }
+
+    /// 観測レートの許容する古さ（ns）。`None` は判定しない。
+    fn max_age_ns(&self) -> Option<u64> {
+        None
+    }
+
+    /// `now_ns` 時点で `from → to` に使えるレート。
+    ///
+    /// 固定レート、鮮度内の観測レート、代替レートの順に選ぶ。
+    ///
+    /// # Errors
+    ///
+    /// どれも使えなければ、観測レートが無い・古い・0 以下に応じて
+    /// [`FxError::Missing`]・[`FxError::Stale`]・[`FxError::Invalid`]。
+    fn rate_at(&self, from: u64, to: u64, now_ns: u64) -> Result<i64, FxError> {
+        if let Some(rate) = self.pegged(from, to) {
+            return if rate > 0 {
+                Ok(rate)
+            } else {
+                Err(FxError::Invalid { from, to })
+            };
+        }
+        let error = match self.quote(from, to) {
+            None => FxError::Missing { from, to },
+            Some(q) if q.rate <= 0 => FxError::Invalid { from, to },
+            Some(q) => {
+                let age_ns = now_ns.saturating_sub(q.timestamp_ns);
+                match self.max_age_ns() {
+                    Some(max_age_ns) if age_ns > max_age_ns => FxError::Stale {
+                        from,
+                        to,
+                        age_ns,
+                        max_age_ns,
+                    },
+                    _ => return Ok(q.rate),
+                }
+            }
+        };
+        self.fallback(from, to).filter(|&r| r > 0).ok_or(error)
+    }
+}
+
+/// `now_ns` 時点のレートで `amount`（`from` 建て）を `to` 建てに換算する。
+///
+/// 同じ通貨はそのまま。`from → to` のレートが無ければ `to → from` のレートで
+/// 割る。
+///
+/// # Errors
+///
+/// 使えるレートが無ければ [`FxRateProvider::rate_at`] の拒否（`from → to` が
+/// 無いときは `to → from` の拒否）、結果が `i64` に収まらなければ
+/// [`FxError::Overflow`]。
+pub fn convert<F: FxRateProvider + ?Sized>(
+    fx: &F,
+    amount: i64,
+    from: u64,
+    to: u64,
+    rounding: Rounding,
+    now_ns: u64,
+) -> Result<i64, FxError> {
+    if from == to {
+        return Ok(amount);
+    }
+    let amount = i128::from(amount);
+    let scale = i128::from(FX_RATE_SCALE);
+    let converted = match fx.rate_at(from, to, now_ns) {
+        Ok(rate) => rounding.div(amount * i128::from(rate), scale),
+        Err(FxError::Missing { .. }) => {
+            let inverse = fx.rate_at(to, from, now_ns).map_err(|e| match e {
+                FxError::Missing { .. } => FxError::Missing { from, to },
+                e => e,
+            })?;
+            rounding.div(amount * scale, i128::from(inverse))
+        }
+        Err(e) => return Err(e),
+    };
+    i64::try_from(converted).map_err(|_| FxError::Overflow)
+}
+
+/// 想定元本を換算する（絶対値を切り上げ、符号は保つ）。
+///
+/// # Errors
+///
+/// [`convert`] を参照。
+pub fn notional_in<F: FxRateProvider + ?Sized>(
+    fx: &F,
+    notional: i64,
+    from: u64,
+    to: u64,
+    now_ns: u64,
+) -> Result<i64, FxError> {
+    convert(fx, notional, from, to, Rounding::Outward, now_ns)
+}
+
+/// 損益を換算する（切り捨て。損失は大きく、利益は小さく見る）。
+///
+/// # Errors
+///
+/// [`convert`] を参照。
+pub fn pnl_in<F: FxRateProvider + ?Sized>(
+    fx: &F,
+    pnl: i64,
+    from: u64,
+    to: u64,
+    now_ns: u64,
+) -> Result<i64, FxError> {
+    convert(fx, pnl, from, to, Rounding::Floor, now_ns)
+}
+
+/// 証拠金などの要求額を換算する（切り上げ）。
+///
+/// # Errors
+///
+/// [`convert`] を参照。
+pub fn margin_in<F: FxRateProvider + ?Sized>(
+    fx: &F,
+    margin: i64,
+    from: u64,
+    to: u64,
+    now_ns: u64,
+) -> Result<i64, FxError> {
+    convert(fx, margin, from, to, Rounding::Ceil, now_ns)
+}
+
+// ---------------------------------------------------------------------------
+// FxRates
+// ---------------------------------------------------------------------------
+
+/// 通貨の組ごとの観測・固定・代替レートの表。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FxRates {
+    /// (from, to) → 観測レート。
+    quotes: BTreeMap<(u64, u64), FxQuote>,
+    /// (from, to) → 固定レート。
+    pegs: BTreeMap<(u64, u64), i64>,
+    /// (from, to) → 代替レート。
+    fallbacks: BTreeMap<(u64, u64), i64>,
+    /// 観測レートの許容する古さ（ns）。
+    max_age_ns: Option<u64>,
+}
+
+impl FxRates {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            quotes: BTreeMap::new(),
+            pegs: BTreeMap::new(),
+            fallbacks: BTreeMap::new(),
+            max_age_ns: None,
+        }
+    }
+
+    /// 観測レートの許容する古さを設定する（builder 形式）。
+    #[must_use]
+    pub const fn with_max_age_ns(mut self, max_age_ns: u64) -> Self {
+        self.max_age_ns = Some(max_age_ns);
+        self
+    }
+
+    /// 観測レートの許容する古さを設定する。`None` は判定しない。
+    pub fn set_max_age_ns(&mut self, max_age_ns: Option<u64>) {
+        self.max_age_ns = max_age_ns;
+    }
+
+    /// `timestamp_ns` に観測した `from` 1 単位あたりの `to` のレートを設定する。
+    pub fn set_rate(&mut self, from: u64, to: u64, rate: i64, timestamp_ns: u64) {
+        self.quotes
+            .insert((from, to), FxQuote { rate, timestamp_ns });
+    }
+
+    /// 観測レートを外す。
+    pub fn remove(&mut self, from: u64, to: u64) -> Option<FxQuote> {
+        self.quotes.remove(&(from, to))
+    }
+
+    /// `from → to` を固定レートにする。
+    pub fn peg(&mut self, from: u64, to: u64, rate: i64) {
+        self.pegs.insert((from, to), rate);
+    }
+
+    /// 固定レートを外す。
+    pub fn unpeg(&mut self, from: u64, to: u64) -> Option<i64> {
+        self.pegs.remove(&(from, to))
+    }
+
+    /// `from → to` の代替レートを設定する。
+    pub fn set_fallback(&mut self, from: u64, to: u64, rate: i64) {
+        self.fallbacks.insert((from, to), rate);
+    }
+
+    /// 代替レートを外す。
+    pub fn remove_fallback(&mut self, from: u64, to: u64) -> Option<i64> {
+        self.fallbacks.remove(&(from, to))
+    }
+}
+
+impl FxRateProvider for FxRates {
+    fn quote(&self, from: u64, to: u64) -> Option<FxQuote> {
+        self.quotes.get(&(from, to)).copied()
+    }
+
+    fn pegged(&self, from: u64, to: u64) -> Option<i64> {
+        self.pegs.get(&(from, to)).copied()
+    }
+
+    fn fallback(&self, from: u64, to: u64) -> Option<i64> {
+        self.fallbacks.get(&(from, to)).copied()
+    }
+
+    fn max_age_ns(&self) -> Option<u64> {
+        self.max_age_ns
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Margin
+// ---------------------------------------------------------------------------
+
+impl MarginCalculator {
+    /// [`Self::instrument_initial_margin`] を `now_ns` 時点のレートで保有する
+    /// 通貨に切り上げで換算する。
+    ///
+    /// （通貨, 額）を返す。口座の基準通貨は `account_currency`。
+    ///
+    /// # Errors
+    ///
+    /// 使えるレートが無ければ [`FxError`]。
+    pub fn instrument_initial_margin_held<F: FxRateProvider + ?Sized>(
+        &self,
+        instrument: &Instrument,
+        price: i64,
+        quantity: u64,
+        account_currency: u64,
+        fx: &F,
+        now_ns: u64,
+    ) -> Result<(u64, i64), FxError> {
+        let margin = self.instrument_initial_margin(instrument, price, quantity);
+        held(instrument, margin, account_currency, fx, now_ns)
+    }
+
+    /// [`Self::instrument_maintenance_margin`] を `now_ns` 時点のレートで保有
+    /// する通貨に切り上げで換算する。
+    ///
+    /// [`Self::instrument_initial_margin_held`] を参照。
+    ///
+    /// # Errors
+    ///
+    /// 使えるレートが無ければ [`FxError`]。
+    pub fn instrument_maintenance_margin_held<F: FxRateProvider + ?Sized>(
+        &self,
+        instrument: &Instrument,
+        price: i64,
+        quantity: u64,
+        account_currency: u64,
+        fx: &F,
+        now_ns: u64,
+    ) -> Result<(u64, i64), FxError> {
+        let margin = self.instrument_maintenance_margin(instrument, price, quantity);
+        held(instrument, margin, account_currency, fx, now_ns)
+    }
+}
+
+/// 決済通貨の証拠金 `margin` を保有する通貨に切り上げで換算する。
+fn held<F: FxRateProvider + ?Sized>(
+    instrument: &Instrument,
+    margin: i64,
+    account_currency: u64,
+    fx: &F,
+    now_ns: u64,
+) -> Result<(u64, i64), FxError> {
+    let currency = instrument.margin_currency_hash(account_currency);
+    let amount = margin_in(fx, margin, instrument.quote_currency, currency, now_ns)?;
+    Ok((currency, amount))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instrument::MarginCurrency;
+    use crate::margin::MarginParams;
+
+    const USD: u64 = 1;
+    const JPY: u64 = 2;
+    const EUR: u64 = 3;
+    const HKD: u64 = 4;
+
+    #[test]
+    fn convert_rounds_against_the_account() {
+        let mut fx = FxRates::new();
+        // 1 USD = 150.5 JPY。
+        fx.set_rate(USD, JPY, 15_050_000_000, 0);
+        assert_eq!(convert(&fx, 3, USD, JPY, Rounding::Floor, 0), Ok(451));
+        assert_eq!(convert(&fx, 3, USD, JPY, Rounding::Ceil, 0), Ok(452));
+        // 逆方向は割る: 1_000 / 150.5 = 6.64…
+        assert_eq!(pnl_in(&fx, 1_000, JPY, USD, 0), Ok(6));
+        assert_eq!(margin_in(&fx, 1_000, JPY, USD, 0), Ok(7));
+        assert_eq!(pnl_in(&fx, -1_000, JPY, USD, 0), Ok(-7));
+        // 想定元本は売りでも絶対値を切り上げる。
+        assert_eq!(notional_in(&fx, -1_000, JPY, USD, 0), Ok(-7));
+        assert_eq!(notional_in(&fx, 1_000, JPY, USD, 0), Ok(7));
+        assert_eq!(convert(&fx, 5, EUR, EUR, Rounding::Ceil, 0), Ok(5));
+        assert_eq!(
+            convert(&fx, 5, EUR, USD, Rounding::Ceil, 0),
+            Err(FxError::Missing { from: EUR, to: USD })
+        );
+        fx.set_rate(EUR, USD, 0, 0);
+        assert_eq!(
+            convert(&fx, 5, EUR, USD, Rounding::Ceil, 0),
+            Err(FxError::Invalid { from: EUR, to: USD })
+        );
+        assert_eq!(
+            convert(&fx, i64::MAX, USD, JPY, Rounding::Ceil, 0),
+            Err(FxError::Overflow)
+        );
+    }
+
+    #[test]
+    fn stale_rates_fail_closed_unless_pegged_or_backed() {
+        let mut fx = FxRates::new().with_max_age_ns(1_000);
+        fx.set_rate(USD, JPY, 15_000_000_000, 10_000);
+        assert_eq!(margin_in(&fx, 2, USD, JPY, 11_000), Ok(300));
+        // 逆方向の換算も同じレートの鮮度で判定する。
+        let stale = FxError::Stale {
+            from: USD,
+            to: JPY,
+            age_ns: 1_001,
+            max_age_ns: 1_000,
+        };
+        assert_eq!(margin_in(&fx, 2, USD, JPY, 11_001), Err(stale));
+        assert_eq!(pnl_in(&fx, 300, JPY, USD, 11_001), Err(stale));
+        // 古いレートは代替レートで置き換える。
+        fx.set_fallback(USD, JPY, 16_000_000_000);
+        assert_eq!(margin_in(&fx, 2, USD, JPY, 11_001), Ok(320));
+        assert_eq!(margin_in(&fx, 2, USD, JPY, 11_000), Ok(300));
+        // 固定レートは古さを問わず、観測レートより優先する。
+        fx.set_rate(HKD, USD, 12_900_000, 0);
+        fx.peg(HKD, USD, 12_800_000);
+        assert_eq!(notional_in(&fx, 1_000, HKD, USD, u64::MAX), Ok(128));
+        assert_eq!(fx.unpeg(HKD, USD), Some(12_800_000));
+        assert!(matches!(
+            notional_in(&fx, 1_000, HKD, USD, u64::MAX),
+            Err(FxError::Stale { .. })
+        ));
+        assert_eq!(fx.remove(HKD, USD).map(|q| q.rate), Some(12_900_000));
+        assert_eq!(fx.remove_fallback(USD, JPY), Some(16_000_000_000));
+    }
+
+    #[test]
+    fn margin_held_in_configured_currency() {
+        let mut fx = FxRates::new();
+        fx.set_rate(USD, JPY, 15_000_000_000, 0);
+        fx.set_rate(EUR, JPY, 16_000_000_000, 0);
+        let calc = MarginCalculator::new(MarginParams::default());
+        // USD/JPY、価格は JPY 建て。10 × 150 × 10% = 150 JPY。
+        let usdjpy = Instrument::perpetual(9).with_currencies(USD, JPY);
+        assert_eq!(
+            calc.instrument_initial_margin_held(&usdjpy, 150, 10, EUR, &fx, 0),
+            Ok((JPY, 150))
+        );
+        let base = usdjpy.with_margin_currency(MarginCurrency::Base);
+        assert_eq!(
+            calc.instrument_initial_margin_held(&base, 150, 10, EUR, &fx, 0),
+            Ok((USD, 1))
+        );
+        // 150 / 160 = 0.9375 EUR → 切り上げて 1。
+        let account = usdjpy.with_margin_currency(MarginCurrency::Account);
+        assert_eq!(
+            calc.instrument_maintenance_margin_held(&account, 150, 20, EUR, &fx, 0),
+            Ok((EUR, 1))
+        );
+        assert!(calc
+            .instrument_initial_margin_held(&account, 150, 10, USD + 10, &fx, 0)
+            .is_err());
+    }
+}
@@ -7,6 +7,19 @@
 //!
 //! ポジション全体の delta/gamma/vega エクスポージャーが
 //! 設定上限を超えていないか検証する。
+//!
+//! [`PortfolioGreeks`] はグリークスを原資産ごとに集計し、発注前の判定に
+//! 組み込む。注文の 1 ロットあたりのグリークスは呼び出し側が与え、約定後の
+//! 原資産ごとの合計が上限を超える注文を [`RiskReject::GreeksLimitExceeded`]
+//! （約定後の予想値付き）で拒否する。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{PreTradeChecker, RiskReject};
+
+extern crate alloc;
 
 // ---------------------------------------------------------------------------
 // GreeksLimits
@@ -68,6 +81,30 @@ impl GreeksExposure {
             vega: self.vega.saturating_add(other.vega),
         }
     }
+
+    /// 1 ロットあたりのグリークスを `quantity` 倍する（売りは負）。
+    #[must_use]
+    pub const fn scaled(self, quantity: i64) -> Self {
+        Self {
+            delta: self.delta.saturating_mul(quantity),
+            gamma: self.gamma.saturating_mul(quantity),
+            vega: self.vega.saturating_mul(quantity),
+        }
+    }
+
+    /// 1 ロットあたりのグリークス `per_lot` の `order` が約定したときの変化。
+    #[must_use]
+    pub const fn of_order(per_lot: Self, order: &Order) -> Self {
+        let quantity = if order.quantity > i64::MAX as u64 {
+            i64::MAX
+        } else {
+            order.quantity as i64
+        };
+        match order.side {
+            Side::Bid => per_lot.scaled(quantity),
+            Side::Ask => per_lot.scaled(-quantity),
+        }
+    }
 }
 
 impl Default for GreeksExposure {
@@ -125,6 +162,117 @@ pub const fn check_greeks(
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// PortfolioGreeks
+// ---------------------------------------------------------------------------
+
+/// 原資産ごとのグリークスの合計と上限。
+#[derive(Debug, Clone)]
+pub struct PortfolioGreeks {
+    /// 原資産 → グリークスの合計。
+    exposures: BTreeMap<u64, GreeksExposure>,
+    /// 原資産別の上限が無い場合の上限。
+    default_limits: GreeksLimits,
+    /// 原資産 → 上限。
+    limits: BTreeMap<u64, GreeksLimits>,
+}
+
+impl PortfolioGreeks {
+    /// 全原資産に共通の上限で作成。
+    #[must_use]
+    pub const fn new(default_limits: GreeksLimits) -> Self {
+        Self {
+            exposures: BTreeMap::new(),
+            default_limits,
+            limits: BTreeMap::new(),
+        }
+    }
+
+    /// 原資産の上限を設定する。
+    pub fn set_limits(&mut self, underlying_hash: u64, limits: GreeksLimits) {
+        self.limits.insert(underlying_hash, limits);
+    }
+
+    /// 原資産に適用する上限。
+    #[must_use]
+    pub fn limits(&self, underlying_hash: u64) -> &GreeksLimits {
+        self.limits
+            .get(&underlying_hash)
+            .unwrap_or(&self.default_limits)
+    }
+
+    /// 原資産のグリークスの合計を置き換える（再評価時）。
+    pub fn set_exposure(&mut self, underlying_hash: u64, exposure: GreeksExposure) {
+        self.exposures.insert(underlying_hash, exposure);
+    }
+
+    /// 原資産のグリークスの合計。
+    #[must_use]
+    pub fn exposure(&self, underlying_hash: u64) -> GreeksExposure {
+        self.exposures
+            .get(&underlying_hash)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// 1 ロットあたりのグリークス `per_lot` の注文の約定を合計に加える。
+    pub fn on_fill(&mut self, underlying_hash: u64, per_lot: GreeksExposure, order: &Order) {
+        let projected = self.projected(underlying_hash, per_lot, order);
+        self.exposures.insert(underlying_hash, projected);
+    }
+
+    /// `order` が約定した後の原資産のグリークスの合計。
+    #[must_use]
+    pub fn projected(
+        &self,
+        underlying_hash: u64,
+        per_lot: GreeksExposure,
+        order: &Order,
+    ) -> GreeksExposure {
+        self.exposure(underlying_hash)
+            .add(GreeksExposure::of_order(per_lot, order))
+    }
+
+    /// `order` が約定した後の原資産のグリークスを上限と比べる。
+    ///
+    /// # Errors
+    ///
+    /// 約定後の delta・gamma・vega のいずれかが上限を超える場合は
+    /// [`RiskReject::GreeksLimitExceeded`]。
+    pub fn check(
+        &self,
+        underlying_hash: u64,
+        per_lot: GreeksExposure,
+        order: &Order,
+    ) -> Result<(), RiskReject> {
+        let projected = self.projected(underlying_hash, per_lot, order);
+        check_greeks(&projected, self.limits(underlying_hash)).map_err(|breach| {
+            RiskReject::GreeksLimitExceeded {
+                underlying_hash,
+                projected,
+                breach,
+            }
+        })
+    }
+
+    /// [`Self::check`] の後に `checker` で判定する。
+    ///
+    /// # Errors
+    ///
+    /// グリークスの上限の拒否、それ以外は `checker` の拒否。
+    pub fn check_order(
+        &self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+        underlying_hash: u64,
+        per_lot: GreeksExposure,
+    ) -> Result<(), RiskReject> {
+        self.check(underlying_hash, per_lot, order)?;
+        checker.check_order(order, position)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -294,4 +442,53 @@ mod tests {
         let c = a.add(b);
         assert_eq!(c.delta, i64::MAX);
     }
+
+    #[test]
+    fn portfolio_rejects_with_projected_greeks_per_underlying() {
+        use alice_ledger::{OrderId, OrderType, TimeInForce};
+        let order = |side, quantity| Order {
+            id: OrderId(1),
+            side,
+            order_type: OrderType::Limit,
+            price: 100,
+            quantity,
+            filled_quantity: 0,
+            timestamp_ns: 0,
+            time_in_force: TimeInForce::GTC,
+        };
+        let per_lot = GreeksExposure {
+            delta: 50,
+            gamma: 2,
+            vega: 10,
+        };
+        let mut greeks = PortfolioGreeks::new(GreeksLimits::default());
+        greeks.set_limits(
+            7,
+            GreeksLimits {
+                max_abs_delta: 1_000,
+                ..GreeksLimits::default()
+            },
+        );
+        greeks.on_fill(7, per_lot, &order(Side::Bid, 15));
+        assert_eq!(greeks.exposure(7).delta, 750);
+        assert_eq!(
+            greeks.check(7, per_lot, &order(Side::Bid, 6)),
+            Err(RiskReject::GreeksLimitExceeded {
+                underlying_hash: 7,
+                projected: GreeksExposure {
+                    delta: 1_050,
+                    gamma: 42,
+                    vega: 210,
+                },
+                breach: GreeksReject::DeltaExceeded {
+                    current: 1_050,
+                    limit: 1_000,
+                },
+            })
+        );
+        // 売りは建玉のグリークスを減らす。
+        assert!(greeks.check(7, per_lot, &order(Side::Ask, 10)).is_ok());
+        // 他の原資産は共通の上限で別に集計する。
+        assert!(greeks.check(8, per_lot, &order(Side::Bid, 6)).is_ok());
+    }
 }
@@ -0,0 +1,1290 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 銘柄ごとのサーキットブレーカーと売買停止の登録簿。
+//!
+//! [`HaltRegistry`] は銘柄ごとに [`BreakerConfig`] を持ち、価格の更新
+//! （[`HaltRegistry::on_price`]）で閾値を超えた銘柄を停止する。停止中の銘柄の
+//! 注文は [`RiskReject::SymbolHalted`] で拒否され、停止はすべて
+//! [`TripRecord`] として履歴に残る。
+//!
+//! 判定方式は [`BreakerMode`] で選ぶ。`Band` は 1 つの価格ソースと参照価格の
+//! 乖離を見る。`Divergence` は独立した 2 つの価格ソース（例: 自社の板の仲値と
+//! 外部の指数）を比べ、片方の配信が壊れて価格が動いていないように見える
+//! 場合でも停止できる。主ソースには複数の市場の合成参照価格
+//! （[`HaltRegistry::on_composite`]）も使える。
+//!
+//! 関連銘柄（例: 先物と ETF・オプション）には [`PropagationRule`] で停止を
+//! 伝播できる。伝播先は停止するか、プライスカラーを縮小される。
+//!
+//! 市場全体の下落には [`MarketWideBreaker`] が段階的に発動する。登録簿は
+//! 市場全体の停止中には銘柄の停止の終了・再開を認めない。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, OrderType, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::composite::{median, CompositeReference};
+use crate::instrument::DAY_NS;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// BreakerConfig
+// ---------------------------------------------------------------------------
+
+/// ブレーカーの判定方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerMode {
+    /// 主ソースの価格が参照価格から `band_bps` を超えて動いたら停止する。
+    Band { band_bps: u32 },
+    /// 主ソースと副ソースの価格が `max_divergence_bps` を超えて離れたら停止する。
+    Divergence { max_divergence_bps: u32 },
+}
+
+/// 停止明けの `Band` の参照価格の決め方。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reopening {
+    /// 停止前の参照価格のまま判定を再開する。
+    Keep,
+    /// 停止明けの最初の `count` 件の主ソースの価格の中央値を新しい参照価格に
+    /// する（1 なら最初の価格、例: 再開オークションの約定価格）。揃うまでは
+    /// 判定しない。
+    Median { count: u8 },
+}
+
+/// 1 銘柄のブレーカーの設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerConfig {
+    /// 判定方式。
+    pub mode: BreakerMode,
+    /// 停止時間（ns）。0 は手動で再開するまで。
+    pub halt_ns: u64,
+    /// 停止明けの参照価格の決め方。
+    pub reopening: Reopening,
+}
+
+/// 指数・バスケットのブレーカーグループ。
+///
+/// 構成銘柄の主ソースの価格から指数を計算し、参照指数から `band_bps` を
+/// 超えて動いたら構成銘柄をまとめて停止する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakerGroup {
+    /// 構成銘柄と重み（指数 = Σ 重み × 価格）。
+    pub members: Vec<(u64, i64)>,
+    /// 参照指数からの値幅（bps）。
+    pub band_bps: u32,
+    /// 停止時間（ns）。0 は手動で再開するまで。
+    pub halt_ns: u64,
+}
+
+impl BreakerGroup {
+    /// 構成銘柄の価格から指数を計算する。価格の無い構成銘柄があれば `None`。
+    #[must_use]
+    pub fn index(&self, price: impl Fn(u64) -> Option<i64>) -> Option<i64> {
+        let mut sum = 0_i128;
+        for &(symbol_hash, weight) in &self.members {
+            sum += i128::from(weight) * i128::from(price(symbol_hash)?);
+        }
+        Some(sum.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64)
+    }
+
+    /// `symbol_hash` が構成銘柄か。
+    #[must_use]
+    pub fn contains(&self, symbol_hash: u64) -> bool {
+        self.members.iter().any(|&(s, _)| s == symbol_hash)
+    }
+}
+
+/// 伝播先に掛ける制限。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationAction {
+    /// 停止する。
+    Halt,
+    /// 指値を主ソースの最新価格から `collar_bps` 以内に制限する。
+    TightenCollar { collar_bps: u32 },
+}
+
+/// 1 銘柄の停止を関連銘柄（例: 先物 → ETF・オプション）へ伝播する規則。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropagationRule {
+    /// 伝播先の銘柄。
+    pub targets: Vec<u64>,
+    /// 伝播先に掛ける制限。
+    pub action: PropagationAction,
+}
+
+/// 価格ソース。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    /// 主ソース（例: 自社の板の仲値）。
+    Primary,
+    /// 副ソース（例: 外部の指数）。
+    Secondary,
+}
+
+// ---------------------------------------------------------------------------
+// HaltReason / TripRecord
+// ---------------------------------------------------------------------------
+
+/// 停止の理由。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltReason {
+    /// 参照価格からの値幅を超えた。
+    Band {
+        price: i64,
+        reference: i64,
+        move_bps: u32,
+        band_bps: u32,
+    },
+    /// 2 つの価格ソースが乖離した。
+    Divergence {
+        primary: i64,
+        secondary: i64,
+        divergence_bps: u32,
+        limit_bps: u32,
+    },
+    /// 所属するブレーカーグループの指数が値幅を超えた。
+    Index {
+        group_id: u64,
+        index: i64,
+        reference: i64,
+        move_bps: u32,
+        band_bps: u32,
+    },
+    /// 関連銘柄の停止の伝播。
+    Propagated { source_symbol: u64 },
+    /// 手動の停止。
+    Manual,
+}
+
+/// 停止の理由の種類（統計の集計用）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HaltKind {
+    /// [`HaltReason::Band`]。
+    Band,
+    /// [`HaltReason::Divergence`]。
+    Divergence,
+    /// [`HaltReason::Index`]。
+    Index,
+    /// [`HaltReason::Propagated`]。
+    Propagated,
+    /// [`HaltReason::Manual`]。
+    Manual,
+}
+
+impl HaltReason {
+    /// 理由の種類。
+    #[must_use]
+    pub const fn kind(&self) -> HaltKind {
+        match self {
+            Self::Band { .. } => HaltKind::Band,
+            Self::Divergence { .. } => HaltKind::Divergence,
+            Self::Index { .. } => HaltKind::Index,
+            Self::Propagated { .. } => HaltKind::Propagated,
+            Self::Manual => HaltKind::Manual,
+        }
+    }
+}
+
+/// 停止の履歴の 1 件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TripRecord {
+    /// 銘柄。
+    pub symbol_hash: u64,
+    /// 理由。
+    pub reason: HaltReason,
+    /// 停止時刻（ns）。
+    pub halted_ns: u64,
+    /// 停止の終了予定（ns）。`None` は手動で再開するまで。
+    pub until_ns: Option<u64>,
+    /// 手動で再開した時刻（ns）。
+    pub resumed_ns: Option<u64>,
+    /// 停止ではなくプライスカラーの縮小（bps）。伝播の
+    /// [`PropagationAction::TightenCollar`] で記録される。
+    pub collar_bps: Option<u32>,
+}
+
+impl TripRecord {
+    /// `now_ns` の時点で停止中か。
+    #[must_use]
+    pub const fn is_active(&self, now_ns: u64) -> bool {
+        if self.resumed_ns.is_some() {
+            return false;
+        }
+        match self.until_ns {
+            Some(until) => now_ns < until,
+            None => true,
+        }
+    }
+
+    /// `now_ns` の時点までに停止していた時間（ns）。再開・終了予定・`now_ns`
+    /// の早いほうで終わったものとする。
+    #[must_use]
+    pub fn halted_duration_ns(&self, now_ns: u64) -> u64 {
+        let end = [self.resumed_ns, self.until_ns, Some(now_ns)]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(now_ns);
+        end.saturating_sub(self.halted_ns)
+    }
+}
+
+/// 1 銘柄の停止の統計（取引所の品質報告・閾値の調整用）。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HaltStats {
+    /// 停止の回数。
+    pub trips: u32,
+    /// 停止していた時間の合計（ns）。
+    pub total_halted_ns: u64,
+    /// 最長の停止（ns）。
+    pub longest_halt_ns: u64,
+    /// 理由の種類ごとの停止の回数。
+    pub by_kind: BTreeMap<HaltKind, u32>,
+}
+
+impl HaltStats {
+    fn add(&mut self, trip: &TripRecord, now_ns: u64) {
+        let duration = trip.halted_duration_ns(now_ns);
+        self.trips += 1;
+        self.total_halted_ns = self.total_halted_ns.saturating_add(duration);
+        self.longest_halt_ns = self.longest_halt_ns.max(duration);
+        *self.by_kind.entry(trip.reason.kind()).or_insert(0) += 1;
+    }
+}
+
+/// `price` の `base` からの乖離（`base` に対する bps）。`base` が 0 で価格が
+/// 違う場合は `u32::MAX`。
+pub(crate) fn deviation_bps(price: i64, base: i64) -> u32 {
+    let diff = price.abs_diff(base);
+    if diff == 0 {
+        return 0;
+    }
+    let base = base.unsigned_abs();
+    if base == 0 {
+        return u32::MAX;
+    }
+    (u128::from(diff) * 10_000 / u128::from(base)).min(u128::from(u32::MAX)) as u32
+}
+
+// ---------------------------------------------------------------------------
+// MarketWideBreaker
+// ---------------------------------------------------------------------------
+
+/// 15 分（ns）。
+const FIFTEEN_MINUTES_NS: u64 = 15 * 60 * 1_000_000_000;
+
+/// 市場全体のブレーカーの 1 段階。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketWideLevel {
+    /// 日次の基準値からの下落幅（bps）。
+    pub decline_bps: u32,
+    /// 停止時間（ns）。0 はその日の終わりまで。
+    pub halt_ns: u64,
+    /// 取引日の開始からこの時間（ns）以降に発動した場合は停止しない。
+    /// `None` は時刻によらず停止する。
+    pub cutoff_ns: Option<u64>,
+}
+
+impl MarketWideLevel {
+    /// 取引所型の 3 段階。7% と 13% は 15 分停止（取引日の開始から
+    /// `cutoff_ns` 以降は停止しない）、20% はその日の終わりまで停止する。
+    #[must_use]
+    pub const fn exchange_style(cutoff_ns: u64) -> [Self; 3] {
+        [
+            Self {
+                decline_bps: 700,
+                halt_ns: FIFTEEN_MINUTES_NS,
+                cutoff_ns: Some(cutoff_ns),
+            },
+            Self {
+                decline_bps: 1_300,
+                halt_ns: FIFTEEN_MINUTES_NS,
+                cutoff_ns: Some(cutoff_ns),
+            },
+            Self {
+                decline_bps: 2_000,
+                halt_ns: 0,
+                cutoff_ns: None,
+            },
+        ]
+    }
+}
+
+/// 市場全体のブレーカーの発動の 1 件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketWideTrip {
+    /// 発動した段階（1 始まり）。
+    pub level: u8,
+    /// 発動時の指数。
+    pub index: i64,
+    /// 日次の基準値。
+    pub reference: i64,
+    /// 基準値からの下落幅（bps）。
+    pub decline_bps: u32,
+    /// 発動時刻（ns）。
+    pub triggered_ns: u64,
+    /// 停止の終了（ns）。時刻により停止しなかった場合は `None`。
+    pub until_ns: Option<u64>,
+}
+
+impl MarketWideTrip {
+    /// `now_ns` の時点で停止中か。
+    #[must_use]
+    pub const fn is_active(&self, now_ns: u64) -> bool {
+        match self.until_ns {
+            Some(until) => self.triggered_ns <= now_ns && now_ns < until,
+            None => false,
+        }
+    }
+}
+
+/// 市場全体の指数の日次の下落で段階的に発動するブレーカー。
+///
+/// 各段階は 1 日に 1 回だけ発動する。指数が一度に複数の段階を超えた場合は
+/// 最も深い段階だけが発動する。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarketWideBreaker {
+    /// 下落幅の浅い順の段階。
+    levels: Vec<MarketWideLevel>,
+    day_start_ns: u64,
+    reference: Option<i64>,
+    /// その日に発動済みの段階の数。
+    triggered: usize,
+    trips: Vec<MarketWideTrip>,
+}
+
+impl MarketWideBreaker {
+    /// 段階を指定して作成する。段階は下落幅の浅い順に並べ替える。
+    #[must_use]
+    pub fn new(mut levels: Vec<MarketWideLevel>) -> Self {
+        levels.sort_by_key(|l| l.decline_bps);
+        Self {
+            levels,
+            ..Self::default()
+        }
+    }
+
+    /// 取引日を始める。基準値を設定し、発動済みの段階を戻す。履歴は残す。
+    pub fn start_day(&mut self, day_start_ns: u64, reference: i64) {
+        self.day_start_ns = day_start_ns;
+        self.reference = Some(reference);
+        self.triggered = 0;
+    }
+
+    /// 日次の基準値。
+    #[must_use]
+    pub const fn reference(&self) -> Option<i64> {
+        self.reference
+    }
+
+    /// 指数の更新を受け取り、未発動の段階に達したら発動する。基準値が
+    /// 無ければ判定しない。
+    pub fn on_index(&mut self, index: i64, now_ns: u64) -> Option<MarketWideTrip> {
+        let reference = self.reference?;
+        if index >= reference {
+            return None;
+        }
+        let decline_bps = deviation_bps(index, reference);
+        let reached = self
+            .levels
+            .iter()
+            .take_while(|l| decline_bps >= l.decline_bps)
+            .count();
+        if reached <= self.triggered {
+            return None;
+        }
+        self.triggered = reached;
+        let level = self.levels[reached - 1];
+        let elapsed = now_ns.saturating_sub(self.day_start_ns);
+        let until_ns = if level.cutoff_ns.is_some_and(|c| elapsed >= c) {
+            None
+        } else if level.halt_ns == 0 {
+            Some(self.day_start_ns.saturating_add(DAY_NS))
+        } else {
+            Some(now_ns.saturating_add(level.halt_ns))
+        };
+        let trip = MarketWideTrip {
+            level: reached.min(usize::from(u8::MAX)) as u8,
+            index,
+            reference,
+            decline_bps,
+            triggered_ns: now_ns,
+            until_ns,
+        };
+        self.trips.push(trip);
+        Some(trip)
+    }
+
+    /// `now_ns` の時点の停止。停止中でなければ `None`。
+    #[must_use]
+    pub fn halt_at(&self, now_ns: u64) -> Option<&MarketWideTrip> {
+        self.trips.iter().rev().find(|t| t.is_active(now_ns))
+    }
+
+    /// `now_ns` の時点で市場全体が停止中か。
+    #[must_use]
+    pub fn is_halted(&self, now_ns: u64) -> bool {
+        self.halt_at(now_ns).is_some()
+    }
+
+    /// 発動の履歴（発動順）。
+    #[must_use]
+    pub fn trips(&self) -> &[MarketWideTrip] {
+        &self.trips
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HaltRegistry
+// ---------------------------------------------------------------------------
+
+/// 1 銘柄のブレーカーの状態。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SymbolBreaker {
+    config: BreakerConfig,
+    reference: Option<i64>,
+    primary: Option<i64>,
+    secondary: Option<i64>,
+    /// 停止明けの参照価格を決めるまでに集めた価格。`None` は判定中。
+    reopening: Option<Vec<i64>>,
+}
+
+/// 1 グループの設定と参照指数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GroupState {
+    group: BreakerGroup,
+    reference: Option<i64>,
+}
+
+/// 銘柄ごとのブレーカーと停止の履歴。
+#[derive(Debug, Clone, Default)]
+pub struct HaltRegistry {
+    breakers: BTreeMap<u64, SymbolBreaker>,
+    groups: BTreeMap<u64, GroupState>,
+    /// 銘柄 → 主ソースの最新価格（グループの指数の計算用）。
+    last: BTreeMap<u64, i64>,
+    /// 停止元の銘柄 → 伝播の規則。
+    propagation: BTreeMap<u64, Vec<PropagationRule>>,
+    /// 銘柄 → 最後の停止の履歴上の位置。
+    current: BTreeMap<u64, usize>,
+    /// 銘柄 → 最後のカラーの縮小の履歴上の位置。
+    collars: BTreeMap<u64, usize>,
+    trips: Vec<TripRecord>,
+    market_wide: Option<MarketWideBreaker>,
+}
+
+impl HaltRegistry {
+    /// 空の登録簿を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            breakers: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            last: BTreeMap::new(),
+            propagation: BTreeMap::new(),
+            current: BTreeMap::new(),
+            collars: BTreeMap::new(),
+            trips: Vec::new(),
+            market_wide: None,
+        }
+    }
+
+    /// 銘柄のブレーカーを設定する。受け取った価格と参照価格は残す。
+    pub fn configure(&mut self, symbol_hash: u64, config: BreakerConfig) {
+        self.breakers
+            .entry(symbol_hash)
+            .and_modify(|b| b.config = config)
+            .or_insert(SymbolBreaker {
+                config,
+                reference: None,
+                primary: None,
+                secondary: None,
+                reopening: None,
+            });
+    }
+
+    /// 銘柄のブレーカーの設定。
+    #[must_use]
+    pub fn config(&self, symbol_hash: u64) -> Option<&BreakerConfig> {
+        self.breakers.get(&symbol_hash).map(|b| &b.config)
+    }
+
+    /// `Band` の参照価格を設定する。設定されていなければ最初の主ソースの
+    /// 価格が参照価格になる。停止明けの参照価格（例: 再開オークションの
+    /// 約定価格）を待っている場合はそれを終える。
+    pub fn set_reference(&mut self, symbol_hash: u64, price: i64) {
+        if let Some(b) = self.breakers.get_mut(&symbol_hash) {
+            b.reference = Some(price);
+            b.reopening = None;
+        }
+    }
+
+    /// 停止明けの新しい参照価格を待っているか（[`Reopening::Median`]）。
+    #[must_use]
+    pub fn is_reopening(&self, symbol_hash: u64) -> bool {
+        self.breakers
+            .get(&symbol_hash)
+            .is_some_and(|b| b.reopening.is_some())
+    }
+
+    /// `Band` の参照価格。
+    #[must_use]
+    pub fn reference(&self, symbol_hash: u64) -> Option<i64> {
+        self.breakers.get(&symbol_hash)?.reference
+    }
+
+    /// ブレーカーグループを登録する（既存は置き換え、参照指数は消える）。
+    pub fn add_group(&mut self, group_id: u64, group: BreakerGroup) {
+        self.groups.insert(
+            group_id,
+            GroupState {
+                group,
+                reference: None,
+            },
+        );
+    }
+
+    /// グループの参照指数を設定する。設定されていなければ最初に計算できた
+    /// 指数が参照指数になる。
+    pub fn set_group_reference(&mut self, group_id: u64, index: i64) {
+        if let Some(g) = self.groups.get_mut(&group_id) {
+            g.reference = Some(index);
+        }
+    }
+
+    /// グループの現在の指数。
+    #[must_use]
+    pub fn group_index(&self, group_id: u64) -> Option<i64> {
+        let last = &self.last;
+        self.groups
+            .get(&group_id)?
+            .group
+            .index(|s| last.get(&s).copied())
+    }
+
+    /// 価格の更新を受け取り、閾値を超えたら銘柄を停止して理由を返す。
+    ///
+    /// 主ソースの価格は、その銘柄を含むブレーカーグループの指数にも使う。
+    /// 指数が値幅を超えたら構成銘柄をまとめて停止する（既に停止中の構成銘柄は
+    /// その停止のまま）。銘柄のブレーカーとグループの両方が停止した場合は
+    /// 銘柄のブレーカーの理由を返す。
+    ///
+    /// 未設定の銘柄と停止中の銘柄では停止しない（価格は記録する）。
+    /// `Divergence` は両方のソースの価格が揃うまで判定しない。
+    pub fn on_price(
+        &mut self,
+        symbol_hash: u64,
+        source: PriceSource,
+        price: i64,
+        now_ns: u64,
+    ) -> Option<HaltReason> {
+        let symbol = self.symbol_trip(symbol_hash, source, price, now_ns);
+        if source != PriceSource::Primary {
+            return symbol;
+        }
+        self.last.insert(symbol_hash, price);
+        let group = self.group_trip(symbol_hash, now_ns);
+        symbol.or(group)
+    }
+
+    fn symbol_trip(
+        &mut self,
+        symbol_hash: u64,
+        source: PriceSource,
+        price: i64,
+        now_ns: u64,
+    ) -> Option<HaltReason> {
+        let halted = self.is_halted(symbol_hash, now_ns);
+        let b = self.breakers.get_mut(&symbol_hash)?;
+        match source {
+            PriceSource::Primary => b.primary = Some(price),
+            PriceSource::Secondary => b.secondary = Some(price),
+        }
+        let reason = match b.config.mode {
+            BreakerMode::Band { band_bps } => {
+                if source != PriceSource::Primary {
+                    return None;
+                }
+                if let (Some(samples), false) = (&mut b.reopening, halted) {
+                    samples.push(price);
+                    let count = match b.config.reopening {
+                        Reopening::Median { count } => usize::from(count.max(1)),
+                        Reopening::Keep => 1,
+                    };
+                    if samples.len() >= count {
+                        samples.sort_unstable();
+                        b.reference = Some(median(samples));
+                        b.reopening = None;
+                    }
+                    return None;
+                }
+                let reference = *b.reference.get_or_insert(price);
+                let move_bps = deviation_bps(price, reference);
+                (move_bps > band_bps).then_some(HaltReason::Band {
+                    price,
+                    reference,
+                    move_bps,
+                    band_bps,
+                })
+            }
+            BreakerMode::Divergence { max_divergence_bps } => {
+                let (primary, secondary) = (b.primary?, b.secondary?);
+                let divergence_bps = deviation_bps(primary, secondary);
+                (divergence_bps > max_divergence_bps).then_some(HaltReason::Divergence {
+                    primary,
+                    secondary,
+                    divergence_bps,
+                    limit_bps: max_divergence_bps,
+                })
+            }
+        }?;
+        let halt_ns = b.config.halt_ns;
+        if halted {
+            return None;
+        }
+        let until_ns = (halt_ns != 0).then(|| now_ns.saturating_add(halt_ns));
+        self.halt(symbol_hash, reason, now_ns, until_ns);
+        Some(reason)
+    }
+
+    /// `symbol_hash` を含むグループの指数を判定し、値幅を超えたグループの
+    /// 構成銘柄を停止する。最初に停止したグループの理由を返す。
+    fn group_trip(&mut self, symbol_hash: u64, now_ns: u64) -> Option<HaltReason> {
+        let mut tripped = Vec::new();
+        for (&group_id, state) in &mut self.groups {
+            if !state.group.contains(symbol_hash) {
+                continue;
+            }
+            let last = &self.last;
+            let Some(index) = state.group.index(|s| last.get(&s).copied()) else {
+                continue;
+            };
+            let reference = *state.reference.get_or_insert(index);
+            let move_bps = deviation_bps(index, reference);
+            let band_bps = state.group.band_bps;
+            if move_bps > band_bps {
+                let reason = HaltReason::Index {
+                    group_id,
+                    index,
+                    reference,
+                    move_bps,
+                    band_bps,
+                };
+                tripped.push((group_id, reason, state.group.clone()));
+            }
+        }
+        let mut first = None;
+        for (group_id, reason, group) in tripped {
+            if self.group_halted(&group, group_id, now_ns) {
+                continue;
+            }
+            let until_ns = (group.halt_ns != 0).then(|| now_ns.saturating_add(group.halt_ns));
+            for &(member, _) in &group.members {
+                self.halt(member, reason, now_ns, until_ns);
+            }
+            first = first.or(Some(reason));
+        }
+        first
+    }
+
+    /// 構成銘柄のいずれかが `group_id` の停止中か。
+    fn group_halted(&self, group: &BreakerGroup, group_id: u64, now_ns: u64) -> bool {
+        group.members.iter().any(|&(member, _)| {
+            matches!(
+                self.halt_of(member, now_ns).map(|t| t.reason),
+                Some(HaltReason::Index { group_id: g, .. }) if g == group_id
+            )
+        })
+    }
+
+    /// グループの停止で止まっている構成銘柄をまとめて再開し、再開した
+    /// 銘柄数を返す。市場全体の停止中は再開しない。
+    pub fn resume_group(&mut self, group_id: u64, now_ns: u64) -> usize {
+        if self.market_wide_halt(now_ns).is_some() {
+            return 0;
+        }
+        let Some(state) = self.groups.get(&group_id) else {
+            return 0;
+        };
+        let mut resumed = 0;
+        for &(member, _) in &state.group.members {
+            let Some(&index) = self.current.get(&member) else {
+                continue;
+            };
+            let trip = &mut self.trips[index];
+            if trip.is_active(now_ns)
+                && matches!(trip.reason, HaltReason::Index { group_id: g, .. } if g == group_id)
+            {
+                trip.resumed_ns = Some(now_ns);
+                resumed += 1;
+            }
+        }
+        resumed
+    }
+
+    /// 合成参照価格（[`CompositeReference::price`]）を主ソースの価格として
+    /// [`Self::on_price`] に渡す。合成できない銘柄では何もしない。
+    pub fn on_composite(
+        &mut self,
+        symbol_hash: u64,
+        composite: &CompositeReference,
+        now_ns: u64,
+    ) -> Option<HaltReason> {
+        let price = composite.price(symbol_hash)?;
+        self.on_price(symbol_hash, PriceSource::Primary, price, now_ns)
+    }
+
+    /// 市場全体のブレーカーを設定する。
+    pub fn set_market_wide(&mut self, breaker: MarketWideBreaker) {
+        self.market_wide = Some(breaker);
+    }
+
+    /// 市場全体のブレーカー。
+    #[must_use]
+    pub const fn market_wide(&self) -> Option<&MarketWideBreaker> {
+        self.market_wide.as_ref()
+    }
+
+    /// 市場全体のブレーカー（取引日の開始・指数の更新用）。
+    pub fn market_wide_mut(&mut self) -> Option<&mut MarketWideBreaker> {
+        self.market_wide.as_mut()
+    }
+
+    /// `now_ns` の時点の市場全体の停止。
+    #[must_use]
+    pub fn market_wide_halt(&self, now_ns: u64) -> Option<&MarketWideTrip> {
+        self.market_wide.as_ref()?.halt_at(now_ns)
+    }
+
+    /// `source_symbol` の停止を伝播する規則を追加する。
+    pub fn add_propagation(&mut self, source_symbol: u64, rule: PropagationRule) {
+        self.propagation
+            .entry(source_symbol)
+            .or_default()
+            .push(rule);
+    }
+
+    /// 銘柄を停止する。`until_ns` が `None` なら手動で再開するまで。既に
+    /// 停止中なら何もせず `false`。
+    ///
+    /// `Band` の銘柄で [`Reopening::Median`] なら、停止明けに新しい参照価格が
+    /// 決まるまで判定しない。
+    ///
+    /// 伝播の規則があれば、伝播先にも同じ終了予定で停止またはカラーの縮小を
+    /// 掛け、[`HaltReason::Propagated`] として履歴に残す。伝播はさらに先へは
+    /// 伝播しない。
+    pub fn halt(
+        &mut self,
+        symbol_hash: u64,
+        reason: HaltReason,
+        now_ns: u64,
+        until_ns: Option<u64>,
+    ) -> bool {
+        if self.is_halted(symbol_hash, now_ns) {
+            return false;
+        }
+        self.record(symbol_hash, reason, now_ns, until_ns, None);
+        if let Some(b) = self.breakers.get_mut(&symbol_hash) {
+            if matches!(
+                (b.config.mode, b.config.reopening),
+                (BreakerMode::Band { .. }, Reopening::Median { .. })
+            ) {
+                b.reopening = Some(Vec::new());
+            }
+        }
+        if !matches!(reason, HaltReason::Propagated { .. }) {
+            self.propagate(symbol_hash, now_ns, until_ns);
+        }
+        true
+    }
+
+    fn record(
+        &mut self,
+        symbol_hash: u64,
+        reason: HaltReason,
+        now_ns: u64,
+        until_ns: Option<u64>,
+        collar_bps: Option<u32>,
+    ) {
+        let slots = if collar_bps.is_some() {
+            &mut self.collars
+        } else {
+            &mut self.current
+        };
+        slots.insert(symbol_hash, self.trips.len());
+        self.trips.push(TripRecord {
+            symbol_hash,
+            reason,
+            halted_ns: now_ns,
+            until_ns,
+            resumed_ns: None,
+            collar_bps,
+        });
+    }
+
+    fn propagate(&mut self, source_symbol: u64, now_ns: u64, until_ns: Option<u64>) {
+        let Some(rules) = self.propagation.get(&source_symbol).cloned() else {
+            return;
+        };
+        let reason = HaltReason::Propagated { source_symbol };
+        for rule in rules {
+            for target in rule.targets {
+                match rule.action {
+                    PropagationAction::Halt => {
+                        self.halt(target, reason, now_ns, until_ns);
+                    }
+                    PropagationAction::TightenCollar { collar_bps } => {
+                        self.record(target, reason, now_ns, until_ns, Some(collar_bps));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 停止中の銘柄を再開する。停止中でない、グループの停止
+    /// （[`Self::resume_group`] で再開する）、または市場全体の停止中なら
+    /// 何もせず `false`。
+    pub fn resume(&mut self, symbol_hash: u64, now_ns: u64) -> bool {
+        if self.market_wide_halt(now_ns).is_some() {
+            return false;
+        }
+        let Some(&index) = self.current.get(&symbol_hash) else {
+            return false;
+        };
+        let trip = &mut self.trips[index];
+        if !trip.is_active(now_ns) || matches!(trip.reason, HaltReason::Index { .. }) {
+            return false;
+        }
+        trip.resumed_ns = Some(now_ns);
+        true
+    }
+
+    /// `now_ns` の時点の銘柄の停止。停止中でなければ `None`。
+    #[must_use]
+    pub fn halt_of(&self, symbol_hash: u64, now_ns: u64) -> Option<&TripRecord> {
+        let trip = &self.trips[*self.current.get(&symbol_hash)?];
+        trip.is_active(now_ns).then_some(trip)
+    }
+
+    /// `now_ns` の時点で銘柄に掛かっているカラーの縮小（bps）。
+    #[must_use]
+    pub fn collar_of(&self, symbol_hash: u64, now_ns: u64) -> Option<u32> {
+        let trip = &self.trips[*self.collars.get(&symbol_hash)?];
+        trip.is_active(now_ns).then_some(trip.collar_bps).flatten()
+    }
+
+    /// `now_ns` の時点で銘柄が停止中か。
+    #[must_use]
+    pub fn is_halted(&self, symbol_hash: u64, now_ns: u64) -> bool {
+        self.halt_of(symbol_hash, now_ns).is_some()
+    }
+
+    /// 停止の履歴（停止順）。
+    #[must_use]
+    pub fn trips(&self) -> &[TripRecord] {
+        &self.trips
+    }
+
+    /// `since_ns` 以降（例: 取引日の開始）に始まった銘柄の停止の統計。
+    /// カラーの縮小は数えない。続いている停止は `now_ns` までの時間を数える。
+    #[must_use]
+    pub fn stats(&self, symbol_hash: u64, since_ns: u64, now_ns: u64) -> HaltStats {
+        let mut stats = HaltStats::default();
+        for trip in self.halts_since(since_ns) {
+            if trip.symbol_hash == symbol_hash {
+                stats.add(trip, now_ns);
+            }
+        }
+        stats
+    }
+
+    /// `since_ns` 以降に停止した全銘柄の統計。
+    #[must_use]
+    pub fn all_stats(&self, since_ns: u64, now_ns: u64) -> BTreeMap<u64, HaltStats> {
+        let mut all = BTreeMap::<u64, HaltStats>::new();
+        for trip in self.halts_since(since_ns) {
+            all.entry(trip.symbol_hash).or_default().add(trip, now_ns);
+        }
+        all
+    }
+
+    fn halts_since(&self, since_ns: u64) -> impl Iterator<Item = &TripRecord> {
+        self.trips
+            .iter()
+            .filter(move |t| t.collar_bps.is_none() && t.halted_ns >= since_ns)
+    }
+
+    /// 銘柄の注文を判定する。市場全体の停止中は、銘柄の停止が終わって
+    /// いても拒否する。
+    ///
+    /// # Errors
+    ///
+    /// 市場全体の停止中なら [`RiskReject::MarketWideHalt`]、銘柄の停止中なら
+    /// [`RiskReject::SymbolHalted`]。
+    pub fn check(&self, symbol_hash: u64, now_ns: u64) -> Result<(), RiskReject> {
+        if let Some(trip) = self.market_wide_halt(now_ns) {
+            return Err(RiskReject::MarketWideHalt {
+                level: trip.level,
+                until_ns: trip.until_ns.unwrap_or(now_ns),
+            });
+        }
+        match self.halt_of(symbol_hash, now_ns) {
+            Some(trip) => Err(RiskReject::SymbolHalted {
+                symbol_hash,
+                until_ns: trip.until_ns,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 縮小したカラーで指値を判定する。基準は主ソースの最新価格で、価格が
+    /// 無ければ判定しない。成行注文は判定しない。
+    ///
+    /// # Errors
+    ///
+    /// 指値が基準から縮小したカラーを超えて離れていれば
+    /// [`RiskReject::PriceOutsideCollar`]。
+    pub fn check_collar(&self, symbol_hash: u64, order: &Order) -> Result<(), RiskReject> {
+        let Some(limit_bps) = self.collar_of(symbol_hash, order.timestamp_ns) else {
+            return Ok(());
+        };
+        let Some(&reference) = self.last.get(&symbol_hash) else {
+            return Ok(());
+        };
+        if matches!(order.order_type, OrderType::Market) {
+            return Ok(());
+        }
+        let deviation_bps = deviation_bps(order.price, reference);
+        if deviation_bps > limit_bps {
+            return Err(RiskReject::PriceOutsideCollar {
+                price: order.price,
+                reference,
+                deviation_bps,
+                limit_bps,
+            });
+        }
+        Ok(())
+    }
+
+    /// [`Self::check`] と [`Self::check_collar`] を注文の時刻で行ってから
+    /// `checker` で判定する。
+    ///
+    /// # Errors
+    ///
+    /// 市場全体の停止中なら [`RiskReject::MarketWideHalt`]、停止中なら
+    /// [`RiskReject::SymbolHalted`]、カラーの外なら
+    /// [`RiskReject::PriceOutsideCollar`]、それ以外は `checker` の拒否。
+    pub fn check_order(
+        &self,
+        checker: &PreTradeChecker,
+        symbol_hash: u64,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.check(symbol_hash, order.timestamp_ns)?;
+        self.check_collar(symbol_hash, order)?;
+        checker.check_order(order, position)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(timestamp_ns: u64) -> Order {
+        OrderBuilder::bid(100, 1).at(timestamp_ns).build()
+    }
+
+    #[test]
+    fn band_trips_and_expires() {
+        let mut halts = HaltRegistry::new();
+        let config = BreakerConfig {
+            mode: BreakerMode::Band { band_bps: 500 },
+            halt_ns: 100,
+            reopening: Reopening::Keep,
+        };
+        halts.configure(7, config);
+        // 最初の価格が参照価格になる。
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_000, 0), None);
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_050, 1), None);
+        let reason = halts.on_price(7, PriceSource::Primary, 1_060, 2);
+        assert_eq!(
+            reason,
+            Some(HaltReason::Band {
+                price: 1_060,
+                reference: 1_000,
+                move_bps: 600,
+                band_bps: 500,
+            })
+        );
+        // 停止中は重ねて停止しない。
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 2_000, 3), None);
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        assert_eq!(
+            halts.check_order(&checker, 7, &order(50), None),
+            Err(RiskReject::SymbolHalted {
+                symbol_hash: 7,
+                until_ns: Some(102),
+            })
+        );
+        assert!(halts.check_order(&checker, 8, &order(50), None).is_ok());
+        assert!(halts.check_order(&checker, 7, &order(102), None).is_ok());
+        assert_eq!(halts.trips().len(), 1);
+    }
+
+    #[test]
+    fn reopens_at_median_of_first_prices() {
+        let mut halts = HaltRegistry::new();
+        halts.configure(
+            7,
+            BreakerConfig {
+                mode: BreakerMode::Band { band_bps: 500 },
+                halt_ns: 100,
+                reopening: Reopening::Median { count: 3 },
+            },
+        );
+        halts.set_reference(7, 1_000);
+        assert!(halts.on_price(7, PriceSource::Primary, 1_200, 0).is_some());
+        // 停止中の価格は集めない。
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_500, 50), None);
+        assert!(halts.is_reopening(7));
+        // 停止明けの 3 件が揃うまでは判定しない。
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_210, 100), None);
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_190, 101), None);
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_200, 102), None);
+        assert!(!halts.is_reopening(7));
+        assert_eq!(halts.reference(7), Some(1_200));
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_250, 103), None);
+        assert!(halts
+            .on_price(7, PriceSource::Primary, 1_300, 104)
+            .is_some());
+
+        // 再開オークションの約定価格で待ちを終える。
+        assert!(halts.is_reopening(7));
+        halts.set_reference(7, 1_280);
+        assert!(!halts.is_reopening(7));
+        assert_eq!(halts.on_price(7, PriceSource::Primary, 1_290, 300), None);
+    }
+
+    #[test]
+    fn divergence_trips_on_feed_disagreement() {
+        let mut halts = HaltRegistry::new();
+        halts.configure(
+            1,
+            BreakerConfig {
+                mode: BreakerMode::Divergence {
+                    max_divergence_bps: 100,
+                },
+                halt_ns: 0,
+                reopening: Reopening::Keep,
+            },
+        );
+        // 片方だけでは判定しない。
+        assert_eq!(halts.on_price(1, PriceSource::Primary, 5_000, 0), None);
+        assert_eq!(halts.on_price(1, PriceSource::Secondary, 5_040, 1), None);
+        assert_eq!(
+            halts.on_price(1, PriceSource::Primary, 8_000, 2),
+            Some(HaltReason::Divergence {
+                primary: 8_000,
+                secondary: 5_040,
+                divergence_bps: 5_873,
+                limit_bps: 100,
+            })
+        );
+        // 手動で再開するまで止まる。
+        assert!(halts.is_halted(1, u64::MAX));
+        assert!(halts.resume(1, 10));
+        assert!(!halts.is_halted(1, 10));
+        assert!(!halts.resume(1, 11));
+        assert_eq!(halts.trips()[0].resumed_ns, Some(10));
+    }
+
+    #[test]
+    fn group_halts_and_reopens_together() {
+        let mut halts = HaltRegistry::new();
+        halts.add_group(
+            100,
+            BreakerGroup {
+                members: vec![(1, 1), (2, 2), (3, 1)],
+                band_bps: 1_000,
+                halt_ns: 0,
+            },
+        );
+        halts.on_price(1, PriceSource::Primary, 100, 0);
+        halts.on_price(2, PriceSource::Primary, 50, 0);
+        assert_eq!(halts.group_index(100), None);
+        halts.on_price(3, PriceSource::Primary, 100, 0);
+        // 参照指数は 100 + 2 × 50 + 100 = 300。
+        assert_eq!(halts.group_index(100), Some(300));
+        assert_eq!(halts.on_price(1, PriceSource::Primary, 120, 1), None);
+        let reason = HaltReason::Index {
+            group_id: 100,
+            index: 340,
+            reference: 300,
+            move_bps: 1_333,
+            band_bps: 1_000,
+        };
+        assert_eq!(halts.on_price(2, PriceSource::Primary, 60, 2), Some(reason));
+        assert!((1..=3).all(|s| halts.halt_of(s, 3).map(|t| t.reason) == Some(reason)));
+        assert_eq!(halts.on_price(3, PriceSource::Primary, 200, 3), None);
+        // 構成銘柄は個別には再開しない。
+        assert!(!halts.resume(1, 4));
+        assert_eq!(halts.resume_group(100, 5), 3);
+        assert!((1..=3).all(|s| !halts.is_halted(s, 5)));
+        assert_eq!(halts.trips().len(), 3);
+    }
+
+    #[test]
+    fn propagates_halt_and_collar() {
+        let mut halts = HaltRegistry::new();
+        halts.add_propagation(
+            1,
+            PropagationRule {
+                targets: vec![2],
+                action: PropagationAction::Halt,
+            },
+        );
+        halts.add_propagation(
+            1,
+            PropagationRule {
+                targets: vec![3],
+                action: PropagationAction::TightenCollar { collar_bps: 50 },
+            },
+        );
+        halts.on_price(3, PriceSource::Primary, 100, 0);
+        assert!(halts.halt(1, HaltReason::Manual, 10, Some(100)));
+        let propagated = HaltReason::Propagated { source_symbol: 1 };
+        let history: Vec<_> = halts
+            .trips()
+            .iter()
+            .map(|t| (t.symbol_hash, t.reason, t.until_ns, t.collar_bps))
+            .collect();
+        assert_eq!(
+            history,
+            [
+                (1, HaltReason::Manual, Some(100), None),
+                (2, propagated, Some(100), None),
+                (3, propagated, Some(100), Some(50)),
+            ]
+        );
+        assert!(halts.is_halted(2, 50));
+        assert!(!halts.is_halted(3, 50));
+
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let priced = |price, timestamp_ns| Order {
+            price,
+            ..order(timestamp_ns)
+        };
+        assert!(halts
+            .check_order(&checker, 3, &priced(100, 50), None)
+            .is_ok());
+        assert_eq!(
+            halts.check_order(&checker, 3, &priced(101, 50), None),
+            Err(RiskReject::PriceOutsideCollar {
+                price: 101,
+                reference: 100,
+                deviation_bps: 100,
+                limit_bps: 50,
+            })
+        );
+        assert!(halts
+            .check_order(&checker, 3, &priced(101, 100), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn market_wide_levels_block_resumption() {
+        const MINUTE: u64 = 60 * 1_000_000_000;
+        let mut halts = HaltRegistry::new();
+        let mut breaker =
+            MarketWideBreaker::new(MarketWideLevel::exchange_style(360 * MINUTE).into());
+        breaker.start_day(0, 10_000);
+        halts.set_market_wide(breaker);
+        assert!(halts.halt(1, HaltReason::Manual, 0, Some(5 * MINUTE)));
+        let market = halts.market_wide_mut().unwrap();
+        assert_eq!(market.on_index(9_400, MINUTE), None);
+        let level_1 = market.on_index(9_300, 2 * MINUTE).unwrap();
+        assert_eq!((level_1.level, level_1.until_ns), (1, Some(17 * MINUTE)));
+        // 同じ段階は 1 日に 1 回だけ。
+        assert_eq!(market.on_index(9_250, 3 * MINUTE), None);
+
+        // 銘柄の停止が終わっても市場全体の停止中は再開しない。
+        assert_eq!(
+            halts.check(1, 10 * MINUTE),
+            Err(RiskReject::MarketWideHalt {
+                level: 1,
+                until_ns: 17 * MINUTE,
+            })
+        );
+        assert!(halts.check(2, 10 * MINUTE).is_err());
+        assert!(halts.halt(2, HaltReason::Manual, 10 * MINUTE, None));
+        assert!(!halts.resume(2, 11 * MINUTE));
+        assert!(halts.resume(2, 17 * MINUTE));
+        assert!(halts.check(1, 17 * MINUTE).is_ok());
+
+        // 時刻の締切以降は 13% で停止しないが、20% はその日の終わりまで停止する。
+        let market = halts.market_wide_mut().unwrap();
+        let level_2 = market.on_index(8_700, 400 * MINUTE).unwrap();
+        assert_eq!((level_2.level, level_2.until_ns), (2, None));
+        assert!(halts.check(1, 400 * MINUTE).is_ok());
+        let market = halts.market_wide_mut().unwrap();
+        let level_3 = market.on_index(7_900, 410 * MINUTE).unwrap();
+        assert_eq!((level_3.level, level_3.until_ns), (3, Some(DAY_NS)));
+        assert!(halts.check(1, DAY_NS - 1).is_err());
+        assert_eq!(halts.market_wide().map(|m| m.trips().len()), Some(3));
+    }
+
+    #[test]
+    fn stats_by_symbol_and_kind() {
+        let mut halts = HaltRegistry::new();
+        halts.halt(1, HaltReason::Manual, 0, Some(10));
+        halts.halt(1, HaltReason::Manual, 100, Some(150));
+        halts.halt(2, HaltReason::Manual, 100, None);
+        let band = HaltReason::Band {
+            price: 0,
+            reference: 0,
+            move_bps: 0,
+            band_bps: 0,
+        };
+        halts.halt(1, band, 200, None);
+        assert!(halts.resume(1, 230));
+        halts.halt(1, HaltReason::Manual, 300, None);
+
+        let stats = halts.stats(1, 50, 400);
+        assert_eq!(stats.trips, 3);
+        // 50 + 30 + 続いている 100。
+        assert_eq!(stats.total_halted_ns, 180);
+        assert_eq!(stats.longest_halt_ns, 100);
+        assert_eq!(stats.by_kind.get(&HaltKind::Manual), Some(&2));
+        assert_eq!(stats.by_kind.get(&HaltKind::Band), Some(&1));
+        assert_eq!(halts.stats(1, 0, 400).trips, 4);
+        assert_eq!(halts.stats(3, 0, 400), HaltStats::default());
+
+        let all = halts.all_stats(50, 400);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[&2].total_halted_ns, 300);
+    }
+
+    #[test]
+    fn manual_halt() {
+        let mut halts = HaltRegistry::new();
+        assert!(halts.halt(3, HaltReason::Manual, 0, Some(10)));
+        assert!(!halts.halt(3, HaltReason::Manual, 5, None));
+        assert!(halts.check(3, 9).is_err());
+        assert!(halts.halt(3, HaltReason::Manual, 10, None));
+        assert_eq!(halts.halt_of(3, 20).map(|t| t.halted_ns), Some(10));
+        assert_eq!(halts.trips().len(), 2);
+    }
+}
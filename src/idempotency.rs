@@ -0,0 +1,318 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! クライアント注文 ID による判定の冪等化。
+//!
+//! API ゲートウェイは失敗時に同じリクエストを再送する。再送のたびに全チェックを
+//! やり直して受理分のエクスポージャーを二重に予約しないよう、
+//! [`IdempotencyCache`] はクライアント注文 ID ごとに最初の判定を TTL の間
+//! 保持し、再送には元の判定をそのまま返す。
+//!
+//! 判定と一緒に注文の内容（[`OrderFingerprint`]）を保持し、同じ ID で内容の
+//! 違う注文は再送とみなさず [`RiskReject::ConflictingRetry`] で拒否する。
+//! ID を使い回した別の注文に、最初の注文の受理が返らないようにするため。
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{PreTradeChecker, RiskReject};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// IdempotentDecision
+// ---------------------------------------------------------------------------
+
+/// [`IdempotencyCache::check_order`] の結果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotentDecision {
+    /// 判定結果（再送の場合は最初の判定）。
+    pub result: Result<(), RiskReject>,
+    /// TTL 内の再送で、キャッシュした判定を返したか。
+    pub retry: bool,
+}
+
+// ---------------------------------------------------------------------------
+// OrderFingerprint
+// ---------------------------------------------------------------------------
+
+/// 再送か判別するための注文の内容。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderFingerprint {
+    /// 売買の別。
+    pub side: Side,
+    /// 価格。
+    pub price: i64,
+    /// 数量。
+    pub quantity: u64,
+    /// 銘柄。ポジションが無く分からなければ `None`。
+    pub symbol_hash: Option<u64>,
+}
+
+impl OrderFingerprint {
+    /// `position` の銘柄の `order` の内容。
+    #[must_use]
+    pub fn of(order: &Order, position: Option<&Position>) -> Self {
+        Self {
+            side: order.side,
+            price: order.price,
+            quantity: order.quantity,
+            symbol_hash: position.map(|p| p.symbol_hash),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// IdempotencyCache
+// ---------------------------------------------------------------------------
+
+/// クライアント注文 ID → 最初の判定のキャッシュ。
+#[derive(Debug, Clone)]
+pub struct IdempotencyCache {
+    ttl_ns: u64,
+    /// クライアント注文 ID → (判定時刻, 注文の内容, 判定)。
+    decisions: BTreeMap<u64, (u64, OrderFingerprint, Result<(), RiskReject>)>,
+    /// (判定時刻, クライアント注文 ID)。古い順。
+    expiry: VecDeque<(u64, u64)>,
+}
+
+impl IdempotencyCache {
+    /// 判定を `ttl_ns` の間保持するキャッシュを作成。
+    #[must_use]
+    pub const fn new(ttl_ns: u64) -> Self {
+        Self {
+            ttl_ns,
+            decisions: BTreeMap::new(),
+            expiry: VecDeque::new(),
+        }
+    }
+
+    /// 判定の保持期間（ns）。
+    #[must_use]
+    pub const fn ttl_ns(&self) -> u64 {
+        self.ttl_ns
+    }
+
+    /// 保持中の判定数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.decisions.len()
+    }
+
+    /// 保持中の判定が無いか。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty()
+    }
+
+    /// `now_ns` の時点で TTL 内にある `client_order_id` の判定。
+    #[must_use]
+    pub fn get(&self, client_order_id: u64, now_ns: u64) -> Option<&Result<(), RiskReject>> {
+        self.entry(client_order_id, now_ns)
+            .map(|(_, result)| result)
+    }
+
+    /// `now_ns` の時点で TTL 内にある `client_order_id` の注文の内容。
+    #[must_use]
+    pub fn fingerprint(&self, client_order_id: u64, now_ns: u64) -> Option<OrderFingerprint> {
+        self.entry(client_order_id, now_ns)
+            .map(|(fingerprint, _)| *fingerprint)
+    }
+
+    fn entry(
+        &self,
+        client_order_id: u64,
+        now_ns: u64,
+    ) -> Option<(&OrderFingerprint, &Result<(), RiskReject>)> {
+        self.decisions
+            .get(&client_order_id)
+            .filter(|(at, ..)| now_ns.saturating_sub(*at) < self.ttl_ns)
+            .map(|(_, fingerprint, result)| (fingerprint, result))
+    }
+
+    /// 注文の内容と判定を記録する（既存の判定は置き換え）。
+    pub fn insert(
+        &mut self,
+        client_order_id: u64,
+        now_ns: u64,
+        fingerprint: OrderFingerprint,
+        result: Result<(), RiskReject>,
+    ) {
+        self.decisions
+            .insert(client_order_id, (now_ns, fingerprint, result));
+        self.expiry.push_back((now_ns, client_order_id));
+    }
+
+    /// TTL を過ぎた判定を捨てる。
+    pub fn evict(&mut self, now_ns: u64) {
+        while let Some(&(at, id)) = self.expiry.front() {
+            if now_ns.saturating_sub(at) < self.ttl_ns {
+                break;
+            }
+            self.expiry.pop_front();
+            // 再記録された判定は新しい時刻のエントリで管理する。
+            if self.decisions.get(&id).is_some_and(|(t, ..)| *t == at) {
+                self.decisions.remove(&id);
+            }
+        }
+    }
+
+    /// `client_order_id` の注文を判定する。
+    ///
+    /// TTL 内の再送にはキャッシュした判定を返し、チェックも予約もしない。
+    /// TTL 内でも内容（[`OrderFingerprint::of`]）が最初の注文と違えば
+    /// [`RiskReject::ConflictingRetry`] で拒否し、キャッシュは変えない。
+    /// 初回の注文は `checker` で判定し、受理した場合は
+    /// [`PreTradeChecker::on_order_accepted`] でエクスポージャーを予約する。
+    /// 判定時刻は `order.timestamp_ns`。
+    pub fn check_order(
+        &mut self,
+        checker: &mut PreTradeChecker,
+        client_order_id: u64,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> IdempotentDecision {
+        let now = order.timestamp_ns;
+        self.evict(now);
+        let fingerprint = OrderFingerprint::of(order, position);
+        if let Some((cached, result)) = self.entry(client_order_id, now) {
+            if *cached != fingerprint {
+                return IdempotentDecision {
+                    result: Err(RiskReject::ConflictingRetry { client_order_id }),
+                    retry: false,
+                };
+            }
+            return IdempotentDecision {
+                result: result.clone(),
+                retry: true,
+            };
+        }
+        let result = checker.check_order(order, position);
+        if result.is_ok() {
+            checker.on_order_accepted(order);
+        }
+        self.insert(client_order_id, now, fingerprint, result.clone());
+        IdempotentDecision {
+            result,
+            retry: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+
+    fn order(id: u64, quantity: u64, timestamp_ns: u64) -> Order {
+        OrderBuilder::bid(100, quantity)
+            .id(id)
+            .at(timestamp_ns)
+            .build()
+    }
+
+    fn fingerprint(quantity: u64) -> OrderFingerprint {
+        OrderFingerprint::of(&order(1, quantity, 0), None)
+    }
+
+    #[test]
+    fn retries_return_original_decision_without_reserving() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let mut cache = IdempotencyCache::new(1_000);
+        let first = cache.check_order(&mut checker, 7, &order(1, 10, 0), None);
+        assert_eq!(
+            first,
+            IdempotentDecision {
+                result: Ok(()),
+                retry: false,
+            }
+        );
+        let retry = cache.check_order(&mut checker, 7, &order(1, 10, 500), None);
+        assert!(retry.retry && retry.result.is_ok());
+        assert_eq!(checker.open_order_count(), 1);
+
+        // TTL を過ぎた再送は新規の注文として判定する。
+        let late = cache.check_order(&mut checker, 7, &order(2, 10, 1_000), None);
+        assert!(!late.retry);
+        assert_eq!(checker.open_order_count(), 2);
+    }
+
+    #[test]
+    fn reused_id_with_different_order_is_rejected() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let mut cache = IdempotencyCache::new(1_000);
+        assert!(cache
+            .check_order(&mut checker, 7, &order(1, 1, 0), None)
+            .result
+            .is_ok());
+        let conflict = IdempotentDecision {
+            result: Err(RiskReject::ConflictingRetry { client_order_id: 7 }),
+            retry: false,
+        };
+        // 受理した ID で数量を変えても、最初の受理は返らない。
+        assert_eq!(
+            cache.check_order(&mut checker, 7, &order(1, 1_000_000, 10), None),
+            conflict
+        );
+        let held = PositionBuilder::new(42, 0).build();
+        assert_eq!(
+            cache.check_order(&mut checker, 7, &order(1, 1, 20), Some(&held)),
+            conflict
+        );
+        assert_eq!(checker.open_order_count(), 1);
+        assert_eq!(cache.fingerprint(7, 30), Some(fingerprint(1)));
+        // 元の内容の再送は引き続き最初の判定を返す。
+        assert!(
+            cache
+                .check_order(&mut checker, 7, &order(1, 1, 30), None)
+                .retry
+        );
+    }
+
+    #[test]
+    fn rejects_are_cached_too() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        checker.trip_circuit_breaker();
+        let mut cache = IdempotencyCache::new(1_000);
+        cache.check_order(&mut checker, 7, &order(1, 10, 0), None);
+        checker.reset_circuit_breaker();
+        let retry = cache.check_order(&mut checker, 7, &order(1, 10, 10), None);
+        assert_eq!(retry.result, Err(RiskReject::CircuitBreakerTripped));
+        assert!(cache
+            .check_order(&mut checker, 8, &order(2, 10, 10), None)
+            .result
+            .is_ok());
+    }
+
+    #[test]
+    fn eviction() {
+        let mut cache = IdempotencyCache::new(100);
+        cache.insert(1, 0, fingerprint(1), Ok(()));
+        cache.insert(2, 50, fingerprint(1), Ok(()));
+        cache.insert(
+            1,
+            60,
+            fingerprint(2),
+            Err(RiskReject::CircuitBreakerTripped),
+        );
+        cache.evict(120);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.get(1, 120),
+            Some(&Err(RiskReject::CircuitBreakerTripped))
+        );
+        assert_eq!(cache.fingerprint(1, 120), Some(fingerprint(2)));
+        assert!(cache.get(2, 150).is_none());
+        cache.evict(200);
+        assert!(cache.is_empty());
+        assert_eq!(cache.ttl_ns(), 100);
+    }
+}
@@ -0,0 +1,220 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 先頭 `N` 件をインラインに持つ可変長リスト。
+//!
+//! 違反・警告の一覧はほとんどが 0〜2 件なので、[`InlineVec`] は `N` 件までを
+//! 値の中に保持し、それを超えたときにだけヒープへ移す。`unsafe` を使わない
+//! よう、インライン部は `Option<T>` の配列で持つ。
+
+use alloc::vec::Vec;
+use core::fmt;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// InlineVec
+// ---------------------------------------------------------------------------
+
+/// `N` 件までヒープを使わない可変長リスト。
+#[derive(Clone)]
+pub struct InlineVec<T, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+#[derive(Clone)]
+enum Repr<T, const N: usize> {
+    Inline { len: usize, items: [Option<T>; N] },
+    Heap(Vec<T>),
+}
+
+impl<T, const N: usize> InlineVec<T, N> {
+    /// 空のリストを作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            repr: Repr::Inline {
+                len: 0,
+                items: [const { None }; N],
+            },
+        }
+    }
+
+    /// 末尾に追加する。`N` 件を超えるとヒープへ移す。
+    pub fn push(&mut self, value: T) {
+        match &mut self.repr {
+            Repr::Inline { len, items } if *len < N => {
+                items[*len] = Some(value);
+                *len += 1;
+            }
+            Repr::Inline { items, .. } => {
+                let mut heap = Vec::with_capacity(N * 2 + 1);
+                heap.extend(items.iter_mut().filter_map(Option::take));
+                heap.push(value);
+                self.repr = Repr::Heap(heap);
+            }
+            Repr::Heap(heap) => heap.push(value),
+        }
+    }
+
+    /// 件数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { len, .. } => *len,
+            Repr::Heap(heap) => heap.len(),
+        }
+    }
+
+    /// 空か。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// ヒープへ移っているか。
+    #[must_use]
+    pub const fn spilled(&self) -> bool {
+        matches!(self.repr, Repr::Heap(_))
+    }
+
+    /// `index` 番目の要素。
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match &self.repr {
+            Repr::Inline { items, .. } => items.get(index)?.as_ref(),
+            Repr::Heap(heap) => heap.get(index),
+        }
+    }
+
+    /// 先頭の要素。
+    #[must_use]
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// 要素を先頭から順に返す。
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            list: self,
+            index: 0,
+        }
+    }
+
+    /// `Vec` に変換する。
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        match self.repr {
+            Repr::Inline { items, .. } => items.into_iter().flatten().collect(),
+            Repr::Heap(heap) => heap,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for InlineVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for InlineVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for InlineVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for InlineVec<T, N> {}
+
+impl<T, const N: usize> Extend<T> for InlineVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for InlineVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a InlineVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// [`InlineVec::iter`] の反復子。
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T, const N: usize> {
+    list: &'a InlineVec<T, N>,
+    index: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.list.get(self.index)?;
+        self.index += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.list.len() - self.index;
+        (rest, Some(rest))
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Iter<'_, T, N> {}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_inline_up_to_capacity() {
+        let mut list: InlineVec<u32, 2> = InlineVec::new();
+        assert!(list.is_empty());
+        list.push(1);
+        list.push(2);
+        assert!(!list.spilled());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(list.get(2), None);
+        list.push(3);
+        assert!(list.spilled());
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.first(), Some(&1));
+        assert_eq!(list.into_vec(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn equality_ignores_storage() {
+        let inline: InlineVec<u32, 4> = (0..3).collect();
+        let mut spilled: InlineVec<u32, 4> = (0..5).collect();
+        assert!(spilled.spilled());
+        assert_ne!(inline, spilled);
+        spilled = (0..3).collect();
+        assert_eq!(inline, spilled);
+        assert_eq!(format!("{inline:?}"), "[0, 1, 2]");
+        assert_eq!(inline.iter().len(), 3);
+    }
+}
@@ -0,0 +1,794 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 銘柄（限月）の満期メタデータと満期前の取引制限。
+//!
+//! 満期が近づいた限月では、残り日数が
+//! [`RiskLimits::expiry_no_open_days`](crate::RiskLimits::expiry_no_open_days)
+//! 以内になると新規建て（フラットからの建玉・ドテン）を禁止し、最終取引
+//! セッションに入ると建玉を減らす注文だけを許可する。判定は
+//! [`PreTradeChecker::check_instrument_order`](crate::PreTradeChecker::check_instrument_order)
+//! で行う。
+//!
+//! 現物受渡しのある先物は、初回通知日（first notice day）の
+//! [`RiskLimits::spot_month_days_before_notice`](crate::RiskLimits::spot_month_days_before_notice)
+//! 日前から、通常より厳しい当限（spot month）建玉上限を自動的に適用する。
+//! 誤って受渡しを受けることは致命的な運用事故であるため。
+//!
+//! 期間構造のある商品は、満期までの残存期間で [`MaturityBucket`]（期近・期先・
+//! 1 年超）に分類し、同一商品のネット建玉をバケットごとに上限管理する。
+//! 同じバケット内のカレンダースプレッドは相殺されるため大きなスプレッド
+//! ブックを許容しつつ、単一バケットのアウトライト建玉は抑えられる。
+//!
+//! 暗号資産や FX のように 1e-8 ロットまで端数のある銘柄は
+//! [`Instrument::quantity_scale`] に小数桁数を持たせ、数量を
+//! `10^-quantity_scale` ロット単位の整数（スケール済み単位）で扱う。
+//! [`Instrument::scale_limits`] でロット建ての制限をスケール済み単位へ揃えれば、
+//! `max_position`・`max_order_size` や想定元本の判定はそのまま整合し、
+//! 証拠金は [`MarginCalculator::instrument_initial_margin`](crate::MarginCalculator::instrument_initial_margin)
+//! でロット換算して計算する。[`InstrumentRegistry`] は銘柄ごとの定義を保持し、
+//! 変換を銘柄単位で引き当てる。
+//!
+//! 価格は [`Instrument::price_scale`] 桁の整数 ticks で、金額（想定元本・
+//! 証拠金・損益）は同じ精度で表す。[`Instrument::multiplier`]（取引単位倍率）は
+//! 想定元本・証拠金・損益のすべてに掛かり、乗数 50 倍の指数先物が 1 倍の銘柄と
+//! 同じ扱いにならないよう、[`Instrument::scale_limits`] は金額の上限を乗数で
+//! 割ってエンジン内の `価格 × 数量` に揃える。
+//!
+//! 証拠金は価格の通貨（決済通貨）で計算されるが、保有する通貨は銘柄ごとに
+//! [`MarginCurrency`] で決済通貨・基軸通貨・口座の基準通貨から選べる。換算は
+//! [`fx`](crate::fx) モジュールで行う。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Side};
+
+use crate::fixed::{pow10, rescale_raw, Fixed};
+use crate::limit::RiskLimits;
+
+extern crate alloc;
+
+/// 1 日の長さ（ns）。
+pub const DAY_NS: u64 = 86_400_000_000_000;
+
+/// 期先と 1 年超を分ける残存日数。
+pub const LONG_DATED_DAYS: u64 = 365;
+
+// ---------------------------------------------------------------------------
+// Instrument
+// ---------------------------------------------------------------------------
+
+/// 銘柄の満期メタデータ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instrument {
+    /// 銘柄（`Position::symbol_hash`）。
+    pub symbol_hash: u64,
+    /// 商品（限月をまたいで共通）。既定は `symbol_hash` と同じ。
+    pub product_hash: u64,
+    /// 原資産の現物銘柄（`symbol_hash`）。デリバティブのみ `Some`。
+    pub underlying_hash: Option<u64>,
+    /// 満期時刻（ns）。満期の無い銘柄は `None`。
+    pub expiry_ns: Option<u64>,
+    /// 最終取引セッションの開始時刻（ns）。
+    pub final_session_start_ns: Option<u64>,
+    /// 初回通知日（ns）。現物受渡しのある限月のみ `Some`。
+    pub first_notice_ns: Option<u64>,
+    /// 数量の小数桁数。数量 1 単位は `10^-quantity_scale` ロット。既定は 0。
+    pub quantity_scale: u32,
+    /// 価格の小数桁数。価格 1 tick は `10^-price_scale` 通貨単位。既定は 0。
+    pub price_scale: u32,
+    /// 取引単位倍率（1 ロットあたりの原資産数量）。既定は 1。
+    pub multiplier: u64,
+    /// 基軸通貨（USD/JPY の USD）。既定は 0。
+    pub base_currency: u64,
+    /// 決済通貨（USD/JPY の JPY）。価格・証拠金の計算はこの通貨。既定は 0。
+    pub quote_currency: u64,
+    /// 証拠金を保有する通貨。既定は [`MarginCurrency::Quote`]。
+    pub margin_currency: MarginCurrency,
+}
+
+impl Instrument {
+    /// 満期の無い銘柄。
+    #[must_use]
+    pub const fn perpetual(symbol_hash: u64) -> Self {
+        Self {
+            symbol_hash,
+            product_hash: symbol_hash,
+            underlying_hash: None,
+            expiry_ns: None,
+            final_session_start_ns: None,
+            first_notice_ns: None,
+            quantity_scale: 0,
+            price_scale: 0,
+            multiplier: 1,
+            base_currency: 0,
+            quote_currency: 0,
+            margin_currency: MarginCurrency::Quote,
+        }
+    }
+
+    /// 満期付きの銘柄。
+    #[must_use]
+    pub const fn expiring(symbol_hash: u64, expiry_ns: u64, final_session_start_ns: u64) -> Self {
+        Self {
+            symbol_hash,
+            product_hash: symbol_hash,
+            underlying_hash: None,
+            expiry_ns: Some(expiry_ns),
+            final_session_start_ns: Some(final_session_start_ns),
+            first_notice_ns: None,
+            quantity_scale: 0,
+            price_scale: 0,
+            multiplier: 1,
+            base_currency: 0,
+            quote_currency: 0,
+            margin_currency: MarginCurrency::Quote,
+        }
+    }
+
+    /// 商品を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn of_product(mut self, product_hash: u64) -> Self {
+        self.product_hash = product_hash;
+        self
+    }
+
+    /// 原資産の現物銘柄を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn on_underlying(mut self, underlying_hash: u64) -> Self {
+        self.underlying_hash = Some(underlying_hash);
+        self
+    }
+
+    /// `now_ns` 時点の残存期間によるバケット。
+    ///
+    /// 満期の無い銘柄と満期を過ぎた銘柄は期近に分類する。
+    #[must_use]
+    pub const fn bucket_at(&self, now_ns: u64, front_month_days: u32) -> MaturityBucket {
+        let Some(expiry_ns) = self.expiry_ns else {
+            return MaturityBucket::Front;
+        };
+        let remaining = expiry_ns.saturating_sub(now_ns);
+        if remaining <= (front_month_days as u64).saturating_mul(DAY_NS) {
+            MaturityBucket::Front
+        } else if remaining <= LONG_DATED_DAYS * DAY_NS {
+            MaturityBucket::Back
+        } else {
+            MaturityBucket::Long
+        }
+    }
+
+    /// 現物受渡しの初回通知日を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn deliverable(mut self, first_notice_ns: u64) -> Self {
+        self.first_notice_ns = Some(first_notice_ns);
+        self
+    }
+
+    /// `now_ns` が初回通知日の `lead_ns` 前以降（当限建玉上限の適用期間）か。
+    ///
+    /// 現物受渡しの無い銘柄は常に `false`。
+    #[must_use]
+    pub const fn in_spot_month(&self, now_ns: u64, lead_ns: u64) -> bool {
+        match self.first_notice_ns {
+            Some(notice) => now_ns >= notice.saturating_sub(lead_ns),
+            None => false,
+        }
+    }
+
+    /// 数量の小数桁数を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_quantity_scale(mut self, quantity_scale: u32) -> Self {
+        self.quantity_scale = quantity_scale;
+        self
+    }
+
+    /// 価格の小数桁数を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_price_scale(mut self, price_scale: u32) -> Self {
+        self.price_scale = price_scale;
+        self
+    }
+
+    /// 取引単位倍率を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_multiplier(mut self, multiplier: u64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// 基軸通貨と決済通貨を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_currencies(mut self, base_currency: u64, quote_currency: u64) -> Self {
+        self.base_currency = base_currency;
+        self.quote_currency = quote_currency;
+        self
+    }
+
+    /// 証拠金を保有する通貨を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_margin_currency(mut self, margin_currency: MarginCurrency) -> Self {
+        self.margin_currency = margin_currency;
+        self
+    }
+
+    /// 口座の基準通貨が `account_currency` のときに証拠金を保有する通貨。
+    #[must_use]
+    pub const fn margin_currency_hash(&self, account_currency: u64) -> u64 {
+        match self.margin_currency {
+            MarginCurrency::Quote => self.quote_currency,
+            MarginCurrency::Base => self.base_currency,
+            MarginCurrency::Account => account_currency,
+        }
+    }
+
+    /// 1 ロットあたりのスケール済み単位数（`10^quantity_scale`）。
+    ///
+    /// `u64` に収まらない桁数では `None`。
+    #[must_use]
+    pub const fn lot_size(&self) -> Option<u64> {
+        10_u64.checked_pow(self.quantity_scale)
+    }
+
+    /// 整数ロットをスケール済み単位に変換する。オーバーフロー時は `None`。
+    #[must_use]
+    pub const fn lots_to_units(&self, lots: u64) -> Option<u64> {
+        match self.lot_size() {
+            Some(lot) => lots.checked_mul(lot),
+            None => None,
+        }
+    }
+
+    /// 固定小数点のロット数をスケール済み単位に変換する。
+    ///
+    /// `quantity_scale` 桁未満の端数を含む、またはオーバーフローする場合は
+    /// `None`（丸めない）。
+    #[must_use]
+    pub const fn units_from_fixed<const SCALE: u32>(&self, lots: Fixed<SCALE>) -> Option<i64> {
+        let raw = lots.raw();
+        if self.quantity_scale >= SCALE {
+            match pow10(self.quantity_scale - SCALE) {
+                Some(factor) => raw.checked_mul(factor),
+                None => None,
+            }
+        } else {
+            match pow10(SCALE - self.quantity_scale) {
+                Some(factor) if raw % factor == 0 => Some(raw / factor),
+                // 桁が `i64` を超える分母で割り切れるのは 0 だけ。
+                None if raw == 0 => Some(0),
+                _ => None,
+            }
+        }
+    }
+
+    /// スケール済み単位を固定小数点のロット数に変換する。
+    ///
+    /// `SCALE` 桁で表せない端数が残る、またはオーバーフローする場合は `None`。
+    #[must_use]
+    pub const fn units_to_fixed<const SCALE: u32>(&self, units: i64) -> Option<Fixed<SCALE>> {
+        if SCALE >= self.quantity_scale {
+            match pow10(SCALE - self.quantity_scale) {
+                Some(factor) => match units.checked_mul(factor) {
+                    Some(raw) => Some(Fixed::from_raw(raw)),
+                    None => None,
+                },
+                None => None,
+            }
+        } else {
+            match pow10(self.quantity_scale - SCALE) {
+                Some(factor) if units % factor == 0 => Some(Fixed::from_raw(units / factor)),
+                None if units == 0 => Some(Fixed::ZERO),
+                _ => None,
+            }
+        }
+    }
+
+    /// 固定小数点の価格を ticks に変換する。端数が落ちる場合は `None`。
+    #[must_use]
+    pub fn price_from_fixed<const SCALE: u32>(&self, price: Fixed<SCALE>) -> Option<i64> {
+        rescale_raw(i128::from(price.raw()), SCALE, self.price_scale)
+            .and_then(|raw| i64::try_from(raw).ok())
+    }
+
+    /// ticks を固定小数点の価格に変換する。端数が落ちる場合は `None`。
+    ///
+    /// 金額（想定元本・証拠金・損益）も同じ精度なので、銘柄をまたいで
+    /// 合算する前の共通スケールへの変換にも使える。
+    #[must_use]
+    pub fn price_to_fixed<const SCALE: u32>(&self, ticks: i64) -> Option<Fixed<SCALE>> {
+        rescale_raw(i128::from(ticks), self.price_scale, SCALE)
+            .and_then(|raw| i64::try_from(raw).ok())
+            .map(Fixed::from_raw)
+    }
+
+    /// `price`（1 ロットあたり）で `units` を評価した想定元本の絶対値。
+    ///
+    /// `|price| * units * multiplier / 10^quantity_scale`（端数切り捨て、
+    /// `i64` の範囲に丸める）。
+    #[must_use]
+    pub fn notional(&self, price: i64, units: u64) -> i64 {
+        let gross = i128::from(price).abs().saturating_mul(i128::from(units));
+        self.value(gross, 1).min(i64::MAX as i128) as i64
+    }
+
+    /// 固定小数点の価格（1 ロットあたり）とロット数で評価した想定元本の絶対値。
+    ///
+    /// `|price| * lots * multiplier` を [`Fixed::checked_mul`] で厳密に計算し、
+    /// `OUT` 桁へ無損失で表せる場合のみ `Some` を返す（丸めない）。
+    #[must_use]
+    pub fn notional_fixed<const P: u32, const Q: u32, const OUT: u32>(
+        &self,
+        price: Fixed<P>,
+        lots: Fixed<Q>,
+    ) -> Option<Fixed<OUT>> {
+        let multiplier = Fixed::<0>::from_int(i64::try_from(self.multiplier).ok()?)?;
+        let per_lot: Fixed<P> = price.checked_abs()?.checked_mul(multiplier)?;
+        per_lot.checked_mul(lots.checked_abs()?)
+    }
+
+    /// `entry_price` から `mark_price` までの、ネット建玉 `net_units` の損益。
+    ///
+    /// `(mark - entry) * net_units * multiplier / 10^quantity_scale`
+    /// （0 方向に切り捨て、`i64` の範囲に丸める）。
+    #[must_use]
+    pub fn pnl(&self, entry_price: i64, mark_price: i64, net_units: i64) -> i64 {
+        let gross = (i128::from(mark_price) - i128::from(entry_price))
+            .saturating_mul(i128::from(net_units));
+        self.value(gross, 1)
+            .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+
+    /// `価格 × スケール済み単位` の `gross` を金額に換算し、さらに `divisor` で割る。
+    ///
+    /// `gross * multiplier / (10^quantity_scale * divisor)`（0 方向に切り捨て）。
+    pub(crate) fn value(&self, gross: i128, divisor: i128) -> i128 {
+        let lot = 10_i128
+            .checked_pow(self.quantity_scale)
+            .unwrap_or(i128::MAX);
+        gross.saturating_mul(i128::from(self.multiplier)) / lot.saturating_mul(divisor)
+    }
+
+    /// ロット建ての `limits` をスケール済み単位に揃える。
+    ///
+    /// 数量の上限（`max_position`・`max_order_size`・`min_display_quantity`・
+    /// 当限と満期バケットの建玉上限）は `10^quantity_scale` 倍する。注文の
+    /// 想定元本はエンジン内で `価格 × スケール済み単位` として計算され乗数を
+    /// 含まないため、`max_notional`・`max_open_order_notional` には
+    /// `10^quantity_scale / multiplier` を掛ける（切り捨てで、上限は厳しい側に
+    /// 寄る）。損失上限は呼び出し側が [`Self::pnl`] で換算した金額と比べるので
+    /// そのまま。いずれも飽和演算で、`u64::MAX`・`i64::MAX` などの無効化値は
+    /// 無効のまま残る。
+    ///
+    /// 想定元本の合計は銘柄をまたいで積算されるため、同じチェッカーで扱う
+    /// 銘柄は同じ `quantity_scale`・`price_scale`・`multiplier` であること。
+    #[must_use]
+    pub fn scale_limits(&self, limits: &RiskLimits) -> RiskLimits {
+        let lot = self.lot_size().unwrap_or(u64::MAX);
+        RiskLimits {
+            max_position: limits.max_position.saturating_mul(lot),
+            max_order_size: limits.max_order_size.saturating_mul(lot),
+            max_notional: self.scale_money(limits.max_notional),
+            max_open_order_notional: self.scale_money(limits.max_open_order_notional),
+            min_display_quantity: limits.min_display_quantity.saturating_mul(lot),
+            spot_month_position_limit: limits.spot_month_position_limit.saturating_mul(lot),
+            max_front_month_position: limits.max_front_month_position.saturating_mul(lot),
+            max_back_month_position: limits.max_back_month_position.saturating_mul(lot),
+            max_long_dated_position: limits.max_long_dated_position.saturating_mul(lot),
+            ..limits.clone()
+        }
+    }
+
+    /// 金額の上限を `価格 × スケール済み単位` の尺度に換算する。
+    fn scale_money(&self, limit: i64) -> i64 {
+        if limit == i64::MAX {
+            return limit;
+        }
+        let lot = 10_i128
+            .checked_pow(self.quantity_scale)
+            .unwrap_or(i128::MAX);
+        (i128::from(limit).saturating_mul(lot) / i128::from(self.multiplier.max(1)))
+            .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+
+    /// `now_ns` に適用される満期制限。
+    ///
+    /// 最終セッション（または満期後）は [`ExpiryRestriction::ReduceOnly`]、
+    /// 満期まで `no_open_ns` 以内なら [`ExpiryRestriction::NoNewPositions`]。
+    #[must_use]
+    pub const fn restriction_at(&self, now_ns: u64, no_open_ns: u64) -> Option<ExpiryRestriction> {
+        let Some(expiry_ns) = self.expiry_ns else {
+            return None;
+        };
+        let final_session = match self.final_session_start_ns {
+            Some(start) => now_ns >= start,
+            None => false,
+        };
+        if final_session || now_ns >= expiry_ns {
+            Some(ExpiryRestriction::ReduceOnly)
+        } else if expiry_ns - now_ns <= no_open_ns {
+            Some(ExpiryRestriction::NoNewPositions)
+        } else {
+            None
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MarginCurrency
+// ---------------------------------------------------------------------------
+
+/// 証拠金を計算・保有する通貨。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarginCurrency {
+    /// 決済通貨（価格の通貨）。換算しない。
+    Quote,
+    /// 基軸通貨。
+    Base,
+    /// 口座の基準通貨。
+    Account,
+}
+
+// ---------------------------------------------------------------------------
+// InstrumentRegistry
+// ---------------------------------------------------------------------------
+
+/// 銘柄定義の登録簿。
+///
+/// 数量変換を銘柄（`symbol_hash`）で引き当てる。未登録の銘柄はいずれも `None`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstrumentRegistry {
+    instruments: BTreeMap<u64, Instrument>,
+}
+
+impl InstrumentRegistry {
+    /// 空の登録簿を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            instruments: BTreeMap::new(),
+        }
+    }
+
+    /// 銘柄を登録する。同じ銘柄の既存定義があれば置き換えて返す。
+    pub fn insert(&mut self, instrument: Instrument) -> Option<Instrument> {
+        self.instruments.insert(instrument.symbol_hash, instrument)
+    }
+
+    /// 銘柄の登録を外す。
+    pub fn remove(&mut self, symbol_hash: u64) -> Option<Instrument> {
+        self.instruments.remove(&symbol_hash)
+    }
+
+    /// 銘柄の定義。
+    #[must_use]
+    pub fn get(&self, symbol_hash: u64) -> Option<&Instrument> {
+        self.instruments.get(&symbol_hash)
+    }
+
+    /// 登録数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    /// 空か。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+
+    /// 登録済みの銘柄（`symbol_hash` 順）。
+    pub fn iter(&self) -> impl Iterator<Item = &Instrument> {
+        self.instruments.values()
+    }
+
+    /// 銘柄の数量の小数桁数。
+    #[must_use]
+    pub fn quantity_scale(&self, symbol_hash: u64) -> Option<u32> {
+        self.get(symbol_hash).map(|i| i.quantity_scale)
+    }
+
+    /// 固定小数点のロット数を銘柄のスケール済み単位に変換する。
+    ///
+    /// 未登録、端数が落ちる、またはオーバーフローする場合は `None`。
+    #[must_use]
+    pub fn to_units<const SCALE: u32>(&self, symbol_hash: u64, lots: Fixed<SCALE>) -> Option<i64> {
+        self.get(symbol_hash)?.units_from_fixed(lots)
+    }
+
+    /// 銘柄のスケール済み単位を固定小数点のロット数に変換する。
+    #[must_use]
+    pub fn to_lots<const SCALE: u32>(&self, symbol_hash: u64, units: i64) -> Option<Fixed<SCALE>> {
+        self.get(symbol_hash)?.units_to_fixed(units)
+    }
+
+    /// ロット建ての `limits` を銘柄のスケール済み単位に揃える
+    /// （[`Instrument::scale_limits`]）。
+    #[must_use]
+    pub fn scale_limits(&self, symbol_hash: u64, limits: &RiskLimits) -> Option<RiskLimits> {
+        Some(self.get(symbol_hash)?.scale_limits(limits))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MaturityBucket
+// ---------------------------------------------------------------------------
+
+/// 残存期間によるバケット。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MaturityBucket {
+    /// 期近（残存が `front_month_days` 以内）。
+    Front,
+    /// 期先（1 年以内）。
+    Back,
+    /// 1 年超。
+    Long,
+}
+
+impl MaturityBucket {
+    /// 全バケット（期近から順）。
+    pub const ALL: [Self; 3] = [Self::Front, Self::Back, Self::Long];
+
+    /// [`Self::ALL`] 内の位置。
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// `product_hash` のネット建玉をバケット別に合計する（[`MaturityBucket::ALL`] 順）。
+///
+/// `holdings` は（銘柄, ネット建玉）の組。他商品の銘柄は無視する。
+#[must_use]
+pub fn bucket_exposure(
+    product_hash: u64,
+    holdings: &[(Instrument, i64)],
+    now_ns: u64,
+    front_month_days: u32,
+) -> [i64; 3] {
+    let mut exposure = [0_i64; 3];
+    for (instrument, net) in holdings {
+        if instrument.product_hash == product_hash {
+            let slot = &mut exposure[instrument.bucket_at(now_ns, front_month_days).index()];
+            *slot = slot.saturating_add(*net);
+        }
+    }
+    exposure
+}
+
+// ---------------------------------------------------------------------------
+// ExpiryRestriction
+// ---------------------------------------------------------------------------
+
+/// 満期前に課される取引制限。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryRestriction {
+    /// フラットからの新規建てとドテンを禁止（既存建玉への追加・手仕舞いは可）。
+    NoNewPositions,
+    /// 建玉を減らす注文のみ許可（建玉数量を超える反対売買も不可）。
+    ReduceOnly,
+}
+
+impl ExpiryRestriction {
+    /// ネット建玉 `net` に対する `order` を許可するか。
+    #[must_use]
+    pub const fn permits(self, order: &Order, net: i64) -> bool {
+        let reduces = match order.side {
+            Side::Bid => net < 0,
+            Side::Ask => net > 0,
+        };
+        let within = order.quantity <= net.unsigned_abs();
+        match self {
+            Self::NoNewPositions => net != 0 && (!reduces || within),
+            Self::ReduceOnly => reduces && within,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity).build()
+    }
+
+    #[test]
+    fn quantity_scale_conversions() {
+        let btc = Instrument::perpetual(1).with_quantity_scale(8);
+        assert_eq!(btc.lot_size(), Some(100_000_000));
+        assert_eq!(btc.lots_to_units(3), Some(300_000_000));
+        let lots = Fixed::<4>::parse("0.0015").unwrap();
+        assert_eq!(btc.units_from_fixed(lots), Some(150_000));
+        assert_eq!(btc.units_to_fixed::<4>(150_000), Some(lots));
+        // 4 桁に収まらない端数は丸めない。
+        assert_eq!(btc.units_to_fixed::<4>(1), None);
+        assert_eq!(btc.units_to_fixed::<8>(1), Fixed::<8>::parse("0.00000001"));
+        let lot = Instrument::perpetual(2);
+        assert_eq!(
+            lot.units_from_fixed(Fixed::<2>::parse("1.50").unwrap()),
+            None
+        );
+        assert_eq!(
+            lot.units_from_fixed(Fixed::<2>::parse("2").unwrap()),
+            Some(2)
+        );
+        // 0.5 BTC を 1 BTC あたり 60_000 で評価。
+        assert_eq!(btc.notional(-60_000, 50_000_000), 30_000);
+        assert_eq!(
+            Instrument::perpetual(3).with_quantity_scale(40).lot_size(),
+            None
+        );
+    }
+
+    #[test]
+    fn scaled_limits_keep_disabled_values() {
+        let btc = Instrument::perpetual(1).with_quantity_scale(8);
+        let limits = RiskLimits {
+            max_long_dated_position: u64::MAX,
+            ..RiskLimits::default()
+        };
+        let scaled = btc.scale_limits(&limits);
+        assert_eq!(scaled.max_position, 1000 * 100_000_000);
+        assert_eq!(scaled.max_order_size, 100 * 100_000_000);
+        assert_eq!(scaled.max_notional, 100_000_000 * 100_000_000);
+        assert_eq!(scaled.max_long_dated_position, u64::MAX);
+        assert_eq!(scaled.max_daily_loss, limits.max_daily_loss);
+        assert_eq!(Instrument::perpetual(2).scale_limits(&limits), limits);
+    }
+
+    #[test]
+    fn multiplier_and_price_scale() {
+        // 乗数 50 倍、価格は小数 2 桁の指数先物。
+        let es = Instrument::perpetual(1)
+            .with_multiplier(50)
+            .with_price_scale(2);
+        let price = es.price_from_fixed(Fixed::<4>::parse("5000.25").unwrap());
+        assert_eq!(price, Some(500_025));
+        assert_eq!(
+            es.price_from_fixed(Fixed::<4>::parse("0.001").unwrap()),
+            None
+        );
+        assert_eq!(es.notional(500_025, 2), 50_002_500);
+        assert_eq!(
+            es.price_to_fixed::<2>(50_002_500),
+            Fixed::<2>::parse("500025")
+        );
+        assert_eq!(
+            es.notional_fixed(
+                Fixed::<4>::parse("5000.25").unwrap(),
+                Fixed::<0>::from_raw(-2)
+            ),
+            Fixed::<2>::parse("500025")
+        );
+        assert_eq!(
+            es.notional_fixed::<4, 0, 2>(
+                Fixed::<4>::parse("5000.1251").unwrap(),
+                Fixed::from_raw(1)
+            ),
+            None
+        );
+        assert_eq!(es.pnl(500_000, 499_000, -3), 150_000);
+        assert_eq!(es.pnl(500_000, 499_000, 3), -150_000);
+
+        let scaled = es.scale_limits(&RiskLimits::default());
+        assert_eq!(scaled.max_notional, 100_000_000 / 50);
+        assert_eq!(scaled.max_position, 1000);
+        let open = RiskLimits {
+            max_notional: i64::MAX,
+            ..RiskLimits::default()
+        };
+        assert_eq!(es.scale_limits(&open).max_notional, i64::MAX);
+    }
+
+    #[test]
+    fn registry_resolves_per_symbol() {
+        let mut registry = InstrumentRegistry::new();
+        assert!(registry.is_empty());
+        registry.insert(Instrument::perpetual(1).with_quantity_scale(8));
+        registry.insert(Instrument::perpetual(2).with_quantity_scale(2));
+        assert_eq!(registry.len(), 2);
+        let lots = Fixed::<2>::parse("0.25").unwrap();
+        assert_eq!(registry.to_units(1, lots), Some(25_000_000));
+        assert_eq!(registry.to_units(2, lots), Some(25));
+        assert_eq!(registry.to_units(3, lots), None);
+        assert_eq!(registry.to_lots::<2>(2, 25), Some(lots));
+        assert_eq!(registry.quantity_scale(1), Some(8));
+        assert_eq!(
+            registry
+                .scale_limits(2, &RiskLimits::default())
+                .map(|l| l.max_order_size),
+            Some(10_000)
+        );
+        assert!(registry.remove(1).is_some());
+        assert_eq!(
+            registry.iter().map(|i| i.symbol_hash).collect::<Vec<_>>(),
+            [2]
+        );
+    }
+
+    #[test]
+    fn restriction_windows() {
+        let fut = Instrument::expiring(1, 10 * DAY_NS, 9 * DAY_NS + 1);
+        assert_eq!(fut.restriction_at(0, 2 * DAY_NS), None);
+        assert_eq!(
+            fut.restriction_at(8 * DAY_NS, 2 * DAY_NS),
+            Some(ExpiryRestriction::NoNewPositions)
+        );
+        assert_eq!(
+            fut.restriction_at(9 * DAY_NS + 1, 2 * DAY_NS),
+            Some(ExpiryRestriction::ReduceOnly)
+        );
+        assert_eq!(
+            fut.restriction_at(20 * DAY_NS, 0),
+            Some(ExpiryRestriction::ReduceOnly)
+        );
+        assert_eq!(
+            Instrument::perpetual(1).restriction_at(u64::MAX, u64::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn spot_month_window() {
+        let fut = Instrument::expiring(1, 30 * DAY_NS, 29 * DAY_NS).deliverable(20 * DAY_NS);
+        assert!(!fut.in_spot_month(14 * DAY_NS, 5 * DAY_NS));
+        assert!(fut.in_spot_month(15 * DAY_NS, 5 * DAY_NS));
+        assert!(fut.in_spot_month(25 * DAY_NS, 5 * DAY_NS));
+        // 差金決済の限月には適用しない。
+        let cash = Instrument::expiring(1, 30 * DAY_NS, 29 * DAY_NS);
+        assert!(!cash.in_spot_month(u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn bucket_boundaries() {
+        let at = |days: u64| Instrument::expiring(1, days * DAY_NS, 0).bucket_at(0, 30);
+        assert_eq!(at(30), MaturityBucket::Front);
+        assert_eq!(at(31), MaturityBucket::Back);
+        assert_eq!(at(365), MaturityBucket::Back);
+        assert_eq!(at(366), MaturityBucket::Long);
+        assert_eq!(
+            Instrument::perpetual(1).bucket_at(0, 30),
+            MaturityBucket::Front
+        );
+        // 時間の経過でバケットが移る。
+        let fut = Instrument::expiring(1, 100 * DAY_NS, 0);
+        assert_eq!(fut.bucket_at(80 * DAY_NS, 30), MaturityBucket::Front);
+    }
+
+    #[test]
+    fn calendar_spread_nets_within_bucket() {
+        let m1 = Instrument::expiring(1, 10 * DAY_NS, 0).of_product(9);
+        let m2 = Instrument::expiring(2, 20 * DAY_NS, 0).of_product(9);
+        let m6 = Instrument::expiring(3, 180 * DAY_NS, 0).of_product(9);
+        let other = Instrument::expiring(4, 10 * DAY_NS, 0);
+        let holdings = [(m1, 500), (m2, -480), (m6, -20), (other, 1_000)];
+        assert_eq!(bucket_exposure(9, &holdings, 0, 30), [20, -20, 0]);
+    }
+
+    #[test]
+    fn no_new_positions_allows_adding_and_closing() {
+        let r = ExpiryRestriction::NoNewPositions;
+        assert!(!r.permits(&order(Side::Bid, 1), 0));
+        assert!(r.permits(&order(Side::Bid, 5), 10));
+        assert!(r.permits(&order(Side::Ask, 10), 10));
+        // ドテン。
+        assert!(!r.permits(&order(Side::Ask, 11), 10));
+    }
+
+    #[test]
+    fn reduce_only() {
+        let r = ExpiryRestriction::ReduceOnly;
+        assert!(r.permits(&order(Side::Bid, 3), -3));
+        assert!(!r.permits(&order(Side::Bid, 4), -3));
+        assert!(!r.permits(&order(Side::Ask, 1), -3));
+        assert!(!r.permits(&order(Side::Ask, 1), 0));
+    }
+}
@@ -0,0 +1,163 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 証拠金残高の金利計上。
+//!
+//! 資産額のうち所要証拠金を超える余剰担保には受取金利を、所要証拠金に
+//! 満たない不足分（借入で賄われる証拠金）には支払金利を日割りで計上する。
+//! 料率は通貨ごとに [`InterestSchedule`] に設定し、
+//! [`RiskEngine::roll_session`](crate::RiskEngine::roll_session) がセッション
+//! 切り替え時に各口座の資産額へ反映するため、証拠金判定に使う資産額に
+//! 資金調達コストが含まれる。
+//!
+//! 端数は口座に不利な側へ丸める（受取は切り捨て、支払は切り上げ）。
+
+use alloc::collections::BTreeMap;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// InterestRates
+// ---------------------------------------------------------------------------
+
+/// 1 通貨の年率金利。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterestRates {
+    /// 余剰担保に付く受取金利（年率 bps）。
+    pub credit_bps: u32,
+    /// 不足分に掛かる支払金利（年率 bps）。
+    pub debit_bps: u32,
+    /// 日割りの分母（360 または 365）。
+    pub day_count: u32,
+}
+
+impl Default for InterestRates {
+    fn default() -> Self {
+        Self {
+            credit_bps: 0,
+            debit_bps: 0,
+            day_count: 360,
+        }
+    }
+}
+
+impl InterestRates {
+    /// 資産額 `equity`、所要証拠金 `requirement` の口座に `days` 日分計上する
+    /// 金利（受取が正、支払が負）。
+    ///
+    /// `day_count` が 0 の場合は計上しない。
+    #[must_use]
+    pub fn accrual(&self, equity: i64, requirement: i64, days: u32) -> i64 {
+        let denominator = 10_000_i128 * i128::from(self.day_count);
+        if denominator == 0 {
+            return 0;
+        }
+        let excess = i128::from(equity) - i128::from(requirement);
+        let interest = if excess >= 0 {
+            excess.saturating_mul(i128::from(self.credit_bps) * i128::from(days)) / denominator
+        } else {
+            // 支払は切り上げ（負方向へ丸める）。
+            let numerator = excess.saturating_mul(i128::from(self.debit_bps) * i128::from(days));
+            numerator.div_euclid(denominator)
+        };
+        interest.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// InterestSchedule
+// ---------------------------------------------------------------------------
+
+/// 通貨ごとの金利設定。料率の無い通貨は計上しない。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterestSchedule {
+    rates: BTreeMap<u64, InterestRates>,
+}
+
+impl InterestSchedule {
+    /// 空の設定を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rates: BTreeMap::new(),
+        }
+    }
+
+    /// 通貨の料率を設定する（既存の設定は置き換え）。
+    pub fn set_rates(&mut self, currency_hash: u64, rates: InterestRates) {
+        self.rates.insert(currency_hash, rates);
+    }
+
+    /// 通貨の料率を外す。
+    pub fn remove(&mut self, currency_hash: u64) -> Option<InterestRates> {
+        self.rates.remove(&currency_hash)
+    }
+
+    /// 通貨の料率。
+    #[must_use]
+    pub fn rates(&self, currency_hash: u64) -> Option<&InterestRates> {
+        self.rates.get(&currency_hash)
+    }
+
+    /// `currency_hash` 建ての口座に `days` 日分計上する金利
+    /// （[`InterestRates::accrual`]）。料率の無い通貨は 0。
+    #[must_use]
+    pub fn accrual(&self, currency_hash: u64, equity: i64, requirement: i64, days: u32) -> i64 {
+        self.rates(currency_hash)
+            .map_or(0, |r| r.accrual(equity, requirement, days))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> InterestRates {
+        InterestRates {
+            credit_bps: 360,
+            debit_bps: 720,
+            day_count: 360,
+        }
+    }
+
+    #[test]
+    fn credit_on_excess_debit_on_shortfall() {
+        let r = rates();
+        // 余剰 1_000_000 × 3.6% / 360 = 100。
+        assert_eq!(r.accrual(1_500_000, 500_000, 1), 100);
+        // 週末をまたぐ 3 日分。
+        assert_eq!(r.accrual(1_500_000, 500_000, 3), 300);
+        // 不足 1_000_000 × 7.2% / 360 = 200。
+        assert_eq!(r.accrual(0, 1_000_000, 1), -200);
+        assert_eq!(r.accrual(500_000, 500_000, 1), 0);
+    }
+
+    #[test]
+    fn rounds_against_the_account() {
+        let r = rates();
+        // 受取 99.99 → 99、支払 199.99 → 200。
+        assert_eq!(r.accrual(999_999, 0, 1), 99);
+        assert_eq!(r.accrual(0, 999_999, 1), -200);
+        let none = InterestRates {
+            day_count: 0,
+            ..rates()
+        };
+        assert_eq!(none.accrual(0, 1_000_000, 1), 0);
+    }
+
+    #[test]
+    fn schedule_per_currency() {
+        let mut schedule = InterestSchedule::new();
+        schedule.set_rates(1, rates());
+        assert_eq!(schedule.accrual(1, 1_000_000, 0, 1), 100);
+        assert_eq!(schedule.accrual(2, 1_000_000, 0, 1), 0);
+        assert_eq!(schedule.remove(1), Some(rates()));
+        assert_eq!(schedule.rates(1), None);
+    }
+}
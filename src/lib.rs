@@ -65,23 +65,152 @@
 //! assert!(checker.check_order(&order, None).is_ok());
 //! ```
 
+pub mod account;
+pub mod alert;
+pub mod algo;
+pub mod approval;
+pub mod auction;
+pub mod batch;
+pub mod borrow;
 pub mod check;
 pub mod circuit;
+pub mod compliance;
+pub mod composite;
+pub mod config;
+pub mod context;
 pub mod counterparty;
+pub mod covariance;
+mod crypto;
+pub mod derisk;
+pub mod derived;
+pub mod drawdown;
+pub mod engine;
+pub mod fastpath;
+pub mod fees;
+pub mod fixed;
+pub mod forward;
+pub mod fx;
 pub mod greeks;
+pub mod halt;
+pub mod idempotency;
+pub mod inline;
+pub mod instrument;
+pub mod interest;
 pub mod limit;
+pub mod liquidity;
 pub mod margin;
+pub mod market;
+pub mod monitor;
+pub mod netting;
+pub mod options;
+pub mod orders;
+mod parallel;
+pub mod pdt;
+pub mod pnl;
+pub mod quarantine;
+#[cfg(feature = "recovery-kit")]
+pub mod recovery;
+pub mod regt;
+pub mod regulatory;
+pub mod replay;
+pub mod rollout;
+pub mod rule;
+pub mod score;
+pub mod settlement;
+pub mod shadow;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod slippage;
+pub mod snapshot;
+pub mod strategy;
 pub mod stress;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod throttle;
+pub mod token;
+pub mod trader;
 pub mod var;
+pub mod wal;
+pub mod watchdog;
 
-pub use check::{PreTradeChecker, RiskReject};
+pub use account::{AccountReject, AccountType};
+pub use alert::{AlertEvent, AlertSink, Severity};
+pub use algo::{AlgoLimits, AlgoReject};
+pub use approval::{ApprovalAction, ApprovalError, ApprovalQueue, ApprovalRecord, PendingApproval};
+pub use auction::{AuctionMode, AuctionParams};
+pub use borrow::{short_quantity, BorrowFees, BorrowReject, LocateInventory, LocateUsage};
+pub use check::{
+    rests_on_book, ArithmeticMode, CheckOutcome, DecisionRecord, DecisionTags, LossBasis,
+    PreTradeChecker, RiskReject, SizingMode, Utilization,
+};
 pub use circuit::CircuitBreaker;
+pub use compliance::{
+    self_assessment, AlgoActivity, Attestation, ControlCategory, ControlStatus, DuplicateGuard,
+    MarketAccessControls, MessageRate,
+};
+pub use composite::{CompositeConfig, CompositeMarket, CompositeReference};
+pub use config::{ConfigError, ConfigLayer, LayeredConfig, RiskConfig};
+pub use context::DecisionContext;
 pub use counterparty::{CounterpartyLimits, CounterpartyReject, CounterpartyTracker};
-pub use greeks::{check_greeks, GreeksExposure, GreeksLimits, GreeksReject};
-pub use limit::RiskLimits;
-pub use margin::{MarginCalculator, MarginParams};
+pub use covariance::{CovarianceMatrix, VolatilityBudget};
+pub use derisk::{DeriskAction, DeriskPolicy, DeriskState, DeriskStep};
+pub use derived::{DerivedThresholds, ThresholdCache};
+pub use drawdown::{DrawdownLimits, HighWaterMark};
+pub use engine::{AccountSnapshot, DashboardSnapshot, Exposure, ExposureProjection, RiskEngine};
+pub use fastpath::{CheckMask, ThresholdTable, Violations};
+pub use fees::{FeeRate, FeeSchedule, Liquidity};
+pub use fixed::Fixed;
+pub use forward::{ForwardMarginCalculator, ForwardMarginParams, FxLeg, FxSwap, TenorBucket};
+pub use fx::{FxError, FxQuote, FxRateProvider, FxRates, FX_RATE_SCALE};
+pub use greeks::{check_greeks, GreeksExposure, GreeksLimits, GreeksReject, PortfolioGreeks};
+pub use halt::{
+    BreakerConfig, BreakerGroup, BreakerMode, HaltKind, HaltReason, HaltRegistry, HaltStats,
+    MarketWideBreaker, MarketWideLevel, MarketWideTrip, PriceSource, PropagationAction,
+    PropagationRule, Reopening, TripRecord,
+};
+pub use idempotency::{IdempotencyCache, IdempotentDecision, OrderFingerprint};
+pub use inline::InlineVec;
+pub use instrument::{
+    bucket_exposure, ExpiryRestriction, Instrument, InstrumentRegistry, MarginCurrency,
+    MaturityBucket,
+};
+pub use interest::{InterestRates, InterestSchedule};
+pub use limit::{EnabledChecks, LimitVersion, RiskLimits, SymbolCaps};
+pub use liquidity::LiquidityLimit;
+pub use margin::{MarginCalculator, MarginParams, MarkStaleness, StaleMark, StaleMarkPolicy};
+pub use market::{Bbo, MarketContext, MarketSnapshot, SymbolMarket};
+pub use monitor::{
+    PenaltyBox, PenaltyBoxConfig, RejectRateAlert, RejectRateConfig, RejectRateMonitor,
+};
+pub use netting::{CompressionOpportunity, CompressionReport, NettingGroups};
+pub use options::{
+    AssignmentBuffer, AutoExerciseWarning, ExpiryDayRules, OptionContract, OptionsReject,
+};
+pub use orders::{OpenOrders, StaleAction, StaleOrder, WorkingOrder};
+pub use pdt::{BuyingPower, PdtEvent, PdtParams, PdtReject, PdtTracker};
+pub use pnl::{PnlAttribution, PnlBreakdown, PnlReport};
+pub use quarantine::{Quarantine, QuarantineMode, StrategyQuarantine};
+pub use regt::{MarginModel, OptionKind, OptionStrategy, RegTCalculator, RegTParams};
+pub use regulatory::{AccountabilityWarning, RegulatoryLimit, RegulatoryReject, RegulatoryTracker};
+pub use replay::{replay, ReplayEvent, ReplayReport, Replayer};
+pub use rollout::{CanaryRollout, Cohort, Guardrails, RollbackReason, RolloutState};
+pub use rule::{RuleInput, RuleReject, RuleSet};
+pub use score::{RiskScore, RiskScoreConfig, RiskScoreWeights, RiskScorer};
+pub use settlement::{SettlementReject, SettlementTracker};
+pub use shadow::{ShadowDivergence, ShadowLimits, ShadowReport};
+pub use slippage::{slippage, SlippageStats, SlippageTracker};
+pub use snapshot::{CheckerState, SnapshotCodec, SnapshotError};
+pub use strategy::StrategyReject;
 pub use stress::{apply_scenario, stress_test_portfolio, StressResult, StressScenario};
+pub use throttle::{
+    CancelPenalty, CancelRateConfig, CancelRateLimiter, MessageBudget, MessageKind, MessageWeights,
+    RateWindow,
+};
+pub use token::{DecisionToken, TokenSigner};
+pub use trader::{SubAccountReject, SubAccounts};
 pub use var::{HistoricalVaR, ParametricVaR};
+pub use wal::{Checkpoint, EventLog, WalConfig};
+pub use watchdog::{MarketDataWatchdog, StaleDataMode, StalenessConfig};
 
 /// ALICE-Risk crate version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -5,6 +5,14 @@
 
 //! Per-instrument and per-account risk limit configuration.
 
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::instrument::MaturityBucket;
+
+extern crate alloc;
+
 // ---------------------------------------------------------------------------
 // RiskLimits
 // ---------------------------------------------------------------------------
@@ -22,6 +30,72 @@ pub struct RiskLimits {
     pub max_open_orders: u32,
     /// Maximum daily loss (realized + unrealized) before kill switch triggers.
     pub max_daily_loss: i64,
+    /// Maximum immediate-or-cancel / fill-or-kill orders per
+    /// [`Self::non_resting_window_ns`].  Non-resting orders never count
+    /// against [`Self::max_open_orders`].
+    pub max_non_resting_orders: u32,
+    /// Window length for [`Self::max_non_resting_orders`], in nanoseconds.
+    pub non_resting_window_ns: u64,
+    /// Sustained ceiling on immediate-or-cancel / fill-or-kill orders per
+    /// [`Self::non_resting_sustained_window_ns`].  Lets short bursts up to
+    /// [`Self::max_non_resting_orders`] through while blocking a rate that
+    /// stays high.
+    pub max_non_resting_orders_sustained: u32,
+    /// Window length for [`Self::max_non_resting_orders_sustained`], in
+    /// nanoseconds.  `0` disables the sustained threshold.
+    pub non_resting_sustained_window_ns: u64,
+    /// Maximum combined notional of all working (resting) orders, in ticks.
+    /// Bounds exposure that [`Self::max_open_orders`] alone cannot: 500 tiny
+    /// orders and 500 block orders have the same count.
+    pub max_open_order_notional: i64,
+    /// Maximum open orders resting in any one symbol, on top of the account
+    /// total in [`Self::max_open_orders`].
+    pub max_open_orders_per_symbol: u32,
+    /// Age beyond which a working order is reported as stale, in
+    /// nanoseconds.  Monitoring only; never rejects an order.
+    pub max_working_order_age_ns: u64,
+    /// Maximum distance a limit order may be priced through the opposite
+    /// side of the best bid/offer, in basis points of that quote.  Only
+    /// applied when a [`Bbo`](crate::Bbo) is supplied.
+    pub max_through_bbo_bps: u32,
+    /// Venue minimum for the displayed quantity of an iceberg order, in lots.
+    pub min_display_quantity: u64,
+    /// Days before expiry from which new positions may no longer be opened
+    /// in an expiring contract.
+    pub expiry_no_open_days: u32,
+    /// Maximum absolute position in a physically delivered contract once it
+    /// enters its spot month, in lots.
+    pub spot_month_position_limit: u64,
+    /// Days before first notice day from which
+    /// [`Self::spot_month_position_limit`] applies.
+    pub spot_month_days_before_notice: u32,
+    /// Remaining days up to which a contract falls in the front-month
+    /// maturity bucket.
+    pub front_month_days: u32,
+    /// Maximum absolute net position of one product in the front-month
+    /// bucket, in lots.
+    pub max_front_month_position: u64,
+    /// Maximum absolute net position of one product in the back-month
+    /// bucket (up to one year), in lots.
+    pub max_back_month_position: u64,
+    /// Maximum absolute net position of one product beyond one year, in lots.
+    pub max_long_dated_position: u64,
+    /// Symbols classified as illiquid.  Orders in them are checked against
+    /// [`Self::for_symbol`], and their margin carries
+    /// [`Self::illiquid_margin_multiplier_bps`].
+    pub illiquid_symbols: BTreeSet<u64>,
+    /// Margin multiplier for positions in illiquid symbols, in basis points
+    /// (15000 = 1.5x).
+    pub illiquid_margin_multiplier_bps: u32,
+    /// Scale applied to the order-size and position limits of illiquid
+    /// symbols, in basis points (5000 = half).
+    pub illiquid_limit_factor_bps: u32,
+    /// Price collar for illiquid symbols, in basis points through the
+    /// opposite side of the BBO.  The tighter of this and
+    /// [`Self::max_through_bbo_bps`] applies.
+    pub illiquid_max_through_bbo_bps: u32,
+    /// Which of the checks above are enforced.
+    pub checks: EnabledChecks,
 }
 
 impl Default for RiskLimits {
@@ -32,10 +106,556 @@ impl Default for RiskLimits {
             max_notional: 100_000_000,
             max_open_orders: 500,
             max_daily_loss: -500_000,
+            max_non_resting_orders: 1_000,
+            non_resting_window_ns: 1_000_000_000,
+            max_non_resting_orders_sustained: u32::MAX,
+            non_resting_sustained_window_ns: 0,
+            max_open_order_notional: 1_000_000_000,
+            max_open_orders_per_symbol: 100,
+            max_working_order_age_ns: 3_600_000_000_000,
+            max_through_bbo_bps: 500,
+            min_display_quantity: 1,
+            expiry_no_open_days: 2,
+            spot_month_position_limit: 100,
+            spot_month_days_before_notice: 5,
+            front_month_days: 31,
+            max_front_month_position: 1000,
+            max_back_month_position: 1000,
+            max_long_dated_position: 500,
+            illiquid_symbols: BTreeSet::new(),
+            illiquid_margin_multiplier_bps: 15_000,
+            illiquid_limit_factor_bps: 5_000,
+            illiquid_max_through_bbo_bps: 200,
+            checks: EnabledChecks::ALL,
+        }
+    }
+}
+
+impl RiskLimits {
+    /// Numeric limit names accepted by [`Self::set_field`], in declaration order.
+    pub const FIELDS: [&'static str; 24] = [
+        "max_position",
+        "max_order_size",
+        "max_notional",
+        "max_open_orders",
+        "max_daily_loss",
+        "max_non_resting_orders",
+        "non_resting_window_ns",
+        "max_non_resting_orders_sustained",
+        "non_resting_sustained_window_ns",
+        "max_open_order_notional",
+        "max_open_orders_per_symbol",
+        "max_working_order_age_ns",
+        "max_through_bbo_bps",
+        "min_display_quantity",
+        "expiry_no_open_days",
+        "spot_month_position_limit",
+        "spot_month_days_before_notice",
+        "front_month_days",
+        "max_front_month_position",
+        "max_back_month_position",
+        "max_long_dated_position",
+        "illiquid_margin_multiplier_bps",
+        "illiquid_limit_factor_bps",
+        "illiquid_max_through_bbo_bps",
+    ];
+
+    /// Symbol-list names accepted by [`Self::set_field`].  Values are
+    /// comma-separated symbol hashes (e.g. `17,42`); an empty value clears
+    /// the list.
+    pub const LIST_FIELDS: [&'static str; 1] = ["illiquid_symbols"];
+
+    /// Check toggle names accepted by [`Self::set_field`], in the same order
+    /// as the limits they switch.
+    pub const CHECK_FIELDS: [&'static str; 14] = [
+        "check_position",
+        "check_order_size",
+        "check_notional",
+        "check_open_orders",
+        "check_daily_loss",
+        "check_non_resting_rate",
+        "check_open_order_notional",
+        "check_open_orders_per_symbol",
+        "check_stale_orders",
+        "check_bbo_reasonability",
+        "check_iceberg",
+        "check_expiry",
+        "check_spot_month",
+        "check_maturity_buckets",
+    ];
+
+    /// Set a single limit by field name from its textual value.
+    ///
+    /// Limit values are plain integers; `_` digit separators are accepted
+    /// (e.g. `100_000_000`). Check toggles take `true` or `false`, and
+    /// symbol lists a comma-separated list of integers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LimitFieldError`] if `name` is not a limit field or `value`
+    /// does not parse into the field's type.
+    pub fn set_field(&mut self, name: &str, value: &str) -> Result<(), LimitFieldError> {
+        if let Some(flag) = self.checks.flag_mut(name) {
+            *flag = value
+                .trim()
+                .parse()
+                .map_err(|_| LimitFieldError::InvalidValue)?;
+            return Ok(());
+        }
+        if name == "illiquid_symbols" {
+            self.illiquid_symbols = value
+                .split(',')
+                .map(|v| v.trim().replace('_', ""))
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse().map_err(|_| LimitFieldError::InvalidValue))
+                .collect::<Result<_, _>>()?;
+            return Ok(());
+        }
+        let digits: String = value.trim().chars().filter(|&c| c != '_').collect();
+        let invalid = |_| LimitFieldError::InvalidValue;
+        match name {
+            "max_position" => self.max_position = digits.parse().map_err(invalid)?,
+            "max_order_size" => self.max_order_size = digits.parse().map_err(invalid)?,
+            "max_notional" => self.max_notional = digits.parse().map_err(invalid)?,
+            "max_open_orders" => self.max_open_orders = digits.parse().map_err(invalid)?,
+            "max_daily_loss" => self.max_daily_loss = digits.parse().map_err(invalid)?,
+            "max_non_resting_orders" => {
+                self.max_non_resting_orders = digits.parse().map_err(invalid)?;
+            }
+            "non_resting_window_ns" => {
+                self.non_resting_window_ns = digits.parse().map_err(invalid)?;
+            }
+            "max_non_resting_orders_sustained" => {
+                self.max_non_resting_orders_sustained = digits.parse().map_err(invalid)?;
+            }
+            "non_resting_sustained_window_ns" => {
+                self.non_resting_sustained_window_ns = digits.parse().map_err(invalid)?;
+            }
+            "max_open_order_notional" => {
+                self.max_open_order_notional = digits.parse().map_err(invalid)?;
+            }
+            "max_open_orders_per_symbol" => {
+                self.max_open_orders_per_symbol = digits.parse().map_err(invalid)?;
+            }
+            "max_working_order_age_ns" => {
+                self.max_working_order_age_ns = digits.parse().map_err(invalid)?;
+            }
+            "max_through_bbo_bps" => self.max_through_bbo_bps = digits.parse().map_err(invalid)?,
+            "min_display_quantity" => {
+                self.min_display_quantity = digits.parse().map_err(invalid)?;
+            }
+            "expiry_no_open_days" => self.expiry_no_open_days = digits.parse().map_err(invalid)?,
+            "spot_month_position_limit" => {
+                self.spot_month_position_limit = digits.parse().map_err(invalid)?;
+            }
+            "spot_month_days_before_notice" => {
+                self.spot_month_days_before_notice = digits.parse().map_err(invalid)?;
+            }
+            "front_month_days" => self.front_month_days = digits.parse().map_err(invalid)?,
+            "max_front_month_position" => {
+                self.max_front_month_position = digits.parse().map_err(invalid)?;
+            }
+            "max_back_month_position" => {
+                self.max_back_month_position = digits.parse().map_err(invalid)?;
+            }
+            "max_long_dated_position" => {
+                self.max_long_dated_position = digits.parse().map_err(invalid)?;
+            }
+            "illiquid_margin_multiplier_bps" => {
+                self.illiquid_margin_multiplier_bps = digits.parse().map_err(invalid)?;
+            }
+            "illiquid_limit_factor_bps" => {
+                self.illiquid_limit_factor_bps = digits.parse().map_err(invalid)?;
+            }
+            "illiquid_max_through_bbo_bps" => {
+                self.illiquid_max_through_bbo_bps = digits.parse().map_err(invalid)?;
+            }
+            _ => return Err(LimitFieldError::UnknownField),
+        }
+        Ok(())
+    }
+
+    /// Copy of these limits with every size, count, notional and loss limit
+    /// scaled by `factor_bps` (10000 = unchanged).
+    ///
+    /// Scaled values round toward zero, so the result is never looser than
+    /// the exact product.  Disabled sentinels (`u64::MAX`, `u32::MAX`,
+    /// `i64::MAX`, `i64::MIN`) stay disabled.  Windows, ages, day counts and
+    /// price bands are left as they are.
+    #[must_use]
+    pub fn scaled(&self, factor_bps: u32) -> Self {
+        let factor = u128::from(factor_bps);
+        let lots = |v: u64| {
+            if v == u64::MAX {
+                v
+            } else {
+                (u128::from(v) * factor / 10_000).min(u128::from(u64::MAX)) as u64
+            }
+        };
+        let count = |v: u32| {
+            if v == u32::MAX {
+                v
+            } else {
+                (u128::from(v) * factor / 10_000).min(u128::from(u32::MAX)) as u32
+            }
+        };
+        let money = |v: i64| {
+            if v == i64::MAX || v == i64::MIN {
+                v
+            } else {
+                (i128::from(v) * i128::from(factor_bps) / 10_000)
+                    .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+            }
+        };
+        Self {
+            max_position: lots(self.max_position),
+            max_order_size: lots(self.max_order_size),
+            max_notional: money(self.max_notional),
+            max_open_orders: count(self.max_open_orders),
+            max_daily_loss: money(self.max_daily_loss),
+            max_non_resting_orders: count(self.max_non_resting_orders),
+            max_non_resting_orders_sustained: count(self.max_non_resting_orders_sustained),
+            max_open_order_notional: money(self.max_open_order_notional),
+            max_open_orders_per_symbol: count(self.max_open_orders_per_symbol),
+            spot_month_position_limit: lots(self.spot_month_position_limit),
+            max_front_month_position: lots(self.max_front_month_position),
+            max_back_month_position: lots(self.max_back_month_position),
+            max_long_dated_position: lots(self.max_long_dated_position),
+            ..self.clone()
+        }
+    }
+
+    /// Whether `symbol_hash` is classified as illiquid.
+    #[inline(always)]
+    #[must_use]
+    pub fn is_illiquid(&self, symbol_hash: u64) -> bool {
+        self.illiquid_symbols.contains(&symbol_hash)
+    }
+
+    /// Limits in force for orders in `symbol_hash`: [`Self::illiquid`] if
+    /// the symbol is classified as illiquid, these limits otherwise.
+    #[inline]
+    #[must_use]
+    pub fn for_symbol(&self, symbol_hash: u64) -> Cow<'_, Self> {
+        if self.is_illiquid(symbol_hash) {
+            Cow::Owned(self.illiquid())
+        } else {
+            Cow::Borrowed(self)
+        }
+    }
+
+    /// Copy of these limits as applied to an illiquid symbol.
+    ///
+    /// The order-size and position limits and the BBO collar are those of
+    /// [`SymbolCaps::illiquid`].  The illiquid list is cleared, since the
+    /// result already applies to a single symbol.  This copies the whole
+    /// limit set; the order checks use [`Self::symbol_caps`] instead.
+    #[must_use]
+    pub fn illiquid(&self) -> Self {
+        let caps = SymbolCaps::from(self).illiquid(self);
+        Self {
+            max_order_size: caps.max_order_size,
+            max_position: caps.max_position,
+            max_through_bbo_bps: caps.max_through_bbo_bps,
+            illiquid_symbols: BTreeSet::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Order-size limit, position limit and BBO collar in force for orders
+    /// in `symbol_hash`, tightened by [`SymbolCaps::illiquid`] if the symbol
+    /// is classified as illiquid.  Unlike [`Self::for_symbol`] this never
+    /// copies the limit set.
+    #[inline]
+    #[must_use]
+    pub fn symbol_caps(&self, symbol_hash: u64) -> SymbolCaps {
+        let caps = SymbolCaps::from(self);
+        if self.is_illiquid(symbol_hash) {
+            caps.illiquid(self)
+        } else {
+            caps
+        }
+    }
+
+    /// A lot limit scaled by [`Self::illiquid_limit_factor_bps`], never
+    /// loosened beyond `lots`.
+    #[inline(always)]
+    fn illiquid_lots(&self, lots: u64) -> u64 {
+        if lots == u64::MAX {
+            return lots;
+        }
+        let factor = u128::from(self.illiquid_limit_factor_bps.min(10_000));
+        (u128::from(lots) * factor / 10_000) as u64
+    }
+
+    /// `margin` with the illiquid add-on applied if `symbol_hash` is
+    /// classified as illiquid, rounded up.  A multiplier below 10000 bps is
+    /// not applied, so the add-on never reduces margin.
+    #[must_use]
+    pub fn illiquid_margin(&self, symbol_hash: u64, margin: i64) -> i64 {
+        if !self.is_illiquid(symbol_hash) {
+            return margin;
+        }
+        let bps = i128::from(self.illiquid_margin_multiplier_bps.max(10_000));
+        let scaled = i128::from(margin) * bps;
+        let rounded = scaled.div_euclid(10_000) + i128::from(scaled.rem_euclid(10_000) != 0);
+        rounded.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+
+    /// Position limit for one maturity bucket.
+    #[must_use]
+    pub const fn bucket_limit(&self, bucket: MaturityBucket) -> u64 {
+        match bucket {
+            MaturityBucket::Front => self.max_front_month_position,
+            MaturityBucket::Back => self.max_back_month_position,
+            MaturityBucket::Long => self.max_long_dated_position,
+        }
+    }
+
+    /// Textual value of a field accepted by [`Self::set_field`], or `None`
+    /// for an unknown name.  Round-trips through [`Self::set_field`].
+    #[must_use]
+    pub fn get_field(&self, name: &str) -> Option<String> {
+        let mut checks = self.checks;
+        if let Some(flag) = checks.flag_mut(name) {
+            return Some(flag.to_string());
+        }
+        let value = match name {
+            "max_position" => self.max_position.to_string(),
+            "max_order_size" => self.max_order_size.to_string(),
+            "max_notional" => self.max_notional.to_string(),
+            "max_open_orders" => self.max_open_orders.to_string(),
+            "max_daily_loss" => self.max_daily_loss.to_string(),
+            "max_non_resting_orders" => self.max_non_resting_orders.to_string(),
+            "non_resting_window_ns" => self.non_resting_window_ns.to_string(),
+            "max_non_resting_orders_sustained" => self.max_non_resting_orders_sustained.to_string(),
+            "non_resting_sustained_window_ns" => self.non_resting_sustained_window_ns.to_string(),
+            "max_open_order_notional" => self.max_open_order_notional.to_string(),
+            "max_open_orders_per_symbol" => self.max_open_orders_per_symbol.to_string(),
+            "max_working_order_age_ns" => self.max_working_order_age_ns.to_string(),
+            "max_through_bbo_bps" => self.max_through_bbo_bps.to_string(),
+            "min_display_quantity" => self.min_display_quantity.to_string(),
+            "expiry_no_open_days" => self.expiry_no_open_days.to_string(),
+            "spot_month_position_limit" => self.spot_month_position_limit.to_string(),
+            "spot_month_days_before_notice" => self.spot_month_days_before_notice.to_string(),
+            "front_month_days" => self.front_month_days.to_string(),
+            "max_front_month_position" => self.max_front_month_position.to_string(),
+            "max_back_month_position" => self.max_back_month_position.to_string(),
+            "max_long_dated_position" => self.max_long_dated_position.to_string(),
+            "illiquid_symbols" => self
+                .illiquid_symbols
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            "illiquid_margin_multiplier_bps" => self.illiquid_margin_multiplier_bps.to_string(),
+            "illiquid_limit_factor_bps" => self.illiquid_limit_factor_bps.to_string(),
+            "illiquid_max_through_bbo_bps" => self.illiquid_max_through_bbo_bps.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SymbolCaps
+// ---------------------------------------------------------------------------
+
+/// The limits that differ between symbols of one [`RiskLimits`]: what
+/// [`RiskLimits::symbol_caps`] returns for a single symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolCaps {
+    /// Maximum quantity for a single order.
+    pub max_order_size: u64,
+    /// Maximum absolute net position.
+    pub max_position: u64,
+    /// Maximum distance through the opposite BBO, in basis points.
+    pub max_through_bbo_bps: u32,
+}
+
+impl SymbolCaps {
+    /// These caps as applied to an illiquid symbol of `limits`.
+    ///
+    /// The order-size and position limits are scaled by
+    /// [`RiskLimits::illiquid_limit_factor_bps`] (rounding toward zero;
+    /// disabled `u64::MAX` limits stay disabled) and the BBO collar is
+    /// tightened to [`RiskLimits::illiquid_max_through_bbo_bps`].
+    #[inline]
+    #[must_use]
+    pub fn illiquid(self, limits: &RiskLimits) -> Self {
+        Self {
+            max_order_size: limits.illiquid_lots(self.max_order_size),
+            max_position: limits.illiquid_lots(self.max_position),
+            max_through_bbo_bps: self
+                .max_through_bbo_bps
+                .min(limits.illiquid_max_through_bbo_bps),
         }
     }
 }
 
+impl From<&RiskLimits> for SymbolCaps {
+    #[inline(always)]
+    fn from(limits: &RiskLimits) -> Self {
+        Self {
+            max_order_size: limits.max_order_size,
+            max_position: limits.max_position,
+            max_through_bbo_bps: limits.max_through_bbo_bps,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// EnabledChecks
+// ---------------------------------------------------------------------------
+
+/// Per-check enable flags for [`RiskLimits`].
+///
+/// A disabled check is skipped entirely rather than compared against a
+/// sentinel `MAX` limit, and is reported as disabled in utilization.
+/// The circuit breaker is a kill switch and cannot be disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct EnabledChecks {
+    /// Enforce [`RiskLimits::max_position`].
+    pub position: bool,
+    /// Enforce [`RiskLimits::max_order_size`].
+    pub order_size: bool,
+    /// Enforce [`RiskLimits::max_notional`].
+    pub notional: bool,
+    /// Enforce [`RiskLimits::max_open_orders`].
+    pub open_orders: bool,
+    /// Enforce [`RiskLimits::max_daily_loss`].
+    pub daily_loss: bool,
+    /// Enforce [`RiskLimits::max_non_resting_orders`].
+    pub non_resting_rate: bool,
+    /// Enforce [`RiskLimits::max_open_order_notional`].
+    pub open_order_notional: bool,
+    /// Enforce [`RiskLimits::max_open_orders_per_symbol`].
+    pub open_orders_per_symbol: bool,
+    /// Report working orders older than
+    /// [`RiskLimits::max_working_order_age_ns`].
+    pub stale_orders: bool,
+    /// Enforce [`RiskLimits::max_through_bbo_bps`].
+    pub bbo_reasonability: bool,
+    /// Validate iceberg display quantities against
+    /// [`RiskLimits::min_display_quantity`].
+    pub iceberg: bool,
+    /// Enforce expiry restrictions ([`RiskLimits::expiry_no_open_days`] and
+    /// reduce-only in the final session).
+    pub expiry: bool,
+    /// Enforce [`RiskLimits::spot_month_position_limit`].
+    pub spot_month: bool,
+    /// Enforce the per-bucket limits ([`RiskLimits::max_front_month_position`],
+    /// [`RiskLimits::max_back_month_position`],
+    /// [`RiskLimits::max_long_dated_position`]).
+    pub maturity_buckets: bool,
+}
+
+impl EnabledChecks {
+    /// Every check enforced.
+    pub const ALL: Self = Self {
+        position: true,
+        order_size: true,
+        notional: true,
+        open_orders: true,
+        daily_loss: true,
+        non_resting_rate: true,
+        open_order_notional: true,
+        open_orders_per_symbol: true,
+        stale_orders: true,
+        bbo_reasonability: true,
+        iceberg: true,
+        expiry: true,
+        spot_month: true,
+        maturity_buckets: true,
+    };
+
+    /// No check enforced (circuit breaker only).
+    pub const NONE: Self = Self {
+        position: false,
+        order_size: false,
+        notional: false,
+        open_orders: false,
+        daily_loss: false,
+        non_resting_rate: false,
+        open_order_notional: false,
+        open_orders_per_symbol: false,
+        stale_orders: false,
+        bbo_reasonability: false,
+        iceberg: false,
+        expiry: false,
+        spot_month: false,
+        maturity_buckets: false,
+    };
+
+    /// Flag addressed by a [`RiskLimits::CHECK_FIELDS`] name.
+    fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "check_position" => Some(&mut self.position),
+            "check_order_size" => Some(&mut self.order_size),
+            "check_notional" => Some(&mut self.notional),
+            "check_open_orders" => Some(&mut self.open_orders),
+            "check_daily_loss" => Some(&mut self.daily_loss),
+            "check_non_resting_rate" => Some(&mut self.non_resting_rate),
+            "check_open_order_notional" => Some(&mut self.open_order_notional),
+            "check_open_orders_per_symbol" => Some(&mut self.open_orders_per_symbol),
+            "check_stale_orders" => Some(&mut self.stale_orders),
+            "check_bbo_reasonability" => Some(&mut self.bbo_reasonability),
+            "check_iceberg" => Some(&mut self.iceberg),
+            "check_expiry" => Some(&mut self.expiry),
+            "check_spot_month" => Some(&mut self.spot_month),
+            "check_maturity_buckets" => Some(&mut self.maturity_buckets),
+            _ => None,
+        }
+    }
+}
+
+impl Default for EnabledChecks {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Error returned by [`RiskLimits::set_field`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitFieldError {
+    /// The field name is not one of [`RiskLimits::FIELDS`] or
+    /// [`RiskLimits::CHECK_FIELDS`].
+    UnknownField,
+    /// The value is not valid for the field's type.
+    InvalidValue,
+}
+
+// ---------------------------------------------------------------------------
+// LimitVersion
+// ---------------------------------------------------------------------------
+
+/// One applied version of the risk limits: who pushed what, and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitVersion {
+    /// Monotonic version identifier; version 0 is the set the engine was
+    /// constructed with.
+    pub version: u64,
+    /// The limits in force from this version on.
+    pub limits: RiskLimits,
+    /// Identity of the operator or system that applied this version.
+    pub author: String,
+    /// Time the version was applied, in nanoseconds.
+    pub timestamp_ns: u64,
+    /// If this version was created by a rollback, the version restored.
+    pub rollback_of: Option<u64>,
+}
+
+/// Error returned by limit-history operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitHistoryError {
+    /// The requested version was never applied.
+    UnknownVersion {
+        /// Requested version identifier.
+        version: u64,
+    },
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -44,6 +664,25 @@ impl Default for RiskLimits {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_scaled_limits() {
+        let limits = RiskLimits {
+            max_position: 1001,
+            max_non_resting_orders_sustained: u32::MAX,
+            ..RiskLimits::default()
+        };
+        let half = limits.scaled(5_000);
+        assert_eq!(half.max_position, 500);
+        assert_eq!(half.max_order_size, 50);
+        assert_eq!(half.max_notional, 50_000_000);
+        assert_eq!(half.max_open_orders, 250);
+        assert_eq!(half.max_daily_loss, -250_000);
+        assert_eq!(half.max_non_resting_orders_sustained, u32::MAX);
+        assert_eq!(half.non_resting_window_ns, limits.non_resting_window_ns);
+        assert_eq!(half.max_through_bbo_bps, limits.max_through_bbo_bps);
+        assert_eq!(limits.scaled(10_000), limits);
+    }
+
     #[test]
     fn test_default_limits() {
         let limits = RiskLimits::default();
@@ -52,6 +691,7 @@ mod tests {
         assert_eq!(limits.max_notional, 100_000_000);
         assert_eq!(limits.max_open_orders, 500);
         assert_eq!(limits.max_daily_loss, -500_000);
+        assert_eq!(limits.checks, EnabledChecks::ALL);
     }
 
     #[test]
@@ -62,6 +702,7 @@ mod tests {
             max_notional: 5_000_000,
             max_open_orders: 20,
             max_daily_loss: -10_000,
+            ..RiskLimits::default()
         };
         assert_eq!(limits.max_position, 50);
         assert_eq!(limits.max_order_size, 10);
@@ -78,6 +719,27 @@ mod tests {
             max_notional: 999_999,
             max_open_orders: 3,
             max_daily_loss: -77,
+            max_non_resting_orders: 9,
+            non_resting_window_ns: 11,
+            max_non_resting_orders_sustained: 61,
+            non_resting_sustained_window_ns: 67,
+            max_open_order_notional: 13,
+            max_open_orders_per_symbol: 17,
+            max_working_order_age_ns: 19,
+            max_through_bbo_bps: 23,
+            min_display_quantity: 29,
+            expiry_no_open_days: 31,
+            spot_month_position_limit: 37,
+            spot_month_days_before_notice: 41,
+            front_month_days: 43,
+            max_front_month_position: 47,
+            max_back_month_position: 53,
+            max_long_dated_position: 59,
+            illiquid_symbols: [71, 73].into_iter().collect(),
+            illiquid_margin_multiplier_bps: 79,
+            illiquid_limit_factor_bps: 83,
+            illiquid_max_through_bbo_bps: 89,
+            checks: EnabledChecks::NONE,
         };
         let cloned = original.clone();
         assert_eq!(original, cloned);
@@ -108,6 +770,7 @@ mod tests {
             max_notional: 0,
             max_open_orders: 0,
             max_daily_loss: 0,
+            ..RiskLimits::default()
         };
         assert_eq!(limits.max_position, 0);
         assert_eq!(limits.max_order_size, 0);
@@ -124,6 +787,7 @@ mod tests {
             max_notional: i64::MAX,
             max_open_orders: u32::MAX,
             max_daily_loss: i64::MIN,
+            ..RiskLimits::default()
         };
         assert_eq!(limits.max_position, u64::MAX);
         assert_eq!(limits.max_order_size, u64::MAX);
@@ -132,6 +796,130 @@ mod tests {
         assert_eq!(limits.max_daily_loss, i64::MIN);
     }
 
+    #[test]
+    fn test_set_field_each_field() {
+        let mut limits = RiskLimits::default();
+        limits.set_field("max_position", "10").unwrap();
+        limits.set_field("max_order_size", "2").unwrap();
+        limits.set_field("max_notional", "1_000_000").unwrap();
+        limits.set_field("max_open_orders", " 7 ").unwrap();
+        limits.set_field("max_daily_loss", "-50_000").unwrap();
+        assert_eq!(limits.max_position, 10);
+        assert_eq!(limits.max_order_size, 2);
+        assert_eq!(limits.max_notional, 1_000_000);
+        assert_eq!(limits.max_open_orders, 7);
+        assert_eq!(limits.max_daily_loss, -50_000);
+    }
+
+    #[test]
+    fn test_set_field_errors_leave_limits_unchanged() {
+        let mut limits = RiskLimits::default();
+        assert_eq!(
+            limits.set_field("max_leverage", "3"),
+            Err(LimitFieldError::UnknownField)
+        );
+        assert_eq!(
+            limits.set_field("max_position", "-1"),
+            Err(LimitFieldError::InvalidValue)
+        );
+        assert_eq!(
+            limits.set_field("max_open_orders", "lots"),
+            Err(LimitFieldError::InvalidValue)
+        );
+        assert_eq!(limits, RiskLimits::default());
+    }
+
+    #[test]
+    fn test_fields_are_all_settable() {
+        let mut limits = RiskLimits::default();
+        for name in RiskLimits::FIELDS {
+            assert!(limits.set_field(name, "0").is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_set_field_check_toggles() {
+        let mut limits = RiskLimits::default();
+        limits.set_field("check_open_orders", "false").unwrap();
+        limits.set_field("check_notional", " false ").unwrap();
+        assert!(!limits.checks.open_orders);
+        assert!(!limits.checks.notional);
+        assert!(limits.checks.position);
+        limits.set_field("check_open_orders", "true").unwrap();
+        assert!(limits.checks.open_orders);
+        assert_eq!(
+            limits.set_field("check_position", "0"),
+            Err(LimitFieldError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_check_fields_are_all_settable() {
+        let mut limits = RiskLimits::default();
+        for name in RiskLimits::CHECK_FIELDS {
+            assert!(limits.set_field(name, "false").is_ok(), "{name}");
+        }
+        assert_eq!(limits.checks, EnabledChecks::NONE);
+    }
+
+    #[test]
+    fn test_get_field_roundtrips() {
+        let mut limits = RiskLimits::default();
+        limits.set_field("max_non_resting_orders", "25").unwrap();
+        limits.set_field("check_non_resting_rate", "false").unwrap();
+        limits.set_field("illiquid_symbols", "42, 1_7").unwrap();
+        let mut copy = RiskLimits {
+            max_position: 0,
+            checks: EnabledChecks::ALL,
+            ..RiskLimits::default()
+        };
+        for name in RiskLimits::FIELDS
+            .iter()
+            .chain(&RiskLimits::CHECK_FIELDS)
+            .chain(&RiskLimits::LIST_FIELDS)
+        {
+            let value = limits.get_field(name).unwrap();
+            copy.set_field(name, &value).unwrap();
+        }
+        assert_eq!(copy, limits);
+        assert_eq!(limits.get_field("max_leverage"), None);
+    }
+
+    #[test]
+    fn test_illiquid_symbols_tighten_limits_and_margin() {
+        let mut limits = RiskLimits {
+            max_position: 1001,
+            ..RiskLimits::default()
+        };
+        limits.set_field("illiquid_symbols", "17,42").unwrap();
+        assert_eq!(limits.get_field("illiquid_symbols").unwrap(), "17,42");
+        assert!(limits.is_illiquid(42) && !limits.is_illiquid(1));
+
+        let illiquid = limits.for_symbol(42);
+        assert_eq!(illiquid.max_position, 500);
+        assert_eq!(illiquid.max_order_size, 50);
+        assert_eq!(illiquid.max_through_bbo_bps, 200);
+        assert_eq!(illiquid.max_notional, limits.max_notional);
+        assert!(illiquid.illiquid_symbols.is_empty());
+        assert!(matches!(limits.for_symbol(1), Cow::Borrowed(_)));
+
+        // 1.5x, rounded up; liquid symbols are unchanged.
+        assert_eq!(limits.illiquid_margin(17, 1_001), 1_502);
+        assert_eq!(limits.illiquid_margin(1, 1_001), 1_001);
+        // A multiplier below 1x or a factor above 1x never loosens.
+        limits.illiquid_margin_multiplier_bps = 5_000;
+        limits.illiquid_limit_factor_bps = 20_000;
+        assert_eq!(limits.illiquid_margin(17, 1_001), 1_001);
+        assert_eq!(limits.illiquid().max_position, 1001);
+
+        limits.set_field("illiquid_symbols", "").unwrap();
+        assert!(limits.illiquid_symbols.is_empty());
+        assert_eq!(
+            limits.set_field("illiquid_symbols", "1,x"),
+            Err(LimitFieldError::InvalidValue)
+        );
+    }
+
     #[test]
     fn test_debug_format() {
         let limits = RiskLimits::default();
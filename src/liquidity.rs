@@ -0,0 +1,191 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 流動性に応じた建玉上限。
+//!
+//! 固定のロット数の建玉上限は、出来高の少ない銘柄では手仕舞えない大きさの
+//! 建玉を許してしまう。[`LiquidityLimit`] は建玉の上限を平均日次出来高
+//! （[`MarketContext::adv`]）と、手仕舞いの側の板の表示数量
+//! （[`MarketContext::displayed_depth`]）の倍率で決める。買い建玉は売りで
+//! 手仕舞うので買い板、売り建玉は売り板の数量を使う。
+//!
+//! 両方を設定した場合は小さい方が上限。市場データの無い項目は省略し、
+//! どちらも無ければ判定しない。上限を超えていても建玉を減らす注文は通す。
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{position_headroom, PreTradeChecker, RiskReject};
+use crate::market::MarketContext;
+
+// ---------------------------------------------------------------------------
+// LiquidityLimit
+// ---------------------------------------------------------------------------
+
+/// 出来高と板の表示数量に対する建玉上限。`None` の項目は判定しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LiquidityLimit {
+    /// 平均日次出来高に対する建玉上限（bps、2500 = ADV の 25%）。
+    pub max_adv_bps: Option<u32>,
+    /// 手仕舞いの側の板の表示数量に対する建玉上限（bps、30000 = 3 倍）。
+    pub max_depth_bps: Option<u32>,
+}
+
+impl LiquidityLimit {
+    /// `symbol_hash` のネット建玉 `net` に適用する建玉上限（lots）。市場データが
+    /// 無く決まらなければ `None`。
+    #[must_use]
+    pub fn limit<M: MarketContext + ?Sized>(
+        &self,
+        market: &M,
+        symbol_hash: u64,
+        net: i64,
+    ) -> Option<u64> {
+        let of = |base: u64, bps: u32| {
+            (u128::from(base) * u128::from(bps) / 10_000).min(u128::from(u64::MAX)) as u64
+        };
+        let by_adv = self
+            .max_adv_bps
+            .and_then(|bps| Some(of(market.adv(symbol_hash)?, bps)));
+        // 買い建玉は買い板へ売って、売り建玉は売り板から買って手仕舞う。
+        let exit_side = if net >= 0 { Side::Bid } else { Side::Ask };
+        let by_depth = self
+            .max_depth_bps
+            .and_then(|bps| Some(of(market.displayed_depth(symbol_hash, exit_side)?, bps)));
+        match (by_adv, by_depth) {
+            (Some(a), Some(d)) => Some(a.min(d)),
+            (a, d) => a.or(d),
+        }
+    }
+
+    /// ネット建玉 `current` の `symbol_hash` に `order` を発注したときの建玉を
+    /// 判定する。
+    ///
+    /// # Errors
+    ///
+    /// 約定後の建玉が上限を超え、かつ建玉が増える場合は
+    /// [`RiskReject::LiquidityLimitBreached`]。
+    pub fn check<M: MarketContext + ?Sized>(
+        &self,
+        market: &M,
+        symbol_hash: u64,
+        current: i64,
+        order: &Order,
+    ) -> Result<(), RiskReject> {
+        let after = match order.side {
+            Side::Bid => current.saturating_add_unsigned(order.quantity),
+            Side::Ask => current.saturating_sub_unsigned(order.quantity),
+        };
+        if after.unsigned_abs() <= current.unsigned_abs() {
+            return Ok(());
+        }
+        let Some(limit) = self.limit(market, symbol_hash, after) else {
+            return Ok(());
+        };
+        if after.unsigned_abs() > limit {
+            return Err(RiskReject::LiquidityLimitBreached {
+                symbol_hash,
+                after,
+                limit,
+                max_allowed: position_headroom(current, order.side, limit),
+            });
+        }
+        Ok(())
+    }
+
+    /// `position` の銘柄で [`Self::check`] の後に `checker` で判定する。
+    /// `position` が無ければ `checker` だけで判定する。
+    ///
+    /// # Errors
+    ///
+    /// 流動性に対する建玉上限の拒否、それ以外は `checker` の拒否。
+    pub fn check_order_in_market<M: MarketContext + ?Sized>(
+        &self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+        market: &M,
+    ) -> Result<(), RiskReject> {
+        if let Some(p) = position {
+            self.check(market, p.symbol_hash, p.net_quantity, order)?;
+        }
+        checker.check_order_in_market(order, position, market)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{MarketSnapshot, SymbolMarket};
+    use crate::test_utils::OrderBuilder;
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity).build()
+    }
+
+    fn market() -> MarketSnapshot {
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            1,
+            SymbolMarket {
+                adv: Some(10_000),
+                ..SymbolMarket::default()
+            },
+        );
+        market.set_depth(1, 500, 200);
+        market
+    }
+
+    #[test]
+    fn limit_by_adv_and_exit_side_depth() {
+        let market = market();
+        let limit = LiquidityLimit {
+            max_adv_bps: Some(1_000),
+            max_depth_bps: Some(30_000),
+        };
+        // 買い建玉: min(ADV 10% = 1_000, 買い板 500 × 3 = 1_500)。
+        assert_eq!(limit.limit(&market, 1, 10), Some(1_000));
+        // 売り建玉: 売り板 200 × 3 = 600。
+        assert_eq!(limit.limit(&market, 1, -10), Some(600));
+        assert_eq!(limit.limit(&market, 2, 10), None);
+        let adv_only = LiquidityLimit {
+            max_depth_bps: None,
+            ..limit
+        };
+        assert_eq!(adv_only.limit(&market, 1, -10), Some(1_000));
+    }
+
+    #[test]
+    fn blocks_only_increases_beyond_limit() {
+        let market = market();
+        let limit = LiquidityLimit {
+            max_adv_bps: None,
+            max_depth_bps: Some(30_000),
+        };
+        assert!(limit
+            .check(&market, 1, -500, &order(Side::Ask, 100))
+            .is_ok());
+        assert_eq!(
+            limit.check(&market, 1, -500, &order(Side::Ask, 150)),
+            Err(RiskReject::LiquidityLimitBreached {
+                symbol_hash: 1,
+                after: -650,
+                limit: 600,
+                max_allowed: 100,
+            })
+        );
+        // 上限を超えた建玉でも減らす注文は通す。
+        assert!(limit
+            .check(&market, 1, -900, &order(Side::Bid, 100))
+            .is_ok());
+        // 市場データの無い銘柄は判定しない。
+        assert!(limit
+            .check(&market, 2, 0, &order(Side::Bid, 1_000_000))
+            .is_ok());
+    }
+}
@@ -8,12 +8,62 @@
 //! All monetary values are expressed in the same tick unit used throughout
 //! ALICE-Ledger.  Integer arithmetic with i128 intermediates is used to
 //! prevent overflow when multiplying large prices by large quantities.
+//!
+//! Calendar spreads — offsetting positions in different maturities of the same
+//! product — are far less risky than either leg outright. The `product_*`
+//! methods net such legs and apply [`MarginParams::spread_credit_bps`] to the
+//! matched quantity instead of charging two full outright margins.
+//!
+//! A cash-and-carry book — long spot hedged with a short future or perpetual on
+//! the same underlying — carries little directional risk either. The
+//! `underlying_*` methods match the spot leg against derivative legs whose
+//! [`Instrument::underlying_hash`] names it, in underlying units, and waive
+//! [`MarginParams::cross_margin_offset_bps`] of the matched margin.
+//!
+//! Options books are usually hedged with futures rather than held outright.
+//! The `delta_hedged_*` methods take option legs tagged with a caller-supplied
+//! delta, charge their delta-equivalent exposure at the underlying price, and
+//! net it against the opposite underlying legs, keeping
+//! [`MarginParams::delta_hedge_haircut_bps`] of the matched margin.
+//!
+//! A margin figure is only as good as the mark behind it.  With
+//! [`MarginCalculator::with_mark_staleness`], the `*_marked` methods take the
+//! mark's timestamp and either refuse a mark older than the configured
+//! horizon or load the requirement with a punitive add-on
+//! ([`StaleMarkPolicy`]).
+//!
+//! The bps rates are calibrated to a holding period of
+//! [`MarginParams::mpor_base_days`]. A product liquidated over a different
+//! margin period of risk — two days for a cleared product, ten for a
+//! bilateral one — is charged `sqrt(mpor / mpor_base_days)` times the rate on
+//! every instrument-aware requirement. The period defaults to
+//! [`MarginParams::mpor_days`] and is overridden per product with
+//! [`MarginCalculator::with_product_mpor`].
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::Position;
+
+use crate::check::{ArithmeticMode, RiskReject};
+use crate::fixed::Fixed;
+use crate::instrument::Instrument;
+use crate::market::MarketContext;
+use crate::parallel;
+
+extern crate alloc;
 
 // Reciprocal constant retained for documentation purposes; actual integer
 // division uses the i128 path below.
 #[allow(dead_code)]
 const RCP_BPS: f64 = 1.0 / 10000.0;
 
+// Exposures on an underlying are compared at 10^-9 of a unit so that
+// fractional lots are not truncated to whole units.
+const UNDERLYING_UNIT: i128 = 1_000_000_000;
+
+// Fixed-point scale of the MPOR factor.
+const MPOR_FACTOR_UNIT: u128 = 1_000_000_000;
+
 // ---------------------------------------------------------------------------
 // MarginParams
 // ---------------------------------------------------------------------------
@@ -27,6 +77,22 @@ pub struct MarginParams {
     pub initial_margin_bps: u32,
     /// Maintenance margin rate in basis points (e.g., 500 = 5%).
     pub maintenance_margin_bps: u32,
+    /// Share of the outright margin on calendar-spread legs that is waived,
+    /// in basis points (e.g., 7500 = 75% credit). 0 disables the credit.
+    pub spread_credit_bps: u32,
+    /// Share of the margin on offsetting spot and derivative legs of the same
+    /// underlying that is waived, in basis points. 0 disables the offset.
+    pub cross_margin_offset_bps: u32,
+    /// Share of the delta-hedge offset that is withheld, in basis points
+    /// (e.g., 2000 = 80% of the matched margin is waived). 10000 disables
+    /// the offset.
+    pub delta_hedge_haircut_bps: u32,
+    /// Margin period of risk the bps rates are calibrated to, in days.
+    pub mpor_base_days: u32,
+    /// Margin period of risk of products without their own
+    /// ([`MarginCalculator::with_product_mpor`]), in days. Instrument-aware
+    /// requirements are scaled by `sqrt(mpor_days / mpor_base_days)`.
+    pub mpor_days: u32,
 }
 
 impl Default for MarginParams {
@@ -34,10 +100,49 @@ impl Default for MarginParams {
         Self {
             initial_margin_bps: 1000,    // 10%
             maintenance_margin_bps: 500, // 5%
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// MarkStaleness
+// ---------------------------------------------------------------------------
+
+/// What the `*_marked` margin methods do with a mark older than the horizon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleMarkPolicy {
+    /// Refuse to compute a requirement ([`StaleMark`]).
+    Refuse,
+    /// Compute the requirement and add `bps` of it on top.
+    AddOn {
+        /// Punitive add-on in basis points of the requirement.
+        bps: u32,
+    },
+}
+
+/// Maximum age of a mark price before it is treated as stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkStaleness {
+    /// Maximum age of the mark, in nanoseconds.
+    pub horizon_ns: u64,
+    /// Treatment of a stale mark.
+    pub policy: StaleMarkPolicy,
+}
+
+/// The mark behind a margin computation is older than the horizon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleMark {
+    /// Age of the mark (ns), or `None` if its timestamp is unknown.
+    pub age_ns: Option<u64>,
+    /// Configured horizon (ns).
+    pub horizon_ns: u64,
+}
+
 // ---------------------------------------------------------------------------
 // MarginCalculator
 // ---------------------------------------------------------------------------
@@ -45,6 +150,10 @@ impl Default for MarginParams {
 /// Computes initial and maintenance margin requirements.
 pub struct MarginCalculator {
     params: MarginParams,
+    staleness: Option<MarkStaleness>,
+    /// `product_hash` → margin period of risk in days.
+    product_mpor: BTreeMap<u64, u32>,
+    arithmetic_mode: ArithmeticMode,
 }
 
 impl MarginCalculator {
@@ -52,18 +161,197 @@ impl MarginCalculator {
     #[inline(always)]
     #[must_use]
     pub const fn new(params: MarginParams) -> Self {
-        Self { params }
+        Self {
+            params,
+            staleness: None,
+            product_mpor: BTreeMap::new(),
+            arithmetic_mode: ArithmeticMode::Saturating,
+        }
+    }
+
+    /// Select how overflow in [`Self::try_initial_margin`] and
+    /// [`Self::try_maintenance_margin`] is handled (builder style).
+    ///
+    /// Defaults to [`ArithmeticMode::Saturating`]; pass the checker's
+    /// [`PreTradeChecker::arithmetic_mode`](crate::PreTradeChecker::arithmetic_mode)
+    /// so margin and pre-trade checks treat overflow alike.
+    #[inline(always)]
+    #[must_use]
+    pub const fn with_arithmetic_mode(mut self, mode: ArithmeticMode) -> Self {
+        self.arithmetic_mode = mode;
+        self
+    }
+
+    /// The configured [`ArithmeticMode`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn arithmetic_mode(&self) -> ArithmeticMode {
+        self.arithmetic_mode
+    }
+
+    /// Set the margin period of risk of `product_hash`, in days (builder
+    /// style).
+    #[must_use]
+    pub fn with_product_mpor(mut self, product_hash: u64, days: u32) -> Self {
+        self.product_mpor.insert(product_hash, days);
+        self
+    }
+
+    /// Margin period of risk of `product_hash`, in days.
+    #[must_use]
+    pub fn mpor_days(&self, product_hash: u64) -> u32 {
+        self.product_mpor
+            .get(&product_hash)
+            .copied()
+            .unwrap_or(self.params.mpor_days)
+    }
+
+    /// Scale a requirement calibrated to [`MarginParams::mpor_base_days`] to
+    /// the margin period of risk of `product_hash`, rounding up.
+    #[must_use]
+    pub fn mpor_scaled(&self, product_hash: u64, margin: i64) -> i64 {
+        self.scale_mpor(product_hash, i128::from(margin))
+            .min(i64::MAX as i128) as i64
+    }
+
+    /// `margin * sqrt(mpor_days / mpor_base_days)`, rounded away from zero.
+    /// A zero base or an equal period leaves `margin` unchanged.
+    fn scale_mpor(&self, product_hash: u64, margin: i128) -> i128 {
+        let days = self.mpor_days(product_hash);
+        let base = self.params.mpor_base_days;
+        if base == 0 || days == base {
+            return margin;
+        }
+        let factor = (u128::from(days) * MPOR_FACTOR_UNIT * MPOR_FACTOR_UNIT / u128::from(base))
+            .isqrt() as i128;
+        let unit = MPOR_FACTOR_UNIT as i128;
+        let scaled = margin.saturating_mul(factor);
+        scaled / unit + i128::from(scaled % unit != 0) * scaled.signum()
+    }
+
+    /// Guard the `*_marked` methods against stale marks (builder style).
+    #[must_use]
+    pub const fn with_mark_staleness(mut self, staleness: MarkStaleness) -> Self {
+        self.staleness = Some(staleness);
+        self
+    }
+
+    /// Configured mark staleness guard, if any.
+    #[must_use]
+    pub const fn mark_staleness(&self) -> Option<&MarkStaleness> {
+        self.staleness.as_ref()
+    }
+
+    /// Return the staleness of a mark taken at `mark_ns` (`None` if unknown),
+    /// or `None` if it is within the horizon or no guard is configured.
+    #[must_use]
+    pub fn stale_mark(&self, mark_ns: Option<u64>, now_ns: u64) -> Option<StaleMark> {
+        let staleness = self.staleness?;
+        let age_ns = mark_ns.map(|at| now_ns.saturating_sub(at));
+        age_ns
+            .is_none_or(|age| age > staleness.horizon_ns)
+            .then_some(StaleMark {
+                age_ns,
+                horizon_ns: staleness.horizon_ns,
+            })
+    }
+
+    /// Apply the stale-mark policy to a requirement computed from a mark
+    /// taken at `mark_ns`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StaleMark`] when the mark is stale and the policy is
+    /// [`StaleMarkPolicy::Refuse`].
+    pub fn apply_mark_staleness(
+        &self,
+        margin: i64,
+        mark_ns: Option<u64>,
+        now_ns: u64,
+    ) -> Result<i64, StaleMark> {
+        let Some(stale) = self.stale_mark(mark_ns, now_ns) else {
+            return Ok(margin);
+        };
+        match self.staleness.map(|s| s.policy) {
+            Some(StaleMarkPolicy::AddOn { bps }) => {
+                let add_on = i128::from(margin).saturating_mul(i128::from(bps)) / 10_000;
+                Ok(i128::from(margin)
+                    .saturating_add(add_on)
+                    .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64)
+            }
+            _ => Err(stale),
+        }
+    }
+
+    /// [`Self::initial_margin`] at a mark taken at `mark_ns`, subject to the
+    /// stale-mark guard.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StaleMark`] when the mark is stale and the policy is
+    /// [`StaleMarkPolicy::Refuse`].
+    pub fn initial_margin_marked(
+        &self,
+        price: i64,
+        quantity: u64,
+        mark_ns: u64,
+        now_ns: u64,
+    ) -> Result<i64, StaleMark> {
+        self.apply_mark_staleness(self.initial_margin(price, quantity), Some(mark_ns), now_ns)
+    }
+
+    /// [`Self::maintenance_margin`] at a mark taken at `mark_ns`, subject to
+    /// the stale-mark guard.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StaleMark`] when the mark is stale and the policy is
+    /// [`StaleMarkPolicy::Refuse`].
+    pub fn maintenance_margin_marked(
+        &self,
+        price: i64,
+        quantity: u64,
+        mark_ns: u64,
+        now_ns: u64,
+    ) -> Result<i64, StaleMark> {
+        self.apply_mark_staleness(
+            self.maintenance_margin(price, quantity),
+            Some(mark_ns),
+            now_ns,
+        )
+    }
+
+    /// [`Self::maintenance_margin_in`] using the mark timestamp supplied by
+    /// `market`, subject to the stale-mark guard.
+    ///
+    /// Returns `None` when `market` has no reference price for the symbol.
+    #[must_use]
+    pub fn maintenance_margin_in_at<M: MarketContext + ?Sized>(
+        &self,
+        position: &Position,
+        market: &M,
+        now_ns: u64,
+    ) -> Option<Result<i64, StaleMark>> {
+        let margin = self.maintenance_margin_in(position, market)?;
+        Some(self.apply_mark_staleness(
+            margin,
+            market.mark_timestamp_ns(position.symbol_hash),
+            now_ns,
+        ))
     }
 
     /// Compute the initial margin required to open a position.
     ///
-    /// Formula: `price * quantity * initial_margin_bps / 10000`
+    /// Formula: `|price| * quantity * initial_margin_bps / 10000`
     ///
-    /// Uses an i128 intermediate to prevent overflow on large values.
+    /// Margin scales with the magnitude of the price, so an instrument
+    /// trading below zero still requires positive margin.  Uses an i128
+    /// intermediate to prevent overflow on large values.
     #[inline(always)]
     #[must_use]
     pub fn initial_margin(&self, price: i64, quantity: u64) -> i64 {
         let numerator = (price as i128)
+            .abs()
             .saturating_mul(quantity as i128)
             .saturating_mul(self.params.initial_margin_bps as i128);
         (numerator / 10_000).min(i64::MAX as i128) as i64
@@ -71,18 +359,502 @@ impl MarginCalculator {
 
     /// Compute the maintenance margin required to hold an open position.
     ///
-    /// Formula: `price * quantity * maintenance_margin_bps / 10000`
+    /// Formula: `|price| * quantity * maintenance_margin_bps / 10000`
     ///
-    /// Uses an i128 intermediate to prevent overflow on large values.
+    /// Margin scales with the magnitude of the price, so an instrument
+    /// trading below zero still requires positive margin.  Uses an i128
+    /// intermediate to prevent overflow on large values.
     #[inline(always)]
     #[must_use]
     pub fn maintenance_margin(&self, price: i64, quantity: u64) -> i64 {
         let numerator = (price as i128)
+            .abs()
             .saturating_mul(quantity as i128)
             .saturating_mul(self.params.maintenance_margin_bps as i128);
         (numerator / 10_000).min(i64::MAX as i128) as i64
     }
 
+    /// [`Self::initial_margin`] under the configured [`ArithmeticMode`].
+    ///
+    /// In [`ArithmeticMode::Checked`] a result that does not fit in `i64` is
+    /// [`RiskReject::ArithmeticOverflow`], so mis-scaled inputs surface as
+    /// errors rather than capped values; otherwise it saturates.
+    ///
+    /// # Errors
+    ///
+    /// [`RiskReject::ArithmeticOverflow`] in checked mode only.
+    #[inline(always)]
+    pub fn try_initial_margin(&self, price: i64, quantity: u64) -> Result<i64, RiskReject> {
+        bps_in_mode(
+            self.arithmetic_mode,
+            price,
+            quantity,
+            self.params.initial_margin_bps,
+        )
+    }
+
+    /// [`Self::maintenance_margin`] under the configured [`ArithmeticMode`].
+    ///
+    /// See [`Self::try_initial_margin`].
+    ///
+    /// # Errors
+    ///
+    /// [`RiskReject::ArithmeticOverflow`] in checked mode only.
+    #[inline(always)]
+    pub fn try_maintenance_margin(&self, price: i64, quantity: u64) -> Result<i64, RiskReject> {
+        bps_in_mode(
+            self.arithmetic_mode,
+            price,
+            quantity,
+            self.params.maintenance_margin_bps,
+        )
+    }
+
+    /// Initial margin for `quantity` scaled units of `instrument` at `price`
+    /// per whole lot.
+    ///
+    /// Formula: `|price| * quantity * multiplier * initial_margin_bps / 10000 / 10^quantity_scale`
+    #[must_use]
+    pub fn instrument_initial_margin(
+        &self,
+        instrument: &Instrument,
+        price: i64,
+        quantity: u64,
+    ) -> i64 {
+        let margin = scaled_bps(instrument, price, quantity, self.params.initial_margin_bps);
+        self.mpor_scaled(instrument.product_hash, margin)
+    }
+
+    /// Maintenance margin for `quantity` scaled units of `instrument` at
+    /// `price` per whole lot.
+    ///
+    /// See [`Self::instrument_initial_margin`].
+    #[must_use]
+    pub fn instrument_maintenance_margin(
+        &self,
+        instrument: &Instrument,
+        price: i64,
+        quantity: u64,
+    ) -> i64 {
+        let margin = scaled_bps(
+            instrument,
+            price,
+            quantity,
+            self.params.maintenance_margin_bps,
+        );
+        self.mpor_scaled(instrument.product_hash, margin)
+    }
+
+    /// [`Self::instrument_initial_margin`] for a fixed-point `price` per whole
+    /// lot and `lots`, returned at `OUT` decimal places.
+    ///
+    /// `price` and `lots` are converted to ticks and scaled units of
+    /// `instrument` without rounding, and the margin back to fixed point.
+    /// Returns `None` when a conversion would drop a digit or overflow.
+    #[must_use]
+    pub fn instrument_initial_margin_fixed<const P: u32, const Q: u32, const OUT: u32>(
+        &self,
+        instrument: &Instrument,
+        price: Fixed<P>,
+        lots: Fixed<Q>,
+    ) -> Option<Fixed<OUT>> {
+        let price_ticks = instrument.price_from_fixed(price)?;
+        let units = instrument.units_from_fixed(lots)?.unsigned_abs();
+        instrument.price_to_fixed(self.instrument_initial_margin(instrument, price_ticks, units))
+    }
+
+    /// [`Self::instrument_maintenance_margin`] for a fixed-point `price` per
+    /// whole lot and `lots`, returned at `OUT` decimal places.
+    ///
+    /// See [`Self::instrument_initial_margin_fixed`].
+    #[must_use]
+    pub fn instrument_maintenance_margin_fixed<const P: u32, const Q: u32, const OUT: u32>(
+        &self,
+        instrument: &Instrument,
+        price: Fixed<P>,
+        lots: Fixed<Q>,
+    ) -> Option<Fixed<OUT>> {
+        let price_ticks = instrument.price_from_fixed(price)?;
+        let units = instrument.units_from_fixed(lots)?.unsigned_abs();
+        instrument.price_to_fixed(self.instrument_maintenance_margin(
+            instrument,
+            price_ticks,
+            units,
+        ))
+    }
+
+    /// Initial margin for `position` marked at the reference price supplied
+    /// by `market`.
+    ///
+    /// Returns `None` when `market` has no reference price for the symbol.
+    #[must_use]
+    pub fn initial_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        position: &Position,
+        market: &M,
+    ) -> Option<i64> {
+        let price = market.reference_price(position.symbol_hash)?;
+        Some(self.initial_margin(price, position.net_quantity.unsigned_abs()))
+    }
+
+    /// Maintenance margin for `position` marked at the reference price
+    /// supplied by `market`.
+    ///
+    /// Returns `None` when `market` has no reference price for the symbol.
+    #[must_use]
+    pub fn maintenance_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        position: &Position,
+        market: &M,
+    ) -> Option<i64> {
+        let price = market.reference_price(position.symbol_hash)?;
+        Some(self.maintenance_margin(price, position.net_quantity.unsigned_abs()))
+    }
+
+    /// Initial margin summed over `positions`, each marked at the reference
+    /// price supplied by `market`.
+    ///
+    /// Positions are margined outright, with no spread or offset credit.
+    /// Returns `None` when any position has no reference price.
+    #[must_use]
+    pub fn portfolio_initial_margin_in<M: MarketContext + Sync + ?Sized>(
+        &self,
+        positions: &[Position],
+        market: &M,
+    ) -> Option<i64> {
+        Self::sum_margin(positions, |p| self.initial_margin_in(p, market))
+    }
+
+    /// Maintenance margin summed over `positions`, each marked at the
+    /// reference price supplied by `market`.
+    ///
+    /// See [`Self::portfolio_initial_margin_in`].
+    #[must_use]
+    pub fn portfolio_maintenance_margin_in<M: MarketContext + Sync + ?Sized>(
+        &self,
+        positions: &[Position],
+        market: &M,
+    ) -> Option<i64> {
+        Self::sum_margin(positions, |p| self.maintenance_margin_in(p, market))
+    }
+
+    /// Saturating sum of `margin` over `positions`, in [`parallel`] chunks
+    /// (evaluated in parallel with the `rayon` feature).
+    fn sum_margin<F>(positions: &[Position], margin: F) -> Option<i64>
+    where
+        F: Fn(&Position) -> Option<i64> + Sync + Send,
+    {
+        parallel::map_chunks(positions, |chunk| {
+            chunk
+                .iter()
+                .try_fold(0_i64, |acc, p| Some(acc.saturating_add(margin(p)?)))
+        })
+        .into_iter()
+        .try_fold(0_i64, |acc, m| Some(acc.saturating_add(m?)))
+    }
+
+    /// Initial margin for every holding of `product_hash`, with the calendar
+    /// spread credit applied.
+    ///
+    /// `holdings` pairs each instrument with its net quantity in scaled
+    /// units; instruments of other products are ignored. Each leg is marked at the reference price
+    /// supplied by `market`. Returns `None` when a leg of the product has no
+    /// reference price.
+    #[must_use]
+    pub fn product_initial_margin_in<M: MarketContext + Sync + ?Sized>(
+        &self,
+        product_hash: u64,
+        holdings: &[(Instrument, i64)],
+        market: &M,
+    ) -> Option<i64> {
+        self.product_margin(
+            product_hash,
+            holdings,
+            market,
+            self.params.initial_margin_bps,
+        )
+    }
+
+    /// Maintenance margin for every holding of `product_hash`, with the
+    /// calendar spread credit applied.
+    ///
+    /// See [`Self::product_initial_margin_in`].
+    #[must_use]
+    pub fn product_maintenance_margin_in<M: MarketContext + Sync + ?Sized>(
+        &self,
+        product_hash: u64,
+        holdings: &[(Instrument, i64)],
+        market: &M,
+    ) -> Option<i64> {
+        self.product_margin(
+            product_hash,
+            holdings,
+            market,
+            self.params.maintenance_margin_bps,
+        )
+    }
+
+    /// Outright margin on both sides, less the spread credit on the matched
+    /// quantity.
+    ///
+    /// Long and short lots of the product are paired up to
+    /// `min(long_lots, short_lots)`; the matched share of each side's outright
+    /// margin is pro-rated by lots, and `spread_credit_bps` of it is waived.
+    fn product_margin<M: MarketContext + Sync + ?Sized>(
+        &self,
+        product_hash: u64,
+        holdings: &[(Instrument, i64)],
+        market: &M,
+        bps: u32,
+    ) -> Option<i64> {
+        // (lots, outright margin) per side: [long, short], summed per
+        // `parallel` chunk of holdings and then across chunks in order.
+        let partials = parallel::map_chunks(holdings, |chunk| {
+            let mut sides = [(0_i128, 0_i128); 2];
+            for (instrument, net) in chunk {
+                if instrument.product_hash != product_hash || *net == 0 {
+                    continue;
+                }
+                let price = market.reference_price(instrument.symbol_hash)?;
+                let lots = i128::from(net.unsigned_abs());
+                let margin = self.scale_mpor(
+                    instrument.product_hash,
+                    leg_margin(instrument, price, *net, bps),
+                );
+                let side = &mut sides[usize::from(*net < 0)];
+                side.0 = side.0.saturating_add(lots);
+                side.1 = side.1.saturating_add(margin);
+            }
+            Some(sides)
+        });
+        let mut sides = [(0_i128, 0_i128); 2];
+        for partial in partials {
+            for (side, (lots, margin)) in sides.iter_mut().zip(partial?) {
+                side.0 = side.0.saturating_add(lots);
+                side.1 = side.1.saturating_add(margin);
+            }
+        }
+        let [(long_lots, long_margin), (short_lots, short_margin)] = sides;
+        let gross = long_margin.saturating_add(short_margin);
+        let matched = long_lots.min(short_lots);
+        if matched == 0 {
+            return Some(gross.min(i64::MAX as i128) as i64);
+        }
+        let matched_margin = (long_margin.saturating_mul(matched) / long_lots)
+            .saturating_add(short_margin.saturating_mul(matched) / short_lots);
+        let credit =
+            matched_margin.saturating_mul(i128::from(self.params.spread_credit_bps)) / 10_000;
+        Some(gross.saturating_sub(credit).min(i64::MAX as i128) as i64)
+    }
+
+    /// Initial margin for the spot leg of `underlying_hash` and every
+    /// derivative on it, with the spot/derivative offset applied.
+    ///
+    /// `holdings` pairs each instrument with its net quantity in scaled units.
+    /// The spot leg is the instrument whose `symbol_hash` is `underlying_hash`;
+    /// derivative legs are those whose [`Instrument::underlying_hash`] names
+    /// it. Other instruments are ignored. Returns `None` when a leg has no
+    /// reference price.
+    #[must_use]
+    pub fn underlying_initial_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        underlying_hash: u64,
+        holdings: &[(Instrument, i64)],
+        market: &M,
+    ) -> Option<i64> {
+        self.underlying_margin(
+            underlying_hash,
+            holdings,
+            market,
+            self.params.initial_margin_bps,
+        )
+    }
+
+    /// Maintenance margin for the spot leg of `underlying_hash` and every
+    /// derivative on it, with the spot/derivative offset applied.
+    ///
+    /// See [`Self::underlying_initial_margin_in`].
+    #[must_use]
+    pub fn underlying_maintenance_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        underlying_hash: u64,
+        holdings: &[(Instrument, i64)],
+        market: &M,
+    ) -> Option<i64> {
+        self.underlying_margin(
+            underlying_hash,
+            holdings,
+            market,
+            self.params.maintenance_margin_bps,
+        )
+    }
+
+    /// Outright margin on every leg, less the offset on the matched exposure.
+    ///
+    /// Exposures are compared in underlying units (scaled quantity times the
+    /// multiplier). The spot leg is matched against the derivative legs on the
+    /// opposite side up to the smaller of the two; the matched share of each
+    /// side's outright margin is pro-rated by units, and
+    /// `cross_margin_offset_bps` of it is waived.
+    fn underlying_margin<M: MarketContext + ?Sized>(
+        &self,
+        underlying_hash: u64,
+        holdings: &[(Instrument, i64)],
+        market: &M,
+        bps: u32,
+    ) -> Option<i64> {
+        let mut gross = 0_i128;
+        // (underlying units, outright margin) of the spot leg, and of the
+        // derivative legs per side: [long, short].
+        let mut spot = (0_i128, 0_i128);
+        let mut derivatives = [(0_i128, 0_i128); 2];
+        for (instrument, net) in holdings {
+            let is_spot = instrument.symbol_hash == underlying_hash;
+            if (!is_spot && instrument.underlying_hash != Some(underlying_hash)) || *net == 0 {
+                continue;
+            }
+            let price = market.reference_price(instrument.symbol_hash)?;
+            let margin = self.scale_mpor(
+                instrument.product_hash,
+                leg_margin(instrument, price, *net, bps),
+            );
+            gross = gross.saturating_add(margin);
+            let units = underlying_units(instrument, *net);
+            if is_spot {
+                spot = (units, margin);
+            } else {
+                let side = &mut derivatives[usize::from(units < 0)];
+                side.0 = side.0.saturating_add(units.abs());
+                side.1 = side.1.saturating_add(margin);
+            }
+        }
+        // A long spot leg is hedged by short derivatives, and vice versa.
+        let (hedge_units, hedge_margin) = derivatives[usize::from(spot.0 > 0)];
+        let spot_units = spot.0.abs();
+        let matched = spot_units.min(hedge_units);
+        if matched == 0 {
+            return Some(gross.min(i64::MAX as i128) as i64);
+        }
+        let matched_margin = (spot.1.saturating_mul(matched) / spot_units)
+            .saturating_add(hedge_margin.saturating_mul(matched) / hedge_units);
+        let credit =
+            matched_margin.saturating_mul(i128::from(self.params.cross_margin_offset_bps)) / 10_000;
+        Some(gross.saturating_sub(credit).min(i64::MAX as i128) as i64)
+    }
+
+    /// Initial margin for the underlying legs of `underlying_hash` (see
+    /// [`Self::underlying_initial_margin_in`]) and the delta-equivalent
+    /// exposure of `deltas`, with the delta-hedge offset applied.
+    ///
+    /// `deltas` pairs each option instrument with its net quantity in scaled
+    /// units and its delta per underlying unit in basis points (10000 = 1.0,
+    /// negative for puts). Legs whose [`Instrument::underlying_hash`] is not
+    /// `underlying_hash` are ignored. Returns `None` when a leg, or the
+    /// underlying when there is delta exposure, has no reference price.
+    #[must_use]
+    pub fn delta_hedged_initial_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        underlying_hash: u64,
+        holdings: &[(Instrument, i64)],
+        deltas: &[(Instrument, i64, i32)],
+        market: &M,
+    ) -> Option<i64> {
+        self.delta_hedged_margin(
+            underlying_hash,
+            holdings,
+            deltas,
+            market,
+            self.params.initial_margin_bps,
+        )
+    }
+
+    /// Maintenance margin for the underlying legs of `underlying_hash` and the
+    /// delta-equivalent exposure of `deltas`, with the delta-hedge offset
+    /// applied.
+    ///
+    /// See [`Self::delta_hedged_initial_margin_in`].
+    #[must_use]
+    pub fn delta_hedged_maintenance_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        underlying_hash: u64,
+        holdings: &[(Instrument, i64)],
+        deltas: &[(Instrument, i64, i32)],
+        market: &M,
+    ) -> Option<i64> {
+        self.delta_hedged_margin(
+            underlying_hash,
+            holdings,
+            deltas,
+            market,
+            self.params.maintenance_margin_bps,
+        )
+    }
+
+    /// Underlying margin plus delta-equivalent margin, less the offset on the
+    /// matched exposure.
+    ///
+    /// The net delta of the option legs is charged at the underlying's
+    /// reference price. If it points against the net underlying exposure, the
+    /// two are matched up to the smaller of the two; the matched share of each
+    /// side's margin is pro-rated by units, and all but
+    /// `delta_hedge_haircut_bps` of it is waived.
+    fn delta_hedged_margin<M: MarketContext + ?Sized>(
+        &self,
+        underlying_hash: u64,
+        holdings: &[(Instrument, i64)],
+        deltas: &[(Instrument, i64, i32)],
+        market: &M,
+        bps: u32,
+    ) -> Option<i64> {
+        let hedge_margin =
+            i128::from(self.underlying_margin(underlying_hash, holdings, market, bps)?);
+        let delta_units = deltas
+            .iter()
+            .filter(|(instrument, _, _)| instrument.underlying_hash == Some(underlying_hash))
+            .fold(0_i128, |acc, (instrument, net, delta_bps)| {
+                acc.saturating_add(
+                    underlying_units(instrument, *net).saturating_mul(i128::from(*delta_bps))
+                        / 10_000,
+                )
+            });
+        if delta_units == 0 {
+            return Some(hedge_margin as i64);
+        }
+        let price = market.reference_price(underlying_hash)?;
+        // Delta exposure is underlying risk, held over the underlying's MPOR.
+        let product_hash = holdings
+            .iter()
+            .find(|(instrument, _)| instrument.symbol_hash == underlying_hash)
+            .map_or(underlying_hash, |(instrument, _)| instrument.product_hash);
+        let delta_margin = self.scale_mpor(
+            product_hash,
+            i128::from(price)
+                .abs()
+                .saturating_mul(delta_units.abs())
+                .saturating_mul(i128::from(bps))
+                / UNDERLYING_UNIT.saturating_mul(10_000),
+        );
+        let gross = hedge_margin.saturating_add(delta_margin);
+        let hedge_units = holdings
+            .iter()
+            .filter(|(instrument, _)| {
+                instrument.symbol_hash == underlying_hash
+                    || instrument.underlying_hash == Some(underlying_hash)
+            })
+            .fold(0_i128, |acc, (instrument, net)| {
+                acc.saturating_add(underlying_units(instrument, *net))
+            });
+        if (hedge_units > 0) == (delta_units > 0) || hedge_units == 0 {
+            return Some(gross.min(i64::MAX as i128) as i64);
+        }
+        let matched = hedge_units.abs().min(delta_units.abs());
+        let matched_margin = (hedge_margin.saturating_mul(matched) / hedge_units.abs())
+            .saturating_add(delta_margin.saturating_mul(matched) / delta_units.abs());
+        let waived = 10_000 - i128::from(self.params.delta_hedge_haircut_bps.min(10_000));
+        let credit = matched_margin.saturating_mul(waived) / 10_000;
+        Some(gross.saturating_sub(credit).min(i64::MAX as i128) as i64)
+    }
+
     /// Return `true` when `account_equity` is below the maintenance margin.
     ///
     /// A margin call is triggered when the account can no longer sustain the
@@ -102,6 +874,10 @@ impl MarginCalculator {
     /// - For a **short** position the account loses value as price rises, so:
     ///   `liq_price = entry_price + (equity / (qty * maint_bps / 10000))`
     ///
+    /// The result is not floored at zero: for instruments that can trade
+    /// below zero a long position's liquidation price may be negative, and a
+    /// short position's may lie above a negative entry price.
+    ///
     /// If `quantity` is zero, `entry_price` is returned unchanged.
     #[inline(always)]
     #[must_use]
@@ -133,6 +909,56 @@ impl MarginCalculator {
     }
 }
 
+/// `|price| * quantity * bps / 10000`, with the contract multiplier applied and
+/// scaled units converted to lots.
+#[inline(always)]
+fn scaled_bps(instrument: &Instrument, price: i64, quantity: u64, bps: u32) -> i64 {
+    let numerator = (price as i128)
+        .abs()
+        .saturating_mul(quantity as i128)
+        .saturating_mul(bps as i128);
+    instrument.value(numerator, 10_000).min(i64::MAX as i128) as i64
+}
+
+/// Outright margin on a leg of `net` scaled units at `price`, with the
+/// contract multiplier applied.
+fn leg_margin(instrument: &Instrument, price: i64, net: i64, bps: u32) -> i128 {
+    instrument.value(
+        i128::from(price)
+            .abs()
+            .saturating_mul(i128::from(net.unsigned_abs()))
+            .saturating_mul(i128::from(bps)),
+        10_000,
+    )
+}
+
+/// Signed exposure of `net` scaled units in [`UNDERLYING_UNIT`]s of the
+/// underlying, with the contract multiplier applied.
+fn underlying_units(instrument: &Instrument, net: i64) -> i128 {
+    instrument.value(i128::from(net).saturating_mul(UNDERLYING_UNIT), 1)
+}
+
+/// `|price| * quantity * bps / 10000`, with overflow handled by `mode`.
+#[inline(always)]
+fn bps_in_mode(
+    mode: ArithmeticMode,
+    price: i64,
+    quantity: u64,
+    bps: u32,
+) -> Result<i64, RiskReject> {
+    let gross = (price as i128).abs();
+    let numerator = match mode {
+        ArithmeticMode::Saturating => gross
+            .saturating_mul(quantity as i128)
+            .saturating_mul(bps as i128),
+        ArithmeticMode::Checked => gross
+            .checked_mul(quantity as i128)
+            .and_then(|n| n.checked_mul(bps as i128))
+            .ok_or(RiskReject::ArithmeticOverflow)?,
+    };
+    mode.narrow(numerator / 10_000)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -145,6 +971,267 @@ mod tests {
         MarginCalculator::new(MarginParams::default())
     }
 
+    // -----------------------------------------------------------------------
+    // Market context
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_margin_marked_at_market_reference() {
+        use crate::market::{MarketSnapshot, SymbolMarket};
+        let calc = default_calc();
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(10_000),
+                ..SymbolMarket::default()
+            },
+        );
+        let short = Position {
+            symbol_hash: 7,
+            net_quantity: -10,
+            avg_entry_price: 9_000,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 1,
+        };
+        assert_eq!(calc.initial_margin_in(&short, &market), Some(10_000));
+        assert_eq!(calc.maintenance_margin_in(&short, &market), Some(5_000));
+        let unknown = Position {
+            symbol_hash: 8,
+            ..short
+        };
+        assert_eq!(calc.initial_margin_in(&unknown, &market), None);
+    }
+
+    #[test]
+    fn test_stale_mark_refused_or_loaded() {
+        use crate::market::{MarketSnapshot, SymbolMarket};
+        let refuse = default_calc().with_mark_staleness(MarkStaleness {
+            horizon_ns: 100,
+            policy: StaleMarkPolicy::Refuse,
+        });
+        assert_eq!(
+            refuse.maintenance_margin_marked(10_000, 10, 0, 100),
+            Ok(5_000)
+        );
+        assert_eq!(
+            refuse.maintenance_margin_marked(10_000, 10, 0, 101),
+            Err(StaleMark {
+                age_ns: Some(101),
+                horizon_ns: 100,
+            })
+        );
+        let add_on = default_calc().with_mark_staleness(MarkStaleness {
+            horizon_ns: 100,
+            policy: StaleMarkPolicy::AddOn { bps: 5_000 },
+        });
+        assert_eq!(add_on.initial_margin_marked(10_000, 10, 0, 500), Ok(15_000));
+        // Without a guard the timestamp is ignored.
+        assert_eq!(default_calc().stale_mark(None, 1), None);
+
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(10_000),
+                ..SymbolMarket::default()
+            },
+        );
+        let long = Position {
+            symbol_hash: 7,
+            net_quantity: 10,
+            avg_entry_price: 10_000,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 1,
+        };
+        // An unknown mark time is stale.
+        assert_eq!(
+            refuse.maintenance_margin_in_at(&long, &market, 0),
+            Some(Err(StaleMark {
+                age_ns: None,
+                horizon_ns: 100,
+            }))
+        );
+        market.set_mark_timestamp(7, 50);
+        assert_eq!(
+            refuse.maintenance_margin_in_at(&long, &market, 120),
+            Some(Ok(5_000))
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Calendar spread credit
+    // -----------------------------------------------------------------------
+
+    fn calendar_market() -> crate::market::MarketSnapshot {
+        use crate::market::{MarketSnapshot, SymbolMarket};
+        let mut market = MarketSnapshot::new();
+        for (symbol, price) in [(1, 10_000), (2, 10_200), (3, 5_000)] {
+            market.insert(
+                symbol,
+                SymbolMarket {
+                    reference_price: Some(price),
+                    ..SymbolMarket::default()
+                },
+            );
+        }
+        market
+    }
+
+    fn spread_calc(spread_credit_bps: u32) -> MarginCalculator {
+        MarginCalculator::new(MarginParams {
+            spread_credit_bps,
+            ..MarginParams::default()
+        })
+    }
+
+    #[test]
+    fn test_calendar_spread_credit_applied_to_matched_lots() {
+        use crate::instrument::{Instrument, DAY_NS};
+        let market = calendar_market();
+        let m1 = Instrument::expiring(1, 30 * DAY_NS, 0).of_product(9);
+        let m2 = Instrument::expiring(2, 60 * DAY_NS, 0).of_product(9);
+        let holdings = [(m1, 10), (m2, -10)];
+        // Outright: 10_000 + 10_200 = 20_200; no credit by default.
+        assert_eq!(
+            default_calc().product_initial_margin_in(9, &holdings, &market),
+            Some(20_200)
+        );
+        // 75% credit on both fully matched legs.
+        let calc = spread_calc(7_500);
+        assert_eq!(
+            calc.product_initial_margin_in(9, &holdings, &market),
+            Some(5_050)
+        );
+        assert_eq!(
+            calc.product_maintenance_margin_in(9, &holdings, &market),
+            Some(2_525)
+        );
+    }
+
+    #[test]
+    fn test_calendar_spread_credit_only_on_matched_quantity() {
+        use crate::instrument::{Instrument, DAY_NS};
+        let market = calendar_market();
+        let m1 = Instrument::expiring(1, 30 * DAY_NS, 0).of_product(9);
+        let m2 = Instrument::expiring(2, 60 * DAY_NS, 0).of_product(9);
+        let other = Instrument::perpetual(3);
+        // 4 of the 10 long lots are unhedged; the other product is ignored.
+        let holdings = [(m1, 10), (m2, -6), (other, -100)];
+        // long 10_000, short 6_120; matched 6 lots → 6_000 + 6_120 = 12_120
+        // credit 50% = 6_060 → 16_120 - 6_060 = 10_060
+        assert_eq!(
+            spread_calc(5_000).product_initial_margin_in(9, &holdings, &market),
+            Some(10_060)
+        );
+        // A one-sided book earns no credit.
+        assert_eq!(
+            spread_calc(10_000).product_initial_margin_in(9, &holdings[..1], &market),
+            Some(10_000)
+        );
+    }
+
+    #[test]
+    fn test_product_margin_missing_reference_price() {
+        use crate::instrument::{Instrument, DAY_NS};
+        let market = calendar_market();
+        let unpriced = Instrument::expiring(4, 30 * DAY_NS, 0).of_product(9);
+        let priced = Instrument::expiring(1, 60 * DAY_NS, 0).of_product(9);
+        assert_eq!(
+            default_calc().product_initial_margin_in(9, &[(priced, 1), (unpriced, -1)], &market),
+            None
+        );
+        // Flat legs need no price.
+        assert_eq!(
+            default_calc().product_initial_margin_in(9, &[(priced, 1), (unpriced, 0)], &market),
+            Some(1_000)
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Spot / derivative offset
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_cross_margin_offsets_spot_against_opposite_derivatives() {
+        use crate::instrument::{Instrument, DAY_NS};
+        let market = calendar_market();
+        // 10 whole units of spot held in hundredths.
+        let spot = Instrument::perpetual(3).with_quantity_scale(2);
+        let short_future = Instrument::expiring(1, 30 * DAY_NS, 0).on_underlying(3);
+        let long_future = Instrument::expiring(2, 60 * DAY_NS, 0).on_underlying(3);
+        let other = Instrument::perpetual(4);
+        let holdings = [
+            (spot, 1_000),
+            (short_future, -6),
+            (long_future, 1),
+            (other, 100),
+        ];
+        // Outright: 5_000 + 6_000 + 1_020 = 12_020; no offset by default.
+        assert_eq!(
+            default_calc().underlying_initial_margin_in(3, &holdings, &market),
+            Some(12_020)
+        );
+        // 6 units matched against the short future only:
+        // (3_000 + 6_000) × 50% = 4_500 → 12_020 - 4_500 = 7_520.
+        let calc = MarginCalculator::new(MarginParams {
+            cross_margin_offset_bps: 5_000,
+            ..MarginParams::default()
+        });
+        assert_eq!(
+            calc.underlying_initial_margin_in(3, &holdings, &market),
+            Some(7_520)
+        );
+        assert_eq!(
+            calc.underlying_maintenance_margin_in(3, &holdings, &market),
+            Some(3_760)
+        );
+        // Derivatives without the spot leg earn no offset.
+        assert_eq!(
+            calc.underlying_initial_margin_in(3, &holdings[1..3], &market),
+            Some(7_020)
+        );
+    }
+
+    #[test]
+    fn test_delta_hedge_offset_with_haircut() {
+        use crate::instrument::{Instrument, DAY_NS};
+        let market = calendar_market();
+        let short_future = Instrument::expiring(1, 30 * DAY_NS, 0).on_underlying(3);
+        let option = Instrument::expiring(5, 30 * DAY_NS, 0).on_underlying(3);
+        let holdings = [(short_future, -6)];
+        // 20 calls at 0.5 delta are long 10 units: 10 × 5_000 × 10% = 5_000.
+        let calls = [(option, 20, 5_000)];
+        assert_eq!(
+            default_calc().delta_hedged_initial_margin_in(3, &holdings, &calls, &market),
+            Some(11_000)
+        );
+        // 6 units matched: (6_000 + 3_000) × (100% - 20%) = 7_200 waived.
+        let calc = MarginCalculator::new(MarginParams {
+            delta_hedge_haircut_bps: 2_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
+            ..MarginParams::default()
+        });
+        assert_eq!(
+            calc.delta_hedged_initial_margin_in(3, &holdings, &calls, &market),
+            Some(3_800)
+        );
+        // Long puts add to the short future's exposure instead of hedging it.
+        let puts = [(option, 20, -5_000)];
+        assert_eq!(
+            calc.delta_hedged_initial_margin_in(3, &holdings, &puts, &market),
+            Some(11_000)
+        );
+        // Without delta exposure only the underlying legs are margined.
+        assert_eq!(
+            calc.delta_hedged_maintenance_margin_in(3, &holdings, &[], &market),
+            Some(3_000)
+        );
+    }
+
     // -----------------------------------------------------------------------
     // Initial margin
     // -----------------------------------------------------------------------
@@ -187,6 +1274,40 @@ mod tests {
         assert!(calc.maintenance_margin(price, qty) < calc.initial_margin(price, qty));
     }
 
+    // -----------------------------------------------------------------------
+    // Checked margin
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_checked_margin_matches_saturating_in_range() {
+        let calc = default_calc().with_arithmetic_mode(ArithmeticMode::Checked);
+        assert_eq!(calc.try_initial_margin(10_000, 10), Ok(10_000));
+        assert_eq!(calc.try_maintenance_margin(10_000, 10), Ok(5_000));
+    }
+
+    #[test]
+    fn test_checked_margin_overflow_is_rejected() {
+        let calc = default_calc();
+        // Saturating path caps at i64::MAX; checked path reports the overflow.
+        assert_eq!(calc.initial_margin(i64::MAX, u64::MAX), i64::MAX);
+        assert_eq!(calc.try_initial_margin(i64::MAX, u64::MAX), Ok(i64::MAX));
+        let checked = calc.with_arithmetic_mode(ArithmeticMode::Checked);
+        assert_eq!(checked.arithmetic_mode(), ArithmeticMode::Checked);
+        assert_eq!(
+            checked.try_initial_margin(i64::MAX, u64::MAX),
+            Err(RiskReject::ArithmeticOverflow)
+        );
+        assert_eq!(
+            checked.try_maintenance_margin(i64::MAX, u64::MAX),
+            Err(RiskReject::ArithmeticOverflow)
+        );
+        // Fits in i128 but not in i64 after the bps division.
+        assert_eq!(
+            checked.try_initial_margin(i64::MAX, 100),
+            Err(RiskReject::ArithmeticOverflow)
+        );
+    }
+
     // -----------------------------------------------------------------------
     // Margin call
     // -----------------------------------------------------------------------
@@ -251,6 +1372,7 @@ mod tests {
         let params = MarginParams::default();
         assert_eq!(params.initial_margin_bps, 1000);
         assert_eq!(params.maintenance_margin_bps, 500);
+        assert_eq!(params.spread_credit_bps, 0);
     }
 
     #[test]
@@ -258,6 +1380,11 @@ mod tests {
         let params = MarginParams {
             initial_margin_bps: 2000,
             maintenance_margin_bps: 1000,
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         };
         assert_eq!(params.initial_margin_bps, 2000);
         assert_eq!(params.maintenance_margin_bps, 1000);
@@ -287,9 +1414,75 @@ mod tests {
     fn test_initial_margin_negative_price() {
         let calc = default_calc();
         // Negative price (spreads/differences can be negative).
-        // -10_000 * 10 * 1000 / 10000 = -10_000
+        // |-10_000| * 10 * 1000 / 10000 = 10_000
         let result = calc.initial_margin(-10_000, 10);
-        assert_eq!(result, -10_000);
+        assert_eq!(result, 10_000);
+        assert_eq!(calc.maintenance_margin(-10_000, 10), 5_000);
+        assert_eq!(calc.try_initial_margin(-10_000, 10), Ok(10_000));
+    }
+
+    #[test]
+    fn test_liquidation_price_below_zero() {
+        let calc = default_calc();
+        // Long 10 at -100 with 5_000 equity: distance 10_000, liquidation at
+        // -10_100; a short liquidates as the price rises through zero.
+        assert_eq!(calc.liquidation_price(-100, 10, 5_000, true), -10_100);
+        assert_eq!(calc.liquidation_price(-100, 10, 5_000, false), 9_900);
+    }
+
+    #[test]
+    fn test_instrument_margin_converts_scaled_units() {
+        let calc = default_calc();
+        let btc = Instrument::perpetual(1).with_quantity_scale(8);
+        // 0.5 BTC (50_000_000 units) at 60_000 per BTC: notional 30_000.
+        assert_eq!(
+            calc.instrument_initial_margin(&btc, 60_000, 50_000_000),
+            3_000
+        );
+        assert_eq!(
+            calc.instrument_maintenance_margin(&btc, 60_000, 50_000_000),
+            1_500
+        );
+        assert_eq!(
+            calc.instrument_initial_margin(&Instrument::perpetual(2), 60_000, 5),
+            calc.initial_margin(60_000, 5)
+        );
+        // A 50x index future margins 50 times the 1x notional.
+        let es = Instrument::perpetual(3).with_multiplier(50);
+        assert_eq!(
+            calc.instrument_initial_margin(&es, 5_000, 2),
+            50 * calc.initial_margin(5_000, 2)
+        );
+    }
+
+    #[test]
+    fn test_instrument_margin_fixed() {
+        let calc = default_calc();
+        let btc = Instrument::perpetual(1)
+            .with_quantity_scale(8)
+            .with_price_scale(2);
+        let price = Fixed::<4>::parse("60000.50").unwrap();
+        let lots = Fixed::<8>::parse("-0.5").unwrap();
+        // 30_000.25 notional: 10% initial, 5% maintenance (truncated to cents).
+        assert_eq!(
+            calc.instrument_initial_margin_fixed(&btc, price, lots),
+            Fixed::<2>::parse("3000.02")
+        );
+        assert_eq!(
+            calc.instrument_maintenance_margin_fixed(&btc, price, lots),
+            Fixed::<4>::parse("1500.01")
+        );
+        // Sub-tick prices and sub-unit quantities are refused, not rounded.
+        let sub_tick = Fixed::<4>::parse("60000.505").unwrap();
+        assert_eq!(
+            calc.instrument_initial_margin_fixed::<4, 8, 2>(&btc, sub_tick, lots),
+            None
+        );
+        let dust = Fixed::<10>::parse("0.0000000001").unwrap();
+        assert_eq!(
+            calc.instrument_initial_margin_fixed::<4, 10, 2>(&btc, price, dust),
+            None
+        );
     }
 
     #[test]
@@ -304,6 +1497,11 @@ mod tests {
         let calc = MarginCalculator::new(MarginParams {
             initial_margin_bps: 10_000, // 100%
             maintenance_margin_bps: 500,
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         });
         // 100% of notional: price * qty
         assert_eq!(calc.initial_margin(5000, 10), 50_000);
@@ -318,6 +1516,11 @@ mod tests {
         let calc = MarginCalculator::new(MarginParams {
             initial_margin_bps: 1000,
             maintenance_margin_bps: 0,
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         });
         // 0 bps means zero maintenance margin.
         assert_eq!(calc.maintenance_margin(50_000, 100), 0);
@@ -402,6 +1605,11 @@ mod tests {
         let calc = MarginCalculator::new(MarginParams {
             initial_margin_bps: 1000,
             maintenance_margin_bps: 0,
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         });
         let liq = calc.liquidation_price(10_000, 10, 5_000, true);
         assert_eq!(liq, 10_000);
@@ -432,6 +1640,11 @@ mod tests {
         let calc = MarginCalculator::new(MarginParams {
             initial_margin_bps: 500,
             maintenance_margin_bps: 250,
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         });
         assert_eq!(calc.initial_margin(20_000, 4), 4_000);
     }
@@ -473,11 +1686,106 @@ mod tests {
         let calc = MarginCalculator::new(MarginParams {
             initial_margin_bps: 1000,
             maintenance_margin_bps: 0,
+            spread_credit_bps: 0,
+            cross_margin_offset_bps: 0,
+            delta_hedge_haircut_bps: 10_000,
+            mpor_base_days: 1,
+            mpor_days: 1,
         });
         assert!(!calc.is_margin_call(50_000, 1_000, 0));
         assert!(!calc.is_margin_call(50_000, 1_000, i64::MAX));
     }
 
+    // -------------------------------------------------------------------
+    // Margin period of risk
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn test_mpor_scales_by_square_root_of_holding_period() {
+        use crate::market::{MarketSnapshot, SymbolMarket};
+        // Rates calibrated to a cleared 2-day MPOR; product 7 is bilateral.
+        let calc = MarginCalculator::new(MarginParams {
+            mpor_base_days: 2,
+            mpor_days: 2,
+            ..MarginParams::default()
+        })
+        .with_product_mpor(7, 10);
+        assert_eq!((calc.mpor_days(1), calc.mpor_days(7)), (2, 10));
+        let cleared = Instrument::perpetual(1);
+        let bilateral = Instrument::perpetual(2).of_product(7);
+        assert_eq!(calc.instrument_initial_margin(&cleared, 1_000, 10), 1_000);
+        // 1_000 × √5 = 2_236.07 → rounded up.
+        assert_eq!(calc.instrument_initial_margin(&bilateral, 1_000, 10), 2_237);
+        assert_eq!(calc.mpor_scaled(7, -1_000), -2_237);
+
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            2,
+            SymbolMarket {
+                reference_price: Some(1_000),
+                ..SymbolMarket::default()
+            },
+        );
+        assert_eq!(
+            calc.product_initial_margin_in(7, &[(bilateral, 10)], &market),
+            Some(2_237)
+        );
+
+        // A 4-day default against 1-day rates doubles the requirement exactly.
+        let doubled = MarginCalculator::new(MarginParams {
+            mpor_days: 4,
+            ..MarginParams::default()
+        });
+        assert_eq!(
+            doubled.instrument_maintenance_margin(&cleared, 1_000, 10),
+            1_000
+        );
+        // The plain price × quantity methods are not instrument-aware.
+        assert_eq!(doubled.maintenance_margin(1_000, 10), 500);
+    }
+
+    #[test]
+    fn test_portfolio_margin_sums_positions() {
+        use crate::market::{MarketSnapshot, SymbolMarket};
+        use crate::test_utils::PositionBuilder;
+        let calc = default_calc();
+        let mut market = MarketSnapshot::new();
+        let mut positions: Vec<Position> = (0..3_000_u64)
+            .map(|s| {
+                market.insert(
+                    s,
+                    SymbolMarket {
+                        reference_price: Some(1_000 + s as i64),
+                        ..SymbolMarket::default()
+                    },
+                );
+                PositionBuilder::new(s, s as i64 % 11 - 5)
+                    .avg_entry_price(0)
+                    .build()
+            })
+            .collect();
+        let sequential: i64 = positions
+            .iter()
+            .map(|p| calc.maintenance_margin_in(p, &market).unwrap())
+            .sum();
+        assert_eq!(
+            calc.portfolio_maintenance_margin_in(&positions, &market),
+            Some(sequential)
+        );
+        assert!(calc
+            .portfolio_initial_margin_in(&positions, &market)
+            .is_some_and(|m| m >= sequential));
+        assert_eq!(calc.portfolio_initial_margin_in(&[], &market), Some(0));
+        // An unpriced position in the last chunk voids the total.
+        let mut unpriced = positions[0].clone();
+        unpriced.symbol_hash = 99_999;
+        positions.push(unpriced);
+        assert_eq!(
+            calc.portfolio_maintenance_margin_in(&positions, &market),
+            None
+        );
+    }
+
     // -------------------------------------------------------------------
     // Property-based tests
     // -------------------------------------------------------------------
@@ -510,6 +1818,11 @@ mod tests {
             let calc = MarginCalculator::new(MarginParams {
                 initial_margin_bps: initial_bps,
                 maintenance_margin_bps: maintenance_bps,
+                spread_credit_bps: 0,
+                cross_margin_offset_bps: 0,
+                delta_hedge_haircut_bps: 10_000,
+                mpor_base_days: 1,
+                mpor_days: 1,
             });
             prop_assert!(
                 calc.maintenance_margin(price, quantity) <= calc.initial_margin(price, quantity)
@@ -0,0 +1,273 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 事前チェックに渡す市場データ。
+//!
+//! [`Bbo`] は最良気配（best bid / best offer）。買い注文が売り気配を、
+//! 売り注文が買い気配を大きく突き抜けた価格は誤発注の可能性が高いため、
+//! [`PreTradeChecker::check_order_with_bbo`](crate::PreTradeChecker::check_order_with_bbo)
+//! で拒否できる。
+//!
+//! [`MarketContext`] は銘柄ごとの参照価格・最良気配・平均日次出来高（ADV）・
+//! ボラティリティ・板の表示数量を供給するトレイト。組込み側は市場データをチェックごとに
+//! 渡すのではなく、このトレイトを 1 回実装するだけでよい。
+//! [`MarketSnapshot`] はその単純な表形式の実装。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::Side;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// Bbo
+// ---------------------------------------------------------------------------
+
+/// 最良気配（ticks）。片側の気配が無い場合は `None`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bbo {
+    /// 最良買い気配。
+    pub bid: Option<i64>,
+    /// 最良売り気配。
+    pub ask: Option<i64>,
+}
+
+impl Bbo {
+    /// 両側の気配から作成。
+    #[must_use]
+    pub const fn new(bid: i64, ask: i64) -> Self {
+        Self {
+            bid: Some(bid),
+            ask: Some(ask),
+        }
+    }
+
+    /// `side` の注文が突き抜ける側の気配（買いなら売り気配、売りなら買い気配）。
+    #[must_use]
+    pub const fn touch(&self, side: Side) -> Option<i64> {
+        match side {
+            Side::Bid => self.ask,
+            Side::Ask => self.bid,
+        }
+    }
+
+    /// `side` / `price` の注文が反対側の気配を突き抜けた幅（気配に対する bps）。
+    ///
+    /// 気配の内側にある、または反対側の気配が無い場合は `None`。気配が 0 の
+    /// ときに突き抜けた場合は `u32::MAX` とみなす。
+    #[must_use]
+    pub fn through_bps(&self, side: Side, price: i64) -> Option<u32> {
+        let touch = self.touch(side)?;
+        let through = match side {
+            Side::Bid => i128::from(price) - i128::from(touch),
+            Side::Ask => i128::from(touch) - i128::from(price),
+        };
+        if through <= 0 {
+            return None;
+        }
+        let base = i128::from(touch).abs();
+        if base == 0 {
+            return Some(u32::MAX);
+        }
+        Some((through.saturating_mul(10_000) / base).min(i128::from(u32::MAX)) as u32)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MarketContext
+// ---------------------------------------------------------------------------
+
+/// 銘柄（`Position::symbol_hash`）ごとの市場データの供給元。
+///
+/// 値が無い項目は `None` を返し、その項目を使うチェックは省略される。
+/// 既定実装はすべて `None` なので、持っている項目だけを実装すればよい。
+pub trait MarketContext {
+    /// 参照価格（ticks）。成行注文の評価価格や証拠金の値洗いに使う。
+    fn reference_price(&self, _symbol_hash: u64) -> Option<i64> {
+        None
+    }
+
+    /// 最良気配。
+    fn bbo(&self, _symbol_hash: u64) -> Option<Bbo> {
+        None
+    }
+
+    /// 平均日次出来高（lots）。
+    fn adv(&self, _symbol_hash: u64) -> Option<u64> {
+        None
+    }
+
+    /// ボラティリティ（日次、bps）。
+    fn volatility_bps(&self, _symbol_hash: u64) -> Option<u32> {
+        None
+    }
+
+    /// 参照価格の更新時刻（ns）。鮮度を確かめる証拠金計算が使う。
+    fn mark_timestamp_ns(&self, _symbol_hash: u64) -> Option<u64> {
+        None
+    }
+
+    /// 板の `side` 側（買い板・売り板）に表示されている数量の合計（lots）。
+    fn displayed_depth(&self, _symbol_hash: u64, _side: Side) -> Option<u64> {
+        None
+    }
+}
+
+/// 1 銘柄分の市場データ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SymbolMarket {
+    /// 参照価格（ticks）。
+    pub reference_price: Option<i64>,
+    /// 最良気配。
+    pub bbo: Option<Bbo>,
+    /// 平均日次出来高（lots）。
+    pub adv: Option<u64>,
+    /// ボラティリティ（日次、bps）。
+    pub volatility_bps: Option<u32>,
+}
+
+/// 銘柄 → [`SymbolMarket`] の表による [`MarketContext`] 実装。
+#[derive(Debug, Clone, Default)]
+pub struct MarketSnapshot {
+    symbols: BTreeMap<u64, SymbolMarket>,
+    /// 銘柄 → 参照価格の更新時刻（ns）。
+    marked_at: BTreeMap<u64, u64>,
+    /// 銘柄 → 板の表示数量（買い板, 売り板）（lots）。
+    depth: BTreeMap<u64, (u64, u64)>,
+}
+
+impl MarketSnapshot {
+    /// 空の表を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            symbols: BTreeMap::new(),
+            marked_at: BTreeMap::new(),
+            depth: BTreeMap::new(),
+        }
+    }
+
+    /// 銘柄の市場データを登録（既存は置き換え）。
+    pub fn insert(&mut self, symbol_hash: u64, market: SymbolMarket) {
+        self.symbols.insert(symbol_hash, market);
+    }
+
+    /// 銘柄の参照価格の更新時刻を記録する。
+    pub fn set_mark_timestamp(&mut self, symbol_hash: u64, timestamp_ns: u64) {
+        self.marked_at.insert(symbol_hash, timestamp_ns);
+    }
+
+    /// 銘柄の板の表示数量（lots）を記録する。
+    pub fn set_depth(&mut self, symbol_hash: u64, bid: u64, ask: u64) {
+        self.depth.insert(symbol_hash, (bid, ask));
+    }
+
+    /// 銘柄の市場データ。
+    #[must_use]
+    pub fn get(&self, symbol_hash: u64) -> Option<&SymbolMarket> {
+        self.symbols.get(&symbol_hash)
+    }
+}
+
+impl MarketContext for MarketSnapshot {
+    fn reference_price(&self, symbol_hash: u64) -> Option<i64> {
+        self.get(symbol_hash)?.reference_price
+    }
+
+    fn bbo(&self, symbol_hash: u64) -> Option<Bbo> {
+        self.get(symbol_hash)?.bbo
+    }
+
+    fn adv(&self, symbol_hash: u64) -> Option<u64> {
+        self.get(symbol_hash)?.adv
+    }
+
+    fn volatility_bps(&self, symbol_hash: u64) -> Option<u32> {
+        self.get(symbol_hash)?.volatility_bps
+    }
+
+    fn mark_timestamp_ns(&self, symbol_hash: u64) -> Option<u64> {
+        self.marked_at.get(&symbol_hash).copied()
+    }
+
+    fn displayed_depth(&self, symbol_hash: u64, side: Side) -> Option<u64> {
+        let &(bid, ask) = self.depth.get(&symbol_hash)?;
+        Some(match side {
+            Side::Bid => bid,
+            Side::Ask => ask,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn through_bps_by_side() {
+        let bbo = Bbo::new(9_900, 10_000);
+        assert_eq!(bbo.through_bps(Side::Bid, 10_000), None);
+        assert_eq!(bbo.through_bps(Side::Bid, 10_500), Some(500));
+        assert_eq!(bbo.through_bps(Side::Ask, 9_900), None);
+        assert_eq!(bbo.through_bps(Side::Ask, 9_405), Some(500));
+        // 自側の気配より内側（受動的な指値）は対象外。
+        assert_eq!(bbo.through_bps(Side::Bid, 9_000), None);
+    }
+
+    #[test]
+    fn snapshot_lookup() {
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(1_000),
+                bbo: Some(Bbo::new(999, 1_001)),
+                adv: Some(50_000),
+                volatility_bps: Some(120),
+            },
+        );
+        assert_eq!(market.reference_price(7), Some(1_000));
+        assert_eq!(market.bbo(7), Some(Bbo::new(999, 1_001)));
+        assert_eq!(
+            (market.adv(7), market.volatility_bps(7)),
+            (Some(50_000), Some(120))
+        );
+        assert_eq!(market.reference_price(8), None);
+    }
+
+    #[test]
+    fn default_methods_are_none() {
+        struct PriceOnly;
+        impl MarketContext for PriceOnly {
+            fn reference_price(&self, _symbol_hash: u64) -> Option<i64> {
+                Some(5)
+            }
+        }
+        assert_eq!(PriceOnly.reference_price(1), Some(5));
+        assert_eq!(PriceOnly.bbo(1), None);
+        assert_eq!(PriceOnly.adv(1), None);
+        assert_eq!(PriceOnly.volatility_bps(1), None);
+        assert_eq!(PriceOnly.mark_timestamp_ns(1), None);
+    }
+
+    #[test]
+    fn missing_or_zero_touch() {
+        let one_sided = Bbo {
+            bid: Some(100),
+            ask: None,
+        };
+        assert_eq!(one_sided.through_bps(Side::Bid, i64::MAX), None);
+        assert_eq!(one_sided.through_bps(Side::Ask, 90), Some(1_000));
+        assert_eq!(Bbo::new(0, 0).through_bps(Side::Bid, 1), Some(u32::MAX));
+        assert_eq!(
+            Bbo::new(1, 2).through_bps(Side::Ask, i64::MIN),
+            Some(u32::MAX)
+        );
+    }
+}
@@ -0,0 +1,515 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 拒否率の異常検知。
+//!
+//! 口座ごとに固定時間幅の拒否率を追跡し、過去の時間幅の拒否率の指数移動平均
+//! （ベースライン）の一定倍を超えたら [`RejectRateAlert`] を出す。拒否の急増は
+//! たいてい戦略のモデルが壊れたことを意味するため、設定により口座を自動的に
+//! 隔離（quarantine）できる。隔離は [`RejectRateMonitor::release`] で明示的に
+//! 解除するまで続く。
+//!
+//! [`PenaltyBox`] はより直接的な防御で、短時間に一定回数拒否された口座の注文を
+//! 制限に関係なくクールダウン期間中すべて拒否し、誤動作したクライアントの
+//! 再送の嵐を止める。
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{DecisionRecord, PreTradeChecker, RiskReject};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// RejectRateConfig
+// ---------------------------------------------------------------------------
+
+/// 拒否率監視の設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectRateConfig {
+    /// 拒否率を集計する時間幅（ns）。
+    pub window_ns: u64,
+    /// ベースラインの指数移動平均の期間（時間幅の数）。
+    pub baseline_windows: u32,
+    /// アラートを出すベースラインの倍率。
+    pub spike_multiple: u32,
+    /// ベースラインの下限（bps）。ベースラインがほぼ 0 のときに 1 件の拒否で
+    /// アラートが出るのを防ぐ。
+    pub min_baseline_bps: u32,
+    /// 判定に必要な時間幅内の最小件数。
+    pub min_decisions: u32,
+    /// アラート時に口座を自動的に隔離するか。
+    pub auto_quarantine: bool,
+}
+
+impl Default for RejectRateConfig {
+    fn default() -> Self {
+        Self {
+            window_ns: 60_000_000_000, // 1 分
+            baseline_windows: 30,
+            spike_multiple: 3,
+            min_baseline_bps: 100, // 1%
+            min_decisions: 20,
+            auto_quarantine: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RejectRateAlert
+// ---------------------------------------------------------------------------
+
+/// 拒否率の急増。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectRateAlert {
+    /// 口座 ID。
+    pub account_id: u64,
+    /// 現在の時間幅の拒否率（bps）。
+    pub rate_bps: u32,
+    /// ベースライン（bps、下限適用前）。
+    pub baseline_bps: u32,
+    /// 現在の時間幅の開始時刻（ns）。
+    pub window_start_ns: u64,
+    /// 口座を隔離したか。
+    pub quarantined: bool,
+}
+
+// ---------------------------------------------------------------------------
+// RejectRateMonitor
+// ---------------------------------------------------------------------------
+
+/// 1 口座の集計状態。
+#[derive(Debug, Clone, Copy, Default)]
+struct AccountRate {
+    window_start_ns: u64,
+    decisions: u32,
+    rejects: u32,
+    /// 完了した時間幅の拒否率の指数移動平均（bps）。最初の時間幅が終わるまでは `None`。
+    baseline_bps: Option<u32>,
+    /// 現在の時間幅でアラート済みか。
+    alerted: bool,
+    quarantined: bool,
+}
+
+impl AccountRate {
+    fn rate_bps(&self) -> u32 {
+        if self.decisions == 0 {
+            return 0;
+        }
+        (u64::from(self.rejects) * 10_000 / u64::from(self.decisions)) as u32
+    }
+}
+
+/// 口座別の拒否率監視。
+#[derive(Debug, Clone)]
+pub struct RejectRateMonitor {
+    config: RejectRateConfig,
+    accounts: BTreeMap<u64, AccountRate>,
+}
+
+impl RejectRateMonitor {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new(config: RejectRateConfig) -> Self {
+        Self {
+            config,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &RejectRateConfig {
+        &self.config
+    }
+
+    /// 判定結果を 1 件記録する。急増を検出した場合、その時間幅で 1 回だけ
+    /// アラートを返す。
+    pub fn record(
+        &mut self,
+        account_id: u64,
+        timestamp_ns: u64,
+        accepted: bool,
+    ) -> Option<RejectRateAlert> {
+        let config = self.config;
+        let state = self.accounts.entry(account_id).or_default();
+        if state.decisions > 0
+            && timestamp_ns.saturating_sub(state.window_start_ns) >= config.window_ns
+        {
+            let rate = i64::from(state.rate_bps());
+            let n = i64::from(config.baseline_windows.max(1));
+            state.baseline_bps = Some(match state.baseline_bps {
+                Some(prev) => (i64::from(prev) + (rate - i64::from(prev)) / n) as u32,
+                None => rate as u32,
+            });
+            state.decisions = 0;
+            state.rejects = 0;
+            state.alerted = false;
+        }
+        if state.decisions == 0 {
+            state.window_start_ns = timestamp_ns;
+        }
+        state.decisions = state.decisions.saturating_add(1);
+        if !accepted {
+            state.rejects = state.rejects.saturating_add(1);
+        }
+
+        let baseline = state.baseline_bps?;
+        if state.alerted || state.decisions < config.min_decisions {
+            return None;
+        }
+        let threshold =
+            u64::from(baseline.max(config.min_baseline_bps)) * u64::from(config.spike_multiple);
+        let rate = state.rate_bps();
+        if u64::from(rate) <= threshold {
+            return None;
+        }
+        state.alerted = true;
+        if config.auto_quarantine {
+            state.quarantined = true;
+        }
+        Some(RejectRateAlert {
+            account_id,
+            rate_bps: rate,
+            baseline_bps: baseline,
+            window_start_ns: state.window_start_ns,
+            quarantined: state.quarantined,
+        })
+    }
+
+    /// [`DecisionRecord`] を記録する（[`Self::record`]）。
+    pub fn record_decision(
+        &mut self,
+        account_id: u64,
+        decision: &DecisionRecord,
+    ) -> Option<RejectRateAlert> {
+        self.record(account_id, decision.timestamp_ns, decision.is_accepted())
+    }
+
+    /// 口座が隔離中か。
+    #[must_use]
+    pub fn is_quarantined(&self, account_id: u64) -> bool {
+        self.accounts
+            .get(&account_id)
+            .is_some_and(|s| s.quarantined)
+    }
+
+    /// 口座の隔離を解除する。
+    pub fn release(&mut self, account_id: u64) {
+        if let Some(state) = self.accounts.get_mut(&account_id) {
+            state.quarantined = false;
+        }
+    }
+
+    /// 口座のベースライン（bps）。最初の時間幅が終わるまでは `None`。
+    #[must_use]
+    pub fn baseline_bps(&self, account_id: u64) -> Option<u32> {
+        self.accounts.get(&account_id)?.baseline_bps
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PenaltyBox
+// ---------------------------------------------------------------------------
+
+/// ペナルティボックスの設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PenaltyBoxConfig {
+    /// クールダウンに入る拒否回数。0 で無効。
+    pub max_rejects: u32,
+    /// 拒否を数える時間幅（ns、スライディング）。
+    pub window_ns: u64,
+    /// クールダウン期間（ns）。
+    pub cooldown_ns: u64,
+}
+
+impl Default for PenaltyBoxConfig {
+    fn default() -> Self {
+        Self {
+            max_rejects: 10,
+            window_ns: 1_000_000_000,    // 1 秒
+            cooldown_ns: 30_000_000_000, // 30 秒
+        }
+    }
+}
+
+/// 1 口座の拒否履歴。
+#[derive(Debug, Clone, Default)]
+struct Penalty {
+    /// 時間幅内の拒否時刻。古い順。
+    rejects: VecDeque<u64>,
+    /// クールダウンの終了時刻と、その契機となった拒否回数。
+    until: Option<(u64, u32)>,
+}
+
+/// 口座別のペナルティボックス。
+///
+/// `window_ns` 以内に `max_rejects` 回拒否された口座は、`cooldown_ns` の間
+/// すべての注文が [`RiskReject::PenaltyBox`] で拒否される。クールダウン中の
+/// 拒否は数えないため、再送を続けても期間は延びない。
+#[derive(Debug, Clone)]
+pub struct PenaltyBox {
+    config: PenaltyBoxConfig,
+    accounts: BTreeMap<u64, Penalty>,
+}
+
+impl PenaltyBox {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new(config: PenaltyBoxConfig) -> Self {
+        Self {
+            config,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &PenaltyBoxConfig {
+        &self.config
+    }
+
+    /// 口座が `now_ns` の時点でクールダウン中でないか。
+    ///
+    /// # Errors
+    ///
+    /// クールダウン中なら [`RiskReject::PenaltyBox`]。
+    pub fn check(&self, account_id: u64, now_ns: u64) -> Result<(), RiskReject> {
+        match self.accounts.get(&account_id).and_then(|p| p.until) {
+            Some((until_ns, rejects)) if now_ns < until_ns => {
+                Err(RiskReject::PenaltyBox { rejects, until_ns })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 判定結果を 1 件記録する。この拒否でクールダウンに入った場合は
+    /// その終了時刻を返す。
+    pub fn record(&mut self, account_id: u64, timestamp_ns: u64, accepted: bool) -> Option<u64> {
+        let config = self.config;
+        if accepted || config.max_rejects == 0 {
+            return None;
+        }
+        let state = self.accounts.entry(account_id).or_default();
+        if let Some((until_ns, _)) = state.until {
+            if timestamp_ns < until_ns {
+                return None;
+            }
+            state.until = None;
+            state.rejects.clear();
+        }
+        while let Some(&at) = state.rejects.front() {
+            if timestamp_ns.saturating_sub(at) < config.window_ns {
+                break;
+            }
+            state.rejects.pop_front();
+        }
+        state.rejects.push_back(timestamp_ns);
+        let count = u32::try_from(state.rejects.len()).unwrap_or(u32::MAX);
+        if count < config.max_rejects {
+            return None;
+        }
+        let until_ns = timestamp_ns.saturating_add(config.cooldown_ns);
+        state.until = Some((until_ns, count));
+        state.rejects.clear();
+        Some(until_ns)
+    }
+
+    /// ペナルティボックスを確認してから `checker` で判定し、結果を記録する。
+    ///
+    /// # Errors
+    ///
+    /// クールダウン中なら [`RiskReject::PenaltyBox`]、それ以外は
+    /// [`PreTradeChecker::check_order`] の拒否理由。
+    pub fn check_order(
+        &mut self,
+        account_id: u64,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let now = order.timestamp_ns;
+        self.check(account_id, now)?;
+        let result = checker.check_order(order, position);
+        self.record(account_id, now, result.is_ok());
+        result
+    }
+
+    /// クールダウン中の口座の終了時刻。
+    #[must_use]
+    pub fn cooldown_until(&self, account_id: u64, now_ns: u64) -> Option<u64> {
+        self.accounts
+            .get(&account_id)
+            .and_then(|p| p.until)
+            .map(|(until_ns, _)| until_ns)
+            .filter(|&until_ns| now_ns < until_ns)
+    }
+
+    /// 口座のクールダウンと拒否履歴を消去する。
+    pub fn release(&mut self, account_id: u64) {
+        self.accounts.remove(&account_id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    const W: u64 = 1_000;
+
+    fn monitor(auto_quarantine: bool) -> RejectRateMonitor {
+        RejectRateMonitor::new(RejectRateConfig {
+            window_ns: W,
+            baseline_windows: 4,
+            spike_multiple: 3,
+            min_baseline_bps: 100,
+            min_decisions: 10,
+            auto_quarantine,
+        })
+    }
+
+    /// `window` 番目の時間幅に `n` 件、うち最後の `rejects` 件を拒否として記録する。
+    fn feed(
+        m: &mut RejectRateMonitor,
+        account: u64,
+        window: u64,
+        n: u64,
+        rejects: u64,
+    ) -> Option<RejectRateAlert> {
+        let mut alert = None;
+        for i in 0..n {
+            alert = alert.or(m.record(account, window * W + i, i < n - rejects));
+        }
+        alert
+    }
+
+    #[test]
+    fn baseline_from_completed_windows() {
+        let mut m = monitor(false);
+        assert!(feed(&mut m, 1, 0, 100, 5).is_none());
+        assert_eq!(m.baseline_bps(1), None);
+        feed(&mut m, 1, 1, 100, 9);
+        assert_eq!(m.baseline_bps(1), Some(500));
+        feed(&mut m, 1, 2, 1, 0);
+        // 500 + (900 - 500) / 4
+        assert_eq!(m.baseline_bps(1), Some(600));
+    }
+
+    #[test]
+    fn spike_alerts_once_per_window() {
+        let mut m = monitor(false);
+        feed(&mut m, 1, 0, 100, 5);
+        let alert = feed(&mut m, 1, 1, 20, 20).unwrap();
+        assert_eq!(
+            (alert.rate_bps, alert.baseline_bps, alert.window_start_ns),
+            (10_000, 500, W)
+        );
+        assert!(!alert.quarantined);
+        assert!(feed(&mut m, 1, 1, 5, 5).is_none());
+        // 他の口座には影響しない。
+        assert_eq!(m.baseline_bps(2), None);
+    }
+
+    #[test]
+    fn min_decisions_and_floor() {
+        let mut m = monitor(false);
+        // ベースライン 0 でも下限 1% × 3 = 3% 以下ならアラートなし。
+        feed(&mut m, 1, 0, 100, 0);
+        assert!(feed(&mut m, 1, 1, 100, 3).is_none());
+        // 件数不足。
+        assert!(feed(&mut m, 1, 2, 9, 9).is_none());
+    }
+
+    #[test]
+    fn auto_quarantine_until_released() {
+        let mut m = monitor(true);
+        feed(&mut m, 1, 0, 100, 1);
+        let alert = feed(&mut m, 1, 1, 10, 10).unwrap();
+        assert!(alert.quarantined);
+        assert!(m.is_quarantined(1));
+        feed(&mut m, 1, 2, 100, 0);
+        assert!(m.is_quarantined(1));
+        m.release(1);
+        assert!(!m.is_quarantined(1));
+    }
+
+    #[test]
+    fn decision_records() {
+        use crate::check::{DecisionTags, RiskReject};
+        let mut m = monitor(false);
+        let record = DecisionRecord {
+            order_id: 1,
+            timestamp_ns: 0,
+            limit_version: 0,
+            result: Err(RiskReject::CircuitBreakerTripped),
+            tags: DecisionTags::default(),
+        };
+        assert!(m.record_decision(1, &record).is_none());
+        assert_eq!(m.config().min_decisions, 10);
+    }
+
+    fn penalty_box() -> PenaltyBox {
+        PenaltyBox::new(PenaltyBoxConfig {
+            max_rejects: 3,
+            window_ns: 100,
+            cooldown_ns: 1_000,
+        })
+    }
+
+    #[test]
+    fn penalty_box_after_repeated_rejects() {
+        let mut p = penalty_box();
+        assert_eq!(p.record(1, 0, false), None);
+        assert_eq!(p.record(1, 50, true), None);
+        // 時間幅外の拒否は数えない。
+        assert_eq!(p.record(1, 120, false), None);
+        assert_eq!(p.record(1, 130, false), None);
+        assert_eq!(p.record(1, 140, false), Some(1_140));
+        assert_eq!(
+            p.check(1, 500),
+            Err(RiskReject::PenaltyBox {
+                rejects: 3,
+                until_ns: 1_140,
+            })
+        );
+        assert!(p.check(2, 500).is_ok());
+        // クールダウン中の拒否で期間は延びない。
+        assert_eq!(p.record(1, 600, false), None);
+        assert_eq!(p.cooldown_until(1, 600), Some(1_140));
+        assert!(p.check(1, 1_140).is_ok());
+        assert_eq!(p.record(1, 1_150, false), None);
+        p.release(1);
+        assert_eq!(p.cooldown_until(1, 0), None);
+    }
+
+    #[test]
+    fn penalty_box_wraps_checker() {
+        use crate::limit::RiskLimits;
+
+        let mut p = penalty_box();
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let mut order = OrderBuilder::bid(100, u64::MAX).build();
+        for t in 0..3 {
+            order.timestamp_ns = t;
+            assert!(matches!(
+                p.check_order(1, &checker, &order, None),
+                Err(RiskReject::OrderSizeTooLarge { .. })
+            ));
+        }
+        order.quantity = 1;
+        assert!(matches!(
+            p.check_order(1, &checker, &order, None),
+            Err(RiskReject::PenaltyBox { .. })
+        ));
+        order.timestamp_ns = 2_000;
+        assert!(p.check_order(1, &checker, &order, None).is_ok());
+    }
+}
@@ -0,0 +1,435 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 口座間ネッティング。
+//!
+//! 同じ戦略のヘッジ口座とメイン口座のように、建玉を合算して評価すべき口座を
+//! グループにまとめる。ポジション上限と証拠金はグループでネットした建玉で
+//! 判定し、注文数量・想定元本・未約定注文数などの注文単位の制限は引き続き
+//! 口座ごとの [`PreTradeChecker`] で適用する。
+//!
+//! グループに割り当てていない口座は、口座 ID と同じ ID の単独グループとして
+//! 扱う。
+//!
+//! [`NettingGroups::compression_report`] はグループ（法人・口座）間で相殺できる
+//! 建玉を銘柄ごとに洗い出し、圧縮（コンプレッション）や社内クロスで削減
+//! できる当初証拠金を見積もる（財務部門向けの分析 API）。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::margin::MarginCalculator;
+use crate::market::MarketContext;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// NettingGroups
+// ---------------------------------------------------------------------------
+
+/// 口座 → ネッティンググループの割当てと、口座・銘柄別の建玉。
+#[derive(Debug, Clone, Default)]
+pub struct NettingGroups {
+    /// 口座 ID → グループ ID。
+    group_of: BTreeMap<u64, u64>,
+    /// (口座 ID, 銘柄) → ネット建玉。
+    positions: BTreeMap<(u64, u64), i64>,
+}
+
+impl NettingGroups {
+    /// 空の設定を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            group_of: BTreeMap::new(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /// 口座をグループに割り当てる（既存の割当ては置き換え）。
+    pub fn assign(&mut self, account_id: u64, group_id: u64) {
+        self.group_of.insert(account_id, group_id);
+    }
+
+    /// 口座の割当てを解除し、単独グループに戻す。
+    pub fn unassign(&mut self, account_id: u64) {
+        self.group_of.remove(&account_id);
+    }
+
+    /// 口座の属するグループ。未割当ての口座は口座 ID そのもの。
+    #[must_use]
+    pub fn group_of(&self, account_id: u64) -> u64 {
+        self.group_of
+            .get(&account_id)
+            .copied()
+            .unwrap_or(account_id)
+    }
+
+    /// グループに割り当てた口座（口座 ID 順）。
+    #[must_use]
+    pub fn members(&self, group_id: u64) -> Vec<u64> {
+        self.group_of
+            .iter()
+            .filter(|&(_, &g)| g == group_id)
+            .map(|(&a, _)| a)
+            .collect()
+    }
+
+    /// 口座の建玉を記録する（`Position::symbol_hash` の建玉を置き換え）。
+    pub fn set_position(&mut self, account_id: u64, position: &Position) {
+        self.positions
+            .insert((account_id, position.symbol_hash), position.net_quantity);
+    }
+
+    /// 口座単独の建玉。
+    #[must_use]
+    pub fn position(&self, account_id: u64, symbol_hash: u64) -> i64 {
+        self.positions
+            .get(&(account_id, symbol_hash))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// グループでネットした建玉。
+    #[must_use]
+    pub fn group_net(&self, group_id: u64, symbol_hash: u64) -> i64 {
+        self.positions
+            .iter()
+            .filter(|(&(a, s), _)| s == symbol_hash && self.group_of(a) == group_id)
+            .fold(0_i64, |acc, (_, &n)| acc.saturating_add(n))
+    }
+
+    /// 口座の属するグループでネットした建玉。
+    #[must_use]
+    pub fn netted(&self, account_id: u64, symbol_hash: u64) -> i64 {
+        self.group_net(self.group_of(account_id), symbol_hash)
+    }
+
+    /// グループの銘柄別ネット建玉（銘柄順、ネット 0 の銘柄は除く）。
+    #[must_use]
+    pub fn group_holdings(&self, group_id: u64) -> Vec<(u64, i64)> {
+        let mut net: BTreeMap<u64, i64> = BTreeMap::new();
+        for (&(account, symbol), &qty) in &self.positions {
+            if self.group_of(account) == group_id {
+                let slot = net.entry(symbol).or_insert(0);
+                *slot = slot.saturating_add(qty);
+            }
+        }
+        net.into_iter().filter(|&(_, n)| n != 0).collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Compression report
+// ---------------------------------------------------------------------------
+
+/// 1 銘柄の圧縮候補。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressionOpportunity {
+    /// 銘柄。
+    pub symbol_hash: u64,
+    /// 買い越しのグループと建玉（グループ ID 順）。
+    pub longs: Vec<(u64, i64)>,
+    /// 売り越しのグループと建玉（グループ ID 順）。
+    pub shorts: Vec<(u64, i64)>,
+    /// 相殺できる数量（買い越し合計と売り越し合計の小さい方）。
+    pub compressible: u64,
+    /// 圧縮で削減できる当初証拠金の見積り。参照価格が無い銘柄は `None`。
+    pub margin_savings: Option<i64>,
+}
+
+/// グループ間の圧縮候補の一覧。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompressionReport {
+    /// 相殺できる建玉のある銘柄（銘柄順）。
+    pub opportunities: Vec<CompressionOpportunity>,
+}
+
+impl CompressionReport {
+    /// 見積れた証拠金削減額の合計。
+    #[must_use]
+    pub fn total_margin_savings(&self) -> i64 {
+        self.opportunities
+            .iter()
+            .filter_map(|o| o.margin_savings)
+            .fold(0_i64, i64::saturating_add)
+    }
+}
+
+impl NettingGroups {
+    /// グループ間で相殺できる建玉の一覧。
+    ///
+    /// 各グループの銘柄別ネット建玉を比べ、買い越しと売り越しのグループが
+    /// 両方ある銘柄を候補とする。証拠金削減額は、グループごとのネット建玉の
+    /// 当初証拠金の合計から、全グループを合算したネット建玉の当初証拠金を
+    /// 引いた額で、`market` の参照価格で評価する。
+    #[must_use]
+    pub fn compression_report<M: MarketContext + ?Sized>(
+        &self,
+        calculator: &MarginCalculator,
+        market: &M,
+    ) -> CompressionReport {
+        let mut nets: BTreeMap<(u64, u64), i64> = BTreeMap::new();
+        for (&(account, symbol), &qty) in &self.positions {
+            let slot = nets.entry((symbol, self.group_of(account))).or_insert(0);
+            *slot = slot.saturating_add(qty);
+        }
+        let mut opportunities: Vec<CompressionOpportunity> = Vec::new();
+        for ((symbol, group), net) in nets {
+            if net == 0 {
+                continue;
+            }
+            if opportunities.last().map(|o| o.symbol_hash) != Some(symbol) {
+                opportunities.push(CompressionOpportunity {
+                    symbol_hash: symbol,
+                    longs: Vec::new(),
+                    shorts: Vec::new(),
+                    compressible: 0,
+                    margin_savings: None,
+                });
+            }
+            if let Some(o) = opportunities.last_mut() {
+                if net > 0 {
+                    o.longs.push((group, net));
+                } else {
+                    o.shorts.push((group, net));
+                }
+            }
+        }
+        opportunities.retain(|o| !o.longs.is_empty() && !o.shorts.is_empty());
+        for o in &mut opportunities {
+            let long: u64 = o.longs.iter().map(|&(_, n)| n.unsigned_abs()).sum();
+            let short: u64 = o.shorts.iter().map(|&(_, n)| n.unsigned_abs()).sum();
+            o.compressible = long.min(short);
+            o.margin_savings = market.reference_price(o.symbol_hash).map(|price| {
+                let standalone = o.longs.iter().chain(&o.shorts).fold(0_i64, |acc, &(_, n)| {
+                    acc.saturating_add(calculator.initial_margin(price, n.unsigned_abs()))
+                });
+                standalone.saturating_sub(calculator.initial_margin(price, long.abs_diff(short)))
+            });
+        }
+        CompressionReport { opportunities }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PreTradeChecker / MarginCalculator
+// ---------------------------------------------------------------------------
+
+impl PreTradeChecker {
+    /// `account_id` の注文を、ポジション上限だけグループでネットした建玉で
+    /// チェックする。
+    ///
+    /// `self` はその口座のチェッカーで、注文単位の制限は口座ごとに適用される。
+    /// `position` の `net_quantity` はグループのネット建玉
+    /// （[`NettingGroups::netted`]）に置き換えて評価する。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::check_order`] と同じ。
+    pub fn check_order_netted(
+        &self,
+        order: &Order,
+        account_id: u64,
+        position: &Position,
+        groups: &NettingGroups,
+    ) -> Result<(), RiskReject> {
+        let netted = Position {
+            net_quantity: groups.netted(account_id, position.symbol_hash),
+            ..position.clone()
+        };
+        self.check_order(order, Some(&netted))
+    }
+}
+
+impl MarginCalculator {
+    /// グループのネット建玉に対する当初証拠金の合計。
+    ///
+    /// 各銘柄は `market` の参照価格で評価する。参照価格の無い銘柄がある場合は
+    /// `None`。
+    #[must_use]
+    pub fn group_initial_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        groups: &NettingGroups,
+        group_id: u64,
+        market: &M,
+    ) -> Option<i64> {
+        groups
+            .group_holdings(group_id)
+            .into_iter()
+            .try_fold(0_i64, |acc, (symbol, net)| {
+                let price = market.reference_price(symbol)?;
+                Some(acc.saturating_add(self.initial_margin(price, net.unsigned_abs())))
+            })
+    }
+
+    /// グループのネット建玉に対する維持証拠金の合計。
+    ///
+    /// 参照価格の無い銘柄がある場合は `None`。
+    #[must_use]
+    pub fn group_maintenance_margin_in<M: MarketContext + ?Sized>(
+        &self,
+        groups: &NettingGroups,
+        group_id: u64,
+        market: &M,
+    ) -> Option<i64> {
+        groups
+            .group_holdings(group_id)
+            .into_iter()
+            .try_fold(0_i64, |acc, (symbol, net)| {
+                let price = market.reference_price(symbol)?;
+                Some(acc.saturating_add(self.maintenance_margin(price, net.unsigned_abs())))
+            })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::margin::MarginParams;
+    use crate::market::{MarketSnapshot, SymbolMarket};
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+    use alice_ledger::Side;
+
+    const MAIN: u64 = 1;
+    const HEDGE: u64 = 2;
+    const OTHER: u64 = 3;
+    const GROUP: u64 = 100;
+
+    fn position(symbol_hash: u64, net_quantity: i64) -> Position {
+        PositionBuilder::new(symbol_hash, net_quantity)
+            .avg_entry_price(0)
+            .build()
+    }
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity).build()
+    }
+
+    fn grouped() -> NettingGroups {
+        let mut groups = NettingGroups::new();
+        groups.assign(MAIN, GROUP);
+        groups.assign(HEDGE, GROUP);
+        groups.set_position(MAIN, &position(7, 90));
+        groups.set_position(HEDGE, &position(7, -60));
+        groups.set_position(OTHER, &position(7, 500));
+        groups
+    }
+
+    #[test]
+    fn group_nets_members_only() {
+        let groups = grouped();
+        assert_eq!(groups.netted(MAIN, 7), 30);
+        assert_eq!(groups.netted(HEDGE, 7), 30);
+        assert_eq!(groups.position(MAIN, 7), 90);
+        // 未割当ての口座は単独グループ。
+        assert_eq!(groups.group_of(OTHER), OTHER);
+        assert_eq!(groups.netted(OTHER, 7), 500);
+        assert_eq!(groups.members(GROUP), [MAIN, HEDGE]);
+        assert!(groups.members(OTHER).is_empty());
+    }
+
+    #[test]
+    fn position_limit_uses_group_net() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_position: 100,
+            ..RiskLimits::default()
+        });
+        let groups = grouped();
+        let main = position(7, 90);
+        let buy = order(Side::Bid, 50);
+        // 口座単独では 140 で超過だが、グループでは 80。
+        assert!(checker.check_order(&buy, Some(&main)).is_err());
+        assert!(checker
+            .check_order_netted(&buy, MAIN, &main, &groups)
+            .is_ok());
+        // 注文単位の制限は口座ごとに掛かる。
+        let big = order(Side::Bid, 1_000);
+        assert!(matches!(
+            checker.check_order_netted(&big, MAIN, &main, &groups),
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn unassign_restores_standalone() {
+        let mut groups = grouped();
+        groups.unassign(HEDGE);
+        assert_eq!(groups.netted(MAIN, 7), 90);
+        assert_eq!(groups.netted(HEDGE, 7), -60);
+    }
+
+    #[test]
+    fn margin_on_group_net() {
+        let mut groups = grouped();
+        groups.set_position(HEDGE, &position(8, -5));
+        let mut market = MarketSnapshot::new();
+        for symbol in [7, 8] {
+            market.insert(
+                symbol,
+                SymbolMarket {
+                    reference_price: Some(10_000),
+                    ..SymbolMarket::default()
+                },
+            );
+        }
+        let calc = MarginCalculator::new(MarginParams::default());
+        // |30| + |-5| = 35 lots × 10_000 × 10%.
+        assert_eq!(
+            calc.group_initial_margin_in(&groups, GROUP, &market),
+            Some(35_000)
+        );
+        assert_eq!(
+            calc.group_maintenance_margin_in(&groups, GROUP, &market),
+            Some(17_500)
+        );
+        assert_eq!(
+            calc.group_initial_margin_in(&groups, GROUP, &MarketSnapshot::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn compression_across_groups() {
+        let mut groups = grouped();
+        // グループ内は既にネット済み（30）。OTHER（500）とは同方向。
+        groups.set_position(4, &position(7, -200));
+        groups.set_position(4, &position(8, 10));
+        groups.set_position(OTHER, &position(8, 5));
+        let mut market = MarketSnapshot::new();
+        market.insert(
+            7,
+            SymbolMarket {
+                reference_price: Some(100),
+                ..SymbolMarket::default()
+            },
+        );
+        let calc = MarginCalculator::new(MarginParams::default());
+        let report = groups.compression_report(&calc, &market);
+        assert_eq!(
+            report.opportunities,
+            [CompressionOpportunity {
+                symbol_hash: 7,
+                longs: vec![(OTHER, 500), (GROUP, 30)],
+                shorts: vec![(4, -200)],
+                compressible: 200,
+                // (530 + 200 − 330) × 100 × 10%。
+                margin_savings: Some(4_000),
+            }]
+        );
+        assert_eq!(report.total_margin_savings(), 4_000);
+        let unpriced = groups.compression_report(&calc, &MarketSnapshot::new());
+        assert_eq!(unpriced.opportunities[0].margin_savings, None);
+        assert_eq!(unpriced.total_margin_savings(), 0);
+    }
+}
@@ -0,0 +1,502 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 満期前後のオプション建玉のリスク管理。
+//!
+//! 売りオプションは満期に権利行使を割り当てられると、一夜にして原資産の
+//! 建玉に変わる。[`AssignmentBuffer`] は満期が近い売りオプションについて、
+//! 割当確率で加重した原資産エクスポージャーに株式の維持証拠金率を掛けた
+//! 上乗せ額を求め、維持証拠金に加える。これにより満期日に突然証拠金不足に
+//! なることを避ける。割当確率は呼び出し側が与える（|delta| を使うのが一般的）。
+//!
+//! [`ExpiryDayRules`] は満期日の取引規制で、権利行使の締切前の一定時刻以降は
+//! 建玉を増やす注文を拒否し、自動権利行使の対象になりそうなディープ・イン・ザ・
+//! マネーの売り建玉を警告する。満期日は [`Instrument`] の `expiry_ns` と
+//! `final_session_start_ns`（営業日カレンダーで決まる満期日の最終セッション
+//! 開始時刻）で判定する。満期日の建玉は小さな値動きで delta が急変する
+//! ため、通常の [`GreeksLimits`](crate::GreeksLimits) とは別に満期日の建玉だけの
+//! gamma 合計を [`ExpiryDayRules::check_expiry_gamma`] で制限する
+//! （0DTE ブック向け）。グリークスは呼び出し側が与える。
+
+use alice_ledger::{Order, Side};
+
+use crate::greeks::GreeksExposure;
+use crate::instrument::{ExpiryRestriction, Instrument, DAY_NS};
+use crate::market::MarketContext;
+use crate::regt::OptionKind;
+
+// ---------------------------------------------------------------------------
+// OptionContract
+// ---------------------------------------------------------------------------
+
+/// オプション銘柄。
+///
+/// 満期・取引単位倍率・数量の小数桁数は [`Instrument`] のものを使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionContract {
+    /// オプション銘柄のメタデータ。
+    pub instrument: Instrument,
+    /// 原資産の銘柄。
+    pub underlying_hash: u64,
+    /// コール / プット。
+    pub kind: OptionKind,
+    /// 行使価格。
+    pub strike: i64,
+}
+
+impl OptionContract {
+    /// 原資産価格 `underlying` での本質的価値（原資産 1 単位あたり、0 以上）。
+    #[must_use]
+    pub fn intrinsic(&self, underlying: i64) -> i64 {
+        let value = match self.kind {
+            OptionKind::Call => i128::from(underlying) - i128::from(self.strike),
+            OptionKind::Put => i128::from(self.strike) - i128::from(underlying),
+        };
+        value.clamp(0, i128::from(i64::MAX)) as i64
+    }
+
+    /// 権利行使で受け渡す原資産の売買方向（建玉の持ち手から見て）。
+    ///
+    /// コールの買い手は原資産を買い、プットの買い手は売る。売り手は逆。
+    #[must_use]
+    pub const fn exercise_side(&self, long: bool) -> Side {
+        match (self.kind, long) {
+            (OptionKind::Call, true) | (OptionKind::Put, false) => Side::Bid,
+            (OptionKind::Call, false) | (OptionKind::Put, true) => Side::Ask,
+        }
+    }
+
+    /// `now_ns` から満期までの残り時間。満期の無い銘柄は `None`、満期後は 0。
+    #[must_use]
+    pub const fn time_to_expiry(&self, now_ns: u64) -> Option<u64> {
+        match self.instrument.expiry_ns {
+            Some(expiry) => Some(expiry.saturating_sub(now_ns)),
+            None => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AssignmentBuffer
+// ---------------------------------------------------------------------------
+
+/// 満期が近い売りオプションの割当リスク上乗せ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssignmentBuffer {
+    /// 満期までの残りがこの時間以内の建玉に上乗せする（ns）。
+    pub window_ns: u64,
+    /// 割り当てられた原資産の建玉に掛ける維持証拠金率（bps）。
+    pub maintenance_bps: u32,
+}
+
+impl Default for AssignmentBuffer {
+    fn default() -> Self {
+        Self {
+            window_ns: 2 * DAY_NS,
+            maintenance_bps: 2_500,
+        }
+    }
+}
+
+impl AssignmentBuffer {
+    /// 1 建玉の上乗せ額。
+    ///
+    /// `net` はオプションのネット建玉（売りが負）、`probability_bps` は割当
+    /// 確率（10000 = 確実）。売り建玉で、満期まで `window_ns` 以内（満期後を
+    /// 含む）の場合だけ
+    /// `原資産の想定元本 × probability_bps × maintenance_bps / 10000²` を返す。
+    /// 原資産の想定元本は [`Instrument::notional`] で取引単位倍率と数量の
+    /// 小数桁数を反映する。
+    #[must_use]
+    pub fn buffer(
+        &self,
+        contract: &OptionContract,
+        net: i64,
+        probability_bps: u32,
+        underlying_price: i64,
+        now_ns: u64,
+    ) -> i64 {
+        let near_expiry = contract
+            .time_to_expiry(now_ns)
+            .is_some_and(|t| t <= self.window_ns);
+        if net >= 0 || !near_expiry {
+            return 0;
+        }
+        let exposure = contract
+            .instrument
+            .notional(underlying_price, net.unsigned_abs());
+        let probability = i128::from(probability_bps.min(10_000));
+        (i128::from(exposure) * probability * i128::from(self.maintenance_bps) / 100_000_000)
+            .min(i128::from(i64::MAX)) as i64
+    }
+
+    /// `holdings` 全体の上乗せ額。
+    ///
+    /// 各要素は（銘柄, ネット建玉, 割当確率 bps）。原資産価格は `market` の
+    /// 参照価格で、上乗せ対象の建玉の原資産に参照価格が無い場合は `None`。
+    #[must_use]
+    pub fn portfolio_buffer<M: MarketContext + ?Sized>(
+        &self,
+        holdings: &[(OptionContract, i64, u32)],
+        market: &M,
+        now_ns: u64,
+    ) -> Option<i64> {
+        holdings
+            .iter()
+            .try_fold(0_i64, |acc, (contract, net, probability_bps)| {
+                let applies = *net < 0
+                    && contract
+                        .time_to_expiry(now_ns)
+                        .is_some_and(|t| t <= self.window_ns);
+                if !applies {
+                    return Some(acc);
+                }
+                let price = market.reference_price(contract.underlying_hash)?;
+                Some(acc.saturating_add(self.buffer(
+                    contract,
+                    *net,
+                    *probability_bps,
+                    price,
+                    now_ns,
+                )))
+            })
+    }
+
+    /// 維持証拠金 `maintenance_margin` に `holdings` の上乗せ額を加える。
+    ///
+    /// 上乗せ額が求まらない場合は `None`（[`Self::portfolio_buffer`]）。
+    #[must_use]
+    pub fn apply<M: MarketContext + ?Sized>(
+        &self,
+        maintenance_margin: i64,
+        holdings: &[(OptionContract, i64, u32)],
+        market: &M,
+        now_ns: u64,
+    ) -> Option<i64> {
+        Some(maintenance_margin.saturating_add(self.portfolio_buffer(holdings, market, now_ns)?))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ExpiryDayRules
+// ---------------------------------------------------------------------------
+
+/// 満期日の規制による拒否。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionsReject {
+    /// 締切時刻以降に建玉を増やす注文。
+    OpeningAfterCutoff { symbol_hash: u64, cutoff_ns: u64 },
+    /// 満期日の建玉の gamma 合計が上限を超えた。
+    ExpiryGammaExceeded { current: i64, limit: i64 },
+}
+
+/// 自動権利行使が近いディープ・イン・ザ・マネーの売り建玉の警告。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoExerciseWarning {
+    /// オプション銘柄。
+    pub symbol_hash: u64,
+    /// ネット建玉（負）。
+    pub net: i64,
+    /// 原資産 1 単位あたりの本質的価値。
+    pub intrinsic: i64,
+    /// 満期時刻。
+    pub expiry_ns: u64,
+}
+
+/// 満期日の取引規制。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpiryDayRules {
+    /// 満期のこの時間前以降は建玉を増やす注文を拒否する（ns）。
+    pub cutoff_before_expiry_ns: u64,
+    /// 本質的価値が行使価格のこの割合（bps）以上ならディープ・イン・ザ・マネー。
+    pub deep_itm_bps: u32,
+    /// 満期日の建玉の gamma 合計の絶対値上限（ticks）。
+    pub max_abs_expiry_gamma: i64,
+}
+
+impl Default for ExpiryDayRules {
+    fn default() -> Self {
+        Self {
+            cutoff_before_expiry_ns: DAY_NS / 24,
+            deep_itm_bps: 500,
+            max_abs_expiry_gamma: 1_000,
+        }
+    }
+}
+
+impl ExpiryDayRules {
+    /// 新規建ての締切時刻。満期の無い銘柄は `None`。
+    #[must_use]
+    pub const fn cutoff_ns(&self, contract: &OptionContract) -> Option<u64> {
+        match contract.instrument.expiry_ns {
+            Some(expiry) => Some(expiry.saturating_sub(self.cutoff_before_expiry_ns)),
+            None => None,
+        }
+    }
+
+    /// `now_ns` が満期日（最終セッション開始以降、未設定なら締切以降）か。
+    #[must_use]
+    pub const fn is_expiry_day(&self, contract: &OptionContract, now_ns: u64) -> bool {
+        match (
+            contract.instrument.final_session_start_ns,
+            self.cutoff_ns(contract),
+        ) {
+            (Some(start), _) => now_ns >= start,
+            (None, Some(cutoff)) => now_ns >= cutoff,
+            (None, None) => false,
+        }
+    }
+
+    /// ネット建玉 `net` に対する `order` を判定する。
+    ///
+    /// 締切時刻以降は建玉を減らす注文（建玉数量以内）だけを通す。
+    ///
+    /// # Errors
+    ///
+    /// [`OptionsReject::OpeningAfterCutoff`]。
+    pub fn check_order(
+        &self,
+        contract: &OptionContract,
+        order: &Order,
+        net: i64,
+        now_ns: u64,
+    ) -> Result<(), OptionsReject> {
+        let Some(cutoff_ns) = self.cutoff_ns(contract) else {
+            return Ok(());
+        };
+        if now_ns < cutoff_ns || ExpiryRestriction::ReduceOnly.permits(order, net) {
+            return Ok(());
+        }
+        Err(OptionsReject::OpeningAfterCutoff {
+            symbol_hash: contract.instrument.symbol_hash,
+            cutoff_ns,
+        })
+    }
+
+    /// 満期日に、原資産価格 `underlying` でディープ・イン・ザ・マネーの売り
+    /// 建玉なら警告を返す。
+    #[must_use]
+    pub fn auto_exercise_warning(
+        &self,
+        contract: &OptionContract,
+        net: i64,
+        underlying: i64,
+        now_ns: u64,
+    ) -> Option<AutoExerciseWarning> {
+        let expiry_ns = contract.instrument.expiry_ns?;
+        if net >= 0 || !self.is_expiry_day(contract, now_ns) {
+            return None;
+        }
+        let intrinsic = contract.intrinsic(underlying);
+        let threshold = i128::from(contract.strike).abs() * i128::from(self.deep_itm_bps) / 10_000;
+        (intrinsic > 0 && i128::from(intrinsic) >= threshold).then_some(AutoExerciseWarning {
+            symbol_hash: contract.instrument.symbol_hash,
+            net,
+            intrinsic,
+            expiry_ns,
+        })
+    }
+
+    /// `holdings`（銘柄と建玉のグリークス）のうち、満期日の建玉の gamma 合計。
+    #[must_use]
+    pub fn expiry_gamma(&self, holdings: &[(OptionContract, GreeksExposure)], now_ns: u64) -> i64 {
+        holdings
+            .iter()
+            .filter(|(contract, _)| self.is_expiry_day(contract, now_ns))
+            .fold(0_i64, |acc, (_, greeks)| acc.saturating_add(greeks.gamma))
+    }
+
+    /// 満期日の建玉の gamma 合計が上限内か。
+    ///
+    /// 注文前の判定では、注文が約定した後の建玉のグリークスを `holdings` に
+    /// 与える。
+    ///
+    /// # Errors
+    ///
+    /// [`OptionsReject::ExpiryGammaExceeded`]。
+    pub fn check_expiry_gamma(
+        &self,
+        holdings: &[(OptionContract, GreeksExposure)],
+        now_ns: u64,
+    ) -> Result<(), OptionsReject> {
+        let current = self.expiry_gamma(holdings, now_ns);
+        if current.saturating_abs() > self.max_abs_expiry_gamma {
+            return Err(OptionsReject::ExpiryGammaExceeded {
+                current,
+                limit: self.max_abs_expiry_gamma,
+            });
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{MarketSnapshot, SymbolMarket};
+    use crate::test_utils::OrderBuilder;
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 5, quantity).build()
+    }
+
+    fn call(strike: i64, expiry_ns: u64) -> OptionContract {
+        OptionContract {
+            instrument: Instrument::expiring(10, expiry_ns, expiry_ns).with_multiplier(100),
+            underlying_hash: 1,
+            kind: OptionKind::Call,
+            strike,
+        }
+    }
+
+    #[test]
+    fn contract_basics() {
+        let c = call(100, 5 * DAY_NS);
+        assert_eq!(c.intrinsic(110), 10);
+        assert_eq!(c.intrinsic(90), 0);
+        let put = OptionContract {
+            kind: OptionKind::Put,
+            ..c
+        };
+        assert_eq!(put.intrinsic(90), 10);
+        assert_eq!(c.exercise_side(false), Side::Ask);
+        assert_eq!(put.exercise_side(false), Side::Bid);
+        assert_eq!(c.time_to_expiry(6 * DAY_NS), Some(0));
+    }
+
+    #[test]
+    fn buffers_short_options_near_expiry() {
+        let buffer = AssignmentBuffer::default();
+        let c = call(100, 5 * DAY_NS);
+        // 満期の 1 日前、10 枚の売り、割当確率 60%。
+        // 110 × 10 × 100 = 110_000 × 60% × 25% = 16_500。
+        assert_eq!(buffer.buffer(&c, -10, 6_000, 110, 4 * DAY_NS), 16_500);
+        // 満期まで 2 日を超える、買い建玉は上乗せしない。
+        assert_eq!(buffer.buffer(&c, -10, 6_000, 110, 2 * DAY_NS), 0);
+        assert_eq!(buffer.buffer(&c, 10, 6_000, 110, 4 * DAY_NS), 0);
+        // 確率は 100% で頭打ち。
+        assert_eq!(
+            buffer.buffer(&c, -10, 20_000, 110, 4 * DAY_NS),
+            buffer.buffer(&c, -10, 10_000, 110, 4 * DAY_NS)
+        );
+    }
+
+    #[test]
+    fn portfolio_buffer_uses_market() {
+        let buffer = AssignmentBuffer::default();
+        let mut market = MarketSnapshot::new();
+        let near = call(100, 5 * DAY_NS);
+        let far = OptionContract {
+            underlying_hash: 2,
+            ..call(100, 30 * DAY_NS)
+        };
+        let holdings = [(near, -10, 6_000), (far, -10, 9_000)];
+        // 期近の原資産に参照価格が無い。
+        assert_eq!(
+            buffer.portfolio_buffer(&holdings, &market, 4 * DAY_NS),
+            None
+        );
+        market.insert(
+            1,
+            SymbolMarket {
+                reference_price: Some(110),
+                ..SymbolMarket::default()
+            },
+        );
+        // 期先の原資産に価格が無くても、上乗せ対象外なので影響しない。
+        assert_eq!(
+            buffer.apply(1_000, &holdings, &market, 4 * DAY_NS),
+            Some(17_500)
+        );
+    }
+
+    #[test]
+    fn blocks_opening_after_cutoff() {
+        let rules = ExpiryDayRules::default();
+        let c = call(100, 5 * DAY_NS);
+        let cutoff = 5 * DAY_NS - DAY_NS / 24;
+        assert_eq!(rules.cutoff_ns(&c), Some(cutoff));
+        assert!(rules
+            .check_order(&c, &order(Side::Ask, 5), -10, cutoff - 1)
+            .is_ok());
+        assert_eq!(
+            rules.check_order(&c, &order(Side::Ask, 5), -10, cutoff),
+            Err(OptionsReject::OpeningAfterCutoff {
+                symbol_hash: 10,
+                cutoff_ns: cutoff,
+            })
+        );
+        // 手仕舞いは通すが、ドテンになる数量は拒否する。
+        assert!(rules
+            .check_order(&c, &order(Side::Bid, 10), -10, cutoff)
+            .is_ok());
+        assert!(rules
+            .check_order(&c, &order(Side::Bid, 11), -10, cutoff)
+            .is_err());
+        let perpetual = OptionContract {
+            instrument: Instrument::perpetual(11),
+            ..c
+        };
+        assert!(rules
+            .check_order(&perpetual, &order(Side::Ask, 5), 0, u64::MAX)
+            .is_ok());
+    }
+
+    #[test]
+    fn warns_on_deep_itm_shorts_on_expiry_day() {
+        let rules = ExpiryDayRules::default();
+        // 最終セッションは 4.5 日目から。
+        let c = OptionContract {
+            instrument: Instrument::expiring(10, 5 * DAY_NS, 9 * DAY_NS / 2),
+            ..call(100, 5 * DAY_NS)
+        };
+        let day = 9 * DAY_NS / 2;
+        assert_eq!(
+            rules.auto_exercise_warning(&c, -3, 105, day),
+            Some(AutoExerciseWarning {
+                symbol_hash: 10,
+                net: -3,
+                intrinsic: 5,
+                expiry_ns: 5 * DAY_NS,
+            })
+        );
+        // 前日、行使価格の 5% 未満、買い建玉は警告しない。
+        assert_eq!(rules.auto_exercise_warning(&c, -3, 105, day - 1), None);
+        assert_eq!(rules.auto_exercise_warning(&c, -3, 104, day), None);
+        assert_eq!(rules.auto_exercise_warning(&c, 3, 110, day), None);
+    }
+
+    #[test]
+    fn limits_gamma_of_expiring_positions_only() {
+        let rules = ExpiryDayRules::default();
+        let greeks = |gamma| GreeksExposure {
+            delta: 0,
+            gamma,
+            vega: 0,
+        };
+        let today = OptionContract {
+            instrument: Instrument::expiring(10, 5 * DAY_NS, 4 * DAY_NS),
+            ..call(100, 5 * DAY_NS)
+        };
+        let later = call(100, 30 * DAY_NS);
+        let holdings = [(today, greeks(-800)), (later, greeks(-5_000))];
+        assert_eq!(rules.expiry_gamma(&holdings, 4 * DAY_NS), -800);
+        assert!(rules.check_expiry_gamma(&holdings, 4 * DAY_NS).is_ok());
+        // 前日は満期日の建玉が無い。
+        assert_eq!(rules.expiry_gamma(&holdings, 4 * DAY_NS - 1), 0);
+        let holdings = [(today, greeks(-800)), (today, greeks(-201))];
+        assert_eq!(
+            rules.check_expiry_gamma(&holdings, 4 * DAY_NS),
+            Err(OptionsReject::ExpiryGammaExceeded {
+                current: -1_001,
+                limit: 1_000,
+            })
+        );
+    }
+}
@@ -0,0 +1,363 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 注文 ID 単位の未約定（板に滞留中の）注文管理。
+//!
+//! 件数だけのカウンターでは、日次リセットで GTC 注文まで消えてしまう。
+//! [`OpenOrders`] は注文 ID ごとに執行条件を保持し、日次リセット時には
+//! 当日限りの注文だけを落として GTC 注文を翌日へ持ち越す。未約定注文の
+//! 想定元本合計も併せて保持する（小口 500 件と大口 500 件は同じ件数でも
+//! リスクがまったく異なるため）。銘柄が分かる注文は銘柄別の件数も数える。
+//!
+//! 各注文の発注時刻も保持し、設定した最大経過時間を超えた注文を
+//! [`StaleOrder`] として報告する。忘れられた古い注文は予期しない約定の
+//! 典型的な原因であるため。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Side, TimeInForce};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// WorkingOrder
+// ---------------------------------------------------------------------------
+
+/// 板に滞留中の 1 注文。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingOrder {
+    /// 注文 ID。
+    pub order_id: u64,
+    /// GTC（取消まで有効）なら `true`。日次リセットを越えて持ち越される。
+    pub good_till_cancel: bool,
+    /// 売買の別。
+    pub side: Side,
+    /// 未約定の残数量。
+    pub remaining: u64,
+    /// 未約定数量の想定元本（価格 × 残数量、ticks）。
+    pub notional: i64,
+    /// 銘柄（`Position::symbol_hash`）。不明なら `None` で、銘柄別件数に数えない。
+    pub symbol_hash: Option<u64>,
+    /// 発注時刻（ns、`Order::timestamp_ns`）。
+    pub placed_ns: u64,
+}
+
+impl WorkingOrder {
+    /// 注文から作成。
+    #[must_use]
+    pub const fn from_order(order: &Order) -> Self {
+        let remaining = order.quantity.saturating_sub(order.filled_quantity);
+        Self {
+            order_id: order.id.0,
+            good_till_cancel: matches!(order.time_in_force, TimeInForce::GTC),
+            side: order.side,
+            remaining,
+            notional: saturate_i64((order.price as i128 * remaining as i128).abs()),
+            symbol_hash: None,
+            placed_ns: order.timestamp_ns,
+        }
+    }
+
+    /// 銘柄を設定する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_symbol(mut self, symbol_hash: u64) -> Self {
+        self.symbol_hash = Some(symbol_hash);
+        self
+    }
+
+    /// `now_ns` 時点での経過時間（ns）。
+    #[must_use]
+    pub const fn age_ns(&self, now_ns: u64) -> u64 {
+        now_ns.saturating_sub(self.placed_ns)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// StaleOrder
+// ---------------------------------------------------------------------------
+
+/// 最大経過時間を超えた注文への推奨対応。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StaleAction {
+    /// 警告のみ。
+    #[default]
+    Warn,
+    /// 取消を推奨する（実際の取消は呼び出し側が行う）。
+    RecommendCancel,
+}
+
+/// 最大経過時間を超えて板に残っている注文。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleOrder {
+    /// 注文 ID。
+    pub order_id: u64,
+    /// 銘柄（不明なら `None`）。
+    pub symbol_hash: Option<u64>,
+    /// 経過時間（ns）。
+    pub age_ns: u64,
+    /// 推奨対応。
+    pub action: StaleAction,
+}
+
+// ---------------------------------------------------------------------------
+// OpenOrders
+// ---------------------------------------------------------------------------
+
+/// `i128` を `i64` の範囲へ飽和させる。
+const fn saturate_i64(v: i128) -> i64 {
+    if v > i64::MAX as i128 {
+        i64::MAX
+    } else if v < i64::MIN as i128 {
+        i64::MIN
+    } else {
+        v as i64
+    }
+}
+
+/// 注文 ID をキーとする未約定注文の集合。
+#[derive(Debug, Clone, Default)]
+pub struct OpenOrders {
+    orders: BTreeMap<u64, WorkingOrder>,
+    /// 全注文の想定元本合計（飽和前の厳密値）。
+    total_notional: i128,
+    /// 銘柄別の注文数（0 件の銘柄は保持しない）。
+    per_symbol: BTreeMap<u64, u32>,
+}
+
+impl OpenOrders {
+    /// 空の集合を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            orders: BTreeMap::new(),
+            total_notional: 0,
+            per_symbol: BTreeMap::new(),
+        }
+    }
+
+    /// 注文を登録する。同じ ID が既にあれば置き換えて `false` を返す。
+    pub fn insert(&mut self, order: WorkingOrder) -> bool {
+        self.total_notional += i128::from(order.notional);
+        if let Some(symbol) = order.symbol_hash {
+            *self.per_symbol.entry(symbol).or_insert(0) += 1;
+        }
+        match self.orders.insert(order.order_id, order) {
+            Some(old) => {
+                self.forget(&old);
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// 注文を取り除く（取消・全量約定）。未登録なら `None`。
+    pub fn remove(&mut self, order_id: u64) -> Option<WorkingOrder> {
+        let removed = self.orders.remove(&order_id)?;
+        self.forget(&removed);
+        Some(removed)
+    }
+
+    /// 取り除いた注文を集計から差し引く。
+    fn forget(&mut self, order: &WorkingOrder) {
+        self.total_notional -= i128::from(order.notional);
+        if let Some(symbol) = order.symbol_hash {
+            if let Some(count) = self.per_symbol.get_mut(&symbol) {
+                *count -= 1;
+                if *count == 0 {
+                    self.per_symbol.remove(&symbol);
+                }
+            }
+        }
+    }
+
+    /// 指定銘柄の注文数。
+    #[must_use]
+    pub fn count_for(&self, symbol_hash: u64) -> u32 {
+        self.per_symbol.get(&symbol_hash).copied().unwrap_or(0)
+    }
+
+    /// 全未約定注文の想定元本合計（ticks、`i64` へ飽和）。
+    #[must_use]
+    pub const fn total_notional(&self) -> i64 {
+        saturate_i64(self.total_notional)
+    }
+
+    /// 登録済みか。
+    #[must_use]
+    pub fn contains(&self, order_id: u64) -> bool {
+        self.orders.contains_key(&order_id)
+    }
+
+    /// 指定 ID の注文。
+    #[must_use]
+    pub fn get(&self, order_id: u64) -> Option<&WorkingOrder> {
+        self.orders.get(&order_id)
+    }
+
+    /// 注文数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// 空か。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// 注文 ID 順の走査。
+    pub fn iter(&self) -> impl Iterator<Item = &WorkingOrder> {
+        self.orders.values()
+    }
+
+    /// `now_ns` 時点で経過時間が `max_age_ns` を超えた注文（注文 ID 順）。
+    pub fn older_than(&self, now_ns: u64, max_age_ns: u64) -> impl Iterator<Item = &WorkingOrder> {
+        self.orders
+            .values()
+            .filter(move |o| o.age_ns(now_ns) > max_age_ns)
+    }
+
+    /// 日次リセット: 当日限りの注文を落とし、GTC 注文だけを残す。
+    ///
+    /// 落とした注文数を返す。
+    pub fn roll_day(&mut self) -> usize {
+        let before = self.orders.len();
+        let dropped: alloc::vec::Vec<u64> = self
+            .orders
+            .values()
+            .filter(|o| !o.good_till_cancel)
+            .map(|o| o.order_id)
+            .collect();
+        for id in dropped {
+            self.remove(id);
+        }
+        before - self.orders.len()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+    use alice_ledger::Side;
+
+    fn order(id: u64, tif: TimeInForce) -> Order {
+        OrderBuilder::bid(1000, 1).id(id).time_in_force(tif).build()
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut open = OpenOrders::new();
+        assert!(open.insert(WorkingOrder::from_order(&order(1, TimeInForce::GTC))));
+        assert!(!open.insert(WorkingOrder::from_order(&order(1, TimeInForce::GTC))));
+        assert_eq!(open.len(), 1);
+        assert!(open.contains(1));
+        assert_eq!(open.remove(1).map(|o| o.order_id), Some(1));
+        assert_eq!(open.remove(1), None);
+        assert!(open.is_empty());
+    }
+
+    #[test]
+    fn notional_tracks_remaining_and_replacement() {
+        let mut open = OpenOrders::new();
+        let mut o = order(1, TimeInForce::GTC);
+        o.quantity = 10;
+        o.filled_quantity = 4;
+        assert_eq!(WorkingOrder::from_order(&o).notional, 6_000);
+        open.insert(WorkingOrder::from_order(&o));
+        o.filled_quantity = 9;
+        open.insert(WorkingOrder::from_order(&o));
+        assert_eq!(open.total_notional(), 1_000);
+        open.remove(1);
+        assert_eq!(open.total_notional(), 0);
+    }
+
+    #[test]
+    fn total_notional_saturates() {
+        let mut open = OpenOrders::new();
+        for id in 0..3 {
+            open.insert(WorkingOrder {
+                order_id: id,
+                good_till_cancel: true,
+                side: Side::Bid,
+                remaining: 1,
+                notional: i64::MAX,
+                symbol_hash: None,
+                placed_ns: 0,
+            });
+        }
+        assert_eq!(open.total_notional(), i64::MAX);
+        open.remove(0);
+        open.remove(1);
+        assert_eq!(open.total_notional(), i64::MAX);
+    }
+
+    #[test]
+    fn per_symbol_counts() {
+        let mut open = OpenOrders::new();
+        open.insert(WorkingOrder::from_order(&order(1, TimeInForce::GTC)).with_symbol(7));
+        open.insert(WorkingOrder::from_order(&order(2, TimeInForce::IOC)).with_symbol(7));
+        open.insert(WorkingOrder::from_order(&order(3, TimeInForce::GTC)).with_symbol(8));
+        open.insert(WorkingOrder::from_order(&order(4, TimeInForce::GTC)));
+        assert_eq!((open.count_for(7), open.count_for(8)), (2, 1));
+        // 銘柄を付け替えた置き換え。
+        open.insert(WorkingOrder::from_order(&order(3, TimeInForce::GTC)).with_symbol(7));
+        assert_eq!((open.count_for(7), open.count_for(8)), (3, 0));
+        assert_eq!(open.roll_day(), 1);
+        assert_eq!(open.count_for(7), 2);
+        open.remove(1);
+        open.remove(3);
+        assert_eq!(open.count_for(7), 0);
+        assert_eq!(open.len(), 1);
+    }
+
+    #[test]
+    fn older_than_filters_by_age() {
+        let mut open = OpenOrders::new();
+        for (id, ts) in [(1, 0), (2, 50), (3, 100)] {
+            let mut o = order(id, TimeInForce::GTC);
+            o.timestamp_ns = ts;
+            open.insert(WorkingOrder::from_order(&o));
+        }
+        let ids: Vec<u64> = open.older_than(150, 50).map(|o| o.order_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(open.get(3).map(|o| o.age_ns(150)), Some(50));
+        // 時計が発注時刻より前でも経過時間は 0。
+        assert_eq!(open.get(3).map(|o| o.age_ns(10)), Some(0));
+    }
+
+    #[test]
+    fn gtc_flag_from_tif() {
+        assert!(WorkingOrder::from_order(&order(1, TimeInForce::GTC)).good_till_cancel);
+        assert!(!WorkingOrder::from_order(&order(1, TimeInForce::IOC)).good_till_cancel);
+    }
+
+    #[test]
+    fn roll_day_keeps_gtc() {
+        let mut open = OpenOrders::new();
+        open.insert(WorkingOrder::from_order(&order(1, TimeInForce::GTC)));
+        open.insert(WorkingOrder {
+            order_id: 2,
+            good_till_cancel: false,
+            side: Side::Ask,
+            remaining: 1,
+            notional: 500,
+            symbol_hash: None,
+            placed_ns: 0,
+        });
+        open.insert(WorkingOrder::from_order(&order(3, TimeInForce::GTC)));
+        assert_eq!(open.total_notional(), 2_500);
+        assert_eq!(open.roll_day(), 1);
+        assert_eq!(open.total_notional(), 2_000);
+        let ids: Vec<u64> = open.iter().map(|o| o.order_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        assert_eq!(open.roll_day(), 0);
+    }
+}
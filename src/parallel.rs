@@ -0,0 +1,97 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! ポートフォリオ計算のデータ並列実行（`rayon` フィーチャー）。
+//!
+//! 証拠金・`VaR`・ストレスの各計算器は、シナリオ単位・ポジション（銘柄）
+//! 単位のループをこのモジュール経由で回す。`rayon` フィーチャーが有効なら
+//! rayon のスレッドプールで並列に、無効なら同じ分割のまま逐次に実行する。
+//!
+//! 合算は入力を [`CHUNK_LEN`] 件ずつの連続したチャンクに分け、チャンク内を
+//! 先頭から、チャンクの部分結果をチャンク順に畳み込む。分割はスレッド数に
+//! よらないため、飽和演算を含む合算でも結果は決定的で、フィーチャーの
+//! 有無でも変わらない。
+
+use core::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// 合算時に 1 タスクが受け持つ要素数。
+pub(crate) const CHUNK_LEN: usize = 1_024;
+
+/// `items` の各要素に `f` を適用し、結果を入力順に返す。
+pub(crate) fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// `items` を [`CHUNK_LEN`] 件ずつのチャンクに分けて `f` を適用し、部分結果を
+/// チャンク順に返す。
+pub(crate) fn map_chunks<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&[T]) -> R + Sync + Send,
+{
+    map_ranges(items.len(), |range| f(&items[range]))
+}
+
+/// `0..len` を [`CHUNK_LEN`] 件ずつの範囲に分けて `f` を適用し、部分結果を
+/// 範囲の順に返す。複数のスライスを同じ添字で走査する場合に使う。
+pub(crate) fn map_ranges<R, F>(len: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(Range<usize>) -> R + Sync + Send,
+{
+    let chunk = |i: usize| f(i * CHUNK_LEN..len.min((i + 1) * CHUNK_LEN));
+    #[cfg(feature = "rayon")]
+    {
+        (0..len.div_ceil(CHUNK_LEN))
+            .into_par_iter()
+            .map(chunk)
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..len.div_ceil(CHUNK_LEN)).map(chunk).collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_reduce_in_order() {
+        let items: Vec<u64> = (0..10_000).collect();
+        let sums = map_chunks(&items, |c| (c[0], c.iter().sum::<u64>()));
+        assert_eq!(sums.len(), 10_000_usize.div_ceil(CHUNK_LEN));
+        assert!(sums.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(sums.iter().map(|s| s.1).sum::<u64>(), 49_995_000);
+        assert_eq!(map_chunks(&items[..10], <[u64]>::len), [10]);
+        assert!(map_chunks(&items[..0], <[u64]>::len).is_empty());
+        assert_eq!(
+            map_ranges(CHUNK_LEN + 1, |r| r),
+            [0..CHUNK_LEN, CHUNK_LEN..CHUNK_LEN + 1]
+        );
+        assert_eq!(map(&items[..3], |&i| i * 2), [0, 2, 4]);
+    }
+}
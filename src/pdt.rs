@@ -0,0 +1,362 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 米国株のパターン・デイトレーダー（PDT）規制と買付余力。
+//!
+//! [`PdtTracker`] は約定から日計り取引（同じ営業日に建てて返済した往復）を
+//! 数え、直近 [`PdtParams::window_days`] 営業日に
+//! [`PdtParams::day_trade_threshold`] 回以上になると口座を PDT に指定して
+//! [`PdtEvent::Flagged`] を返す。営業日の区切りは呼び出し側が
+//! [`PdtTracker::roll_day`] で伝える（取引所カレンダーは持たない）。
+//!
+//! 買付余力は維持証拠金を超える余剰資産の倍数で、PDT で最低資産額を満たす
+//! 口座は日中 4 倍、それ以外（および翌日持ち越し）は 2 倍とする。最低資産額を
+//! 割り込んだ PDT 口座は日計り取引を禁止する。
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use alice_ledger::Side;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// PdtParams
+// ---------------------------------------------------------------------------
+
+/// PDT 規制の設定。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdtParams {
+    /// PDT 口座に求める最低資産額（ticks）。
+    pub min_equity: i64,
+    /// 日計り取引を数える営業日数。
+    pub window_days: u32,
+    /// PDT に指定する日計り取引の回数。
+    pub day_trade_threshold: u32,
+    /// PDT 口座の日中買付余力の倍率。
+    pub intraday_multiplier: u32,
+    /// 翌日持ち越し（および PDT 以外の口座）の買付余力の倍率。
+    pub overnight_multiplier: u32,
+}
+
+impl Default for PdtParams {
+    fn default() -> Self {
+        Self {
+            min_equity: 25_000,
+            window_days: 5,
+            day_trade_threshold: 4,
+            intraday_multiplier: 4,
+            overnight_multiplier: 2,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PdtEvent / PdtReject
+// ---------------------------------------------------------------------------
+
+/// PDT 指定状態の変化。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdtEvent {
+    /// PDT に指定された（期間内の日計り取引回数）。
+    Flagged { day_trades: u32 },
+    /// PDT 指定が解除された。
+    Cleared,
+}
+
+/// PDT 規制の違反。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PdtReject {
+    /// 最低資産額を割り込んだ PDT 口座の日計り取引。
+    EquityBelowMinimum { equity: i64, minimum: i64 },
+    /// 日中買付余力の超過。
+    BuyingPowerExceeded { required: i64, available: i64 },
+}
+
+/// 買付余力。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuyingPower {
+    /// 日中の買付余力。
+    pub intraday: i64,
+    /// 翌日持ち越しの買付余力。
+    pub overnight: i64,
+}
+
+// ---------------------------------------------------------------------------
+// PdtTracker
+// ---------------------------------------------------------------------------
+
+/// 1 口座の日計り取引と PDT 指定を追跡する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdtTracker {
+    params: PdtParams,
+    /// 営業日ごとの日計り取引回数（末尾が当日）。
+    day_trades: VecDeque<u32>,
+    /// 銘柄 → 当日に建てたネット数量（買いが正）。
+    opened_today: BTreeMap<u64, i64>,
+    flagged: bool,
+}
+
+impl PdtTracker {
+    /// 設定から作成。
+    #[must_use]
+    pub fn new(params: PdtParams) -> Self {
+        let mut day_trades = VecDeque::with_capacity(params.window_days.max(1) as usize);
+        day_trades.push_back(0);
+        Self {
+            params,
+            day_trades,
+            opened_today: BTreeMap::new(),
+            flagged: false,
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn params(&self) -> &PdtParams {
+        &self.params
+    }
+
+    /// PDT に指定されているか。
+    #[must_use]
+    pub const fn is_pattern_day_trader(&self) -> bool {
+        self.flagged
+    }
+
+    /// 期間内の日計り取引回数。
+    #[must_use]
+    pub fn day_trades_in_window(&self) -> u32 {
+        self.day_trades
+            .iter()
+            .fold(0_u32, |acc, &n| acc.saturating_add(n))
+    }
+
+    /// 次の営業日に進める。期間外の日計り取引は数えなくなり、当日に建てた
+    /// 数量は持ち越しになる。PDT 指定は [`Self::clear_flag`] まで続く。
+    pub fn roll_day(&mut self) {
+        self.day_trades.push_back(0);
+        while self.day_trades.len() > self.params.window_days.max(1) as usize {
+            self.day_trades.pop_front();
+        }
+        self.opened_today.clear();
+    }
+
+    /// PDT 指定を解除する。指定されていた場合は [`PdtEvent::Cleared`]。
+    pub fn clear_flag(&mut self) -> Option<PdtEvent> {
+        core::mem::replace(&mut self.flagged, false).then_some(PdtEvent::Cleared)
+    }
+
+    /// 約定を記録する。
+    ///
+    /// `net_before` は約定前のネット建玉。当日に建てた数量を返済する約定を
+    /// 1 回の日計り取引として数え、回数が閾値に達して PDT に指定された場合は
+    /// [`PdtEvent::Flagged`] を返す。
+    pub fn on_fill(
+        &mut self,
+        symbol_hash: u64,
+        side: Side,
+        quantity: u64,
+        net_before: i64,
+    ) -> Option<PdtEvent> {
+        let signed = match side {
+            Side::Bid => i128::from(quantity),
+            Side::Ask => -i128::from(quantity),
+        };
+        let before = i128::from(net_before);
+        let after = before + signed;
+        // 返済分（建玉と逆向きの約定のうち、建玉の範囲内）。
+        let closing = if before.signum() == -signed.signum() {
+            before.abs().min(signed.abs())
+        } else {
+            0
+        };
+        let opening = signed.abs() - closing;
+
+        let opened = self.opened_today.entry(symbol_hash).or_insert(0);
+        let mut day_trade = false;
+        if closing > 0 && i128::from(*opened).signum() == before.signum() {
+            let matched = closing.min(i128::from(*opened).abs());
+            if matched > 0 {
+                day_trade = true;
+                *opened -= (matched * before.signum()) as i64;
+            }
+        }
+        if opening > 0 {
+            *opened = opened.saturating_add((opening * after.signum()) as i64);
+        }
+        if *opened == 0 {
+            self.opened_today.remove(&symbol_hash);
+        }
+
+        if !day_trade {
+            return None;
+        }
+        if let Some(today) = self.day_trades.back_mut() {
+            *today = today.saturating_add(1);
+        }
+        let day_trades = self.day_trades_in_window();
+        if !self.flagged && day_trades >= self.params.day_trade_threshold {
+            self.flagged = true;
+            return Some(PdtEvent::Flagged { day_trades });
+        }
+        None
+    }
+
+    /// 資産額 `equity`、維持証拠金 `maintenance_margin` の口座の買付余力。
+    ///
+    /// 余剰資産（`equity - maintenance_margin`、負なら 0）の倍数。日中の
+    /// 4 倍は、最低資産額を満たす PDT 口座だけに適用する。
+    #[must_use]
+    pub fn buying_power(&self, equity: i64, maintenance_margin: i64) -> BuyingPower {
+        let excess = (i128::from(equity) - i128::from(maintenance_margin)).max(0);
+        let times = |multiplier: u32| {
+            excess
+                .saturating_mul(i128::from(multiplier))
+                .min(i128::from(i64::MAX)) as i64
+        };
+        let overnight = times(self.params.overnight_multiplier);
+        let intraday = if self.flagged && equity >= self.params.min_equity {
+            times(self.params.intraday_multiplier)
+        } else {
+            overnight
+        };
+        BuyingPower {
+            intraday,
+            overnight,
+        }
+    }
+
+    /// 日中に `required` の建玉を新たに持てるか。
+    ///
+    /// # Errors
+    ///
+    /// 最低資産額を割り込んだ PDT 口座は [`PdtReject::EquityBelowMinimum`]、
+    /// 日中買付余力を超える場合は [`PdtReject::BuyingPowerExceeded`]。
+    pub fn check_intraday(
+        &self,
+        required: i64,
+        equity: i64,
+        maintenance_margin: i64,
+    ) -> Result<(), PdtReject> {
+        if self.flagged && equity < self.params.min_equity {
+            return Err(PdtReject::EquityBelowMinimum {
+                equity,
+                minimum: self.params.min_equity,
+            });
+        }
+        let available = self.buying_power(equity, maintenance_margin).intraday;
+        if required > available {
+            return Err(PdtReject::BuyingPowerExceeded {
+                required,
+                available,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for PdtTracker {
+    fn default() -> Self {
+        Self::new(PdtParams::default())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 当日に建てて同日に返済する往復。
+    fn round_trip(tracker: &mut PdtTracker, symbol: u64) -> Option<PdtEvent> {
+        assert_eq!(tracker.on_fill(symbol, Side::Bid, 10, 0), None);
+        tracker.on_fill(symbol, Side::Ask, 10, 10)
+    }
+
+    #[test]
+    fn flags_after_four_day_trades_in_window() {
+        let mut tracker = PdtTracker::default();
+        assert_eq!(round_trip(&mut tracker, 1), None);
+        tracker.roll_day();
+        assert_eq!(round_trip(&mut tracker, 1), None);
+        assert_eq!(round_trip(&mut tracker, 2), None);
+        tracker.roll_day();
+        assert_eq!(
+            round_trip(&mut tracker, 3),
+            Some(PdtEvent::Flagged { day_trades: 4 })
+        );
+        assert!(tracker.is_pattern_day_trader());
+        assert_eq!(round_trip(&mut tracker, 3), None);
+        assert_eq!(tracker.clear_flag(), Some(PdtEvent::Cleared));
+        assert_eq!(tracker.clear_flag(), None);
+    }
+
+    #[test]
+    fn old_day_trades_leave_the_window() {
+        let mut tracker = PdtTracker::default();
+        for symbol in 1..=3 {
+            round_trip(&mut tracker, symbol);
+        }
+        for _ in 0..5 {
+            tracker.roll_day();
+        }
+        assert_eq!(tracker.day_trades_in_window(), 0);
+        assert_eq!(round_trip(&mut tracker, 1), None);
+    }
+
+    #[test]
+    fn only_same_day_openings_count() {
+        let mut tracker = PdtTracker::default();
+        // 前日からの持ち越しの返済は日計りではない。
+        tracker.on_fill(1, Side::Bid, 10, 0);
+        tracker.roll_day();
+        assert_eq!(tracker.on_fill(1, Side::Ask, 10, 10), None);
+        assert_eq!(tracker.day_trades_in_window(), 0);
+        // 持ち越し 10 に当日 5 を買い増し、15 売ると当日分の返済で 1 回。
+        tracker.on_fill(2, Side::Bid, 10, 0);
+        tracker.roll_day();
+        tracker.on_fill(2, Side::Bid, 5, 10);
+        tracker.on_fill(2, Side::Ask, 15, 15);
+        assert_eq!(tracker.day_trades_in_window(), 1);
+        // ドテン: 当日の売り建て分を買い戻すと日計り。
+        tracker.on_fill(3, Side::Ask, 20, 0);
+        tracker.on_fill(3, Side::Bid, 30, -20);
+        tracker.on_fill(3, Side::Ask, 10, 10);
+        assert_eq!(tracker.day_trades_in_window(), 3);
+    }
+
+    #[test]
+    fn buying_power_multipliers() {
+        let mut tracker = PdtTracker::default();
+        assert_eq!(
+            tracker.buying_power(40_000, 10_000),
+            BuyingPower {
+                intraday: 60_000,
+                overnight: 60_000,
+            }
+        );
+        for symbol in 1..=4 {
+            round_trip(&mut tracker, symbol);
+        }
+        assert_eq!(tracker.buying_power(40_000, 10_000).intraday, 120_000);
+        assert!(tracker.check_intraday(120_000, 40_000, 10_000).is_ok());
+        assert_eq!(
+            tracker.check_intraday(120_001, 40_000, 10_000),
+            Err(PdtReject::BuyingPowerExceeded {
+                required: 120_001,
+                available: 120_000,
+            })
+        );
+        assert_eq!(
+            tracker.check_intraday(1, 20_000, 0),
+            Err(PdtReject::EquityBelowMinimum {
+                equity: 20_000,
+                minimum: 25_000,
+            })
+        );
+        assert_eq!(tracker.buying_power(5_000, 10_000).overnight, 0);
+    }
+}
@@ -0,0 +1,256 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 銘柄別・戦略別の損益の帰属。
+//!
+//! 口座の日次損益（[`PreTradeChecker::daily_pnl`]）は 1 つの値なので、損失が
+//! どの銘柄・戦略から来ているかが分からない。[`PnlAttribution`] は実現損益と
+//! 評価損益を（銘柄, 戦略 ID（[`DecisionTags::algo_id`]））ごとに記録し、
+//! 銘柄別・戦略別に集計した [`PnlReport`] を返す。銘柄ごとの損失上限を
+//! 設定すると、上限に達した銘柄の注文を [`RiskReject::SymbolLossLimitHit`] で
+//! 拒否する。
+//!
+//! [`DecisionTags::algo_id`]: crate::check::DecisionTags::algo_id
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// PnlBreakdown
+// ---------------------------------------------------------------------------
+
+/// 実現損益と評価損益。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PnlBreakdown {
+    /// 実現損益。
+    pub realized: i64,
+    /// 評価損益。
+    pub unrealized: i64,
+}
+
+impl PnlBreakdown {
+    /// 実現損益と評価損益の合計。
+    #[must_use]
+    pub const fn total(&self) -> i64 {
+        self.realized.saturating_add(self.unrealized)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.realized = self.realized.saturating_add(other.realized);
+        self.unrealized = self.unrealized.saturating_add(other.unrealized);
+    }
+}
+
+/// 銘柄別・戦略別に集計した損益。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PnlReport {
+    /// 銘柄 → 損益。
+    pub by_symbol: BTreeMap<u64, PnlBreakdown>,
+    /// 戦略 ID → 損益。戦略 ID の無い損益は `None`。
+    pub by_strategy: BTreeMap<Option<u64>, PnlBreakdown>,
+    /// 全体の損益。
+    pub total: PnlBreakdown,
+}
+
+// ---------------------------------------------------------------------------
+// PnlAttribution
+// ---------------------------------------------------------------------------
+
+/// （銘柄, 戦略 ID）ごとの損益と銘柄別の損失上限。
+#[derive(Debug, Clone, Default)]
+pub struct PnlAttribution {
+    cells: BTreeMap<(u64, Option<u64>), PnlBreakdown>,
+    /// 銘柄 → 損失上限（負の値）。
+    loss_limits: BTreeMap<u64, i64>,
+}
+
+impl PnlAttribution {
+    /// 空の帰属表を作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            cells: BTreeMap::new(),
+            loss_limits: BTreeMap::new(),
+        }
+    }
+
+    /// 実現損益を加算する。
+    pub fn on_realized(&mut self, symbol_hash: u64, algo_id: Option<u64>, pnl: i64) {
+        let cell = self.cells.entry((symbol_hash, algo_id)).or_default();
+        cell.realized = cell.realized.saturating_add(pnl);
+    }
+
+    /// 評価損益を値洗いの結果で置き換える。
+    pub fn set_unrealized(&mut self, symbol_hash: u64, algo_id: Option<u64>, pnl: i64) {
+        self.cells
+            .entry((symbol_hash, algo_id))
+            .or_default()
+            .unrealized = pnl;
+    }
+
+    /// 銘柄の損失上限（負の値）を設定する。`None` で解除。
+    pub fn set_symbol_loss_limit(&mut self, symbol_hash: u64, limit: Option<i64>) {
+        match limit {
+            Some(limit) => self.loss_limits.insert(symbol_hash, limit),
+            None => self.loss_limits.remove(&symbol_hash),
+        };
+    }
+
+    /// 銘柄の損失上限。
+    #[must_use]
+    pub fn symbol_loss_limit(&self, symbol_hash: u64) -> Option<i64> {
+        self.loss_limits.get(&symbol_hash).copied()
+    }
+
+    /// 銘柄の損益（全戦略の合計）。
+    #[must_use]
+    pub fn symbol(&self, symbol_hash: u64) -> PnlBreakdown {
+        let mut pnl = PnlBreakdown::default();
+        for (_, cell) in self
+            .cells
+            .range((symbol_hash, None)..=(symbol_hash, Some(u64::MAX)))
+        {
+            pnl.add(cell);
+        }
+        pnl
+    }
+
+    /// 戦略の損益（全銘柄の合計）。
+    #[must_use]
+    pub fn strategy(&self, algo_id: Option<u64>) -> PnlBreakdown {
+        let mut pnl = PnlBreakdown::default();
+        for (_, cell) in self.cells.iter().filter(|((_, a), _)| *a == algo_id) {
+            pnl.add(cell);
+        }
+        pnl
+    }
+
+    /// 銘柄別・戦略別の集計。
+    #[must_use]
+    pub fn report(&self) -> PnlReport {
+        let mut report = PnlReport::default();
+        for (&(symbol_hash, algo_id), cell) in &self.cells {
+            report.by_symbol.entry(symbol_hash).or_default().add(cell);
+            report.by_strategy.entry(algo_id).or_default().add(cell);
+            report.total.add(cell);
+        }
+        report
+    }
+
+    /// 日次の損益を消す。損失上限は残す。
+    pub fn reset_daily(&mut self) {
+        self.cells.clear();
+    }
+
+    /// 銘柄の損失上限を判定する。上限の無い銘柄は常に通す。
+    ///
+    /// # Errors
+    ///
+    /// 銘柄の損益（実現 + 評価）が上限以下なら [`RiskReject::SymbolLossLimitHit`]。
+    pub fn check(&self, symbol_hash: u64) -> Result<(), RiskReject> {
+        let Some(limit) = self.symbol_loss_limit(symbol_hash) else {
+            return Ok(());
+        };
+        let loss = self.symbol(symbol_hash).total();
+        if loss <= limit {
+            return Err(RiskReject::SymbolLossLimitHit {
+                symbol_hash,
+                loss,
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// [`Self::check`] の後に `checker` で判定する。
+    ///
+    /// # Errors
+    ///
+    /// 銘柄の損失上限に達していれば [`RiskReject::SymbolLossLimitHit`]、
+    /// それ以外は `checker` の拒否。
+    pub fn check_order(
+        &self,
+        checker: &PreTradeChecker,
+        symbol_hash: u64,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        self.check(symbol_hash)?;
+        checker.check_order(order, position)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_by_symbol_and_strategy() {
+        let mut pnl = PnlAttribution::new();
+        pnl.on_realized(1, Some(10), -300);
+        pnl.on_realized(1, Some(10), 100);
+        pnl.set_unrealized(1, Some(10), -50);
+        pnl.set_unrealized(1, Some(10), -80);
+        pnl.on_realized(1, None, 40);
+        pnl.on_realized(2, Some(10), 500);
+        pnl.set_unrealized(2, Some(20), -70);
+
+        assert_eq!(
+            pnl.symbol(1),
+            PnlBreakdown {
+                realized: -160,
+                unrealized: -80,
+            }
+        );
+        assert_eq!(pnl.strategy(Some(10)).total(), 220);
+        assert_eq!(pnl.strategy(None).total(), 40);
+
+        let report = pnl.report();
+        assert_eq!(report.by_symbol.len(), 2);
+        assert_eq!(report.by_symbol[&2].total(), 430);
+        assert_eq!(report.by_strategy[&Some(20)].unrealized, -70);
+        assert_eq!(
+            report.total,
+            PnlBreakdown {
+                realized: 340,
+                unrealized: -150,
+            }
+        );
+
+        pnl.reset_daily();
+        assert_eq!(pnl.report(), PnlReport::default());
+    }
+
+    #[test]
+    fn symbol_loss_limit() {
+        let mut pnl = PnlAttribution::new();
+        pnl.set_symbol_loss_limit(1, Some(-200));
+        pnl.on_realized(1, Some(10), -150);
+        assert!(pnl.check(1).is_ok());
+        pnl.set_unrealized(1, None, -50);
+        assert_eq!(
+            pnl.check(1),
+            Err(RiskReject::SymbolLossLimitHit {
+                symbol_hash: 1,
+                loss: -200,
+                limit: -200,
+            })
+        );
+        // 他の銘柄の損失は影響しない。
+        pnl.on_realized(2, None, -1_000);
+        assert!(pnl.check(2).is_ok());
+        pnl.set_symbol_loss_limit(1, None);
+        assert!(pnl.check(1).is_ok());
+    }
+}
@@ -0,0 +1,216 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 戦略単位の隔離（quarantine）。
+//!
+//! 注文の [`DecisionTags::algo_id`] を戦略タグとして、隔離中の戦略の注文だけを
+//! 拒否（または建玉を減らす注文のみ許可）する。同じ口座の他の戦略は取引を
+//! 続けられる。隔離は手動でも、[`RejectRateMonitor`](crate::RejectRateMonitor)
+//! などの監視側の検知からでも設定でき、[`StrategyQuarantine::release`] で
+//! 明示的に解除するまで続く。戦略タグの無い注文は対象外。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{DecisionTags, PreTradeChecker, RiskReject};
+use crate::instrument::ExpiryRestriction;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// QuarantineMode
+// ---------------------------------------------------------------------------
+
+/// 隔離中の戦略に課す制限。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuarantineMode {
+    /// すべての注文を拒否。
+    Reject,
+    /// 建玉を減らす注文のみ許可（建玉数量を超える反対売買も不可）。
+    ReduceOnly,
+}
+
+impl QuarantineMode {
+    /// ネット建玉 `net` に対する `order` を許可するか。
+    #[must_use]
+    pub const fn permits(self, order: &Order, net: i64) -> bool {
+        match self {
+            Self::Reject => false,
+            Self::ReduceOnly => ExpiryRestriction::ReduceOnly.permits(order, net),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// StrategyQuarantine
+// ---------------------------------------------------------------------------
+
+/// 隔離の内容。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quarantine {
+    /// 制限。
+    pub mode: QuarantineMode,
+    /// 隔離の理由（例: `"manual"`、`"reject-rate"`）。
+    pub reason: &'static str,
+    /// 隔離した時刻（ns）。
+    pub since_ns: u64,
+}
+
+/// 戦略タグ別の隔離状態。
+#[derive(Debug, Clone, Default)]
+pub struct StrategyQuarantine {
+    /// 戦略タグ → 隔離。
+    strategies: BTreeMap<u64, Quarantine>,
+}
+
+impl StrategyQuarantine {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strategies: BTreeMap::new(),
+        }
+    }
+
+    /// 戦略を隔離する（既存の隔離は置き換え）。
+    pub fn quarantine(
+        &mut self,
+        algo_id: u64,
+        mode: QuarantineMode,
+        reason: &'static str,
+        now_ns: u64,
+    ) {
+        self.strategies.insert(
+            algo_id,
+            Quarantine {
+                mode,
+                reason,
+                since_ns: now_ns,
+            },
+        );
+    }
+
+    /// 戦略の隔離を解除する。隔離されていた場合は `true`。
+    pub fn release(&mut self, algo_id: u64) -> bool {
+        self.strategies.remove(&algo_id).is_some()
+    }
+
+    /// 戦略の隔離状態。
+    #[must_use]
+    pub fn get(&self, algo_id: u64) -> Option<Quarantine> {
+        self.strategies.get(&algo_id).copied()
+    }
+
+    /// 隔離中の戦略タグ（昇順）。
+    pub fn quarantined(&self) -> impl Iterator<Item = (u64, Quarantine)> + '_ {
+        self.strategies.iter().map(|(&id, &q)| (id, q))
+    }
+
+    /// ネット建玉 `net` に対する `tags` 付きの `order` が隔離に抵触しないか。
+    ///
+    /// # Errors
+    ///
+    /// 戦略が隔離中で注文が許可されなければ [`RiskReject::StrategyQuarantined`]。
+    pub fn check(&self, order: &Order, tags: DecisionTags, net: i64) -> Result<(), RiskReject> {
+        let Some(algo_id) = tags.algo_id else {
+            return Ok(());
+        };
+        match self.strategies.get(&algo_id) {
+            Some(q) if !q.mode.permits(order, net) => Err(RiskReject::StrategyQuarantined {
+                algo_id,
+                mode: q.mode,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// 隔離を確認してから `checker` で判定する。
+    ///
+    /// # Errors
+    ///
+    /// [`RiskReject::StrategyQuarantined`]、または
+    /// [`PreTradeChecker::check_order`] の拒否理由。
+    pub fn check_order(
+        &self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+        tags: DecisionTags,
+    ) -> Result<(), RiskReject> {
+        self.check(order, tags, position.map_or(0, |p| p.net_quantity))?;
+        checker.check_order(order, position)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+    use alice_ledger::Side;
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity).build()
+    }
+
+    const fn tags(algo_id: u64) -> DecisionTags {
+        DecisionTags {
+            algo_id: Some(algo_id),
+            dea: false,
+        }
+    }
+
+    #[test]
+    fn reject_only_quarantined_strategy() {
+        let mut q = StrategyQuarantine::new();
+        q.quarantine(7, QuarantineMode::Reject, "manual", 10);
+        let o = order(Side::Bid, 1);
+        assert_eq!(
+            q.check(&o, tags(7), 0),
+            Err(RiskReject::StrategyQuarantined {
+                algo_id: 7,
+                mode: QuarantineMode::Reject,
+            })
+        );
+        assert!(q.check(&o, tags(8), 0).is_ok());
+        assert!(q.check(&o, DecisionTags::default(), 0).is_ok());
+        assert_eq!(q.get(7).unwrap().since_ns, 10);
+        assert!(q.release(7));
+        assert!(!q.release(7));
+        assert!(q.check(&o, tags(7), 0).is_ok());
+    }
+
+    #[test]
+    fn reduce_only_allows_unwinds() {
+        let mut q = StrategyQuarantine::new();
+        q.quarantine(7, QuarantineMode::ReduceOnly, "reject-rate", 0);
+        assert!(q.check(&order(Side::Ask, 5), tags(7), 5).is_ok());
+        assert!(q.check(&order(Side::Ask, 6), tags(7), 5).is_err());
+        assert!(q.check(&order(Side::Bid, 1), tags(7), 5).is_err());
+        assert_eq!(q.quarantined().count(), 1);
+    }
+
+    #[test]
+    fn wraps_checker() {
+        let mut q = StrategyQuarantine::new();
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let o = order(Side::Bid, 1);
+        assert!(q.check_order(&checker, &o, None, tags(7)).is_ok());
+        q.quarantine(7, QuarantineMode::Reject, "manual", 0);
+        assert!(matches!(
+            q.check_order(&checker, &o, None, tags(7)),
+            Err(RiskReject::StrategyQuarantined { .. })
+        ));
+        assert!(matches!(
+            q.check_order(&checker, &order(Side::Bid, u64::MAX), None, tags(8)),
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
+    }
+}
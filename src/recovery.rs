@@ -0,0 +1,354 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! クラッシュ復旧の適合性テストキット（`recovery-kit` フィーチャー）。
+//!
+//! シード固定で生成したイベント列を [`EventLog`] に流しながら、組込み側の
+//! 永続化（[`Persistence`]）に書き込み、ランダムな位置でエンジンを破棄
+//! （クラッシュ）しては永続化から復元する。復元した状態は、クラッシュせずに
+//! 同じイベント列を処理し続けた参照チェッカーと毎回比較される。
+//!
+//! 組込み側は自前の永続化を [`Persistence`] として実装し
+//! [`run_recovery_test`] に渡すことで、チェックポイントとイベントの保存・
+//! 読み出しの配線が正しいことを確認できる。
+//!
+//! ```rust
+//! use alice_risk::recovery::{run_recovery_test, MemoryPersistence, RecoveryConfig};
+//!
+//! let report = run_recovery_test(&RecoveryConfig::default(), &mut MemoryPersistence::new());
+//! assert!(report.is_conformant());
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, OrderId, OrderType, Side, TimeInForce};
+
+use crate::check::PreTradeChecker;
+use crate::limit::RiskLimits;
+use crate::replay::{apply_event, ReplayEvent};
+use crate::sim::SimRng;
+use crate::snapshot::CheckerState;
+use crate::wal::{Checkpoint, EventLog, WalConfig};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// Persistence
+// ---------------------------------------------------------------------------
+
+/// テスト対象の永続化。
+pub trait Persistence {
+    /// 通し番号 `seq` のイベントを保存する（ログへの適用前に呼ばれる）。
+    fn append(&mut self, seq: u64, event: &ReplayEvent);
+    /// チェックポイントを保存する。これより前のイベントは捨ててよい。
+    fn checkpoint(&mut self, checkpoint: &Checkpoint);
+    /// クラッシュ後に、最新のチェックポイントとその後のイベントを読み出す。
+    /// チェックポイントが一度も保存されていなければ `None`。
+    fn load(&mut self) -> (Option<Checkpoint>, Vec<ReplayEvent>);
+}
+
+/// メモリ上の参照実装。
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPersistence {
+    checkpoint: Option<Checkpoint>,
+    events: Vec<(u64, ReplayEvent)>,
+}
+
+impl MemoryPersistence {
+    /// 空の永続化。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            checkpoint: None,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl Persistence for MemoryPersistence {
+    fn append(&mut self, seq: u64, event: &ReplayEvent) {
+        self.events.push((seq, event.clone()));
+    }
+
+    fn checkpoint(&mut self, checkpoint: &Checkpoint) {
+        self.events.retain(|&(seq, _)| seq >= checkpoint.seq);
+        self.checkpoint = Some(checkpoint.clone());
+    }
+
+    fn load(&mut self) -> (Option<Checkpoint>, Vec<ReplayEvent>) {
+        let events = self.events.iter().map(|(_, e)| e.clone()).collect();
+        (self.checkpoint.clone(), events)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RecoveryConfig / RecoveryReport
+// ---------------------------------------------------------------------------
+
+/// テストのパラメータ。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryConfig {
+    /// 乱数シード。
+    pub seed: u64,
+    /// 生成するイベント数。
+    pub events: usize,
+    /// クラッシュさせる回数。
+    pub crashes: usize,
+    /// 初期の制限値。
+    pub limits: RiskLimits,
+    /// ログのチェックポイント設定。
+    pub wal: WalConfig,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            events: 2_000,
+            crashes: 20,
+            limits: RiskLimits::default(),
+            wal: WalConfig {
+                checkpoint_interval: 64,
+                retained_checkpoints: 2,
+            },
+        }
+    }
+}
+
+/// 復元した状態と参照状態の食い違い。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryMismatch {
+    /// クラッシュ時点の通し番号（処理済みイベント数）。
+    pub seq: u64,
+    /// 参照チェッカーの状態。
+    pub expected: CheckerState,
+    /// 復元した状態。
+    pub restored: CheckerState,
+    /// 制限値も食い違ったか。
+    pub limits_differ: bool,
+}
+
+/// テスト結果。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// 処理したイベント数。
+    pub events: u64,
+    /// クラッシュ・復元の回数。
+    pub crashes: u64,
+    /// 食い違い（発生順）。
+    pub mismatches: Vec<RecoveryMismatch>,
+}
+
+impl RecoveryReport {
+    /// すべての復元が参照と一致したか。
+    #[must_use]
+    pub fn is_conformant(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Event generation
+// ---------------------------------------------------------------------------
+
+/// 注文・受理・取消・損益・ブレーカー・日次リセット・制限変更を混ぜたイベント列。
+///
+/// 注文イベントの判定記録は生成時点の状態で判定したもの。
+#[must_use]
+pub fn generate_events(seed: u64, n: usize, limits: &RiskLimits) -> Vec<ReplayEvent> {
+    let mut rng = SimRng::new(seed);
+    let mut live = PreTradeChecker::new(limits.clone());
+    let mut events = Vec::with_capacity(n);
+    // 判定で受理され、まだ受理イベントを出していない直近の注文。
+    let mut accepted = None;
+    for i in 0..n {
+        let ts = i as u64 * 1_000;
+        let event = match rng.below(100) {
+            0..=44 => {
+                let order = Order {
+                    id: OrderId(i as u64 + 1),
+                    side: if rng.chance(50) { Side::Bid } else { Side::Ask },
+                    order_type: OrderType::Limit,
+                    price: rng.range_i64(9_000, 11_000),
+                    quantity: rng.below(limits.max_order_size.saturating_mul(2)) + 1,
+                    filled_quantity: 0,
+                    timestamp_ns: ts,
+                    time_in_force: match rng.below(3) {
+                        0 => TimeInForce::IOC,
+                        1 => TimeInForce::FOK,
+                        _ => TimeInForce::GTC,
+                    },
+                };
+                let recorded = live.decide(&order, None);
+                accepted = recorded.is_accepted().then(|| order.clone());
+                ReplayEvent::Order {
+                    order,
+                    position: None,
+                    recorded,
+                }
+            }
+            45..=64 => match accepted.take() {
+                Some(order) => ReplayEvent::Accepted {
+                    order,
+                    position: None,
+                },
+                None => ReplayEvent::Pnl(rng.range_i64(limits.max_daily_loss, 10_000)),
+            },
+            65..=79 => {
+                let working = live.open_orders();
+                let nth = rng.below(working.len().max(1) as u64) as usize;
+                match working.iter().nth(nth) {
+                    Some(o) => ReplayEvent::Closed {
+                        order_id: o.order_id,
+                    },
+                    None => ReplayEvent::Pnl(rng.range_i64(limits.max_daily_loss, 10_000)),
+                }
+            }
+            80..=93 => ReplayEvent::Pnl(rng.range_i64(limits.max_daily_loss, 10_000)),
+            94 => ReplayEvent::TripBreaker,
+            95..=96 => ReplayEvent::ResetBreaker,
+            97 => ReplayEvent::DailyReset,
+            _ => ReplayEvent::ApplyLimits {
+                limits: RiskLimits {
+                    max_order_size: limits.max_order_size / 2 + rng.below(limits.max_order_size),
+                    ..limits.clone()
+                },
+                author: String::from("recovery-kit"),
+                timestamp_ns: ts,
+            },
+        };
+        apply_event(&mut live, &event, true);
+        events.push(event);
+    }
+    events
+}
+
+// ---------------------------------------------------------------------------
+// run_recovery_test
+// ---------------------------------------------------------------------------
+
+/// 生成したイベント列をクラッシュ・復元を挟みながら処理し、復元のたびに
+/// 参照チェッカーと比較する。
+pub fn run_recovery_test<P: Persistence>(
+    config: &RecoveryConfig,
+    persistence: &mut P,
+) -> RecoveryReport {
+    let events = generate_events(config.seed, config.events, &config.limits);
+    let mut rng = SimRng::new(config.seed ^ 0x5EED_C0DE);
+    let mut crash_at: Vec<u64> = (0..config.crashes)
+        .map(|_| rng.below(events.len() as u64 + 1))
+        .collect();
+    crash_at.sort_unstable();
+
+    let mut reference = PreTradeChecker::new(config.limits.clone());
+    let mut log = EventLog::new(config.limits.clone(), config.wal);
+    let mut saved_seq = None;
+    let mut crashes = crash_at.into_iter().peekable();
+    let mut report = RecoveryReport::default();
+    for seq in 0..=events.len() as u64 {
+        while crashes.next_if_eq(&seq).is_some() {
+            drop(log);
+            let (checkpoint, tail) = persistence.load();
+            let checkpoint = checkpoint.unwrap_or_else(|| {
+                EventLog::new(config.limits.clone(), config.wal)
+                    .latest_checkpoint()
+                    .clone()
+            });
+            saved_seq = Some(checkpoint.seq);
+            log = EventLog::resume(checkpoint, tail, config.wal);
+            report.crashes += 1;
+            let (expected, restored) = (reference.state(), log.checker().state());
+            let limits_differ = reference.limits() != log.checker().limits();
+            if expected != restored || limits_differ {
+                report.mismatches.push(RecoveryMismatch {
+                    seq,
+                    expected,
+                    restored,
+                    limits_differ,
+                });
+            }
+        }
+        let Some(event) = events.get(seq as usize) else {
+            break;
+        };
+        persistence.append(log.next_seq(), event);
+        log.append(event.clone());
+        apply_event(&mut reference, event, true);
+        let latest = log.latest_checkpoint();
+        if saved_seq != Some(latest.seq) {
+            persistence.checkpoint(latest);
+            saved_seq = Some(latest.seq);
+        }
+        report.events += 1;
+    }
+    report
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_persistence_conforms() {
+        for seed in 1..=5 {
+            let config = RecoveryConfig {
+                seed,
+                ..RecoveryConfig::default()
+            };
+            let report = run_recovery_test(&config, &mut MemoryPersistence::new());
+            assert!(
+                report.is_conformant(),
+                "seed {seed}: {:?}",
+                report.mismatches
+            );
+            assert_eq!(report.events, 2_000);
+            assert_eq!(report.crashes, 20);
+        }
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        let limits = RiskLimits::default();
+        let a = generate_events(7, 200, &limits);
+        let b = generate_events(7, 200, &limits);
+        assert_eq!(alloc::format!("{a:?}"), alloc::format!("{b:?}"));
+        assert!(a
+            .iter()
+            .any(|e| matches!(e, ReplayEvent::ApplyLimits { .. })));
+    }
+
+    /// 最後のイベントを書き損ねる永続化。
+    struct LosesLastEvent(MemoryPersistence);
+
+    impl Persistence for LosesLastEvent {
+        fn append(&mut self, seq: u64, event: &ReplayEvent) {
+            self.0.append(seq, event);
+        }
+
+        fn checkpoint(&mut self, checkpoint: &Checkpoint) {
+            self.0.checkpoint(checkpoint);
+        }
+
+        fn load(&mut self) -> (Option<Checkpoint>, Vec<ReplayEvent>) {
+            let (checkpoint, mut events) = self.0.load();
+            events.pop();
+            (checkpoint, events)
+        }
+    }
+
+    #[test]
+    fn detects_broken_persistence() {
+        let report = run_recovery_test(
+            &RecoveryConfig::default(),
+            &mut LosesLastEvent(MemoryPersistence::new()),
+        );
+        assert!(!report.is_conformant());
+    }
+}
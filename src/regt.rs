@@ -0,0 +1,335 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! Reg-T 方式の証拠金モデル。
+//!
+//! [`RegTCalculator`] は米国の証券会社向けの Reg-T 方式で、株式は買い・売りとも
+//! 当初 50%、維持は買い 25%・売り 30% とし、オプションは戦略ごとの規則
+//! （[`OptionStrategy`]）で所要額を求める。
+//!
+//! [`MarginModel`] は既存の bps 方式（[`MarginParams`]）と Reg-T 方式を
+//! 口座ごとに選ぶための列挙で、
+//! [`RiskEngine::set_margin_model`](crate::RiskEngine::set_margin_model) で
+//! 口座に割り当てる。
+
+use crate::margin::{MarginCalculator, MarginParams};
+
+// ---------------------------------------------------------------------------
+// RegTParams
+// ---------------------------------------------------------------------------
+
+/// Reg-T 方式の料率（bps）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegTParams {
+    /// 株式の当初証拠金率。
+    pub initial_bps: u32,
+    /// 株式の買い建玉の維持証拠金率。
+    pub long_maintenance_bps: u32,
+    /// 株式の売り建玉の維持証拠金率。
+    pub short_maintenance_bps: u32,
+    /// 売りオプションの原資産価格に対する基本料率。
+    pub naked_option_bps: u32,
+    /// 売りオプションの最低料率（コールは原資産価格、プットは行使価格に対する）。
+    pub naked_option_min_bps: u32,
+    /// オプション 1 枚あたりの原資産数量。
+    pub option_multiplier: u64,
+}
+
+impl Default for RegTParams {
+    fn default() -> Self {
+        Self {
+            initial_bps: 5_000,
+            long_maintenance_bps: 2_500,
+            short_maintenance_bps: 3_000,
+            naked_option_bps: 2_000,
+            naked_option_min_bps: 1_000,
+            option_multiplier: 100,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// OptionStrategy
+// ---------------------------------------------------------------------------
+
+/// オプションの種類。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    /// コール。
+    Call,
+    /// プット。
+    Put,
+}
+
+/// Reg-T の規則で所要額を求めるオプション戦略。価格はすべて原資産 1 単位
+/// あたり。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionStrategy {
+    /// 買いオプション: プレミアムの全額。
+    Long { premium: i64, contracts: u64 },
+    /// 裸の売りオプション: プレミアム + max(基本料率 × 原資産 − アウト・オブ・
+    /// ザ・マネー額, 最低料率 × 原資産（プットは行使価格）)。
+    NakedShort {
+        kind: OptionKind,
+        strike: i64,
+        premium: i64,
+        contracts: u64,
+    },
+    /// カバード・コール（原資産の買い建玉で担保）: オプション分の追加は無い。
+    CoveredCall { contracts: u64 },
+    /// 同じ満期の垂直スプレッド: 最大損失（行使価格差、買い側が不利な場合）。
+    /// 買い側が有利（デビット・スプレッド）なら追加は無く、プレミアムは支払済み。
+    Vertical {
+        kind: OptionKind,
+        short_strike: i64,
+        long_strike: i64,
+        contracts: u64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// RegTCalculator
+// ---------------------------------------------------------------------------
+
+/// Reg-T 方式の証拠金計算器。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegTCalculator {
+    params: RegTParams,
+}
+
+impl RegTCalculator {
+    /// 設定から作成。
+    #[must_use]
+    pub const fn new(params: RegTParams) -> Self {
+        Self { params }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn params(&self) -> &RegTParams {
+        &self.params
+    }
+
+    /// 株式のネット建玉 `net` の当初証拠金（`|price| * |net| * initial_bps / 10000`）。
+    #[must_use]
+    pub fn equity_initial_margin(&self, price: i64, net: i64) -> i64 {
+        bps_of(price, net.unsigned_abs(), self.params.initial_bps)
+    }
+
+    /// 株式のネット建玉 `net` の維持証拠金。売り建玉は売りの料率を使う。
+    #[must_use]
+    pub fn equity_maintenance_margin(&self, price: i64, net: i64) -> i64 {
+        let bps = if net < 0 {
+            self.params.short_maintenance_bps
+        } else {
+            self.params.long_maintenance_bps
+        };
+        bps_of(price, net.unsigned_abs(), bps)
+    }
+
+    /// 原資産価格 `underlying` でのオプション戦略の所要額。
+    #[must_use]
+    pub fn option_requirement(&self, strategy: &OptionStrategy, underlying: i64) -> i64 {
+        let per_unit = match *strategy {
+            OptionStrategy::Long { premium, .. } => i128::from(premium).max(0),
+            OptionStrategy::NakedShort {
+                kind,
+                strike,
+                premium,
+                ..
+            } => {
+                let (underlying, strike) = (i128::from(underlying), i128::from(strike));
+                let (out_of_money, floor_base) = match kind {
+                    OptionKind::Call => ((strike - underlying).max(0), underlying),
+                    OptionKind::Put => ((underlying - strike).max(0), strike),
+                };
+                let base = underlying.abs() * i128::from(self.params.naked_option_bps) / 10_000
+                    - out_of_money;
+                let floor =
+                    floor_base.abs() * i128::from(self.params.naked_option_min_bps) / 10_000;
+                i128::from(premium).max(0) + base.max(floor)
+            }
+            OptionStrategy::CoveredCall { .. } => 0,
+            OptionStrategy::Vertical {
+                kind,
+                short_strike,
+                long_strike,
+                ..
+            } => {
+                let (short, long) = (i128::from(short_strike), i128::from(long_strike));
+                match kind {
+                    OptionKind::Call => (long - short).max(0),
+                    OptionKind::Put => (short - long).max(0),
+                }
+            }
+        };
+        let contracts = match *strategy {
+            OptionStrategy::Long { contracts, .. }
+            | OptionStrategy::NakedShort { contracts, .. }
+            | OptionStrategy::CoveredCall { contracts }
+            | OptionStrategy::Vertical { contracts, .. } => contracts,
+        };
+        per_unit
+            .saturating_mul(i128::from(contracts))
+            .saturating_mul(i128::from(self.params.option_multiplier))
+            .min(i128::from(i64::MAX)) as i64
+    }
+}
+
+impl Default for RegTCalculator {
+    fn default() -> Self {
+        Self::new(RegTParams::default())
+    }
+}
+
+/// `|price| * quantity * bps / 10000`（`i64` の範囲に丸める）。
+fn bps_of(price: i64, quantity: u64, bps: u32) -> i64 {
+    (i128::from(price)
+        .abs()
+        .saturating_mul(i128::from(quantity))
+        .saturating_mul(i128::from(bps))
+        / 10_000)
+        .min(i128::from(i64::MAX)) as i64
+}
+
+// ---------------------------------------------------------------------------
+// MarginModel
+// ---------------------------------------------------------------------------
+
+/// 口座に適用する証拠金モデル。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarginModel {
+    /// 一律の bps 方式（[`MarginCalculator`]）。
+    Bps(MarginParams),
+    /// Reg-T 方式（[`RegTCalculator`]）。
+    RegT(RegTParams),
+}
+
+impl MarginModel {
+    /// ネット建玉 `net` を `price` で評価した当初証拠金。
+    #[must_use]
+    pub fn initial_margin(&self, price: i64, net: i64) -> i64 {
+        match self {
+            Self::Bps(params) => {
+                MarginCalculator::new(params.clone()).initial_margin(price, net.unsigned_abs())
+            }
+            Self::RegT(params) => {
+                RegTCalculator::new(params.clone()).equity_initial_margin(price, net)
+            }
+        }
+    }
+
+    /// ネット建玉 `net` を `price` で評価した維持証拠金。
+    #[must_use]
+    pub fn maintenance_margin(&self, price: i64, net: i64) -> i64 {
+        match self {
+            Self::Bps(params) => {
+                MarginCalculator::new(params.clone()).maintenance_margin(price, net.unsigned_abs())
+            }
+            Self::RegT(params) => {
+                RegTCalculator::new(params.clone()).equity_maintenance_margin(price, net)
+            }
+        }
+    }
+}
+
+impl Default for MarginModel {
+    fn default() -> Self {
+        Self::Bps(MarginParams::default())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equity_rates() {
+        let calc = RegTCalculator::default();
+        assert_eq!(calc.equity_initial_margin(100, 1_000), 50_000);
+        assert_eq!(calc.equity_initial_margin(100, -1_000), 50_000);
+        assert_eq!(calc.equity_maintenance_margin(100, 1_000), 25_000);
+        assert_eq!(calc.equity_maintenance_margin(100, -1_000), 30_000);
+    }
+
+    #[test]
+    fn naked_short_options() {
+        let calc = RegTCalculator::default();
+        // 原資産 100、行使価格 110 のコールを 2 でショート: 20 − 10 = 10 と
+        // 最低 10 の大きい方 + 2 = 12、× 100 株 × 3 枚。
+        let call = OptionStrategy::NakedShort {
+            kind: OptionKind::Call,
+            strike: 110,
+            premium: 2,
+            contracts: 3,
+        };
+        assert_eq!(calc.option_requirement(&call, 100), 3_600);
+        // 深いアウト・オブ・ザ・マネーのプットは行使価格の 10% が下限。
+        let put = OptionStrategy::NakedShort {
+            kind: OptionKind::Put,
+            strike: 50,
+            premium: 1,
+            contracts: 1,
+        };
+        assert_eq!(calc.option_requirement(&put, 100), 600);
+        // イン・ザ・マネーのプット: 20 + 5 = 25。
+        let itm = OptionStrategy::NakedShort {
+            kind: OptionKind::Put,
+            strike: 100,
+            premium: 5,
+            contracts: 1,
+        };
+        assert_eq!(calc.option_requirement(&itm, 100), 2_500);
+    }
+
+    #[test]
+    fn strategy_rules() {
+        let calc = RegTCalculator::default();
+        let long = OptionStrategy::Long {
+            premium: 3,
+            contracts: 2,
+        };
+        assert_eq!(calc.option_requirement(&long, 100), 600);
+        let covered = OptionStrategy::CoveredCall { contracts: 5 };
+        assert_eq!(calc.option_requirement(&covered, 100), 0);
+        // ベア・コール・スプレッド（100 売り / 105 買い）: 最大損失 5。
+        let credit = OptionStrategy::Vertical {
+            kind: OptionKind::Call,
+            short_strike: 100,
+            long_strike: 105,
+            contracts: 2,
+        };
+        assert_eq!(calc.option_requirement(&credit, 100), 1_000);
+        // ブル・コール・スプレッド（105 売り / 100 買い）は追加なし。
+        let debit = OptionStrategy::Vertical {
+            kind: OptionKind::Call,
+            short_strike: 105,
+            long_strike: 100,
+            contracts: 2,
+        };
+        assert_eq!(calc.option_requirement(&debit, 100), 0);
+        let bull_put = OptionStrategy::Vertical {
+            kind: OptionKind::Put,
+            short_strike: 95,
+            long_strike: 90,
+            contracts: 1,
+        };
+        assert_eq!(calc.option_requirement(&bull_put, 100), 500);
+    }
+
+    #[test]
+    fn margin_model_dispatch() {
+        let bps = MarginModel::default();
+        assert_eq!(bps.maintenance_margin(100, -1_000), 5_000);
+        assert_eq!(bps.initial_margin(100, -1_000), 10_000);
+        let regt = MarginModel::RegT(RegTParams::default());
+        assert_eq!(regt.maintenance_margin(100, -1_000), 30_000);
+        assert_eq!(regt.initial_margin(100, 1_000), 50_000);
+    }
+}
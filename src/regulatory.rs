@@ -0,0 +1,253 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 取引所・規制当局が課す建玉制限（CFTC の当限建玉制限など）。
+//!
+//! 社内の [`RiskLimits`](crate::RiskLimits) とは別の制限クラスとして、商品
+//! ごとに 2 段階の水準を持つ:
+//!
+//! - アカウンタビリティ水準: 超えても受理するが、当局への説明義務が生じるため
+//!   [`AccountabilityWarning`] を返す
+//! - ハードリミット: 超える注文は [`RegulatoryReject`] で拒否する
+//!
+//! 規制上の建玉は口座単位ではなく会社全体で合算されるため、
+//! [`RegulatoryTracker`] は全口座の建玉を商品ごとに集計して判定する。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Side};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// RegulatoryLimit
+// ---------------------------------------------------------------------------
+
+/// 1 商品に課される規制上の建玉制限（lots、ネットの絶対値）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegulatoryLimit {
+    /// アカウンタビリティ水準。
+    pub accountability_level: u64,
+    /// ハードリミット。
+    pub hard_limit: u64,
+}
+
+// ---------------------------------------------------------------------------
+// RegulatoryReject / AccountabilityWarning
+// ---------------------------------------------------------------------------
+
+/// 規制上のハードリミット違反。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegulatoryReject {
+    /// 制限を課した規制（例: `"CFTC"`）。
+    pub regime: &'static str,
+    /// 商品。
+    pub product_hash: u64,
+    /// 会社全体の現在のネット建玉。
+    pub current: i64,
+    /// 約定後のネット建玉。
+    pub after: i64,
+    /// ハードリミット。
+    pub limit: u64,
+}
+
+/// アカウンタビリティ水準の超過（注文は受理）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountabilityWarning {
+    /// 制限を課した規制。
+    pub regime: &'static str,
+    /// 商品。
+    pub product_hash: u64,
+    /// 約定後のネット建玉。
+    pub after: i64,
+    /// アカウンタビリティ水準。
+    pub level: u64,
+}
+
+// ---------------------------------------------------------------------------
+// RegulatoryTracker
+// ---------------------------------------------------------------------------
+
+/// 会社全体の商品別建玉と規制上の制限。
+#[derive(Debug, Clone)]
+pub struct RegulatoryTracker {
+    regime: &'static str,
+    /// 商品 → 制限。
+    limits: BTreeMap<u64, RegulatoryLimit>,
+    /// (商品, 口座 ID) → ネット建玉。
+    positions: BTreeMap<(u64, u64), i64>,
+}
+
+impl RegulatoryTracker {
+    /// `regime` の制限を管理する追跡器を作成。
+    #[must_use]
+    pub const fn new(regime: &'static str) -> Self {
+        Self {
+            regime,
+            limits: BTreeMap::new(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /// 規制の名前。
+    #[must_use]
+    pub const fn regime(&self) -> &'static str {
+        self.regime
+    }
+
+    /// 商品の制限を設定する（既存は置き換え）。
+    pub fn set_limit(&mut self, product_hash: u64, limit: RegulatoryLimit) {
+        self.limits.insert(product_hash, limit);
+    }
+
+    /// 商品の制限。
+    #[must_use]
+    pub fn limit(&self, product_hash: u64) -> Option<RegulatoryLimit> {
+        self.limits.get(&product_hash).copied()
+    }
+
+    /// 口座の商品建玉を設定する。
+    pub fn set_position(&mut self, account_id: u64, product_hash: u64, net_quantity: i64) {
+        self.positions
+            .insert((product_hash, account_id), net_quantity);
+    }
+
+    /// 約定を口座の商品建玉に反映する（買いは正、売りは負）。
+    pub fn on_fill(&mut self, account_id: u64, product_hash: u64, side: Side, quantity: u64) {
+        let delta = i64::try_from(quantity).unwrap_or(i64::MAX);
+        let entry = self
+            .positions
+            .entry((product_hash, account_id))
+            .or_insert(0);
+        *entry = match side {
+            Side::Bid => entry.saturating_add(delta),
+            Side::Ask => entry.saturating_sub(delta),
+        };
+    }
+
+    /// 会社全体の商品ネット建玉。
+    #[must_use]
+    pub fn firm_position(&self, product_hash: u64) -> i64 {
+        self.positions
+            .range((product_hash, 0)..=(product_hash, u64::MAX))
+            .fold(0_i64, |acc, (_, &n)| acc.saturating_add(n))
+    }
+
+    /// `product_hash` の `order` が約定した場合の会社全体の建玉を判定する。
+    ///
+    /// 制限の無い商品は常に `Ok(None)`。
+    ///
+    /// # Errors
+    ///
+    /// 約定後の建玉がハードリミットを超える場合 [`RegulatoryReject`]。
+    pub fn check_order(
+        &self,
+        product_hash: u64,
+        order: &Order,
+    ) -> Result<Option<AccountabilityWarning>, RegulatoryReject> {
+        let Some(limit) = self.limit(product_hash) else {
+            return Ok(None);
+        };
+        let current = self.firm_position(product_hash);
+        let qty = i64::try_from(order.quantity).unwrap_or(i64::MAX);
+        let after = match order.side {
+            Side::Bid => current.saturating_add(qty),
+            Side::Ask => current.saturating_sub(qty),
+        };
+        if after.unsigned_abs() > limit.hard_limit {
+            return Err(RegulatoryReject {
+                regime: self.regime,
+                product_hash,
+                current,
+                after,
+                limit: limit.hard_limit,
+            });
+        }
+        if after.unsigned_abs() > limit.accountability_level {
+            return Ok(Some(AccountabilityWarning {
+                regime: self.regime,
+                product_hash,
+                after,
+                level: limit.accountability_level,
+            }));
+        }
+        Ok(None)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(side: Side, quantity: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity).build()
+    }
+
+    fn tracker() -> RegulatoryTracker {
+        let mut t = RegulatoryTracker::new("CFTC");
+        t.set_limit(
+            9,
+            RegulatoryLimit {
+                accountability_level: 500,
+                hard_limit: 1_000,
+            },
+        );
+        t
+    }
+
+    #[test]
+    fn aggregates_across_accounts() {
+        let mut t = tracker();
+        t.set_position(1, 9, 400);
+        t.set_position(2, 9, 300);
+        t.set_position(3, 8, 10_000);
+        t.on_fill(2, 9, Side::Ask, 50);
+        assert_eq!(t.firm_position(9), 650);
+        assert_eq!(t.firm_position(8), 10_000);
+    }
+
+    #[test]
+    fn accountability_then_hard_limit() {
+        let mut t = tracker();
+        t.set_position(1, 9, 400);
+        assert_eq!(t.check_order(9, &order(Side::Bid, 100)), Ok(None));
+        assert_eq!(
+            t.check_order(9, &order(Side::Bid, 101)),
+            Ok(Some(AccountabilityWarning {
+                regime: "CFTC",
+                product_hash: 9,
+                after: 501,
+                level: 500,
+            }))
+        );
+        t.set_position(2, 9, 500);
+        assert_eq!(
+            t.check_order(9, &order(Side::Bid, 101)),
+            Err(RegulatoryReject {
+                regime: "CFTC",
+                product_hash: 9,
+                current: 900,
+                after: 1_001,
+                limit: 1_000,
+            })
+        );
+        // 建玉を減らす注文は受理。
+        assert!(t.check_order(9, &order(Side::Ask, 500)).is_ok());
+    }
+
+    #[test]
+    fn short_side_and_unlimited_products() {
+        let mut t = tracker();
+        t.set_position(1, 9, -950);
+        assert!(t.check_order(9, &order(Side::Ask, 51)).is_err());
+        assert_eq!(t.check_order(8, &order(Side::Bid, u64::MAX)), Ok(None));
+        assert_eq!(t.regime(), "CFTC");
+    }
+}
@@ -0,0 +1,427 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 記録済みイベント列からの決定的リプレイ。
+//!
+//! ALICE-Ledger の注文・約定イベントと、当時記録された [`DecisionRecord`]
+//! を時系列順に再投入し、すべてのリスク判定を再計算する。記録と食い違った
+//! 判定は [`Divergence`] として報告されるため、制限値やロジックの変更を
+//! 過去の取引日に対して回帰試験できる。
+//!
+//! 受理・終了のイベントは注文とポジションを持ち、
+//! [`PreTradeChecker::on_order_accepted_in`] /
+//! [`PreTradeChecker::on_order_closed`] で注文 ID 単位に再現する。銘柄別の
+//! 未約定注文数・未約定注文の想定元本・非常駐注文の流量の判定も記録どおりに
+//! 再計算される。
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{DecisionRecord, PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+
+// ---------------------------------------------------------------------------
+// ReplayEvent
+// ---------------------------------------------------------------------------
+
+/// リプレイ対象の 1 イベント。
+#[derive(Debug, Clone)]
+pub enum ReplayEvent {
+    /// 事前チェックされた注文と、当時の判定記録。
+    Order {
+        /// チェック対象の注文。
+        order: Order,
+        /// チェック時点のポジション。
+        position: Option<Position>,
+        /// 記録済みの判定。
+        recorded: DecisionRecord,
+    },
+    /// 注文が受理された。板に載る注文は未約定注文として、載らない注文は
+    /// 非常駐注文の流量として数える。
+    Accepted {
+        /// 受理された注文。
+        order: Order,
+        /// 受理時点のポジション。指定すればその銘柄の未約定注文として数える。
+        position: Option<Position>,
+    },
+    /// 注文が取消または全量約定した。
+    Closed {
+        /// 注文 ID。
+        order_id: u64,
+    },
+    /// 約定等による日次損益の変化。
+    Pnl(i64),
+    /// サーキットブレーカー発動。
+    TripBreaker,
+    /// サーキットブレーカー解除。
+    ResetBreaker,
+    /// 日次リセット。
+    DailyReset,
+    /// 制限値の変更。
+    ApplyLimits {
+        /// 新しい制限値。
+        limits: RiskLimits,
+        /// 変更者。
+        author: String,
+        /// 変更時刻（ns）。
+        timestamp_ns: u64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// ReplayReport
+// ---------------------------------------------------------------------------
+
+/// 記録と再計算の食い違い。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// イベント列内の位置。
+    pub index: usize,
+    /// 注文 ID。
+    pub order_id: u64,
+    /// 記録済みの判定。
+    pub recorded: Result<(), RiskReject>,
+    /// 再計算した判定。
+    pub replayed: Result<(), RiskReject>,
+}
+
+/// リプレイ結果。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ReplayReport {
+    /// 再計算した判定数。
+    pub decisions: usize,
+    /// 食い違った判定（イベント順）。
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// 全判定が記録と一致したか。
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+
+    /// 記録では受理、再計算では拒否になった判定。
+    pub fn newly_rejected(&self) -> impl Iterator<Item = &Divergence> {
+        self.divergences
+            .iter()
+            .filter(|d| d.recorded.is_ok() && d.replayed.is_err())
+    }
+
+    /// 記録では拒否、再計算では受理になった判定。
+    pub fn newly_accepted(&self) -> impl Iterator<Item = &Divergence> {
+        self.divergences
+            .iter()
+            .filter(|d| d.recorded.is_err() && d.replayed.is_ok())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Replayer
+// ---------------------------------------------------------------------------
+
+/// イベント列を [`PreTradeChecker`] に再投入するリプレイヤー。
+pub struct Replayer {
+    checker: PreTradeChecker,
+    apply_limit_events: bool,
+}
+
+impl Replayer {
+    /// 指定の制限値で初期化したチェッカーでリプレイする。
+    #[must_use]
+    pub const fn new(limits: RiskLimits) -> Self {
+        Self::with_checker(PreTradeChecker::new(limits))
+    }
+
+    /// 任意に構成したチェッカーでリプレイする（演算モード等を変えた検証用）。
+    #[must_use]
+    pub const fn with_checker(checker: PreTradeChecker) -> Self {
+        Self {
+            checker,
+            apply_limit_events: true,
+        }
+    }
+
+    /// 記録中の [`ReplayEvent::ApplyLimits`] を無視する。
+    ///
+    /// 候補の制限値を過去の取引日全体に適用して比較する場合に使う。
+    #[must_use]
+    pub const fn ignore_limit_events(mut self) -> Self {
+        self.apply_limit_events = false;
+        self
+    }
+
+    /// イベント列を順に再投入し、食い違いを報告する。
+    pub fn run<'a, I>(&mut self, events: I) -> ReplayReport
+    where
+        I: IntoIterator<Item = &'a ReplayEvent>,
+    {
+        let mut report = ReplayReport::default();
+        for (index, event) in events.into_iter().enumerate() {
+            let replayed = apply_event(&mut self.checker, event, self.apply_limit_events);
+            if let (
+                Some(replayed),
+                ReplayEvent::Order {
+                    order, recorded, ..
+                },
+            ) = (replayed, event)
+            {
+                report.decisions += 1;
+                if replayed != recorded.result {
+                    report.divergences.push(Divergence {
+                        index,
+                        order_id: order.id.0,
+                        recorded: recorded.result.clone(),
+                        replayed,
+                    });
+                }
+            }
+        }
+        report
+    }
+
+    /// リプレイ後のチェッカー状態。
+    #[must_use]
+    pub const fn checker(&self) -> &PreTradeChecker {
+        &self.checker
+    }
+}
+
+/// `event` を `checker` に適用する。注文イベントは再判定した結果を返す。
+pub(crate) fn apply_event(
+    checker: &mut PreTradeChecker,
+    event: &ReplayEvent,
+    apply_limit_events: bool,
+) -> Option<Result<(), RiskReject>> {
+    match event {
+        ReplayEvent::Order {
+            order, position, ..
+        } => return Some(checker.check_order(order, position.as_ref())),
+        ReplayEvent::Accepted { order, position } => match position {
+            Some(p) => checker.on_order_accepted_in(order, p.symbol_hash),
+            None => checker.on_order_accepted(order),
+        },
+        ReplayEvent::Closed { order_id } => {
+            checker.on_order_closed(*order_id);
+        }
+        ReplayEvent::Pnl(pnl) => checker.update_daily_pnl(*pnl),
+        ReplayEvent::TripBreaker => checker.trip_circuit_breaker(),
+        ReplayEvent::ResetBreaker => checker.reset_circuit_breaker(),
+        ReplayEvent::DailyReset => checker.reset_daily(),
+        ReplayEvent::ApplyLimits {
+            limits,
+            author,
+            timestamp_ns,
+        } => {
+            if apply_limit_events {
+                checker.apply_limits(limits.clone(), author, *timestamp_ns);
+            }
+        }
+    }
+    None
+}
+
+/// 指定の制限値でイベント列をリプレイする。
+#[must_use]
+pub fn replay(limits: RiskLimits, events: &[ReplayEvent]) -> ReplayReport {
+    Replayer::new(limits).run(events)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::DecisionTags;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+
+    fn order(id: u64, quantity: u64) -> Order {
+        OrderBuilder::bid(1000, quantity).id(id).at(id).build()
+    }
+
+    /// 実チェッカーで判定を記録しつつイベント列を生成する。
+    fn record_day(limits: RiskLimits) -> Vec<ReplayEvent> {
+        let mut live = PreTradeChecker::new(limits);
+        let mut events = Vec::new();
+        for (id, qty) in [(1, 10), (2, 150), (3, 60), (4, 90)] {
+            let o = order(id, qty);
+            let recorded = live.decide(&o, None);
+            let accepted = recorded.is_accepted();
+            events.push(ReplayEvent::Order {
+                order: o.clone(),
+                position: None,
+                recorded,
+            });
+            if accepted {
+                live.on_order_accepted(&o);
+                events.push(ReplayEvent::Accepted {
+                    order: o,
+                    position: None,
+                });
+            }
+        }
+        live.update_daily_pnl(-100);
+        events.push(ReplayEvent::Pnl(-100));
+        events
+    }
+
+    #[test]
+    fn replay_same_limits_is_consistent() {
+        let events = record_day(RiskLimits::default());
+        let report = replay(RiskLimits::default(), &events);
+        assert_eq!(report.decisions, 4);
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn tighter_limits_report_newly_rejected() {
+        let events = record_day(RiskLimits::default());
+        let candidate = RiskLimits {
+            max_order_size: 50,
+            ..RiskLimits::default()
+        };
+        let report = replay(candidate, &events);
+        let ids: Vec<u64> = report.newly_rejected().map(|d| d.order_id).collect();
+        assert_eq!(ids, vec![3, 4]);
+        assert_eq!(report.newly_accepted().count(), 0);
+    }
+
+    #[test]
+    fn looser_limits_report_newly_accepted() {
+        let events = record_day(RiskLimits::default());
+        let candidate = RiskLimits {
+            max_order_size: 200,
+            ..RiskLimits::default()
+        };
+        let report = replay(candidate, &events);
+        let d: Vec<&Divergence> = report.newly_accepted().collect();
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].order_id, 2);
+        assert!(matches!(
+            d[0].recorded,
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn state_events_are_replayed() {
+        let events = record_day(RiskLimits::default());
+        let mut replayer = Replayer::new(RiskLimits::default());
+        replayer.run(&events);
+        assert_eq!(replayer.checker().open_order_count(), 3);
+        assert_eq!(replayer.checker().daily_pnl(), -100);
+    }
+
+    #[test]
+    fn working_orders_are_replayed_by_id() {
+        let limits = RiskLimits {
+            max_open_orders_per_symbol: 1,
+            ..RiskLimits::default()
+        };
+        let mut live = PreTradeChecker::new(limits.clone());
+        let position = PositionBuilder::new(7, 0).build();
+        let mut events = Vec::new();
+        for (id, close) in [(1, false), (2, true), (3, false)] {
+            let o = order(id, 10);
+            let recorded = live.decide(&o, Some(&position));
+            let accepted = recorded.is_accepted();
+            events.push(ReplayEvent::Order {
+                order: o.clone(),
+                position: Some(position.clone()),
+                recorded,
+            });
+            if accepted {
+                live.on_order_accepted_in(&o, position.symbol_hash);
+                events.push(ReplayEvent::Accepted {
+                    order: o,
+                    position: Some(position.clone()),
+                });
+            }
+            if close {
+                live.on_order_closed(1);
+                events.push(ReplayEvent::Closed { order_id: 1 });
+            }
+        }
+
+        let mut replayer = Replayer::new(limits);
+        let report = replayer.run(&events);
+        assert!(report.is_consistent());
+        let rejected = events.iter().filter(
+            |e| matches!(e, ReplayEvent::Order { recorded, .. } if !recorded.is_accepted()),
+        );
+        assert_eq!(rejected.count(), 1);
+        let checker = replayer.checker();
+        assert!(checker.open_orders().contains(3));
+        assert_eq!(checker.open_order_notional(), 10_000);
+        assert_eq!(checker.state(), live.state());
+    }
+
+    #[test]
+    fn breaker_and_reset_events() {
+        let o = order(9, 1);
+        let events = vec![
+            ReplayEvent::TripBreaker,
+            ReplayEvent::Order {
+                order: o.clone(),
+                position: None,
+                recorded: DecisionRecord {
+                    order_id: 9,
+                    timestamp_ns: 9,
+                    limit_version: 0,
+                    result: Ok(()),
+                    tags: DecisionTags::default(),
+                },
+            },
+            ReplayEvent::ResetBreaker,
+            ReplayEvent::DailyReset,
+        ];
+        let report = replay(RiskLimits::default(), &events);
+        assert_eq!(report.divergences.len(), 1);
+        assert_eq!(
+            report.divergences[0].replayed,
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+        assert_eq!(report.divergences[0].index, 1);
+    }
+
+    #[test]
+    fn limit_events_applied_or_ignored() {
+        let tight = RiskLimits {
+            max_order_size: 5,
+            ..RiskLimits::default()
+        };
+        let o = order(1, 10);
+        let events = vec![
+            ReplayEvent::ApplyLimits {
+                limits: tight,
+                author: "ops".to_string(),
+                timestamp_ns: 0,
+            },
+            ReplayEvent::Order {
+                order: o,
+                position: None,
+                recorded: DecisionRecord {
+                    order_id: 1,
+                    timestamp_ns: 1,
+                    limit_version: 1,
+                    result: Err(RiskReject::OrderSizeTooLarge {
+                        size: 10,
+                        limit: 5,
+                        max_allowed: 5,
+                    }),
+                    tags: DecisionTags::default(),
+                },
+            },
+        ];
+        let mut applied = Replayer::new(RiskLimits::default());
+        assert!(applied.run(&events).is_consistent());
+        assert_eq!(applied.checker().limit_version(), 1);
+
+        let mut ignored = Replayer::new(RiskLimits::default()).ignore_limit_events();
+        assert_eq!(ignored.run(&events).newly_accepted().count(), 1);
+        assert_eq!(ignored.checker().limit_version(), 0);
+    }
+}
@@ -0,0 +1,362 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 制限値の口座コホート単位の段階適用（カナリア）。
+//!
+//! [`CanaryRollout`] は新しい制限値を、まず [`Cohort`] で選んだ一部の口座だけに
+//! 適用する。コホートの判定結果と日次損益を [`CanaryRollout::record_decision`] /
+//! [`CanaryRollout::record_pnl`] で受け取り、拒否率または損失が [`Guardrails`]
+//! を超えたら [`CanaryRollout::evaluate`] が各口座を適用前のバージョンへ自動で
+//! 戻す（[`PreTradeChecker::rollback_to`](crate::PreTradeChecker::rollback_to)）。
+//! 問題が無ければ [`CanaryRollout::complete`] で残りの口座にも適用する。
+//!
+//! 割合指定のコホートは口座 ID のハッシュで決まるため、同じ割合なら毎回同じ
+//! 口座が選ばれる。
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use crate::engine::RiskEngine;
+use crate::limit::RiskLimits;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// Cohort / Guardrails
+// ---------------------------------------------------------------------------
+
+/// 先行適用する口座の選び方。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cohort {
+    /// 口座 ID のハッシュで選ぶ割合（bps、10000 = 全口座）。
+    Percent(u32),
+    /// 口座 ID の一覧。
+    Accounts(BTreeSet<u64>),
+}
+
+impl Cohort {
+    /// 口座がコホートに含まれるか。
+    #[must_use]
+    pub fn contains(&self, account_id: u64) -> bool {
+        match self {
+            Self::Percent(bps) => bucket(account_id) < u64::from(*bps),
+            Self::Accounts(ids) => ids.contains(&account_id),
+        }
+    }
+}
+
+/// 口座 ID を 0〜9999 に一様に割り当てる（splitmix64）。
+const fn bucket(account_id: u64) -> u64 {
+    let mut z = account_id.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) % 10_000
+}
+
+/// 自動で切り戻す条件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guardrails {
+    /// コホート全体の拒否率の上限（bps）。
+    pub max_reject_rate_bps: u32,
+    /// 拒否率を判定するのに必要な最小判定数。
+    pub min_decisions: u64,
+    /// コホート全体の日次損益の下限（負の値）。
+    pub max_loss: i64,
+}
+
+impl Default for Guardrails {
+    fn default() -> Self {
+        Self {
+            max_reject_rate_bps: 1_000,
+            min_decisions: 50,
+            max_loss: -1_000_000,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CanaryRollout
+// ---------------------------------------------------------------------------
+
+/// 切り戻しの理由。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollbackReason {
+    /// 拒否率が上限を超えた。
+    RejectRate { rate_bps: u32, limit_bps: u32 },
+    /// 日次損益が下限を下回った。
+    Loss { pnl: i64, limit: i64 },
+}
+
+/// 段階適用の状態。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RolloutState {
+    /// コホートに適用中。
+    Canary,
+    /// 切り戻し済み。
+    RolledBack(RollbackReason),
+    /// 全口座に適用済み。
+    Completed,
+}
+
+/// 制限値の段階適用。
+#[derive(Debug, Clone)]
+pub struct CanaryRollout {
+    limits: RiskLimits,
+    cohort: Cohort,
+    guardrails: Guardrails,
+    /// コホートの口座 → 適用前のバージョン。
+    previous: BTreeMap<u64, u64>,
+    decisions: u64,
+    rejects: u64,
+    /// コホートの口座 → 日次損益。
+    pnl: BTreeMap<u64, i64>,
+    state: RolloutState,
+}
+
+impl CanaryRollout {
+    /// `engine` のうち `cohort` に含まれる口座に `limits` を適用して開始する。
+    #[must_use]
+    pub fn start(
+        engine: &mut RiskEngine,
+        limits: RiskLimits,
+        cohort: Cohort,
+        guardrails: Guardrails,
+        author: &str,
+        timestamp_ns: u64,
+    ) -> Self {
+        let mut previous = BTreeMap::new();
+        let ids: Vec<u64> = engine.account_ids().collect();
+        for account_id in ids.into_iter().filter(|&id| cohort.contains(id)) {
+            if let Some(checker) = engine.checker_mut(account_id) {
+                previous.insert(account_id, checker.limit_version());
+                checker.apply_limits(limits.clone(), author, timestamp_ns);
+            }
+        }
+        Self {
+            limits,
+            cohort,
+            guardrails,
+            previous,
+            decisions: 0,
+            rejects: 0,
+            pnl: BTreeMap::new(),
+            state: RolloutState::Canary,
+        }
+    }
+
+    /// 状態。
+    #[must_use]
+    pub const fn state(&self) -> RolloutState {
+        self.state
+    }
+
+    /// コホートの口座（口座 ID 順）。
+    pub fn cohort_accounts(&self) -> impl Iterator<Item = u64> + '_ {
+        self.previous.keys().copied()
+    }
+
+    /// 口座の判定結果を記録する。コホート外の口座は無視する。
+    pub fn record_decision(&mut self, account_id: u64, accepted: bool) {
+        if self.previous.contains_key(&account_id) {
+            self.decisions += 1;
+            self.rejects += u64::from(!accepted);
+        }
+    }
+
+    /// 口座の日次損益を記録する（置き換え）。コホート外の口座は無視する。
+    pub fn record_pnl(&mut self, account_id: u64, daily_pnl: i64) {
+        if self.previous.contains_key(&account_id) {
+            self.pnl.insert(account_id, daily_pnl);
+        }
+    }
+
+    /// コホートの拒否率（bps）。判定が無ければ 0。
+    #[must_use]
+    pub fn reject_rate_bps(&self) -> u32 {
+        if self.decisions == 0 {
+            return 0;
+        }
+        (u128::from(self.rejects) * 10_000 / u128::from(self.decisions)) as u32
+    }
+
+    /// ガードレールを判定し、超えていればコホートを適用前のバージョンへ戻して
+    /// 理由を返す。適用中でなければ何もしない。
+    pub fn evaluate(
+        &mut self,
+        engine: &mut RiskEngine,
+        author: &str,
+        timestamp_ns: u64,
+    ) -> Option<RollbackReason> {
+        if self.state != RolloutState::Canary {
+            return None;
+        }
+        let g = self.guardrails;
+        let rate_bps = self.reject_rate_bps();
+        let pnl = self
+            .pnl
+            .values()
+            .fold(0_i64, |acc, &p| acc.saturating_add(p));
+        let reason = if self.decisions >= g.min_decisions && rate_bps > g.max_reject_rate_bps {
+            RollbackReason::RejectRate {
+                rate_bps,
+                limit_bps: g.max_reject_rate_bps,
+            }
+        } else if pnl < g.max_loss {
+            RollbackReason::Loss {
+                pnl,
+                limit: g.max_loss,
+            }
+        } else {
+            return None;
+        };
+        self.rollback(engine, author, timestamp_ns);
+        self.state = RolloutState::RolledBack(reason);
+        Some(reason)
+    }
+
+    fn rollback(&self, engine: &mut RiskEngine, author: &str, timestamp_ns: u64) {
+        for (&account_id, &version) in &self.previous {
+            if let Some(checker) = engine.checker_mut(account_id) {
+                // 適用前のバージョンは履歴に必ず残っている。
+                let _ = checker.rollback_to(version, author, timestamp_ns);
+            }
+        }
+    }
+
+    /// 残りの口座にも適用して完了する。適用中でなければ何もせず `false`。
+    pub fn complete(&mut self, engine: &mut RiskEngine, author: &str, timestamp_ns: u64) -> bool {
+        if self.state != RolloutState::Canary {
+            return false;
+        }
+        let ids: Vec<u64> = engine.account_ids().collect();
+        for account_id in ids {
+            if self.previous.contains_key(&account_id) {
+                continue;
+            }
+            if let Some(checker) = engine.checker_mut(account_id) {
+                checker.apply_limits(self.limits.clone(), author, timestamp_ns);
+            }
+        }
+        self.state = RolloutState::Completed;
+        true
+    }
+
+    /// 先行適用の対象の選び方。
+    #[must_use]
+    pub const fn cohort(&self) -> &Cohort {
+        &self.cohort
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::margin::MarginParams;
+
+    fn engine() -> RiskEngine {
+        let mut engine = RiskEngine::new(MarginParams::default());
+        for id in 1..=4 {
+            engine.add_account(id, RiskLimits::default(), 0);
+        }
+        engine
+    }
+
+    fn tight() -> RiskLimits {
+        RiskLimits {
+            max_order_size: 5,
+            ..RiskLimits::default()
+        }
+    }
+
+    fn size(engine: &RiskEngine, id: u64) -> u64 {
+        engine.checker(id).unwrap().limits().max_order_size
+    }
+
+    #[test]
+    fn percent_cohort_is_deterministic() {
+        assert!((0..1_000).all(|id| Cohort::Percent(10_000).contains(id)));
+        assert!((0..1_000).all(|id| !Cohort::Percent(0).contains(id)));
+        let picked = (0..10_000)
+            .filter(|&id| Cohort::Percent(2_500).contains(id))
+            .count();
+        assert!((2_300..2_700).contains(&picked), "{picked}");
+        assert_eq!(
+            Cohort::Percent(2_500).contains(42),
+            Cohort::Percent(2_500).contains(42)
+        );
+    }
+
+    #[test]
+    fn rolls_back_on_reject_rate() {
+        let mut engine = engine();
+        let cohort = Cohort::Accounts([1, 3].into_iter().collect());
+        let guardrails = Guardrails {
+            min_decisions: 4,
+            ..Guardrails::default()
+        };
+        let mut rollout = CanaryRollout::start(&mut engine, tight(), cohort, guardrails, "risk", 1);
+        let default_size = RiskLimits::default().max_order_size;
+        assert_eq!(size(&engine, 1), 5);
+        assert_eq!(size(&engine, 2), default_size);
+        assert_eq!(rollout.cohort_accounts().collect::<Vec<_>>(), [1, 3]);
+        for accepted in [true, false, true] {
+            rollout.record_decision(1, accepted);
+        }
+        // コホート外は数えない。判定数が足りないので保留。
+        rollout.record_decision(2, false);
+        assert_eq!(rollout.evaluate(&mut engine, "auto", 2), None);
+        rollout.record_decision(3, true);
+        assert_eq!(
+            rollout.evaluate(&mut engine, "auto", 3),
+            Some(RollbackReason::RejectRate {
+                rate_bps: 2_500,
+                limit_bps: 1_000,
+            })
+        );
+        assert_eq!(size(&engine, 1), default_size);
+        assert_eq!(
+            engine
+                .checker(1)
+                .unwrap()
+                .history()
+                .last()
+                .unwrap()
+                .rollback_of,
+            Some(0)
+        );
+        assert!(!rollout.complete(&mut engine, "risk", 4));
+    }
+
+    #[test]
+    fn rolls_back_on_loss_or_completes() {
+        let mut engine = engine();
+        let cohort = Cohort::Accounts([2].into_iter().collect());
+        let guardrails = Guardrails {
+            max_loss: -1_000,
+            ..Guardrails::default()
+        };
+        let mut rollout =
+            CanaryRollout::start(&mut engine, tight(), cohort.clone(), guardrails, "risk", 1);
+        rollout.record_pnl(2, -1_001);
+        assert_eq!(
+            rollout.evaluate(&mut engine, "auto", 2),
+            Some(RollbackReason::Loss {
+                pnl: -1_001,
+                limit: -1_000,
+            })
+        );
+
+        let mut rollout = CanaryRollout::start(&mut engine, tight(), cohort, guardrails, "risk", 3);
+        rollout.record_pnl(2, -500);
+        assert_eq!(rollout.evaluate(&mut engine, "auto", 4), None);
+        assert!(rollout.complete(&mut engine, "risk", 5));
+        assert_eq!(rollout.state(), RolloutState::Completed);
+        assert!((1..=4).all(|id| size(&engine, id) == 5));
+    }
+}
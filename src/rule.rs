@@ -0,0 +1,652 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 宣言的なカスタムルール。
+//!
+//! リスク管理者が Rust のデプロイなしに判定を追加できるよう、1 行 1 ルールの
+//! 小さな言語を読込時に [`RuleSet`] へコンパイルする。
+//!
+//! ```text
+//! # 名前: reject if 条件
+//! close_index: reject if notional > 1_000_000 and symbol in {ES, NQ} and session == "close"
+//! no_flip:     reject if position > 0 and position_after < 0
+//! reject if side == sell and not (quantity <= 50 or session == "open")
+//! ```
+//!
+//! 条件は `and` / `or` / `not` と括弧で組み合わせる。比較できるフィールドは
+//! 数値の `notional`（`|price| * quantity`）・`quantity`・`price`・`position`
+//! （約定前のネット建玉）・`position_after`、銘柄の `symbol`、売買方向の
+//! `side`（`buy` / `sell`）、セッション名の `session`（文字列）。数値は
+//! `> >= < <= == !=`、その他は `== !=` で比較し、いずれも `in {…}` で集合に
+//! 含まれるかを判定できる。銘柄名は読込時に呼び出し側のリゾルバで
+//! `symbol_hash` に解決する（整数をそのまま書いてもよい）。
+//!
+//! 構文・型の誤りは [`ConfigError`]（行番号とルール名付き）として報告する。
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Side};
+
+use crate::config::{ConfigError, ConfigErrorKind};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// RuleReject / RuleInput
+// ---------------------------------------------------------------------------
+
+/// カスタムルールによる拒否。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleReject {
+    /// 条件に一致したルール。
+    Matched { rule: String },
+}
+
+/// ルールを評価する注文の属性。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleInput<'a> {
+    /// 銘柄。
+    pub symbol_hash: u64,
+    /// 売買方向。
+    pub side: Side,
+    /// 価格。
+    pub price: i64,
+    /// 数量。
+    pub quantity: u64,
+    /// 約定前のネット建玉。
+    pub position: i64,
+    /// セッション名。
+    pub session: &'a str,
+}
+
+impl<'a> RuleInput<'a> {
+    /// 注文と銘柄・建玉・セッションから作成。
+    #[must_use]
+    pub const fn new(order: &Order, symbol_hash: u64, position: i64, session: &'a str) -> Self {
+        Self {
+            symbol_hash,
+            side: order.side,
+            price: order.price,
+            quantity: order.quantity,
+            position,
+            session,
+        }
+    }
+
+    /// 数値フィールドの値。数値でないフィールドは `None`。
+    fn number(&self, field: Field) -> Option<i128> {
+        let quantity = i128::from(self.quantity);
+        Some(match field {
+            Field::Notional => i128::from(self.price).abs() * quantity,
+            Field::Quantity => quantity,
+            Field::Price => i128::from(self.price),
+            Field::Position => i128::from(self.position),
+            Field::PositionAfter => match self.side {
+                Side::Bid => i128::from(self.position) + quantity,
+                Side::Ask => i128::from(self.position) - quantity,
+            },
+            Field::Symbol | Field::Side | Field::Session => return None,
+        })
+    }
+
+    /// フィールドの値が `value` に等しいか。
+    fn equals(&self, field: Field, value: &Value) -> bool {
+        match (field, value) {
+            (Field::Symbol, Value::Symbol(s)) => self.symbol_hash == *s,
+            (Field::Side, Value::Side(s)) => self.side == *s,
+            (Field::Session, Value::Str(s)) => self.session == s.as_str(),
+            (_, Value::Int(v)) => self.number(field) == Some(*v),
+            _ => false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AST
+// ---------------------------------------------------------------------------
+
+/// 比較できるフィールド。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Notional,
+    Quantity,
+    Price,
+    Position,
+    PositionAfter,
+    Symbol,
+    Side,
+    Session,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "notional" => Self::Notional,
+            "quantity" => Self::Quantity,
+            "price" => Self::Price,
+            "position" => Self::Position,
+            "position_after" => Self::PositionAfter,
+            "symbol" => Self::Symbol,
+            "side" => Self::Side,
+            "session" => Self::Session,
+            _ => return None,
+        })
+    }
+
+    const fn type_name(self) -> &'static str {
+        match self {
+            Self::Symbol => "symbol",
+            Self::Side => "side",
+            Self::Session => "string",
+            _ => "integer",
+        }
+    }
+}
+
+/// 比較値。
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Int(i128),
+    Symbol(u64),
+    Side(Side),
+    Str(String),
+}
+
+/// 比較演算子。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+/// コンパイル済みの条件式。
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    And(Box<Self>, Box<Self>),
+    Or(Box<Self>, Box<Self>),
+    Not(Box<Self>),
+    Cmp(Field, CmpOp, Value),
+    In(Field, Vec<Value>),
+}
+
+impl Expr {
+    fn eval(&self, input: &RuleInput<'_>) -> bool {
+        match self {
+            Self::And(a, b) => a.eval(input) && b.eval(input),
+            Self::Or(a, b) => a.eval(input) || b.eval(input),
+            Self::Not(a) => !a.eval(input),
+            Self::Cmp(field, op, value) => match (op, value) {
+                (CmpOp::Eq, _) => input.equals(*field, value),
+                (CmpOp::Ne, _) => !input.equals(*field, value),
+                (op, Value::Int(b)) => input.number(*field).is_some_and(|a| match op {
+                    CmpOp::Gt => a > *b,
+                    CmpOp::Ge => a >= *b,
+                    CmpOp::Lt => a < *b,
+                    _ => a <= *b,
+                }),
+                _ => false,
+            },
+            Self::In(field, values) => values.iter().any(|v| input.equals(*field, v)),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lexer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    Str(String),
+    Op(CmpOp),
+    Open,
+    Close,
+    OpenSet,
+    CloseSet,
+    Comma,
+    Colon,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, &'static str> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        i += 1;
+        let token = match c {
+            b' ' | b'\t' => continue,
+            // 文字列の外の `#` 以降はコメント。
+            b'#' => break,
+            b'(' => Token::Open,
+            b')' => Token::Close,
+            b'{' => Token::OpenSet,
+            b'}' => Token::CloseSet,
+            b',' => Token::Comma,
+            b':' => Token::Colon,
+            b'>' | b'<' | b'=' | b'!' => {
+                let eq = bytes.get(i) == Some(&b'=');
+                if eq {
+                    i += 1;
+                }
+                Token::Op(match (c, eq) {
+                    (b'>', false) => CmpOp::Gt,
+                    (b'>', true) => CmpOp::Ge,
+                    (b'<', false) => CmpOp::Lt,
+                    (b'<', true) => CmpOp::Le,
+                    (b'=', true) => CmpOp::Eq,
+                    (b'!', true) => CmpOp::Ne,
+                    _ => return Err("expected comparison operator"),
+                })
+            }
+            b'"' => {
+                let end = text[i..].find('"').ok_or("unterminated string")?;
+                let s = text[i..i + end].to_string();
+                i += end + 1;
+                Token::Str(s)
+            }
+            b'-' | b'0'..=b'9' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let digits: String = text[start..i].chars().filter(|&c| c != '_').collect();
+                Token::Int(digits.parse().map_err(|_| "invalid integer")?)
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                Token::Ident(text[start..i].to_string())
+            }
+            _ => return Err("unexpected character"),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+struct Parser<'t, F> {
+    tokens: &'t [Token],
+    pos: usize,
+    resolve: &'t F,
+}
+
+impl<F: Fn(&str) -> Option<u64>> Parser<'_, F> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, msg: &'static str) -> Result<(), ConfigErrorKind> {
+        if self.next() == Some(token) {
+            Ok(())
+        } else {
+            Err(ConfigErrorKind::Syntax(msg))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ConfigErrorKind> {
+        let mut left = self.and()?;
+        while self.keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, ConfigErrorKind> {
+        let mut left = self.unary()?;
+        while self.keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ConfigErrorKind> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let expr = self.or()?;
+            self.expect(&Token::Close, "expected ')'")?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ConfigErrorKind> {
+        let Some(Token::Ident(name)) = self.next() else {
+            return Err(ConfigErrorKind::Syntax("expected field"));
+        };
+        let field = Field::parse(name).ok_or(ConfigErrorKind::UnknownField)?;
+        if self.keyword("in") {
+            self.expect(&Token::OpenSet, "expected '{'")?;
+            let mut values = Vec::new();
+            loop {
+                values.push(self.value(field)?);
+                match self.next() {
+                    Some(Token::Comma) => {}
+                    Some(Token::CloseSet) => break,
+                    _ => return Err(ConfigErrorKind::Syntax("expected ',' or '}'")),
+                }
+            }
+            return Ok(Expr::In(field, values));
+        }
+        let Some(&Token::Op(op)) = self.next() else {
+            return Err(ConfigErrorKind::Syntax("expected comparison operator"));
+        };
+        let value = self.value(field)?;
+        if !matches!(value, Value::Int(_)) && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+            return Err(ConfigErrorKind::Invalid(
+                "ordering comparison on non-numeric field",
+            ));
+        }
+        Ok(Expr::Cmp(field, op, value))
+    }
+
+    fn value(&mut self, field: Field) -> Result<Value, ConfigErrorKind> {
+        let resolve = self.resolve;
+        let token = self
+            .next()
+            .ok_or(ConfigErrorKind::Syntax("expected value"))?;
+        let found = match token {
+            Token::Int(_) => "integer",
+            Token::Str(_) => "string",
+            Token::Ident(_) => "identifier",
+            _ => return Err(ConfigErrorKind::Syntax("expected value")),
+        };
+        match (field, token) {
+            (Field::Symbol, Token::Ident(name)) => resolve(name)
+                .map(Value::Symbol)
+                .ok_or(ConfigErrorKind::Invalid("unknown symbol")),
+            (Field::Symbol, &Token::Int(id)) => u64::try_from(id)
+                .map(Value::Symbol)
+                .map_err(|_| ConfigErrorKind::OutOfRange),
+            (Field::Side, Token::Ident(word)) => match word.as_str() {
+                "buy" => Ok(Value::Side(Side::Bid)),
+                "sell" => Ok(Value::Side(Side::Ask)),
+                _ => Err(ConfigErrorKind::Invalid("side must be buy or sell")),
+            },
+            (Field::Session, Token::Str(s)) => Ok(Value::Str(s.clone())),
+            (Field::Symbol | Field::Side | Field::Session, _) => {
+                Err(ConfigErrorKind::TypeMismatch {
+                    expected: field.type_name(),
+                    found,
+                })
+            }
+            (_, &Token::Int(v)) => Ok(Value::Int(v)),
+            _ => Err(ConfigErrorKind::TypeMismatch {
+                expected: field.type_name(),
+                found,
+            }),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RuleSet
+// ---------------------------------------------------------------------------
+
+/// コンパイル済みのルール。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    name: String,
+    expr: Expr,
+}
+
+/// コンパイル済みのルール一式。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// ルール定義を読み込む。
+    ///
+    /// 1 行 1 ルールで、空行と文字列の外の `#` 以降は無視する。名前を省略したルールは
+    /// `line<行番号>` と呼ぶ。`resolve` は銘柄名を `symbol_hash` に解決する。
+    ///
+    /// # Errors
+    ///
+    /// 構文・フィールド・型・銘柄名の誤りは、行番号とルール名を付けた
+    /// [`ConfigError`]。
+    pub fn parse<F: Fn(&str) -> Option<u64>>(text: &str, resolve: F) -> Result<Self, ConfigError> {
+        let mut rules = Vec::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = index + 1;
+            let mut name = alloc::format!("line{line}");
+            let error = |name: &str, kind| ConfigError {
+                line,
+                path: name.to_string(),
+                kind,
+            };
+            let tokens = tokenize(raw).map_err(|msg| error(&name, ConfigErrorKind::Syntax(msg)))?;
+            if tokens.is_empty() {
+                continue;
+            }
+            let mut parser = Parser {
+                tokens: &tokens,
+                pos: 0,
+                resolve: &resolve,
+            };
+            if let (Some(Token::Ident(n)), Some(Token::Colon)) = (tokens.first(), tokens.get(1)) {
+                name.clone_from(n);
+                parser.pos = 2;
+            }
+            if !(parser.keyword("reject") && parser.keyword("if")) {
+                return Err(error(
+                    &name,
+                    ConfigErrorKind::Syntax("expected 'reject if'"),
+                ));
+            }
+            let expr = parser.or().map_err(|kind| error(&name, kind))?;
+            if parser.peek().is_some() {
+                return Err(error(
+                    &name,
+                    ConfigErrorKind::Syntax("unexpected trailing input"),
+                ));
+            }
+            if rules.iter().any(|r: &Rule| r.name == name) {
+                return Err(error(&name, ConfigErrorKind::DuplicateKey));
+            }
+            rules.push(Rule { name, expr });
+        }
+        Ok(Self { rules })
+    }
+
+    /// ルール数。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// ルールが無いか。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// ルール名（定義順）。
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|r| r.name.as_str())
+    }
+
+    /// `input` を定義順に評価し、最初に一致したルールで拒否する。
+    ///
+    /// # Errors
+    ///
+    /// [`RuleReject::Matched`]。
+    pub fn check(&self, input: &RuleInput<'_>) -> Result<(), RuleReject> {
+        match self.rules.iter().find(|r| r.expr.eval(input)) {
+            Some(rule) => Err(RuleReject::Matched {
+                rule: rule.name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 注文を評価する（[`RuleInput::new`] と [`Self::check`]）。
+    ///
+    /// # Errors
+    ///
+    /// [`RuleReject::Matched`]。
+    pub fn check_order(
+        &self,
+        order: &Order,
+        symbol_hash: u64,
+        position: i64,
+        session: &str,
+    ) -> Result<(), RuleReject> {
+        self.check(&RuleInput::new(order, symbol_hash, position, session))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    const ES: u64 = 1;
+    const NQ: u64 = 2;
+
+    fn resolve(name: &str) -> Option<u64> {
+        match name {
+            "ES" => Some(ES),
+            "NQ" => Some(NQ),
+            _ => None,
+        }
+    }
+
+    fn order(side: Side, price: i64, quantity: u64) -> Order {
+        OrderBuilder::new(side, price, quantity).build()
+    }
+
+    const RULES: &str = "
+        # 引けの大口指数先物
+        close_index: reject if notional > 1_000_000 and symbol in {ES, NQ} and session == \"close\"
+        no_flip: reject if position > 0 and position_after < 0  # ドテン禁止
+        reject if side == sell and not (quantity <= 50 or session == \"open\")
+    ";
+
+    #[test]
+    fn evaluates_rules_in_order() {
+        let rules = RuleSet::parse(RULES, resolve).unwrap();
+        assert_eq!(
+            rules.names().collect::<Vec<_>>(),
+            ["close_index", "no_flip", "line5"]
+        );
+        let big = order(Side::Bid, 5_000, 300);
+        assert_eq!(
+            rules.check_order(&big, ES, 0, "close"),
+            Err(RuleReject::Matched {
+                rule: "close_index".into(),
+            })
+        );
+        assert!(rules.check_order(&big, ES, 0, "open").is_ok());
+        assert!(rules.check_order(&big, 3, 0, "close").is_ok());
+        let flip = order(Side::Ask, 100, 20);
+        assert_eq!(
+            rules.check_order(&flip, ES, 10, "open"),
+            Err(RuleReject::Matched {
+                rule: "no_flip".into(),
+            })
+        );
+        assert!(rules.check_order(&flip, ES, 20, "open").is_ok());
+        let sell = order(Side::Ask, 100, 60);
+        assert!(rules.check_order(&sell, ES, -5, "open").is_ok());
+        assert_eq!(
+            rules.check_order(&sell, ES, -5, "close"),
+            Err(RuleReject::Matched {
+                rule: "line5".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_line_and_rule() {
+        let err = |text| RuleSet::parse(text, resolve).unwrap_err();
+        let e = err("\nbad: reject if volume > 1");
+        assert_eq!((e.line, e.path.as_str()), (2, "bad"));
+        assert_eq!(e.kind, ConfigErrorKind::UnknownField);
+        assert_eq!(
+            err("reject if symbol in {ES, CL}").kind,
+            ConfigErrorKind::Invalid("unknown symbol")
+        );
+        assert_eq!(
+            err("reject if session == close").kind,
+            ConfigErrorKind::TypeMismatch {
+                expected: "string",
+                found: "identifier",
+            }
+        );
+        assert_eq!(
+            err("reject if side > buy").kind,
+            ConfigErrorKind::Invalid("ordering comparison on non-numeric field")
+        );
+        assert!(matches!(
+            err("reject if (quantity > 1").kind,
+            ConfigErrorKind::Syntax(_)
+        ));
+        assert!(matches!(
+            err("quantity > 1").kind,
+            ConfigErrorKind::Syntax(_)
+        ));
+        assert_eq!(
+            err("a: reject if quantity > 1\na: reject if price > 1").kind,
+            ConfigErrorKind::DuplicateKey
+        );
+        assert!(RuleSet::parse("# comment only\n", resolve)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn hash_inside_string_is_not_a_comment() {
+        let rules = RuleSet::parse(
+            "late: reject if session == \"close#2\" # 引け後の第 2 セッション\n",
+            resolve,
+        )
+        .unwrap();
+        let buy = order(Side::Bid, 100, 1);
+        assert!(rules.check_order(&buy, ES, 0, "close").is_ok());
+        assert_eq!(
+            rules.check_order(&buy, ES, 0, "close#2"),
+            Err(RuleReject::Matched {
+                rule: "late".into(),
+            })
+        );
+    }
+}
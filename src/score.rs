@@ -0,0 +1,380 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 口座ごとの総合リスクスコア。
+//!
+//! [`RiskScorer`] は制限使用率・ドローダウン・リターンの変動率・拒否率・
+//! 証拠金カバレッジの 5 要素をそれぞれ 0〜10000（10000 = 要注意の上限）に
+//! 正規化し、[`RiskScoreWeights`] で加重平均した [`RiskScore`] を口座ごとに
+//! 保持する。スコアは `on_*` のイベントを受け取るたびに再計算して返すため、
+//! 監視側はその戻り値をそのまま配信でき、[`RiskScorer::ranking`] で注意が
+//! 必要な順に口座を並べられる。
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use crate::check::Utilization;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// RiskScoreConfig
+// ---------------------------------------------------------------------------
+
+/// 各要素の重み（比率のみが意味を持つ）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RiskScoreWeights {
+    /// 制限使用率。
+    pub utilization: u32,
+    /// ピーク資産額からのドローダウン。
+    pub drawdown: u32,
+    /// リターンの変動率。
+    pub volatility: u32,
+    /// 拒否率。
+    pub reject_rate: u32,
+    /// 証拠金カバレッジの不足。
+    pub margin_coverage: u32,
+}
+
+impl Default for RiskScoreWeights {
+    fn default() -> Self {
+        Self {
+            utilization: 1,
+            drawdown: 1,
+            volatility: 1,
+            reject_rate: 1,
+            margin_coverage: 1,
+        }
+    }
+}
+
+/// スコア計算の設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RiskScoreConfig {
+    /// 要素の重み。
+    pub weights: RiskScoreWeights,
+    /// 要素が上限（10000）になるドローダウン（bps）。
+    pub max_drawdown_bps: u32,
+    /// 要素が上限になるリターンの標準偏差（bps）。
+    pub max_volatility_bps: u32,
+    /// 要素が上限になる拒否率（bps）。
+    pub max_reject_rate_bps: u32,
+    /// 要素が 0 になる証拠金カバレッジ（bps）。10000（ちょうど充足）以下で上限。
+    pub safe_coverage_bps: u32,
+    /// 変動率を求めるリターンの件数。
+    pub returns_window: usize,
+    /// 拒否率を求める判定の件数。
+    pub decisions_window: usize,
+}
+
+impl Default for RiskScoreConfig {
+    fn default() -> Self {
+        Self {
+            weights: RiskScoreWeights::default(),
+            max_drawdown_bps: 2_000,
+            max_volatility_bps: 500,
+            max_reject_rate_bps: 2_000,
+            safe_coverage_bps: 30_000,
+            returns_window: 20,
+            decisions_window: 100,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RiskScore
+// ---------------------------------------------------------------------------
+
+/// 口座の総合リスクスコアと要素（いずれも 0〜10000）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RiskScore {
+    /// 口座 ID。
+    pub account_id: u64,
+    /// 加重平均した総合スコア。
+    pub total: u32,
+    /// 制限使用率（注文数・未約定想定元本・日次損失の最大）。
+    pub utilization: u32,
+    /// ドローダウン。
+    pub drawdown: u32,
+    /// リターンの変動率。
+    pub volatility: u32,
+    /// 拒否率。
+    pub reject_rate: u32,
+    /// 証拠金カバレッジの不足。
+    pub margin_coverage: u32,
+}
+
+// ---------------------------------------------------------------------------
+// RiskScorer
+// ---------------------------------------------------------------------------
+
+/// 1 口座の集計状態。
+#[derive(Debug, Clone, Default)]
+struct AccountState {
+    utilization_bps: u32,
+    equity: Option<i64>,
+    peak_equity: i64,
+    /// 資産額の変化率（bps）。
+    returns: VecDeque<i64>,
+    /// 判定結果（true = 拒否）。
+    decisions: VecDeque<bool>,
+    coverage_bps: Option<u32>,
+    score: RiskScore,
+}
+
+/// 口座ごとの総合リスクスコア。
+#[derive(Debug, Clone)]
+pub struct RiskScorer {
+    config: RiskScoreConfig,
+    accounts: BTreeMap<u64, AccountState>,
+}
+
+impl RiskScorer {
+    /// 設定から作成。
+    #[must_use]
+    pub const fn new(config: RiskScoreConfig) -> Self {
+        Self {
+            config,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &RiskScoreConfig {
+        &self.config
+    }
+
+    /// 制限の使用状況を反映する。
+    pub fn on_utilization(&mut self, account_id: u64, utilization: &Utilization) -> RiskScore {
+        let state = self.accounts.entry(account_id).or_default();
+        state.utilization_bps = utilization
+            .open_orders_bps()
+            .max(utilization.open_order_notional_bps())
+            .max(utilization.daily_loss_bps());
+        self.rescore(account_id)
+    }
+
+    /// 資産額の更新を反映する（ドローダウンと変動率）。
+    pub fn on_equity(&mut self, account_id: u64, equity: i64) -> RiskScore {
+        let window = self.config.returns_window;
+        let state = self.accounts.entry(account_id).or_default();
+        if let Some(previous) = state.equity {
+            if previous > 0 {
+                let ret =
+                    (i128::from(equity) - i128::from(previous)) * 10_000 / i128::from(previous);
+                state
+                    .returns
+                    .push_back(ret.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64);
+                while state.returns.len() > window {
+                    state.returns.pop_front();
+                }
+            }
+        }
+        state.equity = Some(equity);
+        state.peak_equity = state.peak_equity.max(equity);
+        self.rescore(account_id)
+    }
+
+    /// 注文判定の結果を反映する（拒否率）。
+    pub fn on_decision(&mut self, account_id: u64, accepted: bool) -> RiskScore {
+        let window = self.config.decisions_window;
+        let state = self.accounts.entry(account_id).or_default();
+        state.decisions.push_back(!accepted);
+        while state.decisions.len() > window {
+            state.decisions.pop_front();
+        }
+        self.rescore(account_id)
+    }
+
+    /// 証拠金カバレッジ（資産額 / 維持証拠金、bps）を反映する。`None` は
+    /// 維持証拠金が 0 または不明。
+    pub fn on_margin_coverage(&mut self, account_id: u64, coverage_bps: Option<u32>) -> RiskScore {
+        self.accounts.entry(account_id).or_default().coverage_bps = coverage_bps;
+        self.rescore(account_id)
+    }
+
+    /// 口座の最新スコア。
+    #[must_use]
+    pub fn score(&self, account_id: u64) -> Option<RiskScore> {
+        self.accounts.get(&account_id).map(|s| s.score)
+    }
+
+    /// 全口座のスコア（総合スコアの高い順、同点は口座 ID 順）。
+    #[must_use]
+    pub fn ranking(&self) -> Vec<RiskScore> {
+        let mut scores: Vec<RiskScore> = self.accounts.values().map(|s| s.score).collect();
+        scores.sort_by_key(|s| core::cmp::Reverse(s.total));
+        scores
+    }
+
+    /// 口座の集計を削除する。
+    pub fn remove(&mut self, account_id: u64) {
+        self.accounts.remove(&account_id);
+    }
+
+    fn rescore(&mut self, account_id: u64) -> RiskScore {
+        let config = self.config;
+        let Some(state) = self.accounts.get_mut(&account_id) else {
+            return RiskScore::default();
+        };
+        let drawdown = match state.equity {
+            Some(equity) if state.peak_equity > 0 && equity < state.peak_equity => {
+                let bps = (i128::from(state.peak_equity) - i128::from(equity)) * 10_000
+                    / i128::from(state.peak_equity);
+                scale(bps, config.max_drawdown_bps)
+            }
+            _ => 0,
+        };
+        let volatility = scale(std_dev(&state.returns), config.max_volatility_bps);
+        let rejects = state.decisions.iter().filter(|&&r| r).count();
+        let reject_rate = if state.decisions.is_empty() {
+            0
+        } else {
+            scale(
+                rejects as i128 * 10_000 / state.decisions.len() as i128,
+                config.max_reject_rate_bps,
+            )
+        };
+        let margin_coverage = state.coverage_bps.map_or(0, |c| {
+            let safe = i128::from(config.safe_coverage_bps);
+            let c = i128::from(c);
+            if c >= safe {
+                0
+            } else if c <= 10_000 || safe <= 10_000 {
+                10_000
+            } else {
+                ((safe - c) * 10_000 / (safe - 10_000)) as u32
+            }
+        });
+        let utilization = state.utilization_bps.min(10_000);
+        let w = config.weights;
+        let components = [
+            (utilization, w.utilization),
+            (drawdown, w.drawdown),
+            (volatility, w.volatility),
+            (reject_rate, w.reject_rate),
+            (margin_coverage, w.margin_coverage),
+        ];
+        let weight_sum: u64 = components.iter().map(|&(_, w)| u64::from(w)).sum();
+        let total = components
+            .iter()
+            .map(|&(v, w)| u64::from(v) * u64::from(w))
+            .sum::<u64>()
+            .checked_div(weight_sum)
+            .map_or(0, |t| t as u32);
+        state.score = RiskScore {
+            account_id,
+            total,
+            utilization,
+            drawdown,
+            volatility,
+            reject_rate,
+            margin_coverage,
+        };
+        state.score
+    }
+}
+
+impl Default for RiskScorer {
+    fn default() -> Self {
+        Self::new(RiskScoreConfig::default())
+    }
+}
+
+/// `value`（bps）を上限 `max_bps` に対する 0〜10000 に正規化する。
+fn scale(value: i128, max_bps: u32) -> u32 {
+    if value <= 0 {
+        return 0;
+    }
+    if max_bps == 0 {
+        return 10_000;
+    }
+    (value.saturating_mul(10_000) / i128::from(max_bps)).min(10_000) as u32
+}
+
+/// 母標準偏差（整数、切り捨て）。2 件未満は 0。
+fn std_dev(values: &VecDeque<i64>) -> i128 {
+    if values.len() < 2 {
+        return 0;
+    }
+    let n = values.len() as i128;
+    let sum: i128 = values.iter().map(|&v| i128::from(v)).sum();
+    let sum_sq: i128 = values
+        .iter()
+        .map(|&v| i128::from(v).saturating_mul(i128::from(v)))
+        .fold(0, i128::saturating_add);
+    let variance = (sum_sq.saturating_mul(n) - sum.saturating_mul(sum)) / (n * n);
+    variance.max(0).unsigned_abs().isqrt() as i128
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::PreTradeChecker;
+    use crate::limit::RiskLimits;
+
+    #[test]
+    fn components_are_normalized() {
+        let mut scorer = RiskScorer::default();
+        scorer.on_equity(1, 10_000);
+        // 10% のドローダウンは上限 20% の半分。
+        let s = scorer.on_equity(1, 9_000);
+        assert_eq!(s.drawdown, 5_000);
+        // リターンは 1 件だけなので変動率は 0。
+        assert_eq!(s.volatility, 0);
+        // 4 件中 1 件拒否 = 25% は上限 20% を超える。
+        for accepted in [true, true, true, false] {
+            scorer.on_decision(1, accepted);
+        }
+        assert_eq!(scorer.score(1).map(|s| s.reject_rate), Some(10_000));
+        // カバレッジ 200% は 100%〜300% の中間。
+        let s = scorer.on_margin_coverage(1, Some(20_000));
+        assert_eq!(s.margin_coverage, 5_000);
+        assert_eq!(s.total, (5_000 + 10_000 + 5_000) / 5);
+    }
+
+    #[test]
+    fn volatility_from_returns() {
+        let mut scorer = RiskScorer::default();
+        // +100, −100, +99 bps: 標準偏差 94 bps（上限 500 の 18.8%）。
+        for equity in [10_000, 10_100, 9_999, 10_098] {
+            scorer.on_equity(2, equity);
+        }
+        assert_eq!(scorer.score(2).map(|s| s.volatility), Some(1_880));
+    }
+
+    #[test]
+    fn ranking_by_total() {
+        let mut scorer = RiskScorer::new(RiskScoreConfig {
+            weights: RiskScoreWeights {
+                utilization: 1,
+                drawdown: 0,
+                volatility: 0,
+                reject_rate: 0,
+                margin_coverage: 0,
+            },
+            ..RiskScoreConfig::default()
+        });
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_daily_loss: -1_000,
+            ..RiskLimits::default()
+        });
+        scorer.on_utilization(1, &checker.utilization());
+        checker.update_daily_pnl(-800);
+        assert_eq!(
+            scorer.on_utilization(2, &checker.utilization()).total,
+            8_000
+        );
+        let ranked: Vec<_> = scorer.ranking().iter().map(|s| s.account_id).collect();
+        assert_eq!(ranked, [2, 1]);
+        scorer.remove(2);
+        assert_eq!(scorer.score(2), None);
+    }
+}
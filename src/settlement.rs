@@ -0,0 +1,243 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 現金口座の受渡（T+n）管理と未決済資金による買付の規制。
+//!
+//! 現金口座は、受渡が済んでいない売却代金で買った銘柄を、その買付代金の
+//! 受渡前に売ると free riding 違反になる。[`SettlementTracker`] は
+//! 通貨ごとに受渡済み現金と受渡待ちの売買代金を持ち、新規の買付を
+//! 「受渡済み現金 + 買付の受渡日までに入金される売却代金 − 支払待ちの買付代金」
+//! に制限する（[`SettlementTracker::check_buy`]）。
+//!
+//! 受渡日は約定時刻に `cycle_days` 日（暦日）を足した時刻とする。営業日
+//! カレンダーは呼び出し側が `cycle_days` と [`SettlementTracker::settle`] の
+//! 呼び出し時刻で調整する。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Side};
+
+use crate::instrument::DAY_NS;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// SettlementReject
+// ---------------------------------------------------------------------------
+
+/// 受渡規制の違反。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementReject {
+    /// 買付代金が受渡済み現金と受渡予定の入金で賄えない（free riding）。
+    InsufficientSettledFunds {
+        currency_hash: u64,
+        required: i64,
+        available: i64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// SettlementTracker
+// ---------------------------------------------------------------------------
+
+/// 受渡待ちの売買代金（入金が正、支払が負）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pending {
+    settle_ns: u64,
+    amount: i64,
+}
+
+/// 1 通貨の残高。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Balance {
+    settled: i64,
+    pending: Vec<Pending>,
+}
+
+/// 通貨ごとの受渡済み・受渡待ち残高。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementTracker {
+    cycle_days: u32,
+    balances: BTreeMap<u64, Balance>,
+}
+
+impl SettlementTracker {
+    /// 受渡サイクル `cycle_days`（T+2 なら 2）で作成。
+    #[must_use]
+    pub const fn new(cycle_days: u32) -> Self {
+        Self {
+            cycle_days,
+            balances: BTreeMap::new(),
+        }
+    }
+
+    /// 受渡サイクル（日）。
+    #[must_use]
+    pub const fn cycle_days(&self) -> u32 {
+        self.cycle_days
+    }
+
+    /// `trade_ns` に約定した売買の受渡時刻。
+    #[must_use]
+    pub const fn settlement_ns(&self, trade_ns: u64) -> u64 {
+        trade_ns.saturating_add((self.cycle_days as u64).saturating_mul(DAY_NS))
+    }
+
+    /// 受渡済み現金を入金する（負なら出金）。
+    pub fn deposit(&mut self, currency_hash: u64, amount: i64) {
+        let balance = self.balances.entry(currency_hash).or_default();
+        balance.settled = balance.settled.saturating_add(amount);
+    }
+
+    /// 約定を記録する。買いは代金の支払、売りは代金の入金を受渡日まで
+    /// 受渡待ちにする。
+    pub fn on_trade(&mut self, currency_hash: u64, side: Side, notional: i64, trade_ns: u64) {
+        let amount = match side {
+            Side::Bid => notional.saturating_abs().saturating_neg(),
+            Side::Ask => notional.saturating_abs(),
+        };
+        let settle_ns = self.settlement_ns(trade_ns);
+        self.balances
+            .entry(currency_hash)
+            .or_default()
+            .pending
+            .push(Pending { settle_ns, amount });
+    }
+
+    /// `now_ns` までに受渡日を迎えた代金を受渡済み現金に振り替える。
+    pub fn settle(&mut self, now_ns: u64) {
+        for balance in self.balances.values_mut() {
+            let mut settled = balance.settled;
+            balance.pending.retain(|p| {
+                let due = p.settle_ns <= now_ns;
+                if due {
+                    settled = settled.saturating_add(p.amount);
+                }
+                !due
+            });
+            balance.settled = settled;
+        }
+    }
+
+    /// 受渡済み現金。
+    #[must_use]
+    pub fn settled(&self, currency_hash: u64) -> i64 {
+        self.balances.get(&currency_hash).map_or(0, |b| b.settled)
+    }
+
+    /// 受渡待ちの代金（入金が正、支払が負の合計）。
+    #[must_use]
+    pub fn unsettled(&self, currency_hash: u64) -> i64 {
+        self.balances.get(&currency_hash).map_or(0, |b| {
+            b.pending
+                .iter()
+                .fold(0_i64, |acc, p| acc.saturating_add(p.amount))
+        })
+    }
+
+    /// `trade_ns` に約定する買付に使える資金。
+    ///
+    /// 受渡済み現金に、買付の受渡日までに入金される売却代金を足し、
+    /// 支払待ちの買付代金をすべて引く。
+    #[must_use]
+    pub fn buying_power(&self, currency_hash: u64, trade_ns: u64) -> i64 {
+        let Some(balance) = self.balances.get(&currency_hash) else {
+            return 0;
+        };
+        let settle_ns = self.settlement_ns(trade_ns);
+        balance
+            .pending
+            .iter()
+            .filter(|p| p.amount < 0 || p.settle_ns <= settle_ns)
+            .fold(balance.settled, |acc, p| acc.saturating_add(p.amount))
+    }
+
+    /// 買い注文 `order` の代金（`|price| * quantity`）を、約定時刻
+    /// `order.timestamp_ns` の [`Self::buying_power`] で賄えるか。売り注文は
+    /// 常に通す。
+    ///
+    /// # Errors
+    ///
+    /// 賄えない場合は [`SettlementReject::InsufficientSettledFunds`]。
+    pub fn check_buy(&self, currency_hash: u64, order: &Order) -> Result<(), SettlementReject> {
+        if order.side != Side::Bid {
+            return Ok(());
+        }
+        let required = (u128::from(order.price.unsigned_abs()) * u128::from(order.quantity))
+            .min(i64::MAX as u128) as i64;
+        let available = self.buying_power(currency_hash, order.timestamp_ns);
+        if required > available {
+            return Err(SettlementReject::InsufficientSettledFunds {
+                currency_hash,
+                required,
+                available,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for SettlementTracker {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn buy(price: i64, quantity: u64, timestamp_ns: u64) -> Order {
+        OrderBuilder::bid(price, quantity).at(timestamp_ns).build()
+    }
+
+    #[test]
+    fn buys_limited_to_settled_and_incoming_funds() {
+        let mut tracker = SettlementTracker::default();
+        tracker.deposit(1, 10_000);
+        assert!(tracker.check_buy(1, &buy(100, 100, 0)).is_ok());
+        tracker.on_trade(1, Side::Bid, 10_000, 0);
+        // 支払待ちの買付で受渡済み現金は使い切っている。
+        assert_eq!(
+            tracker.check_buy(1, &buy(100, 1, 0)),
+            Err(SettlementReject::InsufficientSettledFunds {
+                currency_hash: 1,
+                required: 100,
+                available: 0,
+            })
+        );
+
+        // 1 日目の売却代金は 3 日目に入金されるため、同日の買付（同じく
+        // 3 日目受渡）に使える。
+        tracker.on_trade(1, Side::Ask, 5_000, DAY_NS);
+        assert_eq!(tracker.buying_power(1, DAY_NS), 5_000);
+        assert_eq!(tracker.buying_power(1, 0), 0);
+        assert_eq!(tracker.unsettled(1), -5_000);
+        assert!(tracker.check_buy(1, &buy(100, 50, DAY_NS)).is_ok());
+        assert!(tracker.check_buy(1, &buy(100, 50, 0)).is_err());
+    }
+
+    #[test]
+    fn settle_moves_due_amounts() {
+        let mut tracker = SettlementTracker::new(2);
+        tracker.deposit(1, 1_000);
+        tracker.on_trade(1, Side::Bid, 600, 0);
+        tracker.on_trade(1, Side::Ask, 300, DAY_NS);
+        tracker.settle(2 * DAY_NS);
+        assert_eq!(tracker.settled(1), 400);
+        assert_eq!(tracker.unsettled(1), 300);
+        tracker.settle(3 * DAY_NS);
+        assert_eq!(tracker.settled(1), 700);
+        assert_eq!(tracker.unsettled(1), 0);
+        // 通貨は独立。
+        assert_eq!(tracker.buying_power(2, 0), 0);
+    }
+}
@@ -0,0 +1,200 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 候補の制限値のシャドー評価（A/B）。
+//!
+//! 制限値の変更を本番に切り替える前に、[`ShadowLimits`] に候補の
+//! [`RiskLimits`] を登録し、本番の判定と並べて同じ注文を評価する。候補の判定は
+//! 本番のチェッカーのカウンタ・未約定注文を使い
+//! （[`PreTradeChecker::check_order_with_limits`]）、結果には影響しない。
+//! 本番で通って候補で拒否された注文と、その逆を [`ShadowReport`] に集め、
+//! 問題が無ければ [`ShadowLimits::promote`] で候補を本番に適用する。
+
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// ShadowReport
+// ---------------------------------------------------------------------------
+
+/// 本番と候補の判定の食い違い。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowDivergence {
+    /// 注文 ID。
+    pub order_id: u64,
+    /// 注文の時刻（ns）。
+    pub timestamp_ns: u64,
+    /// 本番の判定。
+    pub live: Result<(), RiskReject>,
+    /// 候補の判定。
+    pub candidate: Result<(), RiskReject>,
+}
+
+impl ShadowDivergence {
+    /// 本番で通り、候補で拒否された（候補の方が厳しい）か。
+    #[must_use]
+    pub const fn candidate_rejected(&self) -> bool {
+        self.live.is_ok() && self.candidate.is_err()
+    }
+}
+
+/// シャドー評価の集計。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShadowReport {
+    /// 評価した注文数。
+    pub evaluated: u64,
+    /// 通否が食い違った注文（評価順）。拒否理由だけが違う注文は含めない。
+    pub divergences: Vec<ShadowDivergence>,
+}
+
+impl ShadowReport {
+    /// 本番で通り、候補で拒否された注文。
+    pub fn candidate_rejected(&self) -> impl Iterator<Item = &ShadowDivergence> {
+        self.divergences.iter().filter(|d| d.candidate_rejected())
+    }
+
+    /// 本番で拒否され、候補で通った注文。
+    pub fn candidate_accepted(&self) -> impl Iterator<Item = &ShadowDivergence> {
+        self.divergences.iter().filter(|d| !d.candidate_rejected())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ShadowLimits
+// ---------------------------------------------------------------------------
+
+/// シャドー評価する候補の制限値。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowLimits {
+    candidate: RiskLimits,
+    report: ShadowReport,
+}
+
+impl ShadowLimits {
+    /// 候補の制限値を登録。
+    #[must_use]
+    pub fn new(candidate: RiskLimits) -> Self {
+        Self {
+            candidate,
+            report: ShadowReport::default(),
+        }
+    }
+
+    /// 候補の制限値。
+    #[must_use]
+    pub const fn candidate(&self) -> &RiskLimits {
+        &self.candidate
+    }
+
+    /// 本番の `live` で判定し、同じ注文を候補でも評価して食い違いを記録する。
+    ///
+    /// # Errors
+    ///
+    /// 本番の判定（[`PreTradeChecker::check_order`]）の拒否。候補の判定は
+    /// 戻り値に影響しない。
+    pub fn check_order(
+        &mut self,
+        live: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let live_result = live.check_order(order, position);
+        let candidate = live.check_order_with_limits(&self.candidate, order, position);
+        self.report.evaluated += 1;
+        if live_result.is_ok() != candidate.is_ok() {
+            self.report.divergences.push(ShadowDivergence {
+                order_id: order.id.0,
+                timestamp_ns: order.timestamp_ns,
+                live: live_result.clone(),
+                candidate,
+            });
+        }
+        live_result
+    }
+
+    /// これまでの集計。
+    #[must_use]
+    pub const fn report(&self) -> &ShadowReport {
+        &self.report
+    }
+
+    /// 集計を取り出し、空に戻す。
+    pub fn take_report(&mut self) -> ShadowReport {
+        core::mem::take(&mut self.report)
+    }
+
+    /// 候補を本番に適用する（[`PreTradeChecker::apply_limits`]）。適用した
+    /// バージョンを返す。
+    pub fn promote(self, live: &mut PreTradeChecker, author: &str, timestamp_ns: u64) -> u64 {
+        live.apply_limits(self.candidate, author, timestamp_ns)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(id: u64, quantity: u64) -> Order {
+        OrderBuilder::bid(100, quantity).id(id).at(id).build()
+    }
+
+    #[test]
+    fn reports_divergences_both_ways() {
+        let live = PreTradeChecker::new(RiskLimits {
+            max_order_size: 50,
+            ..RiskLimits::default()
+        });
+        let mut shadow = ShadowLimits::new(RiskLimits {
+            max_order_size: 20,
+            max_position: 60,
+            ..RiskLimits::default()
+        });
+        // 1: 両方通る、2: 候補だけ拒否、3: 両方拒否（食い違いではない）。
+        assert!(shadow.check_order(&live, &order(1, 10), None).is_ok());
+        assert!(shadow.check_order(&live, &order(2, 30), None).is_ok());
+        assert!(shadow.check_order(&live, &order(3, 70), None).is_err());
+        let report = shadow.report();
+        assert_eq!(report.evaluated, 3);
+        assert_eq!(report.divergences.len(), 1);
+        let d = &report.divergences[0];
+        assert_eq!((d.order_id, d.timestamp_ns), (2, 2));
+        assert!(matches!(
+            d.candidate,
+            Err(RiskReject::OrderSizeTooLarge { limit: 20, .. })
+        ));
+        assert_eq!(report.candidate_rejected().count(), 1);
+        assert_eq!(report.candidate_accepted().count(), 0);
+    }
+
+    #[test]
+    fn candidate_accepts_and_promotes() {
+        let mut live = PreTradeChecker::new(RiskLimits {
+            max_order_size: 10,
+            ..RiskLimits::default()
+        });
+        let mut shadow = ShadowLimits::new(RiskLimits {
+            max_order_size: 40,
+            ..RiskLimits::default()
+        });
+        assert!(shadow.check_order(&live, &order(1, 30), None).is_err());
+        let report = shadow.take_report();
+        assert_eq!(report.candidate_accepted().count(), 1);
+        assert_eq!(shadow.report(), &ShadowReport::default());
+        let version = shadow.promote(&mut live, "risk", 100);
+        assert_eq!(version, 1);
+        assert!(live.check_order(&order(2, 30), None).is_ok());
+    }
+}
@@ -0,0 +1,452 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! シミュレーション / ファズ用シナリオドライバー（`sim` フィーチャー）。
+//!
+//! シード固定の擬似乱数で注文・約定・取消・価格変動の列を生成し、
+//! [`PreTradeChecker`] + [`CircuitBreaker`] + [`MarginCalculator`] の構成に
+//! 流し込みながら、クレート全体の不変条件を検査する。
+//!
+//! - 受理された注文は、その時点のすべての制限を満たす
+//! - 約定済みポジションの絶対値は `max_position` を超えない
+//! - ブレーカー発動中に受理される注文はない
+//! - 維持証拠金は当初証拠金を超えない
+//!
+//! 同じ [`ScenarioConfig`] からは常に同じ列が生成されるため、下流の
+//! 結合テストでも失敗を再現できる。
+
+use alice_ledger::{Order, OrderId, OrderType, Position, Side, TimeInForce};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::circuit::CircuitBreaker;
+use crate::config::BreakerConfig;
+use crate::limit::RiskLimits;
+use crate::margin::{MarginCalculator, MarginParams};
+use crate::test_utils::accepted_order_violation;
+
+// ---------------------------------------------------------------------------
+// SimRng
+// ---------------------------------------------------------------------------
+
+/// 再現可能な擬似乱数生成器（xorshift64*）。
+#[derive(Debug, Clone)]
+pub struct SimRng(u64);
+
+impl SimRng {
+    /// シードから作成。シード 0 は内部で非ゼロに置き換える。
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    /// 次の 64 bit 値。
+    pub const fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// `0..bound` の一様乱数（`bound == 0` なら 0）。
+    pub const fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        self.next_u64() % bound
+    }
+
+    /// `lo..=hi` の一様乱数。
+    pub const fn range_i64(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = hi.abs_diff(lo).saturating_add(1);
+        lo.wrapping_add(self.below(span) as i64)
+    }
+
+    /// 確率 `pct` % で `true`。
+    pub const fn chance(&mut self, pct: u64) -> bool {
+        self.below(100) < pct
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ScenarioConfig
+// ---------------------------------------------------------------------------
+
+/// シナリオ生成パラメータ。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioConfig {
+    /// 乱数シード。
+    pub seed: u64,
+    /// 生成するステップ数。
+    pub steps: usize,
+    /// チェッカーの制限値。
+    pub limits: RiskLimits,
+    /// 証拠金率。
+    pub margin: MarginParams,
+    /// ブレーカー設定。
+    pub breaker: BreakerConfig,
+    /// 初期価格（ticks）。
+    pub start_price: i64,
+    /// 1 ステップあたりの最大価格変動（ticks）。
+    pub max_tick_move: i64,
+    /// 生成する注文数量の上限（制限超過を意図的に含めるため制限値より大きく取る）。
+    pub max_order_qty: u64,
+    /// 1 ステップあたりの時間経過（ns）。
+    pub step_ns: u64,
+}
+
+impl Default for ScenarioConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            steps: 10_000,
+            limits: RiskLimits::default(),
+            margin: MarginParams::default(),
+            breaker: BreakerConfig::default(),
+            start_price: 10_000,
+            max_tick_move: 50,
+            max_order_qty: 150,
+            step_ns: 10_000_000,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SimReport / InvariantViolation
+// ---------------------------------------------------------------------------
+
+/// シナリオ実行の集計。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimReport {
+    /// 生成した注文数。
+    pub orders: u64,
+    /// 受理数。
+    pub accepted: u64,
+    /// 拒否数。
+    pub rejected: u64,
+    /// 即時約定数。
+    pub fills: u64,
+    /// 取消数。
+    pub cancels: u64,
+    /// ブレーカー発動回数。
+    pub breaker_trips: u64,
+    /// 日次損失上限による拒否数。
+    pub daily_loss_rejects: u64,
+    /// 終了時のネットポジション。
+    pub final_position: i64,
+}
+
+/// 不変条件違反。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// 違反が検出されたステップ。
+    pub step: usize,
+    /// 違反した不変条件。
+    pub invariant: &'static str,
+    /// 関係する注文（あれば）の ID。
+    pub order_id: Option<u64>,
+}
+
+// ---------------------------------------------------------------------------
+// ScenarioDriver
+// ---------------------------------------------------------------------------
+
+/// 行動の種類。
+enum Action {
+    Submit,
+    Cancel,
+    DailyReset,
+}
+
+/// チェッカー・ブレーカー・証拠金計算器を束ねたシナリオドライバー。
+pub struct ScenarioDriver {
+    config: ScenarioConfig,
+    rng: SimRng,
+    checker: PreTradeChecker,
+    breaker: CircuitBreaker,
+    margin: MarginCalculator,
+    price: i64,
+    position: i64,
+    now_ns: u64,
+    next_id: u64,
+    report: SimReport,
+}
+
+impl ScenarioDriver {
+    /// 設定からドライバーを構築する。
+    #[must_use]
+    pub fn new(config: ScenarioConfig) -> Self {
+        let mut breaker = config.breaker.build();
+        breaker.reset(config.start_price, 0);
+        Self {
+            rng: SimRng::new(config.seed),
+            checker: PreTradeChecker::new(config.limits.clone()),
+            margin: MarginCalculator::new(config.margin.clone()),
+            price: config.start_price,
+            position: 0,
+            now_ns: 0,
+            next_id: 1,
+            report: SimReport::default(),
+            breaker,
+            config,
+        }
+    }
+
+    /// 全ステップを実行する。
+    ///
+    /// # Errors
+    ///
+    /// 不変条件違反を検出した時点で [`InvariantViolation`] を返す。
+    pub fn run(mut self) -> Result<SimReport, InvariantViolation> {
+        for step in 0..self.config.steps {
+            self.step(step)?;
+        }
+        self.report.final_position = self.position;
+        Ok(self.report)
+    }
+
+    /// 1 ステップ進める。板に載せた模擬注文は注文 ID で管理し、取消は最も
+    /// 古い注文から行う。
+    fn step(&mut self, step: usize) -> Result<(), InvariantViolation> {
+        self.now_ns = self.now_ns.saturating_add(self.config.step_ns);
+        self.move_price();
+
+        let action = match self.rng.below(100) {
+            0..=79 => Action::Submit,
+            80..=98 => Action::Cancel,
+            _ => Action::DailyReset,
+        };
+        match action {
+            Action::Submit => self.submit(step)?,
+            Action::Cancel => {
+                let oldest = self.checker.open_orders().iter().next().map(|o| o.order_id);
+                if let Some(order_id) = oldest {
+                    self.checker.on_order_closed(order_id);
+                    self.report.cancels += 1;
+                }
+            }
+            Action::DailyReset => {
+                self.checker.reset_daily();
+                if self.checker.is_circuit_breaker_tripped() {
+                    self.checker.reset_circuit_breaker();
+                    self.breaker.reset(self.price, self.now_ns);
+                }
+            }
+        }
+
+        if self.position.unsigned_abs() > self.checker.limits().max_position {
+            return Err(violation(
+                step,
+                "filled position exceeds max_position",
+                None,
+            ));
+        }
+        let qty = self.position.unsigned_abs();
+        if self.margin.maintenance_margin(self.price, qty)
+            > self.margin.initial_margin(self.price, qty)
+        {
+            return Err(violation(
+                step,
+                "maintenance margin exceeds initial margin",
+                None,
+            ));
+        }
+        Ok(())
+    }
+
+    /// 価格をランダムウォークさせ、ポジションの評価損益を計上する。
+    fn move_price(&mut self) {
+        let delta = self
+            .rng
+            .range_i64(-self.config.max_tick_move, self.config.max_tick_move);
+        self.price = self.price.saturating_add(delta).max(1);
+        let pnl = (self.position as i128 * delta as i128).clamp(i64::MIN as i128, i64::MAX as i128);
+        self.checker.update_daily_pnl(pnl as i64);
+    }
+
+    /// 注文を生成してチェックし、受理なら即時約定または板に載せる。
+    fn submit(&mut self, step: usize) -> Result<(), InvariantViolation> {
+        let side = if self.rng.chance(50) {
+            Side::Bid
+        } else {
+            Side::Ask
+        };
+        let offset = self.rng.range_i64(-5, 5);
+        let order = Order {
+            id: OrderId(self.next_id),
+            side,
+            order_type: OrderType::Limit,
+            price: self.price.saturating_add(offset).max(1),
+            quantity: self.rng.below(self.config.max_order_qty) + 1,
+            filled_quantity: 0,
+            timestamp_ns: self.now_ns,
+            time_in_force: TimeInForce::GTC,
+        };
+        self.next_id += 1;
+        self.report.orders += 1;
+
+        let position = Position {
+            symbol_hash: 0,
+            net_quantity: self.position,
+            avg_entry_price: self.price,
+            realized_pnl: 0,
+            unrealized_pnl: 0,
+            trade_count: 0,
+        };
+        match self.checker.check_order(&order, Some(&position)) {
+            Ok(()) => {
+                self.report.accepted += 1;
+                self.verify_accepted(step, &order)?;
+                if self.rng.chance(60) {
+                    self.fill(&order);
+                } else {
+                    self.checker.on_order_accepted(&order);
+                }
+            }
+            Err(reject) => {
+                self.report.rejected += 1;
+                if matches!(reject, RiskReject::DailyLossLimitHit { .. }) {
+                    self.report.daily_loss_rejects += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 受理された注文が全制限を満たすことを独立に検算する。
+    fn verify_accepted(&self, step: usize, order: &Order) -> Result<(), InvariantViolation> {
+        match accepted_order_violation(&self.checker, order, self.position) {
+            Some(invariant) => Err(violation(step, invariant, Some(order.id.0))),
+            None => Ok(()),
+        }
+    }
+
+    /// 注文を全量即時約定させる。
+    fn fill(&mut self, order: &Order) {
+        let qty = order.quantity as i64;
+        self.position = match order.side {
+            Side::Bid => self.position.saturating_add(qty),
+            Side::Ask => self.position.saturating_sub(qty),
+        };
+        self.report.fills += 1;
+        if self.breaker.on_fill(order.price, self.now_ns)
+            && !self.checker.is_circuit_breaker_tripped()
+        {
+            self.checker.trip_circuit_breaker();
+            self.report.breaker_trips += 1;
+        }
+    }
+}
+
+/// 不変条件違反を組み立てる。
+const fn violation(
+    step: usize,
+    invariant: &'static str,
+    order_id: Option<u64>,
+) -> InvariantViolation {
+    InvariantViolation {
+        step,
+        invariant,
+        order_id,
+    }
+}
+
+/// 設定からシナリオを 1 回実行する。
+///
+/// # Errors
+///
+/// 不変条件違反を検出した場合に [`InvariantViolation`] を返す。
+pub fn run_scenario(config: ScenarioConfig) -> Result<SimReport, InvariantViolation> {
+    ScenarioDriver::new(config).run()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_reproducible() {
+        let mut a = SimRng::new(42);
+        let mut b = SimRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(SimRng::new(0).next_u64(), 0);
+    }
+
+    #[test]
+    fn rng_ranges() {
+        let mut rng = SimRng::new(7);
+        for _ in 0..1000 {
+            let v = rng.range_i64(-3, 3);
+            assert!((-3..=3).contains(&v));
+            assert!(rng.below(5) < 5);
+        }
+        assert_eq!(rng.below(0), 0);
+    }
+
+    #[test]
+    fn default_scenario_holds_invariants() {
+        let report = run_scenario(ScenarioConfig::default()).unwrap();
+        assert_eq!(report.orders, report.accepted + report.rejected);
+        assert!(report.accepted > 0);
+        assert!(report.rejected > 0);
+    }
+
+    #[test]
+    fn same_seed_same_report() {
+        let cfg = ScenarioConfig {
+            steps: 2_000,
+            ..ScenarioConfig::default()
+        };
+        assert_eq!(run_scenario(cfg.clone()), run_scenario(cfg));
+    }
+
+    #[test]
+    fn many_seeds_tight_limits() {
+        for seed in 1..=20 {
+            let cfg = ScenarioConfig {
+                seed,
+                steps: 1_000,
+                limits: RiskLimits {
+                    max_position: 120,
+                    max_order_size: 40,
+                    max_notional: 300_000,
+                    max_open_orders: 5,
+                    max_daily_loss: -20_000,
+                    ..RiskLimits::default()
+                },
+                ..ScenarioConfig::default()
+            };
+            if let Err(v) = run_scenario(cfg) {
+                panic!("seed {seed}: {v:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn volatile_market_trips_breaker() {
+        let cfg = ScenarioConfig {
+            max_tick_move: 400,
+            breaker: BreakerConfig {
+                max_move: 200,
+                max_fills_per_window: 3,
+                window_ns: 1_000_000_000,
+            },
+            ..ScenarioConfig::default()
+        };
+        let report = run_scenario(cfg).unwrap();
+        assert!(report.breaker_trips > 0);
+    }
+}
@@ -0,0 +1,219 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 約定のスリッページと戦略ごとのスリッページ予算。
+//!
+//! [`SlippageTracker`] は約定価格を注文到着時の参照価格（arrival price）と
+//! 比べたスリッページを戦略 ID（[`DecisionTags::algo_id`]）ごとに日次で
+//! 集計する。予算を設定した戦略のスリッページが予算を超えると、執行品質が
+//! 崩れたとみなして戦略を [`QuarantineMode::ReduceOnly`] で隔離する。隔離は
+//! [`StrategyQuarantine::release`] で解除するまで続く。
+//!
+//! スリッページは口座に不利な方向を正とする（買いは到着価格より高く、売りは
+//! 安く約定した分）。
+//!
+//! [`DecisionTags::algo_id`]: crate::check::DecisionTags::algo_id
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::Side;
+
+use crate::quarantine::{QuarantineMode, StrategyQuarantine};
+
+extern crate alloc;
+
+/// スリッページ予算の超過による隔離の理由。
+pub const SLIPPAGE_QUARANTINE_REASON: &str = "slippage";
+
+/// `quantity` ロットの `side` の約定の到着価格からのスリッページ（ticks ×
+/// lots、不利な方向が正）。
+#[must_use]
+pub fn slippage(side: Side, arrival_price: i64, fill_price: i64, quantity: u64) -> i64 {
+    let per_lot = match side {
+        Side::Bid => i128::from(fill_price) - i128::from(arrival_price),
+        Side::Ask => i128::from(arrival_price) - i128::from(fill_price),
+    };
+    per_lot
+        .saturating_mul(i128::from(quantity))
+        .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
+// ---------------------------------------------------------------------------
+// SlippageStats
+// ---------------------------------------------------------------------------
+
+/// 1 戦略の日次のスリッページ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SlippageStats {
+    /// 約定の件数。
+    pub fills: u32,
+    /// 約定数量の合計。
+    pub quantity: u64,
+    /// スリッページの合計（ticks × lots）。
+    pub slippage: i64,
+    /// 到着価格で評価した約定代金の合計。
+    pub arrival_notional: i64,
+}
+
+impl SlippageStats {
+    /// 約定代金に対するスリッページ（bps、不利な方向が正）。約定が無ければ 0。
+    #[must_use]
+    pub fn slippage_bps(&self) -> i64 {
+        if self.arrival_notional == 0 {
+            return 0;
+        }
+        (i128::from(self.slippage) * 10_000 / i128::from(self.arrival_notional))
+            .clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SlippageTracker
+// ---------------------------------------------------------------------------
+
+/// 戦略ごとの日次のスリッページと予算。
+#[derive(Debug, Clone, Default)]
+pub struct SlippageTracker {
+    /// 戦略 ID → その日のスリッページ。
+    stats: BTreeMap<u64, SlippageStats>,
+    /// 戦略 ID → 日次のスリッページ予算（ticks × lots）。
+    budgets: BTreeMap<u64, i64>,
+}
+
+impl SlippageTracker {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            stats: BTreeMap::new(),
+            budgets: BTreeMap::new(),
+        }
+    }
+
+    /// 戦略の日次のスリッページ予算を設定する。`None` で解除。
+    pub fn set_budget(&mut self, algo_id: u64, budget: Option<i64>) {
+        match budget {
+            Some(budget) => self.budgets.insert(algo_id, budget),
+            None => self.budgets.remove(&algo_id),
+        };
+    }
+
+    /// 戦略の日次のスリッページ予算。
+    #[must_use]
+    pub fn budget(&self, algo_id: u64) -> Option<i64> {
+        self.budgets.get(&algo_id).copied()
+    }
+
+    /// 約定を記録し、その約定のスリッページを返す。
+    ///
+    /// 戦略のその日のスリッページが予算を超え、戦略がまだ隔離されていなければ
+    /// `quarantine` で [`QuarantineMode::ReduceOnly`] に隔離する。
+    #[allow(clippy::too_many_arguments)]
+    pub fn on_fill(
+        &mut self,
+        algo_id: u64,
+        side: Side,
+        arrival_price: i64,
+        fill_price: i64,
+        quantity: u64,
+        now_ns: u64,
+        quarantine: &mut StrategyQuarantine,
+    ) -> i64 {
+        let slipped = slippage(side, arrival_price, fill_price, quantity);
+        let stats = self.stats.entry(algo_id).or_default();
+        stats.fills = stats.fills.saturating_add(1);
+        stats.quantity = stats.quantity.saturating_add(quantity);
+        stats.slippage = stats.slippage.saturating_add(slipped);
+        let notional = i128::from(arrival_price.unsigned_abs()) * i128::from(quantity);
+        stats.arrival_notional = stats
+            .arrival_notional
+            .saturating_add(notional.min(i128::from(i64::MAX)) as i64);
+        if self.is_over_budget(algo_id) && quarantine.get(algo_id).is_none() {
+            quarantine.quarantine(
+                algo_id,
+                QuarantineMode::ReduceOnly,
+                SLIPPAGE_QUARANTINE_REASON,
+                now_ns,
+            );
+        }
+        slipped
+    }
+
+    /// 戦略のその日のスリッページ。
+    #[must_use]
+    pub fn stats(&self, algo_id: u64) -> SlippageStats {
+        self.stats.get(&algo_id).copied().unwrap_or_default()
+    }
+
+    /// 全戦略のその日のスリッページ（戦略 ID の昇順）。
+    pub fn all_stats(&self) -> impl Iterator<Item = (u64, SlippageStats)> + '_ {
+        self.stats.iter().map(|(&id, &s)| (id, s))
+    }
+
+    /// 戦略のその日のスリッページが予算を超えているか。
+    #[must_use]
+    pub fn is_over_budget(&self, algo_id: u64) -> bool {
+        self.budget(algo_id)
+            .is_some_and(|budget| self.stats(algo_id).slippage > budget)
+    }
+
+    /// 日次の集計を消す。予算と隔離は残す。
+    pub fn reset_daily(&mut self) {
+        self.stats.clear();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slippage_is_adverse_positive() {
+        assert_eq!(slippage(Side::Bid, 100, 102, 10), 20);
+        assert_eq!(slippage(Side::Bid, 100, 99, 10), -10);
+        assert_eq!(slippage(Side::Ask, 100, 97, 5), 15);
+        assert_eq!(slippage(Side::Ask, 100, 101, 5), -5);
+    }
+
+    #[test]
+    fn budget_flips_strategy_to_reduce_only() {
+        let mut tracker = SlippageTracker::new();
+        let mut quarantine = StrategyQuarantine::new();
+        tracker.set_budget(7, Some(100));
+        assert_eq!(
+            tracker.on_fill(7, Side::Bid, 1_000, 1_005, 10, 1, &mut quarantine),
+            50
+        );
+        tracker.on_fill(7, Side::Ask, 1_000, 1_002, 10, 2, &mut quarantine);
+        tracker.on_fill(9, Side::Bid, 1_000, 1_100, 10, 3, &mut quarantine);
+        assert_eq!(
+            tracker.stats(7),
+            SlippageStats {
+                fills: 2,
+                quantity: 20,
+                slippage: 30,
+                arrival_notional: 20_000,
+            }
+        );
+        assert_eq!(tracker.stats(7).slippage_bps(), 15);
+        assert!(quarantine.get(7).is_none());
+        // 予算の無い戦略は隔離しない。
+        assert!(quarantine.get(9).is_none());
+
+        tracker.on_fill(7, Side::Bid, 1_000, 1_008, 10, 4, &mut quarantine);
+        assert!(tracker.is_over_budget(7));
+        let q = quarantine.get(7).unwrap();
+        assert_eq!(q.mode, QuarantineMode::ReduceOnly);
+        assert_eq!((q.reason, q.since_ns), (SLIPPAGE_QUARANTINE_REASON, 4));
+
+        tracker.reset_daily();
+        assert_eq!(tracker.stats(7), SlippageStats::default());
+        assert!(quarantine.get(7).is_some());
+    }
+}
@@ -0,0 +1,644 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 日中のリスク状態のスナップショットと、その封印（圧縮・認証付き暗号化）。
+//!
+//! [`CheckerState`] は [`PreTradeChecker`](crate::PreTradeChecker) の日中の
+//! カウンタと注文 ID 付きの未約定注文をバイト列にする。未約定注文を含めるため、
+//! 再起動後も件数・銘柄別件数・想定元本の上限が引き継がれる。制限値とその版・
+//! 変更履歴は設定から復元する前提で含めない。
+//!
+//! [`SnapshotCodec`] は任意のスナップショットのバイト列を共有ストレージに
+//! 置ける形に封印する。圧縮（LZ 系）と、呼び出し側が渡す鍵による認証付き
+//! 暗号化（`ChaCha20` + HMAC-SHA256、encrypt-then-MAC）はそれぞれ任意で、
+//! 封印後のヘッダに使った方式が記録される。
+//!
+//! 形式: `b"ARSK"`、版（1 バイト）、フラグ（1 バイト）、元の長さ（u32 LE）、
+//! [暗号化時: ノンス 12 バイト]、本体、[暗号化時: タグ 16 バイト]。
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use alice_ledger::Side;
+
+use crate::check::LossBasis;
+use crate::crypto::{chacha20_xor, ct_eq, HmacKey};
+use crate::orders::WorkingOrder;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// CheckerState
+// ---------------------------------------------------------------------------
+
+/// [`PreTradeChecker`](crate::PreTradeChecker) の日中のカウンタと、注文 ID
+/// 付きの未約定注文。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CheckerState {
+    /// 日次損益（実現）。
+    pub daily_pnl: i64,
+    /// 日次損益のうち評価損益。
+    pub unrealized_pnl: i64,
+    /// 日次損失上限を適用する損益の範囲。
+    pub loss_basis: LossBasis,
+    /// 注文 ID 無しで数えた未約定注文数。
+    pub open_order_count: u32,
+    /// 現在の時間幅で受理した非常駐注文数。
+    pub non_resting_in_window: u32,
+    /// 非常駐注文の時間幅の開始時刻（ns）。
+    pub non_resting_window_start_ns: u64,
+    /// 持続上限の時間幅で受理した非常駐注文数。
+    pub non_resting_sustained_in_window: u32,
+    /// 非常駐注文の持続上限の時間幅の開始時刻（ns）。
+    pub non_resting_sustained_start_ns: u64,
+    /// サーキットブレーカーが作動中か。
+    pub circuit_breaker_tripped: bool,
+    /// 注文 ID 付きの未約定注文（注文 ID 順）。
+    pub working: Vec<WorkingOrder>,
+}
+
+impl CheckerState {
+    /// 未約定注文を除いた固定部の長さ（バイト）。
+    pub const LEN: usize = 50;
+
+    /// 未約定注文 1 件の長さ（バイト）。
+    pub const WORKING_ORDER_LEN: usize = 41;
+
+    /// リトルエンディアンのバイト列。固定部（末尾に未約定注文の件数）の後に
+    /// 未約定注文が続く。
+    ///
+    /// # Panics
+    ///
+    /// 未約定注文が `u32::MAX` 件を超える場合。
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = u32::try_from(self.working.len()).expect("too many working orders");
+        let mut out = Vec::with_capacity(Self::LEN + self.working.len() * Self::WORKING_ORDER_LEN);
+        out.extend_from_slice(&self.daily_pnl.to_le_bytes());
+        out.extend_from_slice(&self.open_order_count.to_le_bytes());
+        out.extend_from_slice(&self.non_resting_in_window.to_le_bytes());
+        out.extend_from_slice(&self.non_resting_window_start_ns.to_le_bytes());
+        out.push(u8::from(self.circuit_breaker_tripped));
+        out.extend_from_slice(&self.non_resting_sustained_in_window.to_le_bytes());
+        out.extend_from_slice(&self.non_resting_sustained_start_ns.to_le_bytes());
+        out.extend_from_slice(&self.unrealized_pnl.to_le_bytes());
+        out.push(match self.loss_basis {
+            LossBasis::Combined => 0,
+            LossBasis::Realized => 1,
+            LossBasis::Unrealized => 2,
+        });
+        out.extend_from_slice(&count.to_le_bytes());
+        for order in &self.working {
+            let mut flags = 0;
+            if order.good_till_cancel {
+                flags |= WORKING_GTC;
+            }
+            if matches!(order.side, Side::Ask) {
+                flags |= WORKING_ASK;
+            }
+            if order.symbol_hash.is_some() {
+                flags |= WORKING_SYMBOL;
+            }
+            out.extend_from_slice(&order.order_id.to_le_bytes());
+            out.push(flags);
+            out.extend_from_slice(&order.remaining.to_le_bytes());
+            out.extend_from_slice(&order.notional.to_le_bytes());
+            out.extend_from_slice(&order.symbol_hash.unwrap_or(0).to_le_bytes());
+            out.extend_from_slice(&order.placed_ns.to_le_bytes());
+        }
+        out
+    }
+
+    /// [`Self::to_bytes`] の逆変換。長さが件数と合わない場合や、損益の範囲が
+    /// 不明な場合は `None`。
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let fixed = bytes.get(..Self::LEN)?;
+        let u64_at = |b: &[u8], i: usize| {
+            let mut v = [0_u8; 8];
+            v.copy_from_slice(&b[i..i + 8]);
+            u64::from_le_bytes(v)
+        };
+        let u32_at = |b: &[u8], i: usize| {
+            let mut v = [0_u8; 4];
+            v.copy_from_slice(&b[i..i + 4]);
+            u32::from_le_bytes(v)
+        };
+        let loss_basis = match fixed[45] {
+            0 => LossBasis::Combined,
+            1 => LossBasis::Realized,
+            2 => LossBasis::Unrealized,
+            _ => return None,
+        };
+        let count = u32_at(fixed, 46) as usize;
+        let orders = &bytes[Self::LEN..];
+        if count.checked_mul(Self::WORKING_ORDER_LEN) != Some(orders.len()) {
+            return None;
+        }
+        let working = orders
+            .chunks_exact(Self::WORKING_ORDER_LEN)
+            .map(|b| {
+                let flags = b[8];
+                WorkingOrder {
+                    order_id: u64_at(b, 0),
+                    good_till_cancel: flags & WORKING_GTC != 0,
+                    side: if flags & WORKING_ASK == 0 {
+                        Side::Bid
+                    } else {
+                        Side::Ask
+                    },
+                    remaining: u64_at(b, 9),
+                    notional: u64_at(b, 17) as i64,
+                    symbol_hash: (flags & WORKING_SYMBOL != 0).then(|| u64_at(b, 25)),
+                    placed_ns: u64_at(b, 33),
+                }
+            })
+            .collect();
+        Some(Self {
+            daily_pnl: u64_at(fixed, 0) as i64,
+            unrealized_pnl: u64_at(fixed, 37) as i64,
+            loss_basis,
+            open_order_count: u32_at(fixed, 8),
+            non_resting_in_window: u32_at(fixed, 12),
+            non_resting_window_start_ns: u64_at(fixed, 16),
+            circuit_breaker_tripped: fixed[24] != 0,
+            non_resting_sustained_in_window: u32_at(fixed, 25),
+            non_resting_sustained_start_ns: u64_at(fixed, 29),
+            working,
+        })
+    }
+}
+
+const WORKING_GTC: u8 = 0b001;
+const WORKING_ASK: u8 = 0b010;
+const WORKING_SYMBOL: u8 = 0b100;
+
+// ---------------------------------------------------------------------------
+// SnapshotError
+// ---------------------------------------------------------------------------
+
+/// 封印されたスナップショットを開けない理由。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// ヘッダまたは本体が途中で切れている。
+    Truncated,
+    /// 先頭がスナップショットの識別子でない。
+    BadMagic,
+    /// 未対応の版。
+    UnsupportedVersion(u8),
+    /// 暗号化されているが鍵が与えられていない。
+    KeyRequired,
+    /// 認証タグが一致しない（鍵違いまたは改ざん）。
+    AuthenticationFailed,
+    /// 圧縮データが壊れている。
+    Corrupt,
+    /// ヘッダの元の長さが [`SnapshotCodec::MAX_LEN`] を超えている。
+    TooLarge(usize),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("snapshot truncated"),
+            Self::BadMagic => f.write_str("not a snapshot"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported snapshot version {v}"),
+            Self::KeyRequired => f.write_str("snapshot is encrypted but no key was given"),
+            Self::AuthenticationFailed => f.write_str("snapshot authentication failed"),
+            Self::Corrupt => f.write_str("snapshot payload is corrupt"),
+            Self::TooLarge(len) => write!(f, "snapshot payload of {len} bytes is too large"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Compression
+// ---------------------------------------------------------------------------
+
+const MIN_MATCH: usize = 4;
+const MAX_MATCH: usize = MIN_MATCH + 0x7f;
+const MAX_LITERALS: usize = 0x80;
+const HASH_BITS: u32 = 12;
+
+fn hash4(b: &[u8]) -> usize {
+    let v = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+    (v.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+}
+
+fn push_literals(out: &mut Vec<u8>, literals: &[u8]) {
+    for run in literals.chunks(MAX_LITERALS) {
+        out.push((run.len() - 1) as u8);
+        out.extend_from_slice(run);
+    }
+}
+
+/// LZ 系の圧縮。制御バイトが `0x00..=0x7f` なら続く `n + 1` バイトがリテラル、
+/// `0x80..` なら長さ `(n & 0x7f) + 4`・距離（u16 LE）の一致。
+fn compress(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 2 + 8);
+    let mut table = [u32::MAX; 1 << HASH_BITS];
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i + MIN_MATCH <= input.len() {
+        let h = hash4(&input[i..]);
+        let candidate = table[h] as usize;
+        table[h] = i as u32;
+        if candidate < i
+            && u16::try_from(i - candidate).is_ok()
+            && input[candidate..candidate + MIN_MATCH] == input[i..i + MIN_MATCH]
+        {
+            let mut len = MIN_MATCH;
+            while i + len < input.len()
+                && len < MAX_MATCH
+                && input[candidate + len] == input[i + len]
+            {
+                len += 1;
+            }
+            push_literals(&mut out, &input[literal_start..i]);
+            out.push(0x80 | (len - MIN_MATCH) as u8);
+            out.extend_from_slice(&((i - candidate) as u16).to_le_bytes());
+            i += len;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+    push_literals(&mut out, &input[literal_start..]);
+    out
+}
+
+/// [`compress`] の逆変換。展開後の長さが `len` にならなければ
+/// [`SnapshotError::Corrupt`]。
+fn decompress(input: &[u8], len: usize) -> Result<Vec<u8>, SnapshotError> {
+    // 一致 1 つ（3 バイト）が展開できるのは最大 MAX_MATCH バイト。
+    if len > input.len().saturating_mul(MAX_MATCH) {
+        return Err(SnapshotError::Corrupt);
+    }
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+    while i < input.len() {
+        let control = usize::from(input[i]);
+        i += 1;
+        if control < 0x80 {
+            let n = control + 1;
+            let run = input.get(i..i + n).ok_or(SnapshotError::Corrupt)?;
+            out.extend_from_slice(run);
+            i += n;
+        } else {
+            let n = (control & 0x7f) + MIN_MATCH;
+            let d = input.get(i..i + 2).ok_or(SnapshotError::Corrupt)?;
+            let distance = usize::from(u16::from_le_bytes([d[0], d[1]]));
+            i += 2;
+            if distance == 0 || distance > out.len() {
+                return Err(SnapshotError::Corrupt);
+            }
+            let start = out.len() - distance;
+            for k in 0..n {
+                out.push(out[start + k]);
+            }
+        }
+        if out.len() > len {
+            return Err(SnapshotError::Corrupt);
+        }
+    }
+    if out.len() == len {
+        Ok(out)
+    } else {
+        Err(SnapshotError::Corrupt)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SnapshotCodec
+// ---------------------------------------------------------------------------
+
+const MAGIC: [u8; 4] = *b"ARSK";
+/// 形式の版。[`CheckerState`] の配置を変えたら上げる。
+const VERSION: u8 = 5;
+const FLAG_COMPRESSED: u8 = 0b01;
+const FLAG_ENCRYPTED: u8 = 0b10;
+const HEADER_LEN: usize = 10;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// 呼び出し側が渡す 256 ビットの鍵から導出した暗号化鍵と認証鍵。
+#[derive(Clone)]
+struct SealKey {
+    cipher: [u8; 32],
+    mac: HmacKey,
+}
+
+impl SealKey {
+    fn derive(key: &[u8; 32]) -> Self {
+        let root = HmacKey::new(key);
+        Self {
+            cipher: root.mac(b"alice-risk snapshot cipher"),
+            mac: HmacKey::new(&root.mac(b"alice-risk snapshot mac")),
+        }
+    }
+
+    fn tag(&self, sealed: &[u8]) -> [u8; TAG_LEN] {
+        let mut tag = [0_u8; TAG_LEN];
+        tag.copy_from_slice(&self.mac.mac(sealed)[..TAG_LEN]);
+        tag
+    }
+}
+
+/// スナップショットの封印・開封。
+#[derive(Clone, Default)]
+pub struct SnapshotCodec {
+    compress: bool,
+    key: Option<SealKey>,
+}
+
+impl SnapshotCodec {
+    /// 封印・開封できる本体の最大長（バイト）。壊れたヘッダの長さで巨大な
+    /// 領域を確保しないための上限。
+    pub const MAX_LEN: usize = 64 << 20;
+
+    /// 圧縮も暗号化もしない設定で作成。
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            compress: false,
+            key: None,
+        }
+    }
+
+    /// 封印時に圧縮する（ビルダー形式）。
+    #[must_use]
+    pub const fn with_compression(mut self) -> Self {
+        self.compress = true;
+        self
+    }
+
+    /// 封印時に `key` で暗号化し、開封時に認証する（ビルダー形式）。
+    #[must_use]
+    pub fn with_key(mut self, key: &[u8; 32]) -> Self {
+        self.key = Some(SealKey::derive(key));
+        self
+    }
+
+    /// `payload` を封印する。
+    ///
+    /// 暗号化する場合、`nonce` は同じ鍵で二度と使ってはならない（スナップ
+    /// ショットの通し番号と時刻から作るなど）。暗号化しない場合は無視する。
+    ///
+    /// # Panics
+    ///
+    /// `payload` が [`Self::MAX_LEN`] を超える場合。
+    #[must_use]
+    pub fn seal(&self, payload: &[u8], nonce: [u8; NONCE_LEN]) -> Vec<u8> {
+        assert!(
+            payload.len() <= Self::MAX_LEN,
+            "snapshot payload exceeds {} bytes",
+            Self::MAX_LEN
+        );
+        let len = payload.len() as u32;
+        let mut flags = 0;
+        let mut body = if self.compress {
+            flags |= FLAG_COMPRESSED;
+            compress(payload)
+        } else {
+            payload.to_vec()
+        };
+        if self.key.is_some() {
+            flags |= FLAG_ENCRYPTED;
+        }
+        let mut out = Vec::with_capacity(HEADER_LEN + NONCE_LEN + body.len() + TAG_LEN);
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.push(flags);
+        out.extend_from_slice(&len.to_le_bytes());
+        if let Some(key) = &self.key {
+            out.extend_from_slice(&nonce);
+            chacha20_xor(&key.cipher, 1, &nonce, &mut body);
+            out.extend_from_slice(&body);
+            let tag = key.tag(&out);
+            out.extend_from_slice(&tag);
+        } else {
+            out.extend_from_slice(&body);
+        }
+        out
+    }
+
+    /// [`Self::seal`] で封印したバイト列を開く。
+    ///
+    /// 暗号化されていないスナップショットは鍵の有無にかかわらず開ける。
+    ///
+    /// # Errors
+    ///
+    /// 形式が不正、鍵が無い・違う、または改ざんされている場合
+    /// [`SnapshotError`]。
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, SnapshotError> {
+        let header = sealed.get(..HEADER_LEN).ok_or(SnapshotError::Truncated)?;
+        if header[..4] != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        if header[4] != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(header[4]));
+        }
+        let flags = header[5];
+        let len = u32::from_le_bytes([header[6], header[7], header[8], header[9]]) as usize;
+        if len > Self::MAX_LEN {
+            return Err(SnapshotError::TooLarge(len));
+        }
+        let body = if flags & FLAG_ENCRYPTED == 0 {
+            sealed[HEADER_LEN..].to_vec()
+        } else {
+            let key = self.key.as_ref().ok_or(SnapshotError::KeyRequired)?;
+            if sealed.len() < HEADER_LEN + NONCE_LEN + TAG_LEN {
+                return Err(SnapshotError::Truncated);
+            }
+            let (signed, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+            if !ct_eq(&key.tag(signed), tag) {
+                return Err(SnapshotError::AuthenticationFailed);
+            }
+            let mut nonce = [0_u8; NONCE_LEN];
+            nonce.copy_from_slice(&signed[HEADER_LEN..HEADER_LEN + NONCE_LEN]);
+            let mut body = signed[HEADER_LEN + NONCE_LEN..].to_vec();
+            chacha20_xor(&key.cipher, 1, &nonce, &mut body);
+            body
+        };
+        if flags & FLAG_COMPRESSED == 0 {
+            if body.len() == len {
+                Ok(body)
+            } else {
+                Err(SnapshotError::Truncated)
+            }
+        } else {
+            decompress(&body, len)
+        }
+    }
+}
+
+impl fmt::Debug for SnapshotCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotCodec")
+            .field("compress", &self.compress)
+            .field("encrypted", &self.key.is_some())
+            .finish()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::PreTradeChecker;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    fn payload() -> Vec<u8> {
+        // 口座ごとの状態を並べたような、繰り返しの多いデータ。
+        let mut v = Vec::new();
+        for account in 0..50_u64 {
+            v.extend_from_slice(&account.to_le_bytes());
+            v.extend_from_slice(
+                &CheckerState {
+                    daily_pnl: -1_000,
+                    open_order_count: 3,
+                    ..CheckerState::default()
+                }
+                .to_bytes(),
+            );
+        }
+        v
+    }
+
+    #[test]
+    fn checker_state_round_trip() {
+        let mut checker =
+            PreTradeChecker::new(RiskLimits::default()).with_loss_basis(LossBasis::Unrealized);
+        checker.update_daily_pnl(-12_345);
+        checker.set_unrealized_pnl(-678);
+        checker.on_order_accepted(&OrderBuilder::bid(1_000, 1).id(1).build());
+        checker.trip_circuit_breaker();
+        checker.apply_limits(RiskLimits::default(), "ops", 1);
+        let state = checker.state();
+        let decoded = CheckerState::from_bytes(&state.to_bytes()).unwrap();
+        assert_eq!(decoded, state);
+        assert!(CheckerState::from_bytes(&[0; 3]).is_none());
+        assert!(CheckerState::from_bytes(&state.to_bytes()[..25]).is_none());
+        let mut unknown = state.to_bytes();
+        unknown[45] = 3;
+        assert!(CheckerState::from_bytes(&unknown).is_none());
+
+        let mut restored = PreTradeChecker::new(RiskLimits::default());
+        restored.restore_state(&decoded);
+        assert_eq!(restored.state(), state);
+        assert_eq!(restored.realized_pnl(), -12_345);
+        assert_eq!(restored.unrealized_pnl(), -678);
+        assert_eq!(restored.loss_basis(), LossBasis::Unrealized);
+        assert_eq!(restored.loss_pnl(), -678);
+        assert!(restored.is_circuit_breaker_tripped());
+        // 制限値の版は制限値とともに設定側で管理する。
+        assert_eq!(restored.limit_version(), 0);
+    }
+
+    #[test]
+    fn working_orders_survive_restore() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        checker.on_order_accepted_in(&OrderBuilder::bid(1_000, 5).id(1).at(10).build(), 7);
+        checker.on_order_accepted(&OrderBuilder::ask(2_000, 3).id(2).filled(1).build());
+        let state = checker.state();
+        assert_eq!(state.working.len(), 2);
+        let bytes = state.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            CheckerState::LEN + 2 * CheckerState::WORKING_ORDER_LEN
+        );
+        assert!(CheckerState::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+
+        let mut restored = PreTradeChecker::new(RiskLimits::default());
+        restored.restore_state(&CheckerState::from_bytes(&bytes).unwrap());
+        assert_eq!(restored.state(), state);
+        assert_eq!(restored.open_order_count(), 2);
+        assert_eq!(restored.symbol_open_order_count(7), 1);
+        assert_eq!(restored.open_order_notional(), 9_000);
+        assert!(restored.on_order_closed(2));
+    }
+
+    #[test]
+    fn compression_round_trip() {
+        for data in [Vec::new(), b"abc".to_vec(), vec![0; 1_000], payload()] {
+            let packed = compress(&data);
+            assert_eq!(decompress(&packed, data.len()).unwrap(), data);
+        }
+        let data = payload();
+        assert!(compress(&data).len() < data.len() / 4);
+        assert_eq!(decompress(&[0x80, 1, 0], 4), Err(SnapshotError::Corrupt));
+        assert_eq!(decompress(&[5, 1], 6), Err(SnapshotError::Corrupt));
+        assert_eq!(
+            decompress(&[0x80, 1, 0], 1 << 20),
+            Err(SnapshotError::Corrupt)
+        );
+    }
+
+    #[test]
+    fn plain_and_compressed_seal() {
+        let data = payload();
+        let plain = SnapshotCodec::new();
+        assert_eq!(plain.open(&plain.seal(&data, [0; 12])).unwrap(), data);
+        let packed = SnapshotCodec::new().with_compression();
+        let sealed = packed.seal(&data, [0; 12]);
+        assert!(sealed.len() < data.len());
+        // 開封側の設定によらずヘッダのフラグで展開する。
+        assert_eq!(plain.open(&sealed).unwrap(), data);
+    }
+
+    #[test]
+    fn encrypted_seal_authenticates() {
+        let data = payload();
+        let codec = SnapshotCodec::new().with_compression().with_key(&KEY);
+        let sealed = codec.seal(&data, [1; 12]);
+        assert!(!sealed.windows(8).any(|w| w == 1_u64.to_le_bytes()));
+        assert_eq!(codec.open(&sealed).unwrap(), data);
+
+        assert_eq!(
+            SnapshotCodec::new().open(&sealed),
+            Err(SnapshotError::KeyRequired)
+        );
+        assert_eq!(
+            SnapshotCodec::new().with_key(&[8; 32]).open(&sealed),
+            Err(SnapshotError::AuthenticationFailed)
+        );
+        let mut tampered = sealed.clone();
+        tampered[HEADER_LEN + NONCE_LEN] ^= 1;
+        assert_eq!(
+            codec.open(&tampered),
+            Err(SnapshotError::AuthenticationFailed)
+        );
+        assert_eq!(codec.open(&sealed[..20]), Err(SnapshotError::Truncated));
+    }
+
+    #[test]
+    fn header_errors() {
+        let codec = SnapshotCodec::new();
+        let mut sealed = codec.seal(b"state", [0; 12]);
+        assert_eq!(codec.open(&sealed[..4]), Err(SnapshotError::Truncated));
+        sealed[4] = 9;
+        assert_eq!(
+            codec.open(&sealed),
+            Err(SnapshotError::UnsupportedVersion(9))
+        );
+        sealed[4] = VERSION;
+        sealed[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            codec.open(&sealed),
+            Err(SnapshotError::TooLarge(u32::MAX as usize))
+        );
+        sealed[0] = b'X';
+        assert_eq!(codec.open(&sealed), Err(SnapshotError::BadMagic));
+        assert_eq!(
+            SnapshotError::KeyRequired.to_string(),
+            "snapshot is encrypted but no key was given"
+        );
+    }
+}
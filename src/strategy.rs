@@ -0,0 +1,344 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! スプレッド・複数レッグ注文の一括（アトミック）チェック。
+//!
+//! レッグごとに [`PreTradeChecker::check_order`] を呼ぶと、完全にヘッジされた
+//! 戦略でも片側のレッグだけを見て想定元本や未約定注文数で拒否してしまう。
+//! [`PreTradeChecker::check_strategy_order`] は全レッグをまとめて評価し、
+//!
+//! - 同一銘柄のレッグはネットしてポジション上限と比較
+//! - 想定元本は売買の符号付きで合計（ヘッジ分は相殺）
+//! - 未約定注文数・想定元本合計・非滞留スループットは全レッグ分を同時に予約
+//!
+//! として、全レッグを受理するか 1 件も受理しないかを判定する。
+//! 注文単位の数量上限はレッグごとに適用する。
+
+use alice_ledger::{Order, Position, Side};
+
+use crate::check::{rests_on_book, PreTradeChecker, RiskReject};
+
+// ---------------------------------------------------------------------------
+// StrategyReject
+// ---------------------------------------------------------------------------
+
+/// 戦略注文の拒否理由。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyReject {
+    /// 原因となったレッグの位置。戦略全体の判定で拒否された場合は `None`。
+    pub leg: Option<usize>,
+    /// 拒否理由。戦略全体の判定では `max_allowed` は 0。
+    pub reject: RiskReject,
+}
+
+impl StrategyReject {
+    const fn strategy(reject: RiskReject) -> Self {
+        Self { leg: None, reject }
+    }
+
+    const fn leg(leg: usize, reject: RiskReject) -> Self {
+        Self {
+            leg: Some(leg),
+            reject,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PreTradeChecker::check_strategy_order
+// ---------------------------------------------------------------------------
+
+impl PreTradeChecker {
+    /// 複数レッグの戦略注文を一括でチェックする。
+    ///
+    /// `positions[i]` は `legs[i]` の銘柄のポジション。同じ `symbol_hash` の
+    /// ポジションを持つレッグは同一銘柄としてネットする（ポジション無しの
+    /// レッグは単独の銘柄とみなす）。状態は変化しない。銘柄別の未約定注文数と
+    /// BBO 妥当性は対象外。
+    ///
+    /// # Errors
+    ///
+    /// いずれかの判定に掛かった場合、最初の [`StrategyReject`] を返す。
+    ///
+    /// # Panics
+    ///
+    /// `legs` と `positions` の長さが異なる場合。
+    pub fn check_strategy_order(
+        &self,
+        legs: &[Order],
+        positions: &[Option<&Position>],
+    ) -> Result<(), StrategyReject> {
+        assert_eq!(
+            legs.len(),
+            positions.len(),
+            "legs and positions must have the same length"
+        );
+        if self.is_circuit_breaker_tripped() {
+            return Err(StrategyReject::strategy(RiskReject::CircuitBreakerTripped));
+        }
+        let limits = self.limits();
+        let checks = limits.checks;
+
+        // 1. レッグ単位の数量上限。
+        if checks.order_size {
+            if let Some((i, leg)) = legs
+                .iter()
+                .enumerate()
+                .find(|(_, o)| o.quantity > limits.max_order_size)
+            {
+                return Err(StrategyReject::leg(
+                    i,
+                    RiskReject::OrderSizeTooLarge {
+                        size: leg.quantity,
+                        limit: limits.max_order_size,
+                        max_allowed: limits.max_order_size,
+                    },
+                ));
+            }
+        }
+
+        // 2. 銘柄ごとにネットしたポジション。
+        if checks.position {
+            // (銘柄, 最後に寄与したレッグ, 現在値, 約定後)
+            let mut nets: Vec<(Option<u64>, usize, i64, i64)> = Vec::with_capacity(legs.len());
+            for (i, (leg, position)) in legs.iter().zip(positions).enumerate() {
+                let symbol = position.map(|p| p.symbol_hash);
+                let k = if let Some(k) =
+                    symbol.and_then(|s| nets.iter().position(|n| n.0 == Some(s)))
+                {
+                    k
+                } else {
+                    let current = position.map_or(0, |p| p.net_quantity);
+                    nets.push((symbol, i, current, current));
+                    nets.len() - 1
+                };
+                let entry = &mut nets[k];
+                entry.1 = i;
+                entry.3 = self
+                    .net_after(entry.3, leg)
+                    .map_err(|r| StrategyReject::leg(i, r))?;
+            }
+            if let Some(&(_, i, current, after)) = nets
+                .iter()
+                .find(|n| n.3.unsigned_abs() > limits.max_position)
+            {
+                return Err(StrategyReject::leg(
+                    i,
+                    RiskReject::PositionLimitBreached {
+                        current,
+                        after,
+                        limit: limits.max_position,
+                        max_allowed: 0,
+                    },
+                ));
+            }
+        }
+
+        // 3. 売買の符号付き想定元本の合計。
+        let mut signed: i128 = 0;
+        let mut resting_notional: i128 = 0;
+        for (i, leg) in legs.iter().enumerate() {
+            let n = i128::from(self.notional(leg).map_err(|r| StrategyReject::leg(i, r))?);
+            signed += match leg.side {
+                Side::Bid => n,
+                Side::Ask => -n,
+            };
+            if rests_on_book(leg) {
+                resting_notional += n;
+            }
+        }
+        let combined = signed.unsigned_abs().min(i64::MAX as u128) as i64;
+        if checks.notional && combined > limits.max_notional {
+            return Err(StrategyReject::strategy(RiskReject::NotionalExceeded {
+                notional: combined,
+                limit: limits.max_notional,
+                max_allowed: 0,
+            }));
+        }
+
+        // 4. 全レッグ分の未約定注文・非滞留スループットを同時に予約できるか。
+        let resting = legs.iter().filter(|o| rests_on_book(o)).count();
+        let resting = u32::try_from(resting).unwrap_or(u32::MAX);
+        let open = self.open_order_count();
+        if checks.open_orders
+            && resting > 0
+            && open.saturating_add(resting) > limits.max_open_orders
+        {
+            return Err(StrategyReject::strategy(RiskReject::MaxOpenOrdersReached {
+                count: open,
+                limit: limits.max_open_orders,
+            }));
+        }
+        let working = self.open_order_notional();
+        let resting_notional =
+            resting_notional.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64;
+        if checks.open_order_notional
+            && resting > 0
+            && working.saturating_add(resting_notional) > limits.max_open_order_notional
+        {
+            return Err(StrategyReject::strategy(
+                RiskReject::OpenOrderNotionalExceeded {
+                    open: working,
+                    notional: resting_notional,
+                    limit: limits.max_open_order_notional,
+                    max_allowed: 0,
+                },
+            ));
+        }
+        let non_resting = u32::try_from(legs.len()).unwrap_or(u32::MAX) - resting;
+        if checks.non_resting_rate && non_resting > 0 {
+            let now_ns = legs.iter().map(|o| o.timestamp_ns).max().unwrap_or(0);
+            if let Some((count, limit)) = self.non_resting_breach(limits, now_ns, non_resting) {
+                return Err(StrategyReject::strategy(
+                    RiskReject::NonRestingRateExceeded { count, limit },
+                ));
+            }
+        }
+
+        // 5. 日次損失。
+        if checks.daily_loss && self.loss_pnl() <= limits.max_daily_loss {
+            return Err(StrategyReject::strategy(
+                self.daily_loss_reject(limits.max_daily_loss),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::{OrderBuilder, PositionBuilder};
+    use alice_ledger::TimeInForce;
+
+    fn leg(id: u64, side: Side, price: i64, quantity: u64) -> Order {
+        OrderBuilder::new(side, price, quantity).id(id).build()
+    }
+
+    fn position(symbol_hash: u64, net_quantity: i64) -> Position {
+        PositionBuilder::new(symbol_hash, net_quantity)
+            .avg_entry_price(0)
+            .build()
+    }
+
+    #[test]
+    fn hedged_spread_passes_where_legs_fail() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_notional: 50_000,
+            ..RiskLimits::default()
+        });
+        let legs = [leg(1, Side::Bid, 1_000, 60), leg(2, Side::Ask, 1_010, 60)];
+        let (a, b) = (position(1, 0), position(2, 0));
+        let positions = [Some(&a), Some(&b)];
+        assert!(checker.check_order(&legs[0], positions[0]).is_err());
+        assert!(checker.check_strategy_order(&legs, &positions).is_ok());
+    }
+
+    #[test]
+    fn same_symbol_legs_net() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_position: 50,
+            ..RiskLimits::default()
+        });
+        let held = position(1, 40);
+        // 同一銘柄の買い 20 と売り 20 は相殺される。
+        let legs = [leg(1, Side::Bid, 100, 20), leg(2, Side::Ask, 100, 20)];
+        assert!(checker
+            .check_strategy_order(&legs, &[Some(&held), Some(&held)])
+            .is_ok());
+        let legs = [leg(1, Side::Bid, 100, 20), leg(2, Side::Ask, 100, 5)];
+        assert_eq!(
+            checker.check_strategy_order(&legs, &[Some(&held), Some(&held)]),
+            Err(StrategyReject {
+                leg: Some(1),
+                reject: RiskReject::PositionLimitBreached {
+                    current: 40,
+                    after: 55,
+                    limit: 50,
+                    max_allowed: 0,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn legs_reserve_open_orders_atomically() {
+        let mut checker = PreTradeChecker::new(RiskLimits {
+            max_open_orders: 3,
+            ..RiskLimits::default()
+        });
+        checker.on_order_accepted(&leg(10, Side::Bid, 100, 1));
+        checker.on_order_accepted(&leg(11, Side::Bid, 100, 1));
+        let legs = [leg(1, Side::Bid, 100, 1), leg(2, Side::Ask, 100, 1)];
+        assert_eq!(
+            checker.check_strategy_order(&legs, &[None, None]),
+            Err(StrategyReject {
+                leg: None,
+                reject: RiskReject::MaxOpenOrdersReached { count: 2, limit: 3 },
+            })
+        );
+        checker.on_order_closed(11);
+        assert!(checker.check_strategy_order(&legs, &[None, None]).is_ok());
+    }
+
+    #[test]
+    fn leg_level_and_account_rejects() {
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let legs = [leg(1, Side::Bid, 100, 1), leg(2, Side::Ask, 100, 1_000)];
+        let err = checker
+            .check_strategy_order(&legs, &[None, None])
+            .unwrap_err();
+        assert_eq!(err.leg, Some(1));
+        assert!(matches!(err.reject, RiskReject::OrderSizeTooLarge { .. }));
+        checker.update_daily_pnl(-1_000_000);
+        let legs = [leg(1, Side::Bid, 100, 1)];
+        assert!(matches!(
+            checker.check_strategy_order(&legs, &[None]),
+            Err(StrategyReject {
+                leg: None,
+                reject: RiskReject::DailyLossLimitHit { .. }
+            })
+        ));
+        checker.trip_circuit_breaker();
+        assert_eq!(
+            checker.check_strategy_order(&[], &[]),
+            Err(StrategyReject {
+                leg: None,
+                reject: RiskReject::CircuitBreakerTripped
+            })
+        );
+    }
+
+    #[test]
+    fn non_resting_legs_counted_together() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_non_resting_orders: 1,
+            ..RiskLimits::default()
+        });
+        let mut a = leg(1, Side::Bid, 100, 1);
+        a.time_in_force = TimeInForce::IOC;
+        let mut b = leg(2, Side::Ask, 100, 1);
+        b.time_in_force = TimeInForce::FOK;
+        assert!(matches!(
+            checker.check_strategy_order(&[a, b], &[None, None]),
+            Err(StrategyReject {
+                leg: None,
+                reject: RiskReject::NonRestingRateExceeded { .. }
+            })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn length_mismatch_panics() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let _ = checker.check_strategy_order(&[leg(1, Side::Bid, 1, 1)], &[]);
+    }
+}
@@ -8,6 +8,8 @@
 //! 仮想的な市場変動シナリオをポートフォリオに適用し、
 //! 想定損益を算出する。
 
+use crate::parallel;
+
 // ---------------------------------------------------------------------------
 // StressScenario
 // ---------------------------------------------------------------------------
@@ -116,21 +118,32 @@ pub fn apply_scenario(position: &StressPosition, scenario: &StressScenario) -> S
 }
 
 /// 複数ポジションのポートフォリオに対してシナリオを適用する。
+///
+/// ポジション単位の損益は固定長のチャンクごとに合算し、部分和をチャンク順に
+/// 畳み込む（`rayon` フィーチャーではチャンク単位に並列）。
 #[must_use]
 pub fn apply_scenario_portfolio(
     positions: &[StressPosition],
     scenario: &StressScenario,
 ) -> StressResult {
-    let mut total_price_pnl: i128 = 0;
-    let mut total_vol_pnl: i128 = 0;
-
-    for pos in positions {
-        total_price_pnl = total_price_pnl
-            .saturating_add((pos.quantity as i128).saturating_mul(scenario.price_shock as i128));
-        total_vol_pnl = total_vol_pnl.saturating_add(
-            (pos.vega_per_bp as i128).saturating_mul(scenario.vol_shock_bps as i128),
-        );
-    }
+    let partials = parallel::map_chunks(positions, |chunk| {
+        let mut price_pnl: i128 = 0;
+        let mut vol_pnl: i128 = 0;
+        for pos in chunk {
+            price_pnl = price_pnl.saturating_add(
+                (pos.quantity as i128).saturating_mul(scenario.price_shock as i128),
+            );
+            vol_pnl = vol_pnl.saturating_add(
+                (pos.vega_per_bp as i128).saturating_mul(scenario.vol_shock_bps as i128),
+            );
+        }
+        (price_pnl, vol_pnl)
+    });
+    let (total_price_pnl, total_vol_pnl) = partials
+        .into_iter()
+        .fold((0_i128, 0_i128), |(price, vol), (p, v)| {
+            (price.saturating_add(p), vol.saturating_add(v))
+        });
 
     let price_pnl = total_price_pnl.min(i64::MAX as i128).max(i64::MIN as i128) as i64;
     let vol_pnl = total_vol_pnl.min(i64::MAX as i128).max(i64::MIN as i128) as i64;
@@ -144,15 +157,14 @@ pub fn apply_scenario_portfolio(
 }
 
 /// 複数シナリオを一括でポートフォリオに適用する。
+///
+/// 結果はシナリオ順。`rayon` フィーチャーではシナリオ単位に並列に評価する。
 #[must_use]
 pub fn stress_test_portfolio(
     positions: &[StressPosition],
     scenarios: &[StressScenario],
 ) -> Vec<StressResult> {
-    scenarios
-        .iter()
-        .map(|s| apply_scenario_portfolio(positions, s))
-        .collect()
+    parallel::map(scenarios, |s| apply_scenario_portfolio(positions, s))
 }
 
 /// 最悪シナリオの損失額を返す。
@@ -325,6 +337,28 @@ mod tests {
         assert_eq!(result.total_pnl, result.price_pnl);
     }
 
+    #[test]
+    fn large_portfolio_sums_every_position() {
+        let positions: Vec<StressPosition> = (0..5_000_i64)
+            .map(|i| StressPosition {
+                quantity: i % 97 - 48,
+                current_price: 10_000 + i,
+                vega_per_bp: i % 13 - 6,
+            })
+            .collect();
+        let scenarios: Vec<StressScenario> = (0..2_000)
+            .map(|i| StressScenario::new("s", i - 1_000, (i % 50) as i32 * 100))
+            .collect();
+        let results = stress_test_portfolio(&positions, &scenarios);
+        assert_eq!(results.len(), scenarios.len());
+        for (result, scenario) in results.iter().zip(&scenarios).step_by(97) {
+            let expected = positions
+                .iter()
+                .fold(0_i64, |acc, p| acc + apply_scenario(p, scenario).total_pnl);
+            assert_eq!(result.total_pnl, expected);
+        }
+    }
+
     #[test]
     fn position_clone_eq() {
         let a = long_position();
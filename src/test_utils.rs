@@ -0,0 +1,520 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 下流サービスのテスト向けユーティリティ（`test-utils` フィーチャー）。
+//!
+//! 各サービスが本クレートのユニットテストにある `make_order` 系ヘルパーを
+//! 複製しなくて済むよう、注文・ポジション・制限値のビルダー、進行を手動で
+//! 制御するモック時計、受理判定の不変条件アサーションを提供する。
+//!
+//! ```rust
+//! use alice_risk::test_utils::{assert_accepted_within_limits, OrderBuilder};
+//! use alice_risk::{PreTradeChecker, RiskLimits};
+//!
+//! let checker = PreTradeChecker::new(RiskLimits::default());
+//! let order = OrderBuilder::bid(1_000, 10).id(7).build();
+//! assert!(assert_accepted_within_limits(&checker, &order, None));
+//! ```
+
+use alloc::collections::BTreeSet;
+
+use alice_ledger::{Order, OrderId, OrderType, Position, Side, TimeInForce};
+
+use crate::check::{rests_on_book, PreTradeChecker};
+use crate::limit::{EnabledChecks, RiskLimits};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// OrderBuilder
+// ---------------------------------------------------------------------------
+
+/// [`Order`] のビルダー。未指定の項目は ID 1・指値・GTC・未約定・時刻 0。
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    order: Order,
+}
+
+impl OrderBuilder {
+    /// 指定サイド・価格・数量の注文。
+    #[must_use]
+    pub const fn new(side: Side, price: i64, quantity: u64) -> Self {
+        Self {
+            order: Order {
+                id: OrderId(1),
+                side,
+                order_type: OrderType::Limit,
+                price,
+                quantity,
+                filled_quantity: 0,
+                timestamp_ns: 0,
+                time_in_force: TimeInForce::GTC,
+            },
+        }
+    }
+
+    /// 買い注文。
+    #[must_use]
+    pub const fn bid(price: i64, quantity: u64) -> Self {
+        Self::new(Side::Bid, price, quantity)
+    }
+
+    /// 売り注文。
+    #[must_use]
+    pub const fn ask(price: i64, quantity: u64) -> Self {
+        Self::new(Side::Ask, price, quantity)
+    }
+
+    /// 注文 ID。
+    #[must_use]
+    pub const fn id(mut self, id: u64) -> Self {
+        self.order.id = OrderId(id);
+        self
+    }
+
+    /// 成行注文にする。
+    #[must_use]
+    pub const fn market(mut self) -> Self {
+        self.order.order_type = OrderType::Market;
+        self
+    }
+
+    /// 執行条件。
+    #[must_use]
+    pub const fn time_in_force(mut self, tif: TimeInForce) -> Self {
+        self.order.time_in_force = tif;
+        self
+    }
+
+    /// 約定済み数量。
+    #[must_use]
+    pub const fn filled(mut self, filled_quantity: u64) -> Self {
+        self.order.filled_quantity = filled_quantity;
+        self
+    }
+
+    /// タイムスタンプ（ns）。
+    #[must_use]
+    pub const fn at(mut self, timestamp_ns: u64) -> Self {
+        self.order.timestamp_ns = timestamp_ns;
+        self
+    }
+
+    /// 注文を生成する。
+    #[must_use]
+    pub fn build(self) -> Order {
+        self.order
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PositionBuilder
+// ---------------------------------------------------------------------------
+
+/// [`Position`] のビルダー。未指定の項目は 0（建値のみ 1000）。
+#[derive(Debug, Clone)]
+pub struct PositionBuilder {
+    position: Position,
+}
+
+impl PositionBuilder {
+    /// 指定銘柄・ネット数量のポジション。
+    #[must_use]
+    pub const fn new(symbol_hash: u64, net_quantity: i64) -> Self {
+        Self {
+            position: Position {
+                symbol_hash,
+                net_quantity,
+                avg_entry_price: 1000,
+                realized_pnl: 0,
+                unrealized_pnl: 0,
+                trade_count: 0,
+            },
+        }
+    }
+
+    /// 平均建値。
+    #[must_use]
+    pub const fn avg_entry_price(mut self, price: i64) -> Self {
+        self.position.avg_entry_price = price;
+        self
+    }
+
+    /// 実現損益。
+    #[must_use]
+    pub const fn realized_pnl(mut self, pnl: i64) -> Self {
+        self.position.realized_pnl = pnl;
+        self
+    }
+
+    /// 評価損益。
+    #[must_use]
+    pub const fn unrealized_pnl(mut self, pnl: i64) -> Self {
+        self.position.unrealized_pnl = pnl;
+        self
+    }
+
+    /// 約定回数。
+    #[must_use]
+    pub const fn trade_count(mut self, count: u64) -> Self {
+        self.position.trade_count = count;
+        self
+    }
+
+    /// ポジションを生成する。
+    #[must_use]
+    pub fn build(self) -> Position {
+        self.position
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LimitsBuilder
+// ---------------------------------------------------------------------------
+
+/// [`RiskLimits`] のビルダー。[`RiskLimits::default`] を起点とする。
+#[derive(Debug, Clone, Default)]
+pub struct LimitsBuilder {
+    limits: RiskLimits,
+}
+
+impl LimitsBuilder {
+    /// 既定値から開始する。
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 実質無制限の制限値から開始する（特定の制限だけを検証する場合）。
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self {
+            limits: RiskLimits {
+                max_position: u64::MAX,
+                max_order_size: u64::MAX,
+                max_notional: i64::MAX,
+                max_open_orders: u32::MAX,
+                max_daily_loss: i64::MIN,
+                max_non_resting_orders: u32::MAX,
+                non_resting_window_ns: 1,
+                max_non_resting_orders_sustained: u32::MAX,
+                non_resting_sustained_window_ns: 0,
+                max_open_order_notional: i64::MAX,
+                max_open_orders_per_symbol: u32::MAX,
+                max_working_order_age_ns: u64::MAX,
+                max_through_bbo_bps: u32::MAX,
+                min_display_quantity: 0,
+                expiry_no_open_days: 0,
+                spot_month_position_limit: u64::MAX,
+                spot_month_days_before_notice: 0,
+                front_month_days: 0,
+                max_front_month_position: u64::MAX,
+                max_back_month_position: u64::MAX,
+                max_long_dated_position: u64::MAX,
+                illiquid_symbols: BTreeSet::new(),
+                illiquid_margin_multiplier_bps: 10_000,
+                illiquid_limit_factor_bps: 10_000,
+                illiquid_max_through_bbo_bps: u32::MAX,
+                checks: EnabledChecks::ALL,
+            },
+        }
+    }
+
+    /// 最大ポジション。
+    #[must_use]
+    pub const fn max_position(mut self, value: u64) -> Self {
+        self.limits.max_position = value;
+        self
+    }
+
+    /// 1 注文の最大数量。
+    #[must_use]
+    pub const fn max_order_size(mut self, value: u64) -> Self {
+        self.limits.max_order_size = value;
+        self
+    }
+
+    /// 1 注文の最大想定元本。
+    #[must_use]
+    pub const fn max_notional(mut self, value: i64) -> Self {
+        self.limits.max_notional = value;
+        self
+    }
+
+    /// 最大未約定注文数。
+    #[must_use]
+    pub const fn max_open_orders(mut self, value: u32) -> Self {
+        self.limits.max_open_orders = value;
+        self
+    }
+
+    /// 日次損失上限（負値）。
+    #[must_use]
+    pub const fn max_daily_loss(mut self, value: i64) -> Self {
+        self.limits.max_daily_loss = value;
+        self
+    }
+
+    /// ウィンドウあたりの最大非滞留（IOC / FOK）注文数。
+    #[must_use]
+    pub const fn max_non_resting_orders(mut self, value: u32, window_ns: u64) -> Self {
+        self.limits.max_non_resting_orders = value;
+        self.limits.non_resting_window_ns = window_ns;
+        self
+    }
+
+    /// 持続時間幅あたりの最大非滞留（IOC / FOK）注文数。
+    #[must_use]
+    pub const fn max_non_resting_orders_sustained(mut self, value: u32, window_ns: u64) -> Self {
+        self.limits.max_non_resting_orders_sustained = value;
+        self.limits.non_resting_sustained_window_ns = window_ns;
+        self
+    }
+
+    /// 未約定注文の想定元本合計の上限。
+    #[must_use]
+    pub const fn max_open_order_notional(mut self, value: i64) -> Self {
+        self.limits.max_open_order_notional = value;
+        self
+    }
+
+    /// 制限値を生成する。
+    #[must_use]
+    pub fn build(self) -> RiskLimits {
+        self.limits
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MockClock
+// ---------------------------------------------------------------------------
+
+/// 手動で進めるモック時計（ns）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MockClock {
+    now_ns: u64,
+}
+
+impl MockClock {
+    /// 指定時刻から開始する。
+    #[must_use]
+    pub const fn new(start_ns: u64) -> Self {
+        Self { now_ns: start_ns }
+    }
+
+    /// 現在時刻。
+    #[must_use]
+    pub const fn now_ns(&self) -> u64 {
+        self.now_ns
+    }
+
+    /// `ns` だけ進め、進めた後の時刻を返す。
+    pub const fn advance(&mut self, ns: u64) -> u64 {
+        self.now_ns = self.now_ns.saturating_add(ns);
+        self.now_ns
+    }
+
+    /// 時刻を設定する。
+    pub const fn set(&mut self, now_ns: u64) {
+        self.now_ns = now_ns;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Invariant assertions
+// ---------------------------------------------------------------------------
+
+/// 受理された注文が、チェック時点の状態ですべての制限を満たすかを独立に検算する。
+///
+/// 満たさない場合、違反した不変条件の説明を返す。`position` はチェック時点の
+/// ネット数量。
+#[must_use]
+pub fn accepted_order_violation(
+    checker: &PreTradeChecker,
+    order: &Order,
+    position: i64,
+) -> Option<&'static str> {
+    let limits = checker.limits();
+    if checker.is_circuit_breaker_tripped() {
+        return Some("order accepted while breaker tripped");
+    }
+    let checks = limits.checks;
+    if checks.order_size && order.quantity > limits.max_order_size {
+        return Some("accepted order exceeds max_order_size");
+    }
+    let signed = match order.side {
+        Side::Bid => order.quantity as i128,
+        Side::Ask => -(order.quantity as i128),
+    };
+    if checks.position && (position as i128 + signed).unsigned_abs() > limits.max_position as u128 {
+        return Some("accepted order exceeds max_position");
+    }
+    let notional = (order.price as i128 * order.quantity as i128).abs();
+    if checks.notional && notional > limits.max_notional as i128 {
+        return Some("accepted order exceeds max_notional");
+    }
+    if checks.open_orders
+        && rests_on_book(order)
+        && checker.open_order_count() >= limits.max_open_orders
+    {
+        return Some("accepted order exceeds max_open_orders");
+    }
+    if checks.open_order_notional
+        && rests_on_book(order)
+        && checker.open_order_notional() as i128 + notional > limits.max_open_order_notional as i128
+    {
+        return Some("accepted order exceeds max_open_order_notional");
+    }
+    if checks.daily_loss && checker.loss_pnl() <= limits.max_daily_loss {
+        return Some("order accepted past daily loss limit");
+    }
+    None
+}
+
+/// チェッカーの判定が不変条件と整合することを表明する。
+///
+/// 受理された場合は [`accepted_order_violation`] で全制限を検算する。
+/// 受理されたかどうかを返す。
+///
+/// # Panics
+///
+/// 受理された注文が制限を満たさない場合。
+#[track_caller]
+#[must_use]
+pub fn assert_accepted_within_limits(
+    checker: &PreTradeChecker,
+    order: &Order,
+    position: Option<&Position>,
+) -> bool {
+    let accepted = checker.check_order(order, position).is_ok();
+    if accepted {
+        let net = position.map_or(0, |p| p.net_quantity);
+        if let Some(invariant) = accepted_order_violation(checker, order, net) {
+            panic!("order {}: {invariant}", order.id.0);
+        }
+    }
+    accepted
+}
+
+/// 注文が拒否され、拒否理由が `field` の制限に対応することを表明する。
+///
+/// # Panics
+///
+/// 注文が受理された場合、または拒否理由が別の制限による場合。
+#[track_caller]
+pub fn assert_rejected_by(
+    checker: &PreTradeChecker,
+    order: &Order,
+    position: Option<&Position>,
+    field: &str,
+) {
+    match checker.check_order(order, position) {
+        Ok(()) => panic!("order {}: expected rejection by {field}", order.id.0),
+        Err(reject) => assert_eq!(
+            reject.limit_field(),
+            Some(field),
+            "order {}: rejected by {reject:?}",
+            order.id.0
+        ),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_builder_fields() {
+        let o = OrderBuilder::ask(250, 3)
+            .id(9)
+            .market()
+            .time_in_force(TimeInForce::IOC)
+            .filled(1)
+            .at(42)
+            .build();
+        assert_eq!(o.id.0, 9);
+        assert!(matches!(o.side, Side::Ask));
+        assert!(matches!(o.order_type, OrderType::Market));
+        assert!(matches!(o.time_in_force, TimeInForce::IOC));
+        assert_eq!((o.price, o.quantity, o.filled_quantity), (250, 3, 1));
+        assert_eq!(o.timestamp_ns, 42);
+    }
+
+    #[test]
+    fn position_builder_fields() {
+        let p = PositionBuilder::new(7, -5)
+            .avg_entry_price(990)
+            .realized_pnl(10)
+            .unrealized_pnl(-3)
+            .trade_count(2)
+            .build();
+        assert_eq!(p.symbol_hash, 7);
+        assert_eq!(p.net_quantity, -5);
+        assert_eq!(p.avg_entry_price, 990);
+        assert_eq!(
+            (p.realized_pnl, p.unrealized_pnl, p.trade_count),
+            (10, -3, 2)
+        );
+    }
+
+    #[test]
+    fn limits_builder() {
+        let l = LimitsBuilder::new().max_order_size(5).build();
+        assert_eq!(l.max_order_size, 5);
+        assert_eq!(l.max_position, RiskLimits::default().max_position);
+        let u = LimitsBuilder::unlimited().max_open_orders(1).build();
+        assert_eq!(u.max_notional, i64::MAX);
+        assert_eq!(u.max_open_orders, 1);
+    }
+
+    #[test]
+    fn mock_clock() {
+        let mut c = MockClock::new(100);
+        assert_eq!(c.advance(50), 150);
+        c.set(u64::MAX - 1);
+        assert_eq!(c.advance(10), u64::MAX);
+        assert_eq!(MockClock::default().now_ns(), 0);
+    }
+
+    #[test]
+    fn accepted_order_within_limits() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let o = OrderBuilder::bid(1000, 10).build();
+        assert!(assert_accepted_within_limits(&checker, &o, None));
+        let big = OrderBuilder::bid(1000, 1_000_000).build();
+        assert!(!assert_accepted_within_limits(&checker, &big, None));
+    }
+
+    #[test]
+    fn violation_detected_against_other_state() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let o = OrderBuilder::bid(1000, 10).build();
+        assert_eq!(accepted_order_violation(&checker, &o, 0), None);
+        let max = RiskLimits::default().max_position as i64;
+        assert_eq!(
+            accepted_order_violation(&checker, &o, max),
+            Some("accepted order exceeds max_position")
+        );
+    }
+
+    #[test]
+    fn rejected_by_field() {
+        let checker = PreTradeChecker::new(LimitsBuilder::new().max_order_size(5).build());
+        let o = OrderBuilder::bid(1000, 10).build();
+        assert_rejected_by(&checker, &o, None, "max_order_size");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected rejection")]
+    fn rejected_by_panics_on_accept() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let o = OrderBuilder::bid(1000, 1).build();
+        assert_rejected_by(&checker, &o, None, "max_order_size");
+    }
+}
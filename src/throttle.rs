@@ -0,0 +1,833 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 注文送信とは別枠のメッセージ流量制限。
+//!
+//! 取引所は取消メッセージの流量を新規注文とは独立に監視するため、
+//! [`CancelRateLimiter`] は口座ごとに取消専用の予算（固定時間幅あたりの件数）を
+//! 持つ。予算を超えた取消は [`RiskReject::CancelRateExceeded`] で拒否し、
+//! [`CancelPenalty::BlockOrders`] を設定していれば、その口座の新規注文も
+//! 一定期間 [`RiskReject::CancelRatePenalty`] で止める。
+//!
+//! [`MessageBudget`] は取引所のセッション課金と同じく、新規・訂正・取消を
+//! 種類別の重み（[`MessageWeights`]）で合算した時間幅あたりの予算で、超過は
+//! [`RiskReject::MessageBudgetExceeded`] になる。新規注文は
+//! [`MessageBudget::check_order`] で事前チェックの前段に組み込める。
+//!
+//! [`SessionThrottles`] はメッセージ予算を口座に加えてゲートウェイの
+//! セッション ID ごとにも持つ。暴走したり乗っ取られたりした FIX セッションは
+//! 予算を絞るか [`SessionThrottles::choke`] で止められ、同じ口座の他の
+//! セッションには影響しない。
+//!
+//! どの流量制限も、短い時間幅のバースト上限に加えて、長い時間幅の持続上限
+//! （[`RateWindow`]）を持てる。一瞬のバーストは通しつつ、過負荷が続く場合だけ
+//! 止められる。持続上限に掛かった場合も拒否理由は同じで、件数と上限は持続側の
+//! 値になる。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// RateWindow
+// ---------------------------------------------------------------------------
+
+/// 長い時間幅の持続上限。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateWindow {
+    /// 1 時間幅あたりの上限。
+    pub limit: u32,
+    /// 時間幅（ns、固定）。0 で無効。
+    pub window_ns: u64,
+}
+
+impl RateWindow {
+    /// 持続上限なし。
+    pub const DISABLED: Self = Self::new(u32::MAX, 0);
+
+    /// `window_ns` あたり `limit` まで。
+    #[must_use]
+    pub const fn new(limit: u32, window_ns: u64) -> Self {
+        Self { limit, window_ns }
+    }
+
+    /// `counter` にあと `n` 足すと上限を超える場合、時間幅の現在の値。
+    pub(crate) const fn exceeded(
+        &self,
+        counter: &WindowCounter,
+        now_ns: u64,
+        n: u32,
+    ) -> Option<u32> {
+        if self.window_ns == 0 {
+            return None;
+        }
+        let count = counter.count_at(self.window_ns, now_ns);
+        if count.saturating_add(n) > self.limit {
+            Some(count)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for RateWindow {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
+
+/// 固定時間幅の件数（重み）の累計。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct WindowCounter {
+    /// 時間幅の開始時刻（ns）。
+    pub(crate) start_ns: u64,
+    /// 時間幅の累計。
+    pub(crate) count: u32,
+}
+
+impl WindowCounter {
+    /// 空のカウンタ。
+    pub(crate) const fn new() -> Self {
+        Self {
+            start_ns: 0,
+            count: 0,
+        }
+    }
+
+    /// `now_ns` を含む長さ `window_ns` の時間幅の累計。
+    pub(crate) const fn count_at(&self, window_ns: u64, now_ns: u64) -> u32 {
+        if now_ns.saturating_sub(self.start_ns) >= window_ns {
+            0
+        } else {
+            self.count
+        }
+    }
+
+    /// `now_ns` に `n` を足す。時間幅を過ぎていれば新しい時間幅を始める。
+    pub(crate) const fn add(&mut self, window_ns: u64, now_ns: u64, n: u32) {
+        if now_ns.saturating_sub(self.start_ns) >= window_ns {
+            self.start_ns = now_ns;
+            self.count = 0;
+        }
+        self.count = self.count.saturating_add(n);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CancelRateConfig
+// ---------------------------------------------------------------------------
+
+/// 取消レート超過時の扱い。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelPenalty {
+    /// 超過した取消だけを拒否する。
+    RejectCancel,
+    /// 超過した取消を拒否し、`cooldown_ns` の間は口座の新規注文も拒否する。
+    /// 取消はリスクを減らすため、次の時間幅からは通常どおり受け付ける。
+    BlockOrders { cooldown_ns: u64 },
+}
+
+/// 取消レート制限の設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelRateConfig {
+    /// 1 時間幅あたりの取消件数の上限（バースト）。0 で無効。
+    pub max_cancels: u32,
+    /// 時間幅（ns、固定）。
+    pub window_ns: u64,
+    /// 持続上限。
+    pub sustained: RateWindow,
+    /// 超過時の扱い。
+    pub penalty: CancelPenalty,
+}
+
+impl Default for CancelRateConfig {
+    fn default() -> Self {
+        Self {
+            max_cancels: 100,
+            window_ns: 1_000_000_000, // 1 秒
+            sustained: RateWindow::DISABLED,
+            penalty: CancelPenalty::RejectCancel,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CancelRateLimiter
+// ---------------------------------------------------------------------------
+
+/// 1 口座の取消の流量。
+#[derive(Debug, Clone, Copy, Default)]
+struct CancelState {
+    burst: WindowCounter,
+    sustained: WindowCounter,
+    /// 新規注文を止める期間の終了時刻。
+    penalty_until: Option<u64>,
+}
+
+/// 口座別の取消レート制限。
+#[derive(Debug, Clone)]
+pub struct CancelRateLimiter {
+    config: CancelRateConfig,
+    accounts: BTreeMap<u64, CancelState>,
+}
+
+impl CancelRateLimiter {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new(config: CancelRateConfig) -> Self {
+        Self {
+            config,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &CancelRateConfig {
+        &self.config
+    }
+
+    /// 口座が `now_ns` のバーストの時間幅で送った取消の件数。
+    #[must_use]
+    pub fn count_at(&self, account_id: u64, now_ns: u64) -> u32 {
+        self.accounts
+            .get(&account_id)
+            .map_or(0, |s| s.burst.count_at(self.config.window_ns, now_ns))
+    }
+
+    /// 口座の取消を 1 件判定し、通れば数える。
+    ///
+    /// # Errors
+    ///
+    /// バーストまたは持続の予算を使い切っていれば
+    /// [`RiskReject::CancelRateExceeded`]。
+    /// [`CancelPenalty::BlockOrders`] の場合は同時に新規注文の停止を始める
+    /// （停止中の超過で期間は延びない）。
+    pub fn check_cancel(&mut self, account_id: u64, now_ns: u64) -> Result<(), RiskReject> {
+        let config = self.config;
+        if config.max_cancels == 0 {
+            return Ok(());
+        }
+        let count = self.count_at(account_id, now_ns);
+        let state = self.accounts.entry(account_id).or_default();
+        let breach = if count >= config.max_cancels {
+            Some((count, config.max_cancels))
+        } else {
+            let sustained = config.sustained;
+            sustained
+                .exceeded(&state.sustained, now_ns, 1)
+                .map(|c| (c, sustained.limit))
+        };
+        if let Some((count, limit)) = breach {
+            if let CancelPenalty::BlockOrders { cooldown_ns } = config.penalty {
+                if state.penalty_until.is_none_or(|until| now_ns >= until) {
+                    state.penalty_until = Some(now_ns.saturating_add(cooldown_ns));
+                }
+            }
+            return Err(RiskReject::CancelRateExceeded { count, limit });
+        }
+        state.burst.add(config.window_ns, now_ns, 1);
+        state.sustained.add(config.sustained.window_ns, now_ns, 1);
+        Ok(())
+    }
+
+    /// 口座が `now_ns` に新規注文を出せるか。
+    ///
+    /// # Errors
+    ///
+    /// 取消レート超過による停止中なら [`RiskReject::CancelRatePenalty`]。
+    pub fn check_order(&self, account_id: u64, now_ns: u64) -> Result<(), RiskReject> {
+        match self.penalty_until(account_id, now_ns) {
+            Some(until_ns) => Err(RiskReject::CancelRatePenalty { until_ns }),
+            None => Ok(()),
+        }
+    }
+
+    /// 新規注文の停止中の口座の終了時刻。
+    #[must_use]
+    pub fn penalty_until(&self, account_id: u64, now_ns: u64) -> Option<u64> {
+        self.accounts
+            .get(&account_id)
+            .and_then(|s| s.penalty_until)
+            .filter(|&until_ns| now_ns < until_ns)
+    }
+
+    /// 口座の取消の流量と停止を消去する。
+    pub fn release(&mut self, account_id: u64) {
+        self.accounts.remove(&account_id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MessageBudget
+// ---------------------------------------------------------------------------
+
+/// メッセージの種類。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    /// 新規注文。
+    New,
+    /// 訂正。
+    Amend,
+    /// 取消。
+    Cancel,
+}
+
+/// 種類別の重み。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageWeights {
+    /// 新規注文の重み。
+    pub new: u32,
+    /// 訂正の重み。
+    pub amend: u32,
+    /// 取消の重み。
+    pub cancel: u32,
+}
+
+impl MessageWeights {
+    /// `kind` の重み。
+    #[must_use]
+    pub const fn weight(&self, kind: MessageKind) -> u32 {
+        match kind {
+            MessageKind::New => self.new,
+            MessageKind::Amend => self.amend,
+            MessageKind::Cancel => self.cancel,
+        }
+    }
+}
+
+impl Default for MessageWeights {
+    fn default() -> Self {
+        Self {
+            new: 1,
+            amend: 1,
+            cancel: 1,
+        }
+    }
+}
+
+/// 固定時間幅あたりの重み付きメッセージ予算。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageBudget {
+    max_weight: u32,
+    window_ns: u64,
+    weights: MessageWeights,
+    sustained: RateWindow,
+    burst_used: WindowCounter,
+    sustained_used: WindowCounter,
+}
+
+impl MessageBudget {
+    /// `window_ns` あたり重みの合計 `max_weight` まで。
+    #[must_use]
+    pub const fn new(max_weight: u32, window_ns: u64, weights: MessageWeights) -> Self {
+        Self {
+            max_weight,
+            window_ns,
+            weights,
+            sustained: RateWindow::DISABLED,
+            burst_used: WindowCounter::new(),
+            sustained_used: WindowCounter::new(),
+        }
+    }
+
+    /// 重みの合計の持続上限を設定する。
+    #[must_use]
+    pub const fn with_sustained(mut self, sustained: RateWindow) -> Self {
+        self.sustained = sustained;
+        self
+    }
+
+    /// 上限なし。
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self::new(
+            u32::MAX,
+            0,
+            MessageWeights {
+                new: 0,
+                amend: 0,
+                cancel: 0,
+            },
+        )
+    }
+
+    /// 1 時間幅あたりの予算（バースト）。
+    #[must_use]
+    pub const fn max_weight(&self) -> u32 {
+        self.max_weight
+    }
+
+    /// 持続上限。
+    #[must_use]
+    pub const fn sustained(&self) -> &RateWindow {
+        &self.sustained
+    }
+
+    /// 種類別の重み。
+    #[must_use]
+    pub const fn weights(&self) -> &MessageWeights {
+        &self.weights
+    }
+
+    /// `now_ns` のバーストの時間幅で使った重み。
+    #[must_use]
+    pub const fn used_at(&self, now_ns: u64) -> u32 {
+        self.burst_used.count_at(self.window_ns, now_ns)
+    }
+
+    /// `now_ns` に `kind` のメッセージを 1 件送れるか。
+    ///
+    /// # Errors
+    ///
+    /// バーストまたは持続の予算を超えるなら
+    /// [`RiskReject::MessageBudgetExceeded`]。
+    pub const fn check(&self, kind: MessageKind, now_ns: u64) -> Result<(), RiskReject> {
+        let used = self.used_at(now_ns);
+        let weight = self.weights.weight(kind);
+        if used.saturating_add(weight) > self.max_weight {
+            return Err(RiskReject::MessageBudgetExceeded {
+                used,
+                weight,
+                limit: self.max_weight,
+            });
+        }
+        if let Some(used) = self
+            .sustained
+            .exceeded(&self.sustained_used, now_ns, weight)
+        {
+            return Err(RiskReject::MessageBudgetExceeded {
+                used,
+                weight,
+                limit: self.sustained.limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// `now_ns` に送った `kind` のメッセージを数える。
+    pub const fn record(&mut self, kind: MessageKind, now_ns: u64) {
+        let weight = self.weights.weight(kind);
+        self.burst_used.add(self.window_ns, now_ns, weight);
+        self.sustained_used
+            .add(self.sustained.window_ns, now_ns, weight);
+    }
+
+    /// 判定し、通れば数える。訂正・取消はこちらを使う。
+    ///
+    /// # Errors
+    ///
+    /// 予算を超えるなら [`RiskReject::MessageBudgetExceeded`]（数えない）。
+    pub fn try_send(&mut self, kind: MessageKind, now_ns: u64) -> Result<(), RiskReject> {
+        self.check(kind, now_ns)?;
+        self.record(kind, now_ns);
+        Ok(())
+    }
+
+    /// 予算を確認してから `checker` で新規注文を判定し、通った注文だけを
+    /// 数える（拒否された注文は取引所に届かない）。
+    ///
+    /// # Errors
+    ///
+    /// 予算を超えるなら [`RiskReject::MessageBudgetExceeded`]、それ以外は
+    /// [`PreTradeChecker::check_order`] の拒否理由。
+    pub fn check_order(
+        &mut self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let now = order.timestamp_ns;
+        self.check(MessageKind::New, now)?;
+        checker.check_order(order, position)?;
+        self.record(MessageKind::New, now);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SessionThrottles
+// ---------------------------------------------------------------------------
+
+/// 1 セッションの流量。
+#[derive(Debug, Clone, Copy)]
+struct SessionState {
+    account_id: u64,
+    budget: MessageBudget,
+    choked: bool,
+}
+
+/// ゲートウェイのセッション別と口座別のメッセージ予算。
+///
+/// セッションは最初のメッセージの口座に属するものとして記録する。
+#[derive(Debug, Clone)]
+pub struct SessionThrottles {
+    session_budget: MessageBudget,
+    account_budget: MessageBudget,
+    sessions: BTreeMap<u64, SessionState>,
+    accounts: BTreeMap<u64, MessageBudget>,
+}
+
+impl SessionThrottles {
+    /// セッションごとに `session_budget`、口座ごとに `account_budget` を掛ける。
+    #[must_use]
+    pub const fn new(session_budget: MessageBudget, account_budget: MessageBudget) -> Self {
+        Self {
+            session_budget,
+            account_budget,
+            sessions: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    fn session(&mut self, account_id: u64, session_id: u64) -> &mut SessionState {
+        let budget = self.session_budget;
+        self.sessions
+            .entry(session_id)
+            .or_insert_with(|| SessionState {
+                account_id,
+                budget,
+                choked: false,
+            })
+    }
+
+    /// セッションの予算を差し替える（使用量は引き継がない）。
+    pub fn set_session_budget(&mut self, account_id: u64, session_id: u64, budget: MessageBudget) {
+        self.session(account_id, session_id).budget = budget;
+    }
+
+    /// セッションのメッセージをすべて止める。
+    pub fn choke(&mut self, account_id: u64, session_id: u64) {
+        self.session(account_id, session_id).choked = true;
+    }
+
+    /// セッションの停止を解除する。
+    pub fn unchoke(&mut self, session_id: u64) {
+        if let Some(state) = self.sessions.get_mut(&session_id) {
+            state.choked = false;
+        }
+    }
+
+    /// セッションが止められているか。
+    #[must_use]
+    pub fn is_choked(&self, session_id: u64) -> bool {
+        self.sessions.get(&session_id).is_some_and(|s| s.choked)
+    }
+
+    /// セッションが `now_ns` のバーストの時間幅で使った重み。
+    #[must_use]
+    pub fn session_used_at(&self, session_id: u64, now_ns: u64) -> u32 {
+        self.sessions
+            .get(&session_id)
+            .map_or(0, |s| s.budget.used_at(now_ns))
+    }
+
+    /// 口座が `now_ns` のバーストの時間幅で使った重み（全セッションの合計）。
+    #[must_use]
+    pub fn account_used_at(&self, account_id: u64, now_ns: u64) -> u32 {
+        self.accounts
+            .get(&account_id)
+            .map_or(0, |b| b.used_at(now_ns))
+    }
+
+    /// 口座のセッション（セッション ID 順）。
+    pub fn sessions_of(&self, account_id: u64) -> impl Iterator<Item = u64> + '_ {
+        self.sessions
+            .iter()
+            .filter(move |(_, s)| s.account_id == account_id)
+            .map(|(&id, _)| id)
+    }
+
+    /// セッションの流量と停止を消去する（ログアウト時など）。
+    pub fn remove_session(&mut self, session_id: u64) {
+        self.sessions.remove(&session_id);
+    }
+
+    /// `session_id` から `kind` のメッセージを 1 件送れるか。
+    ///
+    /// # Errors
+    ///
+    /// セッションが止められていれば [`RiskReject::SessionChoked`]、セッション
+    /// または口座の予算を超えるなら [`RiskReject::MessageBudgetExceeded`]。
+    pub fn check(
+        &self,
+        account_id: u64,
+        session_id: u64,
+        kind: MessageKind,
+        now_ns: u64,
+    ) -> Result<(), RiskReject> {
+        match self.sessions.get(&session_id) {
+            Some(s) if s.choked => return Err(RiskReject::SessionChoked { session_id }),
+            Some(s) => s.budget.check(kind, now_ns)?,
+            None => self.session_budget.check(kind, now_ns)?,
+        }
+        self.accounts
+            .get(&account_id)
+            .unwrap_or(&self.account_budget)
+            .check(kind, now_ns)
+    }
+
+    /// `session_id` から送った `kind` のメッセージを数える。
+    pub fn record(&mut self, account_id: u64, session_id: u64, kind: MessageKind, now_ns: u64) {
+        self.session(account_id, session_id)
+            .budget
+            .record(kind, now_ns);
+        let budget = self.account_budget;
+        self.accounts
+            .entry(account_id)
+            .or_insert(budget)
+            .record(kind, now_ns);
+    }
+
+    /// 判定し、通れば数える。訂正・取消はこちらを使う。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::check`] と同じ（拒否したメッセージは数えない）。
+    pub fn try_send(
+        &mut self,
+        account_id: u64,
+        session_id: u64,
+        kind: MessageKind,
+        now_ns: u64,
+    ) -> Result<(), RiskReject> {
+        self.check(account_id, session_id, kind, now_ns)?;
+        self.record(account_id, session_id, kind, now_ns);
+        Ok(())
+    }
+
+    /// 予算を確認してから `checker` で新規注文を判定し、通った注文だけを
+    /// 数える。
+    ///
+    /// # Errors
+    ///
+    /// [`Self::check`] の拒否、それ以外は [`PreTradeChecker::check_order`] の
+    /// 拒否理由。
+    pub fn check_order(
+        &mut self,
+        account_id: u64,
+        session_id: u64,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let now = order.timestamp_ns;
+        self.check(account_id, session_id, MessageKind::New, now)?;
+        checker.check_order(order, position)?;
+        self.record(account_id, session_id, MessageKind::New, now);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+
+    fn limiter(penalty: CancelPenalty) -> CancelRateLimiter {
+        CancelRateLimiter::new(CancelRateConfig {
+            max_cancels: 2,
+            window_ns: 100,
+            sustained: RateWindow::DISABLED,
+            penalty,
+        })
+    }
+
+    #[test]
+    fn rejects_excess_cancels_per_account() {
+        let mut l = limiter(CancelPenalty::RejectCancel);
+        assert!(l.check_cancel(1, 0).is_ok());
+        assert!(l.check_cancel(1, 10).is_ok());
+        assert_eq!(
+            l.check_cancel(1, 20),
+            Err(RiskReject::CancelRateExceeded { count: 2, limit: 2 })
+        );
+        // 別口座・次の時間幅は別枠。新規注文は止めない。
+        assert!(l.check_cancel(2, 20).is_ok());
+        assert!(l.check_order(1, 20).is_ok());
+        assert!(l.check_cancel(1, 100).is_ok());
+        assert_eq!(l.count_at(1, 100), 1);
+        assert_eq!(
+            RiskReject::CancelRateExceeded { count: 2, limit: 2 }.limit_field(),
+            None
+        );
+    }
+
+    #[test]
+    fn penalty_blocks_new_orders() {
+        let mut l = limiter(CancelPenalty::BlockOrders { cooldown_ns: 1_000 });
+        for t in 0..2 {
+            assert!(l.check_cancel(1, t).is_ok());
+        }
+        assert!(l.check_cancel(1, 5).is_err());
+        assert_eq!(
+            l.check_order(1, 500),
+            Err(RiskReject::CancelRatePenalty { until_ns: 1_005 })
+        );
+        assert!(l.check_order(2, 500).is_ok());
+        // 停止中の超過で期間は延びない。取消は次の時間幅から通る。
+        assert!(l.check_cancel(1, 50).is_err());
+        assert_eq!(l.penalty_until(1, 50), Some(1_005));
+        assert!(l.check_cancel(1, 200).is_ok());
+        assert!(l.check_order(1, 1_005).is_ok());
+        l.check_cancel(1, 210).unwrap();
+        assert!(l.check_cancel(1, 220).is_err());
+        l.release(1);
+        assert_eq!(l.penalty_until(1, 220), None);
+        assert!(CancelRateLimiter::new(CancelRateConfig {
+            max_cancels: 0,
+            ..CancelRateConfig::default()
+        })
+        .check_cancel(1, 0)
+        .is_ok());
+    }
+
+    #[test]
+    fn weighted_budget_across_kinds() {
+        let weights = MessageWeights {
+            new: 1,
+            amend: 2,
+            cancel: 3,
+        };
+        let mut b = MessageBudget::new(5, 100, weights);
+        b.try_send(MessageKind::New, 0).unwrap();
+        b.try_send(MessageKind::Amend, 10).unwrap();
+        assert_eq!(
+            b.try_send(MessageKind::Cancel, 20),
+            Err(RiskReject::MessageBudgetExceeded {
+                used: 3,
+                weight: 3,
+                limit: 5,
+            })
+        );
+        assert_eq!(b.used_at(20), 3);
+        b.try_send(MessageKind::Amend, 30).unwrap();
+        assert!(b.check(MessageKind::New, 40).is_err());
+        assert!(b.try_send(MessageKind::Cancel, 100).is_ok());
+        assert_eq!(b.used_at(100), 3);
+        assert!(MessageBudget::unlimited()
+            .check(MessageKind::Cancel, 0)
+            .is_ok());
+    }
+
+    #[test]
+    fn sustained_thresholds() {
+        let mut b = MessageBudget::new(2, 100, MessageWeights::default())
+            .with_sustained(RateWindow::new(3, 1_000));
+        b.try_send(MessageKind::New, 0).unwrap();
+        b.try_send(MessageKind::Cancel, 10).unwrap();
+        b.try_send(MessageKind::Amend, 100).unwrap();
+        assert_eq!(
+            b.try_send(MessageKind::New, 200),
+            Err(RiskReject::MessageBudgetExceeded {
+                used: 3,
+                weight: 1,
+                limit: 3,
+            })
+        );
+        assert!(b.try_send(MessageKind::New, 1_000).is_ok());
+
+        let mut l = CancelRateLimiter::new(CancelRateConfig {
+            max_cancels: 2,
+            window_ns: 100,
+            sustained: RateWindow::new(3, 1_000),
+            penalty: CancelPenalty::RejectCancel,
+        });
+        for t in [0, 10, 100] {
+            l.check_cancel(1, t).unwrap();
+        }
+        assert_eq!(
+            l.check_cancel(1, 200),
+            Err(RiskReject::CancelRateExceeded { count: 3, limit: 3 })
+        );
+        assert!(l.check_cancel(1, 1_000).is_ok());
+    }
+
+    #[test]
+    fn budget_wraps_checker() {
+        let checker = PreTradeChecker::new(RiskLimits {
+            max_order_size: 10,
+            ..RiskLimits::default()
+        });
+        let mut b = MessageBudget::new(1, 1_000, MessageWeights::default());
+        let mut order = OrderBuilder::bid(100, 20).build();
+        // 事前チェックで拒否された注文は数えない。
+        assert!(matches!(
+            b.check_order(&checker, &order, None),
+            Err(RiskReject::OrderSizeTooLarge { .. })
+        ));
+        order.quantity = 5;
+        assert!(b.check_order(&checker, &order, None).is_ok());
+        assert!(matches!(
+            b.check_order(&checker, &order, None),
+            Err(RiskReject::MessageBudgetExceeded { used: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn sessions_are_throttled_independently() {
+        let mut t = SessionThrottles::new(
+            MessageBudget::new(2, 100, MessageWeights::default()),
+            MessageBudget::new(3, 100, MessageWeights::default()),
+        );
+        // セッション 10 が暴走しても、同じ口座のセッション 11 は通る。
+        t.try_send(1, 10, MessageKind::New, 0).unwrap();
+        t.try_send(1, 10, MessageKind::Cancel, 1).unwrap();
+        assert!(matches!(
+            t.try_send(1, 10, MessageKind::New, 2),
+            Err(RiskReject::MessageBudgetExceeded { limit: 2, .. })
+        ));
+        t.try_send(1, 11, MessageKind::New, 3).unwrap();
+        // 口座全体の予算も掛かる。
+        assert!(matches!(
+            t.try_send(1, 11, MessageKind::New, 4),
+            Err(RiskReject::MessageBudgetExceeded {
+                used: 3,
+                limit: 3,
+                ..
+            })
+        ));
+        assert_eq!(t.account_used_at(1, 4), 3);
+        assert_eq!(t.session_used_at(10, 4), 2);
+        assert!(t.try_send(2, 20, MessageKind::New, 4).is_ok());
+        assert_eq!(t.sessions_of(1).collect::<Vec<_>>(), [10, 11]);
+
+        t.choke(1, 10);
+        assert_eq!(
+            t.check(1, 10, MessageKind::Cancel, 100),
+            Err(RiskReject::SessionChoked { session_id: 10 })
+        );
+        assert!(t.check(1, 11, MessageKind::Cancel, 100).is_ok());
+        t.unchoke(10);
+        assert!(!t.is_choked(10));
+        assert!(t.check(1, 10, MessageKind::Cancel, 100).is_ok());
+    }
+
+    #[test]
+    fn session_budget_override_and_checker() {
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        let mut t = SessionThrottles::new(MessageBudget::unlimited(), MessageBudget::unlimited());
+        t.set_session_budget(
+            1,
+            10,
+            MessageBudget::new(1, 1_000, MessageWeights::default()),
+        );
+        let order = OrderBuilder::ask(100, 1).build();
+        assert!(t.check_order(1, 10, &checker, &order, None).is_ok());
+        assert!(t.check_order(1, 10, &checker, &order, None).is_err());
+        assert!(t.check_order(1, 11, &checker, &order, None).is_ok());
+        t.remove_session(10);
+        assert!(t.check_order(1, 10, &checker, &order, None).is_ok());
+    }
+}
@@ -0,0 +1,227 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 署名付きの判定トークン。
+//!
+//! 受理した判定ごとに、判定 ID と注文フィールド・制限バージョンに対する
+//! HMAC-SHA256（先頭 128 ビット）からなる [`DecisionToken`] を発行する。
+//! マッチングエンジン側は同じ鍵の [`TokenSigner`] で
+//! [`TokenSigner::verify`] を呼び、このクレートの判定を経ていない注文
+//! （トークンが無い・注文内容が書き換えられた注文）を拒否できる。
+//!
+//! 判定 ID の再利用（リプレイ）の検出は検証側の責任とする。
+
+use alice_ledger::{Order, OrderType, Position, Side, TimeInForce};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::crypto::{ct_eq, HmacKey};
+
+// ---------------------------------------------------------------------------
+// DecisionToken
+// ---------------------------------------------------------------------------
+
+/// トークンに含める MAC の長さ（バイト）。
+pub const MAC_LEN: usize = 16;
+
+/// 受理判定の証明。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecisionToken {
+    /// 発行者内で一意な判定 ID。
+    pub decision_id: u64,
+    /// HMAC-SHA256 の先頭 [`MAC_LEN`] バイト。
+    pub mac: [u8; MAC_LEN],
+}
+
+impl DecisionToken {
+    /// 直列化した長さ（バイト）。
+    pub const LEN: usize = 8 + MAC_LEN;
+
+    /// 判定 ID（リトルエンディアン）と MAC を連結したバイト列。
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0_u8; Self::LEN];
+        out[..8].copy_from_slice(&self.decision_id.to_le_bytes());
+        out[8..].copy_from_slice(&self.mac);
+        out
+    }
+
+    /// [`Self::to_bytes`] の逆変換。
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; Self::LEN]) -> Self {
+        let mut id = [0_u8; 8];
+        id.copy_from_slice(&bytes[..8]);
+        let mut mac = [0_u8; MAC_LEN];
+        mac.copy_from_slice(&bytes[8..]);
+        Self {
+            decision_id: u64::from_le_bytes(id),
+            mac,
+        }
+    }
+}
+
+/// MAC の対象: 判定 ID・注文フィールド・制限バージョン。
+fn message(decision_id: u64, order: &Order, limit_version: u64) -> [u8; 59] {
+    let mut m = [0_u8; 59];
+    m[0..8].copy_from_slice(&decision_id.to_le_bytes());
+    m[8..16].copy_from_slice(&order.id.0.to_le_bytes());
+    m[16..24].copy_from_slice(&order.price.to_le_bytes());
+    m[24..32].copy_from_slice(&order.quantity.to_le_bytes());
+    m[32..40].copy_from_slice(&order.filled_quantity.to_le_bytes());
+    m[40..48].copy_from_slice(&order.timestamp_ns.to_le_bytes());
+    m[48..56].copy_from_slice(&limit_version.to_le_bytes());
+    m[56] = match order.side {
+        Side::Bid => 0,
+        Side::Ask => 1,
+    };
+    m[57] = match order.order_type {
+        OrderType::Limit => 0,
+        OrderType::Market => 1,
+    };
+    m[58] = match order.time_in_force {
+        TimeInForce::GTC => 0,
+        TimeInForce::IOC => 1,
+        TimeInForce::FOK => 2,
+    };
+    m
+}
+
+// ---------------------------------------------------------------------------
+// TokenSigner
+// ---------------------------------------------------------------------------
+
+/// 共有鍵で判定トークンを発行・検証する。
+#[derive(Clone)]
+pub struct TokenSigner {
+    key: HmacKey,
+    next_id: u64,
+}
+
+impl TokenSigner {
+    /// 共有鍵 `key` から作成。判定 ID は 1 から振る。
+    #[must_use]
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: HmacKey::new(key),
+            next_id: 1,
+        }
+    }
+
+    /// 次に発行する判定 ID を設定する（再起動後に ID を重複させないため）。
+    pub const fn set_next_id(&mut self, next_id: u64) {
+        self.next_id = next_id;
+    }
+
+    /// 次に発行する判定 ID。
+    #[must_use]
+    pub const fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    fn mac(&self, decision_id: u64, order: &Order, limit_version: u64) -> [u8; MAC_LEN] {
+        let full = self.key.mac(&message(decision_id, order, limit_version));
+        let mut mac = [0_u8; MAC_LEN];
+        mac.copy_from_slice(&full[..MAC_LEN]);
+        mac
+    }
+
+    /// `limit_version` の制限で受理した `order` のトークンを発行する。
+    pub fn sign(&mut self, order: &Order, limit_version: u64) -> DecisionToken {
+        let decision_id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        DecisionToken {
+            decision_id,
+            mac: self.mac(decision_id, order, limit_version),
+        }
+    }
+
+    /// `token` が `order` と `limit_version` に対して発行されたものか。
+    ///
+    /// MAC の比較は一致位置によらず一定時間で行う。
+    #[must_use]
+    pub fn verify(&self, token: &DecisionToken, order: &Order, limit_version: u64) -> bool {
+        ct_eq(
+            &self.mac(token.decision_id, order, limit_version),
+            &token.mac,
+        )
+    }
+
+    /// `checker` で判定し、受理した場合はトークンを発行する。
+    ///
+    /// # Errors
+    ///
+    /// [`PreTradeChecker::check_order`] の拒否理由。
+    pub fn check_order(
+        &mut self,
+        checker: &PreTradeChecker,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<DecisionToken, RiskReject> {
+        checker.check_order(order, position)?;
+        Ok(self.sign(order, checker.limit_version()))
+    }
+}
+
+impl core::fmt::Debug for TokenSigner {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TokenSigner")
+            .field("next_id", &self.next_id)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+
+    fn order() -> Order {
+        OrderBuilder::bid(50_000, 10).id(42).at(1_000).build()
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let mut signer = TokenSigner::new(b"secret");
+        let o = order();
+        let token = signer.sign(&o, 3);
+        assert_eq!(token.decision_id, 1);
+        assert_eq!(signer.next_id(), 2);
+        assert!(signer.verify(&token, &o, 3));
+        assert_eq!(DecisionToken::from_bytes(&token.to_bytes()), token);
+
+        // 注文内容・制限バージョン・鍵が異なれば検証に失敗する。
+        let mut tampered = o.clone();
+        tampered.quantity = 11;
+        assert!(!signer.verify(&token, &tampered, 3));
+        assert!(!signer.verify(&token, &o, 4));
+        assert!(!TokenSigner::new(b"other").verify(&token, &o, 3));
+        let forged = DecisionToken {
+            decision_id: 2,
+            ..token
+        };
+        assert!(!signer.verify(&forged, &o, 3));
+    }
+
+    #[test]
+    fn tokens_only_for_accepted_orders() {
+        let mut signer = TokenSigner::new(b"secret");
+        signer.set_next_id(100);
+        let mut checker = PreTradeChecker::new(RiskLimits::default());
+        let o = order();
+        let token = signer.check_order(&checker, &o, None).unwrap();
+        assert_eq!(token.decision_id, 100);
+        assert!(signer.verify(&token, &o, 0));
+        checker.trip_circuit_breaker();
+        assert_eq!(
+            signer.check_order(&checker, &o, None),
+            Err(RiskReject::CircuitBreakerTripped)
+        );
+        assert_eq!(signer.next_id(), 101);
+    }
+}
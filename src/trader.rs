@@ -0,0 +1,242 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 口座内のトレーダー（セッション）別サブアカウント制限。
+//!
+//! 口座の [`PreTradeChecker`] に加えて、トレーダー ID ごとに独自の
+//! [`RiskLimits`] を持つチェッカーを置く。注文はまずトレーダーの制限、次に
+//! 口座の制限で判定する。トレーダーのサーキットブレーカーや日次損失上限は
+//! そのトレーダーだけを止め、口座の他のトレーダーの注文フローは止めない。
+
+use alloc::collections::BTreeMap;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// SubAccountReject
+// ---------------------------------------------------------------------------
+
+/// サブアカウント付き口座での拒否理由。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubAccountReject {
+    /// 登録されていないトレーダー。
+    UnknownTrader { trader_id: u64 },
+    /// トレーダーの制限に違反。
+    Trader { trader_id: u64, reject: RiskReject },
+    /// 口座全体の制限に違反。
+    Account(RiskReject),
+}
+
+impl SubAccountReject {
+    /// 元の [`RiskReject`]（未登録トレーダーは `None`）。
+    #[must_use]
+    pub const fn reject(&self) -> Option<&RiskReject> {
+        match self {
+            Self::UnknownTrader { .. } => None,
+            Self::Trader { reject, .. } | Self::Account(reject) => Some(reject),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SubAccounts
+// ---------------------------------------------------------------------------
+
+/// 口座のチェッカーとトレーダー別チェッカーの組。
+pub struct SubAccounts {
+    account: PreTradeChecker,
+    traders: BTreeMap<u64, PreTradeChecker>,
+}
+
+impl SubAccounts {
+    /// 口座全体の制限から作成。
+    #[must_use]
+    pub const fn new(account_limits: RiskLimits) -> Self {
+        Self {
+            account: PreTradeChecker::new(account_limits),
+            traders: BTreeMap::new(),
+        }
+    }
+
+    /// トレーダーを登録する（既存のトレーダーは状態ごと置き換え）。
+    pub fn add_trader(&mut self, trader_id: u64, limits: RiskLimits) {
+        self.traders.insert(trader_id, PreTradeChecker::new(limits));
+    }
+
+    /// トレーダーを削除する。
+    pub fn remove_trader(&mut self, trader_id: u64) -> Option<PreTradeChecker> {
+        self.traders.remove(&trader_id)
+    }
+
+    /// 口座全体のチェッカー。
+    #[must_use]
+    pub const fn account(&self) -> &PreTradeChecker {
+        &self.account
+    }
+
+    /// 口座全体のチェッカー（可変）。
+    pub const fn account_mut(&mut self) -> &mut PreTradeChecker {
+        &mut self.account
+    }
+
+    /// トレーダーのチェッカー。
+    #[must_use]
+    pub fn trader(&self, trader_id: u64) -> Option<&PreTradeChecker> {
+        self.traders.get(&trader_id)
+    }
+
+    /// トレーダーのチェッカー（可変）。
+    pub fn trader_mut(&mut self, trader_id: u64) -> Option<&mut PreTradeChecker> {
+        self.traders.get_mut(&trader_id)
+    }
+
+    /// 登録済みのトレーダー数。
+    #[must_use]
+    pub fn trader_count(&self) -> usize {
+        self.traders.len()
+    }
+
+    /// トレーダーの制限、次に口座の制限で注文をチェックする。
+    ///
+    /// `trader_position` はそのトレーダーの建玉、`account_position` は口座全体の
+    /// 建玉。
+    ///
+    /// # Errors
+    ///
+    /// 最初に違反した制限を [`SubAccountReject`] で返す。
+    pub fn check_order(
+        &self,
+        trader_id: u64,
+        order: &Order,
+        trader_position: Option<&Position>,
+        account_position: Option<&Position>,
+    ) -> Result<(), SubAccountReject> {
+        let trader = self
+            .traders
+            .get(&trader_id)
+            .ok_or(SubAccountReject::UnknownTrader { trader_id })?;
+        trader
+            .check_order(order, trader_position)
+            .map_err(|reject| SubAccountReject::Trader { trader_id, reject })?;
+        self.account
+            .check_order(order, account_position)
+            .map_err(SubAccountReject::Account)
+    }
+
+    /// 受理した注文をトレーダーと口座の未約定注文として記録する。
+    ///
+    /// 未登録のトレーダーの場合は口座のみ記録する。
+    pub fn on_order_accepted(&mut self, trader_id: u64, order: &Order) {
+        if let Some(trader) = self.traders.get_mut(&trader_id) {
+            trader.on_order_accepted(order);
+        }
+        self.account.on_order_accepted(order);
+    }
+
+    /// 約定・取消で終了した注文をトレーダーと口座の未約定注文から外す。
+    pub fn on_order_closed(&mut self, trader_id: u64, order_id: u64) {
+        if let Some(trader) = self.traders.get_mut(&trader_id) {
+            trader.on_order_closed(order_id);
+        }
+        self.account.on_order_closed(order_id);
+    }
+
+    /// P&L の変化をトレーダーと口座の日次損益に加算する。
+    pub fn record_pnl(&mut self, trader_id: u64, delta: i64) {
+        if let Some(trader) = self.traders.get_mut(&trader_id) {
+            let pnl = trader.daily_pnl().saturating_add(delta);
+            trader.update_daily_pnl(pnl);
+        }
+        let pnl = self.account.daily_pnl().saturating_add(delta);
+        self.account.update_daily_pnl(pnl);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+
+    fn order(id: u64, quantity: u64) -> Order {
+        OrderBuilder::bid(100, quantity).id(id).build()
+    }
+
+    fn desk() -> SubAccounts {
+        let mut desk = SubAccounts::new(RiskLimits::default());
+        let tight = RiskLimits {
+            max_order_size: 10,
+            max_daily_loss: -1_000,
+            ..RiskLimits::default()
+        };
+        desk.add_trader(1, tight.clone());
+        desk.add_trader(2, tight);
+        desk
+    }
+
+    #[test]
+    fn trader_limit_checked_first() {
+        let desk = desk();
+        assert!(desk.check_order(1, &order(1, 10), None, None).is_ok());
+        assert!(matches!(
+            desk.check_order(1, &order(1, 11), None, None),
+            Err(SubAccountReject::Trader {
+                trader_id: 1,
+                reject: RiskReject::OrderSizeTooLarge { .. }
+            })
+        ));
+        assert_eq!(
+            desk.check_order(9, &order(1, 1), None, None),
+            Err(SubAccountReject::UnknownTrader { trader_id: 9 })
+        );
+    }
+
+    #[test]
+    fn one_trader_halt_leaves_others_trading() {
+        let mut desk = desk();
+        desk.record_pnl(1, -1_000);
+        let err = desk.check_order(1, &order(1, 1), None, None).unwrap_err();
+        assert!(matches!(
+            err.reject(),
+            Some(RiskReject::DailyLossLimitHit { .. })
+        ));
+        assert!(desk.check_order(2, &order(2, 1), None, None).is_ok());
+        assert_eq!(desk.account().daily_pnl(), -1_000);
+
+        desk.trader_mut(2).unwrap().trip_circuit_breaker();
+        assert!(desk.check_order(2, &order(2, 1), None, None).is_err());
+        assert!(!desk.account().is_circuit_breaker_tripped());
+    }
+
+    #[test]
+    fn account_limits_still_apply() {
+        let mut desk = SubAccounts::new(RiskLimits {
+            max_open_orders: 1,
+            ..RiskLimits::default()
+        });
+        desk.add_trader(1, RiskLimits::default());
+        desk.add_trader(2, RiskLimits::default());
+        desk.on_order_accepted(1, &order(1, 1));
+        assert_eq!(desk.trader(1).unwrap().open_order_count(), 1);
+        assert!(matches!(
+            desk.check_order(2, &order(2, 1), None, None),
+            Err(SubAccountReject::Account(
+                RiskReject::MaxOpenOrdersReached { .. }
+            ))
+        ));
+        desk.on_order_closed(1, 1);
+        assert!(desk.check_order(2, &order(2, 1), None, None).is_ok());
+        assert_eq!(desk.trader_count(), 2);
+        assert!(desk.remove_trader(2).is_some());
+    }
+}
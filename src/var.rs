@@ -8,6 +8,8 @@
 //! ポートフォリオレベルのリスク制限に使用する。
 //! ヒストリカル法とパラメトリック法（正規分布仮定）の 2 手法を提供。
 
+use crate::parallel;
+
 // ---------------------------------------------------------------------------
 // HistoricalVaR
 // ---------------------------------------------------------------------------
@@ -54,6 +56,18 @@ impl HistoricalVaR {
         self.sorted = false;
     }
 
+    /// 過去シナリオごとのポートフォリオ損益（ticks）を一括追加。
+    ///
+    /// `moves[k][i]` はシナリオ `k` での銘柄 `i` の価格変動で、損益は
+    /// `Σ quantities[i] × moves[k][i]`（`i64` に飽和）。`moves[k]` が
+    /// `quantities` より短い場合、不足分の変動は 0 とみなす。`rayon`
+    /// フィーチャーではシナリオ単位・銘柄単位に並列に評価し、追加順は
+    /// シナリオ順のまま変わらない。
+    pub fn add_scenarios(&mut self, quantities: &[i64], moves: &[Vec<i64>]) {
+        let pnls = parallel::map(moves, |m| scenario_pnl(quantities, m));
+        self.add_returns(&pnls);
+    }
+
     /// サンプル数。
     #[must_use]
     pub const fn count(&self) -> usize {
@@ -74,6 +88,9 @@ impl HistoricalVaR {
         }
 
         if !self.sorted {
+            #[cfg(feature = "rayon")]
+            rayon::slice::ParallelSliceMut::par_sort_unstable(&mut self.returns[..]);
+            #[cfg(not(feature = "rayon"))]
             self.returns.sort_unstable();
             self.sorted = true;
         }
@@ -100,6 +117,22 @@ impl Default for HistoricalVaR {
     }
 }
 
+/// 1 シナリオのポートフォリオ損益。銘柄単位の積をチャンク順に合算する。
+fn scenario_pnl(quantities: &[i64], moves: &[i64]) -> i64 {
+    let len = quantities.len().min(moves.len());
+    let pnl = parallel::map_ranges(len, |range| {
+        quantities[range.clone()]
+            .iter()
+            .zip(&moves[range])
+            .fold(0_i128, |acc, (&q, &d)| {
+                acc.saturating_add(i128::from(q) * i128::from(d))
+            })
+    })
+    .into_iter()
+    .fold(0_i128, i128::saturating_add);
+    pnl.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
 // ---------------------------------------------------------------------------
 // ParametricVaR
 // ---------------------------------------------------------------------------
@@ -307,6 +340,26 @@ mod tests {
         assert!(v < 0, "All positive returns should give negative VaR: {v}");
     }
 
+    #[test]
+    fn historical_var_from_scenarios() {
+        // 3_000 銘柄 × 2_000 シナリオ。シナリオ k では銘柄 i が k 動き、
+        // 最後の銘柄だけ変動が欠けている。
+        let quantities: Vec<i64> = (0..3_000).map(|i| i % 7 - 3).collect();
+        let net: i64 = quantities[..2_999].iter().sum();
+        let moves: Vec<Vec<i64>> = (0..2_000).map(|k| vec![k - 1_000; 2_999]).collect();
+        let mut var = HistoricalVaR::new();
+        var.add_scenarios(&quantities, &moves);
+        assert_eq!(var.count(), 2_000);
+        assert_eq!(var.returns[0], net * -1_000);
+        assert_eq!(var.returns[1_999], net * 999);
+        assert_eq!(
+            var.var_at_confidence(1.0),
+            Some(-(net * 999).min(net * -1_000))
+        );
+        // -6 × i64::MAX は飽和する。
+        assert_eq!(scenario_pnl(&quantities, &[i64::MAX; 4]), i64::MIN);
+    }
+
     // -----------------------------------------------------------------------
     // ParametricVaR
     // -----------------------------------------------------------------------
@@ -0,0 +1,355 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! イベントソーシングされたチェッカー状態のログ（WAL）、チェックポイント、
+//! コンパクション。
+//!
+//! [`EventLog`] は [`ReplayEvent`] を追記しながらライブのチェッカーに適用し、
+//! 一定イベント数ごとに [`Checkpoint`]（制限値と [`CheckerState`]）を取る。
+//! 保持数を超えた古いチェックポイントと、それより前のイベントは捨てる。
+//! 復旧は最新のチェックポイントからその後のイベントだけを再適用するため、
+//! イベントが数百万件に達する取引日でも復旧時間はチェックポイント間隔で
+//! 抑えられる。
+//!
+//! チェックポイントは制限値の変更履歴を含まない（[`CheckerState`] を参照）。永続化する場合はチェックポイントを
+//! [`SnapshotCodec`](crate::SnapshotCodec) で封印し、イベントとともに保存する。
+
+use alloc::collections::VecDeque;
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::limit::RiskLimits;
+use crate::replay::{apply_event, ReplayEvent};
+use crate::snapshot::CheckerState;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// WalConfig / Checkpoint
+// ---------------------------------------------------------------------------
+
+/// チェックポイントとコンパクションの設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalConfig {
+    /// チェックポイントを取るイベント数の間隔。0 で自動チェックポイント無し。
+    pub checkpoint_interval: u64,
+    /// 保持するチェックポイント数（1 以上に切り上げ）。
+    pub retained_checkpoints: usize,
+}
+
+impl Default for WalConfig {
+    fn default() -> Self {
+        Self {
+            checkpoint_interval: 100_000,
+            retained_checkpoints: 2,
+        }
+    }
+}
+
+/// ある時点のチェッカー状態。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// このチェックポイントの後に続く最初のイベントの通し番号。
+    pub seq: u64,
+    /// その時点の制限値。
+    pub limits: RiskLimits,
+    /// その時点のカウンタ。
+    pub state: CheckerState,
+}
+
+impl Checkpoint {
+    fn capture(seq: u64, checker: &PreTradeChecker) -> Self {
+        Self {
+            seq,
+            limits: checker.limits().clone(),
+            state: checker.state(),
+        }
+    }
+}
+
+/// `checkpoint` から `tail`（その後のイベント）を再適用してチェッカーを復元する。
+///
+/// 記録済みの [`ReplayEvent::ApplyLimits`] は再適用する。
+#[must_use]
+pub fn recover<'a, I>(checkpoint: &Checkpoint, tail: I) -> PreTradeChecker
+where
+    I: IntoIterator<Item = &'a ReplayEvent>,
+{
+    let mut checker = PreTradeChecker::new(checkpoint.limits.clone());
+    checker.restore_state(&checkpoint.state);
+    for event in tail {
+        apply_event(&mut checker, event, true);
+    }
+    checker
+}
+
+// ---------------------------------------------------------------------------
+// EventLog
+// ---------------------------------------------------------------------------
+
+/// チェックポイント付きのイベントログとライブのチェッカー。
+pub struct EventLog {
+    config: WalConfig,
+    checker: PreTradeChecker,
+    /// 保持中のイベント。先頭の通し番号は最古のチェックポイントの `seq`。
+    events: VecDeque<ReplayEvent>,
+    /// 最新以外の保持中のチェックポイント（古い順）。
+    older: VecDeque<Checkpoint>,
+    latest: Checkpoint,
+    next_seq: u64,
+}
+
+impl EventLog {
+    /// `limits` で初期化したチェッカーと、通し番号 0 のチェックポイントで作成。
+    #[must_use]
+    pub fn new(limits: RiskLimits, config: WalConfig) -> Self {
+        let checker = PreTradeChecker::new(limits);
+        let latest = Checkpoint::capture(0, &checker);
+        Self {
+            config,
+            checker,
+            events: VecDeque::new(),
+            older: VecDeque::new(),
+            latest,
+            next_seq: 0,
+        }
+    }
+
+    /// 保存済みの `checkpoint` と、その後のイベント `tail` から再開する。
+    ///
+    /// `checkpoint` が再開後の最古かつ最新のチェックポイントになる。
+    #[must_use]
+    pub fn resume<I>(checkpoint: Checkpoint, tail: I, config: WalConfig) -> Self
+    where
+        I: IntoIterator<Item = ReplayEvent>,
+    {
+        let events: VecDeque<ReplayEvent> = tail.into_iter().collect();
+        let checker = recover(&checkpoint, &events);
+        let next_seq = checkpoint.seq + events.len() as u64;
+        Self {
+            config,
+            checker,
+            events,
+            older: VecDeque::new(),
+            latest: checkpoint,
+            next_seq,
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &WalConfig {
+        &self.config
+    }
+
+    /// ライブのチェッカー。
+    #[must_use]
+    pub const fn checker(&self) -> &PreTradeChecker {
+        &self.checker
+    }
+
+    /// 次に追記するイベントの通し番号（追記済みイベントの総数）。
+    #[must_use]
+    pub const fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// イベントを追記してチェッカーに適用する。注文イベントは判定結果を返す。
+    ///
+    /// 最新のチェックポイントから `checkpoint_interval` 件に達したら
+    /// チェックポイントを取る。
+    pub fn append(&mut self, event: ReplayEvent) -> Option<Result<(), RiskReject>> {
+        let result = apply_event(&mut self.checker, &event, true);
+        self.events.push_back(event);
+        self.next_seq += 1;
+        let interval = self.config.checkpoint_interval;
+        if interval > 0 && self.next_seq - self.latest.seq >= interval {
+            self.checkpoint();
+        }
+        result
+    }
+
+    /// 現時点のチェックポイントを取り、保持数を超えた古いチェックポイントと
+    /// 不要になったイベントを捨てる。
+    pub fn checkpoint(&mut self) -> &Checkpoint {
+        if self.latest.seq != self.next_seq {
+            let latest = Checkpoint::capture(self.next_seq, &self.checker);
+            self.older
+                .push_back(core::mem::replace(&mut self.latest, latest));
+        }
+        self.compact();
+        &self.latest
+    }
+
+    fn compact(&mut self) {
+        let retained = self.config.retained_checkpoints.max(1);
+        while self.older.len() >= retained {
+            self.older.pop_front();
+        }
+        let oldest = self.older.front().map_or(self.latest.seq, |c| c.seq);
+        let drop = (oldest - self.first_seq()) as usize;
+        self.events.drain(..drop);
+    }
+
+    /// 保持中の最初のイベントの通し番号。
+    fn first_seq(&self) -> u64 {
+        self.next_seq - self.events.len() as u64
+    }
+
+    /// 最新のチェックポイント。
+    #[must_use]
+    pub const fn latest_checkpoint(&self) -> &Checkpoint {
+        &self.latest
+    }
+
+    /// 保持中のチェックポイント（古い順）。
+    pub fn checkpoints(&self) -> impl Iterator<Item = &Checkpoint> {
+        self.older.iter().chain(core::iter::once(&self.latest))
+    }
+
+    /// 保持中のイベント数。
+    #[must_use]
+    pub fn retained_events(&self) -> usize {
+        self.events.len()
+    }
+
+    /// 通し番号 `seq` 以降の保持中のイベント。`seq` が既に捨てられていれば
+    /// `None`。
+    #[must_use]
+    pub fn events_since(&self, seq: u64) -> Option<impl Iterator<Item = &ReplayEvent>> {
+        let first = self.first_seq();
+        if seq < first || seq > self.next_seq {
+            return None;
+        }
+        Some(self.events.iter().skip((seq - first) as usize))
+    }
+
+    /// 最新のチェックポイントとその後のイベントから復元したチェッカー。
+    #[must_use]
+    pub fn recover(&self) -> PreTradeChecker {
+        let skip = (self.latest.seq - self.first_seq()) as usize;
+        recover(&self.latest, self.events.iter().skip(skip))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::OrderBuilder;
+    use alloc::string::String;
+
+    fn log(interval: u64) -> EventLog {
+        EventLog::new(
+            RiskLimits::default(),
+            WalConfig {
+                checkpoint_interval: interval,
+                retained_checkpoints: 2,
+            },
+        )
+    }
+
+    fn day() -> alloc::vec::Vec<ReplayEvent> {
+        let mut events = alloc::vec::Vec::new();
+        for i in 0..10_i64 {
+            events.push(ReplayEvent::Accepted {
+                order: OrderBuilder::bid(1_000, 1).id(i as u64).build(),
+                position: None,
+            });
+            events.push(ReplayEvent::Pnl(-i * 100));
+            if i == 6 {
+                events.push(ReplayEvent::ApplyLimits {
+                    limits: RiskLimits {
+                        max_order_size: 5,
+                        ..RiskLimits::default()
+                    },
+                    author: String::from("ops"),
+                    timestamp_ns: 0,
+                });
+                events.push(ReplayEvent::TripBreaker);
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn checkpoints_bound_retained_events() {
+        let mut wal = log(4);
+        for event in day() {
+            wal.append(event);
+        }
+        assert_eq!(wal.next_seq(), 22);
+        let seqs: alloc::vec::Vec<u64> = wal.checkpoints().map(|c| c.seq).collect();
+        assert_eq!(seqs, [16, 20]);
+        // 最古のチェックポイント以降だけを保持する。
+        assert_eq!(wal.retained_events(), 6);
+        assert!(wal.events_since(15).is_none());
+        assert_eq!(wal.events_since(20).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn recovery_matches_live_state() {
+        let mut wal = log(4);
+        for event in day() {
+            wal.append(event);
+            let recovered = wal.recover();
+            assert_eq!(recovered.state(), wal.checker().state());
+            assert_eq!(recovered.limits(), wal.checker().limits());
+        }
+        assert_eq!(wal.checker().limits().max_order_size, 5);
+        assert!(wal.checker().is_circuit_breaker_tripped());
+    }
+
+    #[test]
+    fn recover_from_older_checkpoint() {
+        let mut wal = log(4);
+        for event in day() {
+            wal.append(event);
+        }
+        let oldest = wal.checkpoints().next().unwrap().clone();
+        let recovered = recover(&oldest, wal.events_since(oldest.seq).unwrap());
+        assert_eq!(recovered.state(), wal.checker().state());
+    }
+
+    #[test]
+    fn manual_checkpoints() {
+        let mut wal = log(0);
+        for event in day() {
+            wal.append(event);
+        }
+        assert_eq!(wal.retained_events(), 22);
+        assert_eq!(wal.checkpoint().seq, 22);
+        assert_eq!(wal.checkpoint().seq, 22);
+        assert_eq!(wal.checkpoints().count(), 2);
+        assert_eq!(wal.retained_events(), 22);
+        wal.append(ReplayEvent::Closed { order_id: 3 });
+        wal.checkpoint();
+        assert_eq!(wal.retained_events(), 1);
+        assert_eq!(wal.recover().state(), wal.checker().state());
+        assert_eq!(wal.config().checkpoint_interval, 0);
+    }
+
+    #[test]
+    fn resume_from_saved_checkpoint() {
+        let mut wal = log(4);
+        let events = day();
+        for event in events.iter().take(18).cloned() {
+            wal.append(event);
+        }
+        let saved = wal.latest_checkpoint().clone();
+        let tail: alloc::vec::Vec<ReplayEvent> =
+            wal.events_since(saved.seq).unwrap().cloned().collect();
+        let mut resumed = EventLog::resume(saved, tail, *wal.config());
+        assert_eq!(resumed.next_seq(), 18);
+        for event in events.into_iter().skip(18) {
+            wal.append(event.clone());
+            resumed.append(event);
+        }
+        assert_eq!(resumed.checker().state(), wal.checker().state());
+        assert_eq!(resumed.latest_checkpoint(), wal.latest_checkpoint());
+    }
+}
@@ -0,0 +1,251 @@
+/*
+    ALICE-Risk
+    Copyright (C) 2026 Moroya Sakamoto
+*/
+
+//! 市場データの鮮度の監視。
+//!
+//! 価格カラーや証拠金の判定が古い価格に対して行われると、判定しないより悪い。
+//! [`MarketDataWatchdog`] は銘柄ごとに参照価格・値洗い価格の最終更新時刻を
+//! 記録し、閾値より古くなった銘柄の注文を自動的に止める（フェイルクローズ）。
+//! 止め方は [`StaleDataMode`] で選び、建玉を減らす注文だけを通すこともできる。
+//! 一度も更新を受けていない銘柄も古いものとして扱う。
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use alice_ledger::{Order, Position};
+
+use crate::check::{PreTradeChecker, RiskReject};
+use crate::instrument::ExpiryRestriction;
+
+extern crate alloc;
+
+// ---------------------------------------------------------------------------
+// StalenessConfig
+// ---------------------------------------------------------------------------
+
+/// 市場データが古い銘柄の注文の扱い。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StaleDataMode {
+    /// すべて拒否する。
+    #[default]
+    Reject,
+    /// 建玉を減らす注文（建玉の範囲内）だけを通す。
+    ReduceOnly,
+}
+
+/// 鮮度監視の設定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalenessConfig {
+    /// 最終更新からの許容時間（ns）。
+    pub max_age_ns: u64,
+    /// 古い銘柄の注文の扱い。
+    pub mode: StaleDataMode,
+}
+
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self {
+            max_age_ns: 5_000_000_000, // 5 秒
+            mode: StaleDataMode::Reject,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MarketDataWatchdog
+// ---------------------------------------------------------------------------
+
+/// 銘柄別の市場データの鮮度監視。
+#[derive(Debug, Clone, Default)]
+pub struct MarketDataWatchdog {
+    config: StalenessConfig,
+    /// 銘柄 → 許容時間（ns）の個別設定。
+    max_age: BTreeMap<u64, u64>,
+    /// 銘柄 → 最終更新時刻（ns）。
+    last_update: BTreeMap<u64, u64>,
+}
+
+impl MarketDataWatchdog {
+    /// 新規作成。
+    #[must_use]
+    pub const fn new(config: StalenessConfig) -> Self {
+        Self {
+            config,
+            max_age: BTreeMap::new(),
+            last_update: BTreeMap::new(),
+        }
+    }
+
+    /// 設定。
+    #[must_use]
+    pub const fn config(&self) -> &StalenessConfig {
+        &self.config
+    }
+
+    /// 銘柄の許容時間を個別に設定する（流動性の低い銘柄など）。
+    pub fn set_max_age(&mut self, symbol_hash: u64, max_age_ns: u64) {
+        self.max_age.insert(symbol_hash, max_age_ns);
+    }
+
+    /// 銘柄の許容時間（ns）。
+    #[must_use]
+    pub fn max_age_ns(&self, symbol_hash: u64) -> u64 {
+        self.max_age
+            .get(&symbol_hash)
+            .copied()
+            .unwrap_or(self.config.max_age_ns)
+    }
+
+    /// 参照価格・値洗い価格の更新を記録する。古い時刻の更新は無視する。
+    pub fn on_update(&mut self, symbol_hash: u64, timestamp_ns: u64) {
+        let last = self.last_update.entry(symbol_hash).or_insert(timestamp_ns);
+        *last = (*last).max(timestamp_ns);
+    }
+
+    /// 銘柄の最終更新時刻。
+    #[must_use]
+    pub fn last_update_ns(&self, symbol_hash: u64) -> Option<u64> {
+        self.last_update.get(&symbol_hash).copied()
+    }
+
+    /// `now_ns` 時点の最終更新からの経過時間。更新が無ければ `None`。
+    #[must_use]
+    pub fn age_ns(&self, symbol_hash: u64, now_ns: u64) -> Option<u64> {
+        self.last_update_ns(symbol_hash)
+            .map(|at| now_ns.saturating_sub(at))
+    }
+
+    /// 銘柄の市場データが `now_ns` の時点で古いか（更新が無い場合も古い）。
+    #[must_use]
+    pub fn is_stale(&self, symbol_hash: u64, now_ns: u64) -> bool {
+        self.age_ns(symbol_hash, now_ns)
+            .is_none_or(|age| age > self.max_age_ns(symbol_hash))
+    }
+
+    /// 更新を受けたことがあり、`now_ns` の時点で古くなっている銘柄。
+    #[must_use]
+    pub fn stale_symbols(&self, now_ns: u64) -> Vec<u64> {
+        self.last_update
+            .keys()
+            .copied()
+            .filter(|&s| self.is_stale(s, now_ns))
+            .collect()
+    }
+
+    /// ネット建玉 `net` の銘柄に対する `order` を `now_ns` に通せるか。
+    ///
+    /// # Errors
+    ///
+    /// 市場データが古く、[`StaleDataMode`] で許可されない注文は
+    /// [`RiskReject::StaleMarketData`]。
+    pub fn check(
+        &self,
+        symbol_hash: u64,
+        order: &Order,
+        net: i64,
+        now_ns: u64,
+    ) -> Result<(), RiskReject> {
+        if !self.is_stale(symbol_hash, now_ns) {
+            return Ok(());
+        }
+        if self.config.mode == StaleDataMode::ReduceOnly
+            && ExpiryRestriction::ReduceOnly.permits(order, net)
+        {
+            return Ok(());
+        }
+        Err(RiskReject::StaleMarketData {
+            symbol_hash,
+            age_ns: self.age_ns(symbol_hash, now_ns),
+            max_age_ns: self.max_age_ns(symbol_hash),
+        })
+    }
+
+    /// 鮮度を注文の時刻で確認してから `checker` で判定する。
+    ///
+    /// # Errors
+    ///
+    /// 市場データが古ければ [`RiskReject::StaleMarketData`]、それ以外は
+    /// [`PreTradeChecker::check_order`] の拒否理由。
+    pub fn check_order(
+        &self,
+        checker: &PreTradeChecker,
+        symbol_hash: u64,
+        order: &Order,
+        position: Option<&Position>,
+    ) -> Result<(), RiskReject> {
+        let net = position.map_or(0, |p| p.net_quantity);
+        self.check(symbol_hash, order, net, order.timestamp_ns)?;
+        checker.check_order(order, position)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limit::RiskLimits;
+    use crate::test_utils::OrderBuilder;
+    use alice_ledger::Side;
+
+    fn order(side: Side, quantity: u64, timestamp_ns: u64) -> Order {
+        OrderBuilder::new(side, 100, quantity)
+            .at(timestamp_ns)
+            .build()
+    }
+
+    #[test]
+    fn rejects_stale_and_missing_data() {
+        let mut w = MarketDataWatchdog::new(StalenessConfig {
+            max_age_ns: 100,
+            mode: StaleDataMode::Reject,
+        });
+        let buy = order(Side::Bid, 1, 0);
+        assert_eq!(
+            w.check(7, &buy, 0, 0),
+            Err(RiskReject::StaleMarketData {
+                symbol_hash: 7,
+                age_ns: None,
+                max_age_ns: 100,
+            })
+        );
+        w.on_update(7, 1_000);
+        w.on_update(7, 900);
+        assert_eq!(w.last_update_ns(7), Some(1_000));
+        assert!(w.check(7, &buy, 0, 1_100).is_ok());
+        assert_eq!(
+            w.check(7, &buy, 0, 1_101),
+            Err(RiskReject::StaleMarketData {
+                symbol_hash: 7,
+                age_ns: Some(101),
+                max_age_ns: 100,
+            })
+        );
+        w.set_max_age(7, 1_000);
+        assert!(!w.is_stale(7, 1_101));
+        w.on_update(8, 0);
+        assert_eq!(w.stale_symbols(1_101), [8]);
+    }
+
+    #[test]
+    fn reduce_only_mode_and_checker() {
+        let w = MarketDataWatchdog::new(StalenessConfig {
+            mode: StaleDataMode::ReduceOnly,
+            ..StalenessConfig::default()
+        });
+        // 建玉 5 の買い持ちに対し、売りの減少は通り、買い増し・ドテンは拒否。
+        assert!(w.check(7, &order(Side::Ask, 5, 0), 5, 0).is_ok());
+        assert!(w.check(7, &order(Side::Ask, 6, 0), 5, 0).is_err());
+        assert!(w.check(7, &order(Side::Bid, 1, 0), 5, 0).is_err());
+
+        let checker = PreTradeChecker::new(RiskLimits::default());
+        assert!(matches!(
+            w.check_order(&checker, 7, &order(Side::Bid, 1, 0), None),
+            Err(RiskReject::StaleMarketData { .. })
+        ));
+    }
+}